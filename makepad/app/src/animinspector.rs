@@ -0,0 +1,274 @@
+use makepad_render::*;
+use makepad_widget::*;
+
+// lists everything in cx.playing_anim_areas with the curves of its tracks,
+// and drives the global anim clock: pause, slow motion and scrubbing on a timeline
+#[derive(Clone)]
+pub struct AnimInspector {
+    pub view: ScrollView,
+    pub pause_button: NormalButton,
+    pub speed_buttons: Elements<usize, NormalButton, NormalButton>,
+    pub text: Text,
+    pub timeline: Quad,
+    pub curve: Quad,
+    pub timelines: Vec<AnimInspectorTimeline>,
+}
+
+// where a timeline was drawn and which stretch of anim time it covers
+#[derive(Clone)]
+pub struct AnimInspectorTimeline {
+    pub area: Area,
+    pub start_time: f64,
+    pub duration: f64,
+}
+
+const TIME_SCALES: [f64; 5] = [1.0, 0.5, 0.25, 0.1, 0.02];
+
+impl AnimInspector {
+    pub fn new(cx: &mut Cx) -> Self {
+        Self {
+            view: ScrollView::new(cx),
+            pause_button: NormalButton::new(cx),
+            speed_buttons: Elements::new(NormalButton::new(cx)),
+            text: Text::new(cx),
+            timeline: Quad::new(cx),
+            curve: Quad {
+                z: 1.,
+                ..Quad::new(cx)
+            },
+            timelines: Vec::new(),
+        }
+    }
+    
+    pub fn style(cx: &mut Cx) {
+        live!(cx, r#"
+            self::layout_bg: Layout {
+                padding: all(6.),
+                line_wrap: NewLine,
+            }
+            
+            self::layout_label: Layout {
+                walk: Walk {width: Fix(180.), height: Fix(24.)},
+                align: {fx: 0.0, fy: 0.5},
+                padding: {l: 12., t: 0., r: 4., b: 0.},
+            }
+            
+            self::walk_timeline: Walk {width: Fill, height: Fix(24.), margin: {l: 0., t: 1., r: 6., b: 1.}}
+            
+            self::text_style_label: TextStyle {
+                ..makepad_widget::widgetstyle::text_style_normal
+            }
+            
+            self::color_text: #b;
+            self::color_text_dim: #8;
+            self::color_timeline: #28;
+            self::color_segment: #2f;
+            self::color_key: #6;
+            self::color_curve: #5a8fd0;
+            self::color_playhead: #f0c040;
+        "#)
+    }
+    
+    pub fn handle_anim_inspector(&mut self, cx: &mut Cx, event: &mut Event) {
+        if self.view.handle_scroll_view(cx, event) {
+        }
+        
+        match event {
+            // every anim frame moves the playheads
+            Event::Animate(_) | Event::AnimEnded(_) => {
+                self.view.redraw_view_area(cx);
+            },
+            _ => ()
+        }
+        
+        if let ButtonEvent::Clicked = self.pause_button.handle_normal_button(cx, event) {
+            let paused = cx.is_anim_paused();
+            cx.set_anim_paused(!paused);
+            self.view.redraw_view_area(cx);
+        }
+        
+        for (index, button) in self.speed_buttons.enumerate() {
+            if let ButtonEvent::Clicked = button.handle_normal_button(cx, event) {
+                cx.set_anim_time_scale(TIME_SCALES[*index]);
+                self.view.redraw_view_area(cx);
+            }
+        }
+        
+        let mut scrub = None;
+        for timeline in &self.timelines {
+            match event.hits(cx, timeline.area, HitOpt::default()) {
+                Event::FingerDown(fe) => {
+                    cx.set_down_mouse_cursor(MouseCursor::Arrow);
+                    scrub = Some((timeline.clone(), fe.rel.x / fe.rect.w));
+                },
+                Event::FingerMove(fe) => {
+                    scrub = Some((timeline.clone(), fe.rel.x / fe.rect.w));
+                },
+                _ => ()
+            }
+        }
+        // scrubbing only makes sense with the clock stopped, otherwise it runs off right away
+        if let Some((timeline, pos)) = scrub {
+            if timeline.start_time.is_nan() {
+                return
+            }
+            if !cx.is_anim_paused() {
+                cx.set_anim_paused(true);
+            }
+            let pos = pos.max(0.).min(1.) as f64;
+            cx.scrub_anim_time(timeline.start_time + pos * timeline.duration);
+            self.view.redraw_view_area(cx);
+        }
+    }
+    
+    fn area_name(cx: &Cx, area: Area) -> String {
+        match area {
+            Area::Instance(inst) => {
+                let cxview = &cx.views[inst.view_id];
+                if inst.draw_call_id < cxview.draw_calls.len() {
+                    let shader_id = cxview.draw_calls[inst.draw_call_id].shader_id;
+                    format!("{} #{}", cx.shaders[shader_id].name, inst.instance_offset)
+                }
+                else {
+                    "?".to_string()
+                }
+            },
+            Area::View(view_area) => format!("view {}", view_area.view_id),
+            _ => "?".to_string()
+        }
+    }
+    
+    fn draw_label(&mut self, cx: &mut Cx, label: &str, color: Color) {
+        cx.begin_turtle(live_layout!(cx, self::layout_label), Area::Empty);
+        self.text.color = color;
+        self.text.draw_text(cx, label);
+        cx.end_turtle(Area::Empty);
+    }
+    
+    // the key segments of a track, each one eased from 0 to 1. time runs over the normalized anim
+    fn draw_track_curve(&mut self, cx: &mut Cx, rect: Rect, track: &Track) {
+        let mut keys = track.key_times();
+        if keys.len() == 0 {
+            return
+        }
+        // before the first key it eases in from wherever the value was when the anim cut in
+        if keys[0] > 0. {
+            keys.insert(0, 0.);
+        }
+        let ease = track.ease().clone();
+        // discrete tracks jump at the next key
+        let is_step = match track {Track::Int {..} | Track::Bool {..} => true, _ => false};
+        // back and elastic overshoot, leave some room above and below
+        let map_y = | v: f64 | rect.y + rect.h - 2. - (((v + 0.25) / 1.5) as f32) * (rect.h - 4.);
+        for i in 0..keys.len() {
+            let x0 = rect.x + (keys[i].max(0.).min(1.) as f32) * rect.w;
+            self.curve.color = live_color!(cx, self::color_key);
+            self.curve.draw_quad_abs(cx, Rect {x: x0, y: rect.y, w: 1., h: rect.h});
+            if i + 1 >= keys.len() {
+                break
+            }
+            let x1 = rect.x + (keys[i + 1].max(0.).min(1.) as f32) * rect.w;
+            let steps = ((x1 - x0) / 2.).max(2.) as usize;
+            self.curve.color = live_color!(cx, self::color_curve);
+            for step in 0..=steps {
+                let t = step as f64 / steps as f64;
+                let x = x0 + (x1 - x0) * t as f32;
+                let v = if is_step {if step == steps {1.} else {0.}} else {ease.map(t)};
+                self.curve.draw_quad_abs(cx, Rect {x: x - 1., y: map_y(v) - 1., w: 2., h: 2.});
+            }
+        }
+    }
+    
+    fn draw_timeline(&mut self, cx: &mut Cx, anim_area: &AnimArea, track: Option<&Track>, index: usize) {
+        let rect = cx.walk_turtle(live_walk!(cx, self::walk_timeline));
+        self.timeline.color = if track.is_some() {live_color!(cx, self::color_timeline)} else {live_color!(cx, self::color_segment)};
+        let area: Area = self.timeline.draw_quad_abs(cx, rect).into();
+        
+        // keep a drag going when the timeline gets redrawn under the finger
+        let duration = anim_area.anim.play.duration();
+        let timeline = AnimInspectorTimeline {area: area, start_time: anim_area.start_time, duration: duration};
+        if index < self.timelines.len() {
+            cx.update_area_refs(self.timelines[index].area, area);
+            self.timelines[index] = timeline;
+        }
+        else {
+            self.timelines.push(timeline);
+        }
+        
+        if let Some(track) = track {
+            self.draw_track_curve(cx, rect, track);
+        }
+        
+        if !anim_area.start_time.is_nan() {
+            let local = anim_area.anim.play.compute_time(cx.get_anim_time() - anim_area.start_time);
+            if local.is_finite() {
+                let x = rect.x + (local.max(0.).min(1.) as f32) * rect.w;
+                self.curve.color = live_color!(cx, self::color_playhead);
+                self.curve.draw_quad_abs(cx, Rect {x: x - 1., y: rect.y, w: 2., h: rect.h});
+            }
+        }
+    }
+    
+    fn track_label(cx: &Cx, track: &Track) -> String {
+        let name = cx.live_styles.get_live_id_name(track.live_id());
+        // the last path segment is what tells tracks apart
+        let name = name.rsplit("::").next().unwrap_or("").to_string();
+        match track {
+            Track::Spring {target, ..} => format!("{} spring -> {}", name, target),
+            Track::Int {..} | Track::Bool {..} => format!("{} step", name),
+            _ => format!("{} {:?}", name, track.ease())
+        }
+    }
+    
+    pub fn draw_anim_inspector(&mut self, cx: &mut Cx) {
+        if self.view.begin_view(cx, live_layout!(cx, self::layout_bg)).is_err() {
+            return
+        }
+        self.text.text_style = live_text_style!(cx, self::text_style_label);
+        
+        let paused = cx.is_anim_paused();
+        self.pause_button.draw_normal_button(cx, if paused {"Resume"} else {"Pause"});
+        let time_scale = cx.get_anim_time_scale();
+        for (index, time_scale) in TIME_SCALES.iter().enumerate() {
+            self.speed_buttons.get_draw(cx, index, | _cx, templ | {
+                templ.clone()
+            }).draw_normal_button(cx, &format!("{}x", time_scale));
+        }
+        let anim_time = cx.get_anim_time();
+        let status = if anim_time.is_nan() {
+            "clock not started".to_string()
+        }
+        else {
+            format!("t {:.3}  speed {}x{}", anim_time, time_scale, if paused {"  paused"} else {""})
+        };
+        self.draw_label(cx, &status, live_color!(cx, self::color_text_dim));
+        cx.turtle_new_line();
+        
+        let anim_areas = cx.playing_anim_areas.clone();
+        let mut index = 0;
+        for anim_area in &anim_areas {
+            cx.turtle_new_line();
+            let header = format!("{}  {:?}", Self::area_name(cx, anim_area.area), anim_area.anim.play);
+            self.draw_label(cx, &header, live_color!(cx, self::color_text));
+            // the header row is a timeline too, its just easier to grab
+            self.draw_timeline(cx, anim_area, None, index);
+            index += 1;
+            cx.turtle_new_line();
+            for track in &anim_area.anim.tracks {
+                let label = Self::track_label(cx, track);
+                self.draw_label(cx, &label, live_color!(cx, self::color_text_dim));
+                self.draw_timeline(cx, anim_area, Some(track), index);
+                index += 1;
+                cx.turtle_new_line();
+            }
+            if let Some(next) = &anim_area.next {
+                let label = format!("then {:?}", next.play);
+                self.draw_label(cx, &label, live_color!(cx, self::color_text_dim));
+                cx.turtle_new_line();
+            }
+        }
+        self.timelines.truncate(index);
+        
+        self.view.end_view(cx);
+    }
+}
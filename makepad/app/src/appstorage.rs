@@ -8,6 +8,7 @@ use crate::filetree::*;
 use crate::fileeditor::*;
 use crate::buildmanager::*;
 use std::collections::HashMap;
+use std::path::Path;
 use crate::builder;
 use crate::livemacro::*;
 
@@ -16,6 +17,7 @@ pub struct AppSettings {
     pub build_on_save: bool,
     pub exec_when_done: bool,
     pub live_macros_on_self: bool,
+    pub vim_mode: Option<bool>,
     pub hub_server: HubServerConfig,
     pub builders: HashMap<String, HubBuilderConfig>,
     pub builds: Vec<BuildTarget>,
@@ -28,6 +30,7 @@ impl Default for AppSettings {
         Self {
             exec_when_done: false,
             live_macros_on_self: true,
+            vim_mode: None,
             build_on_save: true,
             hub_server: HubServerConfig::Offline,
             builders: HashMap::new(),
@@ -43,6 +46,7 @@ impl AppSettings {
             exec_when_done: false,
             build_on_save: true,
             live_macros_on_self: true,
+            vim_mode: None,
             hub_server: HubServerConfig::Offline,
             builders: {
                 let mut cfg = HashMap::new();
@@ -52,7 +56,12 @@ impl AppSettings {
                         let mut workspace = HashMap::new();
                         workspace.insert("makepad".to_string(), ".".to_string());
                         workspace
-                    }
+                    },
+                    lsp_servers: Some(vec![LspServerConfig {
+                        command: "rust-analyzer".to_string(),
+                        args: vec![],
+                        extensions: vec![".rs".to_string()]
+                    }])
                 });
                 cfg
             },
@@ -96,6 +105,7 @@ pub struct AppStorage {
     pub file_tree_file_read: FileRead,
     pub app_state_file_read: FileRead,
     pub app_settings_file_read: FileRead,
+    pub keymap_file_read: FileRead,
     pub text_buffer_path_to_id: HashMap<String, AppTextBufferId>,
     pub text_buffer_id_to_path: HashMap<AppTextBufferId, String>,
     pub text_buffers: Vec<AppTextBuffer>,
@@ -137,7 +147,8 @@ impl AppStorage {
             text_buffers: Vec::new(),
             file_tree_file_read: FileRead::default(),
             app_state_file_read: FileRead::default(),
-            app_settings_file_read: FileRead::default()
+            app_settings_file_read: FileRead::default(),
+            keymap_file_read: FileRead::default()
         }
     }
      
@@ -149,6 +160,7 @@ impl AppStorage {
             
             self.app_state_file_read = cx.file_read("makepad_state.ron");
             self.app_settings_file_read = cx.file_read("makepad_settings.ron");
+            self.keymap_file_read = cx.file_read("makepad_keymap.ron");
             
             // lets start the router
             let mut hub_router = HubRouter::start_hub_router(HubLog::None);
@@ -191,6 +203,18 @@ impl AppStorage {
         }
     }
     
+    // problems with the keymap file show up in the log list
+    pub fn load_keymap(&mut self, cx: &mut Cx, build_manager: &mut BuildManager, utf8_data: &str) {
+        if let Err(errors) = cx.load_keymap_ron(utf8_data) {
+            for error in errors {
+                build_manager.add_log_item(cx, HubLogItem::Error(format!("Cannot load makepad_keymap.ron: {}", error)));
+            }
+        }
+        for conflict in cx.keymap_conflicts() {
+            build_manager.add_log_item(cx, HubLogItem::Warning(format!("Keymap conflict: {}", conflict)));
+        }
+    }
+    
     pub fn save_settings(&mut self, cx: &mut Cx) {
         let utf8_data = self.settings.serialize_ron();
         let path = "makepad_settings.ron";
@@ -250,8 +274,8 @@ impl AppStorage {
     
     pub fn text_buffer_from_path(&mut self, cx: &mut Cx, path: &str) -> &mut AppTextBuffer {
         
-        // if online, fallback to readfile
-        if !cx.platform_type.is_desktop() || path.find('/').is_none() {
+        // if online, fallback to readfile. absolute paths are files dropped in from outside the workspace
+        if !cx.platform_type.is_desktop() || path.find('/').is_none() || Path::new(path).is_absolute() {
             if let Some(tb_id) = self.text_buffer_path_to_id.get(path) {
                 &mut self.text_buffers[tb_id.0 as usize]
             }
@@ -321,9 +345,9 @@ impl AppStorage {
         }
     }
     
-    pub fn text_buffer_file_write(&mut self, cx: &mut Cx, path: &str) {
+    pub fn text_buffer_file_write(&mut self, cx: &mut Cx, build_manager: &mut BuildManager, path: &str) {
         if cx.platform_type.is_desktop() {
-            if path.find('/').is_some() {
+            if path.find('/').is_some() && !Path::new(path).is_absolute() {
                 if let Some(tb_id) = self.text_buffer_path_to_id.get(path) {
                     let atb = &self.text_buffers[tb_id.0 as usize];
                     let hub_ui = self.hub_ui.as_mut().unwrap();
@@ -359,7 +383,7 @@ impl AppStorage {
                     }
                 }
             }
-            else { // its not a workspace, its a system (settings) file
+            else { // its not a workspace, its a system (settings) file or one dropped in from outside
                 if let Some(tb_id) = self.text_buffer_path_to_id.get(path) {
                     let atb = &self.text_buffers[tb_id.0 as usize];
                     let utf8_data = atb.text_buffer.get_as_string();
@@ -368,6 +392,9 @@ impl AppStorage {
                     if path == "makepad_settings.ron" {
                         self.load_settings(cx, &utf8_data);
                     };
+                    if path == "makepad_keymap.ron" {
+                        self.load_keymap(cx, build_manager, &utf8_data);
+                    };
                 }
             }
         }
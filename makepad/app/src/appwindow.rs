@@ -2,18 +2,23 @@
 use makepad_render::*;
 use makepad_widget::*;
 use makepad_microserde::*;
+use makepad_hub::*;
 use std::collections::HashMap;
 
 use crate::appstorage::*;
+use crate::makepadapp::*;
 use crate::fileeditor::*;
 use crate::filetree::*;
 use crate::filepanel::*;
 use crate::loglist::*;
 use crate::itemdisplay::*;
 use crate::keyboard::*;
+use crate::animinspector::*;
 use crate::buildmanager::*;
+use crate::lspmanager::*;
 use crate::homepage::*;
 use crate::searchresults::*;
+use crate::commandpalette::*;
 use crate::rusteditor::*;
 use crate::jseditor::*;
 use crate::soleditor::*;
@@ -26,10 +31,11 @@ pub enum Panel {
     SearchResults,
     ItemDisplay,
     Keyboard,
+    AnimInspector,
     ShaderView,
     FileTree,
     FileEditorTarget,
-    FileEditor {path: String, scroll_pos: Vec2, editor_id: u64}
+    FileEditor {path: String, scroll_pos: Vec2, editor_id: u64, folded_rows: Option<Vec<usize>>}
 }
 
 #[derive(Clone)]
@@ -40,7 +46,9 @@ pub struct AppWindow {
     pub item_display: ItemDisplay,
     pub log_list: LogList,
     pub search_results: SearchResults,
+    pub command_palette: CommandPalette,
     pub keyboard: Keyboard,
+    pub anim_inspector: AnimInspector,
     pub file_editors: FileEditors,
     pub xr_control: XRControl,
     pub dock: Dock<Panel>,
@@ -76,16 +84,18 @@ impl AppWindow {
             },
             home_page: HomePage::new(cx),
             keyboard: Keyboard::new(cx),
+            anim_inspector: AnimInspector::new(cx),
             item_display: ItemDisplay::new(cx),
             log_list: LogList::new(cx),
             search_results: SearchResults::new(cx),
+            command_palette: CommandPalette::new(cx),
             file_panel: FilePanel::new(cx),
             xr_control: XRControl::new(cx),
             dock: Dock ::new(cx),
         }
     }
 
-    pub fn handle_app_window(&mut self, cx: &mut Cx, event: &mut Event, window_index: usize, state: &mut AppState, storage: &mut AppStorage, build_manager: &mut BuildManager) {
+    pub fn handle_app_window(&mut self, cx: &mut Cx, event: &mut Event, window_index: usize, state: &mut AppState, storage: &mut AppStorage, build_manager: &mut BuildManager, lsp_manager: &mut LspManager) {
         match self.desktop_window.handle_desktop_window(cx, event) {
             DesktopWindowEvent::EventForOtherWindow => {
                 return
@@ -114,35 +124,32 @@ impl AppWindow {
                     match event{
                         Event::FingerHover(fe)=>{
                             let digit = fe.digit;
-                            self.handle_app_window(cx, event, window_index, state, storage, build_manager);
+                            self.handle_app_window(cx, event, window_index, state, storage, build_manager, lsp_manager);
                             cx.fingers[digit]._over_last = cx.fingers[digit].over_last;
                         },
                         _=>{
-                            self.handle_app_window(cx, event, window_index, state, storage, build_manager);
+                            self.handle_app_window(cx, event, window_index, state, storage, build_manager, lsp_manager);
                         }
                     }
                 }
             },
-            Event::KeyDown(ke) => match ke.key_code {
-                KeyCode::Backtick => if ke.modifiers.logo || ke.modifiers.control {
-                    if build_manager.active_builds.len() == 0 {
-                        build_manager.restart_build(cx, storage);
+            Event::Command(command) => if *command == MakepadApp::command_start_program() {
+                if build_manager.active_builds.len() == 0 {
+                    build_manager.restart_build(cx, storage);
+                }
+                let mut clear = true;
+                for ab in &build_manager.active_builds {
+                    if !ab.build_uid.is_none() {
+                        clear = false;
                     }
-                    let mut clear = true;
-                    for ab in &build_manager.active_builds {
-                        if !ab.build_uid.is_none() {
-                            clear = false;
-                        }
-                    }
-                    if clear {
-                        build_manager.tail_log_items = true;
-                        build_manager.log_items.truncate(0);
-                    }
-                    build_manager.artifact_run(storage);
-                    self.show_log_tab(cx, window_index, state);
                 }
-                _ => ()
-            }
+                if clear {
+                    build_manager.tail_log_items = true;
+                    build_manager.log_items.truncate(0);
+                }
+                build_manager.artifact_run(storage);
+                self.show_log_tab(cx, window_index, state);
+            },
             _ => ()
         }
 
@@ -150,14 +157,26 @@ impl AppWindow {
             self.show_search_tab(cx, window_index, state);
         }
 
-        let dock_items = &mut state.windows[window_index].dock_items;
-        let mut dock_walker = self.dock.walker(dock_items);
         let mut file_tree_event = FileTreeEvent::None;
         //let mut text_editor_event = TextEditorEvent::None;
         let mut set_last_cursor = None;
+
+        match self.command_palette.handle_command_palette(cx, event) {
+            CommandPaletteEvent::OpenFile {path, cursor} => {
+                file_tree_event = FileTreeEvent::SelectFile {path: path};
+                set_last_cursor = cursor;
+            },
+            _ => ()
+        }
+
+        let dock_items = &mut state.windows[window_index].dock_items;
+        let mut dock_walker = self.dock.walker(dock_items);
         let mut do_search = None;
         let mut show_item_display_tab = false;
         let mut do_display_rust_file = None;
+        let mut do_lsp_request = None;
+        let mut do_complete = None;
+        let mut do_open_files = None;
 
         while let Some(item) = dock_walker.walk_handle_dock(cx, event) {
             match item {
@@ -204,13 +223,16 @@ impl AppWindow {
                 Panel::Keyboard => {
                     self.keyboard.handle_keyboard(cx, event, storage);
                 }
+                Panel::AnimInspector => {
+                    self.anim_inspector.handle_anim_inspector(cx, event);
+                }
                 Panel::FileEditorTarget => {
                     self.home_page.handle_home_page(cx, event);
                 }
                 Panel::FileTree => {
                     file_tree_event = self.file_panel.handle_file_panel(cx, event);
                 }
-                Panel::FileEditor {path, scroll_pos, editor_id} => {
+                Panel::FileEditor {path, scroll_pos, editor_id, folded_rows} => {
                     if let Some(file_editor) = &mut self.file_editors.editors.get_mut(editor_id) {
 
                         let atb = storage.text_buffer_from_path(cx, path);
@@ -228,15 +250,33 @@ impl AppWindow {
                             TextEditorEvent::Change => {
                                 do_search = Some((None, AppTextBufferId(0), false, false));
                             }
-                            TextEditorEvent::LagChange => {
-                                storage.text_buffer_file_write(cx, path);
+                            TextEditorEvent::LagChange | TextEditorEvent::Save => {
+                                storage.text_buffer_file_write(cx, build_manager, path);
+                                lsp_manager.document_sync(cx, storage, path, true);
                                 if storage.settings.build_on_save {
                                     build_manager.restart_build(cx, storage);
                                 }
                             },
+                            TextEditorEvent::Hover => {
+                                do_lsp_request = Some((path.clone(), LspRequest::Hover, file_editor.get_last_cursor_text_pos(&atb.text_buffer)));
+                            },
+                            TextEditorEvent::Complete => {
+                                do_complete = Some((*editor_id, file_editor.get_completion_filter()));
+                                do_lsp_request = Some((path.clone(), LspRequest::Completion, file_editor.get_last_cursor_text_pos(&atb.text_buffer)));
+                            },
+                            TextEditorEvent::GotoDefinition => {
+                                do_lsp_request = Some((path.clone(), LspRequest::Definition, file_editor.get_last_cursor_text_pos(&atb.text_buffer)));
+                            },
+                            TextEditorEvent::OpenFiles(paths) => {
+                                do_open_files = Some(paths);
+                            },
                             _ => ()
                         }
                         *scroll_pos = file_editor.get_scroll_pos(cx);
+                        let rows = file_editor.get_folded_rows();
+                        if rows != folded_rows.as_ref().map_or(&[][..], | v | &v[..]) {
+                            *folded_rows = if rows.len() > 0 {Some(rows.to_vec())} else {None};
+                        }
                     }
                 }
             }
@@ -262,10 +302,31 @@ impl AppWindow {
             }
         }
 
+        if let Some((editor_id, filter)) = do_complete {
+            let items = build_manager.search_index.complete(&filter, storage);
+            if let Some(file_editor) = self.file_editors.editors.get_mut(&editor_id) {
+                file_editor.add_completions(cx, items);
+            }
+        }
+
+        if let Some((path, request, pos)) = do_lsp_request {
+            lsp_manager.request(cx, storage, window_index, &path, request, pos);
+        }
+
         if show_item_display_tab {
             self.show_item_display_tab(cx, window_index, state);
         }
 
+        // files dropped on an editor from outside
+        if let Some(paths) = do_open_files {
+            for path in paths {
+                if self.focus_or_new_editor(cx, window_index, state, &path, None) {
+                    storage.save_state(cx, state);
+                    self.ensure_unique_tab_title_for_file_editors(cx, window_index, state);
+                }
+            }
+        }
+
         if let Some(tbid) = do_display_rust_file {
             let path = storage.text_buffer_id_to_path.get(&tbid).unwrap();
             if self.open_preview_editor_tab(cx, window_index, state, &path, set_last_cursor) {
@@ -400,17 +461,25 @@ impl AppWindow {
                 Panel::Keyboard => {
                     self.keyboard.draw_keyboard(cx);
                 }
+                Panel::AnimInspector => {
+                    self.anim_inspector.draw_anim_inspector(cx);
+                }
                 Panel::FileEditorTarget => {
                     self.home_page.draw_home_page(cx);
                 }
                 Panel::FileTree => {
                     file_panel.draw_file_panel(cx);
                 }
-                Panel::FileEditor {path, scroll_pos, editor_id} => {
+                Panel::FileEditor {path, scroll_pos, editor_id, folded_rows} => {
+                    let vim_mode = storage.settings.vim_mode.unwrap_or(false);
                     let text_buffer = storage.text_buffer_from_path(cx, path);
                     let (file_editor, is_new) = self.file_editors.get_file_editor_for_path(path, *editor_id);
                     if is_new {
                         file_editor.set_scroll_pos_on_load(*scroll_pos);
+                        if let Some(folded_rows) = folded_rows {
+                            file_editor.set_folded_rows(folded_rows.clone());
+                        }
+                        file_editor.set_vim_mode(vim_mode);
                     }
                     file_editor.draw_file_editor(cx, text_buffer, &mut build_manager.search_index);
                 }
@@ -419,9 +488,18 @@ impl AppWindow {
         if self.desktop_window.window.xr_is_presenting(cx){
             self.xr_control.draw_xr_control(cx);
         }
+        let window_size = self.desktop_window.window.get_inner_size(cx);
+        self.command_palette.draw_command_palette(cx, window_size);
         self.desktop_window.end_desktop_window(cx);
     }
 
+    pub fn open_command_palette(&mut self, cx: &mut Cx, prefix: &str, menu: &Menu, storage: &AppStorage, build_manager: &BuildManager) {
+        let commands = CommandPalette::command_items(cx, menu);
+        let files = CommandPalette::file_items(self.file_panel.file_tree.get_all_file_paths());
+        let symbols = CommandPalette::symbol_items(&build_manager.search_index, storage);
+        self.command_palette.open(cx, prefix, commands, files, symbols);
+    }
+
     pub fn ensure_unique_tab_title_for_file_editors(&mut self, cx: &mut Cx, window_index: usize, state: &mut AppState) {
         // we walk through the dock collecting tab titles, if we run into a collision
         // we need to find the shortest uniqueness
@@ -514,7 +592,8 @@ impl AppWindow {
             item: Panel::FileEditor {
                 path: path.to_string(),
                 scroll_pos: Vec2::default(),
-                editor_id: editor_id
+                editor_id: editor_id,
+                folded_rows: None
             }
         }
     }
@@ -594,7 +673,7 @@ impl AppWindow {
                         Panel::ItemDisplay => { // found the editor target
                             item_ctrl_id = Some((ctrl_id, id));
                         },
-                        Panel::FileEditor {path, scroll_pos: _, editor_id} => {
+                        Panel::FileEditor {path, editor_id, ..} => {
                             if *path == file_path {
                                 // check if we aren't the preview..
                                 if let Some((item_ctrl_id, tab_id)) = item_ctrl_id {
@@ -666,7 +745,7 @@ impl AppWindow {
                         // check what tab is right next to ItemDisplay
                         if target_tab_after + 1 < tabs.len() {
                             match &mut tabs[target_tab_after + 1].item {
-                                Panel::FileEditor {path, scroll_pos: _, editor_id} => {
+                                Panel::FileEditor {path, editor_id, ..} => {
                                     if self.file_editors.does_path_match_editor_type(file_path, *editor_id) {
                                         *path = file_path.to_string();
                                         let (file_editor, _is_new) = self.file_editors.get_file_editor_for_path(path, *editor_id);
@@ -125,7 +125,8 @@ impl BuildManager {
             
             let msg = TextBufferMessage {
                 body: loc_message.body.clone(),
-                level: level
+                level: level,
+                from_lsp: false
             };
             if let Some(pos) = inserted {
                 atb.text_buffer.markers.message_bodies.insert(pos, msg);
@@ -218,8 +219,12 @@ impl BuildManager {
     }
     
     pub fn add_log_message(&mut self, cx:&mut Cx, msg:String){
+        self.add_log_item(cx, HubLogItem::Message(msg));
+    }
+    
+    pub fn add_log_item(&mut self, cx:&mut Cx, item:HubLogItem){
         self.handle_log_item_limit(cx);
-        self.log_items.push(HubLogItem::Message(msg));
+        self.log_items.push(item);
         cx.send_signal(self.signal, BuildManager::status_new_log_item());
     }
 
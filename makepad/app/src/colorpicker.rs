@@ -26,6 +26,9 @@ pub enum ColorPickerDragMode {
 }
 impl ColorPicker {
     
+    pub fn command_copy_rgb() -> CommandId {uid!()}
+    pub fn command_copy_vec4() -> CommandId {uid!()}
+    
     pub fn new(cx: &mut Cx) -> Self {
         Self {
             hue: 0.0,
@@ -78,8 +81,36 @@ impl ColorPicker {
         }
     }
     
+    // copy puts the color on the clipboard as hex text with an html swatch next to it
+    fn copy_color(&self, cx: &mut Cx, command: CommandId) {
+        let color = Color::from_hsva(Vec4 {x: self.hue, y: self.sat, z: self.val, w: 1.0});
+        let hex = format!("#{}", color.to_hex());
+        let text = if command == Self::command_copy_rgb() {
+            format!("rgb({}, {}, {})", (color.r * 255.0) as u8, (color.g * 255.0) as u8, (color.b * 255.0) as u8)
+        }
+        else if command == Self::command_copy_vec4() {
+            format!("vec4({:.3}, {:.3}, {:.3}, 1.0)", color.r, color.g, color.b)
+        }
+        else {
+            hex.clone()
+        };
+        cx.clipboard_write(ClipboardSelection::Clipboard, ClipboardContent {
+            html: Some(format!("<span style=\"background-color:{}\">&nbsp;&nbsp;&nbsp;</span> {}", hex, text)),
+            text: Some(text),
+            png: None
+        });
+    }
+    
     pub fn handle_color_picker(&mut self, cx: &mut Cx, event: &mut Event) -> ColorPickerEvent {
         match event.hits(cx, self.animator.area, HitOpt::default()) {
+            Event::KeyFocus(_) => {
+                cx.set_key_focus_context("color_picker");
+            },
+            Event::Command(command) => {
+                if command == Cx::command_copy() || command == Self::command_copy_rgb() || command == Self::command_copy_vec4() {
+                    self.copy_color(cx, command);
+                }
+            },
             Event::Animate(ae) => {
                 self.animator.calc_area(cx, self.animator.area, ae.time);
             },
@@ -98,6 +129,7 @@ impl ColorPicker {
                 }
             },
             Event::FingerDown(fe) => {
+                cx.set_key_focus(self.animator.area);
                 self.animator.play_anim(cx, live_anim!(cx, self::anim_down));
                 cx.set_down_mouse_cursor(MouseCursor::Arrow);
                 let rsize = (self.size * 0.28) / 2.0f32.sqrt();
@@ -152,7 +184,8 @@ impl ColorPicker {
         let k = self.wheel.draw_quad(cx, Walk {
             margin: Margin::bottom(10.),
             width: Width::Fill,
-            height: Height::Fix(self.size * 1.0)
+            height: Height::Fix(self.size * 1.0),
+            ..Walk::default()
         });
         // lets put a hsv int here
         k.push_float(cx, self.hue);
@@ -166,6 +199,9 @@ impl ColorPicker {
     }
 
     pub fn style(cx: &mut Cx) {
+        Self::command_copy_rgb().set_name(cx, "color_picker.copy_rgb").bind_in(cx, "color_picker", "Cmd+Shift+C");
+        Self::command_copy_vec4().set_name(cx, "color_picker.copy_vec4").bind_in(cx, "color_picker", "Cmd+Alt+C");
+        
         live!(cx, r#"
             self::anim_default: Anim {
                 play: Cut {duration: 0.2},
@@ -0,0 +1,511 @@
+use makepad_render::*;
+use makepad_widget::*;
+use crate::appstorage::*;
+use crate::searchindex::*;
+
+// the fuzzy searchable popup for commands (>), symbols (@) and files (no prefix)
+#[derive(Clone)]
+pub struct CommandPalette {
+    pub overlay: View,
+    pub view: ScrollView,
+    pub list: ListLogic,
+    pub search_input: TextInput,
+    pub input_bg: Quad,
+    pub item_bg: Quad,
+    pub label_text: Text,
+    pub detail_text: Text,
+    pub shadow: ScrollShadow,
+    pub is_open: bool,
+    pub commands: Vec<PaletteItem>,
+    pub files: Vec<PaletteItem>,
+    pub symbols: Vec<PaletteItem>,
+    pub filtered: Vec<usize>,
+}
+
+#[derive(Clone)]
+pub struct PaletteItem {
+    pub label: String,
+    pub detail: String,
+    pub match_text: String,
+    pub action: PaletteAction
+}
+
+#[derive(Clone)]
+pub enum PaletteAction {
+    Command(CommandId),
+    OpenFile {path: String, cursor: Option<(usize, usize)>}
+}
+
+#[derive(Clone)]
+pub enum CommandPaletteEvent {
+    None,
+    OpenFile {path: String, cursor: Option<(usize, usize)>}
+}
+
+impl CommandPalette {
+    pub fn command_show_commands() -> CommandId {uid!()}
+    pub fn command_go_to_file() -> CommandId {uid!()}
+    pub fn command_go_to_symbol() -> CommandId {uid!()}
+    
+    pub fn new(cx: &mut Cx) -> Self {
+        let mut search_input = TextInput::new(cx, TextInputOptions {multiline: false, read_only: false, empty_message: "go to file, > for commands, @ for symbols".to_string()});
+        // the editors live at z 0..3, the palette has to stay in front of them
+        let te = &mut search_input.text_editor;
+        for quad in vec![
+            &mut te.bg,
+            &mut te.gutter_bg,
+            &mut te.cursor,
+            &mut te.selection,
+            &mut te.token_highlight,
+            &mut te.cursor_row,
+            &mut te.paren_pair,
+            &mut te.indent_lines,
+            &mut te.message_marker,
+            &mut te.search_marker
+        ] {
+            quad.z += 20.5;
+        }
+        te.text.z += 20.5;
+        te.line_number_text.z += 20.5;
+        te.shadow.z += 20.5;
+        
+        Self {
+            overlay: View::proto_overlay(cx),
+            view: ScrollView {
+                scroll_h: None,
+                ..ScrollView::new(cx)
+            },
+            list: ListLogic {
+                multi_select: false,
+                ..ListLogic::default()
+            },
+            search_input: search_input,
+            input_bg: Quad {z: 20., ..Quad::new(cx)},
+            item_bg: Quad {z: 20., ..Quad::new(cx)},
+            label_text: Text {z: 20.1, ..Text::new(cx)},
+            detail_text: Text {z: 20.1, ..Text::new(cx)},
+            shadow: ScrollShadow {z: 20.2, ..ScrollShadow::new(cx)},
+            is_open: false,
+            commands: Vec::new(),
+            files: Vec::new(),
+            symbols: Vec::new(),
+            filtered: Vec::new(),
+        }
+    }
+    
+    pub fn style(cx: &mut Cx) {
+        live!(cx, r#"
+            self::layout_input: Layout {
+                walk: Walk {width: Fill, height: Fix(36.)},
+                padding: all(4.),
+            }
+            
+            self::style_text_input: Style {
+                makepad_widget::texteditor::layout_bg: Layout {
+                    walk: Walk {
+                        width: Fill,
+                        height: Compute,
+                        margin: all(0.)
+                    },
+                    padding: all(7.),
+                }
+                makepad_widget::texteditor::color_bg: #34;
+            }
+            
+            self::layout_item: Layout {
+                walk: Walk {width: Fill, height: Fix(22.)},
+                align: {fx: 0.0, fy: 0.5},
+                padding: {l: 8., t: 0., r: 8., b: 0.},
+                line_wrap: None,
+            }
+            
+            self::text_style_item: TextStyle {
+                ..makepad_widget::widgetstyle::text_style_normal
+            }
+            
+            self::palette_width: 560.;
+            self::palette_top: 40.;
+            self::max_rows: 14.;
+            
+            self::color_input_bg: #25;
+            self::color_label: #d;
+            self::color_detail: #8;
+            self::color_bg: #25;
+            self::color_bg_over: #38;
+            self::color_bg_selected: #11466e;
+        "#)
+    }
+    
+    pub fn default_keymap(cx: &mut Cx) {
+        Self::command_show_commands().set_name(cx, "app.command_palette").bind(cx, "Cmd+Shift+P");
+        Self::command_go_to_file().set_name(cx, "app.go_to_file").bind(cx, "Cmd+P");
+        Self::command_go_to_symbol().set_name(cx, "app.go_to_symbol").bind(cx, "Cmd+Shift+O");
+    }
+    
+    // the prefix each opener starts with
+    pub fn prefix_for_command(command: CommandId) -> Option<&'static str> {
+        if command == Self::command_show_commands() {
+            Some(">")
+        }
+        else if command == Self::command_go_to_file() {
+            Some("")
+        }
+        else if command == Self::command_go_to_symbol() {
+            Some("@")
+        }
+        else {
+            None
+        }
+    }
+    
+    // every named command, labeled like the menu has it where it is in there
+    pub fn command_items(cx: &Cx, menu: &Menu) -> Vec<PaletteItem> {
+        fn recur_walk(menu: &Menu, base: &str, out: &mut Vec<(CommandId, String)>) {
+            match menu {
+                Menu::Main {items} => for item in items {
+                    recur_walk(item, base, out);
+                },
+                Menu::Sub {name, items} => for item in items {
+                    recur_walk(item, name, out);
+                },
+                Menu::Item {name, command} => if out.iter().find( | (c, _) | c == command).is_none() {
+                    out.push((*command, format!("{}: {}", base, name)));
+                },
+                Menu::Line => ()
+            }
+        }
+        let mut menu_labels = Vec::new();
+        recur_walk(menu, "", &mut menu_labels);
+        
+        let mut items: Vec<PaletteItem> = cx.keymap.commands.iter().map( | (name, command) | {
+            let label = if let Some((_, label)) = menu_labels.iter().find( | (c, _) | c == command) {
+                label.clone()
+            }
+            else {
+                name.clone()
+            };
+            PaletteItem {
+                match_text: format!("{} {}", label, name),
+                detail: cx.keymap.bindings_for(*command).iter().map( | b | b.keys_to_string()).collect::<Vec<String >> ().join(", "),
+                label: label,
+                action: PaletteAction::Command(*command)
+            }
+        }).collect();
+        items.sort_by( | a, b | a.label.cmp(&b.label));
+        items
+    }
+    
+    pub fn file_items(paths: Vec<String>) -> Vec<PaletteItem> {
+        paths.into_iter().map( | path | {
+            let split = path.split('/').collect::<Vec<&str >> ();
+            PaletteItem {
+                label: split.last().unwrap().to_string(),
+                detail: split[0..split.len() - 1].join("/"),
+                match_text: path.clone(),
+                action: PaletteAction::OpenFile {path: path.clone(), cursor: None}
+            }
+        }).collect()
+    }
+    
+    pub fn symbol_items(search_index: &SearchIndex, storage: &AppStorage) -> Vec<PaletteItem> {
+        search_index.declarations(storage).into_iter().filter_map( | (name, result) | {
+            let path = storage.text_buffer_id_to_path.get(&result.text_buffer_id) ?;
+            let text_buffer = &storage.text_buffers[result.text_buffer_id.as_index()].text_buffer;
+            let tok = &text_buffer.token_chunks[result.token as usize];
+            let pos = text_buffer.offset_to_text_pos(tok.offset);
+            Some(PaletteItem {
+                detail: format!("{}:{}", path.split('/').last().unwrap(), pos.row),
+                match_text: name.clone(),
+                label: name,
+                action: PaletteAction::OpenFile {path: path.clone(), cursor: Some((tok.offset + tok.len, tok.offset))}
+            })
+        }).collect()
+    }
+    
+    pub fn open(&mut self, cx: &mut Cx, prefix: &str, commands: Vec<PaletteItem>, files: Vec<PaletteItem>, symbols: Vec<PaletteItem>) {
+        self.commands = commands;
+        self.files = files;
+        self.symbols = symbols;
+        self.is_open = true;
+        self.search_input.set_value(cx, prefix);
+        let len = self.search_input.text_buffer.calc_char_count();
+        self.search_input.text_editor.cursors.clear_and_set_last_cursor_head(len, &self.search_input.text_buffer);
+        self.search_input.text_editor.set_key_focus(cx);
+        self.refilter(cx);
+        self.overlay.redraw_view_area(cx);
+    }
+    
+    pub fn close(&mut self, cx: &mut Cx) {
+        if self.is_open {
+            self.is_open = false;
+            self.commands.truncate(0);
+            self.files.truncate(0);
+            self.symbols.truncate(0);
+            self.filtered.truncate(0);
+            self.overlay.redraw_view_area(cx);
+        }
+    }
+    
+    // the mode is picked by the first character of the input
+    fn pick_items<'a>(value: &str, commands: &'a [PaletteItem], symbols: &'a [PaletteItem], files: &'a [PaletteItem]) -> (&'a [PaletteItem], String) {
+        if value.starts_with('>') {
+            (commands, value[1..].trim().to_string())
+        }
+        else if value.starts_with('@') {
+            (symbols, value[1..].trim().to_string())
+        }
+        else {
+            (files, value.trim().to_string())
+        }
+    }
+    
+    fn current_items(&self) -> (&[PaletteItem], String) {
+        Self::pick_items(&self.search_input.get_value(), &self.commands, &self.symbols, &self.files)
+    }
+    
+    fn refilter(&mut self, cx: &mut Cx) {
+        let (items, filter) = self.current_items();
+        let filter: Vec<char> = filter.chars().flat_map( | v | v.to_lowercase()).collect();
+        let mut scored: Vec<(isize, usize)> = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            if let Some(score) = CompletionList::fuzzy_score(&filter, &item.match_text) {
+                scored.push((score, index));
+            }
+        }
+        // an empty filter keeps the order we were given
+        if filter.len() > 0 {
+            scored.sort_by( | a, b | {
+                b.0.cmp(&a.0)
+                    .then(items[a.1].match_text.len().cmp(&items[b.1].match_text.len()))
+            });
+        }
+        self.filtered = scored.into_iter().map( | (_, index) | index).collect();
+        self.list.set_list_len(0);
+        self.list.set_list_len(self.filtered.len());
+        if self.filtered.len() > 0 {
+            self.select(cx, ListSelect::Single(0));
+        }
+        self.overlay.redraw_view_area(cx);
+    }
+    
+    fn handle_list_item_anim(cx: &mut Cx, item_event: ListLogicEvent, item: &mut ListItem) {
+        match item_event {
+            ListLogicEvent::Animate(ae) => {
+                item.animator.calc_area(cx, item.animator.area, ae.time);
+            },
+            ListLogicEvent::AnimEnded => {
+                item.animator.end();
+            },
+            ListLogicEvent::Select | ListLogicEvent::Over => {
+                item.animator.play_anim(cx, Self::get_over_anim(cx, item.is_selected));
+            },
+            ListLogicEvent::Deselect | ListLogicEvent::Out => {
+                item.animator.play_anim(cx, Self::get_default_anim(cx, item.is_selected));
+            },
+            ListLogicEvent::Cleanup => {
+                item.animator.play_anim(cx, Anim {
+                    play: Play::Cut {duration: 0.01},
+                    ..Self::get_default_anim(cx, item.is_selected)
+                });
+            }
+        }
+    }
+    
+    fn select(&mut self, cx: &mut Cx, select: ListSelect) {
+        self.list.scroll_item_in_view = select.item_index();
+        self.list.handle_list_logic(cx, &mut Event::None, select, false, | cx, item_event, item, _ | {
+            Self::handle_list_item_anim(cx, item_event, item)
+        });
+        self.view.redraw_view_area(cx);
+    }
+    
+    fn choose_selected(&mut self, cx: &mut Cx) -> CommandPaletteEvent {
+        let action = {
+            let (items, _) = self.current_items();
+            match self.list.selection.first().and_then( | index | self.filtered.get(*index)) {
+                Some(index) => items[*index].action.clone(),
+                None => return CommandPaletteEvent::None
+            }
+        };
+        cx.revert_key_focus();
+        self.close(cx);
+        match action {
+            PaletteAction::Command(command) => {
+                // sent after this event so it lands on whatever had focus before the palette
+                cx.send_command(command);
+                CommandPaletteEvent::None
+            },
+            PaletteAction::OpenFile {path, cursor} => CommandPaletteEvent::OpenFile {path, cursor}
+        }
+    }
+    
+    pub fn handle_command_palette(&mut self, cx: &mut Cx, event: &mut Event) -> CommandPaletteEvent {
+        if !self.is_open {
+            return CommandPaletteEvent::None
+        }
+        
+        // navigation keys are eaten here, the input only gets to see typing
+        if let Event::KeyDown(ke) = event {
+            if self.search_input.text_editor.has_key_focus(cx) {
+                let rows = live_float!(cx, self::max_rows) as usize;
+                let last = self.list.selection.first().cloned().unwrap_or(0);
+                match ke.key_code {
+                    KeyCode::ArrowDown => {
+                        let select = self.list.get_next_single_selection();
+                        self.select(cx, select);
+                        return CommandPaletteEvent::None
+                    },
+                    KeyCode::ArrowUp => {
+                        let select = self.list.get_prev_single_selection();
+                        self.select(cx, select);
+                        return CommandPaletteEvent::None
+                    },
+                    KeyCode::PageDown => {
+                        if self.filtered.len() > 0 {
+                            self.select(cx, ListSelect::Single((last + rows).min(self.filtered.len() - 1)));
+                        }
+                        return CommandPaletteEvent::None
+                    },
+                    KeyCode::PageUp => {
+                        if self.filtered.len() > 0 {
+                            self.select(cx, ListSelect::Single(last.max(rows) - rows));
+                        }
+                        return CommandPaletteEvent::None
+                    },
+                    KeyCode::Return => {
+                        return self.choose_selected(cx)
+                    },
+                    _ => ()
+                }
+            }
+        }
+        
+        match self.search_input.handle_text_input(cx, event) {
+            TextEditorEvent::Change => {
+                self.refilter(cx);
+            },
+            TextEditorEvent::Escape => {
+                cx.revert_key_focus();
+                self.close(cx);
+                return CommandPaletteEvent::None
+            },
+            TextEditorEvent::KeyFocusLost => {
+                self.close(cx);
+                return CommandPaletteEvent::None
+            },
+            _ => ()
+        }
+        
+        self.list.set_list_len(self.filtered.len());
+        self.list.handle_list_scroll_bars(cx, event, &mut self.view);
+        
+        let le = self.list.handle_list_logic(cx, event, ListSelect::None, false, | cx, item_event, item, _ | {
+            Self::handle_list_item_anim(cx, item_event, item)
+        });
+        match le {
+            ListEvent::SelectSingle(_) | ListEvent::SelectDouble(_) => {
+                return self.choose_selected(cx)
+            },
+            _ => ()
+        }
+        CommandPaletteEvent::None
+    }
+    
+    pub fn get_default_anim(cx: &Cx, selected: bool) -> Anim {
+        Anim {
+            play: Play::Chain {duration: 0.01},
+            tracks: vec![
+                Track::Color {
+                    live_id: live_id!(makepad_render::quad::shader::color),
+                    ease: Ease::Lin,
+                    keys: vec![(1.0, if selected {
+                        live_color!(cx, self::color_bg_selected)
+                    } else {
+                        live_color!(cx, self::color_bg)
+                    })],
+                    cut_init: None
+                }
+            ]
+        }
+    }
+    
+    pub fn get_over_anim(cx: &Cx, selected: bool) -> Anim {
+        Anim {
+            play: Play::Chain {duration: 0.02},
+            tracks: vec![
+                Track::Color {
+                    live_id: live_id!(makepad_render::quad::shader::color),
+                    ease: Ease::Lin,
+                    keys: vec![(0.0, if selected {
+                        live_color!(cx, self::color_bg_selected)
+                    } else {
+                        live_color!(cx, self::color_bg_over)
+                    })],
+                    cut_init: None
+                }
+            ]
+        }
+    }
+    
+    pub fn draw_command_palette(&mut self, cx: &mut Cx, window_size: Vec2) {
+        let row_height = live_layout!(cx, self::layout_item).walk.height.fixed();
+        let input_height = live_layout!(cx, self::layout_input).walk.height.fixed();
+        let width = live_float!(cx, self::palette_width).min(window_size.x - 20.).max(0.);
+        let rows = self.filtered.len().min(live_float!(cx, self::max_rows) as usize);
+        let height = if self.is_open {input_height + rows as f32 * row_height} else {0.};
+        
+        // a closed palette still draws its empty overlay, thats what clears it off the screen
+        if self.overlay.begin_view(cx, Layout {
+            abs_origin: Some(Vec2 {x: ((window_size.x - width) * 0.5).max(0.), y: live_float!(cx, self::palette_top)}),
+            walk: Walk::wh(Width::Fix(width), Height::Fix(height)),
+            ..Layout::default()
+        }).is_err() {
+            return
+        }
+        if !self.is_open {
+            self.overlay.end_view(cx);
+            return
+        }
+        // tab stays in the palette while it is open
+        cx.begin_focus_scope(FocusScope::Trap);
+        
+        self.input_bg.color = live_color!(cx, self::color_input_bg);
+        let bg_inst = self.input_bg.begin_quad(cx, live_layout!(cx, self::layout_input));
+        live_style_begin!(cx, self::style_text_input);
+        self.search_input.draw_text_input(cx);
+        live_style_end!(cx, self::style_text_input);
+        self.input_bg.end_quad(cx, bg_inst);
+        cx.turtle_new_line();
+        
+        self.list.set_list_len(self.filtered.len());
+        if rows > 0 && self.list.begin_list(cx, &mut self.view, false, row_height).is_ok() {
+            self.label_text.text_style = live_text_style!(cx, self::text_style_item);
+            self.detail_text.text_style = live_text_style!(cx, self::text_style_item);
+            self.label_text.color = live_color!(cx, self::color_label);
+            self.detail_text.color = live_color!(cx, self::color_detail);
+            
+            let (items, _) = Self::pick_items(&self.search_input.get_value(), &self.commands, &self.symbols, &self.files);
+            for i in self.list.start_item..self.list.end_item {
+                let item = &items[self.filtered[i]];
+                let list_item = &mut self.list.list_items[i];
+                let selected = list_item.is_selected;
+                list_item.animator.init(cx, | cx | Self::get_default_anim(cx, selected));
+                self.item_bg.color = list_item.animator.last_color(cx, live_id!(makepad_render::quad::shader::color));
+                
+                let bg_inst = self.item_bg.begin_quad(cx, live_layout!(cx, self::layout_item));
+                self.label_text.draw_text(cx, &item.label);
+                if item.detail.len() > 0 {
+                    self.detail_text.draw_text(cx, "  ");
+                    self.detail_text.draw_text(cx, &item.detail);
+                }
+                let bg_area = self.item_bg.end_quad(cx, bg_inst);
+                list_item.animator.set_area(cx, bg_area);
+            }
+            self.list.walk_turtle_to_end(cx, row_height);
+            self.shadow.draw_shadow_top(cx);
+            self.list.end_list(cx, &mut self.view);
+        }
+        cx.end_focus_scope();
+        self.overlay.end_view(cx);
+    }
+}
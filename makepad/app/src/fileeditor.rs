@@ -67,6 +67,33 @@ impl FileEditor {
         }
     }
 
+    pub fn get_last_cursor_text_pos(&self, text_buffer: &TextBuffer) -> TextPos {
+        match self {
+            FileEditor::Rust(re) => re.text_editor.cursors.get_last_cursor_text_pos(text_buffer),
+            FileEditor::JS(re) => re.text_editor.cursors.get_last_cursor_text_pos(text_buffer),
+            FileEditor::SOL(re) => re.text_editor.cursors.get_last_cursor_text_pos(text_buffer),
+            FileEditor::Plain(re) => re.text_editor.cursors.get_last_cursor_text_pos(text_buffer),
+        }
+    }
+
+    pub fn get_completion_filter(&self) -> String {
+        match self {
+            FileEditor::Rust(re) => re.text_editor.completion.filter.clone(),
+            FileEditor::JS(re) => re.text_editor.completion.filter.clone(),
+            FileEditor::SOL(re) => re.text_editor.completion.filter.clone(),
+            FileEditor::Plain(re) => re.text_editor.completion.filter.clone(),
+        }
+    }
+
+    pub fn add_completions(&mut self, cx: &mut Cx, items: Vec<CompletionItem>) {
+        match self {
+            FileEditor::Rust(re) => re.text_editor.completion.add_items(cx, items),
+            FileEditor::JS(re) => re.text_editor.completion.add_items(cx, items),
+            FileEditor::SOL(re) => re.text_editor.completion.add_items(cx, items),
+            FileEditor::Plain(re) => re.text_editor.completion.add_items(cx, items),
+        }
+    }
+
     pub fn get_scroll_pos(&mut self, cx: &mut Cx) -> Vec2 {
         match self {
             FileEditor::Rust(re) => re.text_editor.view.get_scroll_pos(cx),
@@ -85,6 +112,33 @@ impl FileEditor {
         }
     }
 
+    pub fn get_folded_rows(&self) -> &[usize] {
+        match self {
+            FileEditor::Rust(re) => &re.text_editor.folded_rows,
+            FileEditor::JS(re) => &re.text_editor.folded_rows,
+            FileEditor::SOL(re) => &re.text_editor.folded_rows,
+            FileEditor::Plain(re) => &re.text_editor.folded_rows,
+        }
+    }
+
+    pub fn set_folded_rows(&mut self, rows: Vec<usize>) {
+        match self {
+            FileEditor::Rust(re) => re.text_editor.folded_rows = rows,
+            FileEditor::JS(re) => re.text_editor.folded_rows = rows,
+            FileEditor::SOL(re) => re.text_editor.folded_rows = rows,
+            FileEditor::Plain(re) => re.text_editor.folded_rows = rows,
+        }
+    }
+
+    pub fn set_vim_mode(&mut self, enabled: bool) {
+        match self {
+            FileEditor::Rust(re) => re.text_editor.vim.enabled = enabled,
+            FileEditor::JS(re) => re.text_editor.vim.enabled = enabled,
+            FileEditor::SOL(re) => re.text_editor.vim.enabled = enabled,
+            FileEditor::Plain(re) => re.text_editor.vim.enabled = enabled,
+        }
+    }
+
     pub fn draw_file_editor(&mut self, cx: &mut Cx, atb: &mut AppTextBuffer, search_index: &mut SearchIndex) {
         match self {
             FileEditor::Rust(re) => re.draw_rust_editor(cx, atb, Some(search_index)),
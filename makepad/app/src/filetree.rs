@@ -280,6 +280,28 @@ impl FileTree {
     }
     
     
+    // all files, also the ones in closed folders
+    pub fn get_all_file_paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        fn recur_walk(node: &FileNode, base: &str, paths: &mut Vec<String>) {
+            match node {
+                FileNode::File {name, ..} => {
+                    paths.push(if base.len()>0 {format!("{}/{}", base, name)}else {name.to_string()});
+                },
+                FileNode::Folder {name, folder, ..} => {
+                    let new_base = if name.len()>0 {
+                        if base.len()>0 {format!("{}/{}", base, name)}else {name.to_string()}
+                    }else {base.to_string()};
+                    for node in folder {
+                        recur_walk(node, &new_base, paths);
+                    }
+                }
+            }
+        }
+        recur_walk(&self.root_node, "", &mut paths);
+        paths
+    }
+    
     pub fn get_marked_paths(root: &mut FileNode) -> Vec<String> {
         let mut paths = Vec::new();
         let mut file_walker = FileWalker::new(root);
@@ -303,6 +325,7 @@ impl FileTree {
         // but filtered out somewhat based on a bounding rect
         let mut unmark_nodes = false;
         let mut drag_nodes = false;
+        let mut drag_start = false;
         let mut drag_end: Option<FingerUpEvent> = None;
         let mut select_node = 0;
         while let Some((_depth, _index, _len, node)) = file_walker.walk() {
@@ -318,7 +341,27 @@ impl FileTree {
                 Event::AnimEnded(_) => {
                     node_draw.animator.end();
                 },
-                Event::FingerDown(_fe) => {
+                Event::KeyFocus(_) => {
+                    // the arrow keys walk the nodes, keep the one with focus on screen
+                    cx.set_key_focus_context("file_tree");
+                    let rect = node_draw.animator.area.get_rect_unscrolled(cx);
+                    self.view.scroll_into_view_abs(cx, rect);
+                },
+                Event::AccessAction(ae) if ae.action == AccessAction::Expand || ae.action == AccessAction::Collapse => {
+                    if node.set_open(ae.action == AccessAction::Expand) {
+                        self.view.redraw_view_area(cx);
+                    }
+                },
+                Event::KeyDown(ke) if ke.key_code == KeyCode::ArrowRight || ke.key_code == KeyCode::ArrowLeft => {
+                    if node.set_open(ke.key_code == KeyCode::ArrowRight) {
+                        self.view.redraw_view_area(cx);
+                    }
+                },
+                Event::FingerDown(_) |
+                Event::AccessAction(AccessActionEvent {action: AccessAction::Click, ..}) |
+                Event::KeyDown(KeyEvent {key_code: KeyCode::Return, ..}) |
+                Event::KeyDown(KeyEvent {key_code: KeyCode::Space, ..}) => {
+                    cx.set_key_focus(node_draw.animator.area);
                     // mark ourselves, unmark others
                     if is_filenode {
                         select_node = 1;
@@ -362,6 +405,7 @@ impl FileTree {
                     cx.set_down_mouse_cursor(MouseCursor::Hand);
                     if self._drag_move.is_none() {
                         if fe.move_distance() > 10. {
+                            drag_start = true;
                             self._drag_move = Some(fe);
                             self.view.redraw_view_area(cx);
                             self.drag_view.redraw_view_area(cx);
@@ -407,8 +451,16 @@ impl FileTree {
                 }
             }
         }
+        if drag_start {
+            // also offer the paths to drop targets, a text editor inserts them
+            cx.start_dragging(DragPayload::Files(Self::get_marked_paths(&mut self.root_node)));
+        }
         if let Some(fe) = drag_end {
             self._drag_move = None;
+            // someone else took the drop
+            if cx.drag.was_dropped {
+                return FileTreeEvent::DragCancel;
+            }
             let paths = Self::get_marked_paths(&mut self.root_node);
             if !self.view.get_view_area(cx).get_rect(cx).contains(fe.abs.x, fe.abs.y) {
                 return FileTreeEvent::DragEnd {
@@ -422,7 +474,7 @@ impl FileTree {
                 // lets check if we are over our own filetree
                 // ifso, we need to support moving files with directories
                 let paths = Self::get_marked_paths(&mut self.root_node);
-                if !self.view.get_view_area(cx).get_rect(cx).contains(fe.abs.x, fe.abs.y) {
+                if cx.drag.action == DragAction::None && !self.view.get_view_area(cx).get_rect(cx).contains(fe.abs.x, fe.abs.y) {
                     return FileTreeEvent::DragMove {
                         fe: fe.clone(),
                         paths: paths
@@ -456,6 +508,10 @@ impl FileTree {
     
     pub fn draw_file_tree(&mut self, cx: &mut Cx) {
         if self.view.begin_view(cx, Layout::default()).is_err() {return}
+        cx.begin_access_node(AccessNode::new(AccessRole::Tree, "Files"));
+        cx.begin_focus_scope(FocusScope::Column);
+        // folders stay open as access nodes until the walk comes back up to their depth
+        let mut access_depth = 0;
         
         let mut file_walker = FileWalker::new(&mut self.root_node);
         
@@ -498,6 +554,26 @@ impl FileTree {
             node_draw.animator.set_area(cx, inst.clone().into());
             let is_marked = node_draw.marked != 0;
             
+            while access_depth >= depth {
+                cx.end_access_node(Area::Empty);
+                access_depth -= 1;
+            }
+            let is_folder = if let FileNode::Folder {..} = node {true} else {false};
+            cx.begin_access_node(AccessNode {
+                selected: is_marked,
+                expanded: if is_folder {Some(node.is_open())} else {None},
+                actions: if is_folder {
+                    vec![AccessAction::Click, AccessAction::Expand, AccessAction::Collapse]
+                }
+                else {
+                    vec![AccessAction::Click]
+                },
+                area: inst.clone().into(),
+                ..AccessNode::new(AccessRole::TreeItem, &node.name())
+            });
+            access_depth = depth;
+            cx.add_focusable(inst.clone().into(), is_marked);
+            
             for i in 0..(depth - 1) {
                 if i == depth - 2 { // our own thread.
                     let area = self.item_draw.filler.draw_quad(cx, self.item_draw.filler_walk);
@@ -609,6 +685,10 @@ impl FileTree {
                 counter += 1;
             }
         }
+        while access_depth > 0 {
+            cx.end_access_node(Area::Empty);
+            access_depth -= 1;
+        }
         
         // draw filler nodes
         if self.item_draw.row_height > 0. {
@@ -659,7 +739,9 @@ impl FileTree {
         
         self.item_draw.shadow.draw_shadow_top(cx);
         
-        self.view.end_view(cx);
+        cx.end_focus_scope();
+        let view_area = self.view.end_view(cx);
+        cx.end_access_node(view_area);
     }
     
 }
@@ -725,6 +807,21 @@ impl FileNode {
             FileNode::Folder {name, ..} => name.clone()
         }
     }
+    
+    // unlike a click this only ever goes one way, returns if it was a folder
+    fn set_open(&mut self, open: bool) -> bool {
+        if let FileNode::Folder {state, ..} = self {
+            *state = match state {
+                NodeState::Opening(fac) if !open => NodeState::Closing(1.0 - *fac),
+                NodeState::Closing(fac) if open => NodeState::Opening(1.0 - *fac),
+                NodeState::Open if !open => NodeState::Closing(1.0),
+                NodeState::Closed if open => NodeState::Opening(1.0),
+                _ => state.clone()
+            };
+            return true
+        }
+        false
+    }
 }
 
 struct StackEntry<'a> {
@@ -180,7 +180,8 @@ impl FloatSlider {
         let k = self.slider.draw_quad(cx, Walk {
             margin: Margin::left(pad),
             width: Width::FillPad(pad),
-            height: Height::Fix(20.0)
+            height: Height::Fix(20.0),
+            ..Walk::default()
         });
         // lets put a hsv int here
         k.push_float(cx, self.norm_value);
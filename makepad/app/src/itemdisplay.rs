@@ -23,7 +23,9 @@ pub struct ItemDisplay {
     pub prev_button: NormalButton,
     pub next_button: NormalButton,
     pub open_button: NormalButton,
-    pub item_title: Text
+    pub item_title: Text,
+    pub splitter: Splitter,
+    pub explanation: MarkdownView,
 }
 
 impl ItemDisplay {
@@ -48,6 +50,16 @@ impl ItemDisplay {
             next_button: NormalButton::new(cx),
             open_button: NormalButton::new(cx),
             item_title: Text::new(cx),
+            splitter: Splitter {
+                axis: Axis::Horizontal,
+                align: SplitterAlign::Weighted,
+                pos: 0.4,
+                ..Splitter::new(cx)
+            },
+            explanation: MarkdownView {
+                code_tokenizer: Some(Self::tokenize_explanation_code),
+                ..MarkdownView::new(cx)
+            },
             display: ItemDisplayType::Empty,
         };
         editor
@@ -71,6 +83,9 @@ impl ItemDisplay {
     }
     
     pub fn display_message(&mut self, cx: &mut Cx, loc_message: &LocMessage) {
+        if let Some(explanation) = &loc_message.explanation {
+            self.explanation.set_markdown(cx, explanation);
+        }
         self.display = ItemDisplayType::Message {message: loc_message.clone()};
         self.update_display = true;
         self.view.redraw_view_parent_area(cx);
@@ -87,14 +102,32 @@ impl ItemDisplay {
         PlainTokenizer::update_token_chunks(text_buffer, None);
     }
     
+    // fenced code in rustc explanations is rust unless the fence says otherwise
+    pub fn tokenize_explanation_code(lang: &str, text_buffer: &mut TextBuffer) {
+        match lang {
+            "text" | "console" | "sh" | "shell" | "toml" | "json" => {
+                PlainTokenizer::update_token_chunks(text_buffer, None);
+            },
+            _ => if text_buffer.needs_token_chunks() && text_buffer.lines.len() >0 {
+                let mut state = TokenizerState::new(&text_buffer.lines);
+                let mut tokenizer = MprsTokenizer::new();
+                let mut pair_stack = Vec::new();
+                loop {
+                    let offset = text_buffer.flat_text.len();
+                    let token_type = tokenizer.next_token(&mut state, &mut text_buffer.flat_text, &text_buffer.token_chunks);
+                    TokenChunk::push_with_pairing(&mut text_buffer.token_chunks, &mut pair_stack, state.next, offset, text_buffer.flat_text.len(), token_type);
+                    if token_type == TokenType::Eof {
+                        break
+                    }
+                }
+            }
+        }
+    }
+    
+    // the explanation is markdown, it goes to its own view
     pub fn update_message_text_buffer(text_buffer: &mut TextBuffer, loc_message: &LocMessage) {
         let text = if let Some(rendered) = &loc_message.rendered {
-            if let Some(explanation) = &loc_message.explanation {
-                format!("{}{}{}", loc_message.body, rendered, explanation)
-            }
-            else {
-                format!("{}{}", loc_message.body, rendered)
-            }
+            format!("{}{}", loc_message.body, rendered)
         }
         else {
             loc_message.body.clone()
@@ -183,7 +216,15 @@ impl ItemDisplay {
             ItemDisplayType::PlainText {..} => {
                 self.text_disp.handle_text_editor(cx, event, &mut self.text_buffer)
             },
-            ItemDisplayType::Message {..} => {
+            ItemDisplayType::Message {message} => {
+                if message.explanation.is_some() {
+                    if let SplitterEvent::Moving {..} = self.splitter.handle_splitter(cx, event) {
+                        self.view.redraw_view_parent_area(cx);
+                    }
+                    if let MarkdownEvent::LinkClicked {url} = self.explanation.handle_markdown_view(cx, event) {
+                        cx.open_url(&url);
+                    }
+                }
                 self.text_disp.handle_text_editor(cx, event, &mut self.text_buffer)
             },
         }
@@ -207,16 +248,30 @@ impl ItemDisplay {
             ItemDisplayType::Empty=>{
             }
             ItemDisplayType::PlainText {..} | ItemDisplayType::Message {..} => {
+                let has_explanation = match &self.display {
+                    ItemDisplayType::Message {message} => message.explanation.is_some(),
+                    _ => false
+                };
+                if has_explanation {
+                    self.splitter.begin_splitter(cx);
+                }
+                
                 let text_buffer = &mut self.text_buffer;
                 live_style_begin!(cx, self::style_text_editor);
 
-                if self.text_disp.begin_text_editor(cx, text_buffer).is_err() {return live_style_end!(cx, self::style_text_editor);}
-                
-                for (index, token_chunk) in text_buffer.token_chunks.iter_mut().enumerate() {
-                    self.text_disp.draw_chunk(cx, index, &text_buffer.flat_text, token_chunk, &text_buffer.markers);
+                if self.text_disp.begin_text_editor(cx, text_buffer).is_ok() {
+                    for (index, token_chunk) in text_buffer.token_chunks.iter_mut().enumerate() {
+                        self.text_disp.draw_chunk(cx, index, &text_buffer.flat_text, token_chunk, &text_buffer.markers);
+                    }
+                    self.text_disp.end_text_editor(cx, text_buffer);
                 }
-                self.text_disp.end_text_editor(cx, text_buffer);
                 live_style_end!(cx, self::style_text_editor);
+                
+                if has_explanation {
+                    self.splitter.mid_splitter(cx);
+                    self.explanation.draw_markdown_view(cx);
+                    self.splitter.end_splitter(cx);
+                }
             },
         }
     }
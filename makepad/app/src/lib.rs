@@ -1,4 +1,5 @@
 pub mod keyboard;
+pub mod animinspector;
 pub mod fileeditor;
 pub mod filepanel;
 pub mod homepage;
@@ -9,9 +10,11 @@ pub mod appwindow;
 pub mod appstorage;
 pub mod filetree;
 pub mod buildmanager;
+pub mod lspmanager;
 pub mod makepadstyle;
 pub mod searchindex;
 pub mod searchresults;
+pub mod commandpalette;
 
 pub mod codeicon;
 pub mod rusteditor;
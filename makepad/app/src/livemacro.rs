@@ -1,6 +1,5 @@
 use makepad_render::*;
 use makepad_widget::*;
-use crate::mprstokenizer::*;
 use crate::appstorage::*;
 use crate::colorpicker::*;
 use crate::floatslider::*;
@@ -25,6 +25,26 @@ pub enum LogListEvent {
 }
 
 impl LogList {
+    pub fn command_next_item() -> CommandId {uid!()}
+    pub fn command_next_item_end() -> CommandId {uid!()}
+    pub fn command_prev_item() -> CommandId {uid!()}
+    pub fn command_prev_item_end() -> CommandId {uid!()}
+    pub fn command_select_all() -> CommandId {uid!()}
+    pub fn command_tail() -> CommandId {uid!()}
+    pub fn command_clear() -> CommandId {uid!()}
+    
+    // these work regardless of focus, so they are global
+    pub fn default_keymap(cx: &mut Cx) {
+        Self::command_next_item().set_name(cx, "log.next_item").bind(cx, "Cmd+.").bind(cx, "F8");
+        Self::command_next_item_end().set_name(cx, "log.next_item_end").bind(cx, "Cmd+Shift+.");
+        // Cmd+, is the preferences
+        Self::command_prev_item().set_name(cx, "log.prev_item").bind(cx, "Shift+F8");
+        Self::command_prev_item_end().set_name(cx, "log.prev_item_end").bind(cx, "Cmd+Shift+,");
+        Self::command_select_all().set_name(cx, "log.select_all").bind(cx, "Cmd+M");
+        Self::command_tail().set_name(cx, "log.tail").bind(cx, "Cmd+T");
+        Self::command_clear().set_name(cx, "log.clear").bind(cx, "Cmd+K");
+    }
+    
     pub fn new(cx: &mut Cx) -> Self {
         Self {
             item_draw: LogItemDraw::new(cx),
@@ -135,35 +155,34 @@ impl LogList {
         let mut select_at_end = false;
         // global key handle
         match event {
-            Event::KeyDown(ke) => match ke.key_code {
-                KeyCode::Period => if ke.modifiers.logo || ke.modifiers.control {
+            Event::Command(command) => {
+                let command = *command;
+                if command == Self::command_next_item() || command == Self::command_next_item_end() {
                     select = self.list.get_next_single_selection();
                     self.list.scroll_item_in_view = select.item_index();
                     bm.tail_log_items = false;
-                    select_at_end = ke.modifiers.shift;
-                },
-                KeyCode::Comma => if ke.modifiers.logo || ke.modifiers.control {
-                    // lets find the
+                    select_at_end = command == Self::command_next_item_end();
+                }
+                else if command == Self::command_prev_item() || command == Self::command_prev_item_end() {
                     select = self.list.get_prev_single_selection();
                     bm.tail_log_items = false;
                     self.list.scroll_item_in_view = select.item_index();
-                    select_at_end = ke.modifiers.shift;
-                },
-                KeyCode::KeyM => if ke.modifiers.logo || ke.modifiers.control {
+                    select_at_end = command == Self::command_prev_item_end();
+                }
+                else if command == Self::command_select_all() {
                     select = ListSelect::All;
-                },
-                KeyCode::KeyT => if ke.modifiers.logo || ke.modifiers.control {
+                }
+                else if command == Self::command_tail() {
                     // lock scroll
                     bm.tail_log_items = true;
                     self.view.redraw_view_area(cx);
-                },
-                KeyCode::KeyK => if ke.modifiers.logo || ke.modifiers.control {
+                }
+                else if command == Self::command_clear() {
                     // clear and tail log
                     bm.tail_log_items = true;
                     bm.log_items.truncate(0);
                     self.view.redraw_view_area(cx);
-                },
-                _ => ()
+                }
             },
             Event::Signal(se) => if let Some(_) = se.signals.get(&bm.signal) {
                 // we have new things
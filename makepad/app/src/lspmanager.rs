@@ -0,0 +1,209 @@
+use makepad_render::*;
+use makepad_widget::*;
+use makepad_hub::*;
+use crate::appstorage::*;
+use crate::appwindow::*;
+
+// talks to the language servers the builders run next to their workspaces
+pub struct LspManager {
+    pub hover_request: Option<(HubUid, usize)>,
+    pub completion_request: Option<(HubUid, usize)>,
+    pub definition_request: Option<(HubUid, usize)>,
+    // a definition in a file that was still loading, jumped to once it is
+    pub definition_jump: Option<(usize, String, TextPos)>,
+}
+
+impl LspManager {
+    pub fn new() -> LspManager {
+        LspManager {
+            hover_request: None,
+            completion_request: None,
+            definition_request: None,
+            definition_jump: None,
+        }
+    }
+
+    fn split_builder(path: &str) -> Option<(&str, &str)> {
+        let builder_pos = path.find('/') ?;
+        let (builder, rest) = path.split_at(builder_pos);
+        Some((builder, &rest[1..]))
+    }
+
+    pub fn document_sync(&mut self, cx: &mut Cx, storage: &mut AppStorage, path: &str, saved: bool) {
+        if !cx.platform_type.is_desktop() {
+            return
+        }
+        if let Some((builder, rest)) = Self::split_builder(path) {
+            let text = if let Some(tb_id) = storage.text_buffer_path_to_id.get(path) {
+                storage.text_buffers[tb_id.as_index()].text_buffer.get_as_string()
+            }
+            else {
+                return
+            };
+            let hub_ui = storage.hub_ui.as_mut().unwrap();
+            let uid = hub_ui.route_send.alloc_uid();
+            hub_ui.route_send.send(ToHubMsg {
+                to: HubMsgTo::Builder(builder.to_string()),
+                msg: HubMsg::LspDocumentSync {
+                    uid: uid,
+                    path: rest.to_string(),
+                    text: text,
+                    saved: saved
+                }
+            });
+        }
+    }
+
+    pub fn request(&mut self, cx: &mut Cx, storage: &mut AppStorage, window_index: usize, path: &str, request: LspRequest, pos: TextPos) {
+        if !cx.platform_type.is_desktop() {
+            return
+        }
+        // the server needs to see what we see, so sync before asking
+        self.document_sync(cx, storage, path, false);
+        if let Some((builder, rest)) = Self::split_builder(path) {
+            let hub_ui = storage.hub_ui.as_mut().unwrap();
+            let uid = hub_ui.route_send.alloc_uid();
+            let pos = LspPos {line: pos.row, character: pos.col};
+            let msg = match request {
+                LspRequest::Hover => {
+                    self.hover_request = Some((uid, window_index));
+                    HubMsg::LspHoverRequest {uid: uid, path: rest.to_string(), pos: pos}
+                },
+                LspRequest::Completion => {
+                    self.completion_request = Some((uid, window_index));
+                    HubMsg::LspCompletionRequest {uid: uid, path: rest.to_string(), pos: pos}
+                },
+                LspRequest::Definition => {
+                    self.definition_request = Some((uid, window_index));
+                    HubMsg::LspDefinitionRequest {uid: uid, path: rest.to_string(), pos: pos}
+                }
+            };
+            hub_ui.route_send.send(ToHubMsg {
+                to: HubMsgTo::Builder(builder.to_string()),
+                msg: msg
+            });
+        }
+    }
+
+    fn process_diagnostics(&mut self, cx: &mut Cx, storage: &mut AppStorage, path: &str, diagnostics: &Vec<LspDiagnostic>) {
+        // we only mark up files we have loaded, the rest comes along when they are opened
+        let path = storage.remap_sync_path(path);
+        let atb = if let Some(tb_id) = storage.text_buffer_path_to_id.get(&path) {
+            &mut storage.text_buffers[tb_id.as_index()]
+        }
+        else {
+            return
+        };
+        let text_buffer = &mut atb.text_buffer;
+        if !text_buffer.is_loaded {
+            return
+        }
+        let mut sorted: Vec<(usize, usize, TextBufferMessage)> = diagnostics.iter().map( | diag | {
+            // same head/tail convention as the build messages, head at the start
+            let head = text_buffer.text_pos_to_offset(TextPos {row: diag.start.line, col: diag.start.character});
+            let tail = text_buffer.text_pos_to_offset(TextPos {row: diag.end.line, col: diag.end.character});
+            (head, tail.max(head + 1), TextBufferMessage {
+                body: diag.message.clone(),
+                level: match diag.severity {
+                    LspSeverity::Error => TextBufferMessageLevel::Error,
+                    LspSeverity::Warning => TextBufferMessageLevel::Warning,
+                    _ => TextBufferMessageLevel::Log
+                },
+                from_lsp: true
+            })
+        }).collect();
+
+        // the build messages stay, only the previous diagnostics get replaced
+        let markers = &mut text_buffer.markers;
+        let cursors = std::mem::replace(&mut markers.message_cursors, Vec::new());
+        let bodies = std::mem::replace(&mut markers.message_bodies, Vec::new());
+        for (cursor, body) in cursors.into_iter().zip(bodies.into_iter()) {
+            if !body.from_lsp {
+                sorted.push((cursor.head, cursor.tail, body));
+            }
+        }
+        sorted.sort_by_key( | (head, _, _) | *head);
+
+        markers.mutation_id = text_buffer.mutation_id.max(1);
+        for (head, tail, body) in sorted {
+            markers.message_cursors.push(TextCursor {head: head, tail: tail, max: 0});
+            markers.message_bodies.push(body);
+        }
+        cx.send_signal(text_buffer.signal, TextBuffer::status_message_update());
+    }
+
+    fn jump_to_definition(&mut self, cx: &mut Cx, storage: &mut AppStorage, windows: &mut Vec<AppWindow>, state: &mut AppState) {
+        let (window_index, path, pos) = if let Some(jump) = &self.definition_jump {jump.clone()} else {return};
+        let atb = storage.text_buffer_from_path(cx, &path);
+        let cursor = if atb.text_buffer.is_loaded {
+            self.definition_jump = None;
+            let offset = atb.text_buffer.text_pos_to_offset(pos);
+            Some((offset, offset))
+        }
+        else {
+            None
+        };
+        if let Some(window) = windows.get_mut(window_index) {
+            if window.focus_or_new_editor(cx, window_index, state, &path, cursor) {
+                storage.save_state(cx, state);
+                window.ensure_unique_tab_title_for_file_editors(cx, window_index, state);
+            }
+        }
+    }
+
+    pub fn handle_hub_msg(&mut self, cx: &mut Cx, storage: &mut AppStorage, windows: &mut Vec<AppWindow>, state: &mut AppState, htc: &FromHubMsg) {
+        // the storage handled this message first, so a file we were waiting for could be loaded now
+        if let Some((_, path, _)) = &self.definition_jump {
+            if storage.text_buffer_path_to_id.get(path).map( | tb_id | storage.text_buffers[tb_id.as_index()].text_buffer.is_loaded) == Some(true) {
+                self.jump_to_definition(cx, storage, windows, state);
+            }
+        }
+        match &htc.msg {
+            HubMsg::LspDiagnostics {path, diagnostics, ..} => {
+                self.process_diagnostics(cx, storage, path, diagnostics);
+            },
+            HubMsg::LspHoverResponse {uid, text, ..} => if let Some((hover_uid, window_index)) = self.hover_request {
+                if hover_uid == *uid {
+                    self.hover_request = None;
+                    if let Some(text) = text {
+                        if let Some(window) = windows.get_mut(window_index) {
+                            window.item_display.display_plain_text(cx, text);
+                            window.show_item_display_tab(cx, window_index, state);
+                        }
+                    }
+                }
+            },
+            HubMsg::LspCompletionResponse {uid, items, ..} => if let Some((completion_uid, window_index)) = self.completion_request {
+                if completion_uid == *uid {
+                    self.completion_request = None;
+                    if let Some(window) = windows.get_mut(window_index) {
+                        // only the editor that asked has its list open, the others ignore it
+                        for file_editor in window.file_editors.editors.values_mut() {
+                            file_editor.add_completions(cx, items.iter().map( | item | CompletionItem {
+                                label: item.label.clone(),
+                                detail: item.detail.clone(),
+                                insert_text: item.insert_text.clone()
+                            }).collect());
+                        }
+                    }
+                }
+            },
+            HubMsg::LspDefinitionResponse {uid, locations, ..} => if let Some((definition_uid, window_index)) = self.definition_request {
+                if definition_uid == *uid {
+                    self.definition_request = None;
+                    if let Some(location) = locations.first() {
+                        let path = storage.remap_sync_path(&location.path);
+                        // locations outside of a workspace come back as absolute paths we cant open
+                        if path.starts_with("/") {
+                            return
+                        }
+                        // files that aren't loaded yet open now and get the cursor when they arrive
+                        self.definition_jump = Some((window_index, path, TextPos {row: location.start.line, col: location.start.character}));
+                        self.jump_to_definition(cx, storage, windows, state);
+                    }
+                }
+            },
+            _ => ()
+        }
+    }
+}
@@ -6,6 +6,9 @@ use crate::appwindow::*;
 use crate::appstorage::*;
 use crate::filetree::*;
 use crate::buildmanager::*;
+use crate::loglist::*;
+use crate::commandpalette::*;
+use crate::lspmanager::*;
 use crate::makepadstyle::*;
 
 pub struct MakepadApp {
@@ -16,7 +19,9 @@ pub struct MakepadApp {
     pub state: AppState,
     pub storage: AppStorage,
     pub build_manager: BuildManager,
+    pub lsp_manager: LspManager,
     pub windows: Vec<AppWindow>,
+    pub active_window: usize,
 }
 
 impl MakepadApp {
@@ -40,21 +45,43 @@ impl MakepadApp {
     pub fn command_start_program() -> CommandId {uid!()}
     pub fn command_stop_program() -> CommandId {uid!()}
     pub fn command_bring_all_to_front() -> CommandId {uid!()}
+    pub fn command_reload_builders() -> CommandId {uid!()}
+    pub fn command_reset_zoom() -> CommandId {uid!()}
     
     pub fn new(cx: &mut Cx) -> Self {
         
         set_widget_style(cx);
         set_makepad_style(cx);
         let ms = cx.new_signal();
-        // set up the keyboard map
-        Self::command_preferences().set_key(cx, KeyCode::Comma);
-        Self::command_new_file().set_key(cx, KeyCode::KeyN);
-        Self::command_new_window().set_key_shift(cx, KeyCode::KeyN);
-        Self::command_save_as().set_key_shift(cx, KeyCode::KeyS);
-        Self::command_close_editor().set_key(cx, KeyCode::KeyW);
-        Self::command_close_window().set_key_shift(cx, KeyCode::KeyW);
+        // set up the keyboard map, makepad_keymap.ron can override all of these by name
+        Self::command_about_makepad().set_name(cx, "app.about_makepad");
+        Self::command_preferences().set_name(cx, "app.preferences").set_key(cx, KeyCode::Comma);
+        Self::command_new_file().set_name(cx, "app.new_file").set_key(cx, KeyCode::KeyN);
+        Self::command_new_window().set_name(cx, "app.new_window").set_key_shift(cx, KeyCode::KeyN);
+        Self::command_add_folder_to_builder().set_name(cx, "app.add_folder_to_builder");
+        Self::command_save_as().set_name(cx, "app.save_as").set_key_shift(cx, KeyCode::KeyS);
+        Self::command_rename().set_name(cx, "app.rename");
+        Self::command_close_editor().set_name(cx, "app.close_editor").set_key(cx, KeyCode::KeyW);
+        Self::command_remove_folder_from_builder().set_name(cx, "app.remove_folder_from_builder");
+        Self::command_close_window().set_name(cx, "app.close_window").set_key_shift(cx, KeyCode::KeyW);
+        Self::command_find().set_name(cx, "app.find");
+        Self::command_replace().set_name(cx, "app.replace");
+        Self::command_find_in_files().set_name(cx, "app.find_in_files");
+        Self::command_replace_in_files().set_name(cx, "app.replace_in_files");
+        Self::command_toggle_line_comment().set_name(cx, "app.toggle_line_comment");
+        Self::command_toggle_block_comment().set_name(cx, "app.toggle_block_comment");
+        Self::command_start_program().set_name(cx, "app.start_program").set_key(cx, KeyCode::Backtick);
+        Self::command_stop_program().set_name(cx, "app.stop_program");
+        Self::command_bring_all_to_front().set_name(cx, "app.bring_all_to_front");
+        // not in the editor, there Ctrl+R is the vim redo
+        Self::command_reload_builders().set_name(cx, "app.reload_builders").bind_in(cx, "file_tree", "Cmd+R");
+        Self::command_reset_zoom().set_name(cx, "app.reset_zoom").bind(cx, "Cmd+0");
         
         cx.command_default_keymap();
+        // the log list uses Cmd+M
+        Cx::command_minimize().clear_keys(cx);
+        LogList::default_keymap(cx);
+        CommandPalette::default_keymap(cx);
         
         Self {
             menu: Menu::main(vec![
@@ -86,6 +113,8 @@ impl MakepadApp {
                     Menu::item("Cut", Cx::command_cut()),
                     Menu::item("Copy", Cx::command_copy()),
                     Menu::item("Paste", Cx::command_paste()),
+                    Menu::item("Paste as HTML", TextEditor::command_paste_html()),
+                    Menu::item("Paste as String", TextEditor::command_paste_string()),
                     Menu::line(),
                     Menu::item("Find", Self::command_find()),
                     Menu::item("Replace", Self::command_replace()),
@@ -98,6 +127,8 @@ impl MakepadApp {
                 ]),
                 Menu::sub("Selection", vec![
                     Menu::item("Select All", Cx::command_select_all()),
+                    Menu::item("Expand Selection", TextEditor::command_expand_selection()),
+                    Menu::item("Shrink Selection", TextEditor::command_shrink_selection()),
                 ]),
                 Menu::sub("View", vec![
                     Menu::item("Zoom In", Cx::command_zoom_in()),
@@ -175,7 +206,7 @@ impl MakepadApp {
                                 DockTab {
                                     closeable: false,
                                     title: "shaderview.rs".to_string(),
-                                    item: Panel::FileEditor {path: "main/makepad/makepad/app/src/shaderview.rs".to_string(), scroll_pos: Vec2::default(), editor_id: 2}
+                                    item: Panel::FileEditor {path: "main/makepad/makepad/app/src/shaderview.rs".to_string(), scroll_pos: Vec2::default(), editor_id: 2, folded_rows: None}
                                 },
                                 
                             ],
@@ -209,6 +240,11 @@ impl MakepadApp {
                                         title: "Keyboard".to_string(),
                                         item: Panel::Keyboard
                                     },
+                                    DockTab {
+                                        closeable: false,
+                                        title: "Anims".to_string(),
+                                        item: Panel::AnimInspector
+                                    },
                                     
                                 ]
                             })
@@ -217,7 +253,9 @@ impl MakepadApp {
                 },
             },
             windows: vec![],
+            active_window: 0,
             build_manager: BuildManager::new(cx),
+            lsp_manager: LspManager::new(),
             state: AppState::default(),
             storage: AppStorage::new(cx)
         }
@@ -248,39 +286,46 @@ impl MakepadApp {
                     self.default_layout(cx);
                 }
             },
-            Event::KeyDown(ke) => match ke.key_code {
-                KeyCode::KeyD => if ke.modifiers.logo || ke.modifiers.control {
-                    //let size = self.build_manager.search_index.calc_total_size();
-                    //println!("Text Index size {}", size);
-                },
-                KeyCode::KeyR => if ke.modifiers.logo || ke.modifiers.control {
+            Event::Command(command) => {
+                let command = *command;
+                if command == Self::command_reload_builders() {
                     self.storage.reload_builders();
-                },
-                KeyCode::Key0 => if ke.modifiers.logo || ke.modifiers.control {
+                }
+                else if command == Self::command_reset_zoom() {
                     cx.reset_font_atlas_and_redraw();
                     println!("IMPLEMENT SCALE");
                     //self.storage.settings.style_options.scale = 1.0;
                     //self.reload_style(cx);
                     //cx.reset_font_atlas_and_redraw();
                     //self.storage.save_settings(cx);
-                },
-                KeyCode::Equals => if ke.modifiers.logo || ke.modifiers.control {
+                }
+                else if command == Cx::command_zoom_in() {
                     println!("IMPLEMENT SCALE");
                     //let scale = self.storage.settings.style_options.scale * 1.1;
-                   // self.storage.settings.style_options.scale = scale.min(3.0).max(0.3);
+                    // self.storage.settings.style_options.scale = scale.min(3.0).max(0.3);
                     //self.reload_style(cx);
                     //cx.reset_font_atlas_and_redraw();
                     //self.storage.save_settings(cx);
-                },
-                KeyCode::Minus => if ke.modifiers.logo || ke.modifiers.control {
+                }
+                else if let Some(prefix) = CommandPalette::prefix_for_command(command) {
+                    // open it in the window we last clicked in
+                    if let Some(window) = self.windows.get_mut(self.active_window) {
+                        window.open_command_palette(cx, prefix, &self.menu, &self.storage, &self.build_manager);
+                    }
+                }
+                else if command == Cx::command_zoom_out() {
                     println!("IMPLEMENT SCALE");
                     //let scale = self.storage.settings.style_options.scale / 1.1;
                     //self.storage.settings.style_options.scale = scale.min(3.0).max(0.3);
                     //self.reload_style(cx);
                     //cx.reset_font_atlas_and_redraw();
                     //self.storage.save_settings(cx);
-                },
-                _ => ()
+                }
+            },
+            Event::FingerDown(fe) => {
+                if let Some(index) = self.windows.iter().position( | w | w.desktop_window.window.window_id == Some(fe.window_id)) {
+                    self.active_window = index;
+                }
             },
             Event::Signal(se) => {
                 // process network messages for hub_ui
@@ -290,6 +335,7 @@ impl MakepadApp {
                             for htc in msgs.drain(..) {
                                 self.storage.handle_hub_msg(cx, &htc, &mut self.windows, &mut self.state, &mut self.build_manager);
                                 self.build_manager.handle_hub_msg(cx, &mut self.storage, &htc);
+                                self.lsp_manager.handle_hub_msg(cx, &mut self.storage, &mut self.windows, &mut self.state, &htc);
                             }
                             return
                         }
@@ -307,6 +353,13 @@ impl MakepadApp {
                     if self.storage.settings_old.builds != self.storage.settings.builds {
                         self.build_manager.restart_build(cx, &mut self.storage);
                     }
+                    if self.storage.settings_old.vim_mode != self.storage.settings.vim_mode {
+                        for window in &mut self.windows {
+                            for file_editor in window.file_editors.editors.values_mut() {
+                                file_editor.set_vim_mode(self.storage.settings.vim_mode.unwrap_or(false));
+                            }
+                        }
+                    }
                 }
             },
             Event::ShaderRecompile(re) => {
@@ -382,6 +435,12 @@ impl MakepadApp {
                         window.ensure_unique_tab_title_for_file_editors(cx, window_index, &mut self.state);
                     }
                 }
+                else if let Some(utf8_data) = self.storage.keymap_file_read.resolve_utf8(fr) {
+                    // the keymap file is optional, without it the defaults stay
+                    if let Ok(utf8_data) = utf8_data {
+                        self.storage.load_keymap(cx, &mut self.build_manager, utf8_data);
+                    }
+                }
                 else if let Some(utf8_data) = self.storage.app_settings_file_read.resolve_utf8(fr) {
                     if let Ok(utf8_data) = utf8_data {
                         self.storage.load_settings(cx, utf8_data);
@@ -409,7 +468,7 @@ impl MakepadApp {
             _ => ()
         }
         for (window_index, window) in self.windows.iter_mut().enumerate() {
-            window.handle_app_window(cx, event, window_index, &mut self.state, &mut self.storage, &mut self.build_manager);
+            window.handle_app_window(cx, event, window_index, &mut self.state, &mut self.storage, &mut self.build_manager, &mut self.lsp_manager);
             // break;
         }
     }
@@ -5,11 +5,13 @@ use crate::loglist::*;
 use crate::homepage::*;
 use crate::codeicon::*;
 use crate::searchresults::*;
+use crate::commandpalette::*;
 use crate::itemdisplay::*;
 use crate::livemacro::*;
 use crate::colorpicker::*;
 use crate::floatslider::*;
 use crate::shaderview::*;
+use crate::animinspector::*;
 
 pub fn set_makepad_style(cx: &mut Cx) {
     CodeIcon::style(cx);
@@ -17,9 +19,11 @@ pub fn set_makepad_style(cx: &mut Cx) {
     FileTree::style(cx);
     LogList::style(cx);
     SearchResults::style(cx);
+    CommandPalette::style(cx);
     ItemDisplay::style(cx);
     ColorPicker::style(cx);
     FloatSlider::style(cx);
     LiveMacrosView::style(cx);
     ShaderView::style(cx);
+    AnimInspector::style(cx);
 }
@@ -1,203 +1,5 @@
 use makepad_widget::*;
 
-pub struct TokenParserItem {
-    pub chunk: Vec<char>,
-    pub token_type: TokenType,
-}
-
-pub struct TokenParser<'a> {
-    pub tokens: &'a Vec<TokenChunk>,
-    pub flat_text: &'a Vec<char>,
-    pub index: usize,
-    pub next_index: usize
-}
-
-impl <'a>TokenParser<'a> {
-    pub fn new(flat_text: &'a Vec<char>, token_chunks: &'a Vec<TokenChunk>) -> TokenParser<'a> {
-        TokenParser {
-            tokens: token_chunks,
-            flat_text: flat_text,
-            index: 0,
-            next_index: 0
-        }
-    }
-    
-    pub fn advance(&mut self) -> bool {
-        if self.next_index >= self.tokens.len() {
-            return false
-        }
-        self.index = self.next_index;
-        self.next_index += 1;
-        return true;
-    }
-    
-    pub fn eat_should_ignore(&mut self) -> bool {
-        while self.cur_type().should_ignore() {
-            if !self.advance() {
-                return false
-            }
-        }
-        return true
-    }
-    
-    pub fn eat_token(&mut self, tt: TokenType) -> bool {
-        if self.tokens[self.index].token_type == tt{
-            self.advance();
-            return true
-        }
-        false
-    }
-    
-    pub fn eat(&mut self, what: &str) -> bool {
-        // eat as many ignorable tokens
-        if !self.eat_should_ignore() {
-            return false
-        }
-        // then match what in our token
-        let chunk = &self.tokens[self.index];
-        let mut off = chunk.offset;
-        for c in what.chars() {
-            if off - chunk.offset > chunk.len {
-                return false
-            }
-            if self.flat_text[off] != c {
-                return false;
-            }
-            off += 1;
-        }
-        if off - chunk.offset != chunk.len {
-            return false
-        }
-        if !self.eat_should_ignore() {
-            return false
-        }
-        self.advance();
-        true
-    }
-    
-    pub fn prev_type(&self) -> TokenType {
-        if self.index > 0 {
-            self.tokens[self.index - 1].token_type
-        }
-        else {
-            TokenType::Unexpected
-        }
-    }
-    
-    pub fn cur_pair_as_string(&self) -> Option<String> {
-        let pair_token = self.tokens[self.index].pair_token;
-        if pair_token < self.index || pair_token >= self.tokens.len() {
-            return None
-        }
-        let mut out_str = String::new();
-        for i in self.cur_offset() + 1..self.cur_pair_offset() {
-            out_str.push(self.flat_text[i]);
-        }
-        Some(out_str)
-    }
-    
-    pub fn cur_as_string(&self) -> String {
-        let mut out_str = String::new();
-        let tok = &self.tokens[self.index];
-        for i in tok.offset..tok.offset + tok.len {
-            out_str.push(self.flat_text[i]);
-        }
-        return out_str
-    }
-    
-    pub fn cur_type(&self) -> TokenType {
-        self.tokens[self.index].token_type
-    }
-    
-    pub fn cur_line_col(&self) -> (usize, usize) {
-        let off = self.cur_offset();
-        let mut line = 0;
-        let mut lc = 0;
-        for i in 0..off {
-            if self.flat_text[i] == '\n' {
-                line = line + 1;
-                lc = i;
-            }
-        }
-        return (line, off - lc);
-    }
-    
-    pub fn cur_offset(&self) -> usize {
-        self.tokens[self.index].offset
-    }
-    
-    pub fn jump_to_pair(&mut self) {
-        let pair_token = self.tokens[self.index].pair_token;
-        if pair_token > self.index && pair_token < self.tokens.len() {
-            self.index = pair_token;
-        }
-    }
-    
-    pub fn cur_pair_offset(&self) -> usize {
-        self.tokens[self.tokens[self.index].pair_token].offset
-    }
-    
-    pub fn cur_pair_range(&self) -> (usize, usize) {
-        (
-            self.tokens[self.index].offset,
-            self.tokens[self.tokens[self.index].pair_token].offset
-        )
-    }
-    
-    pub fn cur_range(&self) -> (usize, usize) {
-        (
-            self.tokens[self.index].offset,
-            self.tokens[self.index].offset + self.tokens[self.index].len
-        )
-    }
-    
-    pub fn next_type(&self) -> TokenType {
-        if self.index < self.tokens.len() - 1 {
-            self.tokens[self.index + 1].token_type
-        }
-        else {
-            TokenType::Unexpected
-        }
-    }
-    
-    pub fn prev_char(&self) -> char {
-        if self.index > 0 {
-            let len = self.tokens[self.index - 1].len;
-            let ch = self.flat_text[self.tokens[self.index - 1].offset];
-            if len == 1 || ch == ' ' {
-                return ch
-            }
-        }
-        '\0'
-    }
-    
-    pub fn cur_char(&self) -> char {
-        let len = self.tokens[self.index].len;
-        let ch = self.flat_text[self.tokens[self.index].offset];
-        if len == 1 || ch == ' ' {
-            return ch
-        }
-        '\0'
-    }
-    
-    pub fn cur_chunk(&self) -> &[char] {
-        let offset = self.tokens[self.index].offset;
-        let len = self.tokens[self.index].len;
-        &self.flat_text[offset..(offset + len)]
-    }
-    
-    pub fn next_char(&self) -> char {
-        if self.index < self.tokens.len() - 1 {
-            let len = self.tokens[self.index + 1].len;
-            let ch = self.flat_text[self.tokens[self.index + 1].offset];
-            if len == 1 || ch == ' ' {
-                return ch
-            }
-        }
-        '\0'
-    }
-}
-
 pub struct FormatOutput {
     pub out_lines: Vec<Vec<char >>
 }
@@ -141,6 +141,30 @@ impl SearchIndex {
 
         out 
     }
+    
+    // every live identifier that starts like what was typed, the completion list does the fuzzy part
+    pub fn complete(&self, what: &str, storage: &AppStorage) -> Vec<CompletionItem> {
+        let mut out = Vec::new();
+        let first: String = what.chars().take(1).collect();
+        self.identifiers.collect(&first, &| text_buffer_id, mut_id | {
+            (storage.text_buffers[text_buffer_id.as_index()].text_buffer.mutation_id & 0xffff) as u16 == mut_id
+        }, &mut out);
+        out.sort();
+        out.dedup();
+        out.into_iter().map( | label | CompletionItem {
+            insert_text: label.clone(),
+            label: label,
+            detail: None
+        }).collect()
+    }
+    
+    // every live declaration (fn, struct, let ..) with where it is, for go to symbol
+    pub fn declarations(&self, storage: &AppStorage) -> Vec<(String, SearchResult)> {
+        let mut out = Vec::new();
+        self.identifiers.collect_declarations(storage, &mut out);
+        out.sort_by( | a, b | a.0.cmp(&b.0));
+        out
+    }
 }
 
 
@@ -330,6 +354,79 @@ impl TextIndex {
         }
     }
     
+    // is_live tells if an entry (buffer, mutation id) is still current
+    pub fn collect(&self, what: &str, is_live: &dyn Fn(AppTextBufferId, u16) -> bool, out: &mut Vec<String>) {
+        let mut node_id = 0;
+        let mut stem_eat = 0;
+        let mut name = String::new();
+        for c in what.chars() {
+            if stem_eat < self.nodes[node_id].used {
+                if c != self.nodes[node_id].stem[stem_eat] {
+                    return
+                }
+                stem_eat += 1;
+            }
+            else {
+                node_id = if let Some(next_id) = self.nodes[node_id].map.get(&c) {
+                    stem_eat = 0;
+                    *next_id
+                }
+                else {
+                    return
+                };
+            }
+            name.push(c);
+        }
+        // whatever is left of the stem we stopped in
+        for s in stem_eat..self.nodes[node_id].used {
+            name.push(self.nodes[node_id].stem[s]);
+        }
+        self.collect_node(node_id, &mut name, is_live, out);
+    }
+    
+    fn collect_node(&self, node_id: usize, name: &mut String, is_live: &dyn Fn(AppTextBufferId, u16) -> bool, out: &mut Vec<String>) {
+        let node = &self.nodes[node_id];
+        if node.end.iter().any( | ((text_buffer_id, _), entry) | is_live(*text_buffer_id, entry.mut_id)) {
+            out.push(name.clone());
+        }
+        for (key, next) in &node.map {
+            let len = name.len();
+            name.push(*key);
+            for s in 0..self.nodes[*next].used {
+                name.push(self.nodes[*next].stem[s]);
+            }
+            self.collect_node(*next, name, is_live, out);
+            name.truncate(len);
+        }
+    }
+    
+    pub fn collect_declarations(&self, storage: &AppStorage, out: &mut Vec<(String, SearchResult)>) {
+        let mut name: String = self.nodes[0].stem[0..self.nodes[0].used].iter().collect();
+        self.collect_declarations_node(0, &mut name, storage, out);
+    }
+    
+    fn collect_declarations_node(&self, node_id: usize, name: &mut String, storage: &AppStorage, out: &mut Vec<(String, SearchResult)>) {
+        let node = &self.nodes[node_id];
+        for ((text_buffer_id, token), entry) in &node.end {
+            if entry.prio == 1 && (storage.text_buffers[text_buffer_id.as_index()].text_buffer.mutation_id & 0xffff) as u16 == entry.mut_id {
+                out.push((name.clone(), SearchResult {
+                    text_buffer_id: *text_buffer_id,
+                    prio: entry.prio,
+                    token: *token
+                }));
+            }
+        }
+        for (key, next) in &node.map {
+            let len = name.len();
+            name.push(*key);
+            for s in 0..self.nodes[*next].used {
+                name.push(self.nodes[*next].stem[s]);
+            }
+            self.collect_declarations_node(*next, name, storage, out);
+            name.truncate(len);
+        }
+    }
+    
     pub fn _dump_tree(&self, key: char, id: usize, depth: usize) {
         let mut indent = String::new();
         for _ in 0..depth {indent.push_str(" - ");};
@@ -353,3 +450,35 @@ impl TextIndex {
         total
     }*/
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn collected(index: &TextIndex, what: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        // buffer 1 was edited after it got indexed
+        index.collect(what, &| text_buffer_id, mut_id | text_buffer_id.0 == 0 && mut_id == 1, &mut out);
+        out.sort();
+        out
+    }
+    
+    #[test]
+    fn identifiers_are_collected_by_prefix() {
+        let mut index = TextIndex::new();
+        for (token, word) in ["foo", "foobar", "food", "f", "bar", "abcdefghij", "abcdefgh"].iter().enumerate() {
+            index._write_str(word, AppTextBufferId(0), 1, 0, token as u32);
+        }
+        index._write_str("fizz", AppTextBufferId(1), 1, 0, 0);
+        assert_eq!(collected(&index, "f"), vec!["f", "foo", "foobar", "food"]);
+        assert_eq!(collected(&index, "fo"), vec!["foo", "foobar", "food"]);
+        // stopping halfway a stem
+        assert_eq!(collected(&index, "foob"), vec!["foobar"]);
+        // words longer than one node stem
+        assert_eq!(collected(&index, "abc"), vec!["abcdefgh", "abcdefghij"]);
+        assert_eq!(collected(&index, "abcdefghi"), vec!["abcdefghij"]);
+        assert_eq!(collected(&index, "x"), Vec::<String>::new());
+        // stale entries are left out
+        assert_eq!(collected(&index, "fi"), Vec::<String>::new());
+    }
+}
@@ -4,6 +4,7 @@ use crate::hubrouter::*;
 use crate::hubclient::*;
 use crate::httpserver::*;
 use crate::wasmstrip::*;
+use crate::lspclient::*;
 
 use makepad_microserde::*;
 
@@ -21,6 +22,7 @@ pub struct HubBuilder {
     pub builder: String,
     pub abs_cwd_path: String,
     pub processes: Arc<Mutex<Vec<HubProcess>>>,
+    pub lsp_clients: Arc<Mutex<LspClients>>,
 }
 
 pub struct HubWorkspace {
@@ -50,6 +52,7 @@ impl HubBuilder {
         let http_server = Arc::new(Mutex::new(None));
         let abs_cwd_path = format!("{}", std::env::current_dir().unwrap().display());
         let processes = Arc::new(Mutex::new(Vec::<HubProcess>::new()));
+        let lsp_clients = Arc::new(Mutex::new(LspClients::default()));
 
         let (tx_write, rx_write) = mpsc::channel::<FromHubMsg>();
 
@@ -75,6 +78,7 @@ impl HubBuilder {
                             http_server: Arc::clone(&http_server),
                             workspaces: Arc::clone(&workspaces),
                             processes: Arc::clone(&processes),
+                            lsp_clients: Arc::clone(&lsp_clients),
                             builder: builder.to_string(),
                             abs_cwd_path: abs_cwd_path.clone(),
                         };
@@ -115,6 +119,7 @@ impl HubBuilder {
         let http_server = Arc::new(Mutex::new(None));
         let abs_cwd_path = format!("{}", std::env::current_dir().unwrap().display());
         let processes = Arc::new(Mutex::new(Vec::<HubProcess>::new()));
+        let lsp_clients = Arc::new(Mutex::new(LspClients::default()));
 
         loop {
 
@@ -156,6 +161,7 @@ impl HubBuilder {
                         http_server: Arc::clone(&http_server),
                         workspaces: Arc::clone(&workspaces),
                         processes: Arc::clone(&processes),
+                        lsp_clients: Arc::clone(&lsp_clients),
                         builder: builder.to_string(),
                         abs_cwd_path: abs_cwd_path.clone(),
                     };
@@ -264,6 +270,7 @@ impl HubBuilder {
         let workspaces = Arc::new(Mutex::new(HashMap::<String, String>::new()));
         let http_server = Arc::new(Mutex::new(None));
         let processes = Arc::new(Mutex::new(Vec::<HubProcess>::new()));
+        let lsp_clients = Arc::new(Mutex::new(LspClients::default()));
         let abs_cwd_path = format!("{}", std::env::current_dir().unwrap().display());
 
         if let Ok(mut workspaces) = workspaces.lock() {
@@ -284,6 +291,7 @@ impl HubBuilder {
             http_server: Arc::clone(&http_server),
             builder: "".to_string(),
             processes: Arc::clone(&processes),
+            lsp_clients: Arc::clone(&lsp_clients),
             workspaces: Arc::clone(&workspaces),
             abs_cwd_path: abs_cwd_path.clone()
         };
@@ -368,13 +376,27 @@ impl HubBuilder {
 
     pub fn set_config(&mut self, _uid: HubUid, config: HubBuilderConfig) -> Result<(), HubWsError> {
         // if we have a http server. just shut it down
+        let mut workspaces_changed = false;
         if let Ok(mut workspaces) = self.workspaces.lock() {
-            *workspaces = config.workspaces;
-            for (_, rel_path) in workspaces.iter_mut() {
+            let mut new_workspaces = config.workspaces;
+            for (_, rel_path) in new_workspaces.iter_mut() {
                 *rel_path = rel_to_abs_path(&self.abs_cwd_path, &rel_path)
             }
+            workspaces_changed = *workspaces != new_workspaces;
+            *workspaces = new_workspaces;
         };
 
+        // language servers are rooted in a workspace, so restart them if those moved
+        let stopped_clients = if let Ok(mut lsp_clients) = self.lsp_clients.lock() {
+            let mut stopped_clients = if workspaces_changed {lsp_clients.take_clients()} else {Vec::new()};
+            stopped_clients.extend(lsp_clients.set_servers(config.lsp_servers.unwrap_or(Vec::new())));
+            stopped_clients
+        }
+        else {
+            Vec::new()
+        };
+        LspClients::terminate_clients(stopped_clients);
+
         let workspaces = Arc::clone(&self.workspaces);
 
         if let Ok(mut http_server) = self.http_server.lock() {
@@ -432,10 +454,82 @@ impl HubBuilder {
                 ws.program_run(uid, &path, &v) ?;
                 Ok(())
             },
+            HubMsg::LspDocumentSync {uid, path, text, saved} => {
+                ws.lsp_client(uid, &path, | client, abs_path | client.document_sync(abs_path, &text, saved))
+            },
+            HubMsg::LspHoverRequest {uid, path, pos} => {
+                ws.lsp_request(htc.from, uid, &path, LspRequest::Hover, pos)
+            },
+            HubMsg::LspCompletionRequest {uid, path, pos} => {
+                ws.lsp_request(htc.from, uid, &path, LspRequest::Completion, pos)
+            },
+            HubMsg::LspDefinitionRequest {uid, path, pos} => {
+                ws.lsp_request(htc.from, uid, &path, LspRequest::Definition, pos)
+            },
             _ => Ok(())
         }
     }
 
+    pub fn lsp_client<F>(&mut self, uid: HubUid, path: &str, cb: F) -> Result<(), HubWsError>
+    where F: FnOnce(&mut LspClient, &str) -> bool {
+        let (abs_dir, workspace, sub_path) = self.workspace_split_from_path(uid, path) ?;
+        let abs_path = format!("{}/{}", abs_dir, sub_path);
+        let (server, key, client) = if let Ok(lsp_clients) = self.lsp_clients.lock() {
+            let server = if let Some(server) = lsp_clients.server_for_path(&sub_path) {server.clone()}else {return Ok(())};
+            let key = format!("{}:{}", workspace, server.command);
+            let client = lsp_clients.clients.get(&key).cloned();
+            (server, key, client)
+        }
+        else {
+            return Ok(())
+        };
+
+        // starting a server takes a while, so it happens outside the lock
+        let client = match client {
+            Some(client) => client,
+            None => {
+                let client = match LspClient::start(&server, self.route_send.clone(), &self.builder, &workspace, &abs_dir) {
+                    Ok(client) => Some(Arc::new(Mutex::new(client))),
+                    Err(e) => {
+                        self.error(uid, format!("Builder {} cannot start language server {} {:?}", self.builder, server.command, e));
+                        None
+                    }
+                };
+                let inserted = if let Ok(mut lsp_clients) = self.lsp_clients.lock() {
+                    lsp_clients.clients.entry(key.clone()).or_insert(client.clone()).clone()
+                }
+                else {
+                    None
+                };
+                // another request started one in the meantime
+                if let Some(client) = client {
+                    if !inserted.as_ref().map_or(false, | inserted | Arc::ptr_eq(inserted, &client)) {
+                        LspClients::terminate_clients(vec![client]);
+                    }
+                }
+                inserted
+            }
+        };
+
+        if let Some(client) = client {
+            let gone = if let Ok(mut client) = client.lock() {!cb(&mut client, &abs_path)} else {true};
+            if gone { // start a fresh one next time
+                if let Ok(mut lsp_clients) = self.lsp_clients.lock() {
+                    if lsp_clients.clients.get(&key).map_or(false, | v | v.as_ref().map_or(false, | v | Arc::ptr_eq(v, &client))) {
+                        lsp_clients.clients.remove(&key);
+                    }
+                }
+                LspClients::terminate_clients(vec![client]);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn lsp_request(&mut self, from: HubAddr, uid: HubUid, path: &str, request: LspRequest, pos: LspPos) -> Result<(), HubWsError> {
+        let full_path = format!("{}/{}", self.builder, path);
+        self.lsp_client(uid, path, | client, abs_path | client.request(request, from, uid, &full_path, abs_path, pos))
+    }
+
     pub fn process_kill(&mut self, uid: HubUid) {
         if let Ok(mut procs) = self.processes.lock() {
             for proc in procs.iter_mut() {
@@ -131,6 +131,56 @@ pub enum HubMsg {
         path: String,
         done: bool
     },
+    
+    // language server proxying, paths are workspace relative like file reads
+    LspDocumentSync {
+        uid: HubUid,
+        path: String,
+        text: String,
+        saved: bool
+    },
+    
+    LspDiagnostics {
+        uid: HubUid,
+        path: String,
+        diagnostics: Vec<LspDiagnostic>
+    },
+    
+    LspHoverRequest {
+        uid: HubUid,
+        path: String,
+        pos: LspPos
+    },
+    
+    LspHoverResponse {
+        uid: HubUid,
+        path: String,
+        text: Option<String>
+    },
+    
+    LspCompletionRequest {
+        uid: HubUid,
+        path: String,
+        pos: LspPos
+    },
+    
+    LspCompletionResponse {
+        uid: HubUid,
+        path: String,
+        items: Vec<LspCompletion>
+    },
+    
+    LspDefinitionRequest {
+        uid: HubUid,
+        path: String,
+        pos: LspPos
+    },
+    
+    LspDefinitionResponse {
+        uid: HubUid,
+        path: String,
+        locations: Vec<LspLocation>
+    },
 }
 
 impl HubMsg{
@@ -138,6 +188,7 @@ impl HubMsg{
         match self{
             HubMsg::BuilderConfig{..}=>true,
             HubMsg::FileWriteRequest{..}=>true,
+            HubMsg::LspDocumentSync{..}=>true,
             _=>false
         }
     }
@@ -214,6 +265,50 @@ impl HubPackage {
 pub struct HubBuilderConfig {
     pub http_server: HttpServerConfig,
     pub workspaces: HashMap<String, String>,
+    pub lsp_servers: Option<Vec<LspServerConfig>>,
+}
+
+#[derive(Debug, Clone, SerBin, DeBin, PartialEq, SerRon, DeRon)]
+pub struct LspServerConfig {
+    pub command: String,
+    pub args: Vec<String>,
+    pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, SerBin, DeBin)]
+pub struct LspPos {
+    pub line: usize,
+    pub character: usize
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, SerBin, DeBin)]
+pub enum LspSeverity {
+    Error,
+    Warning,
+    Info,
+    Hint
+}
+
+#[derive(Debug, Clone, PartialEq, SerBin, DeBin)]
+pub struct LspDiagnostic {
+    pub start: LspPos,
+    pub end: LspPos,
+    pub severity: LspSeverity,
+    pub message: String
+}
+
+#[derive(Debug, Clone, PartialEq, SerBin, DeBin)]
+pub struct LspCompletion {
+    pub label: String,
+    pub detail: Option<String>,
+    pub insert_text: String
+}
+
+#[derive(Debug, Clone, PartialEq, SerBin, DeBin)]
+pub struct LspLocation {
+    pub path: String,
+    pub start: LspPos,
+    pub end: LspPos
 }
 
 
@@ -24,3 +24,6 @@ pub use crate::httpserver::*;
 
 mod wasmstrip;
 pub use crate::wasmstrip::*;
+
+mod lspclient;
+pub use crate::lspclient::*;
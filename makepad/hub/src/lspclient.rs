@@ -0,0 +1,777 @@
+// the json structs mirror the LSP spec names, which are camelCase
+#![allow(non_snake_case)]
+
+use crate::hubmsg::*;
+use crate::hubrouter::*;
+
+use makepad_microserde::*;
+
+use std::process::{Command, Child, Stdio};
+use std::io::{Write, BufRead, BufReader};
+use std::sync::{mpsc, Arc, Mutex};
+use std::collections::HashMap;
+use std::str::Chars;
+
+// all the language servers of a builder, keyed by workspace and command. each client has its own
+// lock so a slow server only holds up the requests that go to it
+#[derive(Default)]
+pub struct LspClients {
+    pub servers: Vec<LspServerConfig>,
+    // None means it failed to start, so we dont retry on every keystroke
+    pub clients: HashMap<String, Option<Arc<Mutex<LspClient>>>>,
+}
+
+pub struct LspClient {
+    child: Child,
+    tx_write: mpsc::Sender<String>,
+    next_id: u64,
+    pending: Arc<Mutex<HashMap<u64, LspPending>>>,
+    versions: HashMap<String, u64>,
+    // the last synced text per uri, to convert columns to and from the server encoding
+    documents: Arc<Mutex<HashMap<String, String>>>,
+    encoding: Arc<Mutex<LspEncoding>>,
+    language_id: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LspRequest {
+    Hover,
+    Completion,
+    Definition
+}
+
+struct LspPending {
+    from: HubAddr,
+    uid: HubUid,
+    path: String,
+    request: LspRequest
+}
+
+impl LspClients {
+    // returns the clients of the old servers, to be terminated after letting go of the lock
+    pub fn set_servers(&mut self, servers: Vec<LspServerConfig>) -> Vec<Arc<Mutex<LspClient>>> {
+        if servers != self.servers {
+            self.servers = servers;
+            return self.take_clients()
+        }
+        Vec::new()
+    }
+
+    pub fn server_for_path(&self, path: &str) -> Option<&LspServerConfig> {
+        self.servers.iter().find( | server | server.extensions.iter().any( | ext | path.ends_with(ext)))
+    }
+
+    pub fn take_clients(&mut self) -> Vec<Arc<Mutex<LspClient>>> {
+        self.clients.drain().filter_map( | (_, client) | client).collect()
+    }
+
+    pub fn terminate_clients(clients: Vec<Arc<Mutex<LspClient>>>) {
+        for client in clients {
+            if let Ok(mut client) = client.lock() {
+                client.terminate();
+            }
+        }
+    }
+}
+
+impl LspClient {
+
+    pub fn start(config: &LspServerConfig, route_send: HubRouteSend, builder: &str, workspace: &str, abs_root: &str) -> Result<LspClient, std::io::Error> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .current_dir(abs_root)
+            .spawn() ?;
+
+        let mut stdin = child.stdin.take().expect("stdin cannot be taken!");
+        let stdout = child.stdout.take().expect("stdout cannot be taken!");
+
+        let (tx_write, rx_write) = mpsc::channel::<String>();
+        let (tx_ready, rx_ready) = mpsc::channel::<()>();
+        let pending = Arc::new(Mutex::new(HashMap::<u64, LspPending>::new()));
+        let documents = Arc::new(Mutex::new(HashMap::<String, String>::new()));
+        // servers that dont answer with a positionEncoding use utf-16
+        let encoding = Arc::new(Mutex::new(LspEncoding::Utf16));
+
+        let initialize = rpc_request(0, "initialize", &format!(
+            concat!(
+                "{{\"processId\":{},\"rootUri\":{},\"capabilities\":{{",
+                "\"general\":{{\"positionEncodings\":[\"utf-32\",\"utf-16\"]}},",
+                "\"textDocument\":{{",
+                "\"synchronization\":{{\"didSave\":true}},",
+                "\"hover\":{{\"contentFormat\":[\"plaintext\"]}},",
+                "\"completion\":{{\"completionItem\":{{\"snippetSupport\":false}}}},",
+                "\"definition\":{{\"linkSupport\":false}},",
+                "\"publishDiagnostics\":{{}}",
+                "}}}}}}"
+            ),
+            std::process::id(),
+            path_to_uri(abs_root).serialize_json()
+        ));
+
+        // the writer holds back everything until the server answered initialize
+        let _writer_thread = std::thread::spawn(move || {
+            if write_frame(&mut stdin, &initialize).is_err() {
+                return
+            }
+            if rx_ready.recv().is_err() {
+                return
+            }
+            if write_frame(&mut stdin, &rpc_notify("initialized", "{}")).is_err() {
+                return
+            }
+            while let Ok(body) = rx_write.recv() {
+                if write_frame(&mut stdin, &body).is_err() {
+                    return
+                }
+            }
+        });
+
+        let _reader_thread = {
+            let pending = Arc::clone(&pending);
+            let documents = Arc::clone(&documents);
+            let encoding = Arc::clone(&encoding);
+            let tx_write = tx_write.clone();
+            let builder = builder.to_string();
+            let workspace = workspace.to_string();
+            let abs_root = abs_root.to_string();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stdout);
+                let mut tx_ready = Some(tx_ready);
+                while let Some(body) = read_frame(&mut reader) {
+                    let head: LspHeadJson = match DeJson::deserialize_json(&body) {
+                        Ok(head) => head,
+                        Err(_) => continue
+                    };
+                    match (head.id, head.method) {
+                        (Some(LspIdJson::Num(0)), None) => if let Some(tx_ready) = tx_ready.take() {
+                            let parsed: Result<LspInitializeMsgJson, DeJsonErr> = DeJson::deserialize_json(&body);
+                            if let Some(server_encoding) = parsed.ok().and_then( | v | v.result).and_then( | v | v.capabilities.positionEncoding) {
+                                if let Ok(mut encoding) = encoding.lock() {
+                                    *encoding = match server_encoding.as_ref() {
+                                        "utf-8" => LspEncoding::Utf8,
+                                        "utf-32" => LspEncoding::Utf32,
+                                        _ => LspEncoding::Utf16
+                                    };
+                                }
+                            }
+                            let _ = tx_ready.send(());
+                        },
+                        (Some(LspIdJson::Num(id)), None) => {
+                            let encoding = if let Ok(encoding) = encoding.lock() {*encoding} else {LspEncoding::Utf16};
+                            let pending = if let Ok(mut pending) = pending.lock() {pending.remove(&id)}else {None};
+                            if let Some(pending) = pending {
+                                let msg = match pending.request {
+                                    LspRequest::Hover => {
+                                        let parsed: Result<LspHoverMsgJson, DeJsonErr> = DeJson::deserialize_json(&body);
+                                        HubMsg::LspHoverResponse {
+                                            uid: pending.uid,
+                                            path: pending.path,
+                                            text: parsed.ok().and_then( | v | v.result).map( | v | v.contents.0)
+                                        }
+                                    },
+                                    LspRequest::Completion => {
+                                        let parsed: Result<LspCompletionMsgJson, DeJsonErr> = DeJson::deserialize_json(&body);
+                                        HubMsg::LspCompletionResponse {
+                                            uid: pending.uid,
+                                            path: pending.path,
+                                            items: if let Some(Some(items)) = parsed.ok().map( | v | v.result) {
+                                                items.0.into_iter().map( | v | v.into_completion()).collect()
+                                            }
+                                            else {
+                                                Vec::new()
+                                            }
+                                        }
+                                    },
+                                    LspRequest::Definition => {
+                                        let parsed: Result<LspDefinitionMsgJson, DeJsonErr> = DeJson::deserialize_json(&body);
+                                        HubMsg::LspDefinitionResponse {
+                                            uid: pending.uid,
+                                            path: pending.path,
+                                            locations: if let Some(Some(locations)) = parsed.ok().map( | v | v.result) {
+                                                locations.0.into_iter().filter_map( | v | v.into_location(&builder, &workspace, &abs_root, encoding, &documents)).collect()
+                                            }
+                                            else {
+                                                Vec::new()
+                                            }
+                                        }
+                                    }
+                                };
+                                route_send.send(ToHubMsg {
+                                    to: HubMsgTo::Client(pending.from),
+                                    msg: msg
+                                });
+                            }
+                        },
+                        (None, Some(method)) => if method == "textDocument/publishDiagnostics" {
+                            let parsed: Result<LspPublishDiagnosticsMsgJson, DeJsonErr> = DeJson::deserialize_json(&body);
+                            if let Ok(parsed) = parsed {
+                                let encoding = if let Ok(encoding) = encoding.lock() {*encoding} else {LspEncoding::Utf16};
+                                let text = document_text(&documents, &parsed.params.uri);
+                                let lines = LspLines::new(text.as_ref().map( | v | v.as_ref()));
+                                route_send.send(ToHubMsg {
+                                    to: HubMsgTo::UI,
+                                    msg: HubMsg::LspDiagnostics {
+                                        uid: HubUid::zero(),
+                                        path: uri_to_path(&parsed.params.uri, &builder, &workspace, &abs_root),
+                                        diagnostics: parsed.params.diagnostics.into_iter().map( | v | v.into_diagnostic(encoding, &lines)).collect()
+                                    }
+                                });
+                            }
+                        },
+                        (Some(id), Some(_)) => { // a request from the server, we support none of them
+                            let _ = tx_write.send(format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"result\":null}}", id.serialize_json()));
+                        },
+                        _ => ()
+                    }
+                }
+            })
+        };
+
+        let language_id = match config.extensions.first().map( | v | v.as_ref()) {
+            Some(".rs") => "rust",
+            Some(".js") => "javascript",
+            Some(".sol") => "solidity",
+            _ => "plaintext"
+        }.to_string();
+
+        Ok(LspClient {
+            child: child,
+            tx_write: tx_write,
+            next_id: 1,
+            pending: pending,
+            versions: HashMap::new(),
+            documents: documents,
+            encoding: encoding,
+            language_id: language_id,
+        })
+    }
+
+    // returns false if the server has gone away
+    pub fn document_sync(&mut self, abs_path: &str, text: &str, saved: bool) -> bool {
+        let uri = path_to_uri(abs_path);
+        if let Ok(mut documents) = self.documents.lock() {
+            documents.insert(uri.clone(), text.to_string());
+        }
+        let ok = if let Some(version) = self.versions.get_mut(&uri) {
+            *version += 1;
+            self.tx_write.send(rpc_notify("textDocument/didChange", &LspDidChangeJson {
+                textDocument: LspVersionedIdJson {uri: uri.clone(), version: *version},
+                contentChanges: vec![LspContentChangeJson {text: text.to_string()}]
+            }.serialize_json())).is_ok()
+        }
+        else {
+            self.versions.insert(uri.clone(), 1);
+            self.tx_write.send(rpc_notify("textDocument/didOpen", &LspDidOpenJson {
+                textDocument: LspTextDocumentItemJson {
+                    uri: uri.clone(),
+                    languageId: self.language_id.clone(),
+                    version: 1,
+                    text: text.to_string()
+                }
+            }.serialize_json())).is_ok()
+        };
+        if ok && saved {
+            return self.tx_write.send(rpc_notify("textDocument/didSave", &LspDidSaveJson {
+                textDocument: LspDocumentIdJson {uri: uri}
+            }.serialize_json())).is_ok()
+        }
+        ok
+    }
+
+    pub fn request(&mut self, request: LspRequest, from: HubAddr, uid: HubUid, path: &str, abs_path: &str, pos: LspPos) -> bool {
+        let id = self.next_id;
+        self.next_id += 1;
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(id, LspPending {
+                from: from,
+                uid: uid,
+                path: path.to_string(),
+                request: request
+            });
+        }
+        let method = match request {
+            LspRequest::Hover => "textDocument/hover",
+            LspRequest::Completion => "textDocument/completion",
+            LspRequest::Definition => "textDocument/definition",
+        };
+        let uri = path_to_uri(abs_path);
+        let encoding = if let Ok(encoding) = self.encoding.lock() {*encoding} else {LspEncoding::Utf16};
+        let text = document_text(&self.documents, &uri);
+        let lines = LspLines::new(text.as_ref().map( | v | v.as_ref()));
+        self.tx_write.send(rpc_request(id, method, &LspPositionParamsJson {
+            textDocument: LspDocumentIdJson {uri: uri},
+            position: LspPositionJson {line: pos.line as u64, character: encoding.to_server(lines.line(pos.line), pos.character)}
+        }.serialize_json())).is_ok()
+    }
+
+    pub fn terminate(&mut self) {
+        let _ = self.tx_write.send(rpc_request(self.next_id, "shutdown", "null"));
+        let _ = self.tx_write.send(rpc_notify("exit", "null"));
+        // give it a moment to exit by itself before we pull the plug
+        for _ in 0..10 {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn rpc_request(id: u64, method: &str, params: &str) -> String {
+    format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"method\":\"{}\",\"params\":{}}}", id, method, params)
+}
+
+fn rpc_notify(method: &str, params: &str) -> String {
+    format!("{{\"jsonrpc\":\"2.0\",\"method\":\"{}\",\"params\":{}}}", method, params)
+}
+
+fn write_frame(out: &mut dyn Write, body: &str) -> Result<(), std::io::Error> {
+    write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body) ?;
+    out.flush()
+}
+
+fn read_frame(reader: &mut dyn BufRead) -> Option<String> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok() ? == 0 {
+            return None
+        }
+        let line = line.trim_end();
+        if line.len() == 0 {
+            break
+        }
+        if line.to_lowercase().starts_with("content-length:") {
+            content_length = line["content-length:".len()..].trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0u8; content_length ?];
+    reader.read_exact(&mut body).ok() ?;
+    String::from_utf8(body).ok()
+}
+
+pub fn path_to_uri(abs_path: &str) -> String {
+    let mut uri = "file://".to_string();
+    if !abs_path.starts_with("/") {
+        uri.push('/');
+    }
+    for c in abs_path.chars() {
+        match c {
+            ' ' => uri.push_str("%20"),
+            '%' => uri.push_str("%25"),
+            '#' => uri.push_str("%23"),
+            '?' => uri.push_str("%3F"),
+            '\\' => uri.push('/'),
+            _ => uri.push(c)
+        }
+    }
+    uri
+}
+
+// the synced text, or whats on disk for files we never sent over
+fn document_text(documents: &Arc<Mutex<HashMap<String, String>>>, uri: &str) -> Option<String> {
+    if let Ok(documents) = documents.lock() {
+        if let Some(text) = documents.get(uri) {
+            return Some(text.clone())
+        }
+    }
+    std::fs::read_to_string(uri_to_abs_path(uri)).ok()
+}
+
+// turns a file uri back into a builder path, or leaves it absolute if its outside the workspace
+pub fn uri_to_path(uri: &str, builder: &str, workspace: &str, abs_root: &str) -> String {
+    let abs_path = uri_to_abs_path(uri);
+    let root = format!("{}/", abs_root.trim_end_matches('/'));
+    if abs_path.starts_with(&root) {
+        return format!("{}/{}/{}", builder, workspace, &abs_path[root.len()..])
+    }
+    abs_path
+}
+
+pub fn uri_to_abs_path(uri: &str) -> String {
+    let encoded = if uri.starts_with("file://") {&uri[7..]} else {uri};
+    let mut bytes = Vec::new();
+    let mut iter = encoded.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex: Vec<u8> = iter.by_ref().take(2).collect();
+            if let Ok(v) = u8::from_str_radix(std::str::from_utf8(&hex).unwrap_or(""), 16) {
+                bytes.push(v);
+                continue;
+            }
+        }
+        bytes.push(b);
+    }
+    let mut abs_path = String::from_utf8_lossy(&bytes).to_string();
+    // windows drive letters come as /C:/
+    if abs_path.len() > 2 && abs_path.as_bytes()[2] == b':' {
+        abs_path.remove(0);
+    }
+    abs_path
+}
+
+// our columns count chars, the server counts in the encoding it picked
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LspEncoding {
+    Utf8,
+    Utf16,
+    Utf32
+}
+
+impl LspEncoding {
+    fn units(&self, c: char) -> usize {
+        match self {
+            LspEncoding::Utf8 => c.len_utf8(),
+            LspEncoding::Utf16 => c.len_utf16(),
+            LspEncoding::Utf32 => 1
+        }
+    }
+
+    pub fn to_server(&self, line: Option<&str>, col: usize) -> u64 {
+        let line = if let Some(line) = line {line} else {return col as u64};
+        let mut units = 0;
+        let mut chars = 0;
+        for c in line.chars().take(col) {
+            units += self.units(c);
+            chars += 1;
+        }
+        (units + (col - chars)) as u64
+    }
+
+    pub fn from_server(&self, line: Option<&str>, character: u64) -> usize {
+        let line = if let Some(line) = line {line} else {return character as usize};
+        let character = character as usize;
+        let mut units = 0;
+        let mut chars = 0;
+        for c in line.chars() {
+            if units >= character {
+                return chars
+            }
+            units += self.units(c);
+            chars += 1;
+        }
+        chars + character.saturating_sub(units)
+    }
+}
+
+pub struct LspLines<'a> {
+    lines: Vec<&'a str>
+}
+
+impl<'a> LspLines<'a> {
+    pub fn new(text: Option<&'a str>) -> Self {
+        Self {lines: if let Some(text) = text {text.split('\n').collect()} else {Vec::new()}}
+    }
+
+    pub fn line(&self, line: usize) -> Option<&'a str> {
+        self.lines.get(line).cloned()
+    }
+}
+
+// outgoing json
+
+#[derive(SerJson)]
+struct LspDocumentIdJson {
+    uri: String
+}
+
+#[derive(SerJson)]
+struct LspVersionedIdJson {
+    uri: String,
+    version: u64
+}
+
+#[derive(SerJson)]
+struct LspTextDocumentItemJson {
+    uri: String,
+    languageId: String,
+    version: u64,
+    text: String
+}
+
+#[derive(SerJson)]
+struct LspDidOpenJson {
+    textDocument: LspTextDocumentItemJson
+}
+
+#[derive(SerJson)]
+struct LspContentChangeJson {
+    text: String
+}
+
+#[derive(SerJson)]
+struct LspDidChangeJson {
+    textDocument: LspVersionedIdJson,
+    contentChanges: Vec<LspContentChangeJson>
+}
+
+#[derive(SerJson)]
+struct LspDidSaveJson {
+    textDocument: LspDocumentIdJson
+}
+
+#[derive(SerJson, DeJson)]
+#[microserde(skip_unknown)]
+struct LspPositionJson {
+    line: u64,
+    character: u64
+}
+
+#[derive(SerJson)]
+struct LspPositionParamsJson {
+    textDocument: LspDocumentIdJson,
+    position: LspPositionJson
+}
+
+// incoming json
+
+// request ids can be numbers or strings, ours are always numbers
+enum LspIdJson {
+    Num(u64),
+    Str(String)
+}
+
+impl DeJson for LspIdJson {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        if let DeJsonTok::Str = s.tok {
+            return Ok(LspIdJson::Str(DeJson::de_json(s, i) ?))
+        }
+        Ok(LspIdJson::Num(DeJson::de_json(s, i) ?))
+    }
+}
+
+impl SerJson for LspIdJson {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        match self {
+            LspIdJson::Num(id) => id.ser_json(d, s),
+            LspIdJson::Str(id) => id.ser_json(d, s)
+        }
+    }
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspHeadJson {
+    id: Option<LspIdJson>,
+    method: Option<String>
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspServerCapabilitiesJson {
+    positionEncoding: Option<String>
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspInitializeJson {
+    capabilities: LspServerCapabilitiesJson
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspInitializeMsgJson {
+    result: Option<LspInitializeJson>
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspRangeJson {
+    start: LspPositionJson,
+    end: LspPositionJson
+}
+
+impl LspPositionJson {
+    fn into_pos(&self, encoding: LspEncoding, lines: &LspLines) -> LspPos {
+        LspPos {
+            line: self.line as usize,
+            character: encoding.from_server(lines.line(self.line as usize), self.character)
+        }
+    }
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspDiagnosticJson {
+    range: LspRangeJson,
+    severity: Option<u64>,
+    message: String
+}
+
+impl LspDiagnosticJson {
+    fn into_diagnostic(self, encoding: LspEncoding, lines: &LspLines) -> LspDiagnostic {
+        LspDiagnostic {
+            start: self.range.start.into_pos(encoding, lines),
+            end: self.range.end.into_pos(encoding, lines),
+            severity: match self.severity {
+                Some(2) => LspSeverity::Warning,
+                Some(3) => LspSeverity::Info,
+                Some(4) => LspSeverity::Hint,
+                _ => LspSeverity::Error
+            },
+            message: self.message
+        }
+    }
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspPublishDiagnosticsJson {
+    uri: String,
+    diagnostics: Vec<LspDiagnosticJson>
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspPublishDiagnosticsMsgJson {
+    params: LspPublishDiagnosticsJson
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspMarkedStringJson {
+    value: String
+}
+
+// hover contents can be a string, a markup object or an array of either
+struct LspHoverContentsJson(String);
+
+impl DeJson for LspHoverContentsJson {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        match s.tok {
+            DeJsonTok::BlockOpen => {
+                s.block_open(i) ?;
+                let mut parts = Vec::new();
+                while s.tok != DeJsonTok::BlockClose {
+                    let part: LspHoverContentsJson = DeJson::de_json(s, i) ?;
+                    parts.push(part.0);
+                    s.eat_comma_block(i) ?;
+                }
+                s.block_close(i) ?;
+                Ok(LspHoverContentsJson(parts.join("\n")))
+            },
+            DeJsonTok::CurlyOpen => {
+                let marked: LspMarkedStringJson = DeJson::de_json(s, i) ?;
+                Ok(LspHoverContentsJson(marked.value))
+            },
+            _ => Ok(LspHoverContentsJson(DeJson::de_json(s, i) ?))
+        }
+    }
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspHoverJson {
+    contents: LspHoverContentsJson
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspHoverMsgJson {
+    result: Option<LspHoverJson>
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspTextEditJson {
+    newText: String
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspCompletionItemJson {
+    label: String,
+    detail: Option<String>,
+    insertText: Option<String>,
+    textEdit: Option<LspTextEditJson>
+}
+
+impl LspCompletionItemJson {
+    fn into_completion(self) -> LspCompletion {
+        LspCompletion {
+            insert_text: if let Some(edit) = self.textEdit {
+                edit.newText
+            }
+            else if let Some(text) = self.insertText {
+                text
+            }
+            else {
+                self.label.clone()
+            },
+            label: self.label,
+            detail: self.detail
+        }
+    }
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspCompletionListJson {
+    items: Vec<LspCompletionItemJson>
+}
+
+// completion results are either an array of items or a list object
+struct LspCompletionsJson(Vec<LspCompletionItemJson>);
+
+impl DeJson for LspCompletionsJson {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        if let DeJsonTok::CurlyOpen = s.tok {
+            let list: LspCompletionListJson = DeJson::de_json(s, i) ?;
+            return Ok(LspCompletionsJson(list.items))
+        }
+        Ok(LspCompletionsJson(DeJson::de_json(s, i) ?))
+    }
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspCompletionMsgJson {
+    result: Option<LspCompletionsJson>
+}
+
+// covers both Location and LocationLink
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspLocationJson {
+    uri: Option<String>,
+    range: Option<LspRangeJson>,
+    targetUri: Option<String>,
+    targetSelectionRange: Option<LspRangeJson>
+}
+
+impl LspLocationJson {
+    fn into_location(self, builder: &str, workspace: &str, abs_root: &str, encoding: LspEncoding, documents: &Arc<Mutex<HashMap<String, String>>>) -> Option<LspLocation> {
+        let uri = self.uri.or(self.targetUri) ?;
+        let range = self.range.or(self.targetSelectionRange) ?;
+        let text = if encoding != LspEncoding::Utf32 {document_text(documents, &uri)} else {None};
+        let lines = LspLines::new(text.as_ref().map( | v | v.as_ref()));
+        Some(LspLocation {
+            path: uri_to_path(&uri, builder, workspace, abs_root),
+            start: range.start.into_pos(encoding, &lines),
+            end: range.end.into_pos(encoding, &lines)
+        })
+    }
+}
+
+// definition results are a single location or an array of them
+struct LspLocationsJson(Vec<LspLocationJson>);
+
+impl DeJson for LspLocationsJson {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        if let DeJsonTok::CurlyOpen = s.tok {
+            return Ok(LspLocationsJson(vec![DeJson::de_json(s, i) ?]))
+        }
+        Ok(LspLocationsJson(DeJson::de_json(s, i) ?))
+    }
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct LspDefinitionMsgJson {
+    result: Option<LspLocationsJson>
+}
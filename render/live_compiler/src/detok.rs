@@ -6,7 +6,7 @@ use crate::lit::{Lit};
 use crate::colors::Color;
 use crate::math::*;
 use crate::livestyles::LiveStyles;
-use crate::livetypes::{Font, LiveId, Play, Anim, Ease, Track};
+use crate::livetypes::{Font, LiveId, Play, Anim, Ease, Track, Spring, Walk, Margin, Padding};
 
 pub trait DeTokParser {
     fn ident_path_to_live_id(&self, ident_path: &IdentPath) -> LiveId;
@@ -254,6 +254,32 @@ impl DeTok for Font {
                 p.skip_token();
                 return Ok(p.get_live_styles().get_or_insert_font_by_ident(ident))
             }
+            // a fallback chain, the first font is the one we use
+            Token::LeftBracket => {
+                p.skip_token();
+                let mut fonts = Vec::new();
+                while !p.accept_token(Token::RightBracket) {
+                    match p.peek_token() {
+                        Token::String(ident) => {
+                            p.skip_token();
+                            fonts.push(p.get_live_styles().get_or_insert_font_by_ident(ident));
+                        }
+                        token => {
+                            return Err(p.error(format!("Expected font path {}", token)));
+                        }
+                    }
+                    if !p.accept_token(Token::Comma) {
+                        p.expect_token(Token::RightBracket) ?;
+                        break;
+                    }
+                }
+                if fonts.len() == 0 {
+                    return Err(p.error(format!("Expected at least one font path")));
+                }
+                let font = fonts.remove(0);
+                p.get_live_styles().font_fallbacks.insert(font.font_id, fonts);
+                return Ok(font)
+            }
             _ => ()
         }
         Err(p.error(format!("Expected integer literal")))
@@ -278,6 +304,24 @@ fn parse_track_rhs(p: &mut dyn DeTokParser, time: f64, track: &mut Track) -> Res
         Track::Color {keys, ..} => {
             keys.push((time, Color::de_tok(p) ?));
         }
+        Track::Spring {target, ..} => { // a spring just heads for the last key
+            *target = f32::de_tok(p) ?;
+        }
+        Track::Int {keys, ..} => {
+            keys.push((time, i64::de_tok(p) ?));
+        }
+        Track::Bool {keys, ..} => {
+            keys.push((time, bool::de_tok(p) ?));
+        }
+        Track::Walk {keys, ..} => {
+            keys.push((time, Walk::de_tok(p) ?));
+        }
+        Track::Margin {keys, ..} => {
+            keys.push((time, Margin::de_tok(p) ?));
+        }
+        Track::Padding {keys, ..} => {
+            keys.push((time, Padding::de_tok(p) ?));
+        }
     }
     Ok(())
 }
@@ -293,6 +337,28 @@ fn parse_track(p: &mut dyn DeTokParser, track: &mut Track) -> Result<(), LiveErr
                     p.expect_token(Token::Colon) ?;
                     track.set_ease(Ease::de_tok(p) ?);
                 }
+                else if ident == Ident::new("spring") {
+                    p.skip_token();
+                    p.expect_token(Token::Colon) ?;
+                    let value = Spring::de_tok(p) ?;
+                    if let Track::Spring {spring, ..} = track {
+                        *spring = value;
+                    }
+                    else {
+                        return Err(span.error(p, format!("Only Spring tracks have a spring")));
+                    }
+                }
+                else if ident == Ident::new("target") {
+                    p.skip_token();
+                    p.expect_token(Token::Colon) ?;
+                    let value = f32::de_tok(p) ?;
+                    if let Track::Spring {target, ..} = track {
+                        *target = value;
+                    }
+                    else {
+                        return Err(span.error(p, format!("Only Spring tracks have a target, use keys")));
+                    }
+                }
                 else if ident == Ident::new("live_id") {
                     p.skip_token();
                     p.expect_token(Token::Colon) ?;
@@ -434,6 +500,61 @@ impl DeTok for Anim {
                         parse_track(p, &mut track) ?;
                         tracks.push(track);
                     }
+                    else if ident == Ident::new("Spring") {
+                        let mut track = Track::Spring {
+                            live_id: LiveId(tracks.len() as u64),
+                            spring: Spring::default(),
+                            target: 0.
+                        };
+                        parse_track(p, &mut track) ?;
+                        tracks.push(track);
+                    }
+                    else if ident == Ident::new("Int") {
+                        let mut track = Track::Int {
+                            live_id: LiveId(tracks.len() as u64),
+                            keys: Vec::new()
+                        };
+                        parse_track(p, &mut track) ?;
+                        tracks.push(track);
+                    }
+                    else if ident == Ident::new("Bool") {
+                        let mut track = Track::Bool {
+                            live_id: LiveId(tracks.len() as u64),
+                            keys: Vec::new()
+                        };
+                        parse_track(p, &mut track) ?;
+                        tracks.push(track);
+                    }
+                    else if ident == Ident::new("Walk") {
+                        let mut track = Track::Walk {
+                            live_id: LiveId(tracks.len() as u64),
+                            ease: Ease::Lin,
+                            cut_init: None,
+                            keys: Vec::new()
+                        };
+                        parse_track(p, &mut track) ?;
+                        tracks.push(track);
+                    }
+                    else if ident == Ident::new("Margin") {
+                        let mut track = Track::Margin {
+                            live_id: LiveId(tracks.len() as u64),
+                            ease: Ease::Lin,
+                            cut_init: None,
+                            keys: Vec::new()
+                        };
+                        parse_track(p, &mut track) ?;
+                        tracks.push(track);
+                    }
+                    else if ident == Ident::new("Padding") {
+                        let mut track = Track::Padding {
+                            live_id: LiveId(tracks.len() as u64),
+                            ease: Ease::Lin,
+                            cut_init: None,
+                            keys: Vec::new()
+                        };
+                        parse_track(p, &mut track) ?;
+                        tracks.push(track);
+                    }
                     else {
                         return Err(span.error(p, format!("Unexpected track type {}", ident)));
                     }
@@ -41,7 +41,8 @@ pub struct LiveStyles {
     pub style_list: Vec<LiveStyle>,
     pub style_map: HashMap<LiveId, usize>,
     pub style_stack: Vec<usize>,
-    pub font_index: HashMap<Ident, Font>
+    pub font_index: HashMap<Ident, Font>,
+    pub font_fallbacks: HashMap<usize, Vec<Font>>
 }
 
 #[derive(Clone, Debug, Default)]
@@ -201,7 +202,39 @@ impl LiveStyles {
         }
         *self.base.layouts.get(&live_id).expect(&format!("Anim not found {}", name))
     }
-    
+
+    // reverse lookup of a layout for the layout debugger. widgets often override the walk
+    // of a live layout, so if nothing matches exactly we try again ignoring the walk.
+    // default looking layouts would match just about anything so those are left alone
+    pub fn find_layout_id(&self, layout: &Layout) -> Option<(LiveId, bool)> {
+        if *layout == Layout::default() {
+            return None
+        }
+        let match_walk_only = (Layout {walk: Walk::default(), ..*layout}) != Layout::default();
+        let styles = self.style_stack.iter().map( | index | &self.style_list[*index]).chain(std::iter::once(&self.base));
+        let mut walk_only = None;
+        for style in styles {
+            for (live_id, live_layout) in &style.layouts {
+                if live_layout == layout {
+                    return Some((*live_id, true))
+                }
+                if match_walk_only && walk_only.is_none() && (Layout {walk: layout.walk, ..*live_layout}) == *layout {
+                    walk_only = Some((*live_id, false));
+                }
+            }
+        }
+        walk_only
+    }
+
+    pub fn get_live_id_name(&self, live_id: LiveId) -> String {
+        if let Some(path) = self.collision_check.get(&live_id) {
+            path.to_string()
+        }
+        else {
+            format!("{:x}", live_id.0)
+        }
+    }
+
     pub fn get_or_insert_font_by_ident(&mut self, ident:Ident)->Font{
          if let Some(font) = self.font_index.get(&ident){
             return *font
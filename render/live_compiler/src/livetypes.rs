@@ -79,6 +79,7 @@ pub struct TextStyle {
     pub line_spacing: f32,
     pub top_drop: f32,
     pub height_factor: f32,
+    pub sdf: bool,
 }
 
 impl Default for TextStyle {
@@ -91,6 +92,7 @@ impl Default for TextStyle {
             line_spacing: 1.4,
             top_drop: 1.1,
             height_factor: 1.3,
+            sdf: false,
         }
     }
 }
@@ -107,10 +109,10 @@ impl DeTokSplat for TextStyle {
             return Err(p.error(format!("Textstyle {} not found in splat", ident_path)));
         }
     }
-    
+
 }
 
-#[derive(Copy, Clone, Debug, DeTok)]
+#[derive(Copy, Clone, Debug, PartialEq, DeTok)]
 pub enum LineWrap {
     None,
     NewLine,
@@ -122,7 +124,7 @@ impl Default for LineWrap {
     }
 }
 
-#[derive(Copy, Clone, Default, Debug, DeTokSplat, DeTok)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, DeTokSplat, DeTok)]
 pub struct Layout {
     pub padding: Padding,
     pub align: Align,
@@ -131,14 +133,19 @@ pub struct Layout {
     pub new_line_padding: f32,
     pub abs_origin: Option<Vec2>,
     pub abs_size: Option<Vec2>,
+    pub flex: Option<Flex>,
+    pub grid: Option<Grid>,
     pub walk: Walk,
 }
 
-#[derive(Copy, Clone, Default, Debug, DeTokSplat, DeTok)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, DeTokSplat, DeTok)]
 pub struct Walk {
     pub margin: Margin,
     pub width: Width,
     pub height: Height,
+    pub grow: f32,
+    pub shrink: f32,
+    pub cell: Option<GridCell>,
 }
 
 impl Walk {
@@ -147,7 +154,143 @@ impl Walk {
             width: w,
             height: h,
             margin: Margin::zero(),
+            grow: 0.,
+            shrink: 0.,
+            cell: None,
+        }
+    }
+    
+    pub fn lerp(&self, other: &Walk, f: f32) -> Walk {
+        Walk {
+            margin: self.margin.lerp(&other.margin, f),
+            width: self.width.lerp(&other.width, f),
+            height: self.height.lerp(&other.height, f),
+            grow: lerp_f32(self.grow, other.grow, f),
+            shrink: lerp_f32(self.shrink, other.shrink, f),
+            cell: if f < 0.5 {self.cell} else {other.cell},
+        }
+    }
+}
+
+// a turtle with flex set measures its children as they walk and arranges them in end_turtle
+#[derive(Copy, Clone, Default, Debug, PartialEq, DeTokSplat, DeTok)]
+pub struct Flex {
+    pub gap: f32,
+    pub justify: Justify,
+    pub cross_align: CrossAlign,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, DeTok)]
+pub enum Justify {
+    Start,
+    End,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly
+}
+
+impl Default for Justify {
+    fn default() -> Self {
+        Justify::Start
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, DeTok)]
+pub enum CrossAlign {
+    Start,
+    End,
+    Center,
+    Stretch
+}
+
+impl Default for CrossAlign {
+    fn default() -> Self {
+        CrossAlign::Start
+    }
+}
+
+// a turtle with grid set places its children in cells, measured the same way as flex
+#[derive(Copy, Clone, Default, Debug, PartialEq, DeTokSplat, DeTok)]
+pub struct Grid {
+    pub columns: GridTracks,
+    pub rows: GridTracks,
+    pub column_gap: f32,
+    pub row_gap: f32,
+    pub cell_align: Align,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, DeTok)]
+pub enum GridTrack {
+    Fix(f32),
+    Fr(f32),
+    Compute
+}
+
+impl Default for GridTrack {
+    fn default() -> Self {
+        GridTrack::Compute
+    }
+}
+
+pub const MAX_GRID_TRACKS: usize = 16;
+
+// fixed capacity so Layout stays Copy, in live its written as [Fix(100.), Fr(1.), Compute]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct GridTracks {
+    pub len: usize,
+    pub tracks: [GridTrack; MAX_GRID_TRACKS]
+}
+
+impl GridTracks {
+    pub fn new(tracks: &[GridTrack]) -> Self {
+        let mut ret = Self::default();
+        for track in tracks.iter().take(MAX_GRID_TRACKS) {
+            ret.tracks[ret.len] = *track;
+            ret.len += 1;
         }
+        ret
+    }
+    
+    pub fn as_slice(&self) -> &[GridTrack] {
+        &self.tracks[0..self.len]
+    }
+}
+
+impl DeTok for GridTracks {
+    fn de_tok(p: &mut dyn DeTokParser) -> Result<Self,
+    LiveError> {
+        let mut ret = Self::default();
+        p.expect_token(Token::LeftBracket) ?;
+        while !p.accept_token(Token::RightBracket) {
+            if ret.len == MAX_GRID_TRACKS {
+                return Err(p.error(format!("Grid has more than {} tracks", MAX_GRID_TRACKS)));
+            }
+            ret.tracks[ret.len] = GridTrack::de_tok(p) ?;
+            ret.len += 1;
+            p.accept_token(Token::Comma);
+        }
+        Ok(ret)
+    }
+}
+
+// where a child goes in a grid, children without one fill the next free cell
+#[derive(Copy, Clone, Default, Debug, PartialEq, DeTokSplat, DeTok)]
+pub struct GridCell {
+    pub column: usize,
+    pub row: usize,
+    pub column_span: usize,
+    pub row_span: usize,
+    pub align: Option<Align>,
+}
+
+impl GridCell {
+    pub fn at(column: usize, row: usize) -> Self {
+        Self {column, row, column_span: 1, row_span: 1, align: None}
+    }
+    
+    pub fn span(column: usize, row: usize, column_span: usize, row_span: usize) -> Self {
+        Self {column, row, column_span, row_span, align: None}
     }
 }
 
@@ -160,7 +303,7 @@ impl Layout {
     }
 }
 
-#[derive(Clone, Copy, Default, Debug, DeTokSplat, DeTok)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, DeTokSplat, DeTok)]
 pub struct Align {
     pub fx: f32,
     pub fy: f32
@@ -178,7 +321,7 @@ impl Align {
     pub fn right_bottom() -> Align {Align {fx: 1.0, fy: 1.0}}
 }
 
-#[derive(Clone, Copy, Default, Debug, DeTokSplat, DeTok)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, DeTokSplat, DeTok)]
 pub struct Margin {
     pub l: f32,
     pub t: f32,
@@ -211,9 +354,18 @@ impl Margin {
         Margin {l: 0.0, t: 0.0, r: 0.0, b: v}
     }
     
+    pub fn lerp(&self, other: &Margin, f: f32) -> Margin {
+        Margin {
+            l: lerp_f32(self.l, other.l, f),
+            t: lerp_f32(self.t, other.t, f),
+            r: lerp_f32(self.r, other.r, f),
+            b: lerp_f32(self.b, other.b, f)
+        }
+    }
+
 }
 
-#[derive(Clone, Copy, Default, Debug, DeTokSplat, DeTok)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, DeTokSplat, DeTok)]
 pub struct Padding {
     pub l: f32,
     pub t: f32,
@@ -228,10 +380,23 @@ impl Padding {
     pub fn all(v: f32) -> Padding {
         Padding {l: v, t: v, r: v, b: v}
     }
+    
+    pub fn lerp(&self, other: &Padding, f: f32) -> Padding {
+        Padding {
+            l: lerp_f32(self.l, other.l, f),
+            t: lerp_f32(self.t, other.t, f),
+            r: lerp_f32(self.r, other.r, f),
+            b: lerp_f32(self.b, other.b, f)
+        }
+    }
+}
+
+fn lerp_f32(a: f32, b: f32, f: f32) -> f32 {
+    a * (1.0 - f) + b * f
 }
 
 
-#[derive(Copy, Clone, Debug, DeTok)]
+#[derive(Copy, Clone, Debug, PartialEq, DeTok)]
 pub enum Direction {
     Left,
     Right,
@@ -258,7 +423,7 @@ impl Default for Axis {
 }
 
 
-#[derive(Copy, Clone, Debug, DeTok)]
+#[derive(Copy, Clone, Debug, PartialEq, DeTok)]
 pub enum Width {
     Fill,
     Fix(f32),
@@ -271,7 +436,7 @@ pub enum Width {
     ScalePad(f32, f32),
 }
 
-#[derive(Copy, Clone, Debug, DeTok)]
+#[derive(Copy, Clone, Debug, PartialEq, DeTok)]
 pub enum Height {
     Fill,
     Fix(f32),
@@ -306,6 +471,25 @@ impl Width {
         }
     }
     
+    pub fn is_fill(&self) -> bool {
+        match self {
+            Width::Fill | Width::FillPad(_) | Width::FillScale(_) | Width::FillScalePad(_, _) | Width::ComputeFill => true,
+            _ => false
+        }
+    }
+    
+    // sizes of the same kind interpolate, a different kind can only switch halfway
+    pub fn lerp(&self, other: &Width, f: f32) -> Width {
+        match (self, other) {
+            (Width::Fix(a), Width::Fix(b)) => Width::Fix(lerp_f32(*a, *b, f)),
+            (Width::FillPad(a), Width::FillPad(b)) => Width::FillPad(lerp_f32(*a, *b, f)),
+            (Width::FillScale(a), Width::FillScale(b)) => Width::FillScale(lerp_f32(*a, *b, f)),
+            (Width::Scale(a), Width::Scale(b)) => Width::Scale(lerp_f32(*a, *b, f)),
+            (Width::FillScalePad(a, c), Width::FillScalePad(b, d)) => Width::FillScalePad(lerp_f32(*a, *b, f), lerp_f32(*c, *d, f)),
+            (Width::ScalePad(a, c), Width::ScalePad(b, d)) => Width::ScalePad(lerp_f32(*a, *b, f), lerp_f32(*c, *d, f)),
+            _ => if f < 0.5 {*self} else {*other}
+        }
+    }
 }
 
 impl Height {
@@ -315,6 +499,25 @@ impl Height {
             _ => 0.
         }
     }
+    
+    pub fn is_fill(&self) -> bool {
+        match self {
+            Height::Fill | Height::FillPad(_) | Height::FillScale(_) | Height::FillScalePad(_, _) | Height::ComputeFill => true,
+            _ => false
+        }
+    }
+    
+    pub fn lerp(&self, other: &Height, f: f32) -> Height {
+        match (self, other) {
+            (Height::Fix(a), Height::Fix(b)) => Height::Fix(lerp_f32(*a, *b, f)),
+            (Height::FillPad(a), Height::FillPad(b)) => Height::FillPad(lerp_f32(*a, *b, f)),
+            (Height::FillScale(a), Height::FillScale(b)) => Height::FillScale(lerp_f32(*a, *b, f)),
+            (Height::Scale(a), Height::Scale(b)) => Height::Scale(lerp_f32(*a, *b, f)),
+            (Height::FillScalePad(a, c), Height::FillScalePad(b, d)) => Height::FillScalePad(lerp_f32(*a, *b, f), lerp_f32(*c, *d, f)),
+            (Height::ScalePad(a, c), Height::ScalePad(b, d)) => Height::ScalePad(lerp_f32(*a, *b, f), lerp_f32(*c, *d, f)),
+            _ => if f < 0.5 {*self} else {*other}
+        }
+    }
 }
 
 
@@ -642,7 +845,7 @@ impl Ease {
                 if time > 1. {
                     return 1.;
                 }
-
+                
                 let it = time * (1. / (1. - dampen)) + 0.5;
                 let inlog = (dampen - 1.) * it + 1.0;
                 if inlog <= 0. {
@@ -773,6 +976,80 @@ pub enum Track {
         cut_init: Option<Color>,
         keys: Vec<(f64, Color)>
     },
+    Spring {
+        live_id: LiveId,
+        spring: Spring,
+        target: f32
+    },
+    // discrete values dont interpolate, they step to a key when its time comes
+    Int {
+        live_id: LiveId,
+        keys: Vec<(f64, i64)>
+    },
+    Bool {
+        live_id: LiveId,
+        keys: Vec<(f64, bool)>
+    },
+    Walk {
+        live_id: LiveId,
+        ease: Ease,
+        cut_init: Option<Walk>,
+        keys: Vec<(f64, Walk)>
+    },
+    Margin {
+        live_id: LiveId,
+        ease: Ease,
+        cut_init: Option<Margin>,
+        keys: Vec<(f64, Margin)>
+    },
+    Padding {
+        live_id: LiveId,
+        ease: Ease,
+        cut_init: Option<Padding>,
+        keys: Vec<(f64, Padding)>
+    },
+}
+
+// a damped spring that pulls a float towards a target. it has no duration, it runs until it settles
+// and the velocity lives in the animator so playing a new target mid flight doesnt jerk
+#[derive(Clone, Copy, Debug, PartialEq, DeTokSplat, DeTok)]
+pub struct Spring {
+    pub stiffness: f32,
+    pub damping: f32,
+    pub mass: f32,
+    pub precision: f32,
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Spring {
+            stiffness: 170.,
+            damping: 26.,
+            mass: 1.,
+            precision: 0.01
+        }
+    }
+}
+
+impl Spring {
+    // semi implicit euler in small fixed steps, stiff springs blow up with one big step
+    pub fn step(&self, value: f32, velocity: f32, target: f32, dt: f64) -> (f32, f32) {
+        let steps = (dt * 240.).ceil().max(1.) as usize;
+        let h = (dt / steps as f64) as f32;
+        let mass = self.mass.max(0.001);
+        let mut value = value;
+        let mut velocity = velocity;
+        for _ in 0..steps {
+            let force = -self.stiffness * (value - target) - self.damping * velocity;
+            velocity += force / mass * h;
+            value += velocity * h;
+        }
+        (value, velocity)
+    }
+    
+    pub fn is_settled(&self, value: f32, velocity: f32, target: f32) -> bool {
+        (value - target).abs() < self.precision && velocity.abs() < self.precision
+    }
 }
 
 impl Track {
@@ -916,6 +1193,50 @@ impl Track {
         return lerp(*val1, val2.1, f)
     }
     
+    // a step track holds on to a key until the next one, before the first key it keeps what it had
+    pub fn compute_track_step<T: Clone>(time: f64, track: &Vec<(f64, T)>, init: T) -> T {
+        for i in (0..track.len()).rev() {
+            if time >= track[i].0 {
+                return track[i].1.clone()
+            }
+        }
+        init
+    }
+    
+    fn compute_track_lerp<T: Copy>(time: f64, track: &Vec<(f64, T)>, cut_init: &mut Option<T>, init: T, ease: &Ease, lerp: fn(&T, &T, f32) -> T) -> T {
+        if track.is_empty() {return init}
+        for i in 0..track.len() {
+            if time >= track[i].0 {
+                let val1 = &track[i];
+                if i == track.len() - 1 {
+                    return val1.1
+                }
+                let val2 = &track[i + 1];
+                let f = ease.map((time - val1.0) / (val2.0 - val1.0)) as f32;
+                return lerp(&val1.1, &val2.1, f);
+            }
+        }
+        if cut_init.is_none() {
+            *cut_init = Some(init);
+        }
+        let val2 = &track[0];
+        let val1 = cut_init.as_ref().unwrap();
+        let f = ease.map(time / val2.0) as f32;
+        return lerp(val1, &val2.1, f)
+    }
+    
+    pub fn compute_track_walk(time: f64, track: &Vec<(f64, Walk)>, cut_init: &mut Option<Walk>, init: Walk, ease: &Ease) -> Walk {
+        Self::compute_track_lerp(time, track, cut_init, init, ease, Walk::lerp)
+    }
+    
+    pub fn compute_track_margin(time: f64, track: &Vec<(f64, Margin)>, cut_init: &mut Option<Margin>, init: Margin, ease: &Ease) -> Margin {
+        Self::compute_track_lerp(time, track, cut_init, init, ease, Margin::lerp)
+    }
+    
+    pub fn compute_track_padding(time: f64, track: &Vec<(f64, Padding)>, cut_init: &mut Option<Padding>, init: Padding, ease: &Ease) -> Padding {
+        Self::compute_track_lerp(time, track, cut_init, init, ease, Padding::lerp)
+    }
+    
     pub fn live_id(&self) -> LiveId {
         match self {
             Track::Float{live_id,..} => {
@@ -933,6 +1254,24 @@ impl Track {
             Track::Color{live_id,..} => {
                 *live_id
             }
+            Track::Spring{live_id,..} => {
+                *live_id
+            }
+            Track::Int{live_id,..} => {
+                *live_id
+            }
+            Track::Bool{live_id,..} => {
+                *live_id
+            }
+            Track::Walk{live_id,..} => {
+                *live_id
+            }
+            Track::Margin{live_id,..} => {
+                *live_id
+            }
+            Track::Padding{live_id,..} => {
+                *live_id
+            }
         }
     }
     
@@ -953,6 +1292,16 @@ impl Track {
             Track::Float{cut_init,..} => {
                 *cut_init = None;
             }
+            Track::Walk{cut_init,..} => {
+                *cut_init = None;
+            }
+            Track::Margin{cut_init,..} => {
+                *cut_init = None;
+            }
+            Track::Padding{cut_init,..} => {
+                *cut_init = None;
+            }
+            Track::Spring{..} | Track::Int{..} | Track::Bool{..} => ()
         }
     }
     
@@ -973,10 +1322,39 @@ impl Track {
             Track::Color{ease, ..} => {
                 ease
             }
+            Track::Walk{ease, ..} => {
+                ease
+            }
+            Track::Margin{ease, ..} => {
+                ease
+            }
+            Track::Padding{ease, ..} => {
+                ease
+            }
+            Track::Spring{..} | Track::Int{..} | Track::Bool{..} => {
+                &Ease::Lin
+            }
         }
     }
     
     
+    // where the keys sit in normalized anim time, a spring has none
+    pub fn key_times(&self) -> Vec<f64> {
+        match self {
+            Track::Float{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Vec2{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Vec3{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Vec4{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Color{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Int{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Bool{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Walk{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Margin{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Padding{keys, ..} => keys.iter().map( | (t, _) | *t).collect(),
+            Track::Spring{..} => Vec::new()
+        }
+    }
+    
     pub fn set_ease(&mut self, new_ease: Ease) {
         match self {
             Track::Float{ease, ..} => {
@@ -994,6 +1372,16 @@ impl Track {
             Track::Color{ease, ..} => {
                 *ease = new_ease
             },
+            Track::Walk{ease, ..} => {
+                *ease = new_ease
+            },
+            Track::Margin{ease, ..} => {
+                *ease = new_ease
+            },
+            Track::Padding{ease, ..} => {
+                *ease = new_ease
+            },
+            Track::Spring{..} | Track::Int{..} | Track::Bool{..} => ()
         }
     }
     
@@ -1014,6 +1402,24 @@ impl Track {
             Track::Color{live_id, ..} => {
                 *live_id = new_live_id
             },
+            Track::Spring{live_id, ..} => {
+                *live_id = new_live_id
+            },
+            Track::Int{live_id, ..} => {
+                *live_id = new_live_id
+            },
+            Track::Bool{live_id, ..} => {
+                *live_id = new_live_id
+            },
+            Track::Walk{live_id, ..} => {
+                *live_id = new_live_id
+            },
+            Track::Margin{live_id, ..} => {
+                *live_id = new_live_id
+            },
+            Track::Padding{live_id, ..} => {
+                *live_id = new_live_id
+            },
         }
     }
 }
@@ -1025,6 +1431,10 @@ impl Anim {
             tracks: vec![]
         }
     }
+    
+    pub fn has_springs(&self) -> bool {
+        self.tracks.iter().any( | track | if let Track::Spring {..} = track {true} else {false})
+    }
 }
 
 #[derive(Clone, DeTok, Debug)]
@@ -6,6 +6,7 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
     
+    parser.eat_attributes();
     parser.eat_ident("pub");
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
@@ -140,6 +141,8 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
     
+    // unknown fields are an error, unless the struct opts in with #[microserde(skip_unknown)]
+    let skip_unknown = parser.eat_attributes().iter().any( | (name, args) | name == "microserde" && args == "skip_unknown");
     parser.eat_ident("pub");
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
@@ -174,7 +177,12 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
                     tb.string(&field).add("=> { s . next_colon ( i ) ? ;");
                     tb.ident(&format!("_{}",field)).add("= Some ( DeJson :: de_json ( s , i ) ? ) ; } ,");
                 }
-                tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+                if skip_unknown{
+                    tb.add("_ => { s . next_colon ( i ) ? ; s . whole_field ( i ) ? ; }");
+                }
+                else{
+                    tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+                }
                 tb.add("} ; s . eat_comma_curly ( i ) ? ;");
                 tb.add("} ; s . curly_close ( i ) ? ;");
                 
@@ -243,7 +251,12 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
                             tb.string(&field).add("=> { s . next_colon ( i ) ? ;");
                             tb.ident(&format!("_{}",field)).add("= Some ( DeJson :: de_json ( s , i ) ? ) ; } ,");
                         }
-                        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+                        if skip_unknown{
+                            tb.add("_ => { s . next_colon ( i ) ? ; s . whole_field ( i ) ? ; }");
+                        }
+                        else{
+                            tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+                        }
                         tb.add("} s . eat_comma_curly ( i ) ? ;");
                         tb.add("} s . curly_close ( i ) ? ;");
                         
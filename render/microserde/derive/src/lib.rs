@@ -22,12 +22,12 @@ pub fn derive_de_bin(input: TokenStream) -> TokenStream {
     derive_de_bin_impl(input)
 }
 
-#[proc_macro_derive(SerJson)]
+#[proc_macro_derive(SerJson, attributes(microserde))]
 pub fn derive_ser_json(input: TokenStream) -> TokenStream {
     derive_ser_json_impl(input)
 }
 
-#[proc_macro_derive(DeJson)]
+#[proc_macro_derive(DeJson, attributes(microserde))]
 pub fn derive_de_json(input: TokenStream) -> TokenStream {
     derive_de_json_impl(input)
 }
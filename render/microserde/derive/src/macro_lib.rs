@@ -379,6 +379,24 @@ impl TokenParser {
         return None
     }
     
+    pub fn eat_attributes(&mut self) -> Vec<(String, String)> {
+        // the #[name(args)] in front of the item, args as a plain string
+        let mut attrs = Vec::new();
+        while self.eat_punct('#') {
+            if !self.open_bracket() {
+                break
+            }
+            if let Some(name) = self.eat_any_ident() {
+                let args = if let Some(TokenTree::Group(group)) = &self.current {group.stream().to_string()} else {String::new()};
+                attrs.push((name, args));
+            }
+            while !self.eat_eot() {
+                self.advance();
+            }
+        }
+        attrs
+    }
+    
     pub fn eat_struct_field(&mut self) -> Option<(String, TokenStream)> {
         // letsparse an ident
         self.eat_ident("pub");
@@ -36,6 +36,11 @@ impl SerJsonState {
     }
     
     pub fn st_post(&mut self, d: usize) {
+        // every field is followed by a comma, drop the last one. {"a":1,} is not json, and
+        // other readers than ours (like language servers) reject it
+        if self.out.ends_with(','){
+            self.out.pop();
+        }
         self.indent(d);
         self.out.push('}');
     }
@@ -223,6 +228,28 @@ impl DeJsonState {
         Ok(())
     }
     
+    pub fn whole_field(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
+        // skips over a value we have no field for, nested or not
+        let mut depth = 0;
+        loop {
+            match self.tok {
+                DeJsonTok::CurlyOpen | DeJsonTok::BlockOpen => depth += 1,
+                DeJsonTok::CurlyClose | DeJsonTok::BlockClose => {
+                    if depth == 0 {
+                        return Err(self.err_token("value"))
+                    }
+                    depth -= 1;
+                },
+                DeJsonTok::Eof => return Err(self.err_token("value")),
+                _ => ()
+            }
+            self.next_tok(i) ?;
+            if depth == 0 {
+                return Ok(())
+            }
+        }
+    }
+    
     pub fn next_str(&mut self) -> Option<()> {
         if let DeJsonTok::Str = &mut self.tok {
             //let mut s = String::new();
@@ -323,6 +350,21 @@ impl DeJsonState {
         Err(self.err_token("string"))
     }
     
+    fn hex_u16(&mut self, i: &mut Chars) -> Result<u16, DeJsonErr> {
+        // reads the 4 hex digits of a \u escape, leaves cur on the last one
+        let mut value = 0u16;
+        for _ in 0..4 {
+            self.next(i);
+            if let Some(digit) = self.cur.to_digit(16) {
+                value = (value << 4) | digit as u16;
+            }
+            else {
+                return Err(self.err_parse("string"));
+            }
+        }
+        Ok(value)
+    }
+    
     pub fn next_tok(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
         while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' {
             self.next(i);
@@ -376,12 +418,26 @@ impl DeJsonState {
                     self.numbuf.push(self.cur);
                     self.next(i);
                 }
-                if self.cur == '.' {
-                    self.numbuf.push(self.cur);
-                    self.next(i);
-                    while self.cur >= '0' && self.cur <= '9' {
+                if self.cur == '.' || self.cur == 'e' || self.cur == 'E' {
+                    if self.cur == '.' {
+                        self.numbuf.push(self.cur);
+                        self.next(i);
+                        while self.cur >= '0' && self.cur <= '9' {
+                            self.numbuf.push(self.cur);
+                            self.next(i);
+                        }
+                    }
+                    if self.cur == 'e' || self.cur == 'E' {
                         self.numbuf.push(self.cur);
                         self.next(i);
+                        if self.cur == '-' || self.cur == '+' {
+                            self.numbuf.push(self.cur);
+                            self.next(i);
+                        }
+                        while self.cur >= '0' && self.cur <= '9' {
+                            self.numbuf.push(self.cur);
+                            self.next(i);
+                        }
                     }
                     if let Ok(num) = self.numbuf.parse() {
                         self.tok = DeJsonTok::F64(num);
@@ -444,6 +500,24 @@ impl DeJsonState {
                             'r'=>self.strbuf.push('\r'),
                             't'=>self.strbuf.push('\t'),
                             '0'=>self.strbuf.push('\0'),
+                            'b'=>self.strbuf.push('\u{8}'),
+                            'f'=>self.strbuf.push('\u{c}'),
+                            'u'=>{
+                                let hi = self.hex_u16(i) ?;
+                                let code = if hi >= 0xd800 && hi < 0xdc00 {
+                                    // surrogate pair, the low half follows as another escape
+                                    self.next(i);
+                                    if self.cur != '\\' {return Err(self.err_parse("string"));}
+                                    self.next(i);
+                                    if self.cur != 'u' {return Err(self.err_parse("string"));}
+                                    let lo = self.hex_u16(i) ?;
+                                    0x10000 + (((hi as u32) - 0xd800) << 10) + ((lo as u32) & 0x3ff)
+                                }
+                                else {
+                                    hi as u32
+                                };
+                                self.strbuf.push(std::char::from_u32(code).unwrap_or('\u{fffd}'));
+                            },
                             '\0'=>{
                                 return Err(self.err_parse("string"));
                             },
@@ -547,7 +621,7 @@ impl<T> SerJson for Option<T> where T: SerJson {
             v.ser_json(d, s);
         }
         else {
-            s.out.push_str("None");
+            s.out.push_str("null");
         }
     }
 }
@@ -590,7 +664,7 @@ impl SerJson for String {
                 '\n'=>{s.out.push('\\');s.out.push('n');},
                 '\r'=>{s.out.push('\\');s.out.push('r');},
                 '\t'=>{s.out.push('\\');s.out.push('t');},
-                '\0'=>{s.out.push('\\');s.out.push('0');},
+                '\0'=>{s.out.push_str("\\u0000");},
                 '\\'=>{s.out.push('\\');s.out.push('\\');},
                 '"'=>{s.out.push('\\');s.out.push('"');},
                 _=>s.out.push(c)
@@ -0,0 +1,59 @@
+use makepad_microserde::*;
+
+#[derive(SerJson, DeJson, Debug, PartialEq)]
+struct Point {
+    x: u32,
+    y: f64,
+    name: Option<String>,
+}
+
+#[derive(DeJson, Debug, PartialEq)]
+#[microserde(skip_unknown)]
+struct Lenient {
+    x: u32,
+}
+
+#[test]
+fn structs_have_no_trailing_comma() {
+    let point = Point {x: 1, y: 2.5, name: Some("a".to_string())};
+    assert_eq!(point.serialize_json(), "{\"x\":1,\"y\":2.5,\"name\":\"a\"}");
+    let point = Point {x: 1, y: 2.5, name: None};
+    assert_eq!(point.serialize_json(), "{\"x\":1,\"y\":2.5}");
+    assert_eq!(Point::deserialize_json(&point.serialize_json()).unwrap(), point);
+    // we still read our own old output
+    assert_eq!(Point::deserialize_json("{\"x\":1,\"y\":2.5,}").unwrap(), point);
+}
+
+#[test]
+fn none_is_null() {
+    let values: Vec<Option<u32>> = vec![Some(1), None];
+    assert_eq!(values.serialize_json(), "[1,null]");
+}
+
+#[test]
+fn unknown_fields_are_skipped_only_when_asked() {
+    let input = "{\"extra\":{\"a\":[1,{\"b\":[]}],\"c\":\"}\"},\"x\":3,\"more\":[true,null]}";
+    assert_eq!(Lenient::deserialize_json(input).unwrap(), Lenient {x: 3});
+    assert!(Point::deserialize_json("{\"x\":1,\"y\":2.5,\"extra\":1}").is_err());
+    assert!(Lenient::deserialize_json("{\"x\":1,\"extra\":").is_err());
+}
+
+#[test]
+fn unicode_escapes_are_decoded() {
+    assert_eq!(String::deserialize_json("\"a\\u00e9\\u4e2d\"").unwrap(), "a\u{e9}\u{4e2d}");
+    // a surrogate pair is one char
+    assert_eq!(String::deserialize_json("\"\\ud83d\\ude00\"").unwrap(), "\u{1f600}");
+    assert_eq!(String::deserialize_json("\"\\b\\f\\n\"").unwrap(), "\u{8}\u{c}\n");
+    assert!(String::deserialize_json("\"\\u12g4\"").is_err());
+    assert!(String::deserialize_json("\"\\ud83dx\"").is_err());
+    assert_eq!("\0".to_string().serialize_json(), "\"\\u0000\"");
+    assert_eq!(String::deserialize_json(&"\0".to_string().serialize_json()).unwrap(), "\0");
+}
+
+#[test]
+fn exponent_numbers_are_read() {
+    assert_eq!(f64::deserialize_json("1e3").unwrap(), 1000.0);
+    assert_eq!(f64::deserialize_json("-2.5E-2").unwrap(), -0.025);
+    assert_eq!(f64::deserialize_json("1.5e+2").unwrap(), 150.0);
+    assert_eq!(Vec::<f64>::deserialize_json("[1e2,2]").unwrap(), vec![100.0, 2.0]);
+}
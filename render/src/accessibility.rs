@@ -0,0 +1,326 @@
+use crate::cx::*;
+use std::collections::HashMap;
+use std::cmp::Ordering;
+
+// widgets describe what they are to assistive technology while they draw. like the layout debug
+// turtles, nodes belong to the view they were drawn in so views that dont redraw keep theirs.
+// the platform turns this into whatever the screen readers on that platform speak
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccessRole {
+    Window,
+    Panel,
+    Button,
+    Tab,
+    TabList,
+    Tree,
+    TreeItem,
+    Text,
+    Label,
+    List,
+    ListItem,
+    Menu,
+    MenuItem,
+    Slider,
+    SplitPane,
+    ScrollPane,
+}
+
+impl Default for AccessRole {
+    fn default() -> Self {
+        AccessRole::Panel
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccessAction {
+    Click,
+    Focus,
+    Expand,
+    Collapse,
+}
+
+impl AccessAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccessAction::Click => "click",
+            AccessAction::Focus => "focus",
+            AccessAction::Expand => "expand",
+            AccessAction::Collapse => "collapse",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccessNode {
+    pub role: AccessRole,
+    pub label: String,
+    pub value: Option<String>,
+    pub cursor: Option<usize>,
+    pub actions: Vec<AccessAction>,
+    pub selected: bool,
+    pub expanded: Option<bool>,
+    pub editable: bool,
+    pub area: Area,
+}
+
+impl AccessNode {
+    pub fn new(role: AccessRole, label: &str) -> Self {
+        Self {
+            role: role,
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CxAccessNode {
+    pub key: u64,
+    pub parent_key: u64,
+    pub window_id: usize,
+    pub view_id: usize,
+    pub node: AccessNode,
+}
+
+#[derive(Clone, Default)]
+pub struct CxAccess {
+    pub enabled: bool,
+    pub changed: bool,
+    pub nodes: Vec<CxAccessNode>,
+    pub stack: Vec<u64>,
+    pub ordinals: HashMap<(u64, usize), usize>,
+}
+
+// the tree of one window as it is on screen right now, node 0 is the window itself
+#[derive(Clone, Debug, Default)]
+pub struct AccessTree {
+    pub window_id: usize,
+    pub position: Vec2,
+    pub dpi_factor: f32,
+    pub nodes: Vec<AccessTreeNode>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AccessTreeNode {
+    pub key: u64,
+    pub node: AccessNode,
+    pub rect: Rect,
+    pub focused: bool,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessActionEvent {
+    pub area: Area,
+    pub action: AccessAction,
+}
+
+impl AccessTree {
+    pub fn find_key(&self, key: u64) -> Option<usize> {
+        self.nodes.iter().position( | node | node.key == key)
+    }
+    
+    pub fn focused(&self) -> Option<usize> {
+        self.nodes.iter().position( | node | node.focused)
+    }
+    
+    // rects are in window coordinates, screen readers want pixels on the screen
+    pub fn screen_rect(&self, index: usize) -> Rect {
+        let r = self.nodes[index].rect;
+        let dpi = self.dpi_factor;
+        Rect {x: self.position.x + r.x * dpi, y: self.position.y + r.y * dpi, w: r.w * dpi, h: r.h * dpi}
+    }
+}
+
+// keys only depend on where a node sits in the tree, so they survive partial redraws
+pub(crate) fn access_key(parent_key: u64, view_id: usize, ordinal: usize) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for value in &[parent_key, view_id as u64, ordinal as u64] {
+        for byte in &value.to_le_bytes() {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+pub fn access_window_key(window_id: usize) -> u64 {
+    access_key(0, window_id, std::usize::MAX)
+}
+
+impl Cx {
+    pub fn enable_accessibility(&mut self) {
+        if self.access.enabled {
+            return
+        }
+        self.access.enabled = true;
+        self.redraw_child_area(Area::All);
+    }
+    
+    pub fn is_accessibility_enabled(&self) -> bool {
+        self.access.enabled
+    }
+    
+    pub(crate) fn access_begin_view(&mut self, view_id: usize) {
+        if !self.access.enabled {
+            return
+        }
+        // a pass root starts a fresh draw, forget nodes someone didnt end
+        if self.view_stack.len() <= 1 {
+            self.access.stack.truncate(0);
+        }
+        self.access.nodes.retain( | node | node.view_id != view_id);
+        self.access.ordinals.retain( | (_, ordinal_view_id), _ | *ordinal_view_id != view_id);
+        self.access.changed = true;
+    }
+    
+    // opens a node, everything registered until end_access_node becomes its children
+    pub fn begin_access_node(&mut self, node: AccessNode) {
+        if !self.access.enabled {
+            return
+        }
+        let window_id = *self.window_stack.last().unwrap_or(&0);
+        let view_id = *self.view_stack.last().unwrap_or(&0);
+        let parent_key = if let Some(key) = self.access.stack.last() {*key} else {access_window_key(window_id)};
+        let ordinal = self.access.ordinals.entry((parent_key, view_id)).or_insert(0);
+        let key = access_key(parent_key, view_id, *ordinal);
+        *ordinal += 1;
+        self.access.nodes.push(CxAccessNode {
+            key: key,
+            parent_key: parent_key,
+            window_id: window_id,
+            view_id: view_id,
+            node: node
+        });
+        self.access.stack.push(key);
+        self.access.changed = true;
+    }
+    
+    // the area usually only exists once the widget is drawn, so it can be passed in here
+    pub fn end_access_node(&mut self, area: Area) {
+        if !self.access.enabled {
+            return
+        }
+        if let Some(key) = self.access.stack.pop() {
+            if area.is_empty() {
+                return
+            }
+            if let Some(node) = self.access.nodes.iter_mut().rev().find( | node | node.key == key) {
+                node.node.area = area;
+            }
+        }
+    }
+    
+    pub fn add_access_node(&mut self, node: AccessNode) {
+        if !self.access.enabled {
+            return
+        }
+        let area = node.area;
+        self.begin_access_node(node);
+        self.end_access_node(area);
+    }
+    
+    // returns wether any node was added or removed since the last call
+    pub fn take_access_changed(&mut self) -> bool {
+        let changed = self.access.changed;
+        self.access.changed = false;
+        changed
+    }
+    
+    pub fn get_access_tree(&self, window_id: usize) -> AccessTree {
+        let window = &self.windows[window_id];
+        let mut tree = AccessTree {
+            window_id: window_id,
+            position: window.window_geom.position,
+            dpi_factor: window.window_geom.dpi_factor,
+            nodes: vec![AccessTreeNode {
+                key: access_window_key(window_id),
+                node: AccessNode::new(AccessRole::Window, &window.title),
+                rect: Rect {x: 0., y: 0., w: window.window_geom.inner_size.x, h: window.window_geom.inner_size.y},
+                ..Default::default()
+            }]
+        };
+        
+        let mut views = Vec::new();
+        if let Some(main_pass_id) = window.main_pass_id {
+            if let Some(main_view_id) = self.passes[main_pass_id].main_view_id {
+                self.collect_visible_views(main_view_id, &mut views);
+            }
+        }
+        let mut index_of_key = HashMap::new();
+        for node in &self.access.nodes {
+            if node.window_id != window_id || !views.contains(&node.view_id) {
+                continue;
+            }
+            let area = node.node.area;
+            index_of_key.insert(node.key, tree.nodes.len());
+            tree.nodes.push(AccessTreeNode {
+                key: node.key,
+                node: node.node.clone(),
+                rect: if area.is_valid(self) {area.get_rect(self)} else {Rect::default()},
+                focused: !area.is_empty() && area == self.key_focus,
+                ..Default::default()
+            });
+        }
+        
+        // nodes whose parent is not on screen hang off the window
+        for index in 1..tree.nodes.len() {
+            let key = tree.nodes[index].key;
+            let parent_key = self.access.nodes.iter().find( | node | node.key == key).map( | node | node.parent_key);
+            let parent = parent_key.and_then( | parent_key | index_of_key.get(&parent_key).cloned()).unwrap_or(0);
+            tree.nodes[index].parent = Some(parent);
+            tree.nodes[parent].children.push(index);
+        }
+        // containers that have no area of their own cover their children
+        fn fill_rect(tree: &mut AccessTree, index: usize) -> Rect {
+            let children = tree.nodes[index].children.clone();
+            let child_rects: Vec<Rect> = children.iter().map( | child | fill_rect(tree, *child)).collect();
+            let node = &mut tree.nodes[index];
+            if node.rect.w <= 0. && node.rect.h <= 0. && node.node.area.is_empty() {
+                let mut bounds: Option<(f32, f32, f32, f32)> = None;
+                for r in child_rects.iter().filter( | r | r.w > 0. || r.h > 0.) {
+                    bounds = Some(match bounds {
+                        None => (r.x, r.y, r.x + r.w, r.y + r.h),
+                        Some((x0, y0, x1, y1)) => (x0.min(r.x), y0.min(r.y), x1.max(r.x + r.w), y1.max(r.y + r.h))
+                    });
+                }
+                if let Some((x0, y0, x1, y1)) = bounds {
+                    node.rect = Rect {x: x0, y: y0, w: x1 - x0, h: y1 - y0};
+                }
+            }
+            node.rect
+        }
+        fill_rect(&mut tree, 0);
+        
+        // partial redraws shuffle the node list, reading order is what is on screen
+        for index in 0..tree.nodes.len() {
+            let mut children = std::mem::replace(&mut tree.nodes[index].children, Vec::new());
+            children.sort_by( | a, b | {
+                let (ra, rb) = (tree.nodes[*a].rect, tree.nodes[*b].rect);
+                ra.y.partial_cmp(&rb.y).unwrap_or(Ordering::Equal).then(ra.x.partial_cmp(&rb.x).unwrap_or(Ordering::Equal))
+            });
+            tree.nodes[index].children = children;
+        }
+        tree
+    }
+    
+    // runs an action a screen reader asked for on the widget that registered the node
+    pub fn call_access_action<F>(&mut self, mut event_handler: F, key: u64, action: AccessAction)
+    where F: FnMut(&mut Cx, &mut Event)
+    {
+        let area = if let Some(node) = self.access.nodes.iter().find( | node | node.key == key) {
+            node.node.area
+        }
+        else {
+            return
+        };
+        if area.is_empty() {
+            return
+        }
+        if action == AccessAction::Focus {
+            self.set_key_focus(area);
+        }
+        self.call_event_handler(&mut event_handler, &mut Event::AccessAction(AccessActionEvent {area: area, action: action}));
+    }
+}
@@ -4,7 +4,10 @@ use crate::cx::*;
 pub struct AnimArea {
     pub area: Area,
     pub start_time: f64,
-    pub total_time: f64
+    pub total_time: f64,
+    // what the animator is playing, kept here so the anim inspector can show it
+    pub anim: Anim,
+    pub next: Option<Anim>,
 }
 
 #[derive(Clone)]
@@ -14,6 +17,19 @@ pub enum AnimLastValue {
     Vec3(Vec3),
     Vec4(Vec4),
     Color(Color),
+    Int(i64),
+    Bool(bool),
+    Walk(Walk),
+    Margin(Margin),
+    Padding(Padding),
+}
+
+// a spring keeps its velocity between anims, last_time is nan while its at rest
+#[derive(Clone)]
+pub struct AnimSpring {
+    pub velocity: f32,
+    pub last_time: f64,
+    pub settled: bool,
 }
 
 #[derive(Default, Clone)]
@@ -23,6 +39,7 @@ pub struct Animator {
     pub area: Area,
     pub live_update_id: u64,
     pub last_values: Vec<(LiveId, AnimLastValue)>,
+    pub springs: Vec<(LiveId, AnimSpring)>,
 }
 
 impl Animator {
@@ -90,6 +107,26 @@ impl Animator {
                         self.last_values.push((live_id.clone(), AnimLastValue::Float(val)));
                     }
                 },
+                Track::Spring{target,..} => {
+                    // jumping to the end of a spring means its at rest
+                    Self::_set_last_float(live_id, *target, &mut self.last_values);
+                    self.springs.retain( | (name, _) | *name != live_id);
+                },
+                Track::Int{keys,..} => if let Some((_, val)) = keys.last() {
+                    Self::_set_last_int(live_id, *val, &mut self.last_values);
+                },
+                Track::Bool{keys,..} => if let Some((_, val)) = keys.last() {
+                    Self::_set_last_bool(live_id, *val, &mut self.last_values);
+                },
+                Track::Walk{keys,..} => if let Some((_, val)) = keys.last() {
+                    Self::_set_last_walk(live_id, *val, &mut self.last_values);
+                },
+                Track::Margin{keys,..} => if let Some((_, val)) = keys.last() {
+                    Self::_set_last_margin(live_id, *val, &mut self.last_values);
+                },
+                Track::Padding{keys,..} => if let Some((_, val)) = keys.last() {
+                    Self::_set_last_padding(live_id, *val, &mut self.last_values);
+                },
             }
         }
     }
@@ -126,29 +163,87 @@ impl Animator {
             self.current = Some(anim);
             return
         }
+        
+        // springs that get a new target start moving again, from where they are and as fast as they were going
+        for track in &anim.tracks {
+            if let Track::Spring{live_id, target, ..} = track {
+                let value = Self::_last_float(*live_id, &self.last_values);
+                if let Some((_, spring)) = self.springs.iter_mut().find( | (name, _) | name == live_id) {
+                    spring.settled = spring.settled && value == *target;
+                }
+            }
+        }
+        
         // alright first we find area, it already exists
         if let Some(anim_area) = cx.playing_anim_areas.iter_mut().find( | v | v.area == self.area) {
             //do we cut the animation in right now?
             if anim.play.cut() || self.current.is_none() {
+                anim_area.total_time = Self::play_total_time(&anim);
+                anim_area.anim = anim.clone();
+                anim_area.next = None;
                 self.current = Some(anim);
                 anim_area.start_time = std::f64::NAN;
                 self.next = None;
-                anim_area.total_time = self.current.as_ref().unwrap().play.total_time();
             }
             else { // queue it
-                self.next = Some(anim);
                 // lets ask an animation anim how long it is
-                anim_area.total_time = self.current.as_ref().unwrap().play.total_time() + self.next.as_ref().unwrap().play.total_time()
+                anim_area.total_time = self.current_total_time() + Self::play_total_time(&anim);
+                anim_area.next = Some(anim.clone());
+                self.next = Some(anim);
             }
         }
         else if self.area != Area::Empty { // its new
-            self.current = Some(anim);
-            self.next = None;
             cx.playing_anim_areas.push(AnimArea {
                 area: self.area.clone(),
                 start_time: std::f64::NAN,
-                total_time: self.current.as_ref().unwrap().play.total_time()
-            })
+                total_time: Self::play_total_time(&anim),
+                anim: anim.clone(),
+                next: None,
+            });
+            self.current = Some(anim);
+            self.next = None;
+        }
+    }
+    
+    // a spring has no duration, an anim with springs plays until they all settled
+    fn play_total_time(anim: &Anim) -> f64 {
+        if anim.has_springs() {
+            std::f64::INFINITY
+        }
+        else {
+            anim.play.total_time()
+        }
+    }
+    
+    fn current_total_time(&self) -> f64 {
+        let current = self.current.as_ref().unwrap();
+        let settled = current.tracks.iter().all( | track | {
+            if let Track::Spring{live_id, ..} = track {
+                self.springs.iter().find( | (name, _) | name == live_id).map_or(true, | (_, spring) | spring.settled)
+            }
+            else {
+                true
+            }
+        });
+        if settled {
+            current.play.total_time()
+        }
+        else {
+            std::f64::INFINITY
+        }
+    }
+    
+    // once the springs came to rest the area can stop animating like any other anim
+    fn update_spring_total_time(&mut self, cx: &mut Cx) {
+        if self.current.as_ref().map_or(true, | current | !current.has_springs()) {
+            return
+        }
+        let mut total_time = self.current_total_time();
+        if let Some(next) = &self.next {
+            total_time += Self::play_total_time(next);
+        }
+        if let Some(anim_area) = cx.playing_anim_areas.iter_mut().find( | v | v.area == self.area) {
+            anim_area.total_time = total_time;
         }
     }
     
@@ -177,7 +272,7 @@ impl Animator {
             return None
         }
         
-        let current_total_time = self.current.as_ref().unwrap().play.total_time();
+        let current_total_time = self.current_total_time();
         
         // process queueing
         if time - start_time >= current_total_time && !self.next.is_none() {
@@ -188,6 +283,8 @@ impl Animator {
             if let Some(anim) = cx.playing_anim_areas.iter_mut().find( | v | v.area == self.area) {
                 anim.start_time = start_time;
                 anim.total_time -= current_total_time;
+                anim.anim = self.current.clone().unwrap();
+                anim.next = None;
             }
             Some(self.current.as_ref().unwrap().play.compute_time(time - start_time))
         }
@@ -209,10 +306,18 @@ impl Animator {
     pub fn calc_float(&mut self, cx: &mut Cx, live_id: LiveId, time: f64) -> f32 {
         let last = Self::_last_float(live_id, &self.last_values);
         let mut ret = last;
+        let abs_time = time;
         if let Some(time) = self.update_anim_track(cx, time) {
             if let Some(track_index) = self.find_track_index(live_id) {
-                if let Track::Float{keys, cut_init, ease, ..} = &mut self.current.as_mut().unwrap().tracks[track_index] {
-                    ret = Track::compute_track_float(time, keys, cut_init, last, ease);
+                match &mut self.current.as_mut().unwrap().tracks[track_index] {
+                    Track::Float{keys, cut_init, ease, ..} => {
+                        ret = Track::compute_track_float(time, keys, cut_init, last, ease);
+                    },
+                    Track::Spring{spring, target, ..} => {
+                        ret = Self::_calc_spring(live_id, *spring, *target, abs_time, &self.last_values, &mut self.springs);
+                        self.update_spring_total_time(cx);
+                    },
+                    _ => ()
                 }
             }
         }
@@ -220,6 +325,47 @@ impl Animator {
         return ret
     }
     
+    pub fn _calc_spring(live_id: LiveId, spring: Spring, target: f32, time: f64, last_values: &Vec<(LiveId, AnimLastValue)>, springs: &mut Vec<(LiveId, AnimSpring)>) -> f32 {
+        let value = Self::_last_float(live_id, last_values);
+        let index = if let Some(index) = springs.iter().position( | (name, _) | *name == live_id) {index} else {
+            springs.push((live_id, AnimSpring {velocity: 0., last_time: std::f64::NAN, settled: false}));
+            springs.len() - 1
+        };
+        let state = &mut springs[index].1;
+        if state.last_time.is_nan() {
+            state.last_time = time;
+        }
+        // dont let a stalled frame fling it across the screen
+        let dt = (time - state.last_time).max(0.).min(0.1);
+        state.last_time = time;
+        let (value, velocity) = spring.step(value, state.velocity, target, dt);
+        if spring.is_settled(value, velocity, target) {
+            *state = AnimSpring {velocity: 0., last_time: std::f64::NAN, settled: true};
+            return target
+        }
+        state.velocity = velocity;
+        state.settled = false;
+        value
+    }
+    
+    pub fn last_velocity(&self, _cx: &Cx, live_id: LiveId) -> f32 {
+        if let Some((_, spring)) = self.springs.iter().find( | (name, _) | *name == live_id) {
+            return spring.velocity
+        }
+        0.
+    }
+    
+    // give a spring a push, like the speed a drag or a flick ended with
+    pub fn set_velocity(&mut self, live_id: LiveId, velocity: f32) {
+        if let Some((_, spring)) = self.springs.iter_mut().find( | (name, _) | *name == live_id) {
+            spring.velocity = velocity;
+            spring.settled = false;
+        }
+        else {
+            self.springs.push((live_id, AnimSpring {velocity: velocity, last_time: std::f64::NAN, settled: false}));
+        }
+    }
+    
     pub fn last_float(&self, _cx: &Cx, live_id: LiveId) -> f32 {
         Self::_last_float(live_id, &self.last_values)
     }
@@ -413,12 +559,257 @@ impl Animator {
         }
     }
     
+    pub fn calc_int(&mut self, cx: &mut Cx, live_id: LiveId, time: f64) -> i64 {
+        let last = Self::_last_int(live_id, &self.last_values);
+        let mut ret = last;
+        if let Some(time) = self.update_anim_track(cx, time) {
+            if let Some(track_index) = self.find_track_index(live_id) {
+                if let Track::Int{keys, ..} = &mut self.current.as_mut().unwrap().tracks[track_index] {
+                    ret = Track::compute_track_step(time, keys, last);
+                }
+            }
+        }
+        if self.set_last_int(live_id, ret) {
+            cx.redraw_child_area(self.area);
+        }
+        return ret
+    }
+    
+    pub fn last_int(&self, _cx: &Cx, live_id: LiveId) -> i64 {
+        Self::_last_int(live_id, &self.last_values)
+    }
+    
+    pub fn _last_int(live_id: LiveId, last_values: &Vec<(LiveId, AnimLastValue)>) -> i64 {
+        if let Some((_, value)) = last_values.iter().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Int(value) = value {
+                return *value
+            }
+        }
+        return 0
+    }
+    
+    pub fn set_last_int(&mut self, live_id: LiveId, value: i64) -> bool {
+        Self::_set_last_int(live_id, value, &mut self.last_values)
+    }
+    
+    // returns if the value changed, so the caller knows to relayout
+    pub fn _set_last_int(live_id: LiveId, value: i64, last_values: &mut Vec<(LiveId, AnimLastValue)>) -> bool {
+        if let Some((_, last)) = last_values.iter_mut().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Int(old) = last {
+                if *old == value {
+                    return false
+                }
+            }
+            *last = AnimLastValue::Int(value);
+        }
+        else {
+            last_values.push((live_id, AnimLastValue::Int(value)))
+        }
+        true
+    }
+    
+    pub fn calc_bool(&mut self, cx: &mut Cx, live_id: LiveId, time: f64) -> bool {
+        let last = Self::_last_bool(live_id, &self.last_values);
+        let mut ret = last;
+        if let Some(time) = self.update_anim_track(cx, time) {
+            if let Some(track_index) = self.find_track_index(live_id) {
+                if let Track::Bool{keys, ..} = &mut self.current.as_mut().unwrap().tracks[track_index] {
+                    ret = Track::compute_track_step(time, keys, last);
+                }
+            }
+        }
+        if self.set_last_bool(live_id, ret) {
+            cx.redraw_child_area(self.area);
+        }
+        return ret
+    }
+    
+    pub fn last_bool(&self, _cx: &Cx, live_id: LiveId) -> bool {
+        Self::_last_bool(live_id, &self.last_values)
+    }
+    
+    pub fn _last_bool(live_id: LiveId, last_values: &Vec<(LiveId, AnimLastValue)>) -> bool {
+        if let Some((_, value)) = last_values.iter().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Bool(value) = value {
+                return *value
+            }
+        }
+        return false
+    }
+    
+    pub fn set_last_bool(&mut self, live_id: LiveId, value: bool) -> bool {
+        Self::_set_last_bool(live_id, value, &mut self.last_values)
+    }
+    
+    // returns if the value changed, so the caller knows to relayout
+    pub fn _set_last_bool(live_id: LiveId, value: bool, last_values: &mut Vec<(LiveId, AnimLastValue)>) -> bool {
+        if let Some((_, last)) = last_values.iter_mut().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Bool(old) = last {
+                if *old == value {
+                    return false
+                }
+            }
+            *last = AnimLastValue::Bool(value);
+        }
+        else {
+            last_values.push((live_id, AnimLastValue::Bool(value)))
+        }
+        true
+    }
+    
+    pub fn calc_walk(&mut self, cx: &mut Cx, live_id: LiveId, time: f64) -> Walk {
+        let last = Self::_last_walk(live_id, &self.last_values);
+        let mut ret = last;
+        if let Some(time) = self.update_anim_track(cx, time) {
+            if let Some(track_index) = self.find_track_index(live_id) {
+                if let Track::Walk{keys, cut_init, ease, ..} = &mut self.current.as_mut().unwrap().tracks[track_index] {
+                    ret = Track::compute_track_walk(time, keys, cut_init, last, ease);
+                }
+            }
+        }
+        if self.set_last_walk(live_id, ret) {
+            cx.redraw_child_area(self.area);
+        }
+        return ret
+    }
+    
+    pub fn last_walk(&self, _cx: &Cx, live_id: LiveId) -> Walk {
+        Self::_last_walk(live_id, &self.last_values)
+    }
+    
+    pub fn _last_walk(live_id: LiveId, last_values: &Vec<(LiveId, AnimLastValue)>) -> Walk {
+        if let Some((_, value)) = last_values.iter().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Walk(value) = value {
+                return *value
+            }
+        }
+        return Walk::default()
+    }
+    
+    pub fn set_last_walk(&mut self, live_id: LiveId, value: Walk) -> bool {
+        Self::_set_last_walk(live_id, value, &mut self.last_values)
+    }
+    
+    // returns if the value changed, so the caller knows to relayout
+    pub fn _set_last_walk(live_id: LiveId, value: Walk, last_values: &mut Vec<(LiveId, AnimLastValue)>) -> bool {
+        if let Some((_, last)) = last_values.iter_mut().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Walk(old) = last {
+                if *old == value {
+                    return false
+                }
+            }
+            *last = AnimLastValue::Walk(value);
+        }
+        else {
+            last_values.push((live_id, AnimLastValue::Walk(value)))
+        }
+        true
+    }
+    
+    pub fn calc_margin(&mut self, cx: &mut Cx, live_id: LiveId, time: f64) -> Margin {
+        let last = Self::_last_margin(live_id, &self.last_values);
+        let mut ret = last;
+        if let Some(time) = self.update_anim_track(cx, time) {
+            if let Some(track_index) = self.find_track_index(live_id) {
+                if let Track::Margin{keys, cut_init, ease, ..} = &mut self.current.as_mut().unwrap().tracks[track_index] {
+                    ret = Track::compute_track_margin(time, keys, cut_init, last, ease);
+                }
+            }
+        }
+        if self.set_last_margin(live_id, ret) {
+            cx.redraw_child_area(self.area);
+        }
+        return ret
+    }
+    
+    pub fn last_margin(&self, _cx: &Cx, live_id: LiveId) -> Margin {
+        Self::_last_margin(live_id, &self.last_values)
+    }
+    
+    pub fn _last_margin(live_id: LiveId, last_values: &Vec<(LiveId, AnimLastValue)>) -> Margin {
+        if let Some((_, value)) = last_values.iter().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Margin(value) = value {
+                return *value
+            }
+        }
+        return Margin::default()
+    }
+    
+    pub fn set_last_margin(&mut self, live_id: LiveId, value: Margin) -> bool {
+        Self::_set_last_margin(live_id, value, &mut self.last_values)
+    }
+    
+    // returns if the value changed, so the caller knows to relayout
+    pub fn _set_last_margin(live_id: LiveId, value: Margin, last_values: &mut Vec<(LiveId, AnimLastValue)>) -> bool {
+        if let Some((_, last)) = last_values.iter_mut().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Margin(old) = last {
+                if *old == value {
+                    return false
+                }
+            }
+            *last = AnimLastValue::Margin(value);
+        }
+        else {
+            last_values.push((live_id, AnimLastValue::Margin(value)))
+        }
+        true
+    }
+    
+    pub fn calc_padding(&mut self, cx: &mut Cx, live_id: LiveId, time: f64) -> Padding {
+        let last = Self::_last_padding(live_id, &self.last_values);
+        let mut ret = last;
+        if let Some(time) = self.update_anim_track(cx, time) {
+            if let Some(track_index) = self.find_track_index(live_id) {
+                if let Track::Padding{keys, cut_init, ease, ..} = &mut self.current.as_mut().unwrap().tracks[track_index] {
+                    ret = Track::compute_track_padding(time, keys, cut_init, last, ease);
+                }
+            }
+        }
+        if self.set_last_padding(live_id, ret) {
+            cx.redraw_child_area(self.area);
+        }
+        return ret
+    }
+    
+    pub fn last_padding(&self, _cx: &Cx, live_id: LiveId) -> Padding {
+        Self::_last_padding(live_id, &self.last_values)
+    }
+    
+    pub fn _last_padding(live_id: LiveId, last_values: &Vec<(LiveId, AnimLastValue)>) -> Padding {
+        if let Some((_, value)) = last_values.iter().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Padding(value) = value {
+                return *value
+            }
+        }
+        return Padding::default()
+    }
+    
+    pub fn set_last_padding(&mut self, live_id: LiveId, value: Padding) -> bool {
+        Self::_set_last_padding(live_id, value, &mut self.last_values)
+    }
+    
+    // returns if the value changed, so the caller knows to relayout
+    pub fn _set_last_padding(live_id: LiveId, value: Padding, last_values: &mut Vec<(LiveId, AnimLastValue)>) -> bool {
+        if let Some((_, last)) = last_values.iter_mut().find( | v | v.0 == live_id) {
+            if let AnimLastValue::Padding(old) = last {
+                if *old == value {
+                    return false
+                }
+            }
+            *last = AnimLastValue::Padding(value);
+        }
+        else {
+            last_values.push((live_id, AnimLastValue::Padding(value)))
+        }
+        true
+    }
+    
     pub fn last_area(&mut self, _cx: &mut Cx, _area: Area, _time: f64) {
         
     }
     
     pub fn calc_area(&mut self, cx: &mut Cx, area: Area, time: f64) {
-        
+        let abs_time = time;
         if let Some(time) = self.update_anim_track(cx, time) {
             
             for track_index in 0..self.current.as_ref().unwrap().tracks.len() {
@@ -454,10 +845,134 @@ impl Animator {
                         Self::_set_last_float(*live_id, ret, &mut self.last_values);
                         area.write_float(cx, *live_id, ret);
                     }
+                    Track::Spring{live_id, spring, target} => {
+                        let ret = Self::_calc_spring(*live_id, *spring, *target, abs_time, &self.last_values, &mut self.springs);
+                        Self::_set_last_float(*live_id, ret, &mut self.last_values);
+                        area.write_float(cx, *live_id, ret);
+                    }
+                    // these dont live in instances, the widget reads them back with last_* when it draws
+                    Track::Int{live_id, keys} => {
+                        let last = Self::_last_int(*live_id, &self.last_values);
+                        let ret = Track::compute_track_step(time, keys, last);
+                        if Self::_set_last_int(*live_id, ret, &mut self.last_values) {
+                            cx.redraw_child_area(area);
+                        }
+                    }
+                    Track::Bool{live_id, keys} => {
+                        let last = Self::_last_bool(*live_id, &self.last_values);
+                        let ret = Track::compute_track_step(time, keys, last);
+                        if Self::_set_last_bool(*live_id, ret, &mut self.last_values) {
+                            cx.redraw_child_area(area);
+                        }
+                    }
+                    Track::Walk{live_id, keys, cut_init, ease} => {
+                        let init = Self::_last_walk(*live_id, &self.last_values);
+                        let ret = Track::compute_track_walk(time, keys, cut_init, init, ease);
+                        if Self::_set_last_walk(*live_id, ret, &mut self.last_values) {
+                            cx.redraw_child_area(area);
+                        }
+                    }
+                    Track::Margin{live_id, keys, cut_init, ease} => {
+                        let init = Self::_last_margin(*live_id, &self.last_values);
+                        let ret = Track::compute_track_margin(time, keys, cut_init, init, ease);
+                        if Self::_set_last_margin(*live_id, ret, &mut self.last_values) {
+                            cx.redraw_child_area(area);
+                        }
+                    }
+                    Track::Padding{live_id, keys, cut_init, ease} => {
+                        let init = Self::_last_padding(*live_id, &self.last_values);
+                        let ret = Track::compute_track_padding(time, keys, cut_init, init, ease);
+                        if Self::_set_last_padding(*live_id, ret, &mut self.last_values) {
+                            cx.redraw_child_area(area);
+                        }
+                    }
                 };
             }
             //}
+            self.update_spring_total_time(cx);
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // runs a spring through the animator at 60fps until it settles, returns the values it went through
+    fn run_spring(spring: Spring, target: f32, frames: usize, springs: &mut Vec<(LiveId, AnimSpring)>, last_values: &mut Vec<(LiveId, AnimLastValue)>, start_time: f64) -> Vec<f32> {
+        let live_id = LiveId(1);
+        let mut out = Vec::new();
+        for frame in 0..frames {
+            let value = Animator::_calc_spring(live_id, spring, target, start_time + frame as f64 / 60., last_values, springs);
+            Animator::_set_last_float(live_id, value, last_values);
+            out.push(value);
+        }
+        out
+    }
+    
+    #[test]
+    fn spring_settles_on_its_target() {
+        let mut springs = Vec::new();
+        let mut last_values = Vec::new();
+        let values = run_spring(Spring::default(), 1., 120, &mut springs, &mut last_values, 0.);
+        let settled = values.iter().position( | v | *v == 1.).expect("spring never settled");
+        assert!(settled < 90);
+        // and once its there it stays, at rest
+        assert!(values[settled..].iter().all( | v | *v == 1.));
+        assert!(springs[0].1.settled);
+        assert_eq!(springs[0].1.velocity, 0.);
+    }
+    
+    #[test]
+    fn spring_keeps_its_velocity_when_retargeted() {
+        let mut springs = Vec::new();
+        let mut last_values = Vec::new();
+        let values = run_spring(Spring::default(), 1., 6, &mut springs, &mut last_values, 0.);
+        let velocity = springs[0].1.velocity;
+        assert!(velocity > 0.);
+        // pulled back to 0 mid flight it first carries on forward instead of jumping back
+        let back = run_spring(Spring::default(), 0., 2, &mut springs, &mut last_values, 6. / 60.);
+        assert!(back[1] > values[5]);
+        assert!(springs[0].1.velocity < velocity);
+        // a push from outside carries over the same way
+        let mut animator = Animator::default();
+        animator.set_velocity(LiveId(1), 5.);
+        let pushed = run_spring(Spring::default(), 0., 2, &mut animator.springs, &mut animator.last_values, 0.);
+        assert!(pushed[1] > 0.);
+    }
+    
+    #[test]
+    fn stiff_springs_stay_stable_over_long_frames() {
+        let spring = Spring {stiffness: 5000., damping: 20., mass: 1., precision: 0.001};
+        // a single euler step this long would overshoot by a factor of 50
+        let (value, velocity) = spring.step(0., 0., 1., 0.1);
+        assert!(value.is_finite() && velocity.is_finite());
+        assert!((value - 1.).abs() < 1.);
+        // and one long step lands where many short ones do
+        let (mut short_value, mut short_velocity) = (0., 0.);
+        for _ in 0..24 {
+            let (v, vel) = spring.step(short_value, short_velocity, 1., 0.1 / 24.);
+            short_value = v;
+            short_velocity = vel;
+        }
+        assert!((value - short_value).abs() < 0.001);
+    }
+    
+    #[test]
+    fn step_tracks_switch_exactly_at_their_keys() {
+        let keys = vec![(0.25, 1), (0.5, 2), (1.0, 3)];
+        // before the first key it holds on to what it had
+        assert_eq!(Track::compute_track_step(0.0, &keys, 7), 7);
+        assert_eq!(Track::compute_track_step(0.2499, &keys, 7), 7);
+        // at a key it already has the new value, right before it the old one
+        assert_eq!(Track::compute_track_step(0.25, &keys, 7), 1);
+        assert_eq!(Track::compute_track_step(0.4999, &keys, 7), 1);
+        assert_eq!(Track::compute_track_step(0.5, &keys, 7), 2);
+        // the last key stays
+        assert_eq!(Track::compute_track_step(1.0, &keys, 7), 3);
+        assert_eq!(Track::compute_track_step(5.0, &keys, 7), 3);
+        // and without keys theres only the init
+        assert_eq!(Track::compute_track_step(0.5, &Vec::<(f64, i64)>::new(), 7), 7);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::cx::*;
+
+// the clock all animations run on. Animate events carry the anim time instead of the frame time,
+// so it can be paused, slowed down and scrubbed without the animators knowing about it
+#[derive(Clone)]
+pub struct CxAnimClock {
+    pub paused: bool,
+    pub time_scale: f64,
+    pub anim_time: f64,
+    pub real_time: f64,
+    pub scrubbed: bool,
+}
+
+impl Default for CxAnimClock {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            time_scale: 1.0,
+            anim_time: std::f64::NAN,
+            real_time: std::f64::NAN,
+            scrubbed: false,
+        }
+    }
+}
+
+impl CxAnimClock {
+    // maps a frame time onto the anim clock, None when paused and there is nothing new to show
+    pub fn tick(&mut self, real_time: f64) -> Option<f64> {
+        if self.anim_time.is_nan() {
+            self.anim_time = real_time;
+        }
+        else if !self.paused && !self.real_time.is_nan() {
+            self.anim_time += (real_time - self.real_time).max(0.) * self.time_scale;
+        }
+        self.real_time = real_time;
+        if self.paused && !self.scrubbed {
+            return None
+        }
+        self.scrubbed = false;
+        Some(self.anim_time)
+    }
+    
+    // a paused clock only needs a frame to show a scrub
+    pub fn needs_frames(&self) -> bool {
+        !self.paused || self.scrubbed
+    }
+}
+
+impl Cx {
+    pub fn get_anim_time(&self) -> f64 {
+        self.anim_clock.anim_time
+    }
+    
+    // playing animations keep the event loop requesting frames, unless the clock stands still
+    pub fn has_running_anims(&self) -> bool {
+        self.playing_anim_areas.len() != 0 && self.anim_clock.needs_frames()
+    }
+    
+    pub fn is_anim_paused(&self) -> bool {
+        self.anim_clock.paused
+    }
+    
+    pub fn set_anim_paused(&mut self, paused: bool) {
+        self.anim_clock.paused = paused;
+        // dont count the time we spent paused
+        self.anim_clock.real_time = std::f64::NAN;
+    }
+    
+    pub fn get_anim_time_scale(&self) -> f64 {
+        self.anim_clock.time_scale
+    }
+    
+    pub fn set_anim_time_scale(&mut self, time_scale: f64) {
+        self.anim_clock.time_scale = time_scale.max(0.);
+    }
+    
+    // jumps the anim clock, the next frame animates to it even when paused
+    pub fn scrub_anim_time(&mut self, anim_time: f64) {
+        if self.anim_clock.anim_time.is_nan() {
+            return
+        }
+        self.anim_clock.anim_time = anim_time;
+        self.anim_clock.scrubbed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn clock_starts_at_the_first_frame_and_follows_real_time() {
+        let mut clock = CxAnimClock::default();
+        assert_eq!(clock.tick(10.0), Some(10.0));
+        assert_eq!(clock.tick(10.5), Some(10.5));
+        // frame times that go back dont run the clock backwards
+        assert_eq!(clock.tick(10.25), Some(10.5));
+    }
+    
+    #[test]
+    fn paused_clock_stands_still_and_resumes_without_a_jump() {
+        let mut clock = CxAnimClock::default();
+        clock.tick(1.0);
+        clock.paused = true;
+        assert_eq!(clock.tick(2.0), None);
+        assert_eq!(clock.tick(5.0), None);
+        assert!(!clock.needs_frames());
+        // resuming like set_anim_paused does, the first frame only picks up the real time
+        clock.paused = false;
+        clock.real_time = std::f64::NAN;
+        assert!(clock.needs_frames());
+        assert_eq!(clock.tick(9.0), Some(1.0));
+        assert_eq!(clock.tick(9.5), Some(1.5));
+    }
+    
+    #[test]
+    fn time_scale_slows_the_clock_down() {
+        let mut clock = CxAnimClock::default();
+        clock.tick(0.0);
+        clock.time_scale = 0.25;
+        assert_eq!(clock.tick(1.0), Some(0.25));
+        clock.time_scale = 0.0;
+        assert_eq!(clock.tick(2.0), Some(0.25));
+    }
+    
+    #[test]
+    fn scrubbing_while_paused_shows_one_frame() {
+        let mut clock = CxAnimClock::default();
+        clock.tick(1.0);
+        clock.paused = true;
+        clock.anim_time = 0.5;
+        clock.scrubbed = true;
+        assert!(clock.needs_frames());
+        assert_eq!(clock.tick(2.0), Some(0.5));
+        assert!(!clock.needs_frames());
+        assert_eq!(clock.tick(3.0), None);
+    }
+}
@@ -160,6 +160,33 @@ impl Area{
         }
     }
 
+    // the rect as it was laid out, before the view scrolled or clipped it
+    pub fn get_rect_unscrolled(&self, cx:&Cx)->Rect{
+        return match self{
+            Area::Instance(inst)=>{
+                if inst.instance_count == 0{
+                    return Rect::default()
+                }
+                let cxview = &cx.views[inst.view_id];
+                if cxview.redraw_id != inst.redraw_id {
+                    return Rect::default();
+                }
+                let draw_call = &cxview.draw_calls[inst.draw_call_id];
+                let sh = &cx.shaders[draw_call.shader_id];
+                let props = &sh.mapping.rect_instance_props;
+                if let (Some(ix), Some(iy), Some(iw), Some(ih)) = (props.x, props.y, props.w, props.h){
+                    let instance = &draw_call.instance[inst.instance_offset..];
+                    return Rect{x:instance[ix], y:instance[iy], w:instance[iw], h:instance[ih]}
+                }
+                Rect::default()
+            },
+            Area::View(view_area)=>{
+                cx.views[view_area.view_id].rect
+            },
+            _=>Rect::default(),
+        }
+    }
+
     pub fn abs_to_rel(&self, cx:&Cx, abs:Vec2)->Vec2{
         return match self{
             Area::Instance(inst)=>{
@@ -0,0 +1,227 @@
+use crate::cx::*;
+
+// programmatic clipboard access, next to the platform copy (TextCopy) and paste (TextInput) that
+// ctrl+c/ctrl+v do. a write puts the same thing on the clipboard in several formats, a read asks
+// for the formats it wants and the answer comes back later as Event::ClipboardRead, because on
+// X11 whoever owns the selection has to respond first
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClipboardSelection {
+    Clipboard,
+    // the X11 select and middle click one. other platforms keep it inside the app
+    Primary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClipboardFormat {
+    Text,
+    Html,
+    Png,
+}
+
+impl ClipboardFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ClipboardFormat::Text => "text/plain;charset=utf-8",
+            ClipboardFormat::Html => "text/html",
+            ClipboardFormat::Png => "image/png",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClipboardContent {
+    pub text: Option<String>,
+    pub html: Option<String>,
+    // an encoded png file
+    pub png: Option<Vec<u8>>,
+}
+
+impl ClipboardContent {
+    pub fn text(text: &str) -> Self {
+        Self {
+            text: Some(text.to_string()),
+            ..Self::default()
+        }
+    }
+    
+    pub fn has_format(&self, format: ClipboardFormat) -> bool {
+        match format {
+            ClipboardFormat::Text => self.text.is_some(),
+            ClipboardFormat::Html => self.html.is_some(),
+            ClipboardFormat::Png => self.png.is_some(),
+        }
+    }
+    
+    pub fn formats(&self) -> Vec<ClipboardFormat> {
+        [ClipboardFormat::Text, ClipboardFormat::Html, ClipboardFormat::Png].iter().cloned().filter( | format | self.has_format(*format)).collect()
+    }
+    
+    pub fn only_formats(&self, formats: &[ClipboardFormat]) -> Self {
+        Self {
+            text: if formats.contains(&ClipboardFormat::Text) {self.text.clone()} else {None},
+            html: if formats.contains(&ClipboardFormat::Html) {self.html.clone()} else {None},
+            png: if formats.contains(&ClipboardFormat::Png) {self.png.clone()} else {None},
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none() && self.html.is_none() && self.png.is_none()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipboardReadEvent {
+    pub read_id: u64,
+    pub selection: ClipboardSelection,
+    pub content: ClipboardContent,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ClipboardRead {
+    pub read_id: u64
+}
+
+impl ClipboardRead {
+    pub fn is_pending(&self) -> bool {
+        self.read_id != 0
+    }
+    
+    pub fn resolve<'a>(&mut self, cr: &'a ClipboardReadEvent) -> Option<&'a ClipboardContent> {
+        if self.read_id != 0 && cr.read_id == self.read_id {
+            self.read_id = 0;
+            return Some(&cr.content)
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CxClipboardRead {
+    pub read_id: u64,
+    pub selection: ClipboardSelection,
+    pub formats: Vec<ClipboardFormat>,
+}
+
+#[derive(Clone, Default)]
+pub struct CxClipboard {
+    pub last_read_id: u64,
+    // what the app itself wrote last
+    pub clipboard: ClipboardContent,
+    pub primary: ClipboardContent,
+    // for the platform to pick up
+    pub writes: Vec<(ClipboardSelection, ClipboardContent)>,
+    pub reads: Vec<CxClipboardRead>,
+}
+
+impl Cx {
+    pub fn clipboard_write(&mut self, selection: ClipboardSelection, content: ClipboardContent) {
+        match selection {
+            ClipboardSelection::Clipboard => self.clipboard.clipboard = content.clone(),
+            ClipboardSelection::Primary => self.clipboard.primary = content.clone(),
+        }
+        self.clipboard.writes.push((selection, content));
+    }
+    
+    // formats in order of preference, the content has the ones that were there
+    pub fn clipboard_read(&mut self, selection: ClipboardSelection, formats: &[ClipboardFormat]) -> ClipboardRead {
+        self.clipboard.last_read_id += 1;
+        let read_id = self.clipboard.last_read_id;
+        self.clipboard.reads.push(CxClipboardRead {
+            read_id: read_id,
+            selection: selection,
+            formats: formats.to_vec()
+        });
+        ClipboardRead {read_id: read_id}
+    }
+    
+    // answers reads with the local content. macos and windows copy the system clipboard into it
+    // first, the web only knows what was copied or written inside the app
+    pub fn call_clipboard_local_reads<F>(&mut self, mut event_handler: F)
+    where F: FnMut(&mut Cx, &mut Event)
+    {
+        let mut reads = Vec::new();
+        std::mem::swap(&mut reads, &mut self.clipboard.reads);
+        for read in reads {
+            let content = match read.selection {
+                ClipboardSelection::Clipboard => &self.clipboard.clipboard,
+                ClipboardSelection::Primary => &self.clipboard.primary,
+            };
+            let mut event = Event::ClipboardRead(ClipboardReadEvent {
+                read_id: read.read_id,
+                selection: read.selection,
+                content: content.only_formats(&read.formats)
+            });
+            self.call_event_handler(&mut event_handler, &mut event);
+        }
+    }
+}
+
+// windows keeps html on the clipboard as "HTML Format": a header with the byte offsets of the
+// document and of the fragment that was copied, in front of the markup
+fn cf_html_header(start_html: usize, end_html: usize, start_fragment: usize, end_fragment: usize) -> String {
+    format!(
+        "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+        start_html,
+        end_html,
+        start_fragment,
+        end_fragment
+    )
+}
+
+pub fn cf_html_from_html(html: &str) -> Vec<u8> {
+    let prefix = "<html><body>\r\n<!--StartFragment-->";
+    let suffix = "<!--EndFragment-->\r\n</body></html>";
+    let start_html = cf_html_header(0, 0, 0, 0).len();
+    let start_fragment = start_html + prefix.len();
+    let end_fragment = start_fragment + html.len();
+    let end_html = end_fragment + suffix.len();
+    let header = cf_html_header(start_html, end_html, start_fragment, end_fragment);
+    format!("{}{}{}{}", header, prefix, html, suffix).into_bytes()
+}
+
+pub fn html_from_cf_html(data: &[u8]) -> Option<String> {
+    let data = if let Some(end) = data.iter().position( | c | *c == 0) {&data[0..end]} else {data};
+    let text = String::from_utf8_lossy(data);
+    let offset = | key: &str | -> Option<usize> {
+        let start = text.find(key) ? + key.len();
+        text[start..].lines().next() ?.trim().parse().ok()
+    };
+    let start = offset("StartFragment:") ?;
+    let end = offset("EndFragment:") ?;
+    if start > end {
+        return None
+    }
+    Some(String::from_utf8_lossy(data.get(start..end) ?).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn cf_html_round_trips() {
+        let html = "<b>h\u{e9}llo</b>\r\nworld";
+        let data = cf_html_from_html(html);
+        let text = String::from_utf8(data.clone()).unwrap();
+        let start_html: usize = text["Version:0.9\r\nStartHTML:".len()..].lines().next().unwrap().parse().unwrap();
+        assert!(text[start_html..].starts_with("<html>"));
+        assert_eq!(html_from_cf_html(&data), Some(html.to_string()));
+    }
+    
+    #[test]
+    fn cf_html_with_short_offsets_is_read() {
+        let mut data = format!("Version:1.0\r\nStartFragment:{}\r\nEndFragment:{}\r\n", 100, 102);
+        while data.len() < 100 {
+            data.push(' ');
+        }
+        data.push_str("hi</p>\0");
+        assert_eq!(html_from_cf_html(data.as_bytes()), Some("hi".to_string()));
+    }
+    
+    #[test]
+    fn broken_cf_html_is_none() {
+        assert_eq!(html_from_cf_html(b"Version:0.9\r\n"), None);
+        assert_eq!(html_from_cf_html(b"StartFragment:20\r\nEndFragment:10\r\n"), None);
+        assert_eq!(html_from_cf_html(b"StartFragment:20\r\nEndFragment:900\r\n"), None);
+    }
+}
@@ -16,11 +16,19 @@ pub use crate::pass::*;
 pub use crate::geometry::*;
 pub use crate::texture::*;
 pub use crate::text::*;
+pub use crate::shaping::*;
 pub use crate::live::*;
 pub use crate::events::*;
 pub use crate::animator::*;
 pub use crate::area::*;
 pub use crate::menu::*;
+pub use crate::keymap::*;
+pub use crate::layoutdebug::*;
+pub use crate::animclock::*;
+pub use crate::accessibility::*;
+pub use crate::focus::*;
+pub use crate::dragdrop::*;
+pub use crate::clipboard::*;
 pub use crate::shader::*;
 pub use crate::live::*;
 pub use crate::geometrygen::*;
@@ -30,6 +38,8 @@ pub use crate::uid;
 pub use crate::cx_linux::*;
 #[cfg(all(not(feature = "ipc"), target_os = "linux"))]
 pub use crate::cx_opengl::*;
+#[cfg(all(not(feature = "ipc"), target_os = "linux"))]
+pub use crate::cx_atspi::*;
 
 #[cfg(all(not(feature = "ipc"), target_os = "macos"))]
 pub use crate::cx_macos::*;
@@ -94,6 +104,7 @@ pub struct Cx {
     
     pub fonts: Vec<CxFont>,
     pub fonts_atlas: CxFontsAtlas,
+    pub fonts_sdf_atlas: CxFontsAtlas,
     pub textures: Vec<CxTexture>,
     pub textures_free: Vec<usize>,
     
@@ -136,6 +147,7 @@ pub struct Cx {
     pub hover_mouse_cursor: Option<MouseCursor>,
     pub fingers: Vec<CxPerFinger>,
     
+    pub anim_clock: CxAnimClock,
     pub playing_anim_areas: Vec<AnimArea>,
     pub ended_anim_areas: Vec<AnimArea>,
     
@@ -147,6 +159,12 @@ pub struct Cx {
     pub live_styles: LiveStyles,
     
     pub command_settings: HashMap<CommandId, CxCommandSetting>,
+    pub keymap: CxKeymap,
+    pub layout_debug: CxLayoutDebug,
+    pub access: CxAccess,
+    pub focus: CxFocus,
+    pub drag: CxDrag,
+    pub clipboard: CxClipboard,
     
     pub panic_now: bool,
     pub panic_redraw: bool,
@@ -191,7 +209,7 @@ impl Default for Cx {
             platform: CxPlatformTexture::default()
         }];
         
-        Self {
+        let mut cx = Self {
             counter: 0,
             platform_type: PlatformType::Windows,
             running: true,
@@ -204,6 +222,7 @@ impl Default for Cx {
             views_free: Vec::new(),
             fonts: Vec::new(),
             fonts_atlas: CxFontsAtlas::default(),
+            fonts_sdf_atlas: CxFontsAtlas::default(),
             textures: textures,
             textures_free: Vec::new(),
             shaders: Vec::new(),
@@ -248,7 +267,14 @@ impl Default for Cx {
             live_styles: LiveStyles::new(),
             
             command_settings: HashMap::new(),
+            keymap: CxKeymap::default(),
+            layout_debug: CxLayoutDebug::default(),
+            access: CxAccess::default(),
+            focus: CxFocus::default(),
+            drag: CxDrag::default(),
+            clipboard: CxClipboard::default(),
             
+            anim_clock: CxAnimClock::default(),
             playing_anim_areas: Vec::new(),
             ended_anim_areas: Vec::new(),
             
@@ -262,7 +288,10 @@ impl Default for Cx {
             
             platform: CxPlatform {..Default::default()},
 
-        }
+        };
+        // every app gets the layout debugger, its keys can be rebound like any other command
+        Cx::command_toggle_layout_debug().set_name(&mut cx, "toggle_layout_debug").bind(&mut cx, "Ctrl+Shift+L");
+        cx
     }
 }
 
@@ -645,6 +674,8 @@ impl Cx {
         if self.next_key_focus != self.key_focus {
             self.prev_key_focus = self.key_focus;
             self.key_focus = self.next_key_focus;
+            self.keymap.context = None;
+            self.keymap.pending.truncate(0);
             event_handler(self, &mut Event::KeyFocus(KeyFocusEvent {
                 prev: self.prev_key_focus,
                 focus: self.key_focus
@@ -687,6 +718,7 @@ impl Cx {
     pub fn call_animation_event<F>(&mut self, mut event_handler: F, time: f64)
     where F: FnMut(&mut Cx, &mut Event)
     {
+        let time = if let Some(time) = self.anim_clock.tick(time) {time} else {return};
         self.call_event_handler(&mut event_handler, &mut Event::Animate(AnimateEvent {time: time, frame: self.repaint_id}));
         self.check_ended_anim_areas(time);
         if self.ended_anim_areas.len() > 0 {
@@ -732,14 +764,22 @@ impl Cx {
     where F: FnMut(&mut Cx, &mut Event)
     {
         let mut counter = 0;
-        while self.signals.len() != 0 {
+        while self.signals.len() != 0 || self.keymap.queued_commands.len() != 0 {
             counter += 1;
-            let mut signals = HashMap::new();
-            std::mem::swap(&mut self.signals, &mut signals);
+            let mut commands = Vec::new();
+            std::mem::swap(&mut self.keymap.queued_commands, &mut commands);
+            for command in commands {
+                self.call_event_handler(&mut event_handler, &mut Event::Command(command));
+            }
             
-            self.call_event_handler(&mut event_handler, &mut Event::Signal(SignalEvent {
-                signals: signals,
-            }));
+            if self.signals.len() != 0 {
+                let mut signals = HashMap::new();
+                std::mem::swap(&mut self.signals, &mut signals);
+                
+                self.call_event_handler(&mut event_handler, &mut Event::Signal(SignalEvent {
+                    signals: signals,
+                }));
+            }
             
             if counter > 100 {
                 println!("Signal feedback loop detected");
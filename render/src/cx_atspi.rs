@@ -0,0 +1,714 @@
+// serves the accessibility tree to AT-SPI, which is what orca and friends read on linux.
+// the bridge runs its own thread on the accessibility bus and answers from a snapshot
+// the main thread publishes after painting. actions come back as a posted signal
+use crate::cx::*;
+use crate::dbus::*;
+use std::sync::{Arc, Mutex};
+
+const ATSPI_ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const ATSPI_NULL_PATH: &str = "/org/a11y/atspi/null";
+const ATSPI_PATH_PREFIX: &str = "/org/a11y/atspi/accessible/";
+
+const ATSPI_ROLE_APPLICATION: u32 = 75;
+
+const ATSPI_STATE_ACTIVE: u32 = 1;
+const ATSPI_STATE_COLLAPSED: u32 = 5;
+const ATSPI_STATE_EDITABLE: u32 = 7;
+const ATSPI_STATE_ENABLED: u32 = 8;
+const ATSPI_STATE_EXPANDABLE: u32 = 9;
+const ATSPI_STATE_EXPANDED: u32 = 10;
+const ATSPI_STATE_FOCUSABLE: u32 = 11;
+const ATSPI_STATE_FOCUSED: u32 = 12;
+const ATSPI_STATE_MULTI_LINE: u32 = 17;
+const ATSPI_STATE_SELECTABLE: u32 = 22;
+const ATSPI_STATE_SELECTED: u32 = 23;
+const ATSPI_STATE_SENSITIVE: u32 = 24;
+const ATSPI_STATE_SHOWING: u32 = 25;
+const ATSPI_STATE_VISIBLE: u32 = 30;
+
+fn atspi_role(role: AccessRole) -> (u32, &'static str) {
+    match role {
+        AccessRole::Window => (23, "frame"),
+        AccessRole::Panel => (39, "panel"),
+        AccessRole::Button => (43, "push button"),
+        AccessRole::Tab => (37, "page tab"),
+        AccessRole::TabList => (38, "page tab list"),
+        AccessRole::Tree => (65, "tree"),
+        AccessRole::TreeItem => (91, "tree item"),
+        AccessRole::Text => (61, "text"),
+        AccessRole::Label => (29, "label"),
+        AccessRole::List => (31, "list"),
+        AccessRole::ListItem => (32, "list item"),
+        AccessRole::Menu => (33, "menu"),
+        AccessRole::MenuItem => (35, "menu item"),
+        AccessRole::Slider => (51, "slider"),
+        AccessRole::SplitPane => (53, "split pane"),
+        AccessRole::ScrollPane => (49, "scroll pane"),
+    }
+}
+
+#[derive(Default)]
+pub struct AtspiShared {
+    pub sender: Option<DbusSender>,
+    pub unique_name: String,
+    pub app_name: String,
+    pub trees: Vec<AccessTree>,
+    pub actions: Vec<(u64, AccessAction)>,
+}
+
+#[derive(Clone)]
+pub struct CxAtspi {
+    pub shared: Arc<Mutex<AtspiShared>>,
+    pub signal: Signal,
+    pub focused: Option<u64>,
+    pub last_key_focus: Area,
+    pub dirty: bool,
+}
+
+#[derive(Clone, Copy)]
+enum AtspiTarget {
+    Root,
+    Node(usize, usize),
+}
+
+type AtspiResult = Result<Vec<DbusValue>, (&'static str, String)>;
+
+fn unknown_method(msg: &DbusMessage) -> (&'static str, String) {
+    ("org.freedesktop.DBus.Error.UnknownMethod", format!(
+        "{}.{} not supported",
+        msg.interface.clone().unwrap_or(String::new()),
+        msg.member.clone().unwrap_or(String::new())
+    ))
+}
+
+fn node_path(key: u64) -> String {
+    format!("{}{}", ATSPI_PATH_PREFIX, key)
+}
+
+fn arg_i32(msg: &DbusMessage, index: usize) -> i64 {
+    msg.body.get(index).and_then( | v | v.as_i64()).unwrap_or(0)
+}
+
+fn arg_str(msg: &DbusMessage, index: usize) -> String {
+    msg.body.get(index).and_then( | v | v.as_str()).unwrap_or("").to_string()
+}
+
+impl AtspiShared {
+    fn resolve(&self, path: &str) -> Option<AtspiTarget> {
+        if path == ATSPI_ROOT_PATH {
+            return Some(AtspiTarget::Root)
+        }
+        let key: u64 = path.strip_prefix(ATSPI_PATH_PREFIX) ?.parse().ok() ?;
+        for (tree_index, tree) in self.trees.iter().enumerate() {
+            if let Some(index) = tree.find_key(key) {
+                return Some(AtspiTarget::Node(tree_index, index))
+            }
+        }
+        None
+    }
+    
+    fn reference(&self, path: &str) -> DbusValue {
+        let name = if path == ATSPI_NULL_PATH {""} else {&self.unique_name};
+        DbusValue::Struct(vec![DbusValue::str(name), DbusValue::ObjectPath(path.to_string())])
+    }
+    
+    fn references(&self, paths: Vec<String>) -> DbusValue {
+        DbusValue::Array("(so)".to_string(), paths.iter().map( | path | self.reference(path)).collect())
+    }
+    
+    fn node(&self, target: AtspiTarget) -> Option<&AccessTreeNode> {
+        match target {
+            AtspiTarget::Root => None,
+            AtspiTarget::Node(tree, index) => Some(&self.trees[tree].nodes[index])
+        }
+    }
+    
+    fn path(&self, target: AtspiTarget) -> String {
+        match target {
+            AtspiTarget::Root => ATSPI_ROOT_PATH.to_string(),
+            AtspiTarget::Node(tree, index) => node_path(self.trees[tree].nodes[index].key)
+        }
+    }
+    
+    fn name(&self, target: AtspiTarget) -> String {
+        match self.node(target) {
+            None => self.app_name.clone(),
+            Some(node) => node.node.label.clone()
+        }
+    }
+    
+    fn parent(&self, target: AtspiTarget) -> String {
+        match target {
+            AtspiTarget::Root => ATSPI_NULL_PATH.to_string(),
+            AtspiTarget::Node(tree, index) => match self.trees[tree].nodes[index].parent {
+                None => ATSPI_ROOT_PATH.to_string(),
+                Some(parent) => self.path(AtspiTarget::Node(tree, parent))
+            }
+        }
+    }
+    
+    fn children(&self, target: AtspiTarget) -> Vec<String> {
+        match target {
+            AtspiTarget::Root => self.trees.iter().map( | tree | node_path(tree.nodes[0].key)).collect(),
+            AtspiTarget::Node(tree, index) => self.trees[tree].nodes[index].children.iter().map( | child | {
+                self.path(AtspiTarget::Node(tree, *child))
+            }).collect()
+        }
+    }
+    
+    fn index_in_parent(&self, target: AtspiTarget) -> i32 {
+        match target {
+            AtspiTarget::Root => -1,
+            AtspiTarget::Node(tree, index) => match self.trees[tree].nodes[index].parent {
+                None => tree as i32,
+                Some(parent) => self.trees[tree].nodes[parent].children.iter().position( | c | *c == index).map( | i | i as i32).unwrap_or(-1)
+            }
+        }
+    }
+    
+    fn role(&self, target: AtspiTarget) -> (u32, &'static str) {
+        match self.node(target) {
+            None => (ATSPI_ROLE_APPLICATION, "application"),
+            Some(node) => atspi_role(node.node.role)
+        }
+    }
+    
+    fn states(&self, target: AtspiTarget) -> Vec<u32> {
+        let mut states = vec![ATSPI_STATE_ENABLED, ATSPI_STATE_SENSITIVE, ATSPI_STATE_VISIBLE];
+        if let Some(node) = self.node(target) {
+            if node.rect.w > 0. && node.rect.h > 0. {
+                states.push(ATSPI_STATE_SHOWING);
+            }
+            match node.node.role {
+                AccessRole::Window => states.push(ATSPI_STATE_ACTIVE),
+                AccessRole::Tab | AccessRole::TreeItem | AccessRole::ListItem => states.push(ATSPI_STATE_SELECTABLE),
+                _ => ()
+            }
+            if node.node.actions.contains(&AccessAction::Focus) {
+                states.push(ATSPI_STATE_FOCUSABLE);
+            }
+            if node.focused {
+                states.push(ATSPI_STATE_FOCUSED);
+            }
+            if node.node.selected {
+                states.push(ATSPI_STATE_SELECTED);
+            }
+            match node.node.expanded {
+                Some(true) => states.extend_from_slice(&[ATSPI_STATE_EXPANDABLE, ATSPI_STATE_EXPANDED]),
+                Some(false) => states.extend_from_slice(&[ATSPI_STATE_EXPANDABLE, ATSPI_STATE_COLLAPSED]),
+                None => ()
+            }
+            if node.node.editable {
+                states.extend_from_slice(&[ATSPI_STATE_EDITABLE, ATSPI_STATE_MULTI_LINE]);
+            }
+        }
+        let mut bits = [0u32; 2];
+        for state in states {
+            bits[(state / 32) as usize] |= 1 << (state % 32);
+        }
+        bits.to_vec()
+    }
+    
+    fn text(&self, target: AtspiTarget) -> Option<Vec<char>> {
+        let node = self.node(target) ?;
+        if node.node.role != AccessRole::Text && node.node.value.is_none() {
+            return None
+        }
+        Some(node.node.value.as_ref().unwrap_or(&node.node.label).chars().collect())
+    }
+    
+    fn interfaces(&self, target: AtspiTarget) -> Vec<&'static str> {
+        let mut interfaces = vec!["org.a11y.atspi.Accessible"];
+        match self.node(target) {
+            None => interfaces.push("org.a11y.atspi.Application"),
+            Some(node) => {
+                interfaces.push("org.a11y.atspi.Component");
+                if node.node.actions.len() > 0 {
+                    interfaces.push("org.a11y.atspi.Action");
+                }
+                if self.text(target).is_some() {
+                    interfaces.push("org.a11y.atspi.Text");
+                }
+            }
+        }
+        interfaces
+    }
+    
+    fn extents(&self, target: AtspiTarget, coord_type: i64) -> Rect {
+        if let AtspiTarget::Node(tree_index, index) = target {
+            let tree = &self.trees[tree_index];
+            let screen = tree.screen_rect(index);
+            return match coord_type {
+                0 => screen,
+                1 => Rect {x: screen.x - tree.position.x, y: screen.y - tree.position.y, ..screen},
+                _ => {
+                    let parent = tree.screen_rect(tree.nodes[index].parent.unwrap_or(0));
+                    Rect {x: screen.x - parent.x, y: screen.y - parent.y, ..screen}
+                }
+            }
+        }
+        Rect::default()
+    }
+    
+    fn node_at_point(&self, x: f32, y: f32, coord_type: i64) -> String {
+        let mut found = None;
+        let mut found_depth = 0;
+        for (tree_index, tree) in self.trees.iter().enumerate() {
+            for index in 0..tree.nodes.len() {
+                let r = self.extents(AtspiTarget::Node(tree_index, index), if coord_type == 2 {1} else {coord_type});
+                if x < r.x || y < r.y || x >= r.x + r.w || y >= r.y + r.h {
+                    continue;
+                }
+                let mut depth = 0;
+                let mut walk = tree.nodes[index].parent;
+                while let Some(parent) = walk {
+                    depth += 1;
+                    walk = tree.nodes[parent].parent;
+                }
+                if found.is_none() || depth > found_depth {
+                    found = Some(AtspiTarget::Node(tree_index, index));
+                    found_depth = depth;
+                }
+            }
+        }
+        found.map( | target | self.path(target)).unwrap_or(ATSPI_NULL_PATH.to_string())
+    }
+    
+    fn properties(&self, target: AtspiTarget, interface: &str) -> Vec<(&'static str, DbusValue)> {
+        match interface {
+            "org.a11y.atspi.Accessible" => vec![
+                ("Name", DbusValue::Str(self.name(target))),
+                ("Description", DbusValue::str("")),
+                ("Parent", self.reference(&self.parent(target))),
+                ("ChildCount", DbusValue::Int32(self.children(target).len() as i32)),
+                ("Locale", DbusValue::str("")),
+                ("AccessibleId", DbusValue::str("")),
+            ],
+            "org.a11y.atspi.Application" => vec![
+                ("ToolkitName", DbusValue::str("makepad")),
+                ("Version", DbusValue::str(env!("CARGO_PKG_VERSION"))),
+                ("AtspiVersion", DbusValue::str("2.1")),
+                ("Id", DbusValue::Int32(0)),
+            ],
+            "org.a11y.atspi.Action" => vec![
+                ("NActions", DbusValue::Int32(self.node(target).map( | node | node.node.actions.len()).unwrap_or(0) as i32)),
+            ],
+            "org.a11y.atspi.Text" => {
+                let text = self.text(target).unwrap_or(Vec::new());
+                let cursor = self.node(target).and_then( | node | node.node.cursor).unwrap_or(0);
+                vec![
+                    ("CharacterCount", DbusValue::Int32(text.len() as i32)),
+                    ("CaretOffset", DbusValue::Int32(cursor as i32)),
+                ]
+            },
+            _ => Vec::new()
+        }
+    }
+    
+    fn queue_action(&mut self, target: AtspiTarget, action: AccessAction) -> bool {
+        if let Some(node) = self.node(target) {
+            if node.node.actions.contains(&action) {
+                let key = node.key;
+                self.actions.push((key, action));
+                return true
+            }
+        }
+        false
+    }
+    
+    fn handle_accessible(&self, target: AtspiTarget, msg: &DbusMessage) -> AtspiResult {
+        Ok(match msg.member.as_ref().map( | s | s.as_str()).unwrap_or("") {
+            "GetChildAtIndex" => {
+                let children = self.children(target);
+                let path = children.get(arg_i32(msg, 0) as usize).cloned().unwrap_or(ATSPI_NULL_PATH.to_string());
+                vec![self.reference(&path)]
+            },
+            "GetChildren" => vec![self.references(self.children(target))],
+            "GetIndexInParent" => vec![DbusValue::Int32(self.index_in_parent(target))],
+            "GetRelationSet" => vec![DbusValue::Array("(ua(so))".to_string(), Vec::new())],
+            "GetRole" => vec![DbusValue::Uint32(self.role(target).0)],
+            "GetRoleName" | "GetLocalizedRoleName" => vec![DbusValue::str(self.role(target).1)],
+            "GetState" => vec![DbusValue::Array("u".to_string(), self.states(target).into_iter().map(DbusValue::Uint32).collect())],
+            "GetAttributes" => vec![DbusValue::Array("{ss}".to_string(), vec![
+                DbusValue::DictEntry(Box::new(DbusValue::str("toolkit")), Box::new(DbusValue::str("makepad")))
+            ])],
+            "GetApplication" => vec![self.reference(ATSPI_ROOT_PATH)],
+            "GetInterfaces" => vec![DbusValue::Array("s".to_string(), self.interfaces(target).into_iter().map(DbusValue::str).collect())],
+            _ => return Err(unknown_method(msg))
+        })
+    }
+    
+    fn handle_component(&mut self, target: AtspiTarget, msg: &DbusMessage) -> AtspiResult {
+        Ok(match msg.member.as_ref().map( | s | s.as_str()).unwrap_or("") {
+            "GetExtents" => {
+                let r = self.extents(target, arg_i32(msg, 0));
+                vec![DbusValue::Struct(vec![
+                    DbusValue::Int32(r.x as i32),
+                    DbusValue::Int32(r.y as i32),
+                    DbusValue::Int32(r.w as i32),
+                    DbusValue::Int32(r.h as i32)
+                ])]
+            },
+            "GetPosition" => {
+                let r = self.extents(target, arg_i32(msg, 0));
+                vec![DbusValue::Int32(r.x as i32), DbusValue::Int32(r.y as i32)]
+            },
+            "GetSize" => {
+                let r = self.extents(target, 0);
+                vec![DbusValue::Int32(r.w as i32), DbusValue::Int32(r.h as i32)]
+            },
+            "Contains" => {
+                let r = self.extents(target, arg_i32(msg, 2));
+                let (x, y) = (arg_i32(msg, 0) as f32, arg_i32(msg, 1) as f32);
+                vec![DbusValue::Bool(x >= r.x && y >= r.y && x < r.x + r.w && y < r.y + r.h)]
+            },
+            "GetAccessibleAtPoint" => {
+                let path = self.node_at_point(arg_i32(msg, 0) as f32, arg_i32(msg, 1) as f32, arg_i32(msg, 2));
+                vec![self.reference(&path)]
+            },
+            "GetLayer" => vec![DbusValue::Uint32(if self.role(target).0 == 23 {7} else {3})],
+            "GetMDIZOrder" => vec![DbusValue::Int16(0)],
+            "GetAlpha" => vec![DbusValue::Double(1.0)],
+            "GrabFocus" => vec![DbusValue::Bool(self.queue_action(target, AccessAction::Focus))],
+            "ScrollTo" | "ScrollToPoint" | "SetExtents" | "SetPosition" | "SetSize" => vec![DbusValue::Bool(false)],
+            _ => return Err(unknown_method(msg))
+        })
+    }
+    
+    fn handle_action(&mut self, target: AtspiTarget, msg: &DbusMessage) -> AtspiResult {
+        let actions = self.node(target).map( | node | node.node.actions.clone()).unwrap_or(Vec::new());
+        let action = actions.get(arg_i32(msg, 0) as usize).cloned();
+        let name = action.map( | action | action.name()).unwrap_or("");
+        Ok(match msg.member.as_ref().map( | s | s.as_str()).unwrap_or("") {
+            "GetName" | "GetLocalizedName" | "GetDescription" => vec![DbusValue::str(name)],
+            "GetKeyBinding" => vec![DbusValue::str("")],
+            "GetActions" => vec![DbusValue::Array("(sss)".to_string(), actions.iter().map( | action | {
+                DbusValue::Struct(vec![DbusValue::str(action.name()), DbusValue::str(action.name()), DbusValue::str("")])
+            }).collect())],
+            "DoAction" => vec![DbusValue::Bool(if let Some(action) = action {self.queue_action(target, action)} else {false})],
+            _ => return Err(unknown_method(msg))
+        })
+    }
+    
+    fn handle_text(&self, target: AtspiTarget, msg: &DbusMessage) -> AtspiResult {
+        let text = self.text(target).unwrap_or(Vec::new());
+        let len = text.len() as i64;
+        let clamp = | offset: i64 | if offset < 0 || offset > len {len} else {offset} as usize;
+        // the boundaries screen readers move the review cursor by
+        let span = | offset: usize, granularity: i64 | -> (usize, usize) {
+            if offset >= text.len() {
+                return (text.len(), text.len())
+            }
+            match granularity {
+                0 => (offset, offset + 1),
+                1 => {
+                    if !text[offset].is_alphanumeric() {
+                        return (offset, offset + 1)
+                    }
+                    let mut start = offset;
+                    while start > 0 && text[start - 1].is_alphanumeric() {start -= 1}
+                    let mut end = offset;
+                    while end < text.len() && text[end].is_alphanumeric() {end += 1}
+                    (start, end)
+                },
+                _ => {
+                    let mut start = offset;
+                    while start > 0 && text[start - 1] != '\n' {start -= 1}
+                    let mut end = offset;
+                    while end < text.len() && text[end] != '\n' {end += 1}
+                    (start, (end + 1).min(text.len()))
+                }
+            }
+        };
+        let string = | start: usize, end: usize | text[start..end.max(start)].iter().collect::<String>();
+        Ok(match msg.member.as_ref().map( | s | s.as_str()).unwrap_or("") {
+            "GetText" => vec![DbusValue::Str(string(clamp(arg_i32(msg, 0)), clamp(arg_i32(msg, 1))))],
+            "GetCharacterAtOffset" => {
+                let offset = arg_i32(msg, 0);
+                vec![DbusValue::Int32(if offset >= 0 && offset < len {text[offset as usize] as i32} else {0})]
+            },
+            "GetStringAtOffset" => {
+                let (start, end) = span(clamp(arg_i32(msg, 0)), arg_i32(msg, 1));
+                vec![DbusValue::Str(string(start, end)), DbusValue::Int32(start as i32), DbusValue::Int32(end as i32)]
+            },
+            "GetTextAtOffset" => {
+                // the old boundary types, start and end variants of word, sentence and line
+                let granularity = match arg_i32(msg, 1) {0 => 0, 1 | 2 => 1, _ => 3};
+                let (start, end) = span(clamp(arg_i32(msg, 0)), granularity);
+                vec![DbusValue::Str(string(start, end)), DbusValue::Int32(start as i32), DbusValue::Int32(end as i32)]
+            },
+            "GetNSelections" => vec![DbusValue::Int32(0)],
+            "GetSelection" => vec![DbusValue::Int32(0), DbusValue::Int32(0)],
+            "SetCaretOffset" | "AddSelection" | "RemoveSelection" | "SetSelection" => vec![DbusValue::Bool(false)],
+            _ => return Err(unknown_method(msg))
+        })
+    }
+    
+    fn handle_properties(&self, target: AtspiTarget, msg: &DbusMessage) -> AtspiResult {
+        let properties = self.properties(target, &arg_str(msg, 0));
+        Ok(match msg.member.as_ref().map( | s | s.as_str()).unwrap_or("") {
+            "Get" => {
+                let name = arg_str(msg, 1);
+                if let Some((_, value)) = properties.into_iter().find( | (n, _) | *n == name) {
+                    vec![DbusValue::variant(value)]
+                }
+                else {
+                    return Err(("org.freedesktop.DBus.Error.UnknownProperty", format!("no property {}", name)))
+                }
+            },
+            "GetAll" => vec![DbusValue::Array("{sv}".to_string(), properties.into_iter().map( | (name, value) | {
+                DbusValue::DictEntry(Box::new(DbusValue::str(name)), Box::new(DbusValue::variant(value)))
+            }).collect())],
+            _ => return Err(unknown_method(msg))
+        })
+    }
+    
+    fn introspect(&self, target: AtspiTarget) -> String {
+        let mut xml = String::from("<node>\n<interface name=\"org.freedesktop.DBus.Introspectable\"/>\n<interface name=\"org.freedesktop.DBus.Properties\"/>\n");
+        for interface in self.interfaces(target) {
+            xml.push_str(&format!("<interface name=\"{}\"/>\n", interface));
+        }
+        xml.push_str("</node>\n");
+        xml
+    }
+    
+    fn handle(&mut self, msg: &DbusMessage) -> AtspiResult {
+        let path = msg.path.clone().unwrap_or(String::new());
+        let interface = msg.interface.clone().unwrap_or(String::new());
+        if interface == "org.freedesktop.DBus.Peer" && msg.member.as_ref().map( | s | s.as_str()) == Some("Ping") {
+            return Ok(Vec::new())
+        }
+        let target = if let Some(target) = self.resolve(&path) {target} else {
+            return Err(("org.freedesktop.DBus.Error.UnknownObject", format!("no object at {}", path)))
+        };
+        match interface.as_str() {
+            "org.freedesktop.DBus.Introspectable" => Ok(vec![DbusValue::Str(self.introspect(target))]),
+            "org.freedesktop.DBus.Properties" => self.handle_properties(target, msg),
+            "org.a11y.atspi.Accessible" => self.handle_accessible(target, msg),
+            "org.a11y.atspi.Application" => Ok(vec![DbusValue::str("")]),
+            "org.a11y.atspi.Component" if self.node(target).is_some() => self.handle_component(target, msg),
+            "org.a11y.atspi.Action" if self.node(target).is_some() => self.handle_action(target, msg),
+            "org.a11y.atspi.Text" if self.text(target).is_some() => self.handle_text(target, msg),
+            _ => Err(unknown_method(msg))
+        }
+    }
+}
+
+impl CxAtspi {
+    pub fn status_action() -> StatusId {uid!()}
+    pub fn status_enabled() -> StatusId {uid!()}
+    
+    // the accessibility bus lives next to the session bus, ask the session bus where
+    fn bus_address() -> Option<String> {
+        if let Ok(address) = std::env::var("AT_SPI_BUS_ADDRESS") {
+            return Some(address)
+        }
+        let session_address = DbusConnection::session_bus_address() ?;
+        if let Ok(mut session) = DbusConnection::connect(&session_address) {
+            let reply = session.call(&DbusMessage::method_call("org.a11y.Bus", "/org/a11y/bus", "org.a11y.Bus", "GetAddress", vec![]));
+            if let Ok(reply) = reply {
+                if let Some(address) = reply.body.get(0).and_then( | v | v.as_str()) {
+                    return Some(address.to_string())
+                }
+            }
+        }
+        Some(session_address)
+    }
+    
+    // screen readers switch these on, until then nobody reads the tree and building it is wasted
+    fn read_status(session: &mut DbusConnection) -> bool {
+        ["IsEnabled", "ScreenReaderEnabled"].iter().any( | property | {
+            let reply = session.call(&DbusMessage::method_call(
+                "org.a11y.Bus",
+                "/org/a11y/bus",
+                "org.freedesktop.DBus.Properties",
+                "Get",
+                vec![DbusValue::str("org.a11y.Status"), DbusValue::str(property)]
+            ));
+            match reply {
+                Ok(reply) => reply.body.get(0) == Some(&DbusValue::variant(DbusValue::Bool(true))),
+                Err(_) => false
+            }
+        })
+    }
+    
+    // keeps an eye on the status so a screen reader started after us still gets the tree
+    fn watch_status(signal: Signal) -> Result<(), String> {
+        let address = DbusConnection::session_bus_address().ok_or("no dbus session".to_string()) ?;
+        let mut session = DbusConnection::connect(&address) ?;
+        session.call(&DbusMessage::method_call(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "AddMatch",
+            vec![DbusValue::str("type='signal',interface='org.freedesktop.DBus.Properties',member='PropertiesChanged',path='/org/a11y/bus'")]
+        )) ?;
+        loop {
+            if Self::read_status(&mut session) {
+                Cx::post_signal(signal, Self::status_enabled());
+                return Ok(())
+            }
+            loop {
+                let msg = session.next_message() ?;
+                if msg.message_type == DbusMessageType::Signal && msg.body.get(0).and_then( | v | v.as_str()) == Some("org.a11y.Status") {
+                    break;
+                }
+            }
+        }
+    }
+    
+    fn run(shared: Arc<Mutex<AtspiShared>>, signal: Signal) -> Result<(), String> {
+        let address = Self::bus_address().ok_or("no dbus session".to_string()) ?;
+        let mut connection = DbusConnection::connect(&address) ?;
+        if let Ok(mut shared) = shared.lock() {
+            shared.sender = Some(connection.sender.clone());
+            shared.unique_name = connection.unique_name.clone();
+        }
+        // tell the registry we exist. dont wait for it, it might not be running
+        connection.send(&DbusMessage::method_call(
+            "org.a11y.atspi.Registry",
+            ATSPI_ROOT_PATH,
+            "org.a11y.atspi.Socket",
+            "Embed",
+            vec![DbusValue::Struct(vec![DbusValue::Str(connection.unique_name.clone()), DbusValue::ObjectPath(ATSPI_ROOT_PATH.to_string())])]
+        )) ?;
+        
+        loop {
+            let msg = connection.next_message() ?;
+            if msg.message_type != DbusMessageType::MethodCall {
+                continue;
+            }
+            let (result, action) = if let Ok(mut shared) = shared.lock() {
+                let actions = shared.actions.len();
+                let result = shared.handle(&msg);
+                (result, shared.actions.len() > actions)
+            }
+            else {
+                return Err("atspi state poisoned".to_string())
+            };
+            if msg.wants_reply() {
+                connection.send(&match result {
+                    Ok(body) => msg.method_return(body),
+                    Err((name, text)) => msg.error(name, &text)
+                }) ?;
+            }
+            if action {
+                Cx::post_signal(signal, Self::status_action());
+            }
+        }
+    }
+    
+    pub fn start(signal: Signal) -> Option<CxAtspi> {
+        if std::env::var("NO_AT_BRIDGE").map( | v | v == "1").unwrap_or(false) {
+            return None
+        }
+        let shared = Arc::new(Mutex::new(AtspiShared::default()));
+        let thread_shared = shared.clone();
+        std::thread::spawn(move || {
+            let _ = Self::run(thread_shared, signal);
+        });
+        std::thread::spawn(move || {
+            let _ = Self::watch_status(signal);
+        });
+        Some(CxAtspi {
+            shared: shared,
+            signal: signal,
+            focused: None,
+            last_key_focus: Area::Empty,
+            dirty: true,
+        })
+    }
+    
+    fn emit_focus(&self, shared: &AtspiShared, key: u64, focused: bool) {
+        if let Some(sender) = &shared.sender {
+            let _ = sender.send(&DbusMessage::signal(
+                &node_path(key),
+                "org.a11y.atspi.Event.Object",
+                "StateChanged",
+                vec![
+                    DbusValue::str("focused"),
+                    DbusValue::Int32(if focused {1} else {0}),
+                    DbusValue::Int32(0),
+                    DbusValue::variant(DbusValue::Int32(0)),
+                    DbusValue::Array("{sv}".to_string(), Vec::new())
+                ]
+            ));
+        }
+    }
+    
+    // hands the trees of all open windows to the bridge thread
+    pub fn publish(&mut self, cx: &Cx) {
+        let mut trees = Vec::new();
+        for (window_id, window) in cx.windows.iter().enumerate() {
+            if let CxWindowState::Created = window.window_state {
+                trees.push(cx.get_access_tree(window_id));
+            }
+        }
+        let focused = trees.iter().find_map( | tree | tree.focused().map( | index | tree.nodes[index].key));
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.app_name = cx.windows.iter().map( | window | window.title.clone()).next().unwrap_or(String::new());
+            shared.trees = trees;
+            if focused != self.focused {
+                if let Some(key) = self.focused {
+                    self.emit_focus(&shared, key, false);
+                }
+                if let Some(key) = focused {
+                    self.emit_focus(&shared, key, true);
+                }
+            }
+        }
+        self.focused = focused;
+        self.last_key_focus = cx.key_focus;
+        self.dirty = false;
+    }
+}
+
+impl Cx {
+    pub(crate) fn atspi_start(&mut self) {
+        let signal = self.new_signal();
+        self.platform.atspi = CxAtspi::start(signal);
+    }
+    
+    pub(crate) fn atspi_publish(&mut self) {
+        if !self.access.enabled {
+            return
+        }
+        if let Some(mut atspi) = self.platform.atspi.take() {
+            if self.take_access_changed() || atspi.dirty || atspi.last_key_focus != self.key_focus {
+                atspi.publish(self);
+            }
+            self.platform.atspi = Some(atspi);
+        }
+    }
+    
+    pub(crate) fn atspi_geom_changed(&mut self) {
+        if let Some(atspi) = &mut self.platform.atspi {
+            atspi.dirty = true;
+        }
+    }
+    
+    // the bridge threads post their signal when a screen reader showed up or asked for an action
+    pub(crate) fn atspi_handle_signal<F>(&mut self, mut event_handler: F, se: &SignalEvent)
+    where F: FnMut(&mut Cx, &mut Event)
+    {
+        let (signal, shared) = if let Some(atspi) = &self.platform.atspi {
+            (atspi.signal, atspi.shared.clone())
+        }
+        else {
+            return
+        };
+        let statusses = if let Some(statusses) = se.signals.get(&signal) {statusses} else {return};
+        if statusses.contains(&CxAtspi::status_enabled()) {
+            self.enable_accessibility();
+        }
+        let actions = if let Ok(mut shared) = shared.lock() {
+            std::mem::replace(&mut shared.actions, Vec::new())
+        }
+        else {
+            Vec::new()
+        };
+        for (key, action) in actions {
+            self.call_access_action(&mut event_handler, key, action);
+        }
+    }
+}
@@ -60,6 +60,8 @@ pub struct CocoaApp {
     pub cocoa_windows: Vec<(id, id)>,
     pub last_key_mod: KeyModifiers,
     pub pasteboard: id,
+    pub pasteboard_html_type: id,
+    pub pasteboard_png_type: id,
     pub startup_focus_hack_ran: bool,
     pub event_callback: Option<*mut dyn FnMut(&mut CocoaApp, &mut Vec<Event>) -> bool>,
     pub event_recur_block: bool,
@@ -105,6 +107,8 @@ impl CocoaApp {
                 startup_focus_hack_ran: false,
                 const_empty_string: str_to_nsstring(""),
                 pasteboard: msg_send![class!(NSPasteboard), generalPasteboard],
+                pasteboard_html_type: str_to_nsstring("public.html"),
+                pasteboard_png_type: str_to_nsstring("public.png"),
                 time_start: mach_absolute_time(),
                 timer_delegate_instance: timer_delegate_instance,
                 timer_delegate_class: timer_delegate_class,
@@ -592,6 +596,56 @@ impl CocoaApp {
         }
     }
     
+    // the pasteboard has a single clipboard, primary writes stay inside the app
+    pub fn clipboard_sync(&mut self, clipboard: &mut CxClipboard) {
+        for (selection, content) in clipboard.writes.drain(..) {
+            if selection == ClipboardSelection::Clipboard {
+                self.clipboard_write(&content);
+            }
+        }
+        if clipboard.reads.iter().any( | read | read.selection == ClipboardSelection::Clipboard) {
+            clipboard.clipboard = self.clipboard_content();
+        }
+    }
+    
+    pub fn clipboard_write(&mut self, content: &ClipboardContent) {
+        unsafe {
+            let mut types = Vec::new();
+            if content.text.is_some() {types.push(NSStringPboardType)}
+            if content.html.is_some() {types.push(self.pasteboard_html_type)}
+            if content.png.is_some() {types.push(self.pasteboard_png_type)}
+            let array: id = msg_send![class!(NSArray), arrayWithObjects: types.as_ptr() count: types.len()];
+            let () = msg_send![self.pasteboard, declareTypes: array owner: nil];
+            if let Some(text) = &content.text {
+                let () = msg_send![self.pasteboard, setString: str_to_nsstring(text) forType: NSStringPboardType];
+            }
+            if let Some(html) = &content.html {
+                let () = msg_send![self.pasteboard, setString: str_to_nsstring(html) forType: self.pasteboard_html_type];
+            }
+            if let Some(png) = &content.png {
+                let data: id = msg_send![class!(NSData), dataWithBytes: png.as_ptr() length: png.len()];
+                let () = msg_send![self.pasteboard, setData: data forType: self.pasteboard_png_type];
+            }
+        }
+    }
+    
+    pub fn clipboard_content(&mut self) -> ClipboardContent {
+        unsafe {
+            let text: id = msg_send![self.pasteboard, stringForType: NSStringPboardType];
+            let html: id = msg_send![self.pasteboard, stringForType: self.pasteboard_html_type];
+            let png: id = msg_send![self.pasteboard, dataForType: self.pasteboard_png_type];
+            ClipboardContent {
+                text: if text != nil {Some(nsstring_to_string(text))} else {None},
+                html: if html != nil {Some(nsstring_to_string(html))} else {None},
+                png: if png != nil {
+                    let bytes: *const u8 = msg_send![png, bytes];
+                    let length: usize = msg_send![png, length];
+                    Some(std::slice::from_raw_parts(bytes, length).to_vec())
+                } else {None},
+            }
+        }
+    }
+    
     pub fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
         if self.current_cursor != cursor {
             self.current_cursor = cursor;
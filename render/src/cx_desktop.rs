@@ -3,6 +3,7 @@ use std::io::prelude::*;
 use std::fs::File;
 use std::io;
 use std::net::TcpStream;
+use std::process::Command;
 //use time::precise_time_ns;
 
 #[derive(Clone)]
@@ -65,12 +66,39 @@ impl Cx {
                 self.fingers[fe.digit].over_last = Area::Empty;
                 //self.hover_mouse_cursor = None;
             },
-            Event::FingerUp(_fe) => {
+            Event::FingerMove(fe) => {
+                if self.drag.payload.is_some() {
+                    let action = self.call_drag_event(&mut event_handler, fe.window_id, fe.abs);
+                    self.down_mouse_cursor = match action {
+                        DragAction::None => self.drag.source_cursor.clone(),
+                        DragAction::Copy | DragAction::Move => Some(MouseCursor::Move)
+                    };
+                }
+            },
+            Event::FingerUp(fe) => {
                 self.down_mouse_cursor = None;
+                if self.drag.payload.is_some() {
+                    self.call_drop_event(&mut event_handler, fe.window_id, fe.abs);
+                }
+            },
+            // drags from other applications, the platform reads the action back
+            Event::Drag(de) => {
+                self.drag.payload = Some(de.payload.clone());
+                self.drag.external = true;
+                de.action = self.call_drag_event(&mut event_handler, de.window_id, de.abs);
+            },
+            Event::Drop(de) => {
+                self.drag.payload = Some(de.payload.clone());
+                self.drag.external = true;
+                de.action = self.call_drop_event(&mut event_handler, de.window_id, de.abs);
+            },
+            Event::DragEnd => {
+                self.call_drag_end_event(&mut event_handler);
             },
             Event::WindowCloseRequested(_cr) => {
             },
             Event::FingerDown(fe) => {
+                self.focus.ring_visible = false;
                 // lets set the finger tap count
                 fe.tap_count = self.process_tap_count(fe.digit, fe.abs, fe.time);
             },
@@ -84,6 +112,19 @@ impl Cx {
                         self.panic_now = true;
                     }
                 }
+                match self.keymap_process_key_down(&ke.clone()) {
+                    KeymapResult::Command(command) => if command == Cx::command_toggle_layout_debug() {
+                        self.toggle_layout_debug();
+                        *event = Event::None;
+                    }
+                    else {
+                        *event = Event::Command(command)
+                    },
+                    KeymapResult::Pending => *event = Event::None,
+                    KeymapResult::None | KeymapResult::Cancelled => if self.process_focus_key_down(&ke.clone()) {
+                        *event = Event::None;
+                    }
+                }
             },
             Event::KeyUp(ke) => {
                 self.process_key_up(&ke);
@@ -145,6 +186,12 @@ impl Cx {
         
         self.process_desktop_file_reads(&mut event_handler);
         
+        // linux answers clipboard reads from the X11 selections, macos and windows synced theirs
+        // into the local clipboard at the start of the paint
+        if !cfg!(target_os = "linux") {
+            self.call_clipboard_local_reads(&mut event_handler);
+        }
+        
         self.call_signals(&mut event_handler);
         
         vsync
@@ -220,6 +267,7 @@ impl Cx {
                 println!("Error loading font {} ", file);
             }
         }
+        self.apply_live_font_fallbacks();
     }
     
     /*pub fn log(&mut self, val:&str){
@@ -228,6 +276,22 @@ impl Cx {
         let _e = stdout.flush();
     }*/
     
+    // hands the url to whatever the os opens links with
+    pub fn open_url(&mut self, url: &str) {
+        let result = if cfg!(target_os = "macos") {
+            Command::new("open").arg(url).spawn()
+        }
+        else if cfg!(target_os = "windows") {
+            Command::new("cmd").args(&["/C", "start", "", url]).spawn()
+        }
+        else {
+            Command::new("xdg-open").arg(url).spawn()
+        };
+        if let Err(err) = result {
+            log!("Cannot open {}: {}", url, err);
+        }
+    }
+    
     pub fn write_log(data: &str) {
         let _ = io::stdout().write(data.as_bytes());
         let _ = io::stdout().flush();
@@ -23,6 +23,8 @@ impl Cx {
         
         self.redraw_child_area(Area::All);
         
+        self.atspi_start();
+        
         let mut passes_todo = Vec::new();
         
         xlib_app.event_loop( | xlib_app, events | {
@@ -47,6 +49,7 @@ impl Cx {
                             }
                             break;
                         }}
+                        self.atspi_geom_changed();
                         // ok lets not redraw all, just this window
                         self.call_event_handler(&mut event_handler, &mut event);
                     },
@@ -154,6 +157,18 @@ impl Cx {
                             xlib_app.stop_timer(timer_id);
                         }
                         
+                        if let Some(payload) = self.drag.outgoing.take() {
+                            xlib_app.start_dragging(payload);
+                        }
+                        
+                        for (selection, content) in self.clipboard.writes.drain(..) {
+                            xlib_app.clipboard_write(selection, content);
+                        }
+                        
+                        for read in self.clipboard.reads.drain(..) {
+                            xlib_app.clipboard_read(read);
+                        }
+                        
                         // build a list of renderpasses to repaint
                         let mut windows_need_repaint = 0;
                         self.compute_passes_to_repaint(&mut passes_todo, &mut windows_need_repaint);
@@ -215,13 +230,17 @@ impl Cx {
                                 }
                             }
                         }
+                        self.atspi_publish();
                     },
-                    Event::Signal {..} => {
+                    Event::Signal(se) => {
+                        self.atspi_handle_signal(&mut event_handler, se);
                         self.call_event_handler(&mut event_handler, &mut event);
                         self.call_signals(&mut event_handler);
                     },
                     Event::None => {
                     },
+                    Event::Drag(_) | Event::Drop(_) | Event::DragEnd => { // already sent in pre_event
+                    },
                     _ => {
                         self.call_event_handler(&mut event_handler, &mut event);
                     }
@@ -238,7 +257,7 @@ impl Cx {
             self.shader_recompiles.truncate(0);
             self.call_shader_recompile_event(shader_results, &mut event_handler);
             */
-            if !paint_dirty && self.playing_anim_areas.len() == 0 && self.redraw_parent_areas.len() == 0 && self.redraw_child_areas.len() == 0 && self.frame_callbacks.len() == 0 {
+            if !paint_dirty && !self.has_running_anims() && self.redraw_parent_areas.len() == 0 && self.redraw_child_areas.len() == 0 && self.frame_callbacks.len() == 0 {
                 true
             } else {
                 false
@@ -292,4 +311,5 @@ pub struct CxPlatform {
     pub stop_timer: Vec<u64>,
     pub text_clipboard_response: Option<String>,
     pub desktop: CxDesktop,
+    pub atspi: Option<CxAtspi>,
 }
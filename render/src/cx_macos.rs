@@ -75,6 +75,8 @@ impl Cx {
                     },
                     Event::Paint => {
                         
+                        cocoa_app.clipboard_sync(&mut self.clipboard);
+                        
                         let vsync = self.process_desktop_paint_callbacks(cocoa_app.time_now(), &mut event_handler);
                         
                         // construct or destruct windows
@@ -251,7 +253,7 @@ impl Cx {
             }*/
                 
             
-            if self.playing_anim_areas.len() == 0 && self.redraw_parent_areas.len() == 0 && self.redraw_child_areas.len() == 0 && self.frame_callbacks.len() == 0 {
+            if !self.has_running_anims() && self.redraw_parent_areas.len() == 0 && self.redraw_child_areas.len() == 0 && self.frame_callbacks.len() == 0 {
                 true
             } else {
                 false
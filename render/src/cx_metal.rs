@@ -528,6 +528,22 @@ impl MetalCx {
             cxtexture.platform.width = width as u64;
             cxtexture.platform.height = height as u64;
         }
+        else if let Some(mtl_texture) = cxtexture.platform.mtl_texture {
+            // same size, just upload the new pixels
+            if cxtexture.image_u32.len() == width * height {
+                let region = MTLRegion {
+                    origin: MTLOrigin {x: 0, y: 0, z: 0},
+                    size: MTLSize {width: width as u64, height: height as u64, depth: 1}
+                };
+                let () = unsafe {msg_send![
+                    mtl_texture,
+                    replaceRegion: region
+                    mipmapLevel: 0
+                    withBytes: cxtexture.image_u32.as_ptr() as *const std::ffi::c_void
+                    bytesPerRow: (width * std::mem::size_of::<u32>()) as u64
+                ]};
+            }
+        }
         
         cxtexture.update_image = false;
     }
@@ -1037,6 +1037,14 @@ impl OpenglCx {
                 gl::BindTexture(gl::TEXTURE_2D, 0);
             }
         }
+        else if let Some(gl_texture) = cxtexture.platform.gl_texture {
+            // same size, just upload the new pixels
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, gl_texture);
+                gl::TexSubImage2D(gl::TEXTURE_2D, 0, 0, 0, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE, cxtexture.image_u32.as_ptr() as *const _);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+        }
         
         cxtexture.update_image = false;
     }
@@ -84,6 +84,7 @@ impl Cx {
                             }
                         }
                     }
+                    self.apply_live_font_fallbacks();
                 },
                 3 => { // init
                     self.platform.window_geom = WindowGeom {
@@ -155,6 +156,7 @@ impl Cx {
                     let modifiers = unpack_key_modifier(to_wasm.mu32());
                     let time = to_wasm.mf64();
                     let tap_count = self.process_tap_count(digit, abs, time);
+                    self.focus.ring_visible = false;
                     self.call_event_handler(&mut event_handler, &mut Event::FingerDown(FingerDownEvent {
                         window_id: 0,
                         abs: abs,
@@ -178,6 +180,9 @@ impl Cx {
                     let is_touch = to_wasm.mu32()>0;
                     let modifiers = unpack_key_modifier(to_wasm.mu32());
                     let time = to_wasm.mf64();
+                    if self.drag.payload.is_some() {
+                        self.call_drop_event(&mut event_handler, 0, abs);
+                    }
                     self.call_event_handler(&mut event_handler, &mut Event::FingerUp(FingerUpEvent {
                         window_id: 0,
                         abs: abs,
@@ -200,6 +205,9 @@ impl Cx {
                     let is_touch = to_wasm.mu32()>0;
                     let modifiers = unpack_key_modifier(to_wasm.mu32());
                     let time = to_wasm.mf64();
+                    if self.drag.payload.is_some() {
+                        self.call_drag_event(&mut event_handler, 0, abs);
+                    }
                     self.call_event_handler(&mut event_handler, &mut Event::FingerMove(FingerMoveEvent {
                         window_id: 0,
                         abs: abs,
@@ -286,7 +294,22 @@ impl Cx {
                         time: to_wasm.mf64()
                     };
                     self.process_key_down(key_event.clone());
-                    self.call_event_handler(&mut event_handler, &mut Event::KeyDown(key_event));
+                    match self.keymap_process_key_down(&key_event) {
+                        KeymapResult::Command(command) => if command == Cx::command_toggle_layout_debug() {
+                            self.toggle_layout_debug();
+                        }
+                        else {
+                            self.call_event_handler(&mut event_handler, &mut Event::Command(command))
+                        },
+                        KeymapResult::Pending => (),
+                        // the focus change still needs an event to go out with
+                        KeymapResult::None | KeymapResult::Cancelled => if self.process_focus_key_down(&key_event) {
+                            self.call_event_handler(&mut event_handler, &mut Event::None)
+                        }
+                        else {
+                            self.call_event_handler(&mut event_handler, &mut Event::KeyDown(key_event))
+                        }
+                    }
                 },
                 13 => { // key up
                     let key_event = KeyEvent {
@@ -300,10 +323,17 @@ impl Cx {
                     self.call_event_handler(&mut event_handler, &mut Event::KeyUp(key_event));
                 },
                 14 => { // text input
+                    let was_paste = to_wasm.mu32()>0;
+                    let replace_last = to_wasm.mu32()>0;
+                    let input = to_wasm.parse_string();
+                    // the browser only hands out its clipboard on paste, keep it for clipboard reads
+                    if was_paste {
+                        self.clipboard.clipboard = ClipboardContent::text(&input);
+                    }
                     self.call_event_handler(&mut event_handler, &mut Event::TextInput(TextInputEvent {
-                        was_paste: to_wasm.mu32()>0,
-                        replace_last: to_wasm.mu32()>0,
-                        input: to_wasm.parse_string(),
+                        was_paste: was_paste,
+                        replace_last: replace_last,
+                        input: input,
                     }));
                 },
                 15 => { // file read data
@@ -332,6 +362,7 @@ impl Cx {
                     self.call_event_handler(&mut event_handler, &mut event);
                     match &event {
                         Event::TextCopy(req) => if let Some(response) = &req.response {
+                            self.clipboard.clipboard = ClipboardContent::text(response);
                             self.platform.from_wasm.text_copy_response(&response);
                         }
                         _ => ()
@@ -423,6 +454,10 @@ impl Cx {
             };
         };
         
+        // writes stay inside the app, the browser clipboard is only reachable from its copy event
+        self.clipboard.writes.truncate(0);
+        self.call_clipboard_local_reads(&mut event_handler);
+        
         self.call_signals(&mut event_handler);
         
         if is_animation_frame && (self.redraw_child_areas.len()>0 || self.redraw_parent_areas.len()>0) {
@@ -509,7 +544,7 @@ impl Cx {
         
         // request animation frame if still need to redraw, or repaint
         // we use request animation frame for that.
-        if !(passes_todo.len() == 0 && self.playing_anim_areas.len() == 0 && self.redraw_parent_areas.len() == 0 && self.redraw_child_areas.len() == 0 && self.frame_callbacks.len() == 0) {
+        if !(passes_todo.len() == 0 && !self.has_running_anims() && self.redraw_parent_areas.len() == 0 && self.redraw_child_areas.len() == 0 && self.frame_callbacks.len() == 0) {
             self.platform.from_wasm.request_animation_frame();
         }
         
@@ -591,6 +626,10 @@ impl Cx {
     pub fn update_menu(&mut self, _menu: &Menu) {
         
     }
+    
+    pub fn open_url(&mut self, url: &str) {
+        self.platform.from_wasm.open_url(url);
+    }
 }
 
 
@@ -1216,6 +1255,12 @@ impl FromWasm {
         self.fit(1);
         self.mu32(29);
     }
+    
+    pub fn open_url(&mut self, url: &str) {
+        self.fit(1);
+        self.mu32(30);
+        self.add_string(url);
+    }
 }
 
 #[derive(Clone)]
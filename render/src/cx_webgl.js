@@ -2009,7 +2009,10 @@
         },
         function normalscreen_29(self) {
             self.normalscreen();
-        }, 
+        },
+        function open_url_30(self) {
+            window.open(self.parse_string(), "_blank");
+        },
     ]
     
     WasmApp.prototype.uniform_fn_table = {
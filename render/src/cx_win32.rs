@@ -37,6 +37,8 @@ pub struct Win32App {
     pub current_cursor: MouseCursor,
     pub status_to_usize: HashMap<StatusId, usize>,
     pub usize_to_status: HashMap<usize, StatusId>,
+    pub clipboard_html_format: UINT,
+    pub clipboard_png_format: UINT,
 }
 
 #[derive(Clone)]
@@ -105,6 +107,8 @@ impl Win32App {
             current_cursor: MouseCursor::Default,
             status_to_usize: HashMap::new(),
             usize_to_status: HashMap::new(),
+            clipboard_html_format: Self::register_clipboard_format("HTML Format"),
+            clipboard_png_format: Self::register_clipboard_format("PNG"),
         };
         
         win32_app.dpi_functions.become_dpi_aware();
@@ -304,6 +308,89 @@ impl Win32App {
         (time_now - self.time_start) as f64 / 1_000_000_000.0
     }
     
+    fn register_clipboard_format(name: &str) -> UINT {
+        let name_wstr: Vec<u16> = OsStr::new(name).encode_wide().chain(Some(0).into_iter()).collect();
+        unsafe {winuser::RegisterClipboardFormatW(name_wstr.as_ptr())}
+    }
+    
+    // windows has a single clipboard, primary writes stay inside the app
+    pub fn clipboard_sync(&mut self, clipboard: &mut CxClipboard) {
+        for (selection, content) in clipboard.writes.drain(..) {
+            if selection == ClipboardSelection::Clipboard {
+                self.clipboard_write(&content);
+            }
+        }
+        if clipboard.reads.iter().any( | read | read.selection == ClipboardSelection::Clipboard) {
+            if let Some(content) = self.clipboard_content() {
+                clipboard.clipboard = content;
+            }
+        }
+    }
+    
+    pub fn clipboard_write(&mut self, content: &ClipboardContent) {
+        unsafe {
+            if winuser::OpenClipboard(ptr::null_mut()) == 0 {
+                return
+            }
+            winuser::EmptyClipboard();
+            if let Some(text) = &content.text {
+                let data: Vec<u8> = OsStr::new(text).encode_wide().chain(Some(0).into_iter()).flat_map( | c | c.to_le_bytes().to_vec()).collect();
+                Self::set_clipboard_data(winuser::CF_UNICODETEXT, &data);
+            }
+            if let Some(html) = &content.html {
+                let mut data = cf_html_from_html(html);
+                data.push(0);
+                Self::set_clipboard_data(self.clipboard_html_format, &data);
+            }
+            if let Some(png) = &content.png {
+                Self::set_clipboard_data(self.clipboard_png_format, png);
+            }
+            winuser::CloseClipboard();
+        }
+    }
+    
+    // none when another app holds the clipboard open
+    pub fn clipboard_content(&mut self) -> Option<ClipboardContent> {
+        unsafe {
+            if winuser::OpenClipboard(ptr::null_mut()) == 0 {
+                return None
+            }
+            let text = Self::get_clipboard_data(winuser::CF_UNICODETEXT).and_then( | data | {
+                let data: Vec<u16> = data.chunks_exact(2).map( | c | u16::from_le_bytes([c[0], c[1]])).take_while( | c | *c != 0).collect();
+                String::from_utf16(&data).ok()
+            });
+            let html = Self::get_clipboard_data(self.clipboard_html_format).and_then( | data | html_from_cf_html(&data));
+            let png = Self::get_clipboard_data(self.clipboard_png_format);
+            winuser::CloseClipboard();
+            Some(ClipboardContent {
+                text: text,
+                html: html,
+                png: png
+            })
+        }
+    }
+    
+    unsafe fn set_clipboard_data(format: UINT, data: &[u8]) {
+        let h_clipboard_data = winbase::GlobalAlloc(winbase::GMEM_MOVEABLE, data.len());
+        let h_clipboard_ptr = winbase::GlobalLock(h_clipboard_data) as *mut u8;
+        std::ptr::copy_nonoverlapping(data.as_ptr(), h_clipboard_ptr, data.len());
+        winbase::GlobalUnlock(h_clipboard_data);
+        winuser::SetClipboardData(format, h_clipboard_data);
+    }
+    
+    unsafe fn get_clipboard_data(format: UINT) -> Option<Vec<u8>> {
+        let h_clipboard_data = winuser::GetClipboardData(format);
+        if h_clipboard_data.is_null() {
+            return None
+        }
+        let h_clipboard_ptr = winbase::GlobalLock(h_clipboard_data) as *const u8;
+        if h_clipboard_ptr.is_null() {
+            return None
+        }
+        let data = std::slice::from_raw_parts(h_clipboard_ptr, winbase::GlobalSize(h_clipboard_data)).to_vec();
+        winbase::GlobalUnlock(h_clipboard_data);
+        Some(data)
+    }
     
     pub fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
         if self.current_cursor != cursor {
@@ -87,6 +87,7 @@ impl Cx {
                     },
                     Event::Paint => {
                         self.repaint_id += 1;
+                        win32_app.clipboard_sync(&mut self.clipboard);
                         let vsync = self.process_desktop_paint_callbacks(win32_app.time_now(), &mut event_handler);
                         
                         // construct or destruct windows
@@ -258,7 +259,7 @@ impl Cx {
             self.shader_recompiles.truncate(0);
             self.call_shader_recompile_event(shader_results, &mut event_handler);
             */
-            if self.playing_anim_areas.len() == 0 && self.redraw_parent_areas.len() == 0 && self.redraw_child_areas.len() == 0 && self.frame_callbacks.len() == 0 {
+            if !self.has_running_anims() && self.redraw_parent_areas.len() == 0 && self.redraw_child_areas.len() == 0 && self.frame_callbacks.len() == 0 {
                 true
             } else {
                 false
@@ -20,9 +20,15 @@ static mut GLOBAL_XLIB_APP: *mut XlibApp = 0 as *mut _;
 pub struct XlibApp {
     pub display: *mut X11_sys::Display,
     pub xim: X11_sys::XIM,
-    pub clipboard: String,
+    pub clipboard: ClipboardContent,
+    pub primary: ClipboardContent,
+    // async reads, one format of the front one is converted at a time
+    pub clipboard_reads: VecDeque<CxClipboardRead>,
+    pub clipboard_read_format: usize,
+    pub clipboard_read_content: ClipboardContent,
     pub display_fd: c_int,
     pub signal_fd: c_int,
+    pub signal_write_fd: c_int,
     pub window_map: HashMap<c_ulong, *mut XlibWindow>,
     pub time_start: u64,
     pub last_scroll_time: f64,
@@ -50,6 +56,10 @@ pub struct XlibApp {
     pub atom_multiple: X11_sys::Atom,
     pub atom_text_plain: X11_sys::Atom,
     pub atom_atom: X11_sys::Atom,
+    pub atom_primary: X11_sys::Atom,
+    pub atom_text_html: X11_sys::Atom,
+    pub atom_image_png: X11_sys::Atom,
+    pub atom_clipboard_read: X11_sys::Atom,
     
     pub dnd: Dnd,
 }
@@ -104,7 +114,12 @@ impl XlibApp {
             let display = X11_sys::XOpenDisplay(ptr::null());
             let display_fd = X11_sys::XConnectionNumber(display);
             let xim = X11_sys::XOpenIM(display, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
-            let signal_fd = 0i32; //libc::pipe();
+            // other threads post signals while we sit in select, a byte down this pipe wakes it up
+            let mut signal_fds = [0 as c_int; 2];
+            libc::pipe(signal_fds.as_mut_ptr());
+            libc::fcntl(signal_fds[0], libc::F_SETFL, libc::O_NONBLOCK);
+            let signal_fd = signal_fds[0];
+            let signal_write_fd = signal_fds[1];
             XlibApp {
                 atom_clipboard: X11_sys::XInternAtom(display, CString::new("CLIPBOARD").unwrap().as_ptr(), 0),
                 atom_net_wm_moveresize: X11_sys::XInternAtom(display, CString::new("_NET_WM_MOVERESIZE").unwrap().as_ptr(), 0),
@@ -119,11 +134,20 @@ impl XlibApp {
                 atom_text: X11_sys::XInternAtom(display, CString::new("TEXT").unwrap().as_ptr(), 0),
                 atom_text_plain: X11_sys::XInternAtom(display, CString::new("text/plain").unwrap().as_ptr(), 0),
                 atom_multiple: X11_sys::XInternAtom(display, CString::new("MULTIPLE").unwrap().as_ptr(), 0),
+                atom_primary: X11_sys::XInternAtom(display, CString::new("PRIMARY").unwrap().as_ptr(), 0),
+                atom_text_html: X11_sys::XInternAtom(display, CString::new("text/html").unwrap().as_ptr(), 0),
+                atom_image_png: X11_sys::XInternAtom(display, CString::new("image/png").unwrap().as_ptr(), 0),
+                atom_clipboard_read: X11_sys::XInternAtom(display, CString::new("MAKEPAD_CLIPBOARD_READ").unwrap().as_ptr(), 0),
                 xim,
                 display,
                 display_fd,
                 signal_fd,
-                clipboard: String::new(),
+                signal_write_fd,
+                clipboard: ClipboardContent::default(),
+                primary: ClipboardContent::default(),
+                clipboard_reads: VecDeque::new(),
+                clipboard_read_format: 0,
+                clipboard_read_content: ClipboardContent::default(),
                 last_scroll_time: 0.0,
                 last_click_time: 0.0,
                 last_click_pos: (0, 0),
@@ -172,6 +196,7 @@ impl XlibApp {
                     let mut fds = mem::MaybeUninit::uninit();
                     libc::FD_ZERO(fds.as_mut_ptr());
                     libc::FD_SET(self.display_fd, fds.as_mut_ptr());
+                    libc::FD_SET(self.signal_fd, fds.as_mut_ptr());
                     // If there are any timers, we set the timeout for select to the `delta_timeout`
                     // of the first timer that should be fired. Otherwise, we set the timeout to
                     // None, so that select will block indefinitely.
@@ -189,7 +214,7 @@ impl XlibApp {
                         None
                     };
                     let _nfds = libc::select(
-                        self.display_fd + 1,
+                        self.display_fd.max(self.signal_fd) + 1,
                         fds.as_mut_ptr(),
                         ptr::null_mut(),
                         ptr::null_mut(),
@@ -232,63 +257,30 @@ impl XlibApp {
                     match event.type_ as u32 {
                         X11_sys::SelectionNotify => {
                             let selection = event.xselection;
-                            if selection.property == self.dnd.atoms.selection {
-                                self.dnd.handle_selection_event(&selection);
-                            } else {
-                                // first get the size of the thing
-                                let mut actual_type = mem::MaybeUninit::uninit();
-                                let mut actual_format = mem::MaybeUninit::uninit();
-                                let mut n_items = mem::MaybeUninit::uninit();
-                                let mut bytes_to_read = mem::MaybeUninit::uninit();
-                                let mut ret = mem::MaybeUninit::uninit();
-                                X11_sys::XGetWindowProperty(
-                                    self.display,
-                                    selection.requestor,
-                                    selection.property,
-                                    0,
-                                    0,
-                                    0,
-                                    X11_sys::AnyPropertyType as c_ulong,
-                                    actual_type.as_mut_ptr(),
-                                    actual_format.as_mut_ptr(),
-                                    n_items.as_mut_ptr(),
-                                    bytes_to_read.as_mut_ptr(),
-                                    ret.as_mut_ptr()
-                                );
-                                //let actual_type = actual_type.assume_init();
-                                //let actual_format = actual_format.assume_init();
-                                //let n_items = n_items.assume_init();
-                                let bytes_to_read = bytes_to_read.assume_init();
-                                //let mut ret = ret.assume_init();
-                                let mut bytes_after = mem::MaybeUninit::uninit();
-                                X11_sys::XGetWindowProperty(
-                                    self.display,
-                                    selection.requestor,
-                                    selection.property,
-                                    0,
-                                    bytes_to_read as c_long,
-                                    0,
-                                    X11_sys::AnyPropertyType as c_ulong,
-                                    actual_type.as_mut_ptr(),
-                                    actual_format.as_mut_ptr(),
-                                    n_items.as_mut_ptr(),
-                                    bytes_after.as_mut_ptr(),
-                                    ret.as_mut_ptr()
-                                );
-                                let ret = ret.assume_init();
-                                //let bytes_after = bytes_after.assume_init();
-                                if ret != ptr::null_mut() && bytes_to_read > 0 {
-                                    let utf8_slice = std::slice::from_raw_parts::<u8>(ret as *const _ as *const u8, bytes_to_read as usize);
-                                    if let Ok(utf8_string) = String::from_utf8(utf8_slice.to_vec()) {
-                                        self.do_callback(&mut vec![
-                                            Event::TextInput(TextInputEvent {
-                                                input: utf8_string,
-                                                was_paste: true,
-                                                replace_last: false
-                                            })
-                                        ]);
-                                    }
-                                    X11_sys::XFree(ret as *mut _ as *mut c_void);
+                            if selection.selection == self.dnd.atoms.selection {
+                                if let Some(window_ptr) = self.window_map.get(&selection.requestor) {
+                                    let window = &mut (**window_ptr);
+                                    self.dnd.handle_selection_event(&selection, window);
+                                }
+                            }
+                            else if self.is_clipboard_read_answer(&selection) {
+                                let data = if selection.property != 0 {
+                                    self.get_selection_property(selection.requestor, selection.property)
+                                }
+                                else {
+                                    None
+                                };
+                                self.handle_clipboard_read_answer(data);
+                            }
+                            else if let Some(data) = self.get_selection_property(selection.requestor, selection.property) {
+                                if let Ok(utf8_string) = String::from_utf8(data) {
+                                    self.do_callback(&mut vec![
+                                        Event::TextInput(TextInputEvent {
+                                            input: utf8_string,
+                                            was_paste: true,
+                                            replace_last: false
+                                        })
+                                    ]);
                                 }
                             }
                         },
@@ -305,32 +297,12 @@ impl XlibApp {
                                 time: request.time,
                                 property: request.property,
                             };
-                            if request.target == self.atom_targets {
-                                let mut targets = [self.atom_utf8_string];
-                                X11_sys::XChangeProperty(
-                                    self.display,
-                                    request.requestor,
-                                    request.property,
-                                    4,
-                                    32,
-                                    X11_sys::PropModeReplace as i32,
-                                    targets.as_mut() as *mut _ as *mut c_uchar,
-                                    targets.len() as i32
-                                );
-                            }
-                            else if request.target == self.atom_utf8_string {
-                                X11_sys::XChangeProperty(
-                                    self.display,
-                                    request.requestor,
-                                    request.property,
-                                    self.atom_utf8_string,
-                                    8,
-                                    X11_sys::PropModeReplace as i32,
-                                    self.clipboard.as_ptr() as *const _ as *const c_uchar,
-                                    self.clipboard.len() as i32
-                                );
+                            if request.selection == self.dnd.atoms.selection {
+                                if !self.dnd.handle_selection_request(&request) {
+                                    response.property = 0;
+                                }
                             }
-                            else {
+                            else if !self.handle_clipboard_request(&request) {
                                 response.property = 0;
                             }
                             X11_sys::XSendEvent(self.display, request.requestor, 1, 0, &mut response as *mut _ as *mut X11_sys::XEvent);
@@ -453,6 +425,7 @@ impl XlibApp {
                                     }
                                 }
                             }
+                            self.dnd.handle_source_motion(&self.window_map, motion.x_root, motion.y_root, motion.time);
                         },
                         X11_sys::ButtonPress => { // mouse down
                             let button = event.xbutton;
@@ -538,6 +511,7 @@ impl XlibApp {
                         },
                         X11_sys::ButtonRelease => { // mouse up
                             let button = event.xbutton;
+                            self.dnd.handle_source_release(button.time);
                             if let Some(window_ptr) = self.window_map.get(&button.window) {
                                 let window = &mut (**window_ptr);
                                 window.send_finger_up(button.button as usize, self.xkeystate_to_modifiers(button.state))
@@ -582,9 +556,8 @@ impl XlibApp {
                                                 match &events[0] {
                                                     Event::TextCopy(req) => if let Some(response) = &req.response {
                                                         // store the text on the clipboard
-                                                        self.clipboard = response.clone();
+                                                        self.clipboard = ClipboardContent::text(response);
                                                         // lets set the owner
-                                                        println!("Set selection owner");
                                                         X11_sys::XSetSelectionOwner(
                                                             self.display,
                                                             self.atom_clipboard,
@@ -653,12 +626,19 @@ impl XlibApp {
                             let event = event.xclient;
                             if event.message_type == self.dnd.atoms.enter {
                                 self.dnd.handle_enter_event(&event);
-                            } else if event.message_type == self.dnd.atoms.drop {
-                                self.dnd.handle_drop_event(&event);
-                            } else if event.message_type == self.dnd.atoms.leave {
-                                self.dnd.handle_leave_event(&event);
-                            } else if event.message_type == self.dnd.atoms.position {
-                                self.dnd.handle_position_event(&event);
+                            } else if event.message_type == self.dnd.atoms.status {
+                                self.dnd.handle_status_event(&event);
+                            } else if event.message_type == self.dnd.atoms.finished {
+                                self.dnd.handle_finished_event(&event);
+                            } else if let Some(window_ptr) = self.window_map.get(&event.window) {
+                                let window = &mut (**window_ptr);
+                                if event.message_type == self.dnd.atoms.drop {
+                                    self.dnd.handle_drop_event(&event, window);
+                                } else if event.message_type == self.dnd.atoms.leave {
+                                    self.dnd.handle_leave_event(&event, window);
+                                } else if event.message_type == self.dnd.atoms.position {
+                                    self.dnd.handle_position_event(&event, window);
+                                }
                             }
                         },
                         X11_sys::Expose => {
@@ -673,6 +653,8 @@ impl XlibApp {
                     }
                 }
                 // process all signals in the queue
+                let mut drain = [0u8; 64];
+                while libc::read(self.signal_fd, drain.as_mut_ptr() as *mut c_void, drain.len()) > 0 {}
                 let mut proc_signals = if let Ok(mut signals) = self.signals.lock() {
                     let sigs = signals.clone();
                     signals.truncate(0);
@@ -706,6 +688,237 @@ impl XlibApp {
         }
     }
     
+    pub fn start_dragging(&mut self, payload: DragPayload) {
+        // the window the mouse went down in owns the drag
+        let window = self.window_map.values().find_map( | window_ptr | unsafe {
+            let window = &(**window_ptr);
+            if window.fingers_down.iter().any( | down | *down) {window.window} else {None}
+        });
+        if let Some(window) = window {
+            unsafe {self.dnd.start_source(window, payload)};
+        }
+    }
+    
+    pub fn clipboard_write(&mut self, selection: ClipboardSelection, content: ClipboardContent) {
+        let selection_atom = match selection {
+            ClipboardSelection::Clipboard => {
+                self.clipboard = content;
+                self.atom_clipboard
+            }
+            ClipboardSelection::Primary => {
+                self.primary = content;
+                self.atom_primary
+            }
+        };
+        // any of our windows can own the selection
+        if let Some(window) = self.any_window() {
+            unsafe {
+                X11_sys::XSetSelectionOwner(self.display, selection_atom, window, X11_sys::CurrentTime as X11_sys::Time);
+                X11_sys::XFlush(self.display);
+            }
+        }
+    }
+    
+    pub fn clipboard_read(&mut self, read: CxClipboardRead) {
+        self.clipboard_reads.push_back(read);
+        if self.clipboard_reads.len() == 1 {
+            self.clipboard_read_format = 0;
+            self.clipboard_read_content = ClipboardContent::default();
+            self.request_clipboard_read_format();
+        }
+    }
+    
+    fn any_window(&self) -> Option<c_ulong> {
+        self.window_map.values().find_map( | window_ptr | unsafe {(**window_ptr).window})
+    }
+    
+    fn clipboard_selection_atom(&self, selection: ClipboardSelection) -> X11_sys::Atom {
+        match selection {
+            ClipboardSelection::Clipboard => self.atom_clipboard,
+            ClipboardSelection::Primary => self.atom_primary,
+        }
+    }
+    
+    fn clipboard_format_atom(&self, format: ClipboardFormat) -> X11_sys::Atom {
+        match format {
+            ClipboardFormat::Text => self.atom_utf8_string,
+            ClipboardFormat::Html => self.atom_text_html,
+            ClipboardFormat::Png => self.atom_image_png,
+        }
+    }
+    
+    // asks the selection owner for the next format of the front read, or answers it when all are in
+    fn request_clipboard_read_format(&mut self) {
+        while let Some(read) = self.clipboard_reads.front().cloned() {
+            if self.clipboard_read_format < read.formats.len() {
+                if let Some(window) = self.any_window() {
+                    unsafe {
+                        X11_sys::XConvertSelection(
+                            self.display,
+                            self.clipboard_selection_atom(read.selection),
+                            self.clipboard_format_atom(read.formats[self.clipboard_read_format]),
+                            self.atom_clipboard_read,
+                            window,
+                            X11_sys::CurrentTime as X11_sys::Time
+                        );
+                        X11_sys::XFlush(self.display);
+                    }
+                    return
+                }
+            }
+            self.clipboard_reads.pop_front();
+            let content = mem::replace(&mut self.clipboard_read_content, ClipboardContent::default());
+            self.clipboard_read_format = 0;
+            self.do_callback(&mut vec![Event::ClipboardRead(ClipboardReadEvent {
+                read_id: read.read_id,
+                selection: read.selection,
+                content: content
+            })]);
+        }
+    }
+    
+    fn is_clipboard_read_answer(&self, selection: &X11_sys::XSelectionEvent) -> bool {
+        if let Some(read) = self.clipboard_reads.front() {
+            if selection.property == self.atom_clipboard_read {
+                return true
+            }
+            // a refused conversion comes back without a property
+            return selection.property == 0
+                && selection.selection == self.clipboard_selection_atom(read.selection)
+                && read.formats.get(self.clipboard_read_format).map( | format | self.clipboard_format_atom(*format)) == Some(selection.target)
+        }
+        false
+    }
+    
+    fn handle_clipboard_read_answer(&mut self, data: Option<Vec<u8>>) {
+        let format = self.clipboard_reads.front().and_then( | read | read.formats.get(self.clipboard_read_format).cloned());
+        if let (Some(format), Some(data)) = (format, data) {
+            match format {
+                ClipboardFormat::Text => self.clipboard_read_content.text = String::from_utf8(data).ok(),
+                ClipboardFormat::Html => self.clipboard_read_content.html = Some(String::from_utf8_lossy(&data).to_string()),
+                ClipboardFormat::Png => self.clipboard_read_content.png = Some(data),
+            }
+        }
+        self.clipboard_read_format += 1;
+        self.request_clipboard_read_format();
+    }
+    
+    // reads and deletes a selection property. large transfers (INCR) are not supported
+    unsafe fn get_selection_property(&self, window: c_ulong, property: X11_sys::Atom) -> Option<Vec<u8>> {
+        // first get the size of the thing
+        let mut actual_type = mem::MaybeUninit::uninit();
+        let mut actual_format = mem::MaybeUninit::uninit();
+        let mut n_items = mem::MaybeUninit::uninit();
+        let mut bytes_to_read = mem::MaybeUninit::uninit();
+        let mut ret = mem::MaybeUninit::uninit();
+        X11_sys::XGetWindowProperty(
+            self.display,
+            window,
+            property,
+            0,
+            0,
+            0,
+            X11_sys::AnyPropertyType as c_ulong,
+            actual_type.as_mut_ptr(),
+            actual_format.as_mut_ptr(),
+            n_items.as_mut_ptr(),
+            bytes_to_read.as_mut_ptr(),
+            ret.as_mut_ptr()
+        );
+        let bytes_to_read = bytes_to_read.assume_init();
+        let ret = ret.assume_init();
+        if ret != ptr::null_mut() {
+            X11_sys::XFree(ret as *mut _ as *mut c_void);
+        }
+        let mut ret = mem::MaybeUninit::uninit();
+        let mut bytes_after = mem::MaybeUninit::uninit();
+        X11_sys::XGetWindowProperty(
+            self.display,
+            window,
+            property,
+            0,
+            bytes_to_read as c_long,
+            1,
+            X11_sys::AnyPropertyType as c_ulong,
+            actual_type.as_mut_ptr(),
+            actual_format.as_mut_ptr(),
+            n_items.as_mut_ptr(),
+            bytes_after.as_mut_ptr(),
+            ret.as_mut_ptr()
+        );
+        let ret = ret.assume_init();
+        let mut data = None;
+        if ret != ptr::null_mut() {
+            if bytes_to_read > 0 {
+                data = Some(slice::from_raw_parts::<u8>(ret as *const _ as *const u8, bytes_to_read as usize).to_vec());
+            }
+            X11_sys::XFree(ret as *mut _ as *mut c_void);
+        }
+        data
+    }
+    
+    // someone asks for the CLIPBOARD or PRIMARY we own
+    unsafe fn handle_clipboard_request(&self, request: &X11_sys::XSelectionRequestEvent) -> bool {
+        let content = if request.selection == self.atom_clipboard {
+            &self.clipboard
+        }
+        else if request.selection == self.atom_primary {
+            &self.primary
+        }
+        else {
+            return false
+        };
+        if request.target == self.atom_targets {
+            let mut targets = vec![self.atom_targets];
+            if content.text.is_some() {
+                targets.extend_from_slice(&[self.atom_utf8_string, self.atom_text_plain]);
+            }
+            if content.html.is_some() {
+                targets.push(self.atom_text_html);
+            }
+            if content.png.is_some() {
+                targets.push(self.atom_image_png);
+            }
+            X11_sys::XChangeProperty(
+                self.display,
+                request.requestor,
+                request.property,
+                self.atom_atom,
+                32,
+                X11_sys::PropModeReplace as i32,
+                targets.as_mut_ptr() as *mut _ as *mut c_uchar,
+                targets.len() as i32
+            );
+            return true
+        }
+        let data = if request.target == self.atom_utf8_string || request.target == self.atom_text_plain {
+            content.text.as_ref().map( | text | text.as_bytes())
+        }
+        else if request.target == self.atom_text_html {
+            content.html.as_ref().map( | html | html.as_bytes())
+        }
+        else if request.target == self.atom_image_png {
+            content.png.as_ref().map( | png | png.as_slice())
+        }
+        else {
+            None
+        };
+        if let Some(data) = data {
+            X11_sys::XChangeProperty(
+                self.display,
+                request.requestor,
+                request.property,
+                request.target,
+                8,
+                X11_sys::PropModeReplace as i32,
+                data.as_ptr() as *const _ as *const c_uchar,
+                data.len() as i32
+            );
+            return true
+        }
+        false
+    }
+    
     pub fn start_timer(&mut self, id: u64, timeout: f64, repeats: bool) {
         //println!("STARTING TIMER {:?} {:?} {:?}", id, timeout, repeats);
         
@@ -782,9 +995,8 @@ impl XlibApp {
                 let mut signals = HashMap::new();
                 signals.insert(signal, vec![status]);
                 signals_locked.push(Event::Signal(SignalEvent {signals}));
-                //let mut f = unsafe { File::from_raw_fd((*GLOBAL_XLIB_APP).display_fd) };
-                //let _ = write!(&mut f, "\0");
-                // !TODO unblock the select!
+                let byte = 0u8;
+                libc::write((*GLOBAL_XLIB_APP).signal_write_fd, &byte as *const u8 as *const c_void, 1);
             }
         }
     }
@@ -1610,8 +1822,20 @@ const _NET_WM_STATE_TOGGLE: c_long = 2;/* toggle property  */
 pub struct Dnd {
     atoms: DndAtoms,
     display: *mut X11_sys::Display,
-    type_list: Option<Vec<X11_sys::Atom>>,
-    selection: Option<CString>,
+    // the type we ask the source for, a uri list when it has one
+    drop_type: Option<X11_sys::Atom>,
+    // where the last XdndPosition was, the drop lands there
+    drop_pos: Option<(usize, Vec2)>,
+    drop_source: Option<X11_sys::Window>,
+    source: Option<DndSource>,
+}
+
+// a drag of ours that left our windows
+struct DndSource {
+    payload: DragPayload,
+    window: X11_sys::Window,
+    target: Option<X11_sys::Window>,
+    accepted: bool,
 }
 
 impl Dnd {
@@ -1619,8 +1843,10 @@ impl Dnd {
         Dnd {
             atoms: DndAtoms::new(display),
             display,
-            type_list: None,
-            selection: None,
+            drop_type: None,
+            drop_pos: None,
+            drop_source: None,
+            source: None,
         }
     }
     
@@ -1657,19 +1883,25 @@ impl Dnd {
         // If the has_more_types flags is set, we have to obtain the list of supported types from
         // the XDndTypeList property. Otherwise, we can obtain the list of supported types from the
         // event itself.
-        self.type_list = Some(if has_more_types {
+        let type_list = if has_more_types {
             self.get_type_list_property(source_window)
         } else {
-            event.data.l[2..4]
+            event.data.l[2..5]
                 .iter()
                 .map( | &l | l as X11_sys::Atom)
                 .filter( | &atom | atom != X11_sys::None as X11_sys::Atom)
                 .collect()
-        });
+        };
+        
+        // Files come as a URI list, anything else we can read as text.
+        self.drop_type = [self.atoms.uri_list, self.atoms.utf8_string, self.atoms.text_plain]
+            .iter()
+            .cloned()
+            .find( | atom | type_list.contains(atom));
     }
     
     /// Handles a XDndDrop event.
-    unsafe fn handle_drop_event(&mut self, event: &X11_sys::XClientMessageEvent) {
+    unsafe fn handle_drop_event(&mut self, event: &X11_sys::XClientMessageEvent, window: &mut XlibWindow) {
         // The XDndLeave event is sent by the source window when a drag is confirmed. That is, the
         // mouse button is released while the mouse is inside the client rectangle of the target
         // window. The target window is supposed to respond to this by requesting that the selection
@@ -1678,21 +1910,33 @@ impl Dnd {
         // selection event containing the data to the source window.
         
         let target_window = event.window as X11_sys::Window;
-        self.convert_selection(target_window);
-        self.type_list = None;
+        let source_window = event.data.l[0] as X11_sys::Window;
+        let time = event.data.l[2] as X11_sys::Time;
+        match self.drop_type {
+            Some(drop_type) => {
+                self.drop_source = Some(source_window);
+                self.convert_selection(target_window, drop_type, time);
+            },
+            None => {
+                self.send_finished_event(source_window, target_window, DragAction::None);
+                self.reset_target();
+                window.do_callback(&mut vec![Event::DragEnd]);
+            }
+        }
     }
     
     /// Handles a XDndLeave event.
-    unsafe fn handle_leave_event(&mut self, _event: &X11_sys::XClientMessageEvent) {
+    unsafe fn handle_leave_event(&mut self, _event: &X11_sys::XClientMessageEvent, window: &mut XlibWindow) {
         // The XDndLeave event is sent by the source window when a drag is canceled. That is, the
         // mouse leaves the client rectangle of the target window. The target window is supposed to
         // repsond this this by pretending the drag never happened.
         
-        self.type_list = None;
+        self.reset_target();
+        window.do_callback(&mut vec![Event::DragEnd]);
     }
     
     /// Handles a XDndPosition event.
-    unsafe fn handle_position_event(&mut self, event: &X11_sys::XClientMessageEvent) {
+    unsafe fn handle_position_event(&mut self, event: &X11_sys::XClientMessageEvent, window: &mut XlibWindow) {
         // The XDndPosition event is sent by the source window after the XDndEnter event, every time
         // the mouse is moved. The target window is supposed to respond to this by sending a status
         // event to the source window notifying whether it can accept the drag at this position.
@@ -1700,42 +1944,113 @@ impl Dnd {
         let target_window = event.window as X11_sys::Window;
         let source_window = event.data.l[0] as X11_sys::Window;
         
-        // For now we accept te drag if and only if the list of types supported by the source
-        // includes a uri list.
-        //
-        // TODO: Extend this test by taking into account the position of the mouse as well.
-        let accepted = self.type_list.as_ref().map_or(false, | type_list | type_list.contains(&self.atoms.uri_list));
-        
-        // Notify the source window whether we can accept the drag at this position.
-        self.send_status_event(source_window, target_window, accepted);
-        
-        // If this is the first time we've accepted the drag, request that the drag-and-drop
-        // selection be converted to a URI list. The target window is supposed to respond to this by
-        // sending a XSelectionEvent containing the URI list.
+        // The position comes in root window coordinates, packed into a single long.
+        let root_x = ((event.data.l[2] >> 16) & 0xffff) as c_int;
+        let root_y = (event.data.l[2] & 0xffff) as c_int;
+        let mut x = 0;
+        let mut y = 0;
+        let mut child = 0;
+        X11_sys::XTranslateCoordinates(
+            self.display,
+            X11_sys::XDefaultRootWindow(self.display),
+            target_window,
+            root_x,
+            root_y,
+            &mut x,
+            &mut y,
+            &mut child
+        );
+        let dpi_factor = window.last_window_geom.dpi_factor;
+        let abs = Vec2 {x: x as f32 / dpi_factor, y: y as f32 / dpi_factor};
+        self.drop_pos = Some((window.window_id, abs));
         
-        // Since this is an asynchronous operation, its possible for another XDndPosition event to
-        // come in before the response to the first conversion request has been received. In this
-        // case, a second conversion request will be sent, the response to which will be ignored.
-        if accepted && self.selection.is_none() {
+        // We accept the drag if the widget under the mouse does. The data itself is only
+        // requested on drop, so until then the widgets only get to see what kind of payload it is.
+        let action = if let Some(payload) = self.hover_payload() {
+            let mut events = vec![Event::Drag(DragEvent {
+                window_id: window.window_id,
+                abs: abs,
+                rel: abs,
+                rect: Rect::default(),
+                state: HoverState::Over,
+                payload: payload,
+                handled: false,
+                action: DragAction::None
+            })];
+            window.do_callback(&mut events);
+            if let Event::Drag(de) = &events[0] {de.action} else {DragAction::None}
         }
+        else {
+            DragAction::None
+        };
+        
+        // Notify the source window whether we can accept the drag at this position.
+        self.send_status_event(source_window, target_window, action);
     }
     
     /// Handles a XSelectionEvent.
-    unsafe fn handle_selection_event(&mut self, event: &X11_sys::XSelectionEvent) {
+    unsafe fn handle_selection_event(&mut self, event: &X11_sys::XSelectionEvent, window: &mut XlibWindow) {
         // The XSelectionEvent is sent by the source window in response to a request by the source
         // window to convert the selection representing the thing being dragged to the appropriate
         // data type. This request is always sent in response to a XDndDrop event, so this event
         // should only be received after a drop operation has completed.
         
-        let source_window = event.requestor;
-        let selection = CString::new(self.get_selection_property(source_window)).unwrap();
-        println!("{:?}", selection);
+        let target_window = event.requestor;
+        let source_window = if let Some(source_window) = self.drop_source {source_window} else {return};
+        let drop_type = self.drop_type.unwrap_or(self.atoms.uri_list);
+        let (window_id, abs) = self.drop_pos.unwrap_or((window.window_id, window.last_mouse_pos));
+        
+        let action = if event.property != X11_sys::None as X11_sys::Atom {
+            let selection = self.get_selection_property(target_window, drop_type);
+            let text = String::from_utf8_lossy(&selection).to_string();
+            let payload = if drop_type == self.atoms.uri_list {
+                DragPayload::from_uri_list(&text)
+            }
+            else {
+                DragPayload::Text(text)
+            };
+            let mut events = vec![Event::Drop(DropEvent {
+                window_id: window_id,
+                abs: abs,
+                rel: abs,
+                rect: Rect::default(),
+                payload: payload,
+                action: DragAction::None,
+                is_external: true,
+                handled: false
+            })];
+            window.do_callback(&mut events);
+            if let Event::Drop(de) = &events[0] {de.action} else {DragAction::None}
+        }
+        else {
+            // the source could not convert it after all
+            window.do_callback(&mut vec![Event::DragEnd]);
+            DragAction::None
+        };
         
-        // TODO: Actually use the selection
+        self.send_finished_event(source_window, target_window, action);
+        self.reset_target();
+    }
+    
+    unsafe fn reset_target(&mut self) {
+        self.drop_type = None;
+        self.drop_pos = None;
+        self.drop_source = None;
+    }
+    
+    // what the widgets see while hovering, the data arrives with the drop
+    fn hover_payload(&self) -> Option<DragPayload> {
+        let drop_type = self.drop_type ?;
+        if drop_type == self.atoms.uri_list {
+            Some(DragPayload::Files(Vec::new()))
+        }
+        else {
+            Some(DragPayload::Text(String::new()))
+        }
     }
     
     /// Gets the XDndSelection property from the source window.
-    unsafe fn get_selection_property(&mut self, source_window: X11_sys::Window) -> Vec<c_uchar> {
+    unsafe fn get_selection_property(&mut self, source_window: X11_sys::Window, drop_type: X11_sys::Atom) -> Vec<c_uchar> {
         let mut selection = Vec::new();
         let mut offset = 0;
         let length = 1024;
@@ -1752,13 +2067,16 @@ impl Dnd {
                 offset,
                 length,
                 X11_sys::False as c_int,
-                self.atoms.uri_list,
+                drop_type,
                 &mut actual_type,
                 &mut actual_format,
                 &mut nitems,
                 &mut bytes_after,
                 &mut prop,
             );
+            if prop == ptr::null_mut() {
+                break;
+            }
             selection.extend_from_slice(slice::from_raw_parts(prop as *mut c_uchar, nitems as usize));
             X11_sys::XFree(prop as *mut c_void);
             if bytes_after == 0 {
@@ -1804,11 +2122,18 @@ impl Dnd {
         type_list
     }
     
-    /// Sends a XDndStatus event to the target window.
-    unsafe fn send_status_event(&mut self, source_window: X11_sys::Window, target_window: X11_sys::Window, accepted: bool) {
+    fn action_atom(&self, action: DragAction) -> X11_sys::Atom {
+        match action {
+            DragAction::None => self.atoms.none,
+            DragAction::Copy => self.atoms.action_copy,
+            DragAction::Move => self.atoms.action_move,
+        }
+    }
+    
+    unsafe fn send_client_message(&self, window: X11_sys::Window, message_type: X11_sys::Atom, l: [c_long; 5]) {
         X11_sys::XSendEvent(
             self.display,
-            source_window,
+            window,
             X11_sys::False as c_int,
             X11_sys::NoEventMask as c_long,
             &mut X11_sys::XClientMessageEvent {
@@ -1816,16 +2141,12 @@ impl Dnd {
                 serial: 0,
                 send_event: 0,
                 display: self.display,
-                window: source_window,
-                message_type: self.atoms.status,
+                window: window,
+                message_type: message_type,
                 format: 32,
                 data: {
                     let mut data = mem::zeroed::<X11_sys::XClientMessageEvent__bindgen_ty_1>();
-                    data.l[0] = target_window as c_long;
-                    data.l[1] = if accepted {1 << 0} else {0};
-                    data.l[2] = 0;
-                    data.l[3] = 0;
-                    data.l[4] = if accepted {self.atoms.action_private} else {self.atoms.none} as c_long;
+                    data.l = l;
                     data
                 }
             } as *mut X11_sys::XClientMessageEvent as *mut X11_sys::XEvent
@@ -1833,48 +2154,248 @@ impl Dnd {
         X11_sys::XFlush(self.display);
     }
     
+    /// Sends a XDndStatus event to the source window.
+    unsafe fn send_status_event(&mut self, source_window: X11_sys::Window, target_window: X11_sys::Window, action: DragAction) {
+        let accepted = action != DragAction::None;
+        self.send_client_message(source_window, self.atoms.status, [
+            target_window as c_long,
+            if accepted {1 << 0} else {0},
+            0,
+            0,
+            self.action_atom(action) as c_long
+        ]);
+    }
+    
+    /// Sends a XDndFinished event to the source window.
+    unsafe fn send_finished_event(&mut self, source_window: X11_sys::Window, target_window: X11_sys::Window, action: DragAction) {
+        let accepted = action != DragAction::None;
+        self.send_client_message(source_window, self.atoms.finished, [
+            target_window as c_long,
+            if accepted {1 << 0} else {0},
+            self.action_atom(action) as c_long,
+            0,
+            0
+        ]);
+    }
+    
     // Requests that the selection representing the thing being dragged is converted to the
-    // appropriate data type (in our case, a URI list).
-    unsafe fn convert_selection(&self, target_window: X11_sys::Window) {
+    // appropriate data type (a URI list or text).
+    unsafe fn convert_selection(&self, target_window: X11_sys::Window, drop_type: X11_sys::Atom, time: X11_sys::Time) {
         X11_sys::XConvertSelection(
             self.display,
             self.atoms.selection,
-            self.atoms.uri_list,
+            drop_type,
             self.atoms.selection,
             target_window,
-            X11_sys::CurrentTime as X11_sys::Time,
+            time,
         );
     }
+    
+    // the other side, dragging something of ours into another application. we own the
+    // XdndSelection and walk the windows under the mouse for one that is XdndAware
+    unsafe fn start_source(&mut self, window: X11_sys::Window, payload: DragPayload) {
+        X11_sys::XSetSelectionOwner(self.display, self.atoms.selection, window, X11_sys::CurrentTime as X11_sys::Time);
+        self.source = Some(DndSource {
+            payload: payload,
+            window: window,
+            target: None,
+            accepted: false
+        });
+    }
+    
+    fn source_types(&self) -> Vec<X11_sys::Atom> {
+        match self.source.as_ref().map( | source | &source.payload) {
+            Some(DragPayload::Files(_)) => vec![self.atoms.uri_list, self.atoms.utf8_string, self.atoms.text_plain],
+            Some(DragPayload::Text(_)) => vec![self.atoms.utf8_string, self.atoms.text_plain],
+            None => Vec::new()
+        }
+    }
+    
+    unsafe fn is_aware(&self, window: X11_sys::Window) -> bool {
+        let mut actual_type = 0;
+        let mut actual_format = 0;
+        let mut nitems = 0;
+        let mut bytes_after = 0;
+        let mut prop = ptr::null_mut();
+        X11_sys::XGetWindowProperty(
+            self.display,
+            window,
+            self.atoms.aware,
+            0,
+            1,
+            X11_sys::False as c_int,
+            4, // XA_ATOM
+            &mut actual_type,
+            &mut actual_format,
+            &mut nitems,
+            &mut bytes_after,
+            &mut prop,
+        );
+        if prop != ptr::null_mut() {
+            X11_sys::XFree(prop as *mut c_void);
+        }
+        nitems > 0
+    }
+    
+    unsafe fn find_target(&self, root_x: c_int, root_y: c_int) -> Option<X11_sys::Window> {
+        let root = X11_sys::XDefaultRootWindow(self.display);
+        let mut window = root;
+        loop {
+            let mut x = 0;
+            let mut y = 0;
+            let mut child = 0;
+            X11_sys::XTranslateCoordinates(self.display, root, window, root_x, root_y, &mut x, &mut y, &mut child);
+            if window != root && self.is_aware(window) {
+                return Some(window)
+            }
+            if child == X11_sys::None as X11_sys::Window {
+                return None
+            }
+            window = child;
+        }
+    }
+    
+    unsafe fn handle_source_motion(&mut self, window_map: &HashMap<c_ulong, *mut XlibWindow>, root_x: c_int, root_y: c_int, time: X11_sys::Time) {
+        if self.source.is_none() {
+            return
+        }
+        // over our own windows the drag is handled by Cx
+        let target = self.find_target(root_x, root_y).filter( | target | !window_map.contains_key(target));
+        let (source_window, old_target) = {
+            let source = self.source.as_ref().unwrap();
+            (source.window, source.target)
+        };
+        if old_target != target {
+            if let Some(old_target) = old_target {
+                self.send_client_message(old_target, self.atoms.leave, [source_window as c_long, 0, 0, 0, 0]);
+            }
+            if let Some(target) = target {
+                let types = self.source_types();
+                self.send_client_message(target, self.atoms.enter, [
+                    source_window as c_long,
+                    5 << 24,
+                    types[0] as c_long,
+                    types[1] as c_long,
+                    *types.get(2).unwrap_or(&0) as c_long
+                ]);
+            }
+            let source = self.source.as_mut().unwrap();
+            source.target = target;
+            source.accepted = false;
+        }
+        if let Some(target) = target {
+            self.send_client_message(target, self.atoms.position, [
+                source_window as c_long,
+                0,
+                ((root_x as c_long) << 16) | (root_y as c_long & 0xffff),
+                time as c_long,
+                self.atoms.action_copy as c_long
+            ]);
+        }
+    }
+    
+    unsafe fn handle_source_release(&mut self, time: X11_sys::Time) {
+        let source = if let Some(source) = &self.source {source} else {return};
+        match source.target {
+            Some(target) if source.accepted => {
+                // keep the source around, the target still has to ask for the data
+                self.send_client_message(target, self.atoms.drop, [source.window as c_long, 0, time as c_long, 0, 0]);
+            },
+            Some(target) => {
+                self.send_client_message(target, self.atoms.leave, [source.window as c_long, 0, 0, 0, 0]);
+                self.source = None;
+            },
+            None => {
+                self.source = None;
+            }
+        }
+    }
+    
+    unsafe fn handle_status_event(&mut self, event: &X11_sys::XClientMessageEvent) {
+        if let Some(source) = &mut self.source {
+            if source.target == Some(event.data.l[0] as X11_sys::Window) {
+                source.accepted = event.data.l[1] & (1 << 0) != 0;
+            }
+        }
+    }
+    
+    unsafe fn handle_finished_event(&mut self, _event: &X11_sys::XClientMessageEvent) {
+        self.source = None;
+    }
+    
+    // a target asking for the data we dragged to it
+    unsafe fn handle_selection_request(&mut self, request: &X11_sys::XSelectionRequestEvent) -> bool {
+        let types = self.source_types();
+        let payload = if let Some(source) = &self.source {&source.payload} else {return false};
+        if request.target == self.atoms.targets {
+            X11_sys::XChangeProperty(
+                self.display,
+                request.requestor,
+                request.property,
+                4, // XA_ATOM
+                32,
+                X11_sys::PropModeReplace as c_int,
+                types.as_ptr() as *const c_uchar,
+                types.len() as c_int
+            );
+            return true
+        }
+        if !types.contains(&request.target) {
+            return false
+        }
+        let data = if request.target == self.atoms.uri_list {payload.to_uri_list()} else {payload.to_text()};
+        X11_sys::XChangeProperty(
+            self.display,
+            request.requestor,
+            request.property,
+            request.target,
+            8,
+            X11_sys::PropModeReplace as c_int,
+            data.as_ptr() as *const c_uchar,
+            data.len() as c_int
+        );
+        true
+    }
 }
 
 struct DndAtoms {
-    action_private: X11_sys::Atom,
+    action_copy: X11_sys::Atom,
+    action_move: X11_sys::Atom,
     aware: X11_sys::Atom,
     drop: X11_sys::Atom,
     enter: X11_sys::Atom,
+    finished: X11_sys::Atom,
     leave: X11_sys::Atom,
     none: X11_sys::Atom,
     position: X11_sys::Atom,
     selection: X11_sys::Atom,
     status: X11_sys::Atom,
+    targets: X11_sys::Atom,
+    text_plain: X11_sys::Atom,
     type_list: X11_sys::Atom,
     uri_list: X11_sys::Atom,
+    utf8_string: X11_sys::Atom,
 }
 
 impl DndAtoms {
     unsafe fn new(display: *mut X11_sys::Display) -> DndAtoms {
         DndAtoms {
-            action_private: X11_sys::XInternAtom(display, CString::new("XdndActionPrivate").unwrap().as_ptr(), 0),
+            action_copy: X11_sys::XInternAtom(display, CString::new("XdndActionCopy").unwrap().as_ptr(), 0),
+            action_move: X11_sys::XInternAtom(display, CString::new("XdndActionMove").unwrap().as_ptr(), 0),
             aware: X11_sys::XInternAtom(display, CString::new("XdndAware").unwrap().as_ptr(), 0),
             drop: X11_sys::XInternAtom(display, CString::new("XdndDrop").unwrap().as_ptr(), 0),
             enter: X11_sys::XInternAtom(display, CString::new("XdndEnter").unwrap().as_ptr(), 0),
+            finished: X11_sys::XInternAtom(display, CString::new("XdndFinished").unwrap().as_ptr(), 0),
             leave: X11_sys::XInternAtom(display, CString::new("XdndLeave").unwrap().as_ptr(), 0),
             none: X11_sys::XInternAtom(display, CString::new("None").unwrap().as_ptr(), 0),
             position: X11_sys::XInternAtom(display, CString::new("XdndPosition").unwrap().as_ptr(), 0),
             selection: X11_sys::XInternAtom(display, CString::new("XdndSelection").unwrap().as_ptr(), 0),
             status: X11_sys::XInternAtom(display, CString::new("XdndStatus").unwrap().as_ptr(), 0),
+            targets: X11_sys::XInternAtom(display, CString::new("TARGETS").unwrap().as_ptr(), 0),
+            text_plain: X11_sys::XInternAtom(display, CString::new("text/plain").unwrap().as_ptr(), 0),
             type_list: X11_sys::XInternAtom(display, CString::new("XdndTypeList").unwrap().as_ptr(), 0),
             uri_list: X11_sys::XInternAtom(display, CString::new("text/uri-list").unwrap().as_ptr(), 0),
+            utf8_string: X11_sys::XInternAtom(display, CString::new("UTF8_STRING").unwrap().as_ptr(), 0),
         }
     }
 }
@@ -0,0 +1,739 @@
+// just enough of a D-Bus client to talk to the accessibility bus without pulling in libdbus.
+// it speaks the wire format, does EXTERNAL auth over a unix socket and nothing else
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DbusValue {
+    Byte(u8),
+    Bool(bool),
+    Int16(i16),
+    Uint16(u16),
+    Int32(i32),
+    Uint32(u32),
+    Int64(i64),
+    Uint64(u64),
+    Double(f64),
+    Str(String),
+    ObjectPath(String),
+    Signature(String),
+    // element signature, so empty arrays still know what they are
+    Array(String, Vec<DbusValue>),
+    Struct(Vec<DbusValue>),
+    DictEntry(Box<DbusValue>, Box<DbusValue>),
+    Variant(Box<DbusValue>),
+}
+
+impl DbusValue {
+    pub fn signature(&self) -> String {
+        match self {
+            DbusValue::Byte(_) => "y".to_string(),
+            DbusValue::Bool(_) => "b".to_string(),
+            DbusValue::Int16(_) => "n".to_string(),
+            DbusValue::Uint16(_) => "q".to_string(),
+            DbusValue::Int32(_) => "i".to_string(),
+            DbusValue::Uint32(_) => "u".to_string(),
+            DbusValue::Int64(_) => "x".to_string(),
+            DbusValue::Uint64(_) => "t".to_string(),
+            DbusValue::Double(_) => "d".to_string(),
+            DbusValue::Str(_) => "s".to_string(),
+            DbusValue::ObjectPath(_) => "o".to_string(),
+            DbusValue::Signature(_) => "g".to_string(),
+            DbusValue::Array(sig, _) => format!("a{}", sig),
+            DbusValue::Struct(fields) => format!("({})", fields.iter().map( | f | f.signature()).collect::<String>()),
+            DbusValue::DictEntry(k, v) => format!("{{{}{}}}", k.signature(), v.signature()),
+            DbusValue::Variant(_) => "v".to_string(),
+        }
+    }
+    
+    pub fn str(s: &str) -> DbusValue {
+        DbusValue::Str(s.to_string())
+    }
+    
+    pub fn variant(v: DbusValue) -> DbusValue {
+        DbusValue::Variant(Box::new(v))
+    }
+    
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            DbusValue::Str(s) | DbusValue::ObjectPath(s) | DbusValue::Signature(s) => Some(s),
+            _ => None
+        }
+    }
+    
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            DbusValue::Byte(v) => Some(*v as i64),
+            DbusValue::Int16(v) => Some(*v as i64),
+            DbusValue::Uint16(v) => Some(*v as i64),
+            DbusValue::Int32(v) => Some(*v as i64),
+            DbusValue::Uint32(v) => Some(*v as i64),
+            DbusValue::Int64(v) => Some(*v),
+            DbusValue::Uint64(v) => Some(*v as i64),
+            _ => None
+        }
+    }
+}
+
+fn sig_alignment(sig: u8) -> usize {
+    match sig {
+        b'n' | b'q' => 2,
+        b'b' | b'i' | b'u' | b's' | b'o' | b'a' => 4,
+        b'x' | b't' | b'd' | b'(' | b'{' => 8,
+        _ => 1
+    }
+}
+
+// splits the first complete type off a signature
+fn split_sig(sig: &str) -> Result<(&str, &str), String> {
+    let bytes = sig.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'a' => {
+                i += 1;
+                continue;
+            },
+            b'(' | b'{' => depth += 1,
+            b')' | b'}' => depth -= 1,
+            _ => ()
+        }
+        i += 1;
+        if depth == 0 {
+            return Ok((&sig[0..i], &sig[i..]))
+        }
+    }
+    Err(format!("invalid signature {}", sig))
+}
+
+#[derive(Default)]
+pub struct DbusWriter {
+    pub buf: Vec<u8>,
+}
+
+impl DbusWriter {
+    fn align(&mut self, align: usize) {
+        while self.buf.len() % align != 0 {
+            self.buf.push(0);
+        }
+    }
+    
+    fn write_u32(&mut self, v: u32) {
+        self.align(4);
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+    
+    fn write_str(&mut self, s: &str) {
+        self.write_u32(s.len() as u32);
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf.push(0);
+    }
+    
+    fn write_sig(&mut self, s: &str) {
+        self.buf.push(s.len() as u8);
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf.push(0);
+    }
+    
+    pub fn write(&mut self, value: &DbusValue) {
+        match value {
+            DbusValue::Byte(v) => self.buf.push(*v),
+            DbusValue::Bool(v) => self.write_u32(if *v {1} else {0}),
+            DbusValue::Int16(v) => {
+                self.align(2);
+                self.buf.extend_from_slice(&v.to_le_bytes());
+            },
+            DbusValue::Uint16(v) => {
+                self.align(2);
+                self.buf.extend_from_slice(&v.to_le_bytes());
+            },
+            DbusValue::Int32(v) => self.write_u32(*v as u32),
+            DbusValue::Uint32(v) => self.write_u32(*v),
+            DbusValue::Int64(v) => {
+                self.align(8);
+                self.buf.extend_from_slice(&v.to_le_bytes());
+            },
+            DbusValue::Uint64(v) => {
+                self.align(8);
+                self.buf.extend_from_slice(&v.to_le_bytes());
+            },
+            DbusValue::Double(v) => {
+                self.align(8);
+                self.buf.extend_from_slice(&v.to_bits().to_le_bytes());
+            },
+            DbusValue::Str(s) | DbusValue::ObjectPath(s) => self.write_str(s),
+            DbusValue::Signature(s) => self.write_sig(s),
+            DbusValue::Array(sig, items) => {
+                self.write_u32(0);
+                let len_pos = self.buf.len() - 4;
+                // the padding to the first element doesnt count towards the length
+                self.align(sig_alignment(sig.as_bytes().get(0).cloned().unwrap_or(0)));
+                let start = self.buf.len();
+                for item in items {
+                    self.write(item);
+                }
+                let len = (self.buf.len() - start) as u32;
+                self.buf[len_pos..len_pos + 4].copy_from_slice(&len.to_le_bytes());
+            },
+            DbusValue::Struct(fields) => {
+                self.align(8);
+                for field in fields {
+                    self.write(field);
+                }
+            },
+            DbusValue::DictEntry(k, v) => {
+                self.align(8);
+                self.write(k);
+                self.write(v);
+            },
+            DbusValue::Variant(v) => {
+                self.write_sig(&v.signature());
+                self.write(v);
+            }
+        }
+    }
+}
+
+pub struct DbusReader<'a> {
+    pub buf: &'a [u8],
+    pub pos: usize,
+    pub big_endian: bool,
+}
+
+impl<'a> DbusReader<'a> {
+    fn align(&mut self, align: usize) {
+        self.pos = (self.pos + align - 1) / align * align;
+    }
+    
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.pos + len > self.buf.len() {
+            return Err("dbus message truncated".to_string())
+        }
+        let slice = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+    
+    fn read_fixed<T>(&mut self, len: usize, le: fn(&[u8]) -> T, be: fn(&[u8]) -> T) -> Result<T, String> {
+        self.align(len);
+        let bytes = self.take(len) ?;
+        Ok(if self.big_endian {be(bytes)} else {le(bytes)})
+    }
+    
+    fn read_u32(&mut self) -> Result<u32, String> {
+        self.read_fixed(4, | b | u32::from_le_bytes([b[0], b[1], b[2], b[3]]), | b | u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    
+    fn read_u64(&mut self) -> Result<u64, String> {
+        self.read_fixed(8, | b | {
+            let mut a = [0u8; 8];
+            a.copy_from_slice(b);
+            u64::from_le_bytes(a)
+        }, | b | {
+            let mut a = [0u8; 8];
+            a.copy_from_slice(b);
+            u64::from_be_bytes(a)
+        })
+    }
+    
+    fn read_u16(&mut self) -> Result<u16, String> {
+        self.read_fixed(2, | b | u16::from_le_bytes([b[0], b[1]]), | b | u16::from_be_bytes([b[0], b[1]]))
+    }
+    
+    fn read_string(&mut self, len: usize) -> Result<String, String> {
+        let bytes = self.take(len + 1) ?;
+        String::from_utf8(bytes[0..len].to_vec()).map_err( | _ | "dbus string not utf8".to_string())
+    }
+    
+    fn read_sig(&mut self) -> Result<String, String> {
+        let len = self.take(1) ?[0] as usize;
+        self.read_string(len)
+    }
+    
+    // the signatures come off the wire, so a broken one is an error and not a panic
+    pub fn read(&mut self, sig: &str) -> Result<DbusValue, String> {
+        let invalid = | | format!("invalid signature {}", sig);
+        if split_sig(sig) ?.1.len() != 0 {
+            return Err(invalid())
+        }
+        Ok(match sig.as_bytes()[0] {
+            b'y' => DbusValue::Byte(self.take(1) ?[0]),
+            b'b' => DbusValue::Bool(self.read_u32() ? != 0),
+            b'n' => DbusValue::Int16(self.read_u16() ? as i16),
+            b'q' => DbusValue::Uint16(self.read_u16() ?),
+            b'i' => DbusValue::Int32(self.read_u32() ? as i32),
+            b'u' => DbusValue::Uint32(self.read_u32() ?),
+            b'x' => DbusValue::Int64(self.read_u64() ? as i64),
+            b't' => DbusValue::Uint64(self.read_u64() ?),
+            b'd' => DbusValue::Double(f64::from_bits(self.read_u64() ?)),
+            b's' => {
+                let len = self.read_u32() ? as usize;
+                DbusValue::Str(self.read_string(len) ?)
+            },
+            b'o' => {
+                let len = self.read_u32() ? as usize;
+                DbusValue::ObjectPath(self.read_string(len) ?)
+            },
+            b'g' => DbusValue::Signature(self.read_sig() ?),
+            b'a' => {
+                let elem_sig = &sig[1..];
+                let len = self.read_u32() ? as usize;
+                self.align(sig_alignment(elem_sig.as_bytes()[0]));
+                let end = self.pos + len;
+                let mut items = Vec::new();
+                while self.pos < end {
+                    items.push(self.read(elem_sig) ?);
+                }
+                DbusValue::Array(elem_sig.to_string(), items)
+            },
+            b'(' => {
+                if sig.len() < 3 || !sig.ends_with(')') {
+                    return Err(invalid())
+                }
+                self.align(8);
+                let mut rest = &sig[1..sig.len() - 1];
+                let mut fields = Vec::new();
+                while rest.len() > 0 {
+                    let (field, next) = split_sig(rest) ?;
+                    fields.push(self.read(field) ?);
+                    rest = next;
+                }
+                DbusValue::Struct(fields)
+            },
+            b'{' => {
+                if sig.len() < 4 || !sig.ends_with('}') {
+                    return Err(invalid())
+                }
+                self.align(8);
+                let (key_sig, rest) = split_sig(&sig[1..sig.len() - 1]) ?;
+                let key = self.read(key_sig) ?;
+                let value = self.read(rest) ?;
+                DbusValue::DictEntry(Box::new(key), Box::new(value))
+            },
+            b'v' => {
+                let inner = self.read_sig() ?;
+                DbusValue::Variant(Box::new(self.read(&inner) ?))
+            },
+            _ => return Err(format!("unsupported dbus type {}", sig))
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DbusMessageType {
+    MethodCall = 1,
+    MethodReturn = 2,
+    Error = 3,
+    Signal = 4,
+}
+
+#[derive(Clone, Debug)]
+pub struct DbusMessage {
+    pub message_type: DbusMessageType,
+    pub flags: u8,
+    pub serial: u32,
+    pub path: Option<String>,
+    pub interface: Option<String>,
+    pub member: Option<String>,
+    pub error_name: Option<String>,
+    pub reply_serial: Option<u32>,
+    pub destination: Option<String>,
+    pub sender: Option<String>,
+    pub body: Vec<DbusValue>,
+}
+
+pub const DBUS_FLAG_NO_REPLY_EXPECTED: u8 = 1;
+
+impl DbusMessage {
+    fn new(message_type: DbusMessageType) -> Self {
+        Self {
+            message_type: message_type,
+            flags: 0,
+            serial: 0,
+            path: None,
+            interface: None,
+            member: None,
+            error_name: None,
+            reply_serial: None,
+            destination: None,
+            sender: None,
+            body: Vec::new()
+        }
+    }
+    
+    pub fn method_call(destination: &str, path: &str, interface: &str, member: &str, body: Vec<DbusValue>) -> Self {
+        Self {
+            destination: Some(destination.to_string()),
+            path: Some(path.to_string()),
+            interface: Some(interface.to_string()),
+            member: Some(member.to_string()),
+            body: body,
+            ..Self::new(DbusMessageType::MethodCall)
+        }
+    }
+    
+    pub fn signal(path: &str, interface: &str, member: &str, body: Vec<DbusValue>) -> Self {
+        Self {
+            path: Some(path.to_string()),
+            interface: Some(interface.to_string()),
+            member: Some(member.to_string()),
+            body: body,
+            ..Self::new(DbusMessageType::Signal)
+        }
+    }
+    
+    pub fn method_return(&self, body: Vec<DbusValue>) -> Self {
+        Self {
+            reply_serial: Some(self.serial),
+            destination: self.sender.clone(),
+            body: body,
+            ..Self::new(DbusMessageType::MethodReturn)
+        }
+    }
+    
+    pub fn error(&self, error_name: &str, text: &str) -> Self {
+        Self {
+            reply_serial: Some(self.serial),
+            destination: self.sender.clone(),
+            error_name: Some(error_name.to_string()),
+            body: vec![DbusValue::str(text)],
+            ..Self::new(DbusMessageType::Error)
+        }
+    }
+    
+    pub fn signature(&self) -> String {
+        self.body.iter().map( | v | v.signature()).collect()
+    }
+    
+    pub fn wants_reply(&self) -> bool {
+        self.message_type == DbusMessageType::MethodCall && self.flags & DBUS_FLAG_NO_REPLY_EXPECTED == 0
+    }
+    
+    pub fn serialize(&self, serial: u32) -> Vec<u8> {
+        let mut body = DbusWriter::default();
+        for value in &self.body {
+            body.write(value);
+        }
+        
+        let mut fields = Vec::new();
+        let mut field = | code: u8, value: DbusValue | {
+            fields.push(DbusValue::Struct(vec![DbusValue::Byte(code), DbusValue::variant(value)]));
+        };
+        if let Some(path) = &self.path {field(1, DbusValue::ObjectPath(path.clone()))}
+        if let Some(interface) = &self.interface {field(2, DbusValue::str(interface))}
+        if let Some(member) = &self.member {field(3, DbusValue::str(member))}
+        if let Some(error_name) = &self.error_name {field(4, DbusValue::str(error_name))}
+        if let Some(reply_serial) = self.reply_serial {field(5, DbusValue::Uint32(reply_serial))}
+        if let Some(destination) = &self.destination {field(6, DbusValue::str(destination))}
+        if self.body.len() > 0 {field(8, DbusValue::Signature(self.signature()))}
+        
+        let mut out = DbusWriter::default();
+        out.buf.extend_from_slice(&[b'l', self.message_type as u8, self.flags, 1]);
+        out.write_u32(body.buf.len() as u32);
+        out.write_u32(serial);
+        out.write(&DbusValue::Array("(yv)".to_string(), fields));
+        out.align(8);
+        out.buf.extend_from_slice(&body.buf);
+        out.buf
+    }
+    
+    pub fn parse(buf: &[u8]) -> Result<DbusMessage, String> {
+        if buf.len() < 16 {
+            return Err("dbus message truncated".to_string())
+        }
+        let mut reader = DbusReader {buf: buf, pos: 0, big_endian: buf[0] == b'B'};
+        let message_type = match buf[1] {
+            1 => DbusMessageType::MethodCall,
+            2 => DbusMessageType::MethodReturn,
+            3 => DbusMessageType::Error,
+            4 => DbusMessageType::Signal,
+            t => return Err(format!("unknown dbus message type {}", t))
+        };
+        let mut msg = Self::new(message_type);
+        msg.flags = buf[2];
+        reader.pos = 4;
+        let _body_len = reader.read_u32() ?;
+        msg.serial = reader.read_u32() ?;
+        let mut signature = String::new();
+        if let DbusValue::Array(_, fields) = reader.read("a(yv)") ? {
+            for field in fields {
+                if let DbusValue::Struct(mut parts) = field {
+                    let value = if let Some(DbusValue::Variant(value)) = parts.pop() {*value} else {continue};
+                    let code = if let Some(DbusValue::Byte(code)) = parts.pop() {code} else {continue};
+                    let text = value.as_str().map( | s | s.to_string());
+                    match code {
+                        1 => msg.path = text,
+                        2 => msg.interface = text,
+                        3 => msg.member = text,
+                        4 => msg.error_name = text,
+                        5 => msg.reply_serial = value.as_i64().map( | v | v as u32),
+                        6 => msg.destination = text,
+                        7 => msg.sender = text,
+                        8 => signature = text.unwrap_or(String::new()),
+                        _ => ()
+                    }
+                }
+            }
+        }
+        reader.align(8);
+        let mut rest = signature.as_str();
+        while rest.len() > 0 {
+            let (sig, next) = split_sig(rest) ?;
+            msg.body.push(reader.read(sig) ?);
+            rest = next;
+        }
+        Ok(msg)
+    }
+}
+
+// the sending half can be cloned to other threads, reading stays with whoever owns the connection
+#[derive(Clone)]
+pub struct DbusSender {
+    stream: Arc<Mutex<UnixStream>>,
+    serial: Arc<AtomicU32>,
+}
+
+impl DbusSender {
+    pub fn send(&self, msg: &DbusMessage) -> Result<u32, String> {
+        let serial = self.serial.fetch_add(1, Ordering::SeqCst);
+        let data = msg.serialize(serial);
+        let mut stream = self.stream.lock().map_err( | _ | "dbus stream poisoned".to_string()) ?;
+        stream.write_all(&data).map_err( | e | e.to_string()) ?;
+        Ok(serial)
+    }
+}
+
+pub struct DbusConnection {
+    pub stream: UnixStream,
+    pub sender: DbusSender,
+    pub unique_name: String,
+    pub queued: VecDeque<DbusMessage>,
+}
+
+impl DbusConnection {
+    pub fn session_bus_address() -> Option<String> {
+        std::env::var("DBUS_SESSION_BUS_ADDRESS").ok()
+    }
+    
+    fn unescape(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' && i + 2 < bytes.len() {
+                if let Ok(v) = u8::from_str_radix(&String::from_utf8_lossy(&bytes[i + 1..i + 3]), 16) {
+                    out.push(v);
+                    i += 3;
+                    continue;
+                }
+            }
+            out.push(bytes[i]);
+            i += 1;
+        }
+        String::from_utf8_lossy(&out).to_string()
+    }
+    
+    fn connect_unix(address: &str) -> Option<UnixStream> {
+        let params = address.strip_prefix("unix:") ?;
+        for param in params.split(',') {
+            let mut kv = param.splitn(2, '=');
+            let key = kv.next().unwrap_or("");
+            let value = Self::unescape(kv.next().unwrap_or(""));
+            match key {
+                "path" => return UnixStream::connect(value).ok(),
+                "abstract" => {
+                    use std::os::linux::net::SocketAddrExt;
+                    let addr = std::os::unix::net::SocketAddr::from_abstract_name(value.as_bytes()).ok() ?;
+                    return UnixStream::connect_addr(&addr).ok()
+                },
+                _ => ()
+            }
+        }
+        None
+    }
+    
+    // connects, authenticates and says hello. addresses can be a ; separated list to try in order
+    pub fn connect(address: &str) -> Result<DbusConnection, String> {
+        let mut stream = address.split(';').find_map( | address | Self::connect_unix(address))
+            .ok_or_else( | | format!("cannot connect to dbus at {}", address)) ?;
+        
+        let uid = unsafe {libc::getuid()};
+        let hex_uid: String = uid.to_string().bytes().map( | b | format!("{:02x}", b)).collect();
+        stream.write_all(format!("\0AUTH EXTERNAL {}\r\n", hex_uid).as_bytes()).map_err( | e | e.to_string()) ?;
+        let mut line = Vec::new();
+        let mut byte = [0u8; 1];
+        while !line.ends_with(b"\r\n") {
+            if stream.read(&mut byte).map_err( | e | e.to_string()) ? == 0 {
+                return Err("dbus closed during auth".to_string())
+            }
+            line.push(byte[0]);
+        }
+        if !line.starts_with(b"OK ") {
+            return Err(format!("dbus auth failed: {}", String::from_utf8_lossy(&line).trim()))
+        }
+        stream.write_all(b"BEGIN\r\n").map_err( | e | e.to_string()) ?;
+        
+        let writer = stream.try_clone().map_err( | e | e.to_string()) ?;
+        let mut connection = DbusConnection {
+            stream: stream,
+            sender: DbusSender {stream: Arc::new(Mutex::new(writer)), serial: Arc::new(AtomicU32::new(1))},
+            unique_name: String::new(),
+            queued: VecDeque::new(),
+        };
+        let reply = connection.call(&DbusMessage::method_call(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "Hello",
+            vec![]
+        )) ?;
+        connection.unique_name = reply.body.get(0).and_then( | v | v.as_str()).unwrap_or("").to_string();
+        Ok(connection)
+    }
+    
+    pub fn send(&self, msg: &DbusMessage) -> Result<u32, String> {
+        self.sender.send(msg)
+    }
+    
+    fn read_message(&mut self) -> Result<DbusMessage, String> {
+        let mut head = [0u8; 16];
+        self.stream.read_exact(&mut head).map_err( | e | e.to_string()) ?;
+        let u32_at = | at: usize | {
+            let b = [head[at], head[at + 1], head[at + 2], head[at + 3]];
+            if head[0] == b'B' {u32::from_be_bytes(b)} else {u32::from_le_bytes(b)}
+        };
+        let body_len = u32_at(4) as usize;
+        let fields_len = u32_at(12) as usize;
+        let header_len = (16 + fields_len + 7) / 8 * 8;
+        let mut buf = head.to_vec();
+        buf.resize(header_len + body_len, 0);
+        self.stream.read_exact(&mut buf[16..]).map_err( | e | e.to_string()) ?;
+        DbusMessage::parse(&buf)
+    }
+    
+    // blocks until the reply comes in, anything else that arrives meanwhile is kept for later
+    pub fn call(&mut self, msg: &DbusMessage) -> Result<DbusMessage, String> {
+        let serial = self.send(msg) ?;
+        loop {
+            let reply = self.read_message() ?;
+            if reply.reply_serial == Some(serial) {
+                if reply.message_type == DbusMessageType::Error {
+                    let text = reply.body.get(0).and_then( | v | v.as_str()).unwrap_or("");
+                    return Err(format!("{}: {}", reply.error_name.clone().unwrap_or(String::new()), text))
+                }
+                return Ok(reply)
+            }
+            self.queued.push_back(reply);
+        }
+    }
+    
+    pub fn next_message(&mut self) -> Result<DbusMessage, String> {
+        if let Some(msg) = self.queued.pop_front() {
+            return Ok(msg)
+        }
+        self.read_message()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn round_trip(values: &[DbusValue]) -> Vec<DbusValue> {
+        let mut writer = DbusWriter::default();
+        for value in values {
+            writer.write(value);
+        }
+        let mut reader = DbusReader {buf: &writer.buf, pos: 0, big_endian: false};
+        let mut out = Vec::new();
+        for value in values {
+            out.push(reader.read(&value.signature()).unwrap());
+        }
+        assert_eq!(reader.pos, writer.buf.len());
+        out
+    }
+    
+    #[test]
+    fn basic_values_round_trip() {
+        // every value after a byte, so each one has to get its alignment right
+        let values = vec![
+            DbusValue::Byte(7),
+            DbusValue::Bool(true),
+            DbusValue::Byte(1),
+            DbusValue::Int16(-2),
+            DbusValue::Uint16(3),
+            DbusValue::Byte(1),
+            DbusValue::Int32(-4),
+            DbusValue::Uint32(5),
+            DbusValue::Byte(1),
+            DbusValue::Int64(-6),
+            DbusValue::Uint64(7),
+            DbusValue::Double(0.5),
+            DbusValue::str("hello"),
+            DbusValue::ObjectPath("/org/a11y/bus".to_string()),
+            DbusValue::Signature("a{sv}".to_string()),
+        ];
+        assert_eq!(round_trip(&values), values);
+    }
+    
+    #[test]
+    fn containers_round_trip() {
+        let values = vec![
+            DbusValue::Byte(1),
+            DbusValue::Array("s".to_string(), vec![DbusValue::str("a"), DbusValue::str("bc")]),
+            // empty arrays still pad to their element
+            DbusValue::Array("x".to_string(), Vec::new()),
+            DbusValue::Byte(1),
+            DbusValue::Struct(vec![DbusValue::Byte(2), DbusValue::Uint64(3), DbusValue::str("d")]),
+            DbusValue::Array("{sv}".to_string(), vec![
+                DbusValue::DictEntry(Box::new(DbusValue::str("focused")), Box::new(DbusValue::variant(DbusValue::Bool(true)))),
+                DbusValue::DictEntry(Box::new(DbusValue::str("nested")), Box::new(DbusValue::variant(DbusValue::Array("i".to_string(), vec![DbusValue::Int32(1)])))),
+            ]),
+            DbusValue::Array("ai".to_string(), vec![
+                DbusValue::Array("i".to_string(), vec![DbusValue::Int32(1), DbusValue::Int32(2)]),
+                DbusValue::Array("i".to_string(), Vec::new()),
+            ]),
+        ];
+        assert_eq!(round_trip(&values), values);
+    }
+    
+    #[test]
+    fn messages_round_trip() {
+        let msg = DbusMessage::method_call(
+            "org.a11y.Bus",
+            "/org/a11y/bus",
+            "org.freedesktop.DBus.Properties",
+            "Get",
+            vec![DbusValue::str("org.a11y.Status"), DbusValue::str("IsEnabled")]
+        );
+        let parsed = DbusMessage::parse(&msg.serialize(42)).unwrap();
+        assert_eq!(parsed.message_type, DbusMessageType::MethodCall);
+        assert_eq!(parsed.serial, 42);
+        assert_eq!(parsed.destination.as_ref().map( | v | v.as_str()), Some("org.a11y.Bus"));
+        assert_eq!(parsed.path.as_ref().map( | v | v.as_str()), Some("/org/a11y/bus"));
+        assert_eq!(parsed.member.as_ref().map( | v | v.as_str()), Some("Get"));
+        assert_eq!(parsed.body, msg.body);
+        
+        let reply = DbusMessage::parse(&parsed.error("org.freedesktop.DBus.Error.Failed", "nope").serialize(43)).unwrap();
+        assert_eq!(reply.message_type, DbusMessageType::Error);
+        assert_eq!(reply.reply_serial, Some(42));
+        assert_eq!(reply.body, vec![DbusValue::str("nope")]);
+    }
+    
+    #[test]
+    fn broken_input_is_an_error() {
+        let buf = [0u8; 64];
+        for sig in &["", "a", "(", "()", "(i", "{", "{s}", "{sv", "aa", "z"] {
+            let mut reader = DbusReader {buf: &buf, pos: 0, big_endian: false};
+            assert!(reader.read(sig).is_err(), "{} should not read", sig);
+        }
+        // a string that claims to be longer than the message
+        let mut reader = DbusReader {buf: &[100, 0, 0, 0, b'a'], pos: 0, big_endian: false};
+        assert!(reader.read("s").is_err());
+        assert!(DbusMessage::parse(&[b'l', 1, 0, 1]).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::cx::*;
+
+// generic drag and drop. a widget starts dragging a payload, everything under the mouse gets
+// Event::Drag and can accept it (that is the hover feedback), the one that accepted last gets
+// the Event::Drop. drags coming in from other applications go through the same events
+#[derive(Clone, Debug, PartialEq)]
+pub enum DragPayload {
+    Text(String),
+    Files(Vec<String>),
+}
+
+impl DragPayload {
+    pub fn to_text(&self) -> String {
+        match self {
+            DragPayload::Text(text) => text.clone(),
+            DragPayload::Files(paths) => paths.join("\n")
+        }
+    }
+    
+    // text/uri-list as other applications want it
+    pub fn to_uri_list(&self) -> String {
+        let mut out = String::new();
+        match self {
+            DragPayload::Text(text) => out.push_str(text),
+            DragPayload::Files(paths) => for path in paths {
+                out.push_str("file://");
+                for byte in path.bytes() {
+                    match byte {
+                        b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+                        _ => out.push_str(&format!("%{:02X}", byte))
+                    }
+                }
+                out.push_str("\r\n");
+            }
+        }
+        out
+    }
+    
+    pub fn from_uri_list(uri_list: &str) -> DragPayload {
+        let mut paths = Vec::new();
+        for line in uri_list.lines() {
+            let line = line.trim();
+            if line.len() == 0 || line.starts_with('#') {
+                continue;
+            }
+            let path = if line.starts_with("file://") {
+                // skip the hostname, if any
+                let rest = &line[7..];
+                if let Some(slash) = rest.find('/') {&rest[slash..]} else {rest}
+            }
+            else {
+                line
+            };
+            let bytes = path.as_bytes();
+            let mut decoded = Vec::new();
+            let mut i = 0;
+            while i < bytes.len() {
+                // from_str_radix takes a sign too, so check for two hex digits first
+                if bytes[i] == b'%' && i + 2 < bytes.len() && bytes[i + 1..i + 3].iter().all( | c | c.is_ascii_hexdigit()) {
+                    let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap();
+                    decoded.push(u8::from_str_radix(hex, 16).unwrap());
+                    i += 3;
+                    continue;
+                }
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+            paths.push(String::from_utf8_lossy(&decoded).to_string());
+        }
+        DragPayload::Files(paths)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DragAction {
+    None,
+    Copy,
+    Move,
+}
+
+impl Default for DragAction {
+    fn default() -> DragAction {
+        DragAction::None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DragEvent {
+    pub window_id: usize,
+    pub abs: Vec2,
+    pub rel: Vec2,
+    pub rect: Rect,
+    pub state: HoverState,
+    pub payload: DragPayload,
+    pub handled: bool,
+    // what the target under the mouse accepted, platforms read it back after the call
+    pub action: DragAction,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DropEvent {
+    pub window_id: usize,
+    pub abs: Vec2,
+    pub rel: Vec2,
+    pub rect: Rect,
+    pub payload: DragPayload,
+    pub action: DragAction,
+    // dropped in from another application
+    pub is_external: bool,
+    pub handled: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct CxDrag {
+    pub payload: Option<DragPayload>,
+    pub window_id: usize,
+    pub abs: Vec2,
+    pub action: DragAction,
+    pub over_last: Area,
+    pub _over_last: Area,
+    // the platform picks this up to also offer the drag to other applications
+    pub outgoing: Option<DragPayload>,
+    pub was_dropped: bool,
+    pub external: bool,
+    pub source_cursor: Option<MouseCursor>,
+}
+
+impl Cx {
+    // call from a FingerMove of a captured finger, the drag ends when that finger goes up
+    pub fn start_dragging(&mut self, payload: DragPayload) {
+        self.drag.payload = Some(payload.clone());
+        self.drag.outgoing = Some(payload);
+        self.drag.action = DragAction::None;
+        self.drag.was_dropped = false;
+        self.drag.external = false;
+        self.drag.source_cursor = self.down_mouse_cursor.clone();
+    }
+    
+    pub fn is_dragging(&self) -> bool {
+        self.drag.payload.is_some()
+    }
+    
+    // call from an Event::Drag to become the drop target
+    pub fn accept_drag(&mut self, action: DragAction) {
+        self.drag.action = action;
+    }
+    
+    pub fn call_drag_event<F>(&mut self, mut event_handler: F, window_id: usize, abs: Vec2) -> DragAction
+    where F: FnMut(&mut Cx, &mut Event)
+    {
+        let payload = if let Some(payload) = &self.drag.payload {payload.clone()} else {return DragAction::None};
+        self.drag.window_id = window_id;
+        self.drag.abs = abs;
+        self.drag.action = DragAction::None;
+        self.drag.over_last = Area::Empty;
+        self.call_event_handler(&mut event_handler, &mut Event::Drag(DragEvent {
+            window_id: window_id,
+            abs: abs,
+            rel: abs,
+            rect: Rect::default(),
+            state: HoverState::Over,
+            payload: payload,
+            handled: false,
+            action: DragAction::None
+        }));
+        self.drag._over_last = self.drag.over_last;
+        if self.drag._over_last.is_empty() {
+            self.drag.action = DragAction::None;
+        }
+        self.drag.action
+    }
+    
+    // the target that accepted last gets the payload, returns what it accepted
+    pub fn call_drop_event<F>(&mut self, mut event_handler: F, window_id: usize, abs: Vec2) -> DragAction
+    where F: FnMut(&mut Cx, &mut Event)
+    {
+        let payload = if let Some(payload) = self.drag.payload.clone() {payload} else {return DragAction::None};
+        let action = self.drag.action;
+        if action != DragAction::None {
+            self.call_event_handler(&mut event_handler, &mut Event::Drop(DropEvent {
+                window_id: window_id,
+                abs: abs,
+                rel: abs,
+                rect: Rect::default(),
+                payload: payload,
+                action: action,
+                is_external: self.drag.external,
+                handled: false
+            }));
+        }
+        self.call_drag_end_event(&mut event_handler);
+        self.drag.was_dropped = action != DragAction::None;
+        action
+    }
+    
+    pub fn call_drag_end_event<F>(&mut self, mut event_handler: F)
+    where F: FnMut(&mut Cx, &mut Event)
+    {
+        if self.drag.payload.is_none() {
+            return
+        }
+        self.call_event_handler(&mut event_handler, &mut Event::DragEnd);
+        self.drag.payload = None;
+        self.drag.action = DragAction::None;
+        self.drag.over_last = Area::Empty;
+        self.drag._over_last = Area::Empty;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn files(paths: &[&str]) -> DragPayload {
+        DragPayload::Files(paths.iter().map( | path | path.to_string()).collect())
+    }
+    
+    #[test]
+    fn uri_lists_round_trip() {
+        let payload = files(&["/home/me/my file.txt", "/tmp/caf\u{e9}/\u{65e5}\u{672c}.rs", "/a%b#c"]);
+        let uri_list = payload.to_uri_list();
+        assert_eq!(uri_list, "file:///home/me/my%20file.txt\r\nfile:///tmp/caf%C3%A9/%E6%97%A5%E6%9C%AC.rs\r\nfile:///a%25b%23c\r\n");
+        assert_eq!(DragPayload::from_uri_list(&uri_list), payload);
+    }
+    
+    #[test]
+    fn uri_lists_from_other_applications() {
+        // a hostname, comments, blank lines, lf and crlf line ends and a plain path
+        let uri_list = "# dragged from a file manager\r\nfile://localhost/etc/hosts\r\n\r\nfile:///tmp/x\n/plain/path\n";
+        assert_eq!(DragPayload::from_uri_list(uri_list), files(&["/etc/hosts", "/tmp/x", "/plain/path"]));
+    }
+    
+    #[test]
+    fn broken_escapes_are_kept() {
+        let uri_list = "file:///100%\r\nfile:///50%2\r\nfile:///x%zz%+1\r\n";
+        assert_eq!(DragPayload::from_uri_list(uri_list), files(&["/100%", "/50%2", "/x%zz%+1"]));
+    }
+}
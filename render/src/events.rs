@@ -291,6 +291,11 @@ pub enum Event {
     KeyUp(KeyEvent),
     TextInput(TextInputEvent),
     TextCopy(TextCopyEvent),
+    AccessAction(AccessActionEvent),
+    Drag(DragEvent),
+    Drop(DropEvent),
+    DragEnd,
+    ClipboardRead(ClipboardReadEvent),
     ShaderRecompile(ShaderRecompileEvent)
 }
 
@@ -338,6 +343,11 @@ impl Event {
                     return self.clone();
                 }
             },
+            Event::Command(_) => {
+                if area == cx.key_focus {
+                    return self.clone();
+                }
+            },
             Event::TextCopy(_) => {
                 if area == cx.key_focus {
                     return Event::TextCopy(
@@ -345,6 +355,11 @@ impl Event {
                     );
                 }
             },
+            Event::AccessAction(ae) => {
+                if area == ae.area {
+                    return self.clone();
+                }
+            },
             Event::Animate(_) => {
                 for anim in &cx.playing_anim_areas {
                     if anim.area == area {
@@ -436,6 +451,44 @@ impl Event {
                     }
                 }
             },
+            Event::Drag(de) => {
+                let rect = area.get_rect(&cx);
+                if !de.handled && rect.contains_with_margin(de.abs.x, de.abs.y, &opt.margin) {
+                    de.handled = true;
+                    cx.drag.over_last = area;
+                    return Event::Drag(DragEvent {
+                        rel: area.abs_to_rel(cx, de.abs),
+                        rect: rect,
+                        state: if cx.drag._over_last == area {HoverState::Over} else {HoverState::In},
+                        ..de.clone()
+                    })
+                }
+                else if cx.drag._over_last == area {
+                    return Event::Drag(DragEvent {
+                        rel: area.abs_to_rel(cx, de.abs),
+                        rect: rect,
+                        state: HoverState::Out,
+                        ..de.clone()
+                    })
+                }
+            },
+            Event::Drop(de) => {
+                // only the area that accepted the drag last gets it
+                if !de.handled && cx.drag._over_last == area {
+                    de.handled = true;
+                    let rect = area.get_rect(&cx);
+                    return Event::Drop(DropEvent {
+                        rel: area.abs_to_rel(cx, de.abs),
+                        rect: rect,
+                        ..de.clone()
+                    })
+                }
+            },
+            Event::DragEnd => {
+                if cx.drag._over_last == area {
+                    return Event::DragEnd
+                }
+            },
             Event::FingerMove(fe) => {
                 // check wether our digit is captured, otherwise don't send
                 if cx.fingers[fe.digit].captured == area {
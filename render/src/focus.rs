@@ -0,0 +1,382 @@
+use crate::cx::*;
+use std::collections::HashMap;
+use std::cmp::Ordering;
+
+// keyboard focus navigation. focusable widgets register their area while they draw, inside
+// focus scopes. tab and shift+tab cycle through the trap the key focus is in, in the order
+// things are on screen. rows and columns (tab bars, lists) are a single tab stop that the
+// arrow keys move through. like accessibility nodes, all of it belongs to the view it was drawn in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FocusScope {
+    // tab never leaves it, f6 moves on to the next one. dialogs, dock panels
+    Trap,
+    // left and right arrows
+    Row,
+    // up and down arrows
+    Column,
+}
+
+#[derive(Clone, Debug)]
+pub struct CxFocusScope {
+    pub key: u64,
+    pub parent_key: u64,
+    pub window_id: usize,
+    pub view_id: usize,
+    pub scope: FocusScope,
+}
+
+#[derive(Clone, Debug)]
+pub struct CxFocusItem {
+    pub area: Area,
+    pub scope_key: u64,
+    pub window_id: usize,
+    pub view_id: usize,
+    pub selected: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct CxFocus {
+    pub ring_visible: bool,
+    pub scopes: Vec<CxFocusScope>,
+    pub items: Vec<CxFocusItem>,
+    pub stack: Vec<u64>,
+    pub ordinals: HashMap<(u64, usize), usize>,
+}
+
+// top to bottom, then left to right
+fn focus_reading_order(mut items: Vec<(usize, Rect)>) -> Vec<usize> {
+    items.sort_by( | (_, a), (_, b) | {
+        a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal).then(a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal))
+    });
+    items.into_iter().map( | (index, _) | index).collect()
+}
+
+impl Cx {
+    pub(crate) fn focus_begin_view(&mut self, view_id: usize) {
+        if self.view_stack.len() <= 1 {
+            self.focus.stack.truncate(0);
+        }
+        self.focus.scopes.retain( | scope | scope.view_id != view_id);
+        self.focus.items.retain( | item | item.view_id != view_id);
+        self.focus.ordinals.retain( | (_, ordinal_view_id), _ | *ordinal_view_id != view_id);
+    }
+    
+    fn focus_current_scope(&self) -> u64 {
+        if let Some(key) = self.focus.stack.last() {*key} else {access_window_key(*self.window_stack.last().unwrap_or(&0))}
+    }
+    
+    pub fn begin_focus_scope(&mut self, scope: FocusScope) {
+        let window_id = *self.window_stack.last().unwrap_or(&0);
+        let view_id = *self.view_stack.last().unwrap_or(&0);
+        let parent_key = self.focus_current_scope();
+        let ordinal = self.focus.ordinals.entry((parent_key, view_id)).or_insert(0);
+        let key = access_key(parent_key, view_id, *ordinal);
+        *ordinal += 1;
+        self.focus.scopes.push(CxFocusScope {
+            key: key,
+            parent_key: parent_key,
+            window_id: window_id,
+            view_id: view_id,
+            scope: scope
+        });
+        self.focus.stack.push(key);
+    }
+    
+    pub fn end_focus_scope(&mut self) {
+        self.focus.stack.pop();
+    }
+    
+    // selected is where the keyboard enters a row or column, f.ex. the selected tab
+    pub fn add_focusable(&mut self, area: Area, selected: bool) {
+        if area.is_empty() {
+            return
+        }
+        let scope_key = self.focus_current_scope();
+        self.focus.items.push(CxFocusItem {
+            area: area,
+            scope_key: scope_key,
+            window_id: *self.window_stack.last().unwrap_or(&0),
+            view_id: *self.view_stack.last().unwrap_or(&0),
+            selected: selected
+        });
+    }
+    
+    // where to draw the focus ring in a pass, only after the keyboard moved the focus
+    pub fn get_focus_ring_rect(&self, pass_id: usize) -> Option<Rect> {
+        if !self.focus.ring_visible || !self.key_focus.is_valid(self) {
+            return None
+        }
+        if !self.focus.items.iter().any( | item | item.area == self.key_focus) {
+            return None
+        }
+        let view_id = match self.key_focus {
+            Area::Instance(inst) => inst.view_id,
+            Area::View(view_area) => view_area.view_id,
+            _ => return None
+        };
+        if self.views[view_id].pass_id != pass_id {
+            return None
+        }
+        let rect = self.key_focus.get_rect(self);
+        if rect.w <= 0. || rect.h <= 0. {
+            return None
+        }
+        Some(rect)
+    }
+    
+    // the scope an item sits in, then its parents up to the window
+    fn focus_path(&self, scope_key: u64) -> Vec<u64> {
+        let mut path = vec![scope_key];
+        let mut key = scope_key;
+        while let Some(scope) = self.focus.scopes.iter().find( | scope | scope.key == key) {
+            key = scope.parent_key;
+            path.push(key);
+        }
+        path
+    }
+    
+    fn focus_scope_kind(&self, key: u64) -> FocusScope {
+        // the window itself is not a registered scope, it traps like a dialog does
+        self.focus.scopes.iter().find( | scope | scope.key == key).map( | scope | scope.scope).unwrap_or(FocusScope::Trap)
+    }
+    
+    fn focus_trap(&self, item: usize) -> u64 {
+        let path = self.focus_path(self.focus.items[item].scope_key);
+        *path.iter().find( | key | self.focus_scope_kind(**key) == FocusScope::Trap).unwrap()
+    }
+    
+    // focusable items of a window that are on screen, sorted in reading order
+    fn focus_visible_items(&self, window_id: usize) -> Vec<usize> {
+        let mut views = Vec::new();
+        if let Some(main_pass_id) = self.windows[window_id].main_pass_id {
+            if let Some(main_view_id) = self.passes[main_pass_id].main_view_id {
+                self.collect_visible_views(main_view_id, &mut views);
+            }
+        }
+        let items: Vec<(usize, Rect)> = self.focus.items.iter().enumerate().filter( | (_, item) | {
+            item.window_id == window_id && views.contains(&item.view_id) && item.area.is_valid(self)
+        }).map( | (index, item) | (index, item.area.get_rect_unscrolled(self))).collect();
+        focus_reading_order(items)
+    }
+    
+    // every row or column directly inside the trap collapses into one stop, traps nested in it have their own
+    fn focus_tab_stops(&self, items: &[usize], trap: u64, current: Option<usize>) -> Vec<usize> {
+        let mut stops: Vec<usize> = Vec::new();
+        let mut groups: Vec<(u64, usize)> = Vec::new();
+        for index in items {
+            let path = self.focus_path(self.focus.items[*index].scope_key);
+            let inside = match path.iter().position( | key | *key == trap) {
+                Some(inside) => inside,
+                None => continue
+            };
+            if path[0..inside].iter().any( | key | self.focus_scope_kind(*key) == FocusScope::Trap) {
+                continue
+            }
+            let group = path[0..inside].last();
+            if let Some(group) = group {
+                if let Some((_, stop)) = groups.iter().find( | (key, _) | key == group) {
+                    let stop = &mut stops[*stop];
+                    if Some(*stop) != current && (Some(*index) == current || self.focus.items[*index].selected && !self.focus.items[*stop].selected) {
+                        *stop = *index;
+                    }
+                    continue;
+                }
+                groups.push((*group, stops.len()));
+            }
+            stops.push(*index);
+        }
+        stops
+    }
+    
+    fn focus_next_tab_stop(&self, items: &[usize], current: Option<usize>, back: bool) -> Option<usize> {
+        let trap = match current {
+            Some(current) => self.focus_trap(current),
+            None => access_window_key(self.focus.items[*items.first() ?].window_id)
+        };
+        let stops = self.focus_tab_stops(items, trap, current);
+        if stops.len() == 0 {
+            return None
+        }
+        let next = match current.and_then( | current | stops.iter().position( | stop | *stop == current)) {
+            Some(pos) if back => (pos + stops.len() - 1) % stops.len(),
+            Some(pos) => (pos + 1) % stops.len(),
+            None if back => stops.len() - 1,
+            None => 0
+        };
+        Some(stops[next])
+    }
+    
+    fn focus_next_trap(&self, items: &[usize], current: Option<usize>, back: bool) -> Option<usize> {
+        let mut traps: Vec<u64> = Vec::new();
+        for index in items {
+            let trap = self.focus_trap(*index);
+            if !traps.contains(&trap) {
+                traps.push(trap);
+            }
+        }
+        if traps.len() == 0 {
+            return None
+        }
+        let next = match current.and_then( | current | traps.iter().position( | trap | *trap == self.focus_trap(current))) {
+            Some(pos) if back => (pos + traps.len() - 1) % traps.len(),
+            Some(pos) => (pos + 1) % traps.len(),
+            None => 0
+        };
+        let trap = traps[next];
+        self.focus_tab_stops(items, trap, None).into_iter().next()
+    }
+    
+    fn focus_next_in_group(&self, items: &[usize], current: usize, key_code: KeyCode) -> Option<usize> {
+        let path = self.focus_path(self.focus.items[current].scope_key);
+        let group = *path.iter().take_while( | key | self.focus_scope_kind(**key) != FocusScope::Trap).next() ?;
+        let step = match (self.focus_scope_kind(group), key_code) {
+            (FocusScope::Row, KeyCode::ArrowLeft) | (FocusScope::Column, KeyCode::ArrowUp) => -1,
+            (FocusScope::Row, KeyCode::ArrowRight) | (FocusScope::Column, KeyCode::ArrowDown) => 1,
+            (_, KeyCode::Home) => -(items.len() as isize),
+            (_, KeyCode::End) => items.len() as isize,
+            _ => return None
+        };
+        let members: Vec<usize> = items.iter().cloned().filter( | index | {
+            self.focus_path(self.focus.items[*index].scope_key).contains(&group)
+        }).collect();
+        let pos = members.iter().position( | index | *index == current) ? as isize;
+        // no wrapping, pressing on at the ends just stays put
+        let next = (pos + step).max(0).min(members.len() as isize - 1);
+        Some(members[next as usize])
+    }
+    
+    // returns true when the key moved the focus and should not go to the widgets
+    pub(crate) fn process_focus_key_down(&mut self, ke: &KeyEvent) -> bool {
+        let modifiers = &ke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.logo {
+            return false
+        }
+        match ke.key_code {
+            KeyCode::Tab | KeyCode::F6 | KeyCode::ArrowLeft | KeyCode::ArrowRight | KeyCode::ArrowUp | KeyCode::ArrowDown
+                | KeyCode::Home | KeyCode::End => (),
+            _ => return false
+        }
+        let mut current = self.focus.items.iter().position( | item | item.area == self.key_focus);
+        // something that did not register (a code editor) has the keyboard, it keeps its tabs
+        if current.is_none() && self.key_focus.is_valid(self) {
+            return false
+        }
+        let window_id = match current {
+            Some(current) => self.focus.items[current].window_id,
+            None => (0..self.windows.len()).find( | window_id | self.focus_visible_items(*window_id).len() > 0).unwrap_or(0)
+        };
+        if window_id >= self.windows.len() {
+            return false
+        }
+        let items = self.focus_visible_items(window_id);
+        if let Some(index) = current {
+            if !items.contains(&index) {
+                current = None;
+            }
+        }
+        let next = match ke.key_code {
+            KeyCode::Tab => self.focus_next_tab_stop(&items, current, modifiers.shift),
+            KeyCode::F6 => self.focus_next_trap(&items, current, modifiers.shift),
+            _ => if let Some(current) = current {
+                self.focus_next_in_group(&items, current, ke.key_code)
+            }
+            else {
+                None
+            }
+        };
+        if let Some(next) = next {
+            self.set_key_focus(self.focus.items[next].area);
+            self.focus.ring_visible = true;
+            return true
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn focusable(cx: &mut Cx, selected: bool) -> usize {
+        let scope_key = cx.focus_current_scope();
+        cx.focus.items.push(CxFocusItem {
+            area: Area::Empty,
+            scope_key: scope_key,
+            window_id: 0,
+            view_id: 0,
+            selected: selected
+        });
+        cx.focus.items.len() - 1
+    }
+    
+    // a button, a row of three tabs with the second one selected, a button and a dialog with two buttons
+    fn focus_cx() -> (Cx, Vec<usize>) {
+        let mut cx = Cx::default();
+        let mut items = Vec::new();
+        items.push(focusable(&mut cx, false));
+        cx.begin_focus_scope(FocusScope::Row);
+        items.push(focusable(&mut cx, false));
+        items.push(focusable(&mut cx, true));
+        items.push(focusable(&mut cx, false));
+        cx.end_focus_scope();
+        items.push(focusable(&mut cx, false));
+        cx.begin_focus_scope(FocusScope::Trap);
+        items.push(focusable(&mut cx, false));
+        items.push(focusable(&mut cx, false));
+        cx.end_focus_scope();
+        (cx, items)
+    }
+    
+    fn rect(x: f32, y: f32) -> Rect {
+        Rect {x: x, y: y, w: 10., h: 10.}
+    }
+    
+    #[test]
+    fn items_are_in_reading_order() {
+        let items = vec![(0, rect(50., 20.)), (1, rect(0., 20.)), (2, rect(90., 0.)), (3, rect(10., 0.))];
+        assert_eq!(focus_reading_order(items), vec![3, 2, 1, 0]);
+    }
+    
+    #[test]
+    fn a_row_is_one_tab_stop_entered_at_the_selected_item() {
+        let (cx, items) = focus_cx();
+        let window = access_window_key(0);
+        // the dialog is a trap of its own, its items are no stops of the window
+        assert_eq!(cx.focus_tab_stops(&items, window, None), vec![0, 2, 4]);
+        // the focused item stands in for its row
+        assert_eq!(cx.focus_tab_stops(&items, window, Some(3)), vec![0, 3, 4]);
+        assert_eq!(cx.focus_next_tab_stop(&items, Some(0), false), Some(2));
+        assert_eq!(cx.focus_next_tab_stop(&items, Some(3), false), Some(4));
+        assert_eq!(cx.focus_next_tab_stop(&items, Some(4), false), Some(0));
+        assert_eq!(cx.focus_next_tab_stop(&items, Some(4), true), Some(2));
+        assert_eq!(cx.focus_next_tab_stop(&items, None, false), Some(0));
+        assert_eq!(cx.focus_next_tab_stop(&items, None, true), Some(4));
+    }
+    
+    #[test]
+    fn tab_never_leaves_a_trap_and_f6_cycles_the_traps() {
+        let (cx, items) = focus_cx();
+        assert_eq!(cx.focus_next_tab_stop(&items, Some(5), false), Some(6));
+        assert_eq!(cx.focus_next_tab_stop(&items, Some(6), false), Some(5));
+        assert_eq!(cx.focus_next_tab_stop(&items, Some(5), true), Some(6));
+        assert_eq!(cx.focus_next_trap(&items, Some(0), false), Some(5));
+        assert_eq!(cx.focus_next_trap(&items, Some(6), false), Some(0));
+        assert_eq!(cx.focus_next_trap(&items, Some(0), true), Some(5));
+        // f6 enters a row at its selected item
+        assert_eq!(cx.focus_next_trap(&items[1..], Some(6), false), Some(2));
+    }
+    
+    #[test]
+    fn arrow_keys_move_through_a_group_without_wrapping() {
+        let (cx, items) = focus_cx();
+        assert_eq!(cx.focus_next_in_group(&items, 1, KeyCode::ArrowRight), Some(2));
+        assert_eq!(cx.focus_next_in_group(&items, 3, KeyCode::ArrowRight), Some(3));
+        assert_eq!(cx.focus_next_in_group(&items, 1, KeyCode::ArrowLeft), Some(1));
+        assert_eq!(cx.focus_next_in_group(&items, 1, KeyCode::End), Some(3));
+        assert_eq!(cx.focus_next_in_group(&items, 3, KeyCode::Home), Some(1));
+        // a row doesnt take up and down, and items outside a group take no arrows at all
+        assert_eq!(cx.focus_next_in_group(&items, 2, KeyCode::ArrowDown), None);
+        assert_eq!(cx.focus_next_in_group(&items, 0, KeyCode::ArrowRight), None);
+        assert_eq!(cx.focus_next_in_group(&items, 5, KeyCode::ArrowRight), None);
+    }
+}
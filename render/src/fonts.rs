@@ -1,8 +1,9 @@
 use crate::cx::*;
 use makepad_trapezoidator::Trapezoidator;
-use makepad_geometry::{AffineTransformation, Transform, Vector};
+use makepad_geometry::{AffineTransformation, Transform, Vector, Point};
 use makepad_internal_iter::*;
-use makepad_path::PathIterator;
+use makepad_path::{PathIterator, LinePathCommand};
+use makepad_live_compiler::livetypes;
 
 #[derive(Clone, Copy, Default)]
 pub struct Font{pub font_id:Option<usize>}
@@ -17,13 +18,117 @@ impl Cx {
     pub fn reset_font_atlas_and_redraw(&mut self) {
         for font in &mut self.fonts {
             font.atlas_pages.truncate(0);
+            font.sdf_glyphs.truncate(0);
         }
-        self.fonts_atlas.alloc_xpos = 0.;
-        self.fonts_atlas.alloc_ypos = 0.;
-        self.fonts_atlas.alloc_hmax = 0.;
-        self.fonts_atlas.clear_buffer = true;
+        self.fonts_atlas.reset_alloc();
+        self.fonts_sdf_atlas.reset_alloc();
         self.redraw_child_area(Area::All);
     }
+    
+    // called after a draw that ran out of space on every atlas texture. the texture drawn with
+    // longest ago is emptied, so only the glyphs that were on it get rasterized again
+    pub fn recover_font_atlas(&mut self) {
+        self.fonts_atlas.alloc_failed = false;
+        let redraw_id = self.redraw_id;
+        let oldest = self.fonts_atlas.textures.iter().enumerate().min_by_key( | (_, texture) | texture.last_used);
+        let texture_index = match oldest {
+            Some((index, texture)) if texture.last_used < redraw_id => index,
+            _ => { // what a single frame draws doesnt fit, evicting wont help
+                self.fonts_atlas.stats.overflows += 1;
+                return
+            }
+        };
+        for font in &mut self.fonts {
+            self.fonts_atlas.stats.evicted_pages += font.evict_atlas_pages(redraw_id, texture_index);
+        }
+        self.fonts_atlas.stats.resets += 1;
+        self.fonts_atlas.reset_texture(texture_index);
+        self.redraw_child_area(Area::All);
+    }
+    
+    // sdf glyphs serve every size so there is nothing old to evict, start over
+    pub fn recover_font_sdf_atlas(&mut self) {
+        self.fonts_sdf_atlas.alloc_failed = false;
+        let redraw_id = self.redraw_id;
+        if self.fonts_sdf_atlas.last_reset_redraw_id + 1 >= redraw_id {
+            // the last reset didnt make enough room, another one wont either
+            self.fonts_sdf_atlas.stats.overflows += 1;
+            return
+        }
+        for font in &mut self.fonts {
+            font.sdf_glyphs.truncate(0);
+        }
+        self.fonts_sdf_atlas.stats.resets += 1;
+        self.fonts_sdf_atlas.last_reset_redraw_id = redraw_id;
+        self.fonts_sdf_atlas.reset_alloc();
+        self.redraw_child_area(Area::All);
+    }
+    
+    // sdf glyphs are rasterized on the cpu straight into the image of the sdf texture
+    pub fn rasterize_font_sdf_atlas(&mut self) {
+        let atlas = &mut self.fonts_sdf_atlas;
+        let width = atlas.texture_size.x as usize;
+        let height = atlas.texture_size.y as usize;
+        for todo in atlas.atlas_todo.drain(..) {
+            let cxfont = &self.fonts[todo.font_id];
+            let font = cxfont.font_loaded.as_ref().unwrap();
+            let tc = if let Some(Some(tc)) = cxfont.sdf_glyphs.get(todo.glyph_id) {*tc} else {continue};
+            let atlas_texture = &mut atlas.textures[tc.texture_index];
+            let cxtexture = &mut self.textures[atlas_texture.texture_id];
+            if atlas_texture.clear_buffer || cxtexture.desc.width != Some(width) || cxtexture.desc.height != Some(height) {
+                atlas_texture.clear_buffer = false;
+                cxtexture.desc = TextureDesc {
+                    format: TextureFormat::ImageBGRA,
+                    width: Some(width),
+                    height: Some(height),
+                    multisample: None
+                };
+                cxtexture.image_u32 = vec![0; width * height];
+            }
+            let glyph = &font.glyphs[todo.glyph_id];
+            let (w, h) = CxFont::sdf_glyph_size(glyph, font.units_per_em);
+            let (w, h) = (w as usize, h as usize);
+            let x = (tc.tx1 * atlas.texture_size.x).round() as usize;
+            let y = (tc.ty1 * atlas.texture_size.y).round() as usize;
+            let sdf = CxFont::rasterize_sdf_glyph(glyph, font.units_per_em, w, h);
+            for row in 0..h {
+                for col in 0..w {
+                    let v = sdf[row * w + col] as u32;
+                    cxtexture.image_u32[(y + row) * width + x + col] = 0xff000000 | v << 16 | v << 8 | v;
+                }
+            }
+            cxtexture.update_image = true;
+        }
+    }
+    
+    pub fn set_font_fallbacks(&mut self, font: livetypes::Font, fallbacks: &[livetypes::Font]) {
+        let cxfont = &mut self.fonts[font.font_id];
+        cxfont.fallbacks.truncate(0);
+        for fallback in fallbacks {
+            cxfont.fallbacks.push(fallback.font_id);
+        }
+        self.redraw_child_area(Area::All);
+    }
+    
+    // registers the bold, italic or bold italic face of a font, rich text picks it up per span
+    pub fn set_font_variant(&mut self, font: livetypes::Font, variant: FontVariant, variant_font: livetypes::Font) {
+        let cxfont = &mut self.fonts[font.font_id];
+        cxfont.variants.retain( | (v, _) | *v != variant);
+        cxfont.variants.push((variant, variant_font.font_id));
+        self.redraw_child_area(Area::All);
+    }
+    
+    // the fallback chains declared in live styles, as font: ["primary.ttf", "fallback.ttf"]
+    pub fn apply_live_font_fallbacks(&mut self) {
+        for (font_id, fallbacks) in &self.live_styles.font_fallbacks {
+            if let Some(cxfont) = self.fonts.get_mut(*font_id) {
+                cxfont.fallbacks.truncate(0);
+                for fallback in fallbacks {
+                    cxfont.fallbacks.push(fallback.font_id);
+                }
+            }
+        }
+    }
 }
 
 pub struct TrapezoidText {
@@ -148,16 +253,12 @@ impl TrapezoidText {
             let cxfont = &cx.fonts[font_id];
             let font = cxfont.font_loaded.as_ref().unwrap();
             
-            let slot = if c < '\u{10000}' {
-                cx.fonts[font_id].font_loaded.as_ref().unwrap().char_code_to_glyph_index_map[c as usize]
-            } else {
-                0
-            };
+            let slot = font.glyph_index(c);
             
             if slot == 0 {
                 return
             }
-            let glyph = &cx.fonts[font_id].font_loaded.as_ref().unwrap().glyphs[slot];
+            let glyph = &font.glyphs[slot];
             let dpi_factor = cx.current_dpi_factor;
             let pos = cx.get_turtle_pos();
             let font_scale_logical = font_size * 96.0 / (72.0 * font.units_per_em);
@@ -200,7 +301,7 @@ impl TrapezoidText {
     }
     
     // atlas drawing function used by CxAfterDraw
-    pub fn draw_todo(&mut self, cx: &mut Cx, todo: CxFontsAtlasTodo) {
+    pub fn draw_todo(&mut self, cx: &mut Cx, todo: &CxFontsAtlasTodo) {
         let inst = cx.new_instance(live_shader!(cx, self::trapezoid_shader), None, 1);
         if inst.need_uniforms_now(cx) {
         }
@@ -219,9 +320,9 @@ impl TrapezoidText {
                 let atlas_page = &cxfont.atlas_pages[todo.atlas_page_id];
                 let glyph = &font.glyphs[todo.glyph_id];
                 
-                if todo.glyph_id == font.char_code_to_glyph_index_map[10] ||
-                todo.glyph_id == font.char_code_to_glyph_index_map[9] ||
-                todo.glyph_id == font.char_code_to_glyph_index_map[13] {
+                if todo.glyph_id == font.glyph_index('\n') ||
+                todo.glyph_id == font.glyph_index('\t') ||
+                todo.glyph_id == font.glyph_index('\r') {
                     return
                 }
                 
@@ -273,77 +374,121 @@ impl TrapezoidText {
 
 pub struct CxAfterDraw {
     pub trapezoid_text: TrapezoidText,
-    pub atlas_pass: Pass,
-    pub atlas_view: View,
-    pub atlas_texture: Texture
+    pub atlas_passes: Vec<Pass>,
+    pub atlas_views: Vec<View>,
+    pub atlas_textures: Vec<Texture>,
+    pub sdf_texture: Texture
 }
 
 impl CxAfterDraw {
     pub fn new(cx: &mut Cx) -> Self {
-        cx.fonts_atlas.texture_size = Vec2 {x: 2048.0, y: 2048.0};
-        let atlas_texture = Texture::new(cx);
-
-        cx.fonts_atlas.texture_id = atlas_texture.texture_id;
+        let mut atlas_textures = Vec::new();
+        let mut atlas_passes = Vec::new();
+        let mut atlas_views = Vec::new();
+        let mut texture_ids = Vec::new();
+        for _ in 0..ATLAS_TEXTURES {
+            let texture = Texture::new(cx);
+            texture_ids.push(texture.texture_id);
+            atlas_textures.push(texture);
+            atlas_passes.push(Pass::default());
+            atlas_views.push(View {
+                always_redraw: true,
+                ..View::new(cx)
+            });
+        }
+        cx.fonts_atlas.init(Vec2 {x: 2048.0, y: 2048.0}, texture_ids);
+        
+        let sdf_texture = Texture::new(cx);
+        cx.fonts_sdf_atlas.init(Vec2 {x: 2048.0, y: 2048.0}, vec![sdf_texture.texture_id]);
         
         Self {
             trapezoid_text: TrapezoidText::style(cx),
-            atlas_pass: Pass::default(),
-            atlas_view: View {
-                always_redraw: true,
-                ..View::new(cx)
-            },
-            atlas_texture: atlas_texture
+            atlas_passes: atlas_passes,
+            atlas_views: atlas_views,
+            atlas_textures: atlas_textures,
+            sdf_texture: sdf_texture
         }
     }
     
     pub fn after_draw(&mut self, cx: &mut Cx) {
         //let start = Cx::profile_time_ns();
+        cx.rasterize_font_sdf_atlas();
         
-        // we need to start a pass that just uses the texture
-        if cx.fonts_atlas.atlas_todo.len()>0 {
-            self.atlas_pass.begin_pass(cx);
-            self.atlas_pass.set_size(cx, cx.fonts_atlas.texture_size);
-            let clear = if cx.fonts_atlas.clear_buffer {
-                cx.fonts_atlas.clear_buffer = false;
+        // every atlas texture with new glyphs gets a pass that draws just those
+        let mut atlas_todo = Vec::new();
+        std::mem::swap(&mut cx.fonts_atlas.atlas_todo, &mut atlas_todo);
+        for texture_index in 0..cx.fonts_atlas.textures.len() {
+            if !atlas_todo.iter().any( | todo | todo.texture_index == texture_index) {
+                continue
+            }
+            let pass = &mut self.atlas_passes[texture_index];
+            pass.begin_pass(cx);
+            pass.set_size(cx, cx.fonts_atlas.texture_size);
+            let clear = if cx.fonts_atlas.textures[texture_index].clear_buffer {
+                cx.fonts_atlas.textures[texture_index].clear_buffer = false;
                 ClearColor::ClearWith(Color::default())
             }
             else {
                 ClearColor::InitWith(Color::default())
             };
-            self.atlas_pass.add_color_texture(cx, self.atlas_texture, clear);
-            let _ = self.atlas_view.begin_view(cx, Layout::default());
-            let mut atlas_todo = Vec::new();
-            std::mem::swap(&mut cx.fonts_atlas.atlas_todo, &mut atlas_todo);
-            for todo in atlas_todo {
+            pass.add_color_texture(cx, self.atlas_textures[texture_index], clear);
+            let _ = self.atlas_views[texture_index].begin_view(cx, Layout::default());
+            for todo in atlas_todo.iter().filter( | todo | todo.texture_index == texture_index) {
                 self.trapezoid_text.draw_todo(cx, todo);
-                // ok we have to draw a font_id
-                //break;
             }
-            self.atlas_view.end_view(cx);
-            self.atlas_pass.end_pass(cx);
+            self.atlas_views[texture_index].end_view(cx);
+            self.atlas_passes[texture_index].end_pass(cx);
+        }
+        
+        // the glyphs of this frame are drawn, now make room for what didnt fit
+        if cx.fonts_atlas.alloc_failed {
+            cx.recover_font_atlas();
+        }
+        if cx.fonts_sdf_atlas.alloc_failed {
+            cx.recover_font_sdf_atlas();
         }
         //println!("TOTALT TIME {}", Cx::profile_time_ns() - start);
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FontVariant {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic
+}
+
 #[derive(Default, Clone)]
 pub struct CxFont {
     pub file: String,
     pub font_loaded: Option<makepad_font::Font>,
+    pub fallbacks: Vec<usize>,
+    pub variants: Vec<(FontVariant, usize)>,
     pub atlas_pages: Vec<CxFontAtlasPage>,
+    pub sdf_glyphs: Vec<Option<CxFontAtlasGlyph>>,
 }
 
 pub const ATLAS_SUBPIXEL_SLOTS: usize = 64;
+// separate textures instead of one tall one, the text shader binds all of them
+pub const ATLAS_TEXTURES: usize = 4;
+pub const ATLAS_KEEP_FRAMES: u64 = 60;
+
+// sdf glyphs are rasterized at this many texels per em, with the distance ramp spread over the padding
+pub const SDF_GLYPH_EM: f32 = 48.0;
+pub const SDF_GLYPH_PAD: f32 = 4.0;
 
 #[derive(Clone)]
 pub struct CxFontAtlasPage {
     pub dpi_factor: f32,
     pub font_size: f32,
+    pub last_used: u64,
     pub atlas_glyphs: Vec<[Option<CxFontAtlasGlyph>; ATLAS_SUBPIXEL_SLOTS]>
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct CxFontAtlasGlyph {
+    pub texture_index: usize,
     pub tx1: f32,
     pub ty1: f32,
     pub tx2: f32,
@@ -357,49 +502,179 @@ pub struct CxFontsAtlasTodo {
     pub font_id: usize,
     pub atlas_page_id: usize,
     pub glyph_id: usize,
-    pub subpixel_id: usize
+    pub subpixel_id: usize,
+    pub texture_index: usize
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct CxFontsAtlasSkyline {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct CxFontsAtlasStats {
+    pub used_pixels: f32,
+    pub resets: usize,
+    pub evicted_pages: usize,
+    pub overflows: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct CxFontsAtlasTexture {
+    pub texture_id: usize,
+    pub last_used: u64,
+    pub clear_buffer: bool,
+    pub used_pixels: f32,
+    pub skyline: Vec<CxFontsAtlasSkyline>,
+}
+
+impl CxFontsAtlasTexture {
+    // the y at which a w wide rect fits if its left edge sits on skyline segment index
+    fn skyline_fit(&self, size: Vec2, index: usize, w: f32, h: f32) -> Option<f32> {
+        let x = self.skyline[index].x;
+        if x + w > size.x {
+            return None
+        }
+        let mut y = 0.0f32;
+        let mut width_left = w;
+        let mut i = index;
+        while width_left > 0.0 {
+            if i >= self.skyline.len() {
+                return None
+            }
+            y = y.max(self.skyline[i].y);
+            if y + h > size.y {
+                return None
+            }
+            width_left -= self.skyline[i].w;
+            i += 1;
+        }
+        Some(y)
+    }
+    
+    fn alloc(&mut self, size: Vec2, w: f32, h: f32) -> Option<(f32, f32)> {
+        // bottom left: the lowest spot, then the tightest segment
+        let mut best: Option<(usize, f32, f32)> = None;
+        for index in 0..self.skyline.len() {
+            if let Some(y) = self.skyline_fit(size, index, w, h) {
+                let seg_w = self.skyline[index].w;
+                if best.map_or(true, | (_, by, bw) | y + h < by || y + h == by && seg_w < bw) {
+                    best = Some((index, y + h, seg_w));
+                }
+            }
+        }
+        let (index, bottom, _) = best ?;
+        let x = self.skyline[index].x;
+        self.skyline.insert(index, CxFontsAtlasSkyline {x: x, y: bottom, w: w});
+        // shrink or remove the segments the new one covers
+        let i = index + 1;
+        while i < self.skyline.len() {
+            let prev_end = self.skyline[i - 1].x + self.skyline[i - 1].w;
+            let seg = &mut self.skyline[i];
+            if seg.x >= prev_end {
+                break
+            }
+            let shrink = prev_end - seg.x;
+            if seg.w <= shrink {
+                self.skyline.remove(i);
+                continue
+            }
+            seg.x += shrink;
+            seg.w -= shrink;
+            break
+        }
+        // merge neighbours at the same height
+        let mut i = 0;
+        while i + 1 < self.skyline.len() {
+            if self.skyline[i].y == self.skyline[i + 1].y {
+                self.skyline[i].w += self.skyline[i + 1].w;
+                self.skyline.remove(i + 1);
+            }
+            else {
+                i += 1;
+            }
+        }
+        self.used_pixels += w * h;
+        Some((x, bottom - h))
+    }
 }
 
 #[derive(Default)]
 pub struct CxFontsAtlas {
-    pub texture_id: usize,
+    pub texture_ids: Vec<usize>,
     pub texture_size: Vec2,
-    pub clear_buffer: bool,
-    pub alloc_xpos: f32,
-    pub alloc_ypos: f32,
-    pub alloc_hmax: f32,
+    pub textures: Vec<CxFontsAtlasTexture>,
+    pub alloc_failed: bool,
+    pub last_reset_redraw_id: u64,
+    pub stats: CxFontsAtlasStats,
     pub atlas_todo: Vec<CxFontsAtlasTodo>,
 }
 
 impl CxFontsAtlas {
-    pub fn alloc_atlas_glyph(&mut self, path: &str, w: f32, h: f32) -> CxFontAtlasGlyph {
-        if w + self.alloc_xpos >= self.texture_size.x {
-            self.alloc_xpos = 0.0;
-            self.alloc_ypos += self.alloc_hmax + 1.0;
-            self.alloc_hmax = 0.0;
-        }
-        if h + self.alloc_ypos >= self.texture_size.y {
-            println!("FONT ATLAS FULL {}, TODO FIX THIS", path);
+    pub fn init(&mut self, texture_size: Vec2, texture_ids: Vec<usize>) {
+        self.texture_size = texture_size;
+        self.texture_ids = texture_ids;
+        self.reset_alloc();
+    }
+    
+    pub fn reset_alloc(&mut self) {
+        self.textures.truncate(0);
+        self.stats.used_pixels = 0.;
+        self.atlas_todo.truncate(0);
+        self.add_texture();
+    }
+    
+    // the next texture only comes into use once the ones before it are full
+    fn add_texture(&mut self) -> bool {
+        if let Some(texture_id) = self.texture_ids.get(self.textures.len()) {
+            self.textures.push(CxFontsAtlasTexture {
+                texture_id: *texture_id,
+                last_used: 0,
+                clear_buffer: true,
+                used_pixels: 0.,
+                skyline: vec![CxFontsAtlasSkyline {x: 0., y: 0., w: self.texture_size.x}]
+            });
+            return true
         }
-        if h > self.alloc_hmax {
-            self.alloc_hmax = h;
+        false
+    }
+    
+    pub fn reset_texture(&mut self, texture_index: usize) {
+        let texture = &mut self.textures[texture_index];
+        self.stats.used_pixels -= texture.used_pixels;
+        texture.used_pixels = 0.;
+        texture.clear_buffer = true;
+        texture.skyline = vec![CxFontsAtlasSkyline {x: 0., y: 0., w: self.texture_size.x}];
+    }
+    
+    pub fn alloc_atlas_glyph(&mut self, w: f32, h: f32) -> Option<CxFontAtlasGlyph> {
+        // one pixel padding between glyphs
+        let pw = w + 1.0;
+        let ph = h + 1.0;
+        let size = self.texture_size;
+        let mut found = None;
+        for (index, texture) in self.textures.iter_mut().enumerate() {
+            if let Some(pos) = texture.alloc(size, pw, ph) {
+                found = Some((index, pos));
+                break
+            }
         }
-        
-        let tx1 = self.alloc_xpos / self.texture_size.x;
-        let ty1 = self.alloc_ypos / self.texture_size.y;
-        
-        self.alloc_xpos += w + 1.0;
-        
-        if h > self.alloc_hmax {
-            self.alloc_hmax = h;
+        if found.is_none() && self.add_texture() {
+            let index = self.textures.len() - 1;
+            found = self.textures[index].alloc(size, pw, ph).map( | pos | (index, pos));
         }
+        let (texture_index, (x, y)) = found ?;
+        self.stats.used_pixels += pw * ph;
         
-        CxFontAtlasGlyph {
-            tx1: tx1,
-            ty1: ty1,
-            tx2: tx1 + (w / self.texture_size.x),
-            ty2: ty1 + (h / self.texture_size.y)
-        }
+        Some(CxFontAtlasGlyph {
+            texture_index: texture_index,
+            tx1: x / size.x,
+            ty1: y / size.y,
+            tx2: (x + w) / size.x,
+            ty2: (y + h) / size.y
+        })
     }
 }
 
@@ -410,10 +685,180 @@ impl CxFont {
         Ok(())
     }
     
+    // the first font in the fallback chain that has a glyph for the char, or the missing glyph of the font itself
+    pub fn find_glyph(fonts: &[CxFont], font_id: usize, c: char) -> (usize, usize) {
+        let font = fonts[font_id].font_loaded.as_ref().unwrap();
+        let glyph_id = font.glyph_index(c);
+        if glyph_id != 0 {
+            return (font_id, glyph_id)
+        }
+        for fallback_id in &fonts[font_id].fallbacks {
+            if let Some(fallback) = fonts.get(*fallback_id).and_then( | f | f.font_loaded.as_ref()) {
+                let glyph_id = fallback.glyph_index(c);
+                if glyph_id != 0 {
+                    return (*fallback_id, glyph_id)
+                }
+            }
+        }
+        (font_id, 0)
+    }
+    
+    // the font to use for a variant, bold italic falls back to bold, then italic, then the font itself
+    pub fn variant_font_id(fonts: &[CxFont], font_id: usize, variant: FontVariant) -> usize {
+        let find = | variant: FontVariant | {
+            fonts[font_id].variants.iter().find( | (v, _) | *v == variant).map( | (_, id) | *id)
+        };
+        let found = match variant {
+            FontVariant::Regular => None,
+            FontVariant::BoldItalic => find(FontVariant::BoldItalic)
+                .or_else( || find(FontVariant::Bold))
+                .or_else( || find(FontVariant::Italic)),
+            _ => find(variant)
+        };
+        found.unwrap_or(font_id)
+    }
+    
+    // the texel size of the sdf atlas entry of a glyph, the same for every font size
+    pub fn sdf_glyph_size(glyph: &makepad_font::Glyph, units_per_em: f32) -> (f32, f32) {
+        let scale = SDF_GLYPH_EM / units_per_em;
+        (
+            ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * scale).max(0.0).ceil() + 2.0 * SDF_GLYPH_PAD,
+            ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * scale).max(0.0).ceil() + 2.0 * SDF_GLYPH_PAD
+        )
+    }
+    
+    // signed distance to the linearized outline at every texel center, positive inside.
+    // 0.5 is the edge and the ramp reaches 0 and 1 at SDF_GLYPH_PAD texels out and in
+    pub fn rasterize_sdf_glyph(glyph: &makepad_font::Glyph, units_per_em: f32, w: usize, h: usize) -> Vec<u8> {
+        let scale = SDF_GLYPH_EM / units_per_em;
+        let mut segments: Vec<(Point, Point)> = Vec::new();
+        let mut start = None;
+        let mut last = None;
+        glyph.outline.commands().map({
+            move | command | {
+                command.transform(
+                    &AffineTransformation::identity()
+                        .translate(Vector::new(-glyph.bounds.p_min.x, -glyph.bounds.p_min.y))
+                        .uniform_scale(scale)
+                        .translate(Vector::new(SDF_GLYPH_PAD, SDF_GLYPH_PAD))
+                )
+            }
+        }).linearize(0.05).for_each(&mut | command | {
+            match command {
+                LinePathCommand::MoveTo(p) => {
+                    if let (Some(start), Some(last)) = (start, last) {
+                        segments.push((last, start));
+                    }
+                    start = Some(p);
+                    last = Some(p);
+                },
+                LinePathCommand::LineTo(p) => {
+                    if let Some(last) = last {
+                        segments.push((last, p));
+                    }
+                    last = Some(p);
+                },
+                LinePathCommand::Close => {
+                    if let (Some(start), Some(last)) = (start, last) {
+                        segments.push((last, start));
+                    }
+                    last = start;
+                }
+            }
+            true
+        });
+        if let (Some(start), Some(last)) = (start, last) {
+            segments.push((last, start));
+        }
+        segments.retain( | (p0, p1) | p0 != p1);
+        Self::sdf_from_segments(&segments, w, h)
+    }
+    
+    // the ramp ends SDF_GLYPH_PAD texels from the edge, so every segment only measures the
+    // texels around it and inside or outside comes from the winding of the crossings on a row
+    pub fn sdf_from_segments(segments: &[(Point, Point)], w: usize, h: usize) -> Vec<u8> {
+        let mut dist_sq = vec![std::f32::INFINITY; w * h];
+        for (p0, p1) in segments {
+            let d = *p1 - *p0;
+            let len_sq = d.x * d.x + d.y * d.y;
+            let x0 = (p0.x.min(p1.x) - SDF_GLYPH_PAD - 0.5).floor().max(0.0) as usize;
+            let y0 = (p0.y.min(p1.y) - SDF_GLYPH_PAD - 0.5).floor().max(0.0) as usize;
+            let x1 = ((p0.x.max(p1.x) + SDF_GLYPH_PAD).ceil().max(0.0) as usize).min(w);
+            let y1 = ((p0.y.max(p1.y) + SDF_GLYPH_PAD).ceil().max(0.0) as usize).min(h);
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let p = Point::new(x as f32 + 0.5, y as f32 + 0.5);
+                    let t = (((p.x - p0.x) * d.x + (p.y - p0.y) * d.y) / len_sq).max(0.0).min(1.0);
+                    let dx = p0.x + d.x * t - p.x;
+                    let dy = p0.y + d.y * t - p.y;
+                    let index = y * w + x;
+                    dist_sq[index] = dist_sq[index].min(dx * dx + dy * dy);
+                }
+            }
+        }
+        
+        let mut sdf = vec![0u8; w * h];
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+        for y in 0..h {
+            let py = y as f32 + 0.5;
+            crossings.truncate(0);
+            for (p0, p1) in segments {
+                if (p0.y <= py) != (p1.y <= py) {
+                    let x = p0.x + (py - p0.y) / (p1.y - p0.y) * (p1.x - p0.x);
+                    crossings.push((x, if p1.y > p0.y {1} else {-1}));
+                }
+            }
+            crossings.sort_by( | a, b | a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+            // nonzero winding, same fill rule the trapezoidator uses
+            let mut winding = 0;
+            let mut next = 0;
+            for x in 0..w {
+                let px = x as f32 + 0.5;
+                while next < crossings.len() && crossings[next].0 < px {
+                    winding += crossings[next].1;
+                    next += 1;
+                }
+                let index = y * w + x;
+                let dist = dist_sq[index].sqrt().min(SDF_GLYPH_PAD);
+                let dist = if winding != 0 {dist} else {-dist};
+                sdf[index] = ((0.5 + dist / (2.0 * SDF_GLYPH_PAD)).max(0.0).min(1.0) * 255.0) as u8;
+            }
+        }
+        sdf
+    }
+    
+    // pages stay at their index when evicted, todos and draws hold on to atlas_page_ids.
+    // an unused page only drops its glyphs, a page that still draws loses the ones on the emptied texture
+    pub fn evict_atlas_pages(&mut self, redraw_id: u64, texture_index: usize) -> usize {
+        let mut evicted = 0;
+        for page in &mut self.atlas_pages {
+            if page.last_used + ATLAS_KEEP_FRAMES < redraw_id {
+                if page.atlas_glyphs.len() != 0 {
+                    page.atlas_glyphs = Vec::new();
+                    evicted += 1;
+                }
+                continue
+            }
+            for glyph in &mut page.atlas_glyphs {
+                for slot in glyph.iter_mut() {
+                    if slot.map_or(false, | tc | tc.texture_index == texture_index) {
+                        *slot = None;
+                    }
+                }
+            }
+        }
+        evicted
+    }
+    
     pub fn get_atlas_page_id(&mut self, dpi_factor: f32, font_size: f32) -> usize {
-        for (index, sg) in self.atlas_pages.iter().enumerate() {
+        let glyphs = if let Some(font) = &self.font_loaded {font.glyphs.len()} else {0};
+        for (index, sg) in self.atlas_pages.iter_mut().enumerate() {
             if sg.dpi_factor == dpi_factor
                 && sg.font_size == font_size {
+                // an evicted page gets its glyph slots back
+                if sg.atlas_glyphs.len() < glyphs {
+                    sg.atlas_glyphs.resize(glyphs, [None; ATLAS_SUBPIXEL_SLOTS]);
+                }
                 return index
             }
         }
@@ -421,6 +866,7 @@ impl CxFont {
             self.atlas_pages.push(CxFontAtlasPage {
                 dpi_factor: dpi_factor,
                 font_size: font_size,
+                last_used: 0,
                 atlas_glyphs: {
                     let mut v = Vec::new();
                     v.resize(font.glyphs.len(), [None; ATLAS_SUBPIXEL_SLOTS]);
@@ -434,3 +880,139 @@ impl CxFont {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn atlas_spills_into_the_next_texture() {
+        let mut atlas = CxFontsAtlas::default();
+        atlas.init(Vec2 {x: 64.0, y: 64.0}, vec![10, 11]);
+        assert_eq!(atlas.textures.len(), 1);
+        // 31 + 1 pixel padding, so four fit on a texture
+        for i in 0..8 {
+            let tc = atlas.alloc_atlas_glyph(31.0, 31.0).unwrap();
+            assert_eq!(tc.texture_index, i / 4);
+        }
+        assert_eq!(atlas.textures.len(), 2);
+        assert_eq!(atlas.textures[1].texture_id, 11);
+        assert!(atlas.alloc_atlas_glyph(31.0, 31.0).is_none());
+        assert!(atlas.alloc_atlas_glyph(1.0, 1.0).is_none());
+    }
+    
+    #[test]
+    fn atlas_reset_texture_only_frees_that_texture() {
+        let mut atlas = CxFontsAtlas::default();
+        atlas.init(Vec2 {x: 64.0, y: 64.0}, vec![10, 11]);
+        for _ in 0..8 {
+            atlas.alloc_atlas_glyph(31.0, 31.0).unwrap();
+        }
+        atlas.reset_texture(1);
+        assert_eq!(atlas.stats.used_pixels, 4.0 * 32.0 * 32.0);
+        assert!(atlas.textures[1].clear_buffer);
+        let tc = atlas.alloc_atlas_glyph(31.0, 31.0).unwrap();
+        assert_eq!(tc.texture_index, 1);
+        assert_eq!((tc.tx1, tc.ty1), (0.0, 0.0));
+    }
+    
+    #[test]
+    fn atlas_packs_bottom_left() {
+        let mut atlas = CxFontsAtlas::default();
+        atlas.init(Vec2 {x: 64.0, y: 64.0}, vec![10]);
+        let a = atlas.alloc_atlas_glyph(15.0, 31.0).unwrap();
+        let b = atlas.alloc_atlas_glyph(15.0, 15.0).unwrap();
+        let c = atlas.alloc_atlas_glyph(15.0, 15.0).unwrap();
+        assert_eq!((a.tx1, a.ty1), (0.0, 0.0));
+        assert_eq!((b.tx1, b.ty1), (0.25, 0.0));
+        assert_eq!((c.tx1, c.ty1), (0.5, 0.0));
+        // the lowest spot is next to the short ones
+        let d = atlas.alloc_atlas_glyph(31.0, 15.0).unwrap();
+        assert_eq!((d.tx1, d.ty1), (0.25, 0.25));
+    }
+    
+    fn atlas_page(font_size: f32, last_used: u64, texture_index: usize) -> CxFontAtlasPage {
+        let tc = CxFontAtlasGlyph {texture_index, ..CxFontAtlasGlyph::default()};
+        CxFontAtlasPage {
+            dpi_factor: 1.0,
+            font_size,
+            last_used,
+            atlas_glyphs: vec![[Some(tc); ATLAS_SUBPIXEL_SLOTS]; 2]
+        }
+    }
+    
+    #[test]
+    fn evicted_atlas_pages_keep_their_index() {
+        let mut font = CxFont::default();
+        font.atlas_pages.push(atlas_page(10.0, 0, 0));
+        font.atlas_pages.push(atlas_page(11.0, 100, 0));
+        font.atlas_pages.push(atlas_page(12.0, 100, 1));
+        assert_eq!(font.evict_atlas_pages(100, 0), 1);
+        assert_eq!(font.atlas_pages.len(), 3);
+        assert_eq!(font.atlas_pages[0].atlas_glyphs.len(), 0);
+        // the page that still draws only loses the glyphs on the emptied texture
+        assert!(font.atlas_pages[1].atlas_glyphs[0].iter().all( | slot | slot.is_none()));
+        assert!(font.atlas_pages[2].atlas_glyphs[0].iter().all( | slot | slot.is_some()));
+        assert_eq!(font.atlas_pages[2].font_size, 12.0);
+        // evicting again doesnt count the empty page twice
+        assert_eq!(font.evict_atlas_pages(100, 0), 0);
+        // the emptied page is found at its old index and gets its slots back
+        assert_eq!(font.get_atlas_page_id(1.0, 10.0), 0);
+    }
+    
+    fn contour(points: &[(f32, f32)], segments: &mut Vec<(Point, Point)>) {
+        for i in 0..points.len() {
+            let (x0, y0) = points[i];
+            let (x1, y1) = points[(i + 1) % points.len()];
+            segments.push((Point::new(x0, y0), Point::new(x1, y1)));
+        }
+    }
+    
+    // every texel against every segment, what the sdf has to match
+    fn brute_force_sdf(segments: &[(Point, Point)], w: usize, h: usize) -> Vec<u8> {
+        let mut sdf = vec![0u8; w * h];
+        for y in 0..h {
+            for x in 0..w {
+                let p = Point::new(x as f32 + 0.5, y as f32 + 0.5);
+                let mut dist_sq = std::f32::INFINITY;
+                let mut winding = 0;
+                for (p0, p1) in segments {
+                    let d = *p1 - *p0;
+                    let t = (((p.x - p0.x) * d.x + (p.y - p0.y) * d.y) / (d.x * d.x + d.y * d.y)).max(0.0).min(1.0);
+                    let dx = p0.x + d.x * t - p.x;
+                    let dy = p0.y + d.y * t - p.y;
+                    dist_sq = dist_sq.min(dx * dx + dy * dy);
+                    if (p0.y <= p.y) != (p1.y <= p.y) && p0.x + (p.y - p0.y) / d.y * d.x > p.x {
+                        winding += if p1.y > p0.y {1} else {-1};
+                    }
+                }
+                let dist = if winding != 0 {dist_sq.sqrt()} else {-dist_sq.sqrt()};
+                sdf[y * w + x] = ((0.5 + dist / (2.0 * SDF_GLYPH_PAD)).max(0.0).min(1.0) * 255.0) as u8;
+            }
+        }
+        sdf
+    }
+    
+    #[test]
+    fn sdf_matches_the_brute_force_distances() {
+        let mut segments = Vec::new();
+        // a square with a hole and a triangle that overlaps it
+        contour(&[(4.25, 4.25), (20.75, 4.25), (20.75, 20.75), (4.25, 20.75)], &mut segments);
+        contour(&[(8.25, 8.25), (8.25, 16.75), (16.75, 16.75), (16.75, 8.25)], &mut segments);
+        contour(&[(18.1, 2.3), (30.7, 12.9), (14.2, 27.6)], &mut segments);
+        let (w, h) = (36, 32);
+        assert_eq!(CxFont::sdf_from_segments(&segments, w, h), brute_force_sdf(&segments, w, h));
+    }
+    
+    #[test]
+    fn sdf_is_half_on_the_edge_and_saturates_past_the_padding() {
+        let mut segments = Vec::new();
+        contour(&[(4.0, 4.0), (20.0, 4.0), (20.0, 20.0), (4.0, 20.0)], &mut segments);
+        let sdf = CxFont::sdf_from_segments(&segments, 24, 24);
+        // texel centers half a texel in and out of the left edge
+        assert_eq!(sdf[12 * 24 + 4], ((0.5 + 0.5 / (2.0 * SDF_GLYPH_PAD)) * 255.0) as u8);
+        assert_eq!(sdf[12 * 24 + 3], ((0.5 - 0.5 / (2.0 * SDF_GLYPH_PAD)) * 255.0) as u8);
+        assert_eq!(sdf[12 * 24 + 12], 255);
+        assert_eq!(sdf[0], 0);
+    }
+}
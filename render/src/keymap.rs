@@ -0,0 +1,607 @@
+use crate::cx::*;
+use makepad_microserde::*;
+use std::fmt;
+
+// a single key press with modifiers. cmd means 'logo or control' like the rest of the codebase uses
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct KeyChord {
+    pub key_code: KeyCode,
+    pub cmd: bool,
+    pub control: bool,
+    pub shift: bool,
+    pub alt: bool
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyBinding {
+    pub chords: Vec<KeyChord>,
+    pub command: CommandId,
+    pub context: Option<String>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeymapResult {
+    None,
+    Pending,
+    Cancelled,
+    Command(CommandId)
+}
+
+// a chord that isnt finished within this many seconds is dropped
+pub const KEYMAP_CHORD_TIMEOUT: f64 = 2.0;
+
+#[derive(Clone, Default)]
+pub struct CxKeymap {
+    pub commands: Vec<(String, CommandId)>,
+    pub defaults: Vec<KeyBinding>,
+    pub overrides: Vec<(CommandId, Vec<KeyBinding>)>,
+    pub bindings: Vec<KeyBinding>,
+    pub pending: Vec<KeyEvent>,
+    pub context: Option<String>,
+    pub queued_commands: Vec<CommandId>,
+}
+
+// the keymap file, f.ex.
+// (bindings: [
+//     (keys: "Ctrl+K Ctrl+C", command: "app.toggle_line_comment", context: "editor"),
+//     (keys: "", command: "editor.hover"),
+// ])
+#[derive(Clone, Debug, Default, SerRon, DeRon)]
+pub struct KeymapFile {
+    pub bindings: Vec<KeymapFileBinding>
+}
+
+#[derive(Clone, Debug, SerRon, DeRon)]
+pub struct KeymapFileBinding {
+    pub keys: String,
+    pub command: String,
+    pub context: Option<String>
+}
+
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::Backtick, "`"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Minus, "-"),
+    (KeyCode::Equals, "="),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::KeyQ, "Q"),
+    (KeyCode::KeyW, "W"),
+    (KeyCode::KeyE, "E"),
+    (KeyCode::KeyR, "R"),
+    (KeyCode::KeyT, "T"),
+    (KeyCode::KeyY, "Y"),
+    (KeyCode::KeyU, "U"),
+    (KeyCode::KeyI, "I"),
+    (KeyCode::KeyO, "O"),
+    (KeyCode::KeyP, "P"),
+    (KeyCode::LBracket, "["),
+    (KeyCode::RBracket, "]"),
+    (KeyCode::Return, "Return"),
+    (KeyCode::KeyA, "A"),
+    (KeyCode::KeyS, "S"),
+    (KeyCode::KeyD, "D"),
+    (KeyCode::KeyF, "F"),
+    (KeyCode::KeyG, "G"),
+    (KeyCode::KeyH, "H"),
+    (KeyCode::KeyJ, "J"),
+    (KeyCode::KeyK, "K"),
+    (KeyCode::KeyL, "L"),
+    (KeyCode::Semicolon, ";"),
+    (KeyCode::Quote, "'"),
+    (KeyCode::Backslash, "\\"),
+    (KeyCode::KeyZ, "Z"),
+    (KeyCode::KeyX, "X"),
+    (KeyCode::KeyC, "C"),
+    (KeyCode::KeyV, "V"),
+    (KeyCode::KeyB, "B"),
+    (KeyCode::KeyN, "N"),
+    (KeyCode::KeyM, "M"),
+    (KeyCode::Comma, ","),
+    (KeyCode::Period, "."),
+    (KeyCode::Slash, "/"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::PrintScreen, "PrintScreen"),
+    (KeyCode::Pause, "Pause"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::NumpadAdd, "NumpadAdd"),
+    (KeyCode::NumpadSubtract, "NumpadSubtract"),
+    (KeyCode::NumpadEnter, "NumpadEnter"),
+    (KeyCode::ArrowUp, "Up"),
+    (KeyCode::ArrowDown, "Down"),
+    (KeyCode::ArrowLeft, "Left"),
+    (KeyCode::ArrowRight, "Right"),
+];
+
+impl KeyChord {
+    pub fn parse(chord: &str) -> Result<KeyChord, String> {
+        let mut kc = KeyChord::default();
+        let parts: Vec<&str> = chord.split('+').map( | v | v.trim()).collect();
+        let (key, mods) = parts.split_last().unwrap();
+        for m in mods {
+            match m.to_lowercase().as_ref() {
+                "cmd" => kc.cmd = true,
+                "ctrl" | "control" => kc.control = true,
+                "shift" => kc.shift = true,
+                "alt" | "option" => kc.alt = true,
+                _ => return Err(format!("unknown modifier {} in {}", m, chord))
+            }
+        }
+        kc.key_code = Self::parse_key_code(key).ok_or_else( || format!("unknown key {} in {}", key, chord)) ?;
+        Ok(kc)
+    }
+    
+    // parses a sequence like "Cmd+K Cmd+C"
+    pub fn parse_sequence(keys: &str) -> Result<Vec<KeyChord>, String> {
+        keys.split_whitespace().map( | v | KeyChord::parse(v)).collect()
+    }
+    
+    pub fn parse_key_code(name: &str) -> Option<KeyCode> {
+        let alias = match name.to_lowercase().as_ref() {
+            "esc" => "Escape",
+            "enter" => "Return",
+            "backtick" => "`",
+            "minus" => "-",
+            "equals" | "plus" => "=",
+            "comma" => ",",
+            "period" => ".",
+            "slash" => "/",
+            "backslash" => "\\",
+            "semicolon" => ";",
+            "quote" => "'",
+            "lbracket" => "[",
+            "rbracket" => "]",
+            "del" => "Delete",
+            _ => name
+        };
+        KEY_NAMES.iter().find( | (_, n) | n.eq_ignore_ascii_case(alias)).map( | (k, _) | *k)
+    }
+    
+    pub fn key_code_name(key_code: KeyCode) -> &'static str {
+        KEY_NAMES.iter().find( | (k, _) | *k == key_code).map( | (_, n) | *n).unwrap_or("?")
+    }
+    
+    // which (control, logo) combinations this chord accepts
+    fn accepts(&self, control: bool, logo: bool) -> bool {
+        if self.cmd && self.control {
+            control && logo
+        }
+        else if self.cmd {
+            control || logo
+        }
+        else if self.control {
+            control && !logo
+        }
+        else {
+            !control && !logo
+        }
+    }
+    
+    pub fn matches(&self, ke: &KeyEvent) -> bool {
+        ke.key_code == self.key_code
+            && ke.modifiers.shift == self.shift
+            && ke.modifiers.alt == self.alt
+            && self.accepts(ke.modifiers.control, ke.modifiers.logo)
+    }
+    
+    // can a single key press match both chords
+    pub fn overlaps(&self, other: &KeyChord) -> bool {
+        if self.key_code != other.key_code || self.shift != other.shift || self.alt != other.alt {
+            return false
+        }
+        [(false, false), (true, false), (false, true), (true, true)].iter().any( | (c, l) | self.accepts(*c, *l) && other.accepts(*c, *l))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cmd {write!(f, "Cmd+") ?}
+        if self.control {write!(f, "Ctrl+") ?}
+        if self.alt {write!(f, "Alt+") ?}
+        if self.shift {write!(f, "Shift+") ?}
+        write!(f, "{}", Self::key_code_name(self.key_code))
+    }
+}
+
+impl KeyBinding {
+    pub fn keys_to_string(&self) -> String {
+        self.chords.iter().map( | v | v.to_string()).collect::<Vec<String>>().join(" ")
+    }
+    
+    fn overlaps_prefix(&self, other: &KeyBinding) -> bool {
+        self.chords.iter().zip(other.chords.iter()).all( | (a, b) | a.overlaps(b))
+    }
+}
+
+impl CxKeymap {
+    
+    pub fn command_name(&self, command: CommandId) -> Option<&str> {
+        self.commands.iter().find( | (_, c) | *c == command).map( | (n, _) | n.as_ref())
+    }
+    
+    pub fn find_command(&self, name: &str) -> Option<CommandId> {
+        self.commands.iter().find( | (n, _) | n == name).map( | (_, c) | *c)
+    }
+    
+    // the keys shown next to a command, f.ex. in menus
+    pub fn bindings_for(&self, command: CommandId) -> Vec<&KeyBinding> {
+        self.bindings.iter().filter( | b | b.command == command).collect()
+    }
+    
+    fn rebuild(&mut self) {
+        let mut bindings: Vec<KeyBinding> = self.defaults.iter().filter( | b | {
+            !self.overrides.iter().any( | (c, _) | *c == b.command)
+        }).cloned().collect();
+        for (_, ov) in &self.overrides {
+            bindings.extend(ov.iter().cloned());
+        }
+        self.bindings = bindings;
+    }
+    
+    // two bindings conflict if they live in the same context and one key sequence can swallow the other
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        for (i, a) in self.bindings.iter().enumerate() {
+            for b in self.bindings.iter().skip(i + 1) {
+                if a.command == b.command || a.context != b.context || !a.overlaps_prefix(b) {
+                    continue;
+                }
+                out.push(format!(
+                    "{} bound to both {} and {}{}",
+                    if a.chords.len() <= b.chords.len() {a.keys_to_string()} else {b.keys_to_string()},
+                    self.command_name(a.command).unwrap_or("?"),
+                    self.command_name(b.command).unwrap_or("?"),
+                    if let Some(context) = &a.context {format!(" in context {}", context)} else {"".to_string()}
+                ));
+            }
+        }
+        out
+    }
+    
+    fn is_active(&self, binding: &KeyBinding) -> bool {
+        binding.context.is_none() || binding.context == self.context
+    }
+    
+    // Cancelled means a pending chord was dropped and the key matched nothing on its own,
+    // so it goes out as a normal key down
+    pub fn process_key_down(&mut self, ke: &KeyEvent) -> KeymapResult {
+        match ke.key_code {
+            KeyCode::Control | KeyCode::Shift | KeyCode::Alt | KeyCode::Logo => return KeymapResult::None,
+            _ => ()
+        }
+        if let Some(last) = self.pending.last() {
+            if ke.time - last.time > KEYMAP_CHORD_TIMEOUT {
+                self.pending.truncate(0);
+            }
+        }
+        let cancelled = self.pending.len() > 0;
+        match self.match_sequence(ke) {
+            KeymapResult::None if cancelled => {
+                // the key that broke the chord can still start or be a binding of its own
+                match self.match_sequence(ke) {
+                    KeymapResult::None => KeymapResult::Cancelled,
+                    result => result
+                }
+            },
+            result => result
+        }
+    }
+    
+    fn match_sequence(&mut self, ke: &KeyEvent) -> KeymapResult {
+        let mut seq = std::mem::replace(&mut self.pending, Vec::new());
+        seq.push(ke.clone());
+        
+        // bindings for the focused context go first and shadow global ones, chord prefixes included
+        for scoped in &[true, false] {
+            let mut longer = false;
+            for binding in &self.bindings {
+                if binding.context.is_some() != *scoped || !self.is_active(binding) || binding.chords.len() < seq.len() {
+                    continue;
+                }
+                if !binding.chords.iter().zip(seq.iter()).all( | (c, k) | c.matches(k)) {
+                    continue;
+                }
+                if binding.chords.len() == seq.len() {
+                    return KeymapResult::Command(binding.command)
+                }
+                longer = true;
+            }
+            if longer {
+                self.pending = seq;
+                return KeymapResult::Pending
+            }
+        }
+        KeymapResult::None
+    }
+    
+    // bindings in the file replace all default bindings of the commands they name
+    // an empty keys string unbinds a command
+    pub fn load_ron(&mut self, utf8_data: &str) -> Result<(), Vec<String>> {
+        let file: KeymapFile = match DeRon::deserialize_ron(utf8_data) {
+            Ok(file) => file,
+            Err(e) => return Err(vec![format!("{:?}", e)])
+        };
+        let mut errors = Vec::new();
+        let mut overrides: Vec<(CommandId, Vec<KeyBinding>)> = Vec::new();
+        for fb in file.bindings {
+            let command = if let Some(command) = self.find_command(&fb.command) {command} else {
+                errors.push(format!("unknown command {}", fb.command));
+                continue;
+            };
+            let chords = match KeyChord::parse_sequence(&fb.keys) {
+                Ok(chords) => chords,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            let index = if let Some(index) = overrides.iter().position( | (c, _) | *c == command) {index} else {
+                overrides.push((command, Vec::new()));
+                overrides.len() - 1
+            };
+            if chords.len() > 0 {
+                overrides[index].1.push(KeyBinding {chords: chords, command: command, context: fb.context});
+            }
+        }
+        self.overrides = overrides;
+        self.rebuild();
+        if errors.len() > 0 {
+            return Err(errors)
+        }
+        Ok(())
+    }
+}
+
+impl Cx {
+    
+    // the context is reset on every keyfocus change, so widgets set it when they receive KeyFocus
+    pub fn set_key_focus_context(&mut self, context: &str) {
+        self.keymap.context = Some(context.to_string());
+    }
+    
+    pub fn keymap_conflicts(&self) -> Vec<String> {
+        self.keymap.conflicts()
+    }
+    
+    pub fn load_keymap_ron(&mut self, utf8_data: &str) -> Result<(), Vec<String>> {
+        let result = self.keymap.load_ron(utf8_data);
+        self.sync_command_settings_keys();
+        result
+    }
+    
+    // keep the native menu key equivalents in line with the keymap
+    fn sync_command_settings_keys(&mut self) {
+        let commands: Vec<CommandId> = self.command_settings.keys().cloned().collect();
+        for command in commands {
+            let chord = self.keymap.bindings.iter().find( | b | {
+                b.command == command && b.context.is_none() && b.chords.len() == 1 && b.chords[0].cmd
+            }).map( | b | b.chords[0]);
+            let s = self.command_settings.get_mut(&command).unwrap();
+            if let Some(chord) = chord {
+                s.key_code = chord.key_code;
+                s.shift = chord.shift;
+            }
+            else {
+                s.key_code = KeyCode::Unknown;
+                s.shift = false;
+            }
+        }
+        #[cfg(all(not(feature = "ipc"), target_os = "macos"))]
+        {
+            self.platform.set_menu = true;
+        }
+    }
+    
+    // runs a command as if its shortcut was pressed, it arrives after the current event so key focus changes apply first
+    pub fn send_command(&mut self, command: CommandId) {
+        self.keymap.queued_commands.push(command);
+    }
+    
+    pub fn keymap_process_key_down(&mut self, ke: &KeyEvent) -> KeymapResult {
+        self.keymap.process_key_down(ke)
+    }
+}
+
+impl CommandId {
+    pub fn set_name(&self, cx: &mut Cx, name: &str) -> Self {
+        if let Some(entry) = cx.keymap.commands.iter_mut().find( | (_, c) | c == self) {
+            entry.0 = name.to_string();
+        }
+        else {
+            cx.keymap.commands.push((name.to_string(), *self));
+        }
+        *self
+    }
+    
+    // drops the default bindings, f.ex. when an app wants the keys for something else
+    pub fn clear_keys(&self, cx: &mut Cx) -> Self {
+        cx.keymap.defaults.retain( | b | b.command != *self);
+        cx.keymap.rebuild();
+        if let Some(s) = cx.command_settings.get_mut(self) {
+            s.key_code = KeyCode::Unknown;
+            s.shift = false;
+        }
+        *self
+    }
+    
+    pub fn bind(&self, cx: &mut Cx, keys: &str) -> Self {
+        self.bind_context(cx, None, keys)
+    }
+    
+    pub fn bind_in(&self, cx: &mut Cx, context: &str, keys: &str) -> Self {
+        self.bind_context(cx, Some(context.to_string()), keys)
+    }
+    
+    fn bind_context(&self, cx: &mut Cx, context: Option<String>, keys: &str) -> Self {
+        match KeyChord::parse_sequence(keys) {
+            Ok(chords) => {
+                // styles get reapplied on live reload, so binding twice is a no-op
+                if !cx.keymap.defaults.iter().any( | b | b.command == *self && b.chords == chords && b.context == context) {
+                    cx.keymap.defaults.push(KeyBinding {chords: chords, command: *self, context: context});
+                    cx.keymap.rebuild();
+                }
+            },
+            Err(e) => log!("Cannot bind {}: {}", keys, e)
+        }
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn command_comment() -> CommandId {uid!()}
+    fn command_save() -> CommandId {uid!()}
+    fn command_reload() -> CommandId {uid!()}
+    
+    fn key_at(key_code: KeyCode, control: bool, logo: bool, time: f64) -> KeyEvent {
+        KeyEvent {
+            key_code,
+            is_repeat: false,
+            modifiers: KeyModifiers {control, logo, ..KeyModifiers::default()},
+            time
+        }
+    }
+    
+    fn ctrl(key_code: KeyCode) -> KeyEvent {
+        key_at(key_code, true, false, 0.0)
+    }
+    
+    fn keymap_with(bindings: &[(&str, CommandId, Option<&str>)]) -> CxKeymap {
+        let mut keymap = CxKeymap::default();
+        keymap.commands.push(("app.comment".to_string(), command_comment()));
+        keymap.commands.push(("app.save".to_string(), command_save()));
+        for (keys, command, context) in bindings {
+            keymap.defaults.push(KeyBinding {
+                chords: KeyChord::parse_sequence(keys).unwrap(),
+                command: *command,
+                context: context.map( | v | v.to_string())
+            });
+        }
+        keymap.rebuild();
+        keymap
+    }
+    
+    #[test]
+    fn parses_and_prints_chords() {
+        let chord = KeyChord::parse("Cmd+Shift+K").unwrap();
+        assert_eq!(chord, KeyChord {key_code: KeyCode::KeyK, cmd: true, shift: true, ..KeyChord::default()});
+        assert_eq!(chord.to_string(), "Cmd+Shift+K");
+        assert_eq!(KeyChord::parse("ctrl+esc").unwrap(), KeyChord {key_code: KeyCode::Escape, control: true, ..KeyChord::default()});
+        assert_eq!(KeyChord::parse("Cmd+plus").unwrap().key_code, KeyCode::Equals);
+        assert!(KeyChord::parse("Cmd+Foo").is_err());
+        assert!(KeyChord::parse("Hyper+K").is_err());
+        assert_eq!(KeyChord::parse_sequence("Cmd+K Cmd+C").unwrap().len(), 2);
+        assert_eq!(KeyChord::parse_sequence("").unwrap().len(), 0);
+    }
+    
+    #[test]
+    fn cmd_accepts_control_or_logo() {
+        let cmd = KeyChord::parse("Cmd+S").unwrap();
+        assert!(cmd.matches(&key_at(KeyCode::KeyS, true, false, 0.0)));
+        assert!(cmd.matches(&key_at(KeyCode::KeyS, false, true, 0.0)));
+        assert!(!cmd.matches(&key_at(KeyCode::KeyS, false, false, 0.0)));
+        let control = KeyChord::parse("Ctrl+S").unwrap();
+        assert!(control.matches(&ctrl(KeyCode::KeyS)));
+        assert!(!control.matches(&key_at(KeyCode::KeyS, false, true, 0.0)));
+        assert!(cmd.overlaps(&control));
+    }
+    
+    #[test]
+    fn chords_wait_for_their_second_key() {
+        let mut keymap = keymap_with(&[("Cmd+K Cmd+C", command_comment(), None), ("Cmd+S", command_save(), None)]);
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyK)), KeymapResult::Pending);
+        // modifiers going down in between dont break the chord
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::Control)), KeymapResult::None);
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyC)), KeymapResult::Command(command_comment()));
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyS)), KeymapResult::Command(command_save()));
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyC)), KeymapResult::None);
+    }
+    
+    #[test]
+    fn the_key_that_cancels_a_chord_still_counts() {
+        let mut keymap = keymap_with(&[("Cmd+K Cmd+C", command_comment(), None), ("Cmd+S", command_save(), None)]);
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyK)), KeymapResult::Pending);
+        assert_eq!(keymap.process_key_down(&key_at(KeyCode::KeyX, false, false, 0.0)), KeymapResult::Cancelled);
+        assert_eq!(keymap.pending.len(), 0);
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyK)), KeymapResult::Pending);
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyS)), KeymapResult::Command(command_save()));
+        // and can start a chord of its own
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyK)), KeymapResult::Pending);
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyK)), KeymapResult::Pending);
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyC)), KeymapResult::Command(command_comment()));
+    }
+    
+    #[test]
+    fn pending_chords_time_out() {
+        let mut keymap = keymap_with(&[("Cmd+K Cmd+C", command_comment(), None)]);
+        assert_eq!(keymap.process_key_down(&key_at(KeyCode::KeyK, true, false, 10.0)), KeymapResult::Pending);
+        let late = 10.0 + KEYMAP_CHORD_TIMEOUT + 0.1;
+        assert_eq!(keymap.process_key_down(&key_at(KeyCode::KeyC, true, false, late)), KeymapResult::None);
+        assert_eq!(keymap.process_key_down(&key_at(KeyCode::KeyK, true, false, 20.0)), KeymapResult::Pending);
+        assert_eq!(keymap.process_key_down(&key_at(KeyCode::KeyC, true, false, 21.0)), KeymapResult::Command(command_comment()));
+    }
+    
+    #[test]
+    fn context_bindings_only_fire_in_their_context_and_shadow_global_ones() {
+        let mut keymap = keymap_with(&[("Cmd+R", command_reload(), Some("file_tree")), ("Cmd+S", command_save(), None), ("Cmd+S", command_comment(), Some("editor"))]);
+        keymap.context = Some("editor".to_string());
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyR)), KeymapResult::None);
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyS)), KeymapResult::Command(command_comment()));
+        keymap.context = Some("file_tree".to_string());
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyR)), KeymapResult::Command(command_reload()));
+        assert_eq!(keymap.process_key_down(&ctrl(KeyCode::KeyS)), KeymapResult::Command(command_save()));
+    }
+    
+    #[test]
+    fn conflicts_are_per_context() {
+        let keymap = keymap_with(&[("Cmd+K", command_save(), None), ("Cmd+K Cmd+C", command_comment(), None)]);
+        assert_eq!(keymap.conflicts(), vec!["Cmd+K bound to both app.save and app.comment".to_string()]);
+        let keymap = keymap_with(&[("Cmd+K", command_save(), None), ("Cmd+K Cmd+C", command_comment(), Some("editor"))]);
+        assert_eq!(keymap.conflicts().len(), 0);
+    }
+    
+    #[test]
+    fn the_keymap_file_replaces_and_unbinds_defaults() {
+        let mut keymap = keymap_with(&[("Cmd+/", command_comment(), None), ("Cmd+S", command_save(), None)]);
+        let result = keymap.load_ron(r#"(bindings: [
+            (keys: "Ctrl+K Ctrl+C", command: "app.comment", context: "editor"),
+            (keys: "", command: "app.save"),
+            (keys: "Ctrl+Q", command: "app.nope"),
+            (keys: "Ctrl+Nope", command: "app.save"),
+        ])"#);
+        assert_eq!(result, Err(vec!["unknown command app.nope".to_string(), "unknown key Nope in Ctrl+Nope".to_string()]));
+        assert_eq!(keymap.bindings.len(), 1);
+        assert_eq!(keymap.bindings[0].keys_to_string(), "Ctrl+K Ctrl+C");
+        assert_eq!(keymap.bindings[0].context, Some("editor".to_string()));
+        assert!(keymap.load_ron("(bindings: [").is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::cx::*;
+
+// the layout debugger records every turtle during the draw pass so an overlay can show
+// what the layout engine actually did. entries belong to the view they were drawn in,
+// so views that dont redraw keep their entries around
+#[derive(Clone, Default)]
+pub struct CxLayoutDebug {
+    pub enabled: bool,
+    pub turtles: Vec<LayoutDebugTurtle>,
+    pub stack: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LayoutDebugTurtle {
+    pub view_id: usize,
+    pub scroll_view_id: usize,
+    pub redraw_id: u64,
+    pub align_start: usize,
+    pub depth: usize,
+    pub layout: Layout,
+    pub layout_id: Option<(LiveId, bool)>,
+    pub available: Vec2,
+    pub width: f32,
+    pub height: f32,
+    pub rect: Rect,
+}
+
+impl LayoutDebugTurtle {
+    pub fn is_collapsed(&self) -> bool {
+        self.rect.w <= 0. || self.rect.h <= 0.
+    }
+    
+    pub fn content_rect(&self) -> Rect {
+        let pad = self.layout.padding;
+        Rect {
+            x: self.rect.x + pad.l,
+            y: self.rect.y + pad.t,
+            w: (self.rect.w - pad.l - pad.r).max(0.),
+            h: (self.rect.h - pad.t - pad.b).max(0.)
+        }
+    }
+    
+    pub fn margin_rect(&self) -> Rect {
+        let margin = self.layout.walk.margin;
+        Rect {
+            x: self.rect.x - margin.l,
+            y: self.rect.y - margin.t,
+            w: self.rect.w + margin.l + margin.r,
+            h: self.rect.h + margin.t + margin.b
+        }
+    }
+}
+
+impl Cx {
+    pub fn command_toggle_layout_debug() -> CommandId {uid!()}
+    
+    pub fn toggle_layout_debug(&mut self) {
+        self.layout_debug.enabled = !self.layout_debug.enabled;
+        self.layout_debug.turtles.truncate(0);
+        self.layout_debug.stack.truncate(0);
+        self.redraw_child_area(Area::All);
+    }
+    
+    pub(crate) fn layout_debug_begin_view(&mut self, view_id: usize) {
+        if !self.layout_debug.enabled {
+            return
+        }
+        self.layout_debug.turtles.retain( | turtle | turtle.view_id != view_id);
+    }
+    
+    pub(crate) fn layout_debug_begin_turtle(&mut self, turtle: &Turtle, available: Vec2) {
+        if !self.layout_debug.enabled {
+            return
+        }
+        let view_id = *self.view_stack.last().unwrap_or(&0);
+        // a views own turtle lives in the coordinates of the view it is nested in
+        let scroll_view_id = match turtle.guard_area {
+            Area::View(view_area) if view_area.view_id == view_id => {
+                let pass_id = self.views[view_id].pass_id;
+                if self.passes[pass_id].main_view_id == Some(view_id) {view_id} else {self.views[view_id].nesting_view_id}
+            }
+            _ => view_id
+        };
+        let layout_id = self.live_styles.find_layout_id(&turtle.layout);
+        self.layout_debug.stack.push(self.layout_debug.turtles.len());
+        self.layout_debug.turtles.push(LayoutDebugTurtle {
+            view_id: view_id,
+            scroll_view_id: scroll_view_id,
+            redraw_id: self.redraw_id,
+            align_start: turtle.align_list_x,
+            depth: self.turtles.len(),
+            layout: turtle.layout,
+            layout_id: layout_id,
+            available: available,
+            width: turtle.width,
+            height: turtle.height,
+            rect: Rect::default()
+        });
+    }
+    
+    pub(crate) fn layout_debug_end_turtle(&mut self, rect: Rect) {
+        if !self.layout_debug.enabled {
+            return
+        }
+        if let Some(index) = self.layout_debug.stack.pop() {
+            self.layout_debug.turtles[index].rect = rect;
+        }
+    }
+    
+    // alignment moves instances after their turtles ended, the recorded rects follow along
+    pub(crate) fn layout_debug_shift(&mut self, align_start: usize, dx: f32, dy: f32) {
+        if !self.layout_debug.enabled {
+            return
+        }
+        let debug = &mut self.layout_debug;
+        for (index, turtle) in debug.turtles.iter_mut().enumerate() {
+            if turtle.redraw_id == self.redraw_id && turtle.align_start >= align_start && !debug.stack.contains(&index) {
+                turtle.rect.x += dx;
+                turtle.rect.y += dy;
+            }
+        }
+    }
+    
+    // same for flex and grid arrangement, the debug turtles an item recorded go where its instances went
+    pub(crate) fn layout_debug_arrange(&mut self, items: &[TurtleItem], deltas: &[(f32, f32, f32, f32)]) {
+        if !self.layout_debug.enabled || items.len() == 0 {
+            return
+        }
+        let start = items[0].debug_range.0;
+        let owners = get_item_owners(items, start, self.layout_debug.turtles.len(), | item | item.debug_range);
+        for (offset, owner) in owners.iter().enumerate() {
+            if let Some(owner) = owner {
+                let turtle = &mut self.layout_debug.turtles[start + offset];
+                turtle.rect = items[*owner].arranged_rect(turtle.rect, deltas[*owner]);
+            }
+        }
+    }
+    
+    // the recorded turtles of a pass in window coordinates, parents first
+    pub fn get_layout_debug_turtles(&self, pass_id: usize, skip_view_id: Option<usize>) -> Vec<(usize, Rect)> {
+        let mut views = Vec::new();
+        if let Some(main_view_id) = self.passes[pass_id].main_view_id {
+            if main_view_id < self.views.len() {
+                self.collect_visible_views(main_view_id, &mut views);
+            }
+        }
+        let mut out: Vec<(usize, Rect)> = self.layout_debug.turtles.iter().enumerate().filter_map( | (index, turtle) | {
+            if !views.contains(&turtle.view_id) || Some(turtle.view_id) == skip_view_id || self.layout_debug.stack.contains(&index) {
+                return None
+            }
+            let cxview = &self.views[turtle.scroll_view_id];
+            let scroll = cxview.get_local_scroll();
+            Some((index, Rect {
+                x: turtle.rect.x - cxview.parent_scroll.x - scroll.x,
+                y: turtle.rect.y - cxview.parent_scroll.y - scroll.y,
+                w: turtle.rect.w,
+                h: turtle.rect.h
+            }))
+        }).collect();
+        out.sort_by_key( | (index, _) | self.layout_debug.turtles[*index].depth);
+        out
+    }
+    
+    // the deepest turtle under a point. collapsed ones get a few pixels of slack so you can find them
+    pub fn get_layout_debug_turtle_at(&self, turtles: &[(usize, Rect)], pos: Vec2) -> Option<usize> {
+        let mut found = None;
+        for (index, rect) in turtles {
+            let slack = if self.layout_debug.turtles[*index].is_collapsed() {3.} else {0.};
+            if pos.x >= rect.x - slack && pos.x <= rect.x + rect.w + slack && pos.y >= rect.y - slack && pos.y <= rect.y + rect.h + slack {
+                found = Some(*index);
+            }
+        }
+        found
+    }
+    
+    pub fn describe_layout_debug_turtle(&self, index: usize) -> Vec<String> {
+        let turtle = &self.layout_debug.turtles[index];
+        let layout = &turtle.layout;
+        let walk = &layout.walk;
+        let mut lines = Vec::new();
+        lines.push(match turtle.layout_id {
+            Some((live_id, true)) => format!("Layout {}", self.live_styles.get_live_id_name(live_id)),
+            Some((live_id, false)) => format!("Layout {} (walk overridden)", self.live_styles.get_live_id_name(live_id)),
+            None => "Layout (inline)".to_string()
+        });
+        let r = turtle.rect;
+        lines.push(format!("rect: {} {} {} x {}", r.x, r.y, r.w, r.h));
+        let eval = | v: f32 | if v.is_nan() {"computed".to_string()} else {format!("{}", v)};
+        lines.push(format!("width: {:?} -> {}  height: {:?} -> {}", walk.width, eval(turtle.width), walk.height, eval(turtle.height)));
+        lines.push(format!("available: {} x {}", eval(turtle.available.x), eval(turtle.available.y)));
+        let m = walk.margin;
+        lines.push(format!("margin: {} {} {} {}", m.l, m.t, m.r, m.b));
+        let p = layout.padding;
+        lines.push(format!("padding: {} {} {} {}", p.l, p.t, p.r, p.b));
+        lines.push(format!("direction: {:?}  align: {} {}", layout.direction, layout.align.fx, layout.align.fy));
+        if let Some(flex) = layout.flex {
+            lines.push(format!("flex: gap {} {:?} {:?}", flex.gap, flex.justify, flex.cross_align));
+        }
+        if layout.grid.is_some() {
+            lines.push("grid".to_string());
+        }
+        
+        // the usual suspects when something ends up with no size
+        if turtle.rect.w <= 0. && walk.width.is_fill() {
+            lines.push(Self::layout_debug_fill_hint("width", turtle.available.x));
+        }
+        if turtle.rect.h <= 0. && walk.height.is_fill() {
+            lines.push(Self::layout_debug_fill_hint("height", turtle.available.y));
+        }
+        if turtle.is_collapsed() && (walk.width == Width::Compute || walk.height == Height::Compute) {
+            lines.push("computed size without content collapses to the padding".to_string());
+        }
+        lines
+    }
+    
+    fn layout_debug_fill_hint(what: &str, available: f32) -> String {
+        if available.is_nan() {
+            format!("{} fills a computed parent, there is nothing to fill", what)
+        }
+        else if available <= 0. {
+            format!("{} fills but the parent had no {} left, earlier siblings used it up", what, what)
+        }
+        else {
+            format!("{} fills but margin or padding ate the {} left", what, available)
+        }
+    }
+}
@@ -11,6 +11,10 @@ mod cx_opengl;
 mod cx_xlib;
 #[cfg(all(not(feature="ipc"),any(target_os = "linux")))]
 mod cx_linux;
+#[cfg(all(not(feature="ipc"),target_os = "linux"))]
+mod dbus;
+#[cfg(all(not(feature="ipc"),target_os = "linux"))]
+mod cx_atspi;
 
 #[cfg(all(not(feature="ipc"),target_os = "macos"))]
 mod cx_metal;
@@ -75,8 +79,17 @@ mod geometrygen;
 mod quad;
 mod blit;
 mod text;
+mod richtext;
+mod shaping;
 mod events;
 mod menu; 
+mod keymap;
+mod layoutdebug;
+mod animclock;
+mod accessibility;
+mod focus;
+mod dragdrop;
+mod clipboard;
 mod geometry;
 mod shader;
 mod cube;
@@ -87,4 +100,5 @@ pub use crate::quad::*;
 pub use crate::cube::*;
 pub use crate::blit::*;
 pub use crate::text::*;
+pub use crate::richtext::*;
 pub use crate::elements::*;
@@ -15,16 +15,17 @@ impl Cx{
     pub fn command_select_all()->CommandId{uid!()}
 
     pub fn command_default_keymap(&mut self){
-        Cx::command_quit().set_key(self, KeyCode::KeyQ);
-        Cx::command_undo().set_key(self, KeyCode::KeyZ);
-        Cx::command_redo().set_key(self, KeyCode::KeyY);
-        Cx::command_cut().set_key(self, KeyCode::KeyX);
-        Cx::command_copy().set_key(self, KeyCode::KeyC);
-        Cx::command_paste().set_key(self, KeyCode::KeyV);
-        Cx::command_select_all().set_key(self, KeyCode::KeyA);
-        Cx::command_zoom_out().set_key(self, KeyCode::Minus);
-        Cx::command_zoom_in().set_key(self, KeyCode::Equals);
-        Cx::command_minimize().set_key(self, KeyCode::KeyM);
+        Cx::command_quit().set_name(self, "quit").set_key(self, KeyCode::KeyQ);
+        Cx::command_undo().set_name(self, "undo").set_key(self, KeyCode::KeyZ);
+        Cx::command_redo().set_name(self, "redo").set_key(self, KeyCode::KeyY).bind(self, "Cmd+Shift+Z");
+        Cx::command_cut().set_name(self, "cut").set_key(self, KeyCode::KeyX);
+        Cx::command_copy().set_name(self, "copy").set_key(self, KeyCode::KeyC);
+        Cx::command_paste().set_name(self, "paste").set_key(self, KeyCode::KeyV);
+        Cx::command_select_all().set_name(self, "select_all").set_key(self, KeyCode::KeyA);
+        Cx::command_zoom_out().set_name(self, "zoom_out").set_key(self, KeyCode::Minus);
+        Cx::command_zoom_in().set_name(self, "zoom_in").set_key(self, KeyCode::Equals);
+        Cx::command_minimize().set_name(self, "minimize").set_key(self, KeyCode::KeyM);
+        Cx::command_zoom().set_name(self, "zoom");
     }
 }
 
@@ -48,6 +49,7 @@ impl CommandId{
         s.shift = false;
         s.key_code = key_code;
         cx.command_settings.insert(*self, s);
+        self.set_key_binding(cx, key_code, false);
         *self
     }
 
@@ -56,8 +58,15 @@ impl CommandId{
         s.shift = true;
         s.key_code = key_code;
         cx.command_settings.insert(*self, s);
+        self.set_key_binding(cx, key_code, true);
         *self
     }
+
+    // the menu key also goes into the keymap, replacing an earlier one
+    fn set_key_binding(&self, cx:&mut Cx, key_code:KeyCode, shift:bool){
+        cx.keymap.defaults.retain(|b| b.command != *self || b.context.is_some() || b.chords.len() != 1 || !b.chords[0].cmd);
+        self.bind(cx, &format!("Cmd+{}{}", if shift{"Shift+"}else{""}, KeyChord::key_code_name(key_code)));
+    }
 }
 
 impl Into<CommandId> for TypeId {
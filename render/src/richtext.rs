@@ -0,0 +1,368 @@
+use crate::cx::*;
+use crate::quad::*;
+
+#[derive(Clone)]
+pub enum TextSpan {
+    Text {
+        text: String,
+        text_style: TextStyle,
+        color: Color,
+        variant: FontVariant,
+        underline: bool,
+    },
+    Quad {
+        width: f32,
+        height: f32,
+        color: Color,
+    }
+}
+
+impl TextSpan {
+    pub fn text(text: &str, text_style: TextStyle, color: Color) -> Self {
+        TextSpan::Text {
+            text: text.to_string(),
+            text_style,
+            color,
+            variant: FontVariant::Regular,
+            underline: false
+        }
+    }
+    
+    pub fn quad(width: f32, height: f32, color: Color) -> Self {
+        TextSpan::Quad {width, height, color}
+    }
+    
+    pub fn with_variant(mut self, new_variant: FontVariant) -> Self {
+        if let TextSpan::Text {variant, ..} = &mut self {
+            *variant = new_variant;
+        }
+        self
+    }
+    
+    pub fn with_underline(mut self) -> Self {
+        if let TextSpan::Text {underline, ..} = &mut self {
+            *underline = true;
+        }
+        self
+    }
+}
+
+// a word or an inline quad, the unit the line breaker works with
+struct RichTextItem {
+    span_index: usize,
+    chars: Vec<char>,
+    width: f32,
+    ink_width: f32,
+    height: f32,
+    ascent: f32,
+    line_height: f32,
+    newline: bool,
+    // the word runs on into the next span, there is no break in between
+    joined: bool,
+}
+
+// the words of a span, ended after the chars Wrapping::Word breaks after or at a newline.
+// returns every word with if it ended in a newline and if the last one runs on into the next span
+fn split_words(text: &str) -> (Vec<(Vec<char>, bool)>, bool) {
+    let mut words = Vec::new();
+    let mut chars = Vec::new();
+    for c in text.chars() {
+        if c == '\n' {
+            words.push((std::mem::replace(&mut chars, Vec::new()), true));
+        }
+        else {
+            chars.push(c);
+            if c == ' ' || c == '\t' || c == ',' {
+                words.push((std::mem::replace(&mut chars, Vec::new()), false));
+            }
+        }
+    }
+    let joined = chars.len() != 0;
+    if joined {
+        words.push((chars, false));
+    }
+    (words, joined)
+}
+
+// the items that go on the line starting at index, and their width. a word split over spans
+// moves to the next line as a whole
+fn break_line(items: &[RichTextItem], index: usize, width_left: f32) -> (usize, f32) {
+    let mut end = index;
+    let mut line_width = 0.0;
+    while end < items.len() {
+        let mut word_end = end;
+        let mut word_width = 0.0;
+        while word_end < items.len() {
+            word_width += items[word_end].width;
+            word_end += 1;
+            if !items[word_end - 1].joined {
+                break;
+            }
+        }
+        if end > index && !width_left.is_nan() && line_width + word_width > width_left {
+            break;
+        }
+        line_width += word_width;
+        end = word_end;
+        if items[end - 1].newline {
+            break;
+        }
+    }
+    (end, line_width)
+}
+
+#[derive(Clone)]
+pub struct RichText {
+    pub text: Text,
+    pub quad: Quad,
+    pub underline_offset: f32,
+    pub underline_thickness: f32,
+    pub span_areas: Vec<Area>,
+    pub span_rects: Vec<Vec<Rect>>,
+}
+
+impl RichText {
+    pub fn new(cx: &mut Cx) -> Self {
+        Self {
+            text: Text::new(cx),
+            quad: Quad::new(cx),
+            underline_offset: 0.15,
+            underline_thickness: 0.08,
+            span_areas: Vec::new(),
+            span_rects: Vec::new(),
+        }
+    }
+    
+    fn set_span_style(&mut self, cx: &Cx, text_style: &TextStyle, variant: FontVariant) {
+        self.text.text_style = *text_style;
+        self.text.text_style.font.font_id = CxFont::variant_font_id(&cx.fonts, text_style.font.font_id, variant);
+    }
+    
+    fn build_items(&mut self, cx: &Cx, spans: &[TextSpan]) -> Vec<RichTextItem> {
+        let mut items = Vec::new();
+        for (span_index, span) in spans.iter().enumerate() {
+            match span {
+                TextSpan::Text {text, text_style, variant, underline, ..} => {
+                    self.set_span_style(cx, text_style, *variant);
+                    let font_scale = self.text.font_scale;
+                    let (words, joined) = split_words(text);
+                    let word_count = words.len();
+                    for (word_index, (chars, newline)) in words.into_iter().enumerate() {
+                        let width = self.text.get_text_width(cx, &chars);
+                        let ink_width = if *underline {
+                            let mut ink_len = chars.len();
+                            while ink_len > 0 && chars[ink_len - 1].is_whitespace() {
+                                ink_len -= 1;
+                            }
+                            self.text.get_text_width(cx, &chars[0..ink_len])
+                        }
+                        else {
+                            width
+                        };
+                        items.push(RichTextItem {
+                            span_index,
+                            chars,
+                            width,
+                            ink_width,
+                            height: text_style.font_size * text_style.height_factor * font_scale,
+                            ascent: text_style.font_size * text_style.top_drop * font_scale,
+                            line_height: text_style.font_size * text_style.line_spacing * font_scale,
+                            newline,
+                            joined: joined && word_index == word_count - 1
+                        });
+                    }
+                },
+                TextSpan::Quad {width, height, ..} => {
+                    items.push(RichTextItem {
+                        span_index,
+                        chars: Vec::new(),
+                        width: *width,
+                        ink_width: *width,
+                        height: *height,
+                        ascent: *height,
+                        line_height: *height,
+                        newline: false,
+                        joined: false
+                    });
+                }
+            }
+        }
+        items
+    }
+    
+    // flows the spans with word wrapping in the current turtle, returns an area per span
+    pub fn draw_rich_text(&mut self, cx: &mut Cx, spans: &[TextSpan]) -> Vec<Area> {
+        let items = self.build_items(cx, spans);
+        
+        // break lines against the space the turtle has left, every line shares one baseline
+        let mut placed = Vec::new();
+        self.span_rects = vec![Vec::new(); spans.len()];
+        let mut index = 0;
+        while index < items.len() {
+            let (end, line_width) = break_line(&items, index, cx.get_width_left());
+            let line = &items[index..end];
+            let mut ascent: f32 = 0.0;
+            let mut descent: f32 = 0.0;
+            let mut line_height: f32 = 0.0;
+            for item in line {
+                ascent = ascent.max(item.ascent);
+                descent = descent.max(item.height - item.ascent);
+                line_height = line_height.max(item.line_height);
+            }
+            let geom = cx.walk_turtle(Walk {
+                width: Width::Fix(line_width),
+                height: Height::Fix(ascent + descent),
+                margin: Margin::zero(),
+                ..Walk::default()
+            });
+            let mut x = geom.x;
+            for (offset, item) in line.iter().enumerate() {
+                placed.push((index + offset, Rect {x, y: geom.y + ascent - item.ascent, w: item.width, h: item.height}));
+                let span_rects = &mut self.span_rects[item.span_index];
+                match span_rects.last_mut() {
+                    Some(rect) if rect.y == geom.y => rect.w = x + item.width - rect.x,
+                    _ => span_rects.push(Rect {x, y: geom.y, w: item.width, h: ascent + descent})
+                }
+                x += item.width;
+            }
+            // the last line stays open so following content can continue on it, like draw_text
+            if end < items.len() || line.last().map_or(false, | item | item.newline) {
+                cx.turtle_new_line_min_height(line_height);
+            }
+            index = end;
+        }
+        
+        // draw span by span so every span gets its own contiguous area
+        let mut quads = Vec::new();
+        self.span_areas = Vec::new();
+        for (span_index, span) in spans.iter().enumerate() {
+            match span {
+                TextSpan::Text {text_style, color, variant, underline, ..} => {
+                    self.set_span_style(cx, text_style, *variant);
+                    self.text.color = *color;
+                    let mut aligned = self.text.begin_text(cx);
+                    for (item_index, rect) in &placed {
+                        let item = &items[*item_index];
+                        if item.span_index != span_index {
+                            continue;
+                        }
+                        self.text.add_text(cx, rect.x, rect.y, 0, &mut aligned, &item.chars, | _, _, _, _ | {0.0});
+                        if *underline && item.ink_width > 0.0 {
+                            let font_size = text_style.font_size * self.text.font_scale;
+                            quads.push((span_index, Rect {
+                                x: rect.x,
+                                y: rect.y + item.ascent + font_size * self.underline_offset,
+                                w: item.ink_width,
+                                h: (font_size * self.underline_thickness).max(1.0)
+                            }, *color));
+                        }
+                    }
+                    self.span_areas.push(self.text.end_text(cx, &aligned));
+                },
+                TextSpan::Quad {color, ..} => {
+                    for (item_index, rect) in &placed {
+                        if items[*item_index].span_index == span_index {
+                            quads.push((span_index, *rect, *color));
+                        }
+                    }
+                    self.span_areas.push(Area::Empty);
+                }
+            }
+        }
+        
+        // underlines and inline quads go last so they batch into one draw call
+        for (span_index, rect, color) in quads {
+            self.quad.color = color;
+            let inst = self.quad.draw_quad_abs(cx, rect);
+            cx.align_instance(inst);
+            if let TextSpan::Quad {..} = spans[span_index] {
+                self.span_areas[span_index] = inst.into();
+            }
+        }
+        self.span_areas.clone()
+    }
+    
+    // the span under a finger position, hit tested against the lines each span covers
+    pub fn find_span(&self, cx: &Cx, pos: Vec2) -> Option<usize> {
+        let scroll_pos = self.span_areas.iter().find( | area | !area.is_empty()).map_or(Vec2::default(), | area | area.get_scroll_pos(cx));
+        let spos = Vec2 {x: pos.x + scroll_pos.x, y: pos.y + scroll_pos.y};
+        for (span_index, rects) in self.span_rects.iter().enumerate() {
+            for rect in rects {
+                if rect.contains(spos.x, spos.y) {
+                    return Some(span_index)
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn item(width: f32, joined: bool, newline: bool) -> RichTextItem {
+        RichTextItem {
+            span_index: 0,
+            chars: Vec::new(),
+            width,
+            ink_width: width,
+            height: 10.0,
+            ascent: 8.0,
+            line_height: 12.0,
+            newline,
+            joined
+        }
+    }
+    
+    fn words(text: &str) -> (Vec<(String, bool)>, bool) {
+        let (words, joined) = split_words(text);
+        (words.into_iter().map( | (chars, newline) | (chars.into_iter().collect(), newline)).collect(), joined)
+    }
+    
+    #[test]
+    fn words_end_after_whitespace_and_commas() {
+        assert_eq!(words("one two,three\tfour"), (vec![
+            ("one ".to_string(), false),
+            ("two,".to_string(), false),
+            ("three\t".to_string(), false),
+            ("four".to_string(), false)
+        ], true));
+        assert_eq!(words("line\n\nnext "), (vec![
+            ("line".to_string(), true),
+            ("".to_string(), true),
+            ("next ".to_string(), false)
+        ], false));
+        // dashes, dots and other punctuation dont break
+        assert_eq!(words("a-b.c").0.len(), 1);
+        assert_eq!(words(""), (vec![], false));
+    }
+    
+    #[test]
+    fn lines_break_before_the_word_that_doesnt_fit() {
+        let items = vec![item(30.0, false, false), item(30.0, false, false), item(30.0, false, false)];
+        assert_eq!(break_line(&items, 0, 70.0), (2, 60.0));
+        assert_eq!(break_line(&items, 2, 70.0), (3, 30.0));
+        // a word wider than the line still goes on it, or nothing would
+        assert_eq!(break_line(&items, 0, 10.0), (1, 30.0));
+        // without a width to break against everything goes on one line
+        assert_eq!(break_line(&items, 0, std::f32::NAN), (3, 90.0));
+    }
+    
+    #[test]
+    fn words_split_over_spans_move_as_a_whole() {
+        // "see " then "bold" and "ly " in two spans, then "said"
+        let items = vec![item(40.0, false, false), item(40.0, true, false), item(30.0, false, false), item(40.0, false, false)];
+        assert_eq!(break_line(&items, 0, 100.0), (1, 40.0));
+        assert_eq!(break_line(&items, 1, 100.0), (3, 70.0));
+        // a joined word that doesnt fit on its own line still goes whole
+        assert_eq!(break_line(&items, 1, 50.0), (3, 70.0));
+    }
+    
+    #[test]
+    fn newlines_end_the_line() {
+        let items = vec![item(10.0, false, true), item(10.0, false, false)];
+        assert_eq!(break_line(&items, 0, 100.0), (1, 10.0));
+        assert_eq!(break_line(&items, 1, 100.0), (2, 10.0));
+    }
+}
@@ -0,0 +1,527 @@
+use crate::cx::*;
+
+// the bidi classes of the unicode bidirectional algorithm, explicit embeddings and isolates
+// are treated as boundary neutrals
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BidiClass {
+    L,
+    R,
+    AL,
+    EN,
+    ES,
+    ET,
+    AN,
+    CS,
+    NSM,
+    BN,
+    B,
+    S,
+    WS,
+    ON
+}
+
+impl BidiClass {
+    pub fn of(c: char) -> BidiClass {
+        let c = c as u32;
+        match c {
+            0x30..=0x39 | 0xB2 | 0xB3 | 0xB9 | 0x6F0..=0x6F9 | 0x2070..=0x2079 | 0x2080..=0x2089 => BidiClass::EN,
+            0x41..=0x5A | 0x61..=0x7A => BidiClass::L,
+            0x0A | 0x0D | 0x1C..=0x1E | 0x85 | 0x2029 => BidiClass::B,
+            0x09 | 0x0B | 0x1F => BidiClass::S,
+            0x20 | 0x0C | 0x2000..=0x200A | 0x2028 | 0x205F | 0x3000 => BidiClass::WS,
+            0x00..=0x08 | 0x0E..=0x1B | 0x7F..=0x84 | 0x86..=0x9F | 0xAD | 0x200B..=0x200D
+                | 0x202A..=0x202E | 0x2060..=0x2069 | 0xFEFF => BidiClass::BN,
+            0x2B | 0x2D | 0x207A | 0x207B | 0x208A | 0x208B => BidiClass::ES,
+            0x23..=0x25 | 0xA2..=0xA5 | 0xB0 | 0xB1 | 0x609 | 0x60A | 0x66A | 0x2030..=0x2034
+                | 0x20A0..=0x20CF => BidiClass::ET,
+            0x2C | 0x2E | 0x2F | 0x3A | 0xA0 | 0x60C | 0x202F | 0x2044 => BidiClass::CS,
+            0x600..=0x605 | 0x660..=0x669 | 0x66B | 0x66C | 0x6DD => BidiClass::AN,
+            0x300..=0x36F | 0x483..=0x489 | 0x591..=0x5BD | 0x5BF | 0x5C1 | 0x5C2 | 0x5C4 | 0x5C5
+                | 0x5C7 | 0x610..=0x61A | 0x64B..=0x65F | 0x670 | 0x6D6..=0x6DC | 0x6DF..=0x6E4
+                | 0x6E7 | 0x6E8 | 0x6EA..=0x6ED | 0x20D0..=0x20F0 | 0xFE00..=0xFE0F
+                | 0xFE20..=0xFE2F => BidiClass::NSM,
+            0x200E => BidiClass::L,
+            0x200F => BidiClass::R,
+            0x61C => BidiClass::AL,
+            0x21..=0x7E | 0xA1..=0xBF | 0xD7 | 0xF7 | 0x2010..=0x2027 | 0x2035..=0x205E
+                | 0x2190..=0x2BFF | 0x3001..=0x3003 | 0xFE50..=0xFE6F | 0xFF01..=0xFF0F => BidiClass::ON,
+            0x590..=0x5FF | 0x7C0..=0x85F | 0xFB1D..=0xFB4F | 0x10800..=0x10CFF
+                | 0x1E800..=0x1EDFF => BidiClass::R,
+            0x600..=0x7BF | 0x860..=0x8FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFE | 0x10D00..=0x10D3F
+                | 0x1EE00..=0x1EEFF => BidiClass::AL,
+            _ => BidiClass::L
+        }
+    }
+
+    fn is_strong(&self) -> bool {
+        match self {
+            BidiClass::L | BidiClass::R | BidiClass::AL => true,
+            _ => false
+        }
+    }
+
+    fn is_neutral(&self) -> bool {
+        match self {
+            BidiClass::B | BidiClass::S | BidiClass::WS | BidiClass::ON | BidiClass::BN => true,
+            _ => false
+        }
+    }
+}
+
+// quick check so left to right text can skip the bidi algorithm entirely
+pub fn has_rtl(chars: &[char]) -> bool {
+    for c in chars {
+        if *c >= '\u{590}' {
+            match BidiClass::of(*c) {
+                BidiClass::R | BidiClass::AL | BidiClass::AN => return true,
+                _ => ()
+            }
+        }
+    }
+    false
+}
+
+// resolves the embedding level of every char, per paragraph. returns the level of the first paragraph
+pub fn bidi_levels(chars: &[char]) -> (u8, Vec<u8>) {
+    let mut levels = vec![0u8; chars.len()];
+    let mut first_para_level = None;
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start;
+        while end < chars.len() {
+            end += 1;
+            if BidiClass::of(chars[end - 1]) == BidiClass::B {
+                break;
+            }
+        }
+        let para_level = bidi_paragraph_levels(&chars[start..end], &mut levels[start..end]);
+        if first_para_level.is_none() {
+            first_para_level = Some(para_level);
+        }
+        start = end;
+    }
+    (first_para_level.unwrap_or(0), levels)
+}
+
+fn bidi_paragraph_levels(chars: &[char], levels: &mut [u8]) -> u8 {
+    let original: Vec<BidiClass> = chars.iter().map( | c | BidiClass::of(*c)).collect();
+
+    // P2, P3 the first strong char picks the paragraph direction
+    let para_level = match original.iter().find( | class | class.is_strong()) {
+        Some(BidiClass::R) | Some(BidiClass::AL) => 1,
+        _ => 0
+    };
+    let sos = if para_level == 1 {BidiClass::R} else {BidiClass::L};
+
+    // X9 boundary neutrals take on the class of whatever precedes them
+    let mut classes = original.clone();
+    for i in 0..classes.len() {
+        if classes[i] == BidiClass::BN {
+            classes[i] = if i == 0 {sos} else {classes[i - 1]};
+        }
+    }
+
+    // W1 nonspacing marks take the class of the previous char
+    for i in 0..classes.len() {
+        if classes[i] == BidiClass::NSM {
+            classes[i] = if i == 0 {sos} else {classes[i - 1]};
+        }
+    }
+
+    // W2, W3 european numbers after arabic letters are arabic numbers
+    let mut last_strong = sos;
+    for class in &mut classes {
+        match *class {
+            BidiClass::L | BidiClass::R => last_strong = *class,
+            BidiClass::AL => {
+                last_strong = BidiClass::AL;
+                *class = BidiClass::R;
+            }
+            BidiClass::EN if last_strong == BidiClass::AL => *class = BidiClass::AN,
+            _ => ()
+        }
+    }
+
+    // W4 a single separator between two numbers of the same kind joins them
+    for i in 1..classes.len().max(1) - 1 {
+        let (prev, next) = (classes[i - 1], classes[i + 1]);
+        match classes[i] {
+            BidiClass::ES if prev == BidiClass::EN && next == BidiClass::EN => classes[i] = BidiClass::EN,
+            BidiClass::CS if prev == next && (prev == BidiClass::EN || prev == BidiClass::AN) => classes[i] = prev,
+            _ => ()
+        }
+    }
+
+    // W5 terminators next to european numbers become european numbers
+    let mut i = 0;
+    while i < classes.len() {
+        if classes[i] == BidiClass::ET {
+            let start = i;
+            while i < classes.len() && classes[i] == BidiClass::ET {
+                i += 1;
+            }
+            let touches_en = (start > 0 && classes[start - 1] == BidiClass::EN)
+                || (i < classes.len() && classes[i] == BidiClass::EN);
+            if touches_en {
+                for class in &mut classes[start..i] {
+                    *class = BidiClass::EN;
+                }
+            }
+        }
+        else {
+            i += 1;
+        }
+    }
+
+    // W6, W7 leftover separators are neutral, european numbers after left to right text are left to right
+    let mut last_strong = sos;
+    for class in &mut classes {
+        match *class {
+            BidiClass::ES | BidiClass::ET | BidiClass::CS => *class = BidiClass::ON,
+            BidiClass::L | BidiClass::R => last_strong = *class,
+            BidiClass::EN if last_strong == BidiClass::L => *class = BidiClass::L,
+            _ => ()
+        }
+    }
+
+    // N1, N2 neutrals between text of the same direction take that direction, otherwise the embedding direction
+    let strong_dir = | class: BidiClass | match class {
+        BidiClass::L => BidiClass::L,
+        _ => BidiClass::R
+    };
+    let mut i = 0;
+    while i < classes.len() {
+        if classes[i].is_neutral() {
+            let start = i;
+            while i < classes.len() && classes[i].is_neutral() {
+                i += 1;
+            }
+            let before = if start == 0 {sos} else {strong_dir(classes[start - 1])};
+            let after = if i == classes.len() {sos} else {strong_dir(classes[i])};
+            let resolved = if before == after {before} else {sos};
+            for class in &mut classes[start..i] {
+                *class = resolved;
+            }
+        }
+        else {
+            i += 1;
+        }
+    }
+
+    // I1, I2 implicit levels
+    for (level, class) in levels.iter_mut().zip(classes.iter()) {
+        *level = match (para_level, class) {
+            (0, BidiClass::R) => 1,
+            (0, BidiClass::AN) | (0, BidiClass::EN) => 2,
+            (0, _) => 0,
+            (_, BidiClass::L) | (_, BidiClass::AN) | (_, BidiClass::EN) => 2,
+            (_, _) => 1
+        };
+    }
+
+    // L1 separators and trailing whitespace go back to the paragraph level
+    let mut trailing = true;
+    for i in (0..original.len()).rev() {
+        match original[i] {
+            BidiClass::B | BidiClass::S => {
+                levels[i] = para_level;
+                trailing = true;
+            }
+            BidiClass::WS | BidiClass::BN if trailing => levels[i] = para_level,
+            _ => trailing = false
+        }
+    }
+    para_level
+}
+
+// L2 turns resolved levels into the visual order of the indices
+pub fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let max_level = levels.iter().cloned().max().unwrap_or(0);
+    let min_odd_level = levels.iter().cloned().filter( | level | level & 1 == 1).min().unwrap_or(max_level + 1);
+    let mut level = max_level;
+    while level >= min_odd_level && level > 0 {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] >= level {
+                let start = i;
+                while i < order.len() && levels[order[i]] >= level {
+                    i += 1;
+                }
+                order[start..i].reverse();
+            }
+            else {
+                i += 1;
+            }
+        }
+        level -= 1;
+    }
+    order
+}
+
+pub fn mirrored_char(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        '‹' => '›',
+        '›' => '‹',
+        _ => c
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArabicJoining {
+    NonJoining,
+    RightJoining,
+    DualJoining,
+    JoinCausing,
+    Transparent
+}
+
+impl ArabicJoining {
+    pub fn of(c: char) -> ArabicJoining {
+        match c as u32 {
+            0x640 | 0x200D => ArabicJoining::JoinCausing,
+            0x622..=0x625 | 0x627 | 0x629 | 0x62F..=0x632 | 0x648 | 0x671..=0x673 | 0x675..=0x677
+                | 0x688..=0x699 | 0x6C0 | 0x6C3..=0x6CB | 0x6CD | 0x6CF | 0x6D2 | 0x6D3 | 0x6D5
+                | 0x6EE | 0x6EF => ArabicJoining::RightJoining,
+            0x626 | 0x628 | 0x62A..=0x62E | 0x633..=0x63F | 0x641..=0x647 | 0x649 | 0x64A
+                | 0x66E | 0x66F | 0x678..=0x687 | 0x69A..=0x6BF | 0x6C1 | 0x6C2 | 0x6CC | 0x6CE
+                | 0x6D0 | 0x6D1 | 0x6FA..=0x6FC | 0x6FF => ArabicJoining::DualJoining,
+            _ => if BidiClass::of(c) == BidiClass::NSM {
+                ArabicJoining::Transparent
+            }
+            else {
+                ArabicJoining::NonJoining
+            }
+        }
+    }
+
+    fn joins_following(&self) -> bool {
+        *self == ArabicJoining::DualJoining || *self == ArabicJoining::JoinCausing
+    }
+
+    fn joins_preceding(&self) -> bool {
+        *self == ArabicJoining::DualJoining || *self == ArabicJoining::RightJoining || *self == ArabicJoining::JoinCausing
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArabicForm {
+    Isolated,
+    Initial,
+    Medial,
+    Final
+}
+
+impl ArabicForm {
+    pub fn feature_tag(&self) -> &'static [u8; 4] {
+        match self {
+            ArabicForm::Isolated => b"isol",
+            ArabicForm::Initial => b"init",
+            ArabicForm::Medial => b"medi",
+            ArabicForm::Final => b"fina",
+        }
+    }
+}
+
+// the positional form of every char that has one, in logical order
+pub fn arabic_forms(chars: &[char]) -> Vec<Option<ArabicForm>> {
+    let joinings: Vec<ArabicJoining> = chars.iter().map( | c | ArabicJoining::of(*c)).collect();
+    let mut forms = vec![None; chars.len()];
+    let mut prev: Option<ArabicJoining> = None;
+    for i in 0..chars.len() {
+        let joining = joinings[i];
+        if joining == ArabicJoining::Transparent {
+            continue;
+        }
+        if joining == ArabicJoining::RightJoining || joining == ArabicJoining::DualJoining {
+            let next = joinings[i + 1..].iter().find( | j | **j != ArabicJoining::Transparent);
+            let joins_prev = prev.map_or(false, | p | p.joins_following());
+            let joins_next = joining.joins_following() && next.map_or(false, | n | n.joins_preceding());
+            forms[i] = Some(match (joins_prev, joins_next) {
+                (true, true) => ArabicForm::Medial,
+                (true, false) => ArabicForm::Final,
+                (false, true) => ArabicForm::Initial,
+                (false, false) => ArabicForm::Isolated
+            });
+        }
+        prev = Some(joining);
+    }
+    forms
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShapedGlyph {
+    pub font_id: usize,
+    pub glyph_id: usize,
+    pub char_index: usize,
+    pub char_count: usize,
+    pub is_rtl: bool,
+}
+
+// turns chars into glyphs in visual order, with fallback fonts, arabic joining forms,
+// ligatures when asked for and bidi reordering applied
+pub fn shape_chars(fonts: &[CxFont], font_id: usize, chars: &[char], ligatures: bool) -> Vec<ShapedGlyph> {
+    let mut glyphs = Vec::with_capacity(chars.len());
+    shape_chars_into(fonts, font_id, chars, None, ligatures, &mut glyphs);
+    glyphs
+}
+
+// shapes into a buffer the caller keeps around. the levels are resolved here unless the caller
+// already did that for the whole paragraph. left to right text without ligatures allocates nothing
+pub fn shape_chars_into(fonts: &[CxFont], font_id: usize, chars: &[char], levels: Option<&[u8]>, ligatures: bool, glyphs: &mut Vec<ShapedGlyph>) {
+    glyphs.clear();
+    let resolved;
+    let levels = match levels {
+        Some(levels) => Some(levels),
+        None if has_rtl(chars) => {
+            resolved = bidi_levels(chars).1;
+            Some(&resolved[..])
+        },
+        None => None
+    };
+    let rtl = levels.map_or(false, | levels | levels.iter().any( | level | level & 1 == 1));
+    let level_at = | index: usize | levels.map_or(0, | levels | levels[index]);
+    let forms = if rtl {arabic_forms(chars)} else {Vec::new()};
+
+    for (index, c) in chars.iter().enumerate() {
+        let is_rtl = level_at(index) & 1 == 1;
+        let c = if is_rtl {mirrored_char(*c)} else {*c};
+        let (glyph_font_id, mut glyph_id) = CxFont::find_glyph(fonts, font_id, c);
+        if let Some(Some(form)) = forms.get(index) {
+            let font = fonts[glyph_font_id].font_loaded.as_ref().unwrap();
+            glyph_id = font.substitute(form.feature_tag(), glyph_id);
+        }
+        glyphs.push(ShapedGlyph {
+            font_id: glyph_font_id,
+            glyph_id,
+            char_index: index,
+            char_count: 1,
+            is_rtl
+        });
+    }
+
+    // ligatures only form within the primary font and within one direction
+    let font = fonts[font_id].font_loaded.as_ref().unwrap();
+    if ligatures && !font.ligatures.is_empty() {
+        let glyph_ids: Vec<usize> = glyphs.iter().map( | g | g.glyph_id).collect();
+        let mut run_ends = vec![glyphs.len(); glyphs.len()];
+        for i in (0..glyphs.len().max(1) - 1).rev() {
+            let same_run = glyphs[i].font_id == glyphs[i + 1].font_id && level_at(i) == level_at(i + 1);
+            run_ends[i] = if same_run {run_ends[i + 1]} else {i + 1};
+        }
+        let mut ligated = Vec::with_capacity(glyphs.len());
+        let mut ligated_levels = Vec::with_capacity(glyphs.len());
+        let mut i = 0;
+        while i < glyphs.len() {
+            let mut glyph = glyphs[i];
+            if glyph.font_id == font_id {
+                if let Some(ligature) = font.ligature(&glyph_ids[i..run_ends[i]]) {
+                    glyph.glyph_id = ligature.glyph_index;
+                    glyph.char_count += ligature.components.len();
+                }
+            }
+            ligated.push(glyph);
+            ligated_levels.push(level_at(i));
+            i += glyph.char_count;
+        }
+        if rtl {
+            *glyphs = visual_order(&ligated_levels).into_iter().map( | i | ligated[i]).collect();
+        }
+        else {
+            *glyphs = ligated;
+        }
+    }
+    else if let (true, Some(levels)) = (rtl, levels) {
+        let logical = std::mem::replace(glyphs, Vec::new());
+        glyphs.extend(visual_order(levels).into_iter().map( | i | logical[i]));
+    }
+}
+
+// L1 for a line of a paragraph: whitespace at the end of the line goes back to the paragraph level
+pub fn reset_line_end_levels(chars: &[char], levels: &mut [u8], para_level: u8) {
+    for (c, level) in chars.iter().zip(levels.iter_mut()).rev() {
+        match BidiClass::of(*c) {
+            BidiClass::WS | BidiClass::BN | BidiClass::S | BidiClass::B => *level = para_level,
+            _ => break
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn left_to_right_text_stays_at_level_zero() {
+        assert!(!has_rtl(&chars("abc, 123")));
+        assert_eq!(bidi_levels(&chars("abc, 123")), (0, vec![0; 8]));
+    }
+
+    #[test]
+    fn hebrew_in_a_left_to_right_paragraph() {
+        let text = chars("ab \u{5d0}\u{5d1} ");
+        assert!(has_rtl(&text));
+        let (para_level, levels) = bidi_levels(&text);
+        assert_eq!(para_level, 0);
+        // the trailing space goes back to the paragraph level
+        assert_eq!(levels, vec![0, 0, 0, 1, 1, 0]);
+        assert_eq!(visual_order(&levels), vec![0, 1, 2, 4, 3, 5]);
+    }
+
+    #[test]
+    fn latin_and_numbers_in_a_right_to_left_paragraph() {
+        let (para_level, levels) = bidi_levels(&chars("\u{5d0}\u{5d1} ab"));
+        assert_eq!(para_level, 1);
+        assert_eq!(levels, vec![1, 1, 1, 2, 2]);
+        assert_eq!(visual_order(&levels), vec![3, 4, 2, 1, 0]);
+
+        // numbers keep reading left to right
+        let (_, levels) = bidi_levels(&chars("\u{5d0} 12"));
+        assert_eq!(levels, vec![1, 1, 2, 2]);
+        assert_eq!(visual_order(&levels), vec![2, 3, 1, 0]);
+
+        // and after arabic letters they are arabic numbers
+        let (_, levels) = bidi_levels(&chars("\u{627}12"));
+        assert_eq!(levels, vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn every_paragraph_picks_its_own_direction() {
+        assert_eq!(bidi_levels(&chars("\u{5d0}\u{5d1}\nab")), (1, vec![1, 1, 1, 0, 0]));
+    }
+
+    #[test]
+    fn visual_order_reverses_from_the_highest_level_down() {
+        assert_eq!(visual_order(&[]), Vec::<usize>::new());
+        assert_eq!(visual_order(&[0, 0, 0]), vec![0, 1, 2]);
+        assert_eq!(visual_order(&[1, 1, 1]), vec![2, 1, 0]);
+        assert_eq!(visual_order(&[0, 1, 2, 2, 1, 0]), vec![0, 4, 2, 3, 1, 5]);
+        // even levels above zero on their own don't reverse anything
+        assert_eq!(visual_order(&[2, 2]), vec![0, 1]);
+    }
+
+    #[test]
+    fn whitespace_at_a_line_break_goes_back_to_the_paragraph_level() {
+        let mut levels = vec![1, 2, 2];
+        reset_line_end_levels(&chars("\u{5d0}  "), &mut levels, 1);
+        assert_eq!(levels, vec![1, 1, 1]);
+
+        let mut levels = vec![2, 1, 2];
+        reset_line_end_levels(&chars("a b"), &mut levels, 1);
+        assert_eq!(levels, vec![2, 1, 2]);
+    }
+}
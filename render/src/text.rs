@@ -44,6 +44,13 @@ pub struct Text {
     pub z: f32,
     pub wrapping: Wrapping,
     pub font_scale: f32,
+    // off by default, in monospace text they would break the columns
+    pub ligatures: bool,
+    // scratch buffers for add_text so the editor doesn't allocate per chunk
+    pub _shaped: Vec<ShapedGlyph>,
+    pub _placed: Vec<(ShapedGlyph, f32)>,
+    pub _char_geoms: Vec<Option<(f32, f32)>>,
+    pub _markers: Vec<f32>,
 }
 
 impl Text {
@@ -57,12 +64,18 @@ impl Text {
                 line_spacing: 1.4,
                 top_drop: 1.1,
                 height_factor: 1.3,
+                sdf: false,
             },
             shader: live_shader!(cx, self::shader),
             z: 0.0,
             wrapping: Wrapping::Word,
             color: Color::parse_name("white").unwrap(),
             font_scale: 1.0,
+            ligatures: false,
+            _shaped: Vec::new(),
+            _placed: Vec::new(),
+            _char_geoms: Vec::new(),
+            _markers: Vec::new(),
         }
     }
     
@@ -77,6 +90,10 @@ impl Text {
             geometry geom: vec2;
 
             texture texturez: texture2D;
+            texture texturez1: texture2D;
+            texture texturez2: texture2D;
+            texture texturez3: texture2D;
+            texture sdf_texture: texture2D;
             
             instance font_tc: vec4;
             instance color: vec4;
@@ -90,6 +107,9 @@ impl Text {
             instance font_size: float;
             instance char_offset: float;
             instance marker: float;
+            instance rtl: float;
+            instance sdf: float;
+            instance atlas_texture: float;
             
             varying tex_coord1: vec2;
             varying tex_coord2: vec2;
@@ -104,8 +124,31 @@ impl Text {
                 return color;
             }
             
+            // the glyph atlas is spread over separate textures
+            fn sample_atlas(pos: vec2) -> vec4 {
+                if atlas_texture < 0.5 {
+                    return sample2d(texturez, pos);
+                }
+                if atlas_texture < 1.5 {
+                    return sample2d(texturez1, pos);
+                }
+                if atlas_texture < 2.5 {
+                    return sample2d(texturez2, pos);
+                }
+                return sample2d(texturez3, pos);
+            }
+            
             fn pixel() -> vec4 {
                 
+                // distance field glyphs have their edge at 0.5, smoothed over about a pixel at any scale
+                if sdf > 0.5 {
+                    let dist = sample2d(sdf_texture, tex_coord1.xy).x;
+                    let aa = max(length(vec2(dFdx(dist), dFdy(dist))) * 0.7, 0.001);
+                    let s = pow(smoothstep(0.5 - aa, 0.5 + aa, dist), curve);
+                    let col = get_color();
+                    return vec4(s * col.rgb * brightness * col.a, s * col.a);
+                }
+                
                 let dx = dFdx(vec2(tex_coord1.x * 2048.0, 0.)).x;
                 let dp = 1.0 / 2048.0;
 
@@ -117,20 +160,20 @@ impl Text {
                 }
                 else if dx > 2.75 {
                     s = (
-                        sample2d(texturez, tex_coord3.xy + vec2(0., 0.)).z
-                            + sample2d(texturez, tex_coord3.xy + vec2(dp, 0.)).z
-                            + sample2d(texturez, tex_coord3.xy + vec2(0., dp)).z
-                            + sample2d(texturez, tex_coord3.xy + vec2(dp, dp)).z
+                        sample_atlas(tex_coord3.xy + vec2(0., 0.)).z
+                            + sample_atlas(tex_coord3.xy + vec2(dp, 0.)).z
+                            + sample_atlas(tex_coord3.xy + vec2(0., dp)).z
+                            + sample_atlas(tex_coord3.xy + vec2(dp, dp)).z
                     ) * 0.25;
                 }
                 else if dx > 1.75 {
-                    s = sample2d(texturez, tex_coord3.xy).z;
+                    s = sample_atlas(tex_coord3.xy).z;
                 }
                 else if dx > 1.3 {
-                    s = sample2d(texturez, tex_coord2.xy).y;
+                    s = sample_atlas(tex_coord2.xy).y;
                 }
                 else {
-                    s = sample2d(texturez, tex_coord1.xy).x;
+                    s = sample_atlas(tex_coord1.xy).x;
                 }
                 
                 s = pow(s, curve);
@@ -182,38 +225,70 @@ impl Text {
         let brightness = text_style.brightness;
         let curve = text_style.curve;
         if aligned.inst.need_uniforms_now(cx) {
-            aligned.inst.push_uniform_texture_2d_id(cx, cx.fonts_atlas.texture_id);
+            for texture_id in cx.fonts_atlas.texture_ids.clone() {
+                aligned.inst.push_uniform_texture_2d_id(cx, texture_id);
+            }
+            aligned.inst.push_uniform_texture_2d_id(cx, cx.fonts_sdf_atlas.texture_ids[0]);
             aligned.inst.push_uniform_float(cx, brightness);
             aligned.inst.push_uniform_float(cx, curve);
         }
         return aligned
     }
     
-    pub fn add_text<F>(&mut self, cx: &mut Cx, geom_x: f32, geom_y: f32, char_offset: usize, aligned: &mut AlignedInstance, chunk: &[char], mut char_callback: F)
+    pub fn add_text<F>(&mut self, cx: &mut Cx, geom_x: f32, geom_y: f32, char_offset: usize, aligned: &mut AlignedInstance, chunk: &[char], char_callback: F)
+    where F: FnMut(char, usize, f32, f32) -> f32
+    {
+        self.add_text_levels(cx, geom_x, geom_y, char_offset, aligned, chunk, None, char_callback)
+    }
+    
+    // same as add_text, with the bidi levels of the chunk resolved by the caller for the whole paragraph
+    pub fn add_text_levels<F>(&mut self, cx: &mut Cx, geom_x: f32, geom_y: f32, char_offset: usize, aligned: &mut AlignedInstance, chunk: &[char], levels: Option<&[u8]>, mut char_callback: F)
     where F: FnMut(char, usize, f32, f32) -> f32
     {
         if geom_x.is_nan() || geom_y.is_nan(){
             return
         }
         
+        let mut shaped = std::mem::replace(&mut self._shaped, Vec::new());
+        let mut placed = std::mem::replace(&mut self._placed, Vec::new());
+        let mut char_geoms = std::mem::replace(&mut self._char_geoms, Vec::new());
+        let mut markers = std::mem::replace(&mut self._markers, Vec::new());
+        
         let text_style = &self.text_style;
-        let mut geom_x = geom_x;
-        let mut char_offset = char_offset;
         let font_id = text_style.font.font_id;
         
-        let cxfont = &mut cx.fonts[font_id];
-        
+        let redraw_id = cx.redraw_id;
         let dpi_factor = cx.current_dpi_factor;
         
-        //let geom_y = (geom_y * dpi_factor).floor() / dpi_factor;
-        let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, text_style.font_size);
+        // fallbacks, joining forms, ligatures and bidi reordering, glyphs come out in visual order
+        shape_chars_into(&cx.fonts, font_id, chunk, levels, self.ligatures, &mut shaped);
         
-        let font = &mut cxfont.font_loaded.as_ref().unwrap();
-        
-        let font_size_logical = text_style.font_size * 96.0 / (72.0 * font.units_per_em);
-        let font_size_pixels = font_size_logical * dpi_factor;
+        // lay the glyphs out left to right, the chars of a glyph share its advance
+        placed.clear();
+        char_geoms.clear();
+        char_geoms.resize(chunk.len(), None);
+        self.place_glyphs(cx, &shaped, geom_x, | glyph, geom_x, advance | {
+            let char_advance = advance / glyph.char_count as f32;
+            for i in 0..glyph.char_count {
+                let x = if glyph.is_rtl {
+                    geom_x + advance - char_advance * (i + 1) as f32
+                }
+                else {
+                    geom_x + char_advance * i as f32
+                };
+                char_geoms[glyph.char_index + i] = Some((x, char_advance));
+            }
+            placed.push((glyph, geom_x));
+        });
         
-        let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
+        // give the callback a chance to do things, in logical order
+        markers.clear();
+        markers.resize(chunk.len(), 0.0);
+        for (index, char_geom) in char_geoms.iter().enumerate() {
+            if let Some((x, w)) = char_geom {
+                markers[index] = char_callback(chunk[index], char_offset + index, *x, *w);
+            }
+        }
         
         let instance = {
             let cxview = &mut cx.views[aligned.inst.view_id];
@@ -221,70 +296,122 @@ impl Text {
             &mut draw_call.instance
         };
         
-        for wc in chunk {
-            let unicode = *wc as usize;
-            let glyph_id = font.char_code_to_glyph_index_map[unicode];
-            if glyph_id >= font.glyphs.len() {
-                println!("GLYPHID OUT OF BOUNDS {} {} len is {}", unicode, glyph_id, font.glyphs.len());
-                continue;
-            }
-
-            let glyph = &font.glyphs[glyph_id];
-            
-            let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
-            
-            // snap width/height to pixel granularity
-            let w = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + 1.0;
-            let h = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + 1.0;
-            
-            // this one needs pixel snapping
-            let min_pos_x = geom_x + font_size_logical * glyph.bounds.p_min.x;
-            let min_pos_y = geom_y - font_size_logical * glyph.bounds.p_min.y + text_style.font_size * text_style.top_drop;
+        let mut last_atlas_page = None;
+        for (shaped_glyph, geom_x) in placed.iter().cloned() {
+            let glyph_font_id = shaped_glyph.font_id;
+            let glyph_id = shaped_glyph.glyph_id;
             
-            // compute subpixel shift
-            let subpixel_x_fract = min_pos_x - (min_pos_x * dpi_factor).floor() / dpi_factor;
-            let subpixel_y_fract = min_pos_y - (min_pos_y * dpi_factor).floor() / dpi_factor;
-            
-            
-            // scale and snap it
-            let scaled_min_pos_x = geom_x + font_size_logical * self.font_scale * glyph.bounds.p_min.x - subpixel_x_fract;
-            let scaled_min_pos_y = geom_y - font_size_logical * self.font_scale * glyph.bounds.p_min.y + text_style.font_size * self.font_scale * text_style.top_drop - subpixel_y_fract;
-            
-            // only use a subpixel id for small fonts
-            let subpixel_id = if text_style.font_size>32.0 {
-                0
-            }
-            else { // subtle 64 index subpixel id
-                ((subpixel_y_fract * 7.0) as usize) << 3 |
-                (subpixel_x_fract * 7.0) as usize
-            };
+            let cxfont = &mut cx.fonts[glyph_font_id];
             
-            let tc = if let Some(tc) = &atlas_page.atlas_glyphs[glyph_id][subpixel_id] {
-                //println!("{} {} {} {}", tc.tx1,tc.tx2,tc.ty1,tc.ty2);
-                tc
+            let (tc, scaled_min_pos_x, scaled_min_pos_y, w, h) = if text_style.sdf {
+                // one distance field entry serves every size, the shader reconstructs the edges
+                let font = cxfont.font_loaded.as_ref().unwrap();
+                let glyph = &font.glyphs[glyph_id];
+                let font_size_logical = text_style.font_size * 96.0 / (72.0 * font.units_per_em);
+                if cxfont.sdf_glyphs.len() != font.glyphs.len() {
+                    cxfont.sdf_glyphs.resize(font.glyphs.len(), None);
+                }
+                let (tw, th) = CxFont::sdf_glyph_size(glyph, font.units_per_em);
+                let tc = if let Some(tc) = cxfont.sdf_glyphs[glyph_id] {
+                    Some(tc)
+                }
+                else if let Some(tc) = cx.fonts_sdf_atlas.alloc_atlas_glyph(tw, th) {
+                    cx.fonts_sdf_atlas.atlas_todo.push(CxFontsAtlasTodo {
+                        font_id: glyph_font_id,
+                        glyph_id,
+                        ..CxFontsAtlasTodo::default()
+                    });
+                    cxfont.sdf_glyphs[glyph_id] = Some(tc);
+                    Some(tc)
+                }
+                else {
+                    cx.fonts_sdf_atlas.alloc_failed = true;
+                    None
+                };
+                let texel_size = font.units_per_em / SDF_GLYPH_EM * font_size_logical * self.font_scale;
+                let pad = SDF_GLYPH_PAD * texel_size;
+                let min_pos_x = geom_x + font_size_logical * self.font_scale * glyph.bounds.p_min.x - pad;
+                let min_pos_y = geom_y - font_size_logical * self.font_scale * glyph.bounds.p_min.y + text_style.font_size * self.font_scale * text_style.top_drop + pad;
+                if let Some(tc) = tc {
+                    (tc, min_pos_x, min_pos_y, tw * texel_size, th * texel_size)
+                }
+                else {
+                    (CxFontAtlasGlyph::default(), min_pos_x, min_pos_y, 0.0, 0.0)
+                }
             }
             else {
-                // see if we can fit it
-                // allocate slot
-                cx.fonts_atlas.atlas_todo.push(CxFontsAtlasTodo {
-                    subpixel_x_fract,
-                    subpixel_y_fract,
-                    font_id,
-                    atlas_page_id,
-                    glyph_id,
-                    subpixel_id
-                });
+                //let geom_y = (geom_y * dpi_factor).floor() / dpi_factor;
+                let atlas_page_id = match last_atlas_page {
+                    Some((last_font_id, atlas_page_id)) if last_font_id == glyph_font_id => atlas_page_id,
+                    _ => cxfont.get_atlas_page_id(dpi_factor, text_style.font_size)
+                };
+                last_atlas_page = Some((glyph_font_id, atlas_page_id));
                 
-                atlas_page.atlas_glyphs[glyph_id][subpixel_id] = Some(
-                    cx.fonts_atlas.alloc_atlas_glyph(&cxfont.file, w, h)
-                );
+                let font = cxfont.font_loaded.as_ref().unwrap();
+                
+                let font_size_logical = text_style.font_size * 96.0 / (72.0 * font.units_per_em);
+                let font_size_pixels = font_size_logical * dpi_factor;
                 
-                atlas_page.atlas_glyphs[glyph_id][subpixel_id].as_ref().unwrap()
+                let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
+                atlas_page.last_used = redraw_id;
+                
+                let glyph = &font.glyphs[glyph_id];
+                
+                // snap width/height to pixel granularity
+                let mut w = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + 1.0;
+                let mut h = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + 1.0;
+                
+                // this one needs pixel snapping
+                let min_pos_x = geom_x + font_size_logical * glyph.bounds.p_min.x;
+                let min_pos_y = geom_y - font_size_logical * glyph.bounds.p_min.y + text_style.font_size * text_style.top_drop;
+                
+                // compute subpixel shift
+                let subpixel_x_fract = min_pos_x - (min_pos_x * dpi_factor).floor() / dpi_factor;
+                let subpixel_y_fract = min_pos_y - (min_pos_y * dpi_factor).floor() / dpi_factor;
+                
+                
+                // scale and snap it
+                let scaled_min_pos_x = geom_x + font_size_logical * self.font_scale * glyph.bounds.p_min.x - subpixel_x_fract;
+                let scaled_min_pos_y = geom_y - font_size_logical * self.font_scale * glyph.bounds.p_min.y + text_style.font_size * self.font_scale * text_style.top_drop - subpixel_y_fract;
+                
+                // only use a subpixel id for small fonts
+                let subpixel_id = if text_style.font_size>32.0 {
+                    0
+                }
+                else { // subtle 64 index subpixel id
+                    ((subpixel_y_fract * 7.0) as usize) << 3 |
+                    (subpixel_x_fract * 7.0) as usize
+                };
+                
+                let tc = if let Some(tc) = &atlas_page.atlas_glyphs[glyph_id][subpixel_id] {
+                    //println!("{} {} {} {}", tc.tx1,tc.tx2,tc.ty1,tc.ty2);
+                    cx.fonts_atlas.textures[tc.texture_index].last_used = redraw_id;
+                    *tc
+                }
+                else if let Some(tc) = cx.fonts_atlas.alloc_atlas_glyph(w, h) {
+                    cx.fonts_atlas.atlas_todo.push(CxFontsAtlasTodo {
+                        subpixel_x_fract,
+                        subpixel_y_fract,
+                        font_id: glyph_font_id,
+                        atlas_page_id,
+                        glyph_id,
+                        subpixel_id,
+                        texture_index: tc.texture_index
+                    });
+                    cx.fonts_atlas.textures[tc.texture_index].last_used = redraw_id;
+                    atlas_page.atlas_glyphs[glyph_id][subpixel_id] = Some(tc);
+                    tc
+                }
+                else {
+                    // atlas is full, draw nothing and let the after draw make room
+                    cx.fonts_atlas.alloc_failed = true;
+                    w = 0.0;
+                    h = 0.0;
+                    CxFontAtlasGlyph::default()
+                };
+                (tc, scaled_min_pos_x, scaled_min_pos_y, w * self.font_scale / dpi_factor, h * self.font_scale / dpi_factor)
             };
             
-            // give the callback a chance to do things
-            let marker = char_callback(*wc, char_offset, geom_x, advance);
-            
             let data = [
                 tc.tx1,
                 tc.ty1,
@@ -296,22 +423,28 @@ impl Text {
                 self.color.a,
                 scaled_min_pos_x,
                 scaled_min_pos_y,
-                w * self.font_scale / dpi_factor,
-                h * self.font_scale / dpi_factor,
-                self.z + 0.00001 * min_pos_x, //slight z-bias so we don't get z-fighting with neighbouring chars overlap a bit
+                w,
+                h,
+                self.z + 0.00001 * scaled_min_pos_x, //slight z-bias so we don't get z-fighting with neighbouring chars overlap a bit
                 geom_x,
                 geom_y,
                 text_style.font_size,
-                char_offset as f32, // char_offset
-                marker, // marker
+                (char_offset + shaped_glyph.char_index) as f32, // char_offset
+                markers[shaped_glyph.char_index], // marker
+                if shaped_glyph.is_rtl {1.0} else {0.0}, // rtl
+                if text_style.sdf {1.0} else {0.0}, // sdf
+                tc.texture_index as f32, // atlas_texture
             ];
             instance.extend_from_slice(&data);
             // !TODO make sure a derived shader adds 'empty' values here.
             
-            geom_x += advance;
-            char_offset += 1;
             aligned.inst.instance_count += 1;
         }
+        
+        self._shaped = shaped;
+        self._placed = placed;
+        self._char_geoms = char_geoms;
+        self._markers = markers;
     }
     
     pub fn end_text(&mut self, cx: &mut Cx, aligned: &AlignedInstance) -> Area {
@@ -329,29 +462,37 @@ impl Text {
         let font_size = text_style.font_size;
         let line_spacing = text_style.line_spacing;
         let height_factor = text_style.height_factor;
-        let mut iter = text.chars().peekable();
+        let chars: Vec<char> = text.chars().collect();
         
         let font_id = text_style.font.font_id;
-        let font_size_logical = text_style.font_size * 96.0 / (72.0 * cx.fonts[font_id].font_loaded.as_ref().unwrap().units_per_em);
+        let wrapping = self.wrapping.clone();
+        
+        // right to left text wraps in logical order first, then every line is drawn reordered
+        let bidi = match wrapping {
+            Wrapping::Char | Wrapping::Word => has_rtl(&chars),
+            _ => false
+        };
+        let mut para_start = 0;
+        let mut chunk_start = 0;
+        let mut line_words: Vec<(usize, usize, Rect)> = Vec::new();
         
-        while let Some(c) = iter.next() {
-            let last = iter.peek().is_none();
+        for (index, c) in chars.iter().cloned().enumerate() {
+            let last = index == chars.len() - 1;
             
             let mut emit = last;
             let mut newline = false;
-            let slot = if c < '\u{10000}' {
-                cx.fonts[font_id].font_loaded.as_ref().unwrap().char_code_to_glyph_index_map[c as usize]
-            } else {
-                0
-            };
+            let (slot_font_id, slot) = CxFont::find_glyph(&cx.fonts, font_id, c);
             if c == '\n' {
                 emit = true;
                 newline = true;
             }
             if slot != 0 {
-                let glyph = &cx.fonts[font_id].font_loaded.as_ref().unwrap().glyphs[slot];
+                // a running estimate for the ellipsis, the walk measures the shaped chunk
+                let font = cx.fonts[slot_font_id].font_loaded.as_ref().unwrap();
+                let font_size_logical = font_size * 96.0 / (72.0 * font.units_per_em);
+                let glyph = &font.glyphs[slot];
                 width += glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
-                match self.wrapping {
+                match wrapping {
                     Wrapping::Char => {
                         chunk.push(c);
                         emit = true
@@ -388,12 +529,24 @@ impl Text {
             if emit {
                 let height = font_size * height_factor * self.font_scale;
                 let geom = cx.walk_turtle(Walk {
-                    width: Width::Fix(width),
+                    width: Width::Fix(self.get_text_width(cx, &chunk)),
                     height: Height::Fix(height),
-                    margin: Margin::zero()
+                    margin: Margin::zero(),
+                    ..Walk::default()
                 });
                 
-                self.add_text(cx, geom.x, geom.y, 0, &mut aligned, &chunk, | _, _, _, _ | {0.0});
+                if bidi {
+                    line_words.push((chunk_start, index + 1, geom));
+                    if c == '\n' || last {
+                        self.draw_bidi_lines(cx, &mut aligned, &chars[para_start..index + 1], para_start, &line_words);
+                        line_words.truncate(0);
+                        para_start = index + 1;
+                    }
+                }
+                else {
+                    self.add_text(cx, geom.x, geom.y, 0, &mut aligned, &chunk, | _, _, _, _ | {0.0});
+                }
+                chunk_start = index + 1;
                 width = 0.0;
                 chunk.truncate(0);
                 if newline {
@@ -404,6 +557,72 @@ impl Text {
         self.end_text(cx, &aligned)
     }
     
+    // the words of a paragraph went through the turtle in logical order, the ones that ended up
+    // on the same row are drawn as one run so bidi can reorder them
+    fn draw_bidi_lines(&mut self, cx: &mut Cx, aligned: &mut AlignedInstance, para: &[char], para_start: usize, words: &[(usize, usize, Rect)]) {
+        let (para_level, levels) = bidi_levels(para);
+        let font_id = self.text_style.font.font_id;
+        let mut line_chars = Vec::new();
+        let mut line_levels = Vec::new();
+        let mut word = 0;
+        while word < words.len() {
+            let line_geom = words[word].2;
+            line_chars.truncate(0);
+            line_levels.truncate(0);
+            while word < words.len() && words[word].2.y == line_geom.y {
+                let (start, end, _) = words[word];
+                for i in start - para_start..end - para_start {
+                    if CxFont::find_glyph(&cx.fonts, font_id, para[i]).1 != 0 {
+                        line_chars.push(para[i]);
+                        line_levels.push(levels[i]);
+                    }
+                }
+                word += 1;
+            }
+            reset_line_end_levels(&line_chars, &mut line_levels, para_level);
+            self.add_text_levels(cx, line_geom.x, line_geom.y, 0, aligned, &line_chars, Some(&line_levels), | _, _, _, _ | {0.0});
+        }
+    }
+    
+    // the advance of a run of chars as add_text would lay it out
+    pub fn get_text_width(&self, cx: &Cx, chunk: &[char]) -> f32 {
+        let shaped = shape_chars(&cx.fonts, self.text_style.font.font_id, chunk, self.ligatures);
+        self.place_glyphs(cx, &shaped, 0.0, | _, _, _ | {})
+    }
+    
+    // walks shaped glyphs left to right from x with kerning between glyphs of the same font,
+    // calls back with every glyph, its x and its advance and returns where the run ends
+    fn place_glyphs<F>(&self, cx: &Cx, shaped: &[ShapedGlyph], x: f32, mut glyph_callback: F) -> f32
+    where F: FnMut(ShapedGlyph, f32, f32)
+    {
+        let mut x = x;
+        let mut last_glyph: Option<ShapedGlyph> = None;
+        for glyph in shaped.iter().cloned() {
+            let font = cx.fonts[glyph.font_id].font_loaded.as_ref().unwrap();
+            if glyph.glyph_id >= font.glyphs.len() {
+                continue;
+            }
+            let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
+            if let Some(last_glyph) = last_glyph {
+                if last_glyph.font_id == glyph.font_id {
+                    // kerning pairs are in logical order
+                    let kerning = if last_glyph.is_rtl && glyph.is_rtl {
+                        font.kerning(glyph.glyph_id, last_glyph.glyph_id)
+                    }
+                    else {
+                        font.kerning(last_glyph.glyph_id, glyph.glyph_id)
+                    };
+                    x += kerning * font_size_logical * self.font_scale;
+                }
+            }
+            last_glyph = Some(glyph);
+            let advance = font.glyphs[glyph.glyph_id].horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+            glyph_callback(glyph, x, advance);
+            x += advance;
+        }
+        x
+    }
+    
     // looks up text with the behavior of a text selection mouse cursor
     pub fn find_closest_offset(&self, cx: &Cx, area: &Area, pos: Vec2) -> usize {
         let scroll_pos = area.get_scroll_pos(cx);
@@ -413,6 +632,7 @@ impl Text {
         let w_o = area.get_instance_offset(cx, live_id!(self::shader::w), Ty::Float).unwrap();
         let font_size_o = area.get_instance_offset(cx, live_id!(self::shader::font_size), Ty::Float).unwrap();
         let char_offset_o = area.get_instance_offset(cx, live_id!(self::shader::char_offset), Ty::Float).unwrap();
+        let rtl_o = area.get_instance_offset(cx, live_id!(self::shader::rtl), Ty::Float).unwrap();
         let read = area.get_read_ref(cx);
         let text_style = &self.text_style;
         let line_spacing = text_style.line_spacing;
@@ -434,7 +654,12 @@ impl Text {
                             if index < read.count - 1 && prev_x > spos.x + prev_w { // fix newline jump-back
                                 return read.buffer[read.offset + char_offset_o + index * read.slots] as usize;
                             } 
-                            return read.buffer[read.offset + char_offset_o + prev_index * read.slots] as usize;
+                            let prev_char_offset = read.buffer[read.offset + char_offset_o + prev_index * read.slots] as usize;
+                            // instances are in visual order, the left half of a right to left glyph is after it
+                            if read.buffer[read.offset + rtl_o + prev_index * read.slots] > 0.5 {
+                                return prev_char_offset + 1
+                            }
+                            return prev_char_offset;
                         }
                         index += 1;
                     }
@@ -452,7 +677,7 @@ impl Text {
     pub fn get_monospace_base(&self, cx: &Cx) -> Vec2 {
         let font_id = self.text_style.font.font_id;
         let font = cx.fonts[font_id].font_loaded.as_ref().unwrap();
-        let slot = font.char_code_to_glyph_index_map[33];
+        let slot = font.glyph_index('!');
         let glyph = &font.glyphs[slot];
         
         //let font_size = if let Some(font_size) = font_size{font_size}else{self.font_size};
@@ -46,7 +46,7 @@ impl Cx {
         // abs origin overrides the computation of width/height to use the parent abs_origin
         let (width, min_width) = self.eval_width(&layout.walk.width, layout.walk.margin, is_abs_origin, abs_size.x);
         let (height, min_height) = self.eval_height(&layout.walk.height, layout.walk.margin, is_abs_origin, abs_size.y);
-
+        
         let turtle = Turtle {
             align_list_x: self.align_list.len(),
             align_list_y: self.align_list.len(),
@@ -64,18 +64,33 @@ impl Cx {
             height_used: 0.,
             abs_size: abs_size,
             guard_area: guard_area,
+            items: Vec::new(),
+            flex_line: 0,
+            items_align_start: self.align_list.len(),
             //..Default::default()
         };
         
+        if self.layout_debug.enabled {
+            let available = Vec2 {x: self._get_width_left(is_abs_origin, abs_size.x), y: self._get_height_left(is_abs_origin, abs_size.y)};
+            self.layout_debug_begin_turtle(&turtle, available);
+        }
         self.turtles.push(turtle);
     }
     
     pub fn end_turtle(&mut self, guard_area: Area) -> Rect {
-        let old = self.turtles.pop().unwrap();
+        let mut old = self.turtles.pop().unwrap();
         if guard_area != old.guard_area {
             panic!("End turtle guard area misaligned!, begin/end pair not matched begin {:?} end {:?}", old.guard_area, guard_area)
         }
         
+        old.close_last_item(self.align_list.len());
+        if old.layout.flex.is_some() {
+            self.arrange_flex(&mut old);
+        }
+        else if old.layout.grid.is_some() {
+            self.arrange_grid(&mut old);
+        }
+        
         let w = if old.width.is_nan() {
             if old.bound_right_bottom.x == std::f32::NEG_INFINITY { // nothing happened, use padding
                 Width::Fix(old.layout.padding.l + old.layout.padding.r)
@@ -100,15 +115,17 @@ impl Cx {
             Height::Fix(old.height)
         };
         
-        let margin = old.layout.walk.margin.clone();
         // if we have alignment set, we should now align our childnodes
-        let dx = Self::compute_align_turtle_x(&old);
-        if dx > 0.0 {
-            self.do_align_x(dx, old.align_list_x);
-        }
-        let dy = Self::compute_align_turtle_y(&old);
-        if dy > 0.0 {
-            self.do_align_y(dy, old.align_list_y);
+        // flex and grid turtles did their own alignment
+        if old.layout.flex.is_none() && old.layout.grid.is_none() {
+            let dx = Self::compute_align_turtle_x(&old);
+            if dx > 0.0 {
+                self.do_align_x(dx, old.align_list_x);
+            }
+            let dy = Self::compute_align_turtle_y(&old);
+            if dy > 0.0 {
+                self.do_align_y(dy, old.align_list_y);
+            }
         }
         
         // when a turtle is x-abs / y-abs you dont walk the parent
@@ -116,10 +133,14 @@ impl Cx {
             let abs_origin = if let Some(abs_origin) = old.layout.abs_origin {abs_origin} else {Vec2::default()};
             let w = if let Width::Fix(vw) = w {vw} else {0.};
             let h = if let Height::Fix(vh) = h {vh} else {0.};
-            return Rect {x: abs_origin.x, y: abs_origin.y, w: w, h: h};
+            let rect = Rect {x: abs_origin.x, y: abs_origin.y, w: w, h: h};
+            self.layout_debug_end_turtle(rect);
+            return rect;
         }
         
-        return self.walk_turtle_with_old(Walk {width: w, height: h, margin}, Some(&old))
+        let rect = self.walk_turtle_with_old(Walk {width: w, height: h, ..old.layout.walk}, Some(&old));
+        self.layout_debug_end_turtle(rect);
+        return rect
     }
     
     pub fn walk_turtle(&mut self, walk: Walk) -> Rect {
@@ -133,33 +154,49 @@ impl Cx {
         let (w,_mw) = self.eval_width(&walk.width, walk.margin, false, 0.0);
         let (h,_mh) = self.eval_height(&walk.height, walk.margin, false, 0.0);
         
+        // what this walk drew, for the arrange pass of a flex or grid parent. a child turtle drew
+        // before it walks, a leaf draws right after so its range stays open until the next walk
+        let align_len = self.align_list.len();
+        let debug_len = self.layout_debug.turtles.len();
+        let (align_range, debug_range) = if let Some(old_turtle) = old_turtle {
+            ((old_turtle.items_align_start, align_len), (*self.layout_debug.stack.last().unwrap_or(&debug_len), debug_len))
+        }
+        else {
+            ((align_len, std::usize::MAX), (debug_len, debug_len))
+        };
+        
         let ret = if let Some(turtle) = self.turtles.last_mut() {
             let (x, y) = match turtle.layout.direction {
                 Direction::Right => {
+                    let mut gap = turtle.item_gap();
                     match turtle.layout.line_wrap {
                         LineWrap::NewLine => {
-                            if (turtle.pos.x + walk.margin.l + w) >
+                            if (turtle.pos.x + gap + walk.margin.l + w) >
                             (turtle.origin.x + turtle.width - turtle.layout.padding.r)+ 0.01 {
                                 // what is the move delta.
                                 let old_x = turtle.pos.x;
                                 let old_y = turtle.pos.y;
                                 turtle.pos.x = turtle.origin.x + turtle.layout.padding.l;
-                                turtle.pos.y += turtle.biggest;
+                                turtle.pos.y += turtle.biggest + turtle.flex_gap();
                                 turtle.biggest = 0.0;
+                                turtle.flex_line += 1;
+                                gap = 0.;
                                 align_dx = turtle.pos.x - old_x;
                                 align_dy = turtle.pos.y - old_y;
                             }
                         },
                         LineWrap::MaxSize(max_size) => {
-                            let new_size = turtle.pos.x + walk.margin.l + w;
+                            let new_size = turtle.pos.x + gap + walk.margin.l + w;
                             if new_size > (turtle.origin.x + turtle.width - turtle.layout.padding.r)
                                 || new_size > (turtle.origin.x + max_size - turtle.layout.padding.r) {
                                 // what is the move delta.
                                 let old_x = turtle.pos.x;
                                 let old_y = turtle.pos.y;
                                 turtle.pos.x = turtle.origin.x + turtle.layout.padding.l;
-                                turtle.pos.y += turtle.biggest;
+                                turtle.pos.y += turtle.biggest + turtle.flex_gap();
                                 turtle.biggest = 0.0;
+                                turtle.flex_line += 1;
+                                gap = 0.;
                                 align_dx = turtle.pos.x - old_x;
                                 align_dy = turtle.pos.y - old_y;
                             }
@@ -168,6 +205,8 @@ impl Cx {
                         }
                     }
                     
+                    // the gap goes between items on a line, so there is none after the last one
+                    turtle.pos.x += gap;
                     let x = turtle.pos.x + walk.margin.l;
                     let y = turtle.pos.y + walk.margin.t;
                     // walk it normally
@@ -181,30 +220,35 @@ impl Cx {
                     (x, y)
                 },
                 Direction::Down => {
+                    let mut gap = turtle.item_gap();
                     match turtle.layout.line_wrap {
                         LineWrap::NewLine => {
-                            if (turtle.pos.y + walk.margin.t + h) >
+                            if (turtle.pos.y + gap + walk.margin.t + h) >
                             (turtle.origin.y + turtle.height - turtle.layout.padding.b) + 0.01 {
                                 // what is the move delta.
                                 let old_x = turtle.pos.x;
                                 let old_y = turtle.pos.y;
                                 turtle.pos.y = turtle.origin.y + turtle.layout.padding.t;
-                                turtle.pos.x += turtle.biggest;
+                                turtle.pos.x += turtle.biggest + turtle.flex_gap();
                                 turtle.biggest = 0.0;
+                                turtle.flex_line += 1;
+                                gap = 0.;
                                 align_dx = turtle.pos.x - old_x;
                                 align_dy = turtle.pos.y - old_y;
                             }
                         },
                         LineWrap::MaxSize(max_size) => {
-                            let new_size = turtle.pos.y + walk.margin.t + h;
+                            let new_size = turtle.pos.y + gap + walk.margin.t + h;
                             if new_size > (turtle.origin.y + turtle.height - turtle.layout.padding.b)
                                 || new_size > (turtle.origin.y + max_size - turtle.layout.padding.b) {
                                 // what is the move delta.
                                 let old_x = turtle.pos.x;
                                 let old_y = turtle.pos.y;
                                 turtle.pos.y = turtle.origin.y + turtle.layout.padding.t;
-                                turtle.pos.x += turtle.biggest;
+                                turtle.pos.x += turtle.biggest + turtle.flex_gap();
                                 turtle.biggest = 0.0;
+                                turtle.flex_line += 1;
+                                gap = 0.;
                                 align_dx = turtle.pos.x - old_x;
                                 align_dy = turtle.pos.y - old_y;
                             }
@@ -213,6 +257,7 @@ impl Cx {
                         }
                     }
                     
+                    turtle.pos.y += gap;
                     let x = turtle.pos.x + walk.margin.l;
                     let y = turtle.pos.y + walk.margin.t;
                     // walk it normally
@@ -246,6 +291,29 @@ impl Cx {
             if y < turtle.bound_left_top.y {
                 turtle.bound_left_top.y = y;
             }
+            
+            // measure pass of a flex or grid turtle, end_turtle arranges what we record here
+            if turtle.layout.flex.is_some() || turtle.layout.grid.is_some() {
+                turtle.close_last_item(align_range.0);
+                turtle.items.push(TurtleItem {
+                    rect: Rect {x: x, y: y, w: zero_if_nan(w), h: zero_if_nan(h)},
+                    margin: walk.margin,
+                    grow: walk.grow,
+                    shrink: walk.shrink,
+                    cell: walk.cell,
+                    align: if let Some(old_turtle) = old_turtle {old_turtle.layout.align} else {Align::default()},
+                    padding: if let Some(old_turtle) = old_turtle {old_turtle.layout.padding} else {Padding::zero()},
+                    sized: if let Some(old_turtle) = old_turtle {(!old_turtle.width.is_nan(), !old_turtle.height.is_nan())} else {(false, false)},
+                    fill: (walk.width.is_fill(), walk.height.is_fill()),
+                    line: turtle.flex_line,
+                    align_range: align_range,
+                    debug_range: debug_range
+                });
+            }
+            // grid children all measure against the whole content box, arrange_grid sizes them to their cell
+            if turtle.layout.grid.is_some() {
+                turtle.pos = Vec2 {x: turtle.origin.x + turtle.layout.padding.l, y: turtle.origin.y + turtle.layout.padding.t};
+            }
             // we could directly h or v align this thing
             
             Rect {
@@ -369,6 +437,7 @@ impl Cx {
     
     fn do_align_x(&mut self, dx: f32, align_start: usize) {
         let dx = (dx * self.current_dpi_factor).floor() / self.current_dpi_factor;
+        self.layout_debug_shift(align_start, dx, 0.);
         for i in align_start..self.align_list.len() {
             let align_item = &self.align_list[i];
             match align_item {
@@ -387,8 +456,305 @@ impl Cx {
         }
     }
     
+    // the rect of the first instance of an aligned area, as it was written by the draw
+    fn get_aligned_rect(&self, index: usize) -> Option<Rect> {
+        if let Area::Instance(inst) = &self.align_list[index] {
+            if inst.instance_count == 0 {
+                return None
+            }
+            let draw_call = &self.views[inst.view_id].draw_calls[inst.draw_call_id];
+            let rp = &self.shaders[draw_call.shader_id].mapping.rect_instance_props;
+            let read = | slot: Option<usize> | slot.map( | s | draw_call.instance[inst.instance_offset + s]);
+            return Some(Rect {
+                x: read(rp.x) ?,
+                y: read(rp.y) ?,
+                w: read(rp.w).unwrap_or(0.),
+                h: read(rp.h).unwrap_or(0.)
+            })
+        }
+        None
+    }
+    
+    fn move_aligned(&mut self, index: usize, dx: f32, dy: f32, dw: f32, dh: f32) {
+        let dx = (dx * self.current_dpi_factor).floor() / self.current_dpi_factor;
+        let dy = (dy * self.current_dpi_factor).floor() / self.current_dpi_factor;
+        if let Area::Instance(inst) = &self.align_list[index] {
+            let cxview = &mut self.views[inst.view_id];
+            let draw_call = &mut cxview.draw_calls[inst.draw_call_id];
+            let sh = &self.shaders[draw_call.shader_id];
+            let rp = &sh.mapping.rect_instance_props;
+            for i in 0..inst.instance_count {
+                let base = inst.instance_offset + i * sh.mapping.instance_props.total_slots;
+                for (slot, delta) in &[(rp.x, dx), (rp.y, dy), (rp.w, dw), (rp.h, dh)] {
+                    if let Some(slot) = slot {
+                        draw_call.instance[base + slot] += delta;
+                    }
+                }
+            }
+        }
+    }
+    
+    // the arrange pass of a flex turtle. its children are already drawn at their measured size,
+    // this hands out the free space per line and moves (or stretches) their instances into place
+    fn arrange_flex(&mut self, turtle: &mut Turtle) {
+        let flex = if let Some(flex) = turtle.layout.flex {flex} else {return};
+        if turtle.items.len() == 0 {
+            return
+        }
+        let is_row = if let Direction::Down = turtle.layout.direction {false} else {true};
+        let pad = turtle.layout.padding;
+        // main/cross instead of x/y so rows and columns share the code
+        let (main_origin, cross_origin, main_size, cross_size) = if is_row {
+            (turtle.origin.x + pad.l, turtle.origin.y + pad.t, turtle.width - pad.l - pad.r, turtle.height - pad.t - pad.b)
+        }
+        else {
+            (turtle.origin.y + pad.t, turtle.origin.x + pad.l, turtle.height - pad.t - pad.b, turtle.width - pad.l - pad.r)
+        };
+        let main = | item: &TurtleItem | if is_row {
+            (item.rect.x, item.rect.w, item.margin.l, item.margin.r)
+        } else {
+            (item.rect.y, item.rect.h, item.margin.t, item.margin.b)
+        };
+        let cross = | item: &TurtleItem | if is_row {
+            (item.rect.y, item.rect.h, item.margin.t, item.margin.b)
+        } else {
+            (item.rect.x, item.rect.w, item.margin.l, item.margin.r)
+        };
+        
+        // (dmain, dcross, dmain_size, dcross_size) for every item
+        let mut deltas = vec![(0f32, 0f32, 0f32, 0f32); turtle.items.len()];
+        let line_count = turtle.items.last().unwrap().line + 1;
+        for line in 0..line_count {
+            let indices: Vec<usize> = (0..turtle.items.len()).filter( | i | turtle.items[*i].line == line).collect();
+            if indices.len() == 0 {
+                continue;
+            }
+            let items: Vec<&TurtleItem> = indices.iter().map( | i | &turtle.items[*i]).collect();
+            let count = items.len() as f32;
+            
+            let mut sizes: Vec<f32> = items.iter().map( | item | main(item).1).collect();
+            let used = items.iter().map( | item | {let (_, s, m1, m2) = main(item); s + m1 + m2}).sum::<f32>() + flex.gap * (count - 1.);
+            // a computed main size has no free space to hand out
+            let mut free = if main_size.is_nan() {0.} else {main_size - used};
+            let total_grow: f32 = items.iter().map( | item | item.grow).sum();
+            let total_shrink: f32 = items.iter().map( | item | item.shrink * main(item).1).sum();
+            if free > 0. && total_grow > 0. {
+                for (k, item) in items.iter().enumerate() {
+                    sizes[k] += free * item.grow / total_grow;
+                }
+                free = 0.;
+            }
+            else if free < 0. && total_shrink > 0. {
+                // shrink in proportion to size like css does, so small items dont vanish first
+                for (k, item) in items.iter().enumerate() {
+                    sizes[k] = (sizes[k] + free * item.shrink * main(item).1 / total_shrink).max(0.);
+                }
+                free = 0.;
+            }
+            let free = free.max(0.);
+            let (mut pos, spacing) = match flex.justify {
+                Justify::Start => (0., 0.),
+                Justify::End => (free, 0.),
+                Justify::Center => (0.5 * free, 0.),
+                Justify::SpaceBetween => if count > 1. {(0., free / (count - 1.))} else {(0., 0.)},
+                Justify::SpaceAround => (0.5 * free / count, free / count),
+                Justify::SpaceEvenly => (free / (count + 1.), free / (count + 1.)),
+            };
+            
+            // a single line gets the whole cross size, wrapped lines are as big as their biggest item
+            let (line_start, line_size) = if line_count == 1 && !cross_size.is_nan() {
+                (cross_origin, cross_size)
+            }
+            else {
+                let start = items.iter().map( | item | {let (p, _, m1, _) = cross(item); p - m1}).fold(std::f32::INFINITY, f32::min);
+                let size = items.iter().map( | item | {let (_, s, m1, m2) = cross(item); s + m1 + m2}).fold(0., f32::max);
+                (start, size)
+            };
+            
+            for (k, item) in items.iter().enumerate() {
+                let (main_pos, main_item_size, m1, m2) = main(item);
+                let (cross_pos, cross_item_size, c1, c2) = cross(item);
+                let dmain = main_origin + pos + m1 - main_pos;
+                pos += m1 + sizes[k] + m2 + flex.gap + spacing;
+                let outer = cross_item_size + c1 + c2;
+                let (dcross, dcross_size) = match flex.cross_align {
+                    CrossAlign::Start => (line_start + c1 - cross_pos, 0.),
+                    CrossAlign::End => (line_start + line_size - outer + c1 - cross_pos, 0.),
+                    CrossAlign::Center => (line_start + 0.5 * (line_size - outer) + c1 - cross_pos, 0.),
+                    CrossAlign::Stretch => (line_start + c1 - cross_pos, (line_size - outer).max(0.)),
+                };
+                deltas[indices[k]] = (dmain, dcross, sizes[k] - main_item_size, dcross_size);
+            }
+        }
+        
+        let deltas: Vec<(f32, f32, f32, f32)> = deltas.into_iter().map( | (dmain, dcross, dmain_size, dcross_size) | {
+            if is_row {(dmain, dcross, dmain_size, dcross_size)} else {(dcross, dmain, dcross_size, dmain_size)}
+        }).collect();
+        
+        self.arrange_owned(turtle, &deltas);
+        
+        // and the turtle bounds follow the arranged items
+        turtle.bound_left_top = Vec2 {x: std::f32::INFINITY, y: std::f32::INFINITY};
+        turtle.bound_right_bottom = Vec2 {x: std::f32::NEG_INFINITY, y: std::f32::NEG_INFINITY};
+        for (item, (dx, dy, dw, dh)) in turtle.items.iter_mut().zip(deltas) {
+            item.rect = Rect {x: item.rect.x + dx, y: item.rect.y + dy, w: item.rect.w + dw, h: item.rect.h + dh};
+            turtle.bound_left_top.x = turtle.bound_left_top.x.min(item.rect.x);
+            turtle.bound_left_top.y = turtle.bound_left_top.y.min(item.rect.y);
+            turtle.bound_right_bottom.x = turtle.bound_right_bottom.x.max(item.rect.x + item.rect.w);
+            turtle.bound_right_bottom.y = turtle.bound_right_bottom.y.max(item.rect.y + item.rect.h);
+        }
+    }
+    
+    // moves and stretches what every item drew by its (dx, dy, dw, dh)
+    fn arrange_owned(&mut self, turtle: &Turtle, deltas: &[(f32, f32, f32, f32)]) {
+        let start = turtle.items_align_start;
+        let owners = get_item_owners(&turtle.items, start, self.align_list.len(), | item | item.align_range);
+        for (offset, owner) in owners.iter().enumerate() {
+            if let Some(owner) = owner {
+                let index = start + offset;
+                if let Some(r) = self.get_aligned_rect(index) {
+                    let nr = turtle.items[*owner].arranged_rect(r, deltas[*owner]);
+                    self.move_aligned(index, nr.x - r.x, nr.y - r.y, nr.w - r.w, nr.h - r.h);
+                }
+            }
+        }
+        self.layout_debug_arrange(&turtle.items, deltas);
+    }
+    
+    // sizes the tracks of a grid, fr tracks share what fixed and computed ones leave over
+    fn size_grid_tracks(tracks: &[GridTrack], count: usize, available: f32, gap: f32, content: &[f32]) -> Vec<f32> {
+        let track = | i: usize | if i < tracks.len() {tracks[i]} else {GridTrack::Compute};
+        let mut sizes: Vec<f32> = (0..count).map( | i | match track(i) {
+            GridTrack::Fix(v) => v,
+            GridTrack::Compute => content[i],
+            GridTrack::Fr(_) => 0.,
+        }).collect();
+        let total_fr: f32 = (0..count).map( | i | if let GridTrack::Fr(f) = track(i) {f} else {0.}).sum();
+        let used: f32 = sizes.iter().sum::<f32>() + gap * (count.max(1) - 1) as f32;
+        for i in 0..count {
+            if let GridTrack::Fr(f) = track(i) {
+                // a computed grid has no space to share, fr tracks size to content then
+                sizes[i] = if available.is_nan() {
+                    content[i]
+                }
+                else {
+                    ((available - used) * f / total_fr).max(0.)
+                };
+            }
+        }
+        sizes
+    }
+    
+    // the arrange pass of a grid turtle, the children were measured at the content origin
+    // and are now moved into their cells, fill ones sized to it
+    fn arrange_grid(&mut self, turtle: &mut Turtle) {
+        let grid = if let Some(grid) = turtle.layout.grid {grid} else {return};
+        if turtle.items.len() == 0 {
+            return
+        }
+        let columns = grid.columns.len.max(1);
+        
+        // place every item, explicit cells first so the ones without a cell
+        // take the next free spot row by row around them
+        let mut taken: Vec<Vec<bool >> = Vec::new();
+        let mut cells = vec![GridCell::default(); turtle.items.len()];
+        let take = | taken: &mut Vec<Vec<bool >>, cell: &GridCell | {
+            for row in cell.row..cell.row + cell.row_span {
+                while taken.len() <= row {
+                    taken.push(vec![false; columns]);
+                }
+                for column in cell.column..(cell.column + cell.column_span).min(columns) {
+                    taken[row][column] = true;
+                }
+            }
+        };
+        for (index, item) in turtle.items.iter().enumerate() {
+            if let Some(cell) = item.cell {
+                cells[index] = GridCell {column_span: cell.column_span.max(1), row_span: cell.row_span.max(1), ..cell};
+                take(&mut taken, &cells[index]);
+            }
+        }
+        let mut cursor = (0, 0);
+        for (index, item) in turtle.items.iter().enumerate() {
+            if item.cell.is_some() {
+                continue;
+            }
+            let (mut column, mut row) = cursor;
+            loop {
+                if column >= columns {
+                    column = 0;
+                    row += 1;
+                }
+                if taken.len() <= row || !taken[row][column] {
+                    break;
+                }
+                column += 1;
+            }
+            cursor = (column + 1, row);
+            cells[index] = GridCell::at(column, row);
+            take(&mut taken, &cells[index]);
+        }
+        let rows = taken.len().max(grid.rows.len);
+        
+        // computed tracks are as big as their biggest single span item, fill items take the size of their cell
+        let mut content_w = vec![0f32; columns];
+        let mut content_h = vec![0f32; rows];
+        for (item, cell) in turtle.items.iter().zip(&cells) {
+            if cell.column_span == 1 && cell.column < columns && !item.fill.0 {
+                content_w[cell.column] = content_w[cell.column].max(item.rect.w + item.margin.l + item.margin.r);
+            }
+            if cell.row_span == 1 && !item.fill.1 {
+                content_h[cell.row] = content_h[cell.row].max(item.rect.h + item.margin.t + item.margin.b);
+            }
+        }
+        let pad = turtle.layout.padding;
+        let widths = Self::size_grid_tracks(grid.columns.as_slice(), columns, turtle.width - pad.l - pad.r, grid.column_gap, &content_w);
+        let heights = Self::size_grid_tracks(grid.rows.as_slice(), rows, turtle.height - pad.t - pad.b, grid.row_gap, &content_h);
+        let track_start = | sizes: &Vec<f32>, gap: f32, index: usize | sizes[0..index].iter().sum::<f32>() + gap * index as f32;
+        let track_span = | sizes: &Vec<f32>, gap: f32, index: usize, span: usize | {
+            let end = (index + span).min(sizes.len());
+            if end <= index {
+                return 0.
+            }
+            sizes[index..end].iter().sum::<f32>() + gap * (end - index - 1) as f32
+        };
+        let origin = Vec2 {x: turtle.origin.x + pad.l, y: turtle.origin.y + pad.t};
+        
+        let deltas: Vec<(f32, f32, f32, f32)> = turtle.items.iter().zip(&cells).map( | (item, cell) | {
+            let align = cell.align.unwrap_or(grid.cell_align);
+            let column = cell.column.min(columns - 1);
+            let x = origin.x + track_start(&widths, grid.column_gap, column);
+            let y = origin.y + track_start(&heights, grid.row_gap, cell.row);
+            let w = track_span(&widths, grid.column_gap, column, cell.column_span);
+            let h = track_span(&heights, grid.row_gap, cell.row, cell.row_span);
+            let outer_w = item.rect.w + item.margin.l + item.margin.r;
+            let outer_h = item.rect.h + item.margin.t + item.margin.b;
+            let dw = if item.fill.0 {(w - outer_w).max(-item.rect.w)} else {0.};
+            let dh = if item.fill.1 {(h - outer_h).max(-item.rect.h)} else {0.};
+            (
+                x + item.margin.l + align.fx * (w - outer_w - dw) - item.rect.x,
+                y + item.margin.t + align.fy * (h - outer_h - dh) - item.rect.y,
+                dw,
+                dh
+            )
+        }).collect();
+        
+        self.arrange_owned(turtle, &deltas);
+        
+        // the grid is as big as its tracks
+        turtle.bound_left_top = origin;
+        turtle.bound_right_bottom = Vec2 {
+            x: origin.x + track_start(&widths, grid.column_gap, columns) - grid.column_gap,
+            y: origin.y + track_start(&heights, grid.row_gap, rows) - grid.row_gap
+        };
+        for (item, (dx, dy, dw, dh)) in turtle.items.iter_mut().zip(deltas) {
+            item.rect = Rect {x: item.rect.x + dx, y: item.rect.y + dy, w: item.rect.w + dw, h: item.rect.h + dh};
+        }
+    }
+    
     fn do_align_y(&mut self, dy: f32, align_start: usize) {
         let dy = (dy * self.current_dpi_factor).floor() / self.current_dpi_factor;
+        self.layout_debug_shift(align_start, 0., dy);
         for i in align_start..self.align_list.len() {
             let align_item = &self.align_list[i];
             match align_item {
@@ -761,7 +1127,7 @@ impl Cx {
             Width::ScalePad(s, p) => (max_zero_keep_nan(self._get_width_total(abs, abs_pos) * s - p - (margin.l + margin.r)),0.),
         }
     }
-
+    
     pub fn eval_height(&self, height: &Height, margin: Margin, abs: bool, abs_pos: f32) -> (f32,f32) {
         match height {
             Height::Compute => (std::f32::NAN,0.),
@@ -777,6 +1143,15 @@ impl Cx {
     }
 }
 
+fn zero_if_nan(v: f32) -> f32 {
+    if v.is_nan() {
+        0.
+    }
+    else {
+        v
+    }
+}
+
 fn max_zero_keep_nan(v: f32) -> f32 {
     if v.is_nan() {
         v
@@ -794,8 +1169,8 @@ pub fn debug_pt(x:f32, y:f32, color:i32, s:&str){
         store.push((x,y,color,s.to_string()));
     })
 }
-
-
+        
+        
         debug_pts_store.with(|c|{
             let mut store = c.borrow_mut();
             for (x,y,col,s) in store.iter(){
@@ -832,5 +1207,233 @@ pub struct Turtle {
     pub height_used: f32,
     pub biggest: f32,
     pub layout: Layout,
-    pub guard_area: Area
+    pub guard_area: Area,
+    pub items: Vec<TurtleItem>,
+    pub flex_line: usize,
+    pub items_align_start: usize,
+}
+
+impl Turtle {
+    fn flex_gap(&self) -> f32 {
+        if let Some(flex) = self.layout.flex {flex.gap} else {0.}
+    }
+    
+    // the gap before the next item, only when there is one before it on the same line
+    fn item_gap(&self) -> f32 {
+        match self.items.last() {
+            Some(item) if item.line == self.flex_line => self.flex_gap(),
+            _ => 0.
+        }
+    }
+    
+    fn close_last_item(&mut self, align_end: usize) {
+        if let Some(item) = self.items.last_mut() {
+            if item.align_range.1 == std::usize::MAX {
+                item.align_range.1 = align_end.max(item.align_range.0);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct TurtleItem {
+    pub rect: Rect,
+    pub margin: Margin,
+    pub grow: f32,
+    pub shrink: f32,
+    pub cell: Option<GridCell>,
+    pub align: Align,
+    pub padding: Padding,
+    pub sized: (bool, bool),
+    pub fill: (bool, bool),
+    pub line: usize,
+    pub align_range: (usize, usize),
+    pub debug_range: (usize, usize),
+}
+
+impl TurtleItem {
+    // where something the item drew ends up when the item moves and resizes by (dx, dy, dw, dh)
+    pub fn arranged_rect(&self, r: Rect, delta: (f32, f32, f32, f32)) -> Rect {
+        let (dx, dy, dw, dh) = delta;
+        let (x, w) = arrange_span(
+            (r.x, r.w),
+            (self.rect.x, self.rect.w),
+            (self.padding.l, self.padding.r),
+            self.sized.0,
+            self.align.fx,
+            (dx, dw)
+        );
+        let (y, h) = arrange_span(
+            (r.y, r.h),
+            (self.rect.y, self.rect.h),
+            (self.padding.t, self.padding.b),
+            self.sized.1,
+            self.align.fy,
+            (dy, dh)
+        );
+        Rect {x: x, y: y, w: w, h: h}
+    }
+}
+
+// one axis of arranged_rect. what spans the item (its background) stretches with it, and so
+// does what fills it: in an item that had a size to fill only fill children reach its inner end.
+// the rest keeps its place according to the items align
+fn arrange_span(span: (f32, f32), item: (f32, f32), padding: (f32, f32), sized: bool, align: f32, delta: (f32, f32)) -> (f32, f32) {
+    let (pos, size) = span;
+    let (item_pos, item_size) = item;
+    let (d, dsize) = delta;
+    let near = | a: f32, b: f32 | (a - b).abs() < 0.5;
+    let end = pos + size;
+    let item_end = item_pos + item_size;
+    let spans = near(pos, item_pos) && near(end, item_end);
+    let fills = sized && near(end, item_end - padding.1) && (align == 0. || near(pos, item_pos + padding.0));
+    if spans || fills {
+        (pos + d, size + dsize)
+    }
+    else {
+        (pos + d + dsize * align, size)
+    }
+}
+
+// every item owns what was drawn in its range, see walk_turtle_with_old
+pub(crate) fn get_item_owners(items: &[TurtleItem], start: usize, end: usize, range: fn(&TurtleItem) -> (usize, usize)) -> Vec<Option<usize >> {
+    let mut owners = vec![None; end.max(start) - start];
+    for (index, item) in items.iter().enumerate() {
+        let (item_start, item_end) = range(item);
+        for i in item_start.max(start)..item_end.min(end) {
+            owners[i - start] = Some(index);
+        }
+    }
+    owners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn flex_cx(layout: Layout) -> Cx {
+        let mut cx = Cx::default();
+        cx.is_in_redraw_cycle = true;
+        cx.begin_turtle(layout, Area::Empty);
+        cx
+    }
+    
+    fn row(width: Width, flex: Flex) -> Layout {
+        Layout {
+            flex: Some(flex),
+            walk: Walk::wh(width, Height::Fix(20.)),
+            ..Layout::default()
+        }
+    }
+    
+    fn grid(columns: &[GridTrack]) -> Layout {
+        Layout {
+            grid: Some(Grid {columns: GridTracks::new(columns), column_gap: 10., ..Grid::default()}),
+            walk: Walk::wh(Width::Fix(310.), Height::Compute),
+            ..Layout::default()
+        }
+    }
+    
+    fn arrange(cx: &mut Cx) -> Vec<Rect> {
+        let mut turtle = cx.turtles.pop().unwrap();
+        turtle.close_last_item(cx.align_list.len());
+        cx.arrange_flex(&mut turtle);
+        cx.arrange_grid(&mut turtle);
+        turtle.items.iter().map( | item | item.rect).collect()
+    }
+    
+    #[test]
+    fn gap_is_only_between_items() {
+        let mut cx = flex_cx(row(Width::Compute, Flex {gap: 10., ..Flex::default()}));
+        for _ in 0..3 {
+            cx.walk_turtle(Walk::wh(Width::Fix(50.), Height::Fix(20.)));
+        }
+        let rect = cx.end_turtle(Area::Empty);
+        assert_eq!(rect.w, 170.);
+    }
+    
+    #[test]
+    fn wrapped_lines_start_without_a_gap() {
+        let mut cx = flex_cx(Layout {line_wrap: LineWrap::NewLine, ..row(Width::Fix(120.), Flex {gap: 10., ..Flex::default()})});
+        for _ in 0..3 {
+            cx.walk_turtle(Walk::wh(Width::Fix(50.), Height::Fix(20.)));
+        }
+        let rects = arrange(&mut cx);
+        assert_eq!((rects[1].x, rects[1].y), (60., 0.));
+        assert_eq!((rects[2].x, rects[2].y), (0., 30.));
+    }
+    
+    #[test]
+    fn grow_hands_out_the_free_space() {
+        let mut cx = flex_cx(row(Width::Fix(300.), Flex {gap: 10., ..Flex::default()}));
+        cx.walk_turtle(Walk {grow: 1., ..Walk::wh(Width::Fix(50.), Height::Fix(20.))});
+        cx.walk_turtle(Walk::wh(Width::Fix(50.), Height::Fix(20.)));
+        let rects = arrange(&mut cx);
+        assert_eq!((rects[0].x, rects[0].w), (0., 240.));
+        assert_eq!((rects[1].x, rects[1].w), (250., 50.));
+    }
+    
+    #[test]
+    fn items_own_what_they_drew() {
+        let mut cx = flex_cx(row(Width::Fix(300.), Flex::default()));
+        // a leaf draws after its walk
+        cx.walk_turtle(Walk::wh(Width::Fix(50.), Height::Fix(20.)));
+        cx.align_list.push(Area::Empty);
+        cx.align_list.push(Area::Empty);
+        // a child turtle before it
+        cx.begin_turtle(Layout {walk: Walk::wh(Width::Fix(50.), Height::Fix(20.)), ..Layout::default()}, Area::Empty);
+        cx.align_list.push(Area::Empty);
+        cx.end_turtle(Area::Empty);
+        cx.walk_turtle(Walk::wh(Width::Fix(50.), Height::Fix(20.)));
+        let mut turtle = cx.turtles.pop().unwrap();
+        turtle.close_last_item(cx.align_list.len());
+        let ranges: Vec<(usize, usize)> = turtle.items.iter().map( | item | item.align_range).collect();
+        assert_eq!(ranges, vec![(0, 2), (2, 3), (3, 3)]);
+        let owners = get_item_owners(&turtle.items, 0, cx.align_list.len(), | item | item.align_range);
+        assert_eq!(owners, vec![Some(0), Some(0), Some(1)]);
+    }
+    
+    #[test]
+    fn background_and_fill_children_stretch_with_their_item() {
+        let item = TurtleItem {
+            rect: Rect {x: 0., y: 0., w: 100., h: 20.},
+            padding: Padding {l: 5., t: 0., r: 5., b: 0.},
+            sized: (true, true),
+            ..TurtleItem::default()
+        };
+        let delta = (10., 0., 40., 0.);
+        // the background
+        assert_eq!(item.arranged_rect(Rect {x: 0., y: 0., w: 100., h: 20.}, delta), Rect {x: 10., y: 0., w: 140., h: 20.});
+        // a fill child after a fixed one
+        assert_eq!(item.arranged_rect(Rect {x: 30., y: 0., w: 65., h: 20.}, delta), Rect {x: 40., y: 0., w: 105., h: 20.});
+        // the fixed one just moves
+        assert_eq!(item.arranged_rect(Rect {x: 5., y: 0., w: 25., h: 20.}, delta), Rect {x: 15., y: 0., w: 25., h: 20.});
+        // and when the item was computed nothing in it filled
+        let computed = TurtleItem {sized: (false, false), ..item.clone()};
+        assert_eq!(computed.arranged_rect(Rect {x: 30., y: 0., w: 65., h: 20.}, delta), Rect {x: 40., y: 0., w: 65., h: 20.});
+    }
+    
+    #[test]
+    fn explicit_cells_are_placed_before_auto_ones() {
+        let mut cx = flex_cx(grid(&[GridTrack::Fix(50.), GridTrack::Fix(50.)]));
+        cx.walk_turtle(Walk::wh(Width::Fix(20.), Height::Fix(20.)));
+        cx.walk_turtle(Walk {cell: Some(GridCell::at(0, 0)), ..Walk::wh(Width::Fix(20.), Height::Fix(20.))});
+        cx.walk_turtle(Walk::wh(Width::Fix(20.), Height::Fix(20.)));
+        let rects = arrange(&mut cx);
+        assert_eq!((rects[0].x, rects[0].y), (60., 0.));
+        assert_eq!((rects[1].x, rects[1].y), (0., 0.));
+        assert_eq!((rects[2].x, rects[2].y), (0., 20.));
+    }
+    
+    #[test]
+    fn fill_children_take_the_size_of_their_cell() {
+        let mut cx = flex_cx(grid(&[GridTrack::Fix(100.), GridTrack::Fr(1.)]));
+        cx.walk_turtle(Walk::wh(Width::Fill, Height::Fix(20.)));
+        cx.walk_turtle(Walk::wh(Width::Fill, Height::Fix(20.)));
+        cx.walk_turtle(Walk::wh(Width::Fix(20.), Height::Fix(20.)));
+        let rects = arrange(&mut cx);
+        assert_eq!((rects[0].x, rects[0].w), (0., 100.));
+        assert_eq!((rects[1].x, rects[1].w), (110., 200.));
+        assert_eq!((rects[2].x, rects[2].w), (0., 20.));
+    }
 }
@@ -153,7 +153,7 @@ impl View {
             // walk the turtle because we aren't drawing
             let w = Width::Fix(cx.views[view_id].rect.w);
             let h = Height::Fix(cx.views[view_id].rect.h);
-            cx.walk_turtle(Walk {width: w, height: h, margin: override_layout.walk.margin});
+            cx.walk_turtle(Walk {width: w, height: h, ..override_layout.walk});
             return Err(());
         }
         
@@ -166,6 +166,9 @@ impl View {
         cxview.draw_calls_len = 0;
         
         cx.view_stack.push(view_id);
+        cx.layout_debug_begin_view(view_id);
+        cx.access_begin_view(view_id);
+        cx.focus_begin_view(view_id);
         
         let old_area = Area::View(ViewArea {view_id: view_id, redraw_id: last_redraw_id});
         let new_area = Area::View(ViewArea {view_id: view_id, redraw_id: cx.redraw_id});
@@ -367,6 +370,17 @@ impl Cx {
         }
     }
     
+    // a view and every view drawn into it, which is what is on screen for a pass
+    pub(crate) fn collect_visible_views(&self, view_id: usize, out: &mut Vec<usize>) {
+        out.push(view_id);
+        let cxview = &self.views[view_id];
+        for draw_call in &cxview.draw_calls[0..cxview.draw_calls_len] {
+            if draw_call.sub_view_id != 0 && draw_call.sub_view_id != view_id && !out.contains(&draw_call.sub_view_id) {
+                self.collect_visible_views(draw_call.sub_view_id, out);
+            }
+        }
+    }
+    
     pub fn set_view_scroll_x(&mut self, view_id: usize, scroll_pos: f32) {
         let fac = self.get_delegated_dpi_factor(self.views[view_id].pass_id);
         let cxview = &mut self.views[view_id];
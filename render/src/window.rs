@@ -32,6 +32,7 @@ impl Window {
                     },
                     position: self.create_position,
                 },
+                title: self.create_title.clone(),
                 ..Default::default()
             };
             let window_id;
@@ -239,6 +240,7 @@ pub struct CxWindow {
     pub window_topmost: Option<bool>,
     pub window_geom: WindowGeom,
     pub main_pass_id: Option<usize>,
+    pub title: String,
 }
 
 impl CxWindow {
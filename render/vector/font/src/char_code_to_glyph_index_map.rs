@@ -0,0 +1,141 @@
+use std::cmp::Ordering;
+
+/// A sparse map from char codes to glyph indices, stored as sorted ranges of char codes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CharCodeToGlyphIndexMap {
+    segments: Vec<CharCodeSegment>,
+}
+
+/// A range of char codes that either map to consecutive glyphs, or all to the same glyph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CharCodeSegment {
+    pub start_char_code: u32,
+    pub end_char_code: u32,
+    pub start_glyph_index: usize,
+    pub is_constant: bool,
+}
+
+impl CharCodeToGlyphIndexMap {
+    pub fn new() -> CharCodeToGlyphIndexMap {
+        CharCodeToGlyphIndexMap::default()
+    }
+
+    /// Maps a single char code, extending the last segment where possible. Char codes are
+    /// expected in ascending order.
+    pub fn insert(&mut self, char_code: u32, glyph_index: usize) {
+        if let Some(last) = self.segments.last_mut() {
+            if !last.is_constant
+                && last.end_char_code + 1 == char_code
+                && last.start_glyph_index + (char_code - last.start_char_code) as usize
+                    == glyph_index
+            {
+                last.end_char_code = char_code;
+                return;
+            }
+        }
+        self.insert_segment(CharCodeSegment {
+            start_char_code: char_code,
+            end_char_code: char_code,
+            start_glyph_index: glyph_index,
+            is_constant: false,
+        });
+    }
+
+    /// Maps a range of char codes. Segments are expected in ascending order and not to overlap.
+    pub fn insert_segment(&mut self, segment: CharCodeSegment) {
+        // get does a binary search, which only finds segments that are sorted and apart
+        debug_assert!(segment.start_char_code <= segment.end_char_code);
+        debug_assert!(self
+            .segments
+            .last()
+            .map_or(true, |last| last.end_char_code < segment.start_char_code));
+        self.segments.push(segment);
+    }
+
+    /// Returns the glyph index for the given char code, or 0 (the missing glyph) if it is not
+    /// mapped.
+    pub fn get(&self, char_code: u32) -> usize {
+        match self.segments.binary_search_by(|segment| {
+            if segment.end_char_code < char_code {
+                Ordering::Less
+            } else if segment.start_char_code > char_code {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        }) {
+            Ok(index) => {
+                let segment = &self.segments[index];
+                if segment.is_constant {
+                    segment.start_glyph_index
+                } else {
+                    segment.start_glyph_index + (char_code - segment.start_char_code) as usize
+                }
+            }
+            Err(_) => 0,
+        }
+    }
+
+    pub fn segments(&self) -> &[CharCodeSegment] {
+        &self.segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consecutive_glyphs_are_merged_into_one_segment() {
+        let mut map = CharCodeToGlyphIndexMap::new();
+        map.insert(65, 1);
+        map.insert(66, 2);
+        map.insert(67, 3);
+        // a gap in the char codes or in the glyphs starts a new segment
+        map.insert(69, 4);
+        map.insert(70, 10);
+        assert_eq!(map.segments().len(), 3);
+        assert_eq!(map.segments()[0].end_char_code, 67);
+        assert_eq!(map.get(65), 1);
+        assert_eq!(map.get(67), 3);
+        assert_eq!(map.get(69), 4);
+        assert_eq!(map.get(70), 10);
+    }
+
+    #[test]
+    fn constant_segments_map_every_char_code_to_one_glyph() {
+        let mut map = CharCodeToGlyphIndexMap::new();
+        map.insert_segment(CharCodeSegment {
+            start_char_code: 0x4E00,
+            end_char_code: 0x9FFF,
+            start_glyph_index: 7,
+            is_constant: true,
+        });
+        // a constant segment is never extended
+        map.insert(0xA000, 8);
+        assert_eq!(map.segments().len(), 2);
+        assert_eq!(map.get(0x4E00), 7);
+        assert_eq!(map.get(0x9FFF), 7);
+        assert_eq!(map.get(0xA000), 8);
+    }
+
+    #[test]
+    fn misses_are_the_missing_glyph() {
+        let mut map = CharCodeToGlyphIndexMap::new();
+        assert_eq!(map.get(65), 0);
+        map.insert(65, 1);
+        map.insert(67, 3);
+        assert_eq!(map.get(0), 0);
+        assert_eq!(map.get(66), 0);
+        assert_eq!(map.get(0x10FFFF), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn overlapping_segments_are_rejected() {
+        let mut map = CharCodeToGlyphIndexMap::new();
+        map.insert(65, 1);
+        map.insert(65, 2);
+    }
+}
@@ -1,5 +1,6 @@
-use crate::Glyph;
+use crate::{CharCodeToGlyphIndexMap, Glyph, KerningClasses, Ligature};
 use makepad_geometry::Rectangle;
+use std::collections::HashMap;
 
 /// A font.
 #[derive(Clone, Debug, PartialEq)]
@@ -9,6 +10,130 @@ pub struct Font {
     pub descender: f32,
     pub line_gap: f32,
     pub bounds: Rectangle,
-    pub char_code_to_glyph_index_map: Vec<usize>,
+    pub char_code_to_glyph_index_map: CharCodeToGlyphIndexMap,
     pub glyphs: Vec<Glyph>,
+    pub kerning_pairs: HashMap<(usize, usize), f32>,
+    pub kerning_classes: Vec<KerningClasses>,
+    /// Ligatures keyed by the first glyph of their sequence, longest first.
+    pub ligatures: HashMap<usize, Vec<Ligature>>,
+    /// Single glyph substitutions keyed by feature tag, such as the Arabic positional forms.
+    pub single_substitutions: HashMap<[u8; 4], HashMap<usize, usize>>,
+}
+
+impl Font {
+    /// Returns the glyph index for the given char, or 0 (the missing glyph) if the font has no
+    /// glyph for it.
+    pub fn glyph_index(&self, c: char) -> usize {
+        self.char_code_to_glyph_index_map.get(c as u32)
+    }
+
+    /// Returns the horizontal adjustment in font units to apply between the given pair of glyphs.
+    pub fn kerning(&self, left_glyph_index: usize, right_glyph_index: usize) -> f32 {
+        if let Some(value) = self
+            .kerning_pairs
+            .get(&(left_glyph_index, right_glyph_index))
+        {
+            return *value;
+        }
+        for classes in &self.kerning_classes {
+            if let Some(value) = classes.get(left_glyph_index, right_glyph_index) {
+                return value;
+            }
+        }
+        0.0
+    }
+
+    /// Returns the glyph that replaces the given glyph under the given feature, or the glyph
+    /// itself if the feature doesn't replace it.
+    pub fn substitute(&self, feature_tag: &[u8; 4], glyph_index: usize) -> usize {
+        self.single_substitutions
+            .get(feature_tag)
+            .and_then(|substitutions| substitutions.get(&glyph_index))
+            .cloned()
+            .unwrap_or(glyph_index)
+    }
+
+    /// Returns the longest ligature that matches the start of the given sequence of glyphs.
+    pub fn ligature(&self, glyph_indices: &[usize]) -> Option<&Ligature> {
+        let (first, rest) = glyph_indices.split_first()?;
+        self.ligatures.get(first)?.iter().find(|ligature| {
+            rest.len() >= ligature.components.len()
+                && ligature.components[..] == rest[..ligature.components.len()]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font() -> Font {
+        Font {
+            units_per_em: 1000.0,
+            ascender: 800.0,
+            descender: -200.0,
+            line_gap: 0.0,
+            bounds: Rectangle::default(),
+            char_code_to_glyph_index_map: CharCodeToGlyphIndexMap::new(),
+            glyphs: Vec::new(),
+            kerning_pairs: HashMap::new(),
+            kerning_classes: Vec::new(),
+            ligatures: HashMap::new(),
+            single_substitutions: HashMap::new(),
+        }
+    }
+
+    fn ligature(components: &[usize], glyph_index: usize) -> Ligature {
+        Ligature {
+            components: components.to_vec(),
+            glyph_index,
+        }
+    }
+
+    #[test]
+    fn kerning_pairs_come_before_classes() {
+        let mut font = font();
+        font.kerning_pairs.insert((1, 2), -50.0);
+        // first class 1 is glyphs 1 and 3, second class 1 is glyph 2, the rest is class 0
+        font.kerning_classes.push(KerningClasses {
+            first_classes: [(1, 1), (3, 1)].iter().cloned().collect(),
+            second_classes: [(2, 1)].iter().cloned().collect(),
+            second_class_count: 2,
+            values: vec![0.0, 0.0, -5.0, -20.0],
+        });
+        font.kerning_classes.push(KerningClasses {
+            first_classes: [(3, 0), (4, 0)].iter().cloned().collect(),
+            second_classes: HashMap::new(),
+            second_class_count: 1,
+            values: vec![-30.0],
+        });
+        assert_eq!(font.kerning(1, 2), -50.0);
+        assert_eq!(font.kerning(3, 2), -20.0);
+        assert_eq!(font.kerning(3, 5), -5.0);
+        // glyph 4 is only covered by the second table
+        assert_eq!(font.kerning(4, 2), -30.0);
+        assert_eq!(font.kerning(5, 2), 0.0);
+    }
+
+    #[test]
+    fn the_longest_ligature_wins() {
+        let mut font = font();
+        // f is 1, i is 2, l is 3, sorted longest first like the parser does
+        font.ligatures.insert(
+            1,
+            vec![
+                ligature(&[1, 2], 10),
+                ligature(&[2], 11),
+                ligature(&[1], 12),
+            ],
+        );
+        assert_eq!(font.ligature(&[1, 1, 2]).unwrap().glyph_index, 10);
+        assert_eq!(font.ligature(&[1, 2, 1]).unwrap().glyph_index, 11);
+        // the ffi ligature is longer than the glyphs left
+        assert_eq!(font.ligature(&[1, 1]).unwrap().glyph_index, 12);
+        assert_eq!(font.ligature(&[1, 3]), None);
+        assert_eq!(font.ligature(&[1]), None);
+        assert_eq!(font.ligature(&[2, 2]), None);
+        assert_eq!(font.ligature(&[]), None);
+    }
 }
@@ -0,0 +1,22 @@
+use std::collections::HashMap;
+
+/// Class based kerning, as found in a GPOS pair adjustment subtable (format 2).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KerningClasses {
+    pub first_classes: HashMap<usize, usize>,
+    pub second_classes: HashMap<usize, usize>,
+    pub second_class_count: usize,
+    pub values: Vec<f32>,
+}
+
+impl KerningClasses {
+    /// Returns the kerning for the given pair of glyphs, or `None` if the first glyph is not
+    /// covered by this table.
+    pub fn get(&self, left_glyph_index: usize, right_glyph_index: usize) -> Option<f32> {
+        let first_class = *self.first_classes.get(&left_glyph_index)?;
+        let second_class = *self.second_classes.get(&right_glyph_index).unwrap_or(&0);
+        self.values
+            .get(first_class * self.second_class_count + second_class)
+            .cloned()
+    }
+}
@@ -1,12 +1,18 @@
 pub mod outline;
 
+mod char_code_to_glyph_index_map;
 mod font;
 mod glyph;
 mod horizontal_metrics;
+mod kerning_classes;
+mod ligature;
 mod outline_point;
 
+pub use self::char_code_to_glyph_index_map::{CharCodeSegment, CharCodeToGlyphIndexMap};
 pub use self::font::Font;
 pub use self::glyph::Glyph;
 pub use self::horizontal_metrics::HorizontalMetrics;
+pub use self::kerning_classes::KerningClasses;
+pub use self::ligature::Ligature;
 pub use self::outline::Outline;
 pub use self::outline_point::OutlinePoint;
@@ -0,0 +1,7 @@
+/// A ligature that replaces a sequence of glyphs with a single glyph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ligature {
+    /// The glyphs following the first glyph of the sequence.
+    pub components: Vec<usize>,
+    pub glyph_index: usize,
+}
@@ -144,6 +144,12 @@ impl<'a> InternalIterator for Commands<'a> {
         // Bezier curve segments, we need to insert a new endpoint at the midpoint of each pair
         // of adjacent off curve points.
         for contour in self.contours {
+            if contour.points().iter().any(|point| point.is_cubic) {
+                if !cubic_contour_commands(contour, f) {
+                    return false;
+                }
+                continue;
+            }
             // The off curve point we encountered before the first on curve point, if it exists.
             let mut first_off_curve_point: Option<Point> = None;
             // The first on curve point we encountered.
@@ -239,6 +245,44 @@ impl<'a> InternalIterator for Commands<'a> {
     }
 }
 
+// Cubic contours start with an on curve point, and have two control points between each pair of
+// on curve points.
+fn cubic_contour_commands<F>(contour: Contour, f: &mut F) -> bool
+where
+    F: FnMut(PathCommand) -> bool,
+{
+    let points = contour.points();
+    let first_point = match points.first() {
+        Some(point) if point.is_on_curve => point.point,
+        _ => return true,
+    };
+    if !f(PathCommand::MoveTo(first_point)) {
+        return false;
+    }
+    let mut control_points = Vec::new();
+    for point in points[1..].iter().cloned().chain(Some(OutlinePoint {
+        is_on_curve: true,
+        is_cubic: false,
+        point: first_point,
+    })) {
+        if !point.is_on_curve {
+            control_points.push(point.point);
+            continue;
+        }
+        let command = match control_points[..] {
+            [] => PathCommand::LineTo(point.point),
+            [p1] => PathCommand::QuadraticTo(p1, point.point),
+            [p1, p2] => PathCommand::CubicTo(p1, p2, point.point),
+            _ => PathCommand::LineTo(point.point),
+        };
+        control_points.clear();
+        if !f(command) {
+            return false;
+        }
+    }
+    f(PathCommand::Close)
+}
+
 #[derive(Debug)]
 pub struct ContourBuilder<'a> {
     contour_ends: &'a mut Vec<usize>,
@@ -6,9 +6,13 @@ use makepad_geometry::{Point, Transform, Transformation};
 /// an endpoint of a quadratic b-spline curve segment. Otherwise, it represents a control point of
 /// a quadratic b-spline curve segment. Each quadratic b-spline curve segment has two endpoints and
 /// zero or more control points.
+///
+/// Outlines from CFF fonts use cubic Bezier curve segments instead. Their off curve points are
+/// marked as cubic, and come in pairs between two on curve points.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct OutlinePoint {
     pub is_on_curve: bool,
+    pub is_cubic: bool,
     pub point: Point,
 }
 
@@ -19,6 +23,7 @@ impl Transform for OutlinePoint {
     {
         OutlinePoint {
             is_on_curve: self.is_on_curve,
+            is_cubic: self.is_cubic,
             point: self.point.transform(t),
         }
     }
@@ -1,3 +1,4 @@
+pub mod cubic_segment;
 pub mod quadratic_segment;
 
 mod affine_transformation;
@@ -12,6 +13,7 @@ mod trapezoid;
 mod vector;
 
 pub use self::affine_transformation::AffineTransformation;
+pub use self::cubic_segment::CubicSegment;
 pub use self::f32_ext::F32Ext;
 pub use self::line_segment::LineSegment;
 pub use self::linear_transformation::LinearTransformation;
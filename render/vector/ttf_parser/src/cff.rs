@@ -0,0 +1,845 @@
+//! A parser for the outlines in a CFF table, as found in OpenType fonts with PostScript outlines.
+//!
+//! Glyph outlines are stored as Type 2 charstrings, small stack based programs that draw the
+//! glyph with lines and cubic Bezier curves. Hints are skipped, we don't use them.
+
+use crate::{parse_horizontal_metrics, Error, Reader, Result};
+use makepad_font::{Glyph, Outline, OutlinePoint};
+use makepad_geometry::{Point, Rectangle};
+
+// Nested subroutine calls are limited to this depth by the spec.
+const MAX_SUBR_DEPTH: usize = 10;
+
+pub(crate) fn parse_cff_glyphs(
+    bytes: &[u8],
+    glyph_count: usize,
+    advance_width_count: usize,
+    hmtx_table_bytes: &[u8],
+) -> Result<Vec<Glyph>> {
+    let mut reader = Reader::new(bytes);
+    reader.skip(2)?;
+    let header_size = reader.read_u8()? as usize;
+    let (_name_index, bytes_after_name_index) = Index::parse(bytes, header_size)?;
+    let (top_dict_index, bytes_after_top_dict_index) = Index::parse(bytes, bytes_after_name_index)?;
+    let (_string_index, bytes_after_string_index) =
+        Index::parse(bytes, bytes_after_top_dict_index)?;
+    let (global_subrs, _) = Index::parse(bytes, bytes_after_string_index)?;
+
+    let top_dict = parse_dict(top_dict_index.get(0)?)?;
+    if dict_operand(&top_dict, 1206, 0).unwrap_or(2.0) != 2.0 {
+        // only Type 2 charstrings are used in OpenType
+        return Err(Error);
+    }
+    let char_strings_offset = dict_operand(&top_dict, 17, 0).ok_or(Error)? as usize;
+    let (char_strings, _) = Index::parse(bytes, char_strings_offset)?;
+
+    // CID keyed fonts select a font dict, with its own local subroutines, per glyph
+    let mut local_subrs = Vec::new();
+    let mut fd_select = None;
+    if let Some(fd_array_offset) = dict_operand(&top_dict, 1236, 0) {
+        let (fd_array, _) = Index::parse(bytes, fd_array_offset as usize)?;
+        for index in 0..fd_array.count {
+            local_subrs.push(parse_private_dict_subrs(
+                bytes,
+                &parse_dict(fd_array.get(index)?)?,
+            )?);
+        }
+        let fd_select_offset = dict_operand(&top_dict, 1237, 0).ok_or(Error)? as usize;
+        fd_select = Some(bytes.get(fd_select_offset..).ok_or(Error)?);
+    } else {
+        local_subrs.push(parse_private_dict_subrs(bytes, &top_dict)?);
+    }
+
+    let mut glyphs = Vec::with_capacity(glyph_count);
+    for index in 0..glyph_count {
+        // a glyph we can't run is left empty instead of failing the whole font
+        let outline =
+            parse_glyph_outline(&char_strings, &global_subrs, &local_subrs, fd_select, index)
+                .unwrap_or_else(|_| Outline::new());
+        glyphs.push(Glyph {
+            horizontal_metrics: parse_horizontal_metrics(
+                hmtx_table_bytes,
+                advance_width_count,
+                index,
+            )?,
+            bounds: outline_bounds(&outline),
+            outline,
+        });
+    }
+    Ok(glyphs)
+}
+
+fn parse_glyph_outline(
+    char_strings: &Index,
+    global_subrs: &Index,
+    local_subrs: &[Index],
+    fd_select: Option<&[u8]>,
+    index: usize,
+) -> Result<Outline> {
+    let fd_index = match fd_select {
+        Some(fd_select) => parse_fd_index(fd_select, index)?,
+        None => 0,
+    };
+    let local_subrs = local_subrs.get(fd_index).ok_or(Error)?;
+    let mut interpreter = CharStringInterpreter::new(global_subrs, local_subrs);
+    interpreter.run(char_strings.get(index)?, 0)?;
+    Ok(interpreter.finish())
+}
+
+// CFF glyphs don't store their bounds, so we use the control box of the outline.
+fn outline_bounds(outline: &Outline) -> Rectangle {
+    let mut points = outline.points().iter();
+    let first = match points.next() {
+        Some(first) => first.point,
+        None => return Rectangle::default(),
+    };
+    points.fold(Rectangle::new(first, first), |bounds, point| {
+        Rectangle::new(
+            Point::new(
+                bounds.p_min.x.min(point.point.x),
+                bounds.p_min.y.min(point.point.y),
+            ),
+            Point::new(
+                bounds.p_max.x.max(point.point.x),
+                bounds.p_max.y.max(point.point.y),
+            ),
+        )
+    })
+}
+
+fn parse_private_dict_subrs<'a>(bytes: &'a [u8], dict: &[(u16, Vec<f32>)]) -> Result<Index<'a>> {
+    let private_size = match dict_operand(dict, 18, 0) {
+        Some(private_size) => private_size as usize,
+        None => return Ok(Index::default()),
+    };
+    let private_offset = dict_operand(dict, 18, 1).ok_or(Error)? as usize;
+    let private_bytes = bytes
+        .get(private_offset..private_offset + private_size)
+        .ok_or(Error)?;
+    match dict_operand(&parse_dict(private_bytes)?, 19, 0) {
+        // the subrs offset is relative to the private dict
+        Some(subrs_offset) => Ok(Index::parse(bytes, private_offset + subrs_offset as usize)?.0),
+        None => Ok(Index::default()),
+    }
+}
+
+fn parse_fd_index(bytes: &[u8], glyph_index: usize) -> Result<usize> {
+    let mut reader = Reader::new(bytes);
+    match reader.read_u8()? {
+        0 => {
+            reader.skip(glyph_index)?;
+            Ok(reader.read_u8()? as usize)
+        }
+        3 => {
+            let range_count = reader.read_u16()? as usize;
+            let mut first = reader.read_u16()? as usize;
+            for _ in 0..range_count {
+                let fd_index = reader.read_u8()? as usize;
+                let next = reader.read_u16()? as usize;
+                if glyph_index >= first && glyph_index < next {
+                    return Ok(fd_index);
+                }
+                first = next;
+            }
+            Err(Error)
+        }
+        _ => Err(Error),
+    }
+}
+
+/// An INDEX, the array of variable sized objects CFF uses for most of its data.
+#[derive(Clone, Copy, Debug, Default)]
+struct Index<'a> {
+    count: usize,
+    offset_size: usize,
+    offsets: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> Index<'a> {
+    /// Parses the INDEX at `start`, and returns it together with the offset of the byte after it.
+    fn parse(bytes: &'a [u8], start: usize) -> Result<(Index<'a>, usize)> {
+        let mut reader = Reader::new(bytes.get(start..).ok_or(Error)?);
+        let count = reader.read_u16()? as usize;
+        if count == 0 {
+            return Ok((Index::default(), start + 2));
+        }
+        let offset_size = reader.read_u8()? as usize;
+        if offset_size < 1 || offset_size > 4 {
+            return Err(Error);
+        }
+        let offsets_start = start + 3;
+        let offsets_end = offsets_start + (count + 1) * offset_size;
+        let offsets = bytes.get(offsets_start..offsets_end).ok_or(Error)?;
+        let mut index = Index {
+            count,
+            offset_size,
+            offsets,
+            data: &[],
+        };
+        // offsets are 1 based, relative to the byte before the data
+        let data_size = index.offset(count)? - 1;
+        index.data = bytes
+            .get(offsets_end..offsets_end + data_size)
+            .ok_or(Error)?;
+        Ok((index, offsets_end + data_size))
+    }
+
+    fn offset(&self, index: usize) -> Result<usize> {
+        let bytes = &self.offsets[index * self.offset_size..][..self.offset_size];
+        let offset = bytes
+            .iter()
+            .fold(0, |offset, byte| offset << 8 | *byte as usize);
+        if offset == 0 {
+            return Err(Error);
+        }
+        Ok(offset)
+    }
+
+    fn get(&self, index: usize) -> Result<&'a [u8]> {
+        if index >= self.count {
+            return Err(Error);
+        }
+        let start = self.offset(index)? - 1;
+        let end = self.offset(index + 1)? - 1;
+        self.data.get(start..end).ok_or(Error)
+    }
+}
+
+/// Parses a DICT into its operators with their operands. Two byte operators are returned as
+/// 1200 plus their second byte.
+fn parse_dict(bytes: &[u8]) -> Result<Vec<(u16, Vec<f32>)>> {
+    let mut reader = Reader::new(bytes);
+    let mut entries = Vec::new();
+    let mut operands = Vec::new();
+    while !reader.bytes.is_empty() {
+        let b0 = reader.read_u8()?;
+        match b0 {
+            0..=21 => {
+                let operator = if b0 == 12 {
+                    1200 + reader.read_u8()? as u16
+                } else {
+                    b0 as u16
+                };
+                entries.push((operator, operands.split_off(0)));
+            }
+            28 => operands.push(reader.read_i16()? as f32),
+            29 => operands.push(reader.read_u32()? as i32 as f32),
+            30 => operands.push(parse_dict_real(&mut reader)?),
+            32..=246 => operands.push(b0 as f32 - 139.0),
+            247..=250 => {
+                operands.push((b0 as f32 - 247.0) * 256.0 + reader.read_u8()? as f32 + 108.0)
+            }
+            251..=254 => {
+                operands.push(-(b0 as f32 - 251.0) * 256.0 - reader.read_u8()? as f32 - 108.0)
+            }
+            _ => return Err(Error),
+        }
+    }
+    Ok(entries)
+}
+
+// a real number, stored as a string of nibbles
+fn parse_dict_real(reader: &mut Reader) -> Result<f32> {
+    let mut string = String::new();
+    loop {
+        let byte = reader.read_u8()?;
+        for nibble in [byte >> 4, byte & 0xF].iter() {
+            match nibble {
+                0..=9 => string.push((b'0' + nibble) as char),
+                0xA => string.push('.'),
+                0xB => string.push('E'),
+                0xC => string.push_str("E-"),
+                0xE => string.push('-'),
+                0xF => return string.parse().map_err(|_| Error),
+                _ => return Err(Error),
+            }
+        }
+    }
+}
+
+fn dict_operand(dict: &[(u16, Vec<f32>)], operator: u16, index: usize) -> Option<f32> {
+    dict.iter()
+        .find(|(entry_operator, _)| *entry_operator == operator)
+        .and_then(|(_, operands)| operands.get(index).cloned())
+}
+
+fn subr_bias(subrs: &Index) -> i32 {
+    if subrs.count < 1240 {
+        107
+    } else if subrs.count < 33900 {
+        1131
+    } else {
+        32768
+    }
+}
+
+/// Runs a Type 2 charstring, building up the outline it draws.
+struct CharStringInterpreter<'a, 'b> {
+    global_subrs: &'b Index<'a>,
+    local_subrs: &'b Index<'a>,
+    stack: Vec<f32>,
+    stem_count: usize,
+    seen_width: bool,
+    current_point: Point,
+    contour: Vec<OutlinePoint>,
+    outline: Outline,
+    is_finished: bool,
+}
+
+impl<'a, 'b> CharStringInterpreter<'a, 'b> {
+    fn new(global_subrs: &'b Index<'a>, local_subrs: &'b Index<'a>) -> Self {
+        CharStringInterpreter {
+            global_subrs,
+            local_subrs,
+            stack: Vec::new(),
+            stem_count: 0,
+            seen_width: false,
+            current_point: Point::origin(),
+            contour: Vec::new(),
+            outline: Outline::new(),
+            is_finished: false,
+        }
+    }
+
+    fn finish(mut self) -> Outline {
+        self.close_contour();
+        self.outline
+    }
+
+    fn run(&mut self, bytes: &[u8], depth: usize) -> Result<()> {
+        if depth > MAX_SUBR_DEPTH {
+            return Err(Error);
+        }
+        let mut reader = Reader::new(bytes);
+        while !self.is_finished && !reader.bytes.is_empty() {
+            let b0 = reader.read_u8()?;
+            match b0 {
+                // hstem, vstem, hstemhm, vstemhm
+                1 | 3 | 18 | 23 => {
+                    self.take_width(self.stack.len() % 2 == 1);
+                    self.stem_count += self.stack.len() / 2;
+                    self.stack.clear();
+                }
+                // hintmask, cntrmask, with implied vstem operands on the stack
+                19 | 20 => {
+                    self.take_width(self.stack.len() % 2 == 1);
+                    self.stem_count += self.stack.len() / 2;
+                    self.stack.clear();
+                    reader.skip((self.stem_count + 7) / 8)?;
+                }
+                // rmoveto
+                21 => {
+                    self.take_width(self.stack.len() > 2);
+                    let (dx, dy) = (self.arg(0)?, self.arg(1)?);
+                    self.move_to(dx, dy);
+                }
+                // hmoveto
+                22 => {
+                    self.take_width(self.stack.len() > 1);
+                    let dx = self.arg(0)?;
+                    self.move_to(dx, 0.0);
+                }
+                // vmoveto
+                4 => {
+                    self.take_width(self.stack.len() > 1);
+                    let dy = self.arg(0)?;
+                    self.move_to(0.0, dy);
+                }
+                // rlineto
+                5 => {
+                    for pair in self.stack.split_off(0).chunks(2) {
+                        if let [dx, dy] = pair {
+                            self.line_to(*dx, *dy);
+                        }
+                    }
+                }
+                // hlineto, vlineto
+                6 | 7 => {
+                    let mut is_horizontal = b0 == 6;
+                    for d in self.stack.split_off(0) {
+                        if is_horizontal {
+                            self.line_to(d, 0.0);
+                        } else {
+                            self.line_to(0.0, d);
+                        }
+                        is_horizontal = !is_horizontal;
+                    }
+                }
+                // rrcurveto
+                8 => {
+                    for args in self.stack.split_off(0).chunks(6) {
+                        if let [dxa, dya, dxb, dyb, dxc, dyc] = args {
+                            self.curve_to(*dxa, *dya, *dxb, *dyb, *dxc, *dyc);
+                        }
+                    }
+                }
+                // rcurveline
+                24 => {
+                    let args = self.stack.split_off(0);
+                    if args.len() < 8 {
+                        return Err(Error);
+                    }
+                    let (curves, line) = args.split_at(args.len() - 2);
+                    for args in curves.chunks(6) {
+                        if let [dxa, dya, dxb, dyb, dxc, dyc] = args {
+                            self.curve_to(*dxa, *dya, *dxb, *dyb, *dxc, *dyc);
+                        }
+                    }
+                    self.line_to(line[0], line[1]);
+                }
+                // rlinecurve
+                25 => {
+                    let args = self.stack.split_off(0);
+                    if args.len() < 8 {
+                        return Err(Error);
+                    }
+                    let (lines, curve) = args.split_at(args.len() - 6);
+                    for pair in lines.chunks(2) {
+                        if let [dx, dy] = pair {
+                            self.line_to(*dx, *dy);
+                        }
+                    }
+                    self.curve_to(curve[0], curve[1], curve[2], curve[3], curve[4], curve[5]);
+                }
+                // vvcurveto
+                26 => {
+                    let mut args = &self.stack.split_off(0)[..];
+                    let mut dx1 = 0.0;
+                    if args.len() % 2 == 1 {
+                        dx1 = args[0];
+                        args = &args[1..];
+                    }
+                    for args in args.chunks(4) {
+                        if let [dya, dxb, dyb, dyc] = args {
+                            self.curve_to(dx1, *dya, *dxb, *dyb, 0.0, *dyc);
+                            dx1 = 0.0;
+                        }
+                    }
+                }
+                // hhcurveto
+                27 => {
+                    let mut args = &self.stack.split_off(0)[..];
+                    let mut dy1 = 0.0;
+                    if args.len() % 2 == 1 {
+                        dy1 = args[0];
+                        args = &args[1..];
+                    }
+                    for args in args.chunks(4) {
+                        if let [dxa, dxb, dyb, dxc] = args {
+                            self.curve_to(*dxa, dy1, *dxb, *dyb, *dxc, 0.0);
+                            dy1 = 0.0;
+                        }
+                    }
+                }
+                // vhcurveto, hvcurveto
+                30 | 31 => {
+                    let args = self.stack.split_off(0);
+                    let mut is_horizontal = b0 == 31;
+                    let mut index = 0;
+                    while index + 4 <= args.len() {
+                        // the last curve can have an extra final delta
+                        let df = if args.len() - index == 5 {
+                            args[index + 4]
+                        } else {
+                            0.0
+                        };
+                        let a = &args[index..index + 4];
+                        if is_horizontal {
+                            self.curve_to(a[0], 0.0, a[1], a[2], df, a[3]);
+                        } else {
+                            self.curve_to(0.0, a[0], a[1], a[2], a[3], df);
+                        }
+                        index += 4;
+                        is_horizontal = !is_horizontal;
+                    }
+                }
+                // callsubr, callgsubr
+                10 | 29 => {
+                    let subrs = if b0 == 10 {
+                        self.local_subrs
+                    } else {
+                        self.global_subrs
+                    };
+                    let index = self.stack.pop().ok_or(Error)? as i32 + subr_bias(subrs);
+                    if index < 0 {
+                        return Err(Error);
+                    }
+                    self.run(subrs.get(index as usize)?, depth + 1)?;
+                }
+                // return
+                11 => return Ok(()),
+                // endchar
+                14 => {
+                    self.take_width(self.stack.len() == 1 || self.stack.len() == 5);
+                    self.stack.clear();
+                    self.is_finished = true;
+                }
+                // shortint
+                28 => self.stack.push(reader.read_i16()? as f32),
+                32..=246 => self.stack.push(b0 as f32 - 139.0),
+                247..=250 => self
+                    .stack
+                    .push((b0 as f32 - 247.0) * 256.0 + reader.read_u8()? as f32 + 108.0),
+                251..=254 => self
+                    .stack
+                    .push(-(b0 as f32 - 251.0) * 256.0 - reader.read_u8()? as f32 - 108.0),
+                // 16.16 fixed
+                255 => self.stack.push(reader.read_u32()? as i32 as f32 / 65536.0),
+                12 => self.run_flex(reader.read_u8()?)?,
+                _ => return Err(Error),
+            }
+        }
+        Ok(())
+    }
+
+    fn run_flex(&mut self, b1: u8) -> Result<()> {
+        let args = self.stack.split_off(0);
+        match (b1, args.len()) {
+            // flex
+            (35, 13) => {
+                self.curve_to(args[0], args[1], args[2], args[3], args[4], args[5]);
+                self.curve_to(args[6], args[7], args[8], args[9], args[10], args[11]);
+            }
+            // hflex
+            (34, 7) => {
+                self.curve_to(args[0], 0.0, args[1], args[2], args[3], 0.0);
+                self.curve_to(args[4], 0.0, args[5], -args[2], args[6], 0.0);
+            }
+            // hflex1
+            (36, 9) => {
+                let dy = args[1] + args[3] + args[7];
+                self.curve_to(args[0], args[1], args[2], args[3], args[4], 0.0);
+                self.curve_to(args[5], 0.0, args[6], args[7], args[8], -dy);
+            }
+            // flex1
+            (37, 11) => {
+                let dx = args[0] + args[2] + args[4] + args[6] + args[8];
+                let dy = args[1] + args[3] + args[5] + args[7] + args[9];
+                let (dx6, dy6) = if dx.abs() > dy.abs() {
+                    (args[10], -dy)
+                } else {
+                    (-dx, args[10])
+                };
+                self.curve_to(args[0], args[1], args[2], args[3], args[4], args[5]);
+                self.curve_to(args[6], args[7], args[8], args[9], dx6, dy6);
+            }
+            // arithmetic and storage operators are not used by any font we've seen
+            _ => return Err(Error),
+        }
+        Ok(())
+    }
+
+    // the first stack clearing operator can have the advance width as an extra first operand,
+    // we take the advance width from hmtx instead
+    fn take_width(&mut self, has_width: bool) {
+        if !self.seen_width {
+            self.seen_width = true;
+            if has_width {
+                self.stack.remove(0);
+            }
+        }
+    }
+
+    fn arg(&self, index: usize) -> Result<f32> {
+        self.stack.get(index).cloned().ok_or(Error)
+    }
+
+    fn close_contour(&mut self) {
+        // contours are closed implicitly, drop an explicit line back to the start
+        if self.contour.len() > 1 && self.contour.first() == self.contour.last() {
+            self.contour.pop();
+        }
+        if self.contour.len() > 1 {
+            let mut contour = self.outline.begin_contour();
+            for point in self.contour.drain(..) {
+                contour.push(point);
+            }
+            contour.end();
+        }
+        self.contour.clear();
+    }
+
+    fn move_to(&mut self, dx: f32, dy: f32) {
+        self.stack.clear();
+        self.close_contour();
+        self.current_point = Point::new(self.current_point.x + dx, self.current_point.y + dy);
+        self.push_point(self.current_point, true);
+    }
+
+    fn line_to(&mut self, dx: f32, dy: f32) {
+        self.current_point = Point::new(self.current_point.x + dx, self.current_point.y + dy);
+        self.push_point(self.current_point, true);
+    }
+
+    fn curve_to(&mut self, dxa: f32, dya: f32, dxb: f32, dyb: f32, dxc: f32, dyc: f32) {
+        let p1 = Point::new(self.current_point.x + dxa, self.current_point.y + dya);
+        let p2 = Point::new(p1.x + dxb, p1.y + dyb);
+        let p3 = Point::new(p2.x + dxc, p2.y + dyc);
+        self.push_point(p1, false);
+        self.push_point(p2, false);
+        self.push_point(p3, true);
+        self.current_point = p3;
+    }
+
+    fn push_point(&mut self, point: Point, is_on_curve: bool) {
+        self.contour.push(OutlinePoint {
+            is_on_curve,
+            is_cubic: !is_on_curve,
+            point,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a charstring from numbers, operator names and raw hex bytes for hint masks
+    fn charstring(source: &str) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for token in source.split_whitespace() {
+            if token.starts_with("0x") {
+                bytes.push(u8::from_str_radix(&token[2..], 16).unwrap());
+                continue;
+            }
+            if let Ok(value) = token.parse::<i32>() {
+                if value >= -107 && value <= 107 {
+                    bytes.push((value + 139) as u8);
+                } else {
+                    bytes.push(28);
+                    bytes.extend_from_slice(&(value as i16).to_be_bytes());
+                }
+                continue;
+            }
+            bytes.extend_from_slice(match token {
+                "hstem" => &[1],
+                "vstem" => &[3],
+                "vmoveto" => &[4],
+                "rlineto" => &[5],
+                "hlineto" => &[6],
+                "vlineto" => &[7],
+                "rrcurveto" => &[8],
+                "callsubr" => &[10],
+                "return" => &[11],
+                "endchar" => &[14],
+                "hstemhm" => &[18],
+                "hintmask" => &[19],
+                "rmoveto" => &[21],
+                "hmoveto" => &[22],
+                "callgsubr" => &[29],
+                "vhcurveto" => &[30],
+                "hvcurveto" => &[31],
+                "hflex" => &[12, 34],
+                "dotsection" => &[12, 0],
+                _ => panic!("unknown operator {}", token),
+            });
+        }
+        bytes
+    }
+
+    fn index(items: &[Vec<u8>]) -> Vec<u8> {
+        if items.is_empty() {
+            return vec![0, 0];
+        }
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(items.len() as u16).to_be_bytes());
+        bytes.push(4);
+        let mut offset = 1u32;
+        bytes.extend_from_slice(&offset.to_be_bytes());
+        for item in items {
+            offset += item.len() as u32;
+            bytes.extend_from_slice(&offset.to_be_bytes());
+        }
+        for item in items {
+            bytes.extend_from_slice(item);
+        }
+        bytes
+    }
+
+    // a 5 byte integer, so an offset doesn't change the size of the dict it is in
+    fn dict_int(value: usize) -> Vec<u8> {
+        let mut bytes = vec![29];
+        bytes.extend_from_slice(&(value as i32).to_be_bytes());
+        bytes
+    }
+
+    // header, name, top dict, string, global subrs, charstrings, private dict and local subrs
+    fn build_cff(char_strings: &[&str], global_subrs: &[&str], local_subrs: &[&str]) -> Vec<u8> {
+        let compile = |sources: &[&str]| -> Vec<Vec<u8>> {
+            sources.iter().map(|source| charstring(source)).collect()
+        };
+        let header = vec![1, 0, 4, 1];
+        let name_index = index(&[b"Test".to_vec()]);
+        let string_index = index(&[]);
+        let global_subrs_index = index(&compile(global_subrs));
+        let char_strings_index = index(&compile(char_strings));
+        // the subrs offset is relative to the private dict, which is 6 bytes long
+        let private_dict = [dict_int(6), vec![19]].concat();
+        let local_subrs_index = index(&compile(local_subrs));
+
+        let top_dict_size = 17;
+        let top_dict_index_size = 3 + 2 * 4 + top_dict_size;
+        let char_strings_offset = header.len()
+            + name_index.len()
+            + top_dict_index_size
+            + string_index.len()
+            + global_subrs_index.len();
+        let private_offset = char_strings_offset + char_strings_index.len();
+        let top_dict = [
+            dict_int(char_strings_offset),
+            vec![17],
+            dict_int(private_dict.len()),
+            dict_int(private_offset),
+            vec![18],
+        ]
+        .concat();
+        assert_eq!(top_dict.len(), top_dict_size);
+        [
+            header,
+            name_index,
+            index(&[top_dict]),
+            string_index,
+            global_subrs_index,
+            char_strings_index,
+            private_dict,
+            local_subrs_index,
+        ]
+        .concat()
+    }
+
+    fn parse(char_strings: &[&str], global_subrs: &[&str], local_subrs: &[&str]) -> Vec<Glyph> {
+        let cff = build_cff(char_strings, global_subrs, local_subrs);
+        let hmtx = [0u8, 100, 0, 0].repeat(char_strings.len());
+        parse_cff_glyphs(&cff, char_strings.len(), char_strings.len(), &hmtx).unwrap()
+    }
+
+    fn points(glyph: &Glyph) -> Vec<(f32, f32, bool)> {
+        glyph
+            .outline
+            .points()
+            .iter()
+            .map(|point| (point.point.x, point.point.y, point.is_on_curve))
+            .collect()
+    }
+
+    #[test]
+    fn lines_with_a_width_operand() {
+        let glyphs = parse(
+            &["100 10 20 rmoveto 30 hlineto 40 vlineto -30 -40 rlineto endchar"],
+            &[],
+            &[],
+        );
+        // the explicit line back to the start is dropped, contours close implicitly
+        assert_eq!(
+            points(&glyphs[0]),
+            vec![(10.0, 20.0, true), (40.0, 20.0, true), (40.0, 60.0, true)]
+        );
+        assert_eq!(glyphs[0].outline.contours().count(), 1);
+        assert_eq!(glyphs[0].bounds.p_min, Point::new(10.0, 20.0));
+        assert_eq!(glyphs[0].bounds.p_max, Point::new(40.0, 60.0));
+        assert_eq!(glyphs[0].horizontal_metrics.advance_width, 100.0);
+    }
+
+    #[test]
+    fn curves() {
+        let glyphs = parse(
+            &[
+                "0 0 rmoveto 10 0 20 10 0 20 rrcurveto endchar",
+                "0 hmoveto 10 20 30 40 hvcurveto endchar",
+                "0 0 rmoveto 10 20 30 40 50 60 5 hflex endchar",
+            ],
+            &[],
+            &[],
+        );
+        assert_eq!(
+            points(&glyphs[0]),
+            vec![
+                (0.0, 0.0, true),
+                (10.0, 0.0, false),
+                (30.0, 10.0, false),
+                (30.0, 30.0, true)
+            ]
+        );
+        // hvcurveto starts horizontal and ends vertical
+        assert_eq!(
+            points(&glyphs[1]),
+            vec![
+                (0.0, 0.0, true),
+                (10.0, 0.0, false),
+                (30.0, 30.0, false),
+                (30.0, 70.0, true)
+            ]
+        );
+        // hflex is two curves that end at the height they started
+        let hflex = points(&glyphs[2]);
+        assert_eq!(hflex.len(), 7);
+        assert_eq!(hflex[3], (70.0, 30.0, true));
+        assert_eq!(hflex[5], (180.0, 0.0, false));
+        assert_eq!(hflex[6], (185.0, 0.0, true));
+    }
+
+    #[test]
+    fn hintmask_skips_its_mask_bytes() {
+        let glyphs = parse(
+            &[
+                // two hstems and one implied vstem, one mask byte that would otherwise be a number
+                "0 10 20 30 hstemhm 40 50 hintmask 0xE0 5 5 rmoveto 10 10 rlineto 10 0 rlineto endchar",
+                // nine stems take two mask bytes
+                "0 1 0 1 0 1 0 1 0 1 hstemhm 0 1 0 1 0 1 0 1 vstem hintmask 0xFF 0x80 5 hmoveto 10 vlineto 10 hlineto endchar",
+            ],
+            &[],
+            &[],
+        );
+        assert_eq!(
+            points(&glyphs[0]),
+            vec![(5.0, 5.0, true), (15.0, 15.0, true), (25.0, 15.0, true)]
+        );
+        assert_eq!(
+            points(&glyphs[1]),
+            vec![(5.0, 0.0, true), (5.0, 10.0, true), (15.0, 10.0, true)]
+        );
+    }
+
+    #[test]
+    fn subroutines_use_the_bias_and_nest() {
+        let glyphs = parse(
+            &[
+                "0 0 rmoveto -107 callsubr -107 callgsubr endchar",
+                "0 0 rmoveto -106 callgsubr endchar",
+            ],
+            &["0 10 rlineto return", "-107 callsubr -107 callgsubr"],
+            &["10 0 rlineto return"],
+        );
+        assert_eq!(
+            points(&glyphs[0]),
+            vec![(0.0, 0.0, true), (10.0, 0.0, true), (10.0, 10.0, true)]
+        );
+        assert_eq!(points(&glyphs[0]), points(&glyphs[1]));
+    }
+
+    #[test]
+    fn broken_glyphs_are_empty_and_the_font_still_loads() {
+        let glyphs = parse(
+            &[
+                "0 0 rmoveto 10 0 rlineto 0 10 rlineto endchar",
+                // an operator we don't support
+                "0 0 rmoveto dotsection 10 0 rlineto endchar",
+                // a subroutine that doesn't exist
+                "0 0 rmoveto 20 callsubr endchar",
+                // a subroutine that calls itself forever
+                "0 0 rmoveto -107 callgsubr endchar",
+                // rmoveto without operands
+                "rmoveto endchar",
+            ],
+            &["-107 callgsubr"],
+            &[],
+        );
+        assert_eq!(glyphs.len(), 5);
+        assert_eq!(points(&glyphs[0]).len(), 3);
+        for glyph in &glyphs[1..] {
+            assert!(glyph.outline.points().is_empty());
+            assert_eq!(glyph.horizontal_metrics.advance_width, 100.0);
+        }
+    }
+}
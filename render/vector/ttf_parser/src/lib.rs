@@ -1,9 +1,14 @@
-use makepad_font::{Font, Glyph, HorizontalMetrics, Outline, OutlinePoint};
+use makepad_font::{
+    CharCodeSegment, CharCodeToGlyphIndexMap, Font, Glyph, HorizontalMetrics, KerningClasses,
+    Ligature, Outline, OutlinePoint,
+};
 use makepad_geometry::{
     AffineTransformation, LinearTransformation, Point, Rectangle, Transform, Vector,
 };
 use makepad_internal_iter::ExtendFromInternalIterator;
-use std::{mem, result};
+use std::{collections::HashMap, mem, result};
+
+mod cff;
 
 #[derive(Clone, Debug)]
 pub struct GlyphsParser<'a> {
@@ -99,23 +104,7 @@ impl<'a> GlyphsParser<'a> {
     }
 
     fn parse_horizontal_metrics(&self, index: usize) -> Result<HorizontalMetrics> {
-        let mut reader = Reader::new(self.hmtx_table_bytes);
-        if index < self.advance_width_count {
-            reader.skip(index * 4)?;
-            Ok(HorizontalMetrics {
-                advance_width: reader.read_u16()? as f32,
-                left_side_bearing: reader.read_i16()? as f32,
-            })
-        } else {
-            reader.skip((self.advance_width_count - 1) * 4)?;
-            let advance_width = reader.read_u16()? as f32;
-            reader.skip(2)?;
-            reader.skip((index - self.advance_width_count) * 2)?;
-            Ok(HorizontalMetrics {
-                advance_width,
-                left_side_bearing: reader.read_i16()? as f32,
-            })
-        }
+        parse_horizontal_metrics(self.hmtx_table_bytes, self.advance_width_count, index)
     }
 
     fn parse_simple_glyph(
@@ -341,6 +330,7 @@ impl<'a> OutlinePointReader<'a> {
         );
         Ok(OutlinePoint {
             is_on_curve: flags.on_curve_point(),
+            is_cubic: false,
             point: self.current_point,
         })
     }
@@ -505,6 +495,30 @@ impl<'a> Reader<'a> {
     }
 }
 
+fn parse_horizontal_metrics(
+    hmtx_table_bytes: &[u8],
+    advance_width_count: usize,
+    index: usize,
+) -> Result<HorizontalMetrics> {
+    let mut reader = Reader::new(hmtx_table_bytes);
+    if index < advance_width_count {
+        reader.skip(index * 4)?;
+        Ok(HorizontalMetrics {
+            advance_width: reader.read_u16()? as f32,
+            left_side_bearing: reader.read_i16()? as f32,
+        })
+    } else {
+        reader.skip((advance_width_count - 1) * 4)?;
+        let advance_width = reader.read_u16()? as f32;
+        reader.skip(2)?;
+        reader.skip((index - advance_width_count) * 2)?;
+        Ok(HorizontalMetrics {
+            advance_width,
+            left_side_bearing: reader.read_i16()? as f32,
+        })
+    }
+}
+
 pub type Result<T> = result::Result<T, Error>;
 
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -513,16 +527,26 @@ pub struct Error;
 pub fn parse_ttf(bytes: &[u8]) -> Result<Font> {
     let mut reader = Reader::new(&bytes[0..12]);
     let sfnt_version = reader.read_u32()?;
-    if ![0x00010000, u32::from_be_bytes(*b"true")].contains(&sfnt_version) {
+    if ![
+        0x00010000,
+        u32::from_be_bytes(*b"true"),
+        u32::from_be_bytes(*b"OTTO"),
+    ]
+    .contains(&sfnt_version)
+    {
         return Err(Error);
     }
     let table_count = reader.read_u16()? as usize;
     reader.skip(6)?;
+    let mut cff_table_bytes = None;
     let mut cmap_table_bytes = None;
     let mut glyf_table_bytes = None;
+    let mut gpos_table_bytes = None;
+    let mut gsub_table_bytes = None;
     let mut head_table_bytes = None;
     let mut hhea_table_bytes = None;
     let mut hmtx_table_bytes = None;
+    let mut kern_table_bytes = None;
     let mut loca_table_bytes = None;
     let mut maxp_table_bytes = None;
     for index in 0..table_count {
@@ -533,22 +557,24 @@ pub fn parse_ttf(bytes: &[u8]) -> Result<Font> {
         let length = reader.read_u32()? as usize;
         let table_bytes = &bytes[offset..][..length];
         match &table_tag.to_be_bytes() {
+            b"CFF " => cff_table_bytes = Some(table_bytes),
             b"cmap" => cmap_table_bytes = Some(table_bytes),
             b"glyf" => glyf_table_bytes = Some(table_bytes),
+            b"GPOS" => gpos_table_bytes = Some(table_bytes),
+            b"GSUB" => gsub_table_bytes = Some(table_bytes),
             b"head" => head_table_bytes = Some(table_bytes),
             b"hhea" => hhea_table_bytes = Some(table_bytes),
             b"hmtx" => hmtx_table_bytes = Some(table_bytes),
+            b"kern" => kern_table_bytes = Some(table_bytes),
             b"loca" => loca_table_bytes = Some(table_bytes),
             b"maxp" => maxp_table_bytes = Some(table_bytes),
             _ => {}
         }
     }
     let cmap_table_bytes = cmap_table_bytes.ok_or(Error)?;
-    let glyf_table_bytes = glyf_table_bytes.ok_or(Error)?;
     let head_table_bytes = head_table_bytes.ok_or(Error)?;
     let hhea_table_bytes = hhea_table_bytes.ok_or(Error)?;
     let hmtx_table_bytes = hmtx_table_bytes.ok_or(Error)?;
-    let loca_table_bytes = loca_table_bytes.ok_or(Error)?;
     let maxp_table_bytes = maxp_table_bytes.ok_or(Error)?;
     let mut reader = Reader::new(hhea_table_bytes);
     reader.skip(4)?;
@@ -558,9 +584,12 @@ pub fn parse_ttf(bytes: &[u8]) -> Result<Font> {
     reader.skip(24)?;
     let advance_width_count = reader.read_u16()? as usize;
     let mut reader = Reader::new(maxp_table_bytes);
-    reader.skip(4)?;
+    let maxp_version = reader.read_u32()?;
     let glyph_count = reader.read_u16()? as usize;
-    reader.skip(26)?;
+    // version 0.5, used by CFF fonts, stops after the glyph count
+    if maxp_version == 0x00010000 {
+        reader.skip(26)?;
+    }
     let mut reader = Reader::new(head_table_bytes);
     reader.skip(18)?;
     let units_per_em = reader.read_u16()? as f32;
@@ -572,6 +601,48 @@ pub fn parse_ttf(bytes: &[u8]) -> Result<Font> {
     reader.skip(6)?;
     let index_to_loc_format = IndexToLocFormat::from_i16(reader.read_i16()?).ok_or(Error)?;
     reader.skip(2)?;
+    // the layout tables are optional, a font we can't read them for still renders
+    let (mut kerning_pairs, kerning_classes) = gpos_table_bytes
+        .map_or(Ok(Default::default()), parse_gpos_kerning)
+        .unwrap_or_default();
+    if kerning_pairs.is_empty() && kerning_classes.is_empty() {
+        kerning_pairs = kern_table_bytes
+            .map_or(Ok(HashMap::new()), parse_kern_table)
+            .unwrap_or_default();
+    }
+    let ligatures = gsub_table_bytes
+        .map_or(Ok(HashMap::new()), parse_gsub_ligatures)
+        .unwrap_or_default();
+    let mut single_substitutions = HashMap::new();
+    if let Some(gsub_table_bytes) = gsub_table_bytes {
+        for feature_tag in &[*b"isol", *b"init", *b"medi", *b"fina"] {
+            if let Ok(substitutions) =
+                parse_gsub_single_substitutions(gsub_table_bytes, feature_tag)
+            {
+                if !substitutions.is_empty() {
+                    single_substitutions.insert(*feature_tag, substitutions);
+                }
+            }
+        }
+    }
+    let glyphs = if let Some(cff_table_bytes) = cff_table_bytes {
+        cff::parse_cff_glyphs(
+            cff_table_bytes,
+            glyph_count,
+            advance_width_count,
+            hmtx_table_bytes,
+        )?
+    } else {
+        GlyphsParser::new(
+            glyph_count,
+            advance_width_count,
+            hmtx_table_bytes,
+            index_to_loc_format,
+            loca_table_bytes.ok_or(Error)?,
+            glyf_table_bytes.ok_or(Error)?,
+        )
+        .parse_glyphs()?
+    };
     Ok(Font {
         units_per_em,
         ascender,
@@ -579,43 +650,49 @@ pub fn parse_ttf(bytes: &[u8]) -> Result<Font> {
         line_gap,
         bounds,
         char_code_to_glyph_index_map: parse_char_code_to_glyph_index_map(cmap_table_bytes)?,
-        glyphs: GlyphsParser::new(
-            glyph_count,
-            advance_width_count,
-            hmtx_table_bytes,
-            index_to_loc_format,
-            loca_table_bytes,
-            glyf_table_bytes,
-        )
-        .parse_glyphs()?,
+        glyphs,
+        kerning_pairs,
+        kerning_classes,
+        ligatures,
+        single_substitutions,
     })
 }
 
-fn parse_char_code_to_glyph_index_map(bytes: &[u8]) -> Result<Vec<usize>> {
+fn parse_char_code_to_glyph_index_map(bytes: &[u8]) -> Result<CharCodeToGlyphIndexMap> {
     let mut reader = Reader::new(bytes);
     reader.skip(2)?;
-    let mut subtable_bytes = None;
+    // prefer a subtable that covers all of unicode over one that only covers the BMP
+    let mut best_subtable = None;
     let subtable_count = reader.read_u16()? as usize;
     for _ in 0..subtable_count {
         let platform_id = reader.read_u16()?;
         let encoding_id = reader.read_u16()?;
         let offset = reader.read_u32()? as usize;
         if let (0, _) | (3, 1) | (3, 10) = (platform_id, encoding_id) {
-            subtable_bytes = Some(&bytes[offset..]);
-            break;
+            let subtable_bytes = bytes.get(offset..).ok_or(Error)?;
+            let format = Reader::new(subtable_bytes).read_u16()?;
+            let priority = match format {
+                12 => 3,
+                13 => 2,
+                4 => 1,
+                _ => continue,
+            };
+            if best_subtable.map_or(true, |(best_priority, _, _)| priority > best_priority) {
+                best_subtable = Some((priority, format, subtable_bytes));
+            }
         }
     }
-    let subtable_bytes = subtable_bytes.ok_or(Error)?;
-    let mut reader = Reader::new(subtable_bytes);
-    let format = reader.read_u16()?;
+    let (_, format, subtable_bytes) = best_subtable.ok_or(Error)?;
     let bytes = &subtable_bytes[2..];
     match format {
         4 => parse_char_code_to_glyph_index_map_format_4(bytes),
+        12 => parse_char_code_to_glyph_index_map_format_12_or_13(bytes, false),
+        13 => parse_char_code_to_glyph_index_map_format_12_or_13(bytes, true),
         _ => Err(Error),
     }
 }
 
-fn parse_char_code_to_glyph_index_map_format_4(bytes: &[u8]) -> Result<Vec<usize>> {
+fn parse_char_code_to_glyph_index_map_format_4(bytes: &[u8]) -> Result<CharCodeToGlyphIndexMap> {
     let mut reader = Reader::new(bytes);
     reader.skip(4)?;
     let seg_count = reader.read_u16()? as usize / 2;
@@ -632,13 +709,19 @@ fn parse_char_code_to_glyph_index_map_format_4(bytes: &[u8]) -> Result<Vec<usize
     let mut start_code_reader = Reader::new(start_code_bytes);
     let mut id_delta_reader = Reader::new(id_delta_bytes);
     let mut id_range_offset_reader = Reader::new(id_range_offset_bytes);
-    let mut char_code_to_glyph_index_map = Vec::new();
+    let mut char_code_to_glyph_index_map = CharCodeToGlyphIndexMap::new();
+    let mut last_end_code = None;
     for seg_index in 0..seg_count {
         let end_code = end_code_reader.read_u16()?;
         let start_code = start_code_reader.read_u16()?;
         let id_delta = id_delta_reader.read_u16()? as usize;
         let id_range_offset = id_range_offset_reader.read_u16()? as usize;
-        for code in start_code..end_code {
+        // the map is binary searched, so segments have to be sorted and apart
+        if end_code < start_code || last_end_code.map_or(false, |last| start_code <= last) {
+            return Err(Error);
+        }
+        last_end_code = Some(end_code);
+        for code in start_code..=end_code {
             let mut id = if id_range_offset == 0 {
                 code
             } else {
@@ -651,9 +734,714 @@ fn parse_char_code_to_glyph_index_map_format_4(bytes: &[u8]) -> Result<Vec<usize
             if id != 0 {
                 id = (id + id_delta) % 65536;
             }
-            char_code_to_glyph_index_map.resize(code as usize + 1, 0);
-            char_code_to_glyph_index_map[code as usize] = id;
+            if id != 0 {
+                char_code_to_glyph_index_map.insert(code as u32, id);
+            }
+        }
+    }
+    Ok(char_code_to_glyph_index_map)
+}
+
+fn parse_char_code_to_glyph_index_map_format_12_or_13(
+    bytes: &[u8],
+    is_constant: bool,
+) -> Result<CharCodeToGlyphIndexMap> {
+    let mut reader = Reader::new(bytes);
+    reader.skip(10)?;
+    let group_count = reader.read_u32()? as usize;
+    let mut char_code_to_glyph_index_map = CharCodeToGlyphIndexMap::new();
+    let mut last_end_char_code = None;
+    for _ in 0..group_count {
+        let start_char_code = reader.read_u32()?;
+        let end_char_code = reader.read_u32()?;
+        let start_glyph_index = reader.read_u32()? as usize;
+        if end_char_code < start_char_code
+            || last_end_char_code.map_or(false, |last| start_char_code <= last)
+        {
+            return Err(Error);
         }
+        last_end_char_code = Some(end_char_code);
+        char_code_to_glyph_index_map.insert_segment(CharCodeSegment {
+            start_char_code,
+            end_char_code,
+            start_glyph_index,
+            is_constant,
+        });
     }
     Ok(char_code_to_glyph_index_map)
 }
+
+fn parse_kern_table(bytes: &[u8]) -> Result<HashMap<(usize, usize), f32>> {
+    let mut kerning_pairs = HashMap::new();
+    let mut reader = Reader::new(bytes);
+    reader.skip(2)?;
+    let subtable_count = reader.read_u16()? as usize;
+    let mut subtable_bytes = &bytes[4..];
+    for _ in 0..subtable_count {
+        let mut reader = Reader::new(subtable_bytes);
+        reader.skip(2)?;
+        let length = reader.read_u16()? as usize;
+        let coverage = reader.read_u16()?;
+        // format 0, horizontal, not minimum values or cross stream
+        if coverage >> 8 == 0 && coverage & 0x7 == 0x1 {
+            let pair_count = reader.read_u16()? as usize;
+            reader.skip(6)?;
+            for _ in 0..pair_count {
+                let left = reader.read_u16()? as usize;
+                let right = reader.read_u16()? as usize;
+                let value = reader.read_i16()? as f32;
+                kerning_pairs.entry((left, right)).or_insert(value);
+            }
+        }
+        subtable_bytes = subtable_bytes.get(length..).ok_or(Error)?;
+    }
+    Ok(kerning_pairs)
+}
+
+fn parse_gpos_kerning(bytes: &[u8]) -> Result<(HashMap<(usize, usize), f32>, Vec<KerningClasses>)> {
+    let mut kerning_pairs = HashMap::new();
+    let mut kerning_classes = Vec::new();
+    for lookup_index in parse_feature_lookup_indices(bytes, &[b"kern"])? {
+        for (lookup_type, subtable_bytes) in parse_lookup_subtables(bytes, lookup_index, 9)? {
+            if lookup_type != 2 {
+                continue;
+            }
+            let mut reader = Reader::new(subtable_bytes);
+            let format = reader.read_u16()?;
+            let coverage = parse_coverage(offset_bytes(subtable_bytes, reader.read_u16()?)?)?;
+            let value_format_1 = reader.read_u16()?;
+            let value_format_2 = reader.read_u16()?;
+            match format {
+                1 => {
+                    let pair_set_count = reader.read_u16()? as usize;
+                    for index in 0..pair_set_count.min(coverage.len()) {
+                        let pair_set_bytes = offset_bytes(subtable_bytes, reader.read_u16()?)?;
+                        let mut reader = Reader::new(pair_set_bytes);
+                        let pair_value_count = reader.read_u16()? as usize;
+                        for _ in 0..pair_value_count {
+                            let right = reader.read_u16()? as usize;
+                            let value = read_value_record_x_advance(&mut reader, value_format_1)?;
+                            reader.skip(value_record_size(value_format_2))?;
+                            // earlier lookups and subtables take precedence
+                            kerning_pairs
+                                .entry((coverage[index], right))
+                                .or_insert(value);
+                        }
+                    }
+                }
+                2 => {
+                    let first_class_def =
+                        parse_class_def(offset_bytes(subtable_bytes, reader.read_u16()?)?)?;
+                    let second_classes =
+                        parse_class_def(offset_bytes(subtable_bytes, reader.read_u16()?)?)?;
+                    let first_class_count = reader.read_u16()? as usize;
+                    let second_class_count = reader.read_u16()? as usize;
+                    let mut values = Vec::with_capacity(first_class_count * second_class_count);
+                    for _ in 0..(first_class_count * second_class_count) {
+                        values.push(read_value_record_x_advance(&mut reader, value_format_1)?);
+                        reader.skip(value_record_size(value_format_2))?;
+                    }
+                    // covered glyphs without a class are in class 0
+                    let first_classes = coverage
+                        .iter()
+                        .map(|&glyph_index| {
+                            (
+                                glyph_index,
+                                *first_class_def.get(&glyph_index).unwrap_or(&0),
+                            )
+                        })
+                        .collect();
+                    kerning_classes.push(KerningClasses {
+                        first_classes,
+                        second_classes,
+                        second_class_count,
+                        values,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok((kerning_pairs, kerning_classes))
+}
+
+fn parse_gsub_ligatures(bytes: &[u8]) -> Result<HashMap<usize, Vec<Ligature>>> {
+    let mut ligatures: HashMap<usize, Vec<Ligature>> = HashMap::new();
+    for lookup_index in parse_feature_lookup_indices(bytes, &[b"liga", b"rlig"])? {
+        for (lookup_type, subtable_bytes) in parse_lookup_subtables(bytes, lookup_index, 7)? {
+            if lookup_type != 4 {
+                continue;
+            }
+            let mut reader = Reader::new(subtable_bytes);
+            if reader.read_u16()? != 1 {
+                continue;
+            }
+            let coverage = parse_coverage(offset_bytes(subtable_bytes, reader.read_u16()?)?)?;
+            let ligature_set_count = reader.read_u16()? as usize;
+            for index in 0..ligature_set_count.min(coverage.len()) {
+                let ligature_set_bytes = offset_bytes(subtable_bytes, reader.read_u16()?)?;
+                let mut reader = Reader::new(ligature_set_bytes);
+                let ligature_count = reader.read_u16()? as usize;
+                for _ in 0..ligature_count {
+                    let mut ligature_reader =
+                        Reader::new(offset_bytes(ligature_set_bytes, reader.read_u16()?)?);
+                    let glyph_index = ligature_reader.read_u16()? as usize;
+                    let component_count = ligature_reader.read_u16()? as usize;
+                    let mut components = Vec::new();
+                    for _ in 1..component_count {
+                        components.push(ligature_reader.read_u16()? as usize);
+                    }
+                    ligatures
+                        .entry(coverage[index])
+                        .or_default()
+                        .push(Ligature {
+                            components,
+                            glyph_index,
+                        });
+                }
+            }
+        }
+    }
+    // the longest match wins, and sort is stable so lookup order breaks ties
+    for ligatures in ligatures.values_mut() {
+        ligatures.sort_by(|a, b| b.components.len().cmp(&a.components.len()));
+    }
+    Ok(ligatures)
+}
+
+fn parse_gsub_single_substitutions(
+    bytes: &[u8],
+    feature_tag: &[u8; 4],
+) -> Result<HashMap<usize, usize>> {
+    let mut substitutions = HashMap::new();
+    for lookup_index in parse_feature_lookup_indices(bytes, &[feature_tag])? {
+        for (lookup_type, subtable_bytes) in parse_lookup_subtables(bytes, lookup_index, 7)? {
+            if lookup_type != 1 {
+                continue;
+            }
+            let mut reader = Reader::new(subtable_bytes);
+            let format = reader.read_u16()?;
+            let coverage = parse_coverage(offset_bytes(subtable_bytes, reader.read_u16()?)?)?;
+            match format {
+                1 => {
+                    let delta = reader.read_u16()? as usize;
+                    for glyph_index in coverage {
+                        substitutions
+                            .entry(glyph_index)
+                            .or_insert((glyph_index + delta) % 65536);
+                    }
+                }
+                2 => {
+                    let glyph_count = reader.read_u16()? as usize;
+                    for index in 0..glyph_count.min(coverage.len()) {
+                        let substitute = reader.read_u16()? as usize;
+                        substitutions.entry(coverage[index]).or_insert(substitute);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(substitutions)
+}
+
+// the lookups of the given features, over all scripts and languages, in lookup list order
+fn parse_feature_lookup_indices(bytes: &[u8], feature_tags: &[&[u8; 4]]) -> Result<Vec<usize>> {
+    let mut reader = Reader::new(bytes);
+    reader.skip(6)?;
+    let feature_list_bytes = offset_bytes(bytes, reader.read_u16()?)?;
+    let mut reader = Reader::new(feature_list_bytes);
+    let feature_count = reader.read_u16()? as usize;
+    let mut lookup_indices = Vec::new();
+    for _ in 0..feature_count {
+        let tag = reader.read_u32()?.to_be_bytes();
+        let feature_bytes = offset_bytes(feature_list_bytes, reader.read_u16()?)?;
+        if !feature_tags.contains(&&tag) {
+            continue;
+        }
+        let mut reader = Reader::new(feature_bytes);
+        reader.skip(2)?;
+        let lookup_index_count = reader.read_u16()? as usize;
+        for _ in 0..lookup_index_count {
+            lookup_indices.push(reader.read_u16()? as usize);
+        }
+    }
+    lookup_indices.sort();
+    lookup_indices.dedup();
+    Ok(lookup_indices)
+}
+
+// the subtables of a lookup with their lookup type, looking through extension subtables
+fn parse_lookup_subtables(
+    bytes: &[u8],
+    lookup_index: usize,
+    extension_lookup_type: u16,
+) -> Result<Vec<(u16, &[u8])>> {
+    let mut reader = Reader::new(bytes);
+    reader.skip(8)?;
+    let lookup_list_bytes = offset_bytes(bytes, reader.read_u16()?)?;
+    let mut reader = Reader::new(lookup_list_bytes);
+    let lookup_count = reader.read_u16()? as usize;
+    if lookup_index >= lookup_count {
+        return Err(Error);
+    }
+    reader.skip(lookup_index * 2)?;
+    let lookup_bytes = offset_bytes(lookup_list_bytes, reader.read_u16()?)?;
+    let mut reader = Reader::new(lookup_bytes);
+    let lookup_type = reader.read_u16()?;
+    reader.skip(2)?;
+    let subtable_count = reader.read_u16()? as usize;
+    let mut subtables = Vec::new();
+    for _ in 0..subtable_count {
+        let subtable_bytes = offset_bytes(lookup_bytes, reader.read_u16()?)?;
+        if lookup_type == extension_lookup_type {
+            let mut reader = Reader::new(subtable_bytes);
+            reader.skip(2)?;
+            let lookup_type = reader.read_u16()?;
+            let offset = reader.read_u32()? as usize;
+            subtables.push((lookup_type, subtable_bytes.get(offset..).ok_or(Error)?));
+        } else {
+            subtables.push((lookup_type, subtable_bytes));
+        }
+    }
+    Ok(subtables)
+}
+
+// the covered glyphs, in coverage index order
+fn parse_coverage(bytes: &[u8]) -> Result<Vec<usize>> {
+    let mut reader = Reader::new(bytes);
+    let format = reader.read_u16()?;
+    let count = reader.read_u16()? as usize;
+    let mut glyph_indices = Vec::new();
+    match format {
+        1 => {
+            for _ in 0..count {
+                glyph_indices.push(reader.read_u16()? as usize);
+            }
+        }
+        2 => {
+            for _ in 0..count {
+                let start = reader.read_u16()? as usize;
+                let end = reader.read_u16()? as usize;
+                let start_coverage_index = reader.read_u16()? as usize;
+                if start_coverage_index != glyph_indices.len() {
+                    return Err(Error);
+                }
+                glyph_indices.extend(start..=end);
+            }
+        }
+        _ => return Err(Error),
+    }
+    Ok(glyph_indices)
+}
+
+fn parse_class_def(bytes: &[u8]) -> Result<HashMap<usize, usize>> {
+    let mut reader = Reader::new(bytes);
+    let format = reader.read_u16()?;
+    let mut classes = HashMap::new();
+    match format {
+        1 => {
+            let start = reader.read_u16()? as usize;
+            let count = reader.read_u16()? as usize;
+            for index in 0..count {
+                classes.insert(start + index, reader.read_u16()? as usize);
+            }
+        }
+        2 => {
+            let count = reader.read_u16()? as usize;
+            for _ in 0..count {
+                let start = reader.read_u16()? as usize;
+                let end = reader.read_u16()? as usize;
+                let class = reader.read_u16()? as usize;
+                for glyph_index in start..=end {
+                    classes.insert(glyph_index, class);
+                }
+            }
+        }
+        _ => return Err(Error),
+    }
+    Ok(classes)
+}
+
+fn value_record_size(value_format: u16) -> usize {
+    (value_format & 0xFF).count_ones() as usize * 2
+}
+
+fn read_value_record_x_advance(reader: &mut Reader, value_format: u16) -> Result<f32> {
+    let mut x_advance = 0.0;
+    for bit in 0..8 {
+        if value_format & (1 << bit) != 0 {
+            let value = reader.read_i16()?;
+            if bit == 2 {
+                x_advance = value as f32;
+            }
+        }
+    }
+    Ok(x_advance)
+}
+
+fn offset_bytes(bytes: &[u8], offset: u16) -> Result<&[u8]> {
+    bytes.get(offset as usize..).ok_or(Error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16s(values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes().to_vec())
+            .collect()
+    }
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes().to_vec())
+            .collect()
+    }
+
+    // a cmap table with the given (platform id, encoding id, subtable) records
+    fn cmap(subtables: &[(u16, u16, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = u16s(&[0, subtables.len() as u16]);
+        let mut offset = 4 + subtables.len() * 8;
+        for (platform_id, encoding_id, subtable) in subtables {
+            bytes.extend(u16s(&[*platform_id, *encoding_id]));
+            bytes.extend(u32s(&[offset as u32]));
+            offset += subtable.len();
+        }
+        for (_, _, subtable) in subtables {
+            bytes.extend(subtable);
+        }
+        bytes
+    }
+
+    // (start char code, end char code, id delta) segments, without a glyph id array
+    fn format_4(segments: &[(u16, u16, u16)]) -> Vec<u8> {
+        let seg_count = segments.len() as u16;
+        let mut bytes = u16s(&[4, 0, 0, seg_count * 2, 0, 0, 0]);
+        bytes.extend(u16s(&segments.iter().map(|s| s.1).collect::<Vec<_>>()));
+        bytes.extend(u16s(&[0]));
+        bytes.extend(u16s(&segments.iter().map(|s| s.0).collect::<Vec<_>>()));
+        bytes.extend(u16s(&segments.iter().map(|s| s.2).collect::<Vec<_>>()));
+        bytes.extend(u16s(&vec![0; segments.len()]));
+        bytes
+    }
+
+    // (start char code, end char code, glyph index) groups
+    fn format_12_or_13(format: u16, groups: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut bytes = u16s(&[format, 0]);
+        bytes.extend(u32s(&[
+            16 + groups.len() as u32 * 12,
+            0,
+            groups.len() as u32,
+        ]));
+        for (start, end, glyph_index) in groups {
+            bytes.extend(u32s(&[*start, *end, *glyph_index]));
+        }
+        bytes
+    }
+
+    #[test]
+    fn format_4_maps_with_the_id_delta() {
+        let map = parse_char_code_to_glyph_index_map(&cmap(&[(
+            3,
+            1,
+            // a delta of -64, glyph indices wrap around at 65536
+            format_4(&[(65, 67, 0xFFC0), (0xFFFF, 0xFFFF, 1)]),
+        )]))
+        .unwrap();
+        assert_eq!(map.get(65), 1);
+        assert_eq!(map.get(67), 3);
+        assert_eq!(map.get(68), 0);
+    }
+
+    #[test]
+    fn format_4_segments_have_to_ascend() {
+        let overlapping = format_4(&[(65, 70, 0), (70, 80, 0)]);
+        assert!(parse_char_code_to_glyph_index_map(&cmap(&[(3, 1, overlapping)])).is_err());
+        let descending = format_4(&[(80, 90, 0), (65, 70, 0)]);
+        assert!(parse_char_code_to_glyph_index_map(&cmap(&[(3, 1, descending)])).is_err());
+    }
+
+    #[test]
+    fn format_12_groups_have_to_ascend() {
+        let groups = format_12_or_13(12, &[(0x1F600, 0x1F64F, 10), (0x20000, 0x20000, 100)]);
+        let map = parse_char_code_to_glyph_index_map(&cmap(&[(3, 10, groups)])).unwrap();
+        assert_eq!(map.get(0x1F601), 11);
+        assert_eq!(map.get(0x20000), 100);
+        assert_eq!(map.get(0x1F650), 0);
+        let descending = format_12_or_13(12, &[(0x20000, 0x20000, 100), (0x1F600, 0x1F64F, 10)]);
+        assert!(parse_char_code_to_glyph_index_map(&cmap(&[(3, 10, descending)])).is_err());
+        let backwards = format_12_or_13(12, &[(0x1F64F, 0x1F600, 10)]);
+        assert!(parse_char_code_to_glyph_index_map(&cmap(&[(3, 10, backwards)])).is_err());
+    }
+
+    #[test]
+    fn format_13_groups_map_to_one_glyph() {
+        let groups = format_12_or_13(13, &[(0, 0x10FFFF, 3)]);
+        let map = parse_char_code_to_glyph_index_map(&cmap(&[(0, 6, groups)])).unwrap();
+        assert_eq!(map.get(65), 3);
+        assert_eq!(map.get(0x10FFFF), 3);
+    }
+
+    #[test]
+    fn full_unicode_subtables_are_preferred() {
+        let bmp = || format_4(&[(65, 65, 0)]);
+        let full = || format_12_or_13(12, &[(65, 65, 12)]);
+        let last_resort = || format_12_or_13(13, &[(65, 65, 13)]);
+        let pick = |subtables: &[(u16, u16, Vec<u8>)]| {
+            parse_char_code_to_glyph_index_map(&cmap(subtables))
+                .unwrap()
+                .get(65)
+        };
+        assert_eq!(pick(&[(3, 1, bmp()), (3, 10, full())]), 12);
+        assert_eq!(pick(&[(3, 10, full()), (3, 1, bmp())]), 12);
+        assert_eq!(pick(&[(3, 1, bmp()), (0, 6, last_resort())]), 13);
+        assert_eq!(pick(&[(0, 6, last_resort()), (0, 4, full())]), 12);
+        // mac roman and symbol encodings aren't unicode
+        assert_eq!(pick(&[(1, 0, full()), (3, 0, full()), (3, 1, bmp())]), 65);
+        assert!(parse_char_code_to_glyph_index_map(&cmap(&[(1, 0, full())])).is_err());
+    }
+
+    // a kern table with (coverage, pairs) subtables
+    fn kern(subtables: &[(u16, &[(u16, u16, i16)])]) -> Vec<u8> {
+        let mut bytes = u16s(&[0, subtables.len() as u16]);
+        for (coverage, pairs) in subtables {
+            let length = 14 + pairs.len() as u16 * 6;
+            bytes.extend(u16s(&[0, length, *coverage, pairs.len() as u16, 0, 0, 0]));
+            for (left, right, value) in pairs.iter() {
+                bytes.extend(u16s(&[*left, *right, *value as u16]));
+            }
+        }
+        bytes
+    }
+
+    enum Field {
+        Value(u16),
+        Offset(Vec<u8>),
+    }
+    use Field::*;
+
+    // a table of u16 fields, with the tables its offsets point to appended in order
+    fn table(fields: &[Field]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut children: Vec<u8> = Vec::new();
+        let mut offset = fields.len() * 2;
+        for field in fields {
+            match field {
+                Value(value) => bytes.extend(u16s(&[*value])),
+                Offset(child) => {
+                    bytes.extend(u16s(&[offset as u16]));
+                    offset += child.len();
+                    children.extend(child);
+                }
+            }
+        }
+        bytes.extend(children);
+        bytes
+    }
+
+    // a GPOS or GSUB table with (tag, lookup indices) features and (lookup type, subtables) lookups
+    fn layout_table(features: &[(&[u8; 4], &[u16])], lookups: &[(u16, Vec<Vec<u8>>)]) -> Vec<u8> {
+        let mut feature_list = vec![Value(features.len() as u16)];
+        for (tag, lookup_indices) in features {
+            feature_list.push(Value(u16::from_be_bytes([tag[0], tag[1]])));
+            feature_list.push(Value(u16::from_be_bytes([tag[2], tag[3]])));
+            let mut feature = vec![Value(0), Value(lookup_indices.len() as u16)];
+            feature.extend(lookup_indices.iter().map(|index| Value(*index)));
+            feature_list.push(Offset(table(&feature)));
+        }
+        let mut lookup_list = vec![Value(lookups.len() as u16)];
+        for (lookup_type, subtables) in lookups {
+            let mut lookup = vec![Value(*lookup_type), Value(0), Value(subtables.len() as u16)];
+            lookup.extend(subtables.iter().map(|subtable| Offset(subtable.clone())));
+            lookup_list.push(Offset(table(&lookup)));
+        }
+        // the script list is never read
+        table(&[
+            Value(1),
+            Value(0),
+            Value(0),
+            Offset(table(&feature_list)),
+            Offset(table(&lookup_list)),
+        ])
+    }
+
+    fn coverage(glyph_indices: &[u16]) -> Vec<u8> {
+        let mut bytes = u16s(&[1, glyph_indices.len() as u16]);
+        bytes.extend(u16s(glyph_indices));
+        bytes
+    }
+
+    fn class_def(classes: &[(u16, u16)]) -> Vec<u8> {
+        let mut bytes = u16s(&[2, classes.len() as u16]);
+        for (glyph_index, class) in classes {
+            bytes.extend(u16s(&[*glyph_index, *glyph_index, *class]));
+        }
+        bytes
+    }
+
+    // pair sets of (second glyph, x advance), with an x placement in front of the advance and
+    // a value for the second glyph behind it, which are both skipped
+    fn pair_pos_format_1(pair_sets: &[(u16, &[(u16, i16)])]) -> Vec<u8> {
+        let first_glyphs: Vec<u16> = pair_sets.iter().map(|(first, _)| *first).collect();
+        let mut fields = vec![
+            Value(1),
+            Offset(coverage(&first_glyphs)),
+            Value(0x0005),
+            Value(0x0004),
+            Value(pair_sets.len() as u16),
+        ];
+        for (_, pairs) in pair_sets {
+            let mut pair_set = vec![pairs.len() as u16];
+            for (second, x_advance) in pairs.iter() {
+                pair_set.extend(&[*second, 99, *x_advance as u16, 77]);
+            }
+            fields.push(Offset(u16s(&pair_set)));
+        }
+        table(&fields)
+    }
+
+    fn pair_pos_format_2(
+        covered: &[u16],
+        first_classes: &[(u16, u16)],
+        second_classes: &[(u16, u16)],
+        first_class_count: u16,
+        values: &[i16],
+    ) -> Vec<u8> {
+        let mut fields = vec![
+            Value(2),
+            Offset(coverage(covered)),
+            Value(0x0004),
+            Value(0),
+            Offset(class_def(first_classes)),
+            Offset(class_def(second_classes)),
+            Value(first_class_count),
+            Value(values.len() as u16 / first_class_count),
+        ];
+        fields.extend(values.iter().map(|value| Value(*value as u16)));
+        table(&fields)
+    }
+
+    // ligature sets of (components after the first glyph, ligature glyph)
+    fn ligature_subst(ligature_sets: &[(u16, &[(&[u16], u16)])]) -> Vec<u8> {
+        let first_glyphs: Vec<u16> = ligature_sets.iter().map(|(first, _)| *first).collect();
+        let mut fields = vec![
+            Value(1),
+            Offset(coverage(&first_glyphs)),
+            Value(ligature_sets.len() as u16),
+        ];
+        for (_, ligatures) in ligature_sets {
+            let mut ligature_set = vec![Value(ligatures.len() as u16)];
+            for (components, glyph_index) in ligatures.iter() {
+                let mut ligature = vec![*glyph_index, components.len() as u16 + 1];
+                ligature.extend(components.iter());
+                ligature_set.push(Offset(u16s(&ligature)));
+            }
+            fields.push(Offset(table(&ligature_set)));
+        }
+        table(&fields)
+    }
+
+    #[test]
+    fn kern_format_0_pairs_of_earlier_subtables_win() {
+        let kerning_pairs = parse_kern_table(&kern(&[
+            (0x0001, &[(1, 2, -50), (3, 4, 20)]),
+            // vertical, cross stream and format 2 subtables are skipped
+            (0x0000, &[(5, 6, -10)]),
+            (0x0005, &[(5, 6, -10)]),
+            (0x0201, &[(5, 6, -10)]),
+            (0x0001, &[(1, 2, -99), (7, 8, -5)]),
+        ]))
+        .unwrap();
+        assert_eq!(kerning_pairs.len(), 3);
+        assert_eq!(kerning_pairs[&(1, 2)], -50.0);
+        assert_eq!(kerning_pairs[&(3, 4)], 20.0);
+        assert_eq!(kerning_pairs[&(7, 8)], -5.0);
+    }
+
+    #[test]
+    fn gpos_pair_format_1_in_lookup_order() {
+        let gpos = layout_table(
+            &[(b"mark", &[2]), (b"kern", &[1, 0])],
+            &[
+                (2, vec![pair_pos_format_1(&[(1, &[(2, -50)])])]),
+                (
+                    2,
+                    vec![pair_pos_format_1(&[
+                        (1, &[(2, -99), (3, -30)]),
+                        (4, &[(5, 10)]),
+                    ])],
+                ),
+                (2, vec![pair_pos_format_1(&[(6, &[(7, -1)])])]),
+            ],
+        );
+        let (kerning_pairs, kerning_classes) = parse_gpos_kerning(&gpos).unwrap();
+        assert!(kerning_classes.is_empty());
+        assert_eq!(kerning_pairs.len(), 3);
+        assert_eq!(kerning_pairs[&(1, 2)], -50.0);
+        assert_eq!(kerning_pairs[&(1, 3)], -30.0);
+        assert_eq!(kerning_pairs[&(4, 5)], 10.0);
+    }
+
+    #[test]
+    fn gpos_pair_format_2_falls_back_to_class_0() {
+        // glyph 3 is covered without a first class, glyph 12 has no second class
+        let gpos = layout_table(
+            &[(b"kern", &[0])],
+            &[(
+                2,
+                vec![pair_pos_format_2(
+                    &[1, 2, 3],
+                    &[(1, 1), (2, 1)],
+                    &[(10, 1), (11, 2)],
+                    2,
+                    &[-1, -2, -3, -10, -20, -30],
+                )],
+            )],
+        );
+        let (kerning_pairs, kerning_classes) = parse_gpos_kerning(&gpos).unwrap();
+        assert!(kerning_pairs.is_empty());
+        assert_eq!(kerning_classes.len(), 1);
+        let classes = &kerning_classes[0];
+        assert_eq!(classes.get(1, 10), Some(-20.0));
+        assert_eq!(classes.get(2, 11), Some(-30.0));
+        assert_eq!(classes.get(1, 12), Some(-10.0));
+        assert_eq!(classes.get(3, 11), Some(-3.0));
+        assert_eq!(classes.get(3, 12), Some(-1.0));
+        assert_eq!(classes.get(4, 10), None);
+    }
+
+    #[test]
+    fn gsub_ligatures_longest_first() {
+        // f is 1, i is 2, l is 3
+        let gsub = layout_table(
+            &[(b"liga", &[0, 1]), (b"dlig", &[2])],
+            &[
+                (
+                    4,
+                    vec![ligature_subst(&[(1, &[(&[2], 11), (&[1, 2], 10)])])],
+                ),
+                (
+                    4,
+                    vec![ligature_subst(&[(1, &[(&[2], 20)]), (3, &[(&[3], 30)])])],
+                ),
+                (4, vec![ligature_subst(&[(1, &[(&[3], 40)])])]),
+            ],
+        );
+        let ligatures = parse_gsub_ligatures(&gsub).unwrap();
+        let glyph_indices = |first| -> Vec<usize> {
+            ligatures[&first]
+                .iter()
+                .map(|ligature| ligature.glyph_index)
+                .collect()
+        };
+        // the ffi ligature comes first, and fi of the earlier lookup wins a tie
+        assert_eq!(glyph_indices(1), vec![10, 11, 20]);
+        assert_eq!(ligatures[&1][0].components, vec![1, 2]);
+        assert_eq!(glyph_indices(3), vec![30]);
+        assert_eq!(ligatures.len(), 2);
+    }
+}
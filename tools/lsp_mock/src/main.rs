@@ -0,0 +1,163 @@
+// a tiny language server that speaks just enough LSP to exercise the makepad client.
+// every line containing FIXME gets a warning, hover echoes the position,
+// completion returns two fixed items and definition points at the top of the file.
+#![allow(non_snake_case)]
+
+use makepad_microserde::*;
+use std::io::{Write, BufRead};
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct MockHead {
+    id: Option<u64>,
+    method: Option<String>
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct MockTextDocument {
+    uri: String,
+    text: Option<String>
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct MockContentChange {
+    text: String
+}
+
+#[derive(DeJson, SerJson)]
+#[microserde(skip_unknown)]
+struct MockPosition {
+    line: u64,
+    character: u64
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct MockParams {
+    textDocument: Option<MockTextDocument>,
+    contentChanges: Option<Vec<MockContentChange>>,
+    position: Option<MockPosition>
+}
+
+#[derive(DeJson)]
+#[microserde(skip_unknown)]
+struct MockMsg {
+    params: Option<MockParams>
+}
+
+#[derive(SerJson)]
+struct MockRange {
+    start: MockPosition,
+    end: MockPosition
+}
+
+#[derive(SerJson)]
+struct MockDiagnostic {
+    range: MockRange,
+    severity: u64,
+    message: String
+}
+
+#[derive(SerJson)]
+struct MockPublishDiagnostics {
+    uri: String,
+    diagnostics: Vec<MockDiagnostic>
+}
+
+fn read_frame(reader: &mut dyn BufRead) -> Option<String> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok() ? == 0 {
+            return None
+        }
+        let line = line.trim_end();
+        if line.len() == 0 {
+            break
+        }
+        if line.starts_with("Content-Length:") {
+            content_length = line["Content-Length:".len()..].trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0u8; content_length ?];
+    reader.read_exact(&mut body).ok() ?;
+    String::from_utf8(body).ok()
+}
+
+fn write_frame(body: &str) {
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    let _ = write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body);
+    let _ = stdout.flush();
+}
+
+fn respond(id: u64, result: &str) {
+    write_frame(&format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"result\":{}}}", id, result));
+}
+
+fn publish_diagnostics(uri: &str, text: &str) {
+    let diagnostics = text.lines().enumerate().filter_map( | (line, text) | {
+        // no positionEncoding in our capabilities, so columns are utf-16
+        let character = text[..text.find("FIXME") ?].encode_utf16().count() as u64;
+        Some(MockDiagnostic {
+            range: MockRange {
+                start: MockPosition {line: line as u64, character: character},
+                end: MockPosition {line: line as u64, character: character + 5}
+            },
+            severity: 2,
+            message: "FIXME found".to_string()
+        })
+    }).collect();
+    let params = MockPublishDiagnostics {uri: uri.to_string(), diagnostics: diagnostics};
+    write_frame(&format!("{{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{}}}", params.serialize_json()));
+}
+
+fn main() {
+    let stdin = std::io::stdin();
+    let mut stdin = stdin.lock();
+    while let Some(body) = read_frame(&mut stdin) {
+        let head: MockHead = match DeJson::deserialize_json(&body) {
+            Ok(head) => head,
+            Err(e) => {
+                eprintln!("lsp_mock cannot parse {:?}", e);
+                continue
+            }
+        };
+        let msg: MockMsg = DeJson::deserialize_json(&body).unwrap_or(MockMsg {params: None});
+        let params = msg.params;
+        let method = head.method.unwrap_or(String::new());
+        match (head.id, method.as_ref()) {
+            (Some(id), "initialize") => respond(id, "{\"capabilities\":{\"textDocumentSync\":1,\"hoverProvider\":true,\"completionProvider\":{},\"definitionProvider\":true}}"),
+            (Some(id), "shutdown") => respond(id, "null"),
+            (None, "exit") => return,
+            (None, "textDocument/didOpen") | (None, "textDocument/didChange") => if let Some(params) = params {
+                if let Some(doc) = params.textDocument {
+                    let text = if let Some(mut changes) = params.contentChanges {
+                        changes.pop().map( | v | v.text)
+                    }
+                    else {
+                        doc.text
+                    };
+                    if let Some(text) = text {
+                        publish_diagnostics(&doc.uri, &text);
+                    }
+                }
+            },
+            (Some(id), "textDocument/hover") => {
+                let pos = params.and_then( | v | v.position).unwrap_or(MockPosition {line: 0, character: 0});
+                respond(id, &format!("{{\"contents\":{{\"kind\":\"plaintext\",\"value\":\"hover {}:{}\"}}}}", pos.line, pos.character));
+            },
+            (Some(id), "textDocument/completion") => {
+                respond(id, "{\"isIncomplete\":false,\"items\":[{\"label\":\"mock_one\"},{\"label\":\"mock_two\",\"detail\":\"fn()\",\"insertText\":\"mock_two()\"}]}");
+            },
+            (Some(id), "textDocument/definition") => {
+                let uri = params.and_then( | v | v.textDocument).map( | v | v.uri).unwrap_or(String::new());
+                respond(id, &format!("{{\"uri\":{},\"range\":{{\"start\":{{\"line\":0,\"character\":0}},\"end\":{{\"line\":0,\"character\":0}}}}}}", uri.serialize_json()));
+            },
+            (Some(id), _) => respond(id, "null"),
+            _ => ()
+        }
+    }
+}
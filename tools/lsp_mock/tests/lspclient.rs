@@ -0,0 +1,54 @@
+use makepad_hub::*;
+use std::sync::{mpsc, Arc, Mutex};
+
+#[test]
+fn lsp_client_against_mock() {
+    let (tx_pump, rx_pump) = mpsc::channel::<(HubAddr, ToHubMsg)>();
+    let route_send = HubRouteSend::Direct {
+        uid_alloc: Arc::new(Mutex::new(0)),
+        tx_pump: tx_pump,
+        own_addr: HubAddr::None
+    };
+    let config = LspServerConfig {
+        command: env!("CARGO_BIN_EXE_lsp_mock").to_string(),
+        args: vec![],
+        extensions: vec![".rs".to_string()]
+    };
+    let root = env!("CARGO_MANIFEST_DIR");
+    let mut client = LspClient::start(&config, route_send, "main", "mock", root).expect("cannot start lsp_mock");
+
+    let abs_path = format!("{}/src/lib.rs", root);
+    let uid = HubUid {addr: HubAddr::None, id: 7};
+    assert!(client.document_sync(&abs_path, "fn main(){\n    // FIXME\n}\n", false));
+    assert!(client.request(LspRequest::Hover, HubAddr::None, uid, "main/mock/src/lib.rs", &abs_path, LspPos {line: 1, character: 4}));
+    assert!(client.request(LspRequest::Completion, HubAddr::None, uid, "main/mock/src/lib.rs", &abs_path, LspPos {line: 1, character: 4}));
+    assert!(client.request(LspRequest::Definition, HubAddr::None, uid, "main/mock/src/lib.rs", &abs_path, LspPos {line: 1, character: 4}));
+
+    let mut got = 0;
+    while got < 4 {
+        let (_, msg) = rx_pump.recv_timeout(std::time::Duration::from_secs(10)).expect("lsp_mock did not answer");
+        match msg.msg {
+            HubMsg::LspDiagnostics {path, diagnostics, ..} => {
+                assert_eq!(path, "main/mock/src/lib.rs");
+                assert_eq!(diagnostics.len(), 1);
+                assert_eq!(diagnostics[0].start, LspPos {line: 1, character: 7});
+                assert_eq!(diagnostics[0].severity, LspSeverity::Warning);
+            },
+            HubMsg::LspHoverResponse {uid: resp_uid, text, ..} => {
+                assert_eq!(resp_uid, uid);
+                assert_eq!(text, Some("hover 1:4".to_string()));
+            },
+            HubMsg::LspCompletionResponse {items, ..} => {
+                assert_eq!(items.len(), 2);
+                assert_eq!(items[1].insert_text, "mock_two()");
+            },
+            HubMsg::LspDefinitionResponse {locations, ..} => {
+                assert_eq!(locations.len(), 1);
+                assert_eq!(locations[0].path, "main/mock/src/lib.rs");
+            },
+            _ => panic!("unexpected message")
+        }
+        got += 1;
+    }
+    client.terminate();
+}
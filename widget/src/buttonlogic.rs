@@ -46,6 +46,13 @@ impl ButtonLogic {
                     _ => ()
                 }
             },
+            Event::AccessAction(ae) => if let AccessAction::Click = ae.action {
+                return ButtonEvent::Clicked;
+            },
+            Event::KeyDown(ke) => match ke.key_code {
+                KeyCode::Return | KeyCode::Space => return ButtonEvent::Clicked,
+                _ => ()
+            },
             Event::FingerUp(fe) => if fe.is_over {
                 if !fe.is_touch {cb(cx, ButtonLogicEvent::Over, area)}
                 else {cb(cx, ButtonLogicEvent::Default, area)}
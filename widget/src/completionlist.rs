@@ -0,0 +1,429 @@
+use makepad_render::*;
+use crate::scrollview::*;
+use crate::listlogic::*;
+use crate::scrollshadow::*;
+use crate::textbuffer::*;
+use crate::textcursor::*;
+
+// a popup list of completions, hosted by the texteditor and anchored under its last cursor
+#[derive(Clone)]
+pub struct CompletionList {
+    pub overlay: View,
+    pub view: ScrollView,
+    pub list: ListLogic,
+    pub item_bg: Quad,
+    pub label_text: Text,
+    pub detail_text: Text,
+    pub shadow: ScrollShadow,
+    pub items: Vec<CompletionItem>,
+    pub filtered: Vec<usize>,
+    pub filter: String,
+    pub is_open: bool,
+    pub anchor: Vec2,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub detail: Option<String>,
+    pub insert_text: String
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompletionListEvent {
+    None,
+    Navigate,
+    Insert(CompletionItem),
+    Close
+}
+
+impl CompletionList {
+    pub fn new(cx: &mut Cx) -> Self {
+        Self {
+            overlay: View::proto_overlay(cx),
+            view: ScrollView {
+                scroll_h: None,
+                ..ScrollView::new(cx)
+            },
+            list: ListLogic {
+                multi_select: false,
+                ..ListLogic::default()
+            },
+            item_bg: Quad {z: 20., ..Quad::new(cx)},
+            label_text: Text {z: 20.1, ..Text::new(cx)},
+            detail_text: Text {z: 20.1, ..Text::new(cx)},
+            shadow: ScrollShadow {z: 20.2, ..ScrollShadow::new(cx)},
+            items: Vec::new(),
+            filtered: Vec::new(),
+            filter: String::new(),
+            is_open: false,
+            anchor: Vec2::default(),
+        }
+    }
+
+    pub fn style(cx: &mut Cx) {
+        live!(cx, r#"
+            self::layout_item: Layout {
+                walk: Walk {width: Fill, height: Fix(20.)},
+                align: {fx: 0.0, fy: 0.5},
+                padding: {l: 6., t: 0., r: 6., b: 0.},
+                line_wrap: None,
+            }
+
+            self::text_style_item: TextStyle {
+                ..makepad_widget::widgetstyle::text_style_fixed
+            }
+
+            self::list_width: 320.;
+            self::max_rows: 10.;
+
+            self::color_label: #d;
+            self::color_detail: #8;
+            self::color_bg: #25;
+            self::color_bg_over: #38;
+            self::color_bg_selected: #11466e;
+        "#)
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.is_open && self.filtered.len() > 0
+    }
+
+    pub fn open(&mut self, cx: &mut Cx, filter: &str, items: Vec<CompletionItem>) {
+        self.items = items;
+        self.filter = filter.to_string();
+        self.is_open = true;
+        self.refilter(cx);
+    }
+
+    // providers that answer later (a language server) add to whats already there
+    pub fn add_items(&mut self, cx: &mut Cx, items: Vec<CompletionItem>) {
+        if !self.is_open {
+            return
+        }
+        for item in items {
+            if self.items.iter().find( | v | v.label == item.label).is_none() {
+                self.items.push(item);
+            }
+        }
+        self.refilter(cx);
+    }
+
+    pub fn set_filter(&mut self, cx: &mut Cx, filter: &str) {
+        if self.filter != filter {
+            self.filter = filter.to_string();
+            self.refilter(cx);
+        }
+    }
+
+    pub fn close(&mut self, cx: &mut Cx) {
+        if self.is_open {
+            self.is_open = false;
+            self.items.truncate(0);
+            self.filtered.truncate(0);
+            self.view.redraw_view_area(cx);
+        }
+    }
+
+    fn refilter(&mut self, cx: &mut Cx) {
+        let filter: Vec<char> = self.filter.chars().flat_map( | v | v.to_lowercase()).collect();
+        let mut scored: Vec<(isize, usize)> = Vec::new();
+        for (index, item) in self.items.iter().enumerate() {
+            if let Some(score) = Self::fuzzy_score(&filter, &item.label) {
+                // dont offer what is already typed out
+                if item.label != self.filter {
+                    scored.push((score, index));
+                }
+            }
+        }
+        let items = &self.items;
+        scored.sort_by( | a, b | {
+            b.0.cmp(&a.0)
+                .then(items[a.1].label.len().cmp(&items[b.1].label.len()))
+                .then(items[a.1].label.cmp(&items[b.1].label))
+        });
+        self.filtered = scored.into_iter().map( | (_, index) | index).collect();
+        self.list.set_list_len(0);
+        self.list.set_list_len(self.filtered.len());
+        if self.filtered.len() > 0 {
+            self.select(cx, ListSelect::Single(0));
+        }
+        self.view.redraw_view_area(cx);
+    }
+
+    // subsequence match, consecutive runs and word starts score higher
+    pub fn fuzzy_score(filter: &[char], label: &str) -> Option<isize> {
+        let mut score = 0;
+        let mut fi = 0;
+        let mut last_match = None;
+        let mut prev = '_';
+        for (li, c) in label.chars().enumerate() {
+            if fi >= filter.len() {
+                break;
+            }
+            let lc = c.to_lowercase().next().unwrap_or(c);
+            if lc == filter[fi] {
+                score += 1;
+                if li == 0 {
+                    score += 8;
+                }
+                else if prev == '_' || (c.is_uppercase() && !prev.is_uppercase()) {
+                    score += 4;
+                }
+                if let Some(last_match) = last_match {
+                    if last_match + 1 == li {
+                        score += 3;
+                    }
+                }
+                last_match = Some(li);
+                fi += 1;
+            }
+            prev = c;
+        }
+        if fi < filter.len() {
+            return None
+        }
+        Some(score - label.len() as isize / 8)
+    }
+
+    pub fn get_selected_item(&self) -> Option<&CompletionItem> {
+        let index = *self.list.selection.first() ?;
+        Some(&self.items[*self.filtered.get(index) ?])
+    }
+
+    fn handle_list_item_anim(cx: &mut Cx, item_event: ListLogicEvent, item: &mut ListItem) {
+        match item_event {
+            ListLogicEvent::Animate(ae) => {
+                item.animator.calc_area(cx, item.animator.area, ae.time);
+            },
+            ListLogicEvent::AnimEnded => {
+                item.animator.end();
+            },
+            ListLogicEvent::Select | ListLogicEvent::Over => {
+                item.animator.play_anim(cx, Self::get_over_anim(cx, item.is_selected));
+            },
+            ListLogicEvent::Deselect | ListLogicEvent::Out => {
+                item.animator.play_anim(cx, Self::get_default_anim(cx, item.is_selected));
+            },
+            ListLogicEvent::Cleanup => {
+                item.animator.play_anim(cx, Anim {
+                    play: Play::Cut {duration: 0.01},
+                    ..Self::get_default_anim(cx, item.is_selected)
+                });
+            }
+        }
+    }
+
+    fn select(&mut self, cx: &mut Cx, select: ListSelect) {
+        self.list.scroll_item_in_view = select.item_index();
+        self.list.handle_list_logic(cx, &mut Event::None, select, false, | cx, item_event, item, _ | {
+            Self::handle_list_item_anim(cx, item_event, item)
+        });
+        self.view.redraw_view_area(cx);
+    }
+
+    // the host calls this with its keydowns while the list is visible, anything but None means we ate the key
+    pub fn handle_completion_key(&mut self, cx: &mut Cx, ke: &KeyEvent) -> CompletionListEvent {
+        if !self.is_visible() {
+            return CompletionListEvent::None
+        }
+        match ke.key_code {
+            KeyCode::ArrowDown => {
+                let select = self.list.get_next_single_selection();
+                self.select(cx, select);
+                CompletionListEvent::Navigate
+            },
+            KeyCode::ArrowUp => {
+                let select = self.list.get_prev_single_selection();
+                self.select(cx, select);
+                CompletionListEvent::Navigate
+            },
+            KeyCode::PageDown => {
+                let rows = live_float!(cx, self::max_rows) as usize;
+                let last = self.list.selection.first().cloned().unwrap_or(0);
+                self.select(cx, ListSelect::Single((last + rows).min(self.filtered.len() - 1)));
+                CompletionListEvent::Navigate
+            },
+            KeyCode::PageUp => {
+                let rows = live_float!(cx, self::max_rows) as usize;
+                let last = self.list.selection.first().cloned().unwrap_or(0);
+                self.select(cx, ListSelect::Single(last.max(rows) - rows));
+                CompletionListEvent::Navigate
+            },
+            KeyCode::Return | KeyCode::Tab => {
+                if let Some(item) = self.get_selected_item().cloned() {
+                    self.close(cx);
+                    return CompletionListEvent::Insert(item)
+                }
+                CompletionListEvent::None
+            },
+            KeyCode::Escape => {
+                self.close(cx);
+                CompletionListEvent::Close
+            },
+            _ => CompletionListEvent::None
+        }
+    }
+
+    pub fn handle_completion_list(&mut self, cx: &mut Cx, event: &mut Event) -> CompletionListEvent {
+        if !self.is_visible() {
+            return CompletionListEvent::None
+        }
+        self.list.set_list_len(self.filtered.len());
+        self.list.handle_list_scroll_bars(cx, event, &mut self.view);
+
+        let le = self.list.handle_list_logic(cx, event, ListSelect::None, false, | cx, item_event, item, _ | {
+            Self::handle_list_item_anim(cx, item_event, item)
+        });
+        match le {
+            ListEvent::SelectSingle(_) | ListEvent::SelectDouble(_) => {
+                if let Some(item) = self.get_selected_item().cloned() {
+                    self.close(cx);
+                    return CompletionListEvent::Insert(item)
+                }
+            },
+            _ => ()
+        }
+        CompletionListEvent::None
+    }
+
+    // replaces the typed prefix in front of every cursor that has it with the completion
+    pub fn insert_completion(&self, item: &CompletionItem, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) {
+        let filter: Vec<char> = self.filter.chars().collect();
+        for cursor in &mut cursors.set {
+            if cursor.head != cursor.tail || cursor.head < filter.len() {
+                continue;
+            }
+            let start = cursor.head - filter.len();
+            if (0..filter.len()).all( | i | text_buffer.get_char(start + i) == filter[i]) {
+                cursor.tail = start;
+            }
+        }
+        cursors.replace_text(&item.insert_text, text_buffer, Some(TextUndoGrouping::Block));
+    }
+
+    pub fn get_default_anim(cx: &Cx, selected: bool) -> Anim {
+        Anim {
+            play: Play::Chain {duration: 0.01},
+            tracks: vec![
+                Track::Color {
+                    live_id: live_id!(makepad_render::quad::shader::color),
+                    ease: Ease::Lin,
+                    keys: vec![(1.0, if selected {
+                        live_color!(cx, self::color_bg_selected)
+                    } else {
+                        live_color!(cx, self::color_bg)
+                    })],
+                    cut_init: None
+                }
+            ]
+        }
+    }
+
+    pub fn get_over_anim(cx: &Cx, selected: bool) -> Anim {
+        Anim {
+            play: Play::Chain {duration: 0.02},
+            tracks: vec![
+                Track::Color {
+                    live_id: live_id!(makepad_render::quad::shader::color),
+                    ease: Ease::Lin,
+                    keys: vec![(0.0, if selected {
+                        live_color!(cx, self::color_bg_selected)
+                    } else {
+                        live_color!(cx, self::color_bg_over)
+                    })],
+                    cut_init: None
+                }
+            ]
+        }
+    }
+
+    pub fn draw_completion_list(&mut self, cx: &mut Cx) {
+        let row_height = live_layout!(cx, self::layout_item).walk.height.fixed();
+        let rows = if self.is_open {
+            self.filtered.len().min(live_float!(cx, self::max_rows) as usize)
+        } else {
+            0
+        };
+        // a closed list still draws its empty overlay, thats what clears it off the screen
+        if self.overlay.begin_view(cx, Layout {
+            abs_origin: Some(self.anchor),
+            walk: Walk::wh(Width::Fix(live_float!(cx, self::list_width)), Height::Fix(rows as f32 * row_height)),
+            ..Layout::default()
+        }).is_err() {
+            return
+        }
+        if rows == 0 {
+            self.overlay.end_view(cx);
+            return
+        }
+
+        self.list.set_list_len(self.filtered.len());
+        if self.list.begin_list(cx, &mut self.view, false, row_height).is_ok() {
+            self.label_text.text_style = live_text_style!(cx, self::text_style_item);
+            self.detail_text.text_style = live_text_style!(cx, self::text_style_item);
+            self.label_text.color = live_color!(cx, self::color_label);
+            self.detail_text.color = live_color!(cx, self::color_detail);
+
+            for i in self.list.start_item..self.list.end_item {
+                let item = &self.items[self.filtered[i]];
+                let list_item = &mut self.list.list_items[i];
+                let selected = list_item.is_selected;
+                list_item.animator.init(cx, | cx | Self::get_default_anim(cx, selected));
+                self.item_bg.color = list_item.animator.last_color(cx, live_id!(makepad_render::quad::shader::color));
+
+                let bg_inst = self.item_bg.begin_quad(cx, live_layout!(cx, self::layout_item));
+                self.label_text.draw_text(cx, &item.label);
+                if let Some(detail) = &item.detail {
+                    self.detail_text.draw_text(cx, " ");
+                    self.detail_text.draw_text(cx, detail);
+                }
+                let bg_area = self.item_bg.end_quad(cx, bg_inst);
+                list_item.animator.set_area(cx, bg_area);
+            }
+            self.list.walk_turtle_to_end(cx, row_height);
+            self.shadow.draw_shadow_top(cx);
+            self.list.end_list(cx, &mut self.view);
+        }
+        self.overlay.end_view(cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(filter: &str, label: &str) -> Option<isize> {
+        let filter: Vec<char> = filter.chars().flat_map( | v | v.to_lowercase()).collect();
+        CompletionList::fuzzy_score(&filter, label)
+    }
+
+    #[test]
+    fn prefix_beats_word_start_beats_scattered() {
+        let prefix = score("fo", "foo_bar").unwrap();
+        let word_start = score("fo", "bar_foo").unwrap();
+        let scattered = score("fo", "xfxo").unwrap();
+        assert!(prefix > word_start);
+        assert!(word_start > scattered);
+        // a camel case hump counts as a word start too
+        assert!(score("b", "fooBar").unwrap() > score("b", "foobar").unwrap());
+        // consecutive letters beat the same letters spread out
+        assert!(score("ab", "xabx").unwrap() > score("ab", "xaxb").unwrap());
+    }
+
+    #[test]
+    fn misses_and_order() {
+        assert_eq!(score("of", "foo"), None);
+        assert_eq!(score("foox", "foo"), None);
+        assert_eq!(score("", "foo"), Some(0));
+        // the same match in a shorter label wins
+        assert!(score("foo", "foo").unwrap() > score("foo", "foo_with_a_long_tail").unwrap());
+    }
+
+    #[test]
+    fn case_is_folded() {
+        assert_eq!(score("FB", "FooBar"), score("fb", "FooBar"));
+        assert!(score("fb", "FooBar").is_some());
+        assert!(score("fb", "FOO_BAR").is_some());
+    }
+}
@@ -188,5 +188,18 @@ impl DesktopButton {
         bg_inst.push_float(cx, ty.shader_float());
         self._bg_area = bg_inst.into();
         self.animator.set_area(cx, self._bg_area); // if our area changed, update animation
+        let label = match ty {
+            DesktopButtonType::WindowsMin => "Minimize",
+            DesktopButtonType::WindowsMax => "Maximize",
+            DesktopButtonType::WindowsMaxToggled => "Restore",
+            DesktopButtonType::WindowsClose => "Close",
+            DesktopButtonType::XRMode => "VR mode",
+            DesktopButtonType::Fullscreen => "Fullscreen",
+        };
+        cx.add_access_node(AccessNode {
+            actions: vec![AccessAction::Click],
+            area: self._bg_area,
+            ..AccessNode::new(AccessRole::Button, label)
+        });
     }
 }
@@ -2,6 +2,8 @@ use makepad_render::*;
 use crate::buttonlogic::*;
 use crate::desktopbutton::*;
 use crate::windowmenu::*;
+use crate::layoutdebugger::*;
+use crate::focusring::*;
 
 #[derive(Clone)]
 pub struct DesktopWindow {
@@ -26,6 +28,8 @@ pub struct DesktopWindow {
     
     pub window_menu: WindowMenu,
     pub default_menu: Menu,
+    pub layout_debugger: LayoutDebugger,
+    pub focus_ring: FocusRing,
     
     pub _last_menu: Option<Menu>,
     
@@ -65,6 +69,8 @@ impl DesktopWindow {
                     Menu::item("Quit App", Cx::command_quit()),
                 ]),
             ]),
+            layout_debugger: LayoutDebugger::new(cx),
+            focus_ring: FocusRing::new(cx),
             caption_text: Text::new(cx),
             //caption_bg_color: Color_bg_selected_over::id(cx),
             caption_bg: Quad::new(cx),
@@ -118,7 +124,13 @@ impl DesktopWindow {
         if let ButtonEvent::Clicked = self.close_btn.handle_button(cx, event) {
             self.window.close_window(cx);
         }
+        self.focus_ring.handle_focus_ring(cx, event);
         if let Some(window_id) = self.window.window_id {
+            match event {
+                Event::FingerHover(ev) if ev.window_id == window_id => self.layout_debugger.handle_layout_debugger(cx, event),
+                Event::FingerMove(ev) if ev.window_id == window_id => self.layout_debugger.handle_layout_debugger(cx, event),
+                _ => ()
+            }
             let is_for_other_window = match event {
                 Event::WindowCloseRequested(ev) => ev.window_id != window_id,
                 Event::WindowClosed(ev) => {
@@ -282,6 +294,9 @@ impl DesktopWindow {
             self.xr_btn.draw_desktop_button(cx, DesktopButtonType::XRMode);
         }
         
+        self.focus_ring.draw_focus_ring(cx, &self.pass);
+        self.layout_debugger.draw_layout_debugger(cx, &self.pass);
+        
         self.main_view.end_view(cx);
         
         self.pass.end_pass(cx);
@@ -314,6 +314,8 @@ where TItem: Clone
                         self.walk_uid += 1;
                         let tab_control = self.tab_controls.get_draw(cx, stack_top.uid, | _cx, tmpl | tmpl.clone());
                         
+                        // every panel is a focus trap, ended after its page is drawn
+                        cx.begin_focus_scope(FocusScope::Trap);
                         if let Ok(_) = tab_control.begin_tabs(cx) {
                             for (id, tab) in tabs.iter().enumerate() {
                                 tab_handler(cx, tab_control, tab, *current == id)
@@ -327,11 +329,13 @@ where TItem: Clone
                             }
                             tab_control.end_tab_page(cx);
                         }
+                        cx.end_focus_scope();
                         None
                     }
                     else {
                         let tab_control = self.tab_controls.get_draw(cx, stack_top.uid, | _cx, tmpl | tmpl.clone());
                         tab_control.end_tab_page(cx);
+                        cx.end_focus_scope();
                         None
                     }
                 },
@@ -0,0 +1,70 @@
+use makepad_render::*;
+
+// draws a ring around whatever has the key focus, but only once the keyboard moved it there.
+// clicking somewhere hides it again
+#[derive(Clone)]
+pub struct FocusRing {
+    pub view: View,
+    pub ring: Quad,
+}
+
+impl FocusRing {
+    pub fn new(cx: &mut Cx) -> Self {
+        Self {
+            view: View {
+                always_redraw: true,
+                ..View::proto_overlay(cx)
+            },
+            ring: Quad {
+                z: 40.,
+                ..Quad::new(cx)
+            },
+        }
+    }
+    
+    pub fn style(cx: &mut Cx) {
+        live!(cx, r#"
+            self::color_ring: #5a9ae0;
+            self::ring_outset: 1.5;
+            
+            self::shader_ring: Shader {
+                use makepad_render::quad::shader::*;
+                
+                fn pixel() -> vec4 {
+                    let cx = Df::viewport(pos * vec2(w, h));
+                    cx.box(1., 1., w - 2., h - 2., 3.);
+                    return cx.stroke(color, 1.5);
+                }
+            }
+        "#);
+    }
+    
+    pub fn handle_focus_ring(&mut self, cx: &mut Cx, event: &mut Event) {
+        match event {
+            Event::KeyFocus(_) | Event::FingerDown(_) => self.view.redraw_view_area(cx),
+            _ => ()
+        }
+    }
+    
+    pub fn draw_focus_ring(&mut self, cx: &mut Cx, pass: &Pass) {
+        if self.view.begin_view(cx, Layout {
+            abs_origin: Some(Vec2::default()),
+            ..Layout::default()
+        }).is_err() {
+            return
+        }
+        let rect = pass.pass_id.and_then( | pass_id | cx.get_focus_ring_rect(pass_id));
+        if let Some(rect) = rect {
+            let outset = live_float!(cx, self::ring_outset);
+            self.ring.shader = live_shader!(cx, self::shader_ring);
+            self.ring.color = live_color!(cx, self::color_ring);
+            self.ring.draw_quad_abs(cx, Rect {
+                x: rect.x - outset,
+                y: rect.y - outset,
+                w: rect.w + 2. * outset,
+                h: rect.h + 2. * outset
+            });
+        }
+        self.view.end_view(cx);
+    }
+}
@@ -0,0 +1,158 @@
+use makepad_render::*;
+
+// draws what cx.layout_debug recorded on top of a window, toggled with ctrl+shift+L.
+// every turtle gets an outline, the one under the mouse shows its margin and padding
+// and an inspector with the layout it came from
+#[derive(Clone)]
+pub struct LayoutDebugger {
+    pub view: View,
+    pub outline: Quad,
+    pub inspector_bg: Quad,
+    pub inspector_text: Text,
+    pub hover_pos: Option<Vec2>,
+}
+
+impl LayoutDebugger {
+    pub fn new(cx: &mut Cx) -> Self {
+        Self {
+            view: View {
+                always_redraw: true,
+                ..View::proto_overlay(cx)
+            },
+            outline: Quad {
+                z: 50.,
+                ..Quad::new(cx)
+            },
+            inspector_bg: Quad {
+                z: 50.1,
+                ..Quad::new(cx)
+            },
+            inspector_text: Text {
+                z: 50.2,
+                ..Text::new(cx)
+            },
+            hover_pos: None,
+        }
+    }
+    
+    pub fn style(cx: &mut Cx) {
+        live!(cx, r#"
+            self::color_outline: #5a8fd080;
+            self::color_collapsed: #e04040;
+            self::color_hover: #f0c040;
+            self::color_padding: #60c06040;
+            self::color_margin: #e0904040;
+            self::color_inspector_bg: #202020e8;
+            self::color_inspector_text: #d;
+            
+            self::layout_inspector: Layout {
+                padding: {l: 8., t: 6., r: 8., b: 6.},
+                direction: Down,
+                walk: Walk {width: Compute, height: Compute},
+            }
+            
+            self::text_style_inspector: TextStyle {
+                ..makepad_widget::widgetstyle::text_style_fixed
+            }
+            
+            self::shader_outline: Shader {
+                use makepad_render::quad::shader::*;
+                
+                instance fill: float;
+                
+                fn pixel() -> vec4 {
+                    let p = pos * vec2(w, h);
+                    let edge = min(min(p.x, w - p.x), min(p.y, h - p.y));
+                    if edge < 1. {
+                        return vec4(color.rgb * color.a, color.a);
+                    }
+                    return vec4(color.rgb * color.a * fill, color.a * fill);
+                }
+            }
+        "#);
+    }
+    
+    pub fn handle_layout_debugger(&mut self, cx: &mut Cx, event: &mut Event) {
+        if !cx.layout_debug.enabled {
+            self.hover_pos = None;
+            return
+        }
+        match event {
+            Event::FingerHover(fe) => {
+                self.hover_pos = Some(fe.abs);
+                self.view.redraw_view_area(cx);
+            },
+            Event::FingerMove(fe) => {
+                self.hover_pos = Some(fe.abs);
+                self.view.redraw_view_area(cx);
+            },
+            _ => ()
+        }
+    }
+    
+    fn draw_outline(&mut self, cx: &mut Cx, rect: Rect, color: Color, fill: f32) {
+        self.outline.color = color;
+        // collapsed turtles still get a visible sliver so you can find them
+        let inst = self.outline.draw_quad_abs(cx, Rect {w: rect.w.max(2.), h: rect.h.max(2.), ..rect});
+        inst.push_float(cx, fill);
+    }
+    
+    pub fn draw_layout_debugger(&mut self, cx: &mut Cx, pass: &Pass) {
+        if self.view.begin_view(cx, Layout {
+            abs_origin: Some(Vec2::default()),
+            ..Layout::default()
+        }).is_err() {
+            return
+        }
+        let pass_id = if let Some(pass_id) = pass.pass_id {pass_id} else {
+            self.view.end_view(cx);
+            return
+        };
+        if !cx.layout_debug.enabled {
+            self.view.end_view(cx);
+            return
+        }
+        
+        self.outline.shader = live_shader!(cx, self::shader_outline);
+        let turtles = cx.get_layout_debug_turtles(pass_id, self.view.view_id);
+        let hover = if let Some(pos) = self.hover_pos {cx.get_layout_debug_turtle_at(&turtles, pos)} else {None};
+        
+        for (index, rect) in &turtles {
+            let color = if cx.layout_debug.turtles[*index].is_collapsed() {
+                live_color!(cx, self::color_collapsed)
+            }
+            else {
+                live_color!(cx, self::color_outline)
+            };
+            self.draw_outline(cx, *rect, color, 0.);
+        }
+        
+        if let Some(hover) = hover {
+            let rect = turtles.iter().find( | (index, _) | *index == hover).unwrap().1;
+            let turtle = cx.layout_debug.turtles[hover].clone();
+            // the recorded rects are unscrolled, move margin and padding along with the hovered one
+            let (dx, dy) = (rect.x - turtle.rect.x, rect.y - turtle.rect.y);
+            let margin = turtle.margin_rect();
+            let content = turtle.content_rect();
+            self.draw_outline(cx, Rect {x: margin.x + dx, y: margin.y + dy, ..margin}, live_color!(cx, self::color_margin), 1.);
+            self.draw_outline(cx, rect, live_color!(cx, self::color_padding), 1.);
+            self.draw_outline(cx, Rect {x: content.x + dx, y: content.y + dy, ..content}, live_color!(cx, self::color_hover), 0.);
+            
+            let pos = self.hover_pos.unwrap();
+            self.inspector_bg.color = live_color!(cx, self::color_inspector_bg);
+            self.inspector_text.text_style = live_text_style!(cx, self::text_style_inspector);
+            self.inspector_text.color = live_color!(cx, self::color_inspector_text);
+            let bg_inst = self.inspector_bg.begin_quad(cx, Layout {
+                abs_origin: Some(Vec2 {x: pos.x + 16., y: pos.y + 16.}),
+                ..live_layout!(cx, self::layout_inspector)
+            });
+            for line in cx.describe_layout_debug_turtle(hover) {
+                self.inspector_text.draw_text(cx, &line);
+                cx.turtle_new_line();
+            }
+            self.inspector_bg.end_quad(cx, bg_inst);
+        }
+        
+        self.view.end_view(cx);
+    }
+}
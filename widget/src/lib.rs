@@ -26,6 +26,16 @@ mod windowmenu;
 pub use crate::windowmenu::*;
 mod listlogic;
 pub use crate::listlogic::*;
+mod completionlist;
+pub use crate::completionlist::*;
+mod layoutdebugger;
+pub use crate::layoutdebugger::*;
+mod focusring;
+pub use crate::focusring::*;
+mod markdown;
+pub use crate::markdown::*;
+mod markdownview;
+pub use crate::markdownview::*;
 
 mod textbuffer;
 pub use crate::textbuffer::*;
@@ -33,6 +43,8 @@ mod texteditor;
 pub use crate::texteditor::*;
 mod textcursor;
 pub use crate::textcursor::*;
+mod textvim;
+pub use crate::textvim::*;
 
 mod textinput;
 pub use crate::textinput::*;
@@ -41,6 +53,8 @@ pub use crate::scrollshadow::*;
 
 mod tokentype;
 pub use crate::tokentype::*;
+mod tokenparser;
+pub use crate::tokenparser::*;
 
 mod xrcontrol;
 pub use crate::xrcontrol::*;
@@ -0,0 +1,549 @@
+// a commonmark subset: atx headings, paragraphs, fenced and indented code blocks,
+// bullet and ordered lists and thematic breaks, with emphasis, code spans and links inline
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarkdownInline {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MarkdownBlock {
+    Heading {level: usize, inlines: Vec<MarkdownInline>},
+    Paragraph {inlines: Vec<MarkdownInline>},
+    CodeBlock {lang: String, code: String},
+    ListItem {depth: usize, number: Option<usize>, inlines: Vec<MarkdownInline>},
+    Rule,
+}
+
+enum PendingBlock {
+    None,
+    Paragraph,
+    ListItem {depth: usize, number: Option<usize>}
+}
+
+struct BlockParser {
+    blocks: Vec<MarkdownBlock>,
+    pending: PendingBlock,
+    pending_text: String,
+    list_indents: Vec<usize>,
+}
+
+impl BlockParser {
+    fn flush(&mut self) {
+        if self.pending_text.ends_with('\\') {
+            self.pending_text.pop();
+        }
+        let inlines = parse_inlines(&self.pending_text);
+        match std::mem::replace(&mut self.pending, PendingBlock::None) {
+            PendingBlock::None => (),
+            PendingBlock::Paragraph => self.blocks.push(MarkdownBlock::Paragraph {inlines}),
+            PendingBlock::ListItem {depth, number} => self.blocks.push(MarkdownBlock::ListItem {depth, number, inlines})
+        }
+        self.pending_text.truncate(0);
+    }
+    
+    fn append_line(&mut self, text: &str, raw_line: &str) {
+        if self.pending_text.len() > 0 {
+            // two trailing spaces or a backslash make a hard break
+            if self.pending_text.ends_with('\\') {
+                self.pending_text.pop();
+                self.pending_text.push('\n');
+            }
+            else {
+                self.pending_text.push(' ');
+            }
+        }
+        self.pending_text.push_str(text.trim_end());
+        if raw_line.ends_with("  ") {
+            self.pending_text.push('\\');
+        }
+    }
+}
+
+fn line_indent(line: &str) -> usize {
+    let mut indent = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => indent += 1,
+            '\t' => indent += 4 - indent % 4,
+            _ => break
+        }
+    }
+    indent
+}
+
+// strips up to the given amount of leading indentation
+fn strip_indent(line: &str, amount: usize) -> &str {
+    let mut indent = 0;
+    for (index, c) in line.char_indices() {
+        if indent >= amount {
+            return &line[index..]
+        }
+        match c {
+            ' ' => indent += 1,
+            '\t' => indent += 4 - indent % 4,
+            _ => return &line[index..]
+        }
+    }
+    ""
+}
+
+fn parse_fence(trimmed: &str) -> Option<(char, usize)> {
+    let fence_char = trimmed.chars().next()?;
+    if fence_char != '`' && fence_char != '~' {
+        return None
+    }
+    let count = trimmed.chars().take_while( | c | *c == fence_char).count();
+    if count < 3 {
+        return None
+    }
+    Some((fence_char, count))
+}
+
+fn parse_heading(trimmed: &str) -> Option<(usize, &str)> {
+    let level = trimmed.chars().take_while( | c | *c == '#').count();
+    if level == 0 || level > 6 {
+        return None
+    }
+    let rest = &trimmed[level..];
+    if rest.len() > 0 && !rest.starts_with(' ') && !rest.starts_with('\t') {
+        return None
+    }
+    // an optional closing sequence of #'s
+    let mut text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    if without_closing.len() == 0 || without_closing.ends_with(' ') {
+        text = without_closing.trim_end();
+    }
+    Some((level, text))
+}
+
+fn is_thematic_break(trimmed: &str) -> bool {
+    let mut rule_char = None;
+    let mut count = 0;
+    for c in trimmed.chars() {
+        match c {
+            ' ' | '\t' => (),
+            '-' | '*' | '_' => {
+                if rule_char.is_some() && rule_char != Some(c) {
+                    return false
+                }
+                rule_char = Some(c);
+                count += 1;
+            }
+            _ => return false
+        }
+    }
+    count >= 3
+}
+
+// returns the item number for ordered lists and the text after the marker
+fn parse_list_marker(trimmed: &str) -> Option<(Option<usize>, &str)> {
+    let mut chars = trimmed.char_indices();
+    match chars.next() {
+        Some((_, '-')) | Some((_, '*')) | Some((_, '+')) => {
+            let rest = &trimmed[1..];
+            if rest.len() == 0 {
+                return Some((None, ""))
+            }
+            if rest.starts_with(' ') || rest.starts_with('\t') {
+                return Some((None, rest.trim_start()))
+            }
+            None
+        }
+        Some((_, c)) if c.is_ascii_digit() => {
+            let digits = trimmed.chars().take_while( | c | c.is_ascii_digit()).count();
+            if digits > 9 {
+                return None
+            }
+            let rest = &trimmed[digits..];
+            if !rest.starts_with('.') && !rest.starts_with(')') {
+                return None
+            }
+            let rest = &rest[1..];
+            if rest.len() > 0 && !rest.starts_with(' ') && !rest.starts_with('\t') {
+                return None
+            }
+            Some((trimmed[0..digits].parse().ok(), rest.trim_start()))
+        }
+        _ => None
+    }
+}
+
+pub fn parse_markdown(source: &str) -> Vec<MarkdownBlock> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut parser = BlockParser {
+        blocks: Vec::new(),
+        pending: PendingBlock::None,
+        pending_text: String::new(),
+        list_indents: Vec::new(),
+    };
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        let indent = line_indent(line);
+        let trimmed = line.trim_start();
+        index += 1;
+        
+        if trimmed.len() == 0 {
+            parser.flush();
+            continue;
+        }
+        
+        // anything but a marker or a lazy continuation at the left edge closes the lists
+        if indent < 2 && parse_list_marker(trimmed).is_none() {
+            if let PendingBlock::None = parser.pending {
+                parser.list_indents.truncate(0);
+            }
+        }
+        
+        // indented code, but not as the lazy continuation of a paragraph or inside a list
+        if indent >= 4 && parser.list_indents.len() == 0 {
+            if let PendingBlock::None = parser.pending {
+                let mut code_lines = vec![strip_indent(line, 4)];
+                while index < lines.len() && (line_indent(lines[index]) >= 4 || lines[index].trim().len() == 0) {
+                    code_lines.push(strip_indent(lines[index], 4));
+                    index += 1;
+                }
+                while code_lines.last().map_or(false, | line | line.trim().len() == 0) {
+                    code_lines.pop();
+                }
+                parser.blocks.push(MarkdownBlock::CodeBlock {lang: String::new(), code: code_lines.join("\n")});
+                continue;
+            }
+        }
+        
+        if indent < 4 {
+            if let Some((fence_char, fence_count)) = parse_fence(trimmed) {
+                let info = trimmed[fence_count..].trim();
+                if fence_char != '`' || !info.contains('`') {
+                    parser.flush();
+                    let lang = info.split( | c: char | c.is_whitespace() || c == ',').next().unwrap_or("").to_string();
+                    let mut code_lines = Vec::new();
+                    while index < lines.len() {
+                        let code_line = lines[index];
+                        index += 1;
+                        let code_trimmed = code_line.trim_start();
+                        if let Some((close_char, close_count)) = parse_fence(code_trimmed) {
+                            if close_char == fence_char && close_count >= fence_count && code_trimmed[close_count..].trim().len() == 0 {
+                                break;
+                            }
+                        }
+                        code_lines.push(strip_indent(code_line, indent));
+                    }
+                    parser.blocks.push(MarkdownBlock::CodeBlock {lang, code: code_lines.join("\n")});
+                    continue;
+                }
+            }
+            
+            if let Some((level, text)) = parse_heading(trimmed) {
+                parser.flush();
+                parser.blocks.push(MarkdownBlock::Heading {level, inlines: parse_inlines(text)});
+                continue;
+            }
+            
+            if is_thematic_break(trimmed) {
+                parser.flush();
+                parser.list_indents.truncate(0);
+                parser.blocks.push(MarkdownBlock::Rule);
+                continue;
+            }
+        }
+        
+        if let Some((number, text)) = parse_list_marker(trimmed) {
+            parser.flush();
+            // deeper markers open a nested list, shallower ones close lists
+            while parser.list_indents.last().map_or(false, | last | *last > indent) {
+                parser.list_indents.pop();
+            }
+            if parser.list_indents.last() != Some(&indent) {
+                parser.list_indents.push(indent);
+            }
+            parser.pending = PendingBlock::ListItem {depth: parser.list_indents.len() - 1, number};
+            parser.append_line(text, line);
+            continue;
+        }
+        
+        if let PendingBlock::None = parser.pending {
+            parser.pending = PendingBlock::Paragraph;
+        }
+        parser.append_line(trimmed, line);
+    }
+    parser.flush();
+    parser.blocks
+}
+
+struct InlineParser {
+    inlines: Vec<MarkdownInline>,
+    text: String,
+    bold: bool,
+    italic: bool,
+}
+
+impl InlineParser {
+    fn flush(&mut self, link: &Option<String>) {
+        if self.text.len() > 0 {
+            self.inlines.push(MarkdownInline {
+                text: std::mem::replace(&mut self.text, String::new()),
+                bold: self.bold,
+                italic: self.italic,
+                code: false,
+                link: link.clone()
+            });
+        }
+    }
+    
+    fn parse(&mut self, chars: &[char], link: &Option<String>) {
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            match c {
+                '\\' if i + 1 < chars.len() && chars[i + 1].is_ascii_punctuation() => {
+                    self.text.push(chars[i + 1]);
+                    i += 2;
+                }
+                '`' => {
+                    let run = count_run(chars, i, '`');
+                    if let Some(close) = find_run(chars, i + run, '`', run) {
+                        self.flush(link);
+                        let mut code: String = chars[i + run..close].iter().collect();
+                        code = code.replace('\n', " ");
+                        if code.len() > 2 && code.starts_with(' ') && code.ends_with(' ') {
+                            code = code[1..code.len() - 1].to_string();
+                        }
+                        self.inlines.push(MarkdownInline {
+                            text: code,
+                            bold: self.bold,
+                            italic: self.italic,
+                            code: true,
+                            link: link.clone()
+                        });
+                        i = close + run;
+                    }
+                    else {
+                        for _ in 0..run {
+                            self.text.push('`');
+                        }
+                        i += run;
+                    }
+                }
+                '*' | '_' => {
+                    let run = count_run(chars, i, c);
+                    let prev = if i > 0 {Some(chars[i - 1])} else {None};
+                    let next = chars.get(i + run).cloned();
+                    let intraword = c == '_'
+                        && prev.map_or(false, | p | p.is_alphanumeric())
+                        && next.map_or(false, | n | n.is_alphanumeric());
+                    let can_close = prev.map_or(false, | p | !p.is_whitespace());
+                    let can_open = next.map_or(false, | n | !n.is_whitespace());
+                    let strength = run.min(3);
+                    let closes = can_close && match strength {
+                        1 => self.italic,
+                        2 => self.bold,
+                        _ => self.italic && self.bold
+                    };
+                    // only open when a closing run follows, so a stray star stays a star
+                    let opens = !closes && can_open && find_run(chars, i + run, c, run).is_some();
+                    if !intraword && (closes || opens) {
+                        self.flush(link);
+                        if strength != 2 {
+                            self.italic = !self.italic;
+                        }
+                        if strength >= 2 {
+                            self.bold = !self.bold;
+                        }
+                    }
+                    else {
+                        for _ in 0..run {
+                            self.text.push(c);
+                        }
+                    }
+                    i += run;
+                }
+                '[' if link.is_none() => {
+                    if let Some((text_end, url, end)) = parse_link(chars, i) {
+                        self.flush(link);
+                        let link_url = Some(url);
+                        self.parse(&chars[i + 1..text_end], &link_url);
+                        self.flush(&link_url);
+                        i = end;
+                    }
+                    else {
+                        self.text.push(c);
+                        i += 1;
+                    }
+                }
+                '<' if link.is_none() => {
+                    let close = chars[i..].iter().position( | c | *c == '>' || c.is_whitespace()).map( | p | p + i);
+                    let autolink = close.and_then( | close | {
+                        let url: String = chars[i + 1..close].iter().collect();
+                        if chars[close] == '>' && (url.starts_with("http://") || url.starts_with("https://") || url.starts_with("mailto:")) {
+                            Some((url, close))
+                        }
+                        else {
+                            None
+                        }
+                    });
+                    if let Some((url, close)) = autolink {
+                        self.flush(link);
+                        self.text.push_str(&url);
+                        self.flush(&Some(url));
+                        i = close + 1;
+                    }
+                    else {
+                        self.text.push(c);
+                        i += 1;
+                    }
+                }
+                _ => {
+                    self.text.push(c);
+                    i += 1;
+                }
+            }
+        }
+        self.flush(link);
+    }
+}
+
+fn count_run(chars: &[char], start: usize, c: char) -> usize {
+    chars[start..].iter().take_while( | ch | **ch == c).count()
+}
+
+// the next run of exactly the given length
+fn find_run(chars: &[char], start: usize, c: char, len: usize) -> Option<usize> {
+    let mut i = start;
+    while i < chars.len() {
+        if chars[i] == c {
+            let run = count_run(chars, i, c);
+            if run == len {
+                return Some(i)
+            }
+            i += run;
+        }
+        else {
+            i += 1;
+        }
+    }
+    None
+}
+
+// [text](url "title"), returns where the text ends, the url and the index after the link
+fn parse_link(chars: &[char], start: usize) -> Option<(usize, String, usize)> {
+    let mut depth = 0;
+    let mut text_end = None;
+    for i in start..chars.len() {
+        match chars[i] {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    text_end = Some(i);
+                    break;
+                }
+            }
+            _ => ()
+        }
+    }
+    let text_end = text_end?;
+    if chars.get(text_end + 1) != Some(&'(') {
+        return None
+    }
+    let close = chars[text_end + 2..].iter().position( | c | *c == ')')? + text_end + 2;
+    let dest: String = chars[text_end + 2..close].iter().collect();
+    let url = dest.trim().split_whitespace().next().unwrap_or("").trim_start_matches('<').trim_end_matches('>').to_string();
+    Some((text_end, url, close + 1))
+}
+
+pub fn parse_inlines(text: &str) -> Vec<MarkdownInline> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut parser = InlineParser {
+        inlines: Vec::new(),
+        text: String::new(),
+        bold: false,
+        italic: false
+    };
+    parser.parse(&chars, &None);
+    parser.inlines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn inline(text: &str) -> MarkdownInline {
+        MarkdownInline {text: text.to_string(), ..MarkdownInline::default()}
+    }
+    
+    fn link(text: &str, url: &str) -> MarkdownInline {
+        MarkdownInline {link: Some(url.to_string()), ..inline(text)}
+    }
+    
+    #[test]
+    fn headings_and_rules() {
+        assert_eq!(parse_markdown("# One\n## Two ##\n###### Six\n####### seven\n#nospace\n***\n- - -"), vec![
+            MarkdownBlock::Heading {level: 1, inlines: vec![inline("One")]},
+            MarkdownBlock::Heading {level: 2, inlines: vec![inline("Two")]},
+            MarkdownBlock::Heading {level: 6, inlines: vec![inline("Six")]},
+            // too deep or without a space it is just text
+            MarkdownBlock::Paragraph {inlines: vec![inline("####### seven #nospace")]},
+            MarkdownBlock::Rule,
+            MarkdownBlock::Rule,
+        ]);
+    }
+    
+    #[test]
+    fn nested_and_ordered_lists() {
+        assert_eq!(parse_markdown("- one\n- two\n  - nested\n  continued\n1. first\n2) second\n\ntext"), vec![
+            MarkdownBlock::ListItem {depth: 0, number: None, inlines: vec![inline("one")]},
+            MarkdownBlock::ListItem {depth: 0, number: None, inlines: vec![inline("two")]},
+            MarkdownBlock::ListItem {depth: 1, number: None, inlines: vec![inline("nested continued")]},
+            MarkdownBlock::ListItem {depth: 0, number: Some(1), inlines: vec![inline("first")]},
+            MarkdownBlock::ListItem {depth: 0, number: Some(2), inlines: vec![inline("second")]},
+            MarkdownBlock::Paragraph {inlines: vec![inline("text")]},
+        ]);
+    }
+    
+    #[test]
+    fn fenced_and_indented_code_blocks() {
+        let source = "```rust\nfn main() {\n    let a = 1;\n}\n```\n\n    indented\n      more\n\n    last\n\npara\n    not code\n~~~\nunclosed";
+        assert_eq!(parse_markdown(source), vec![
+            MarkdownBlock::CodeBlock {lang: "rust".to_string(), code: "fn main() {\n    let a = 1;\n}".to_string()},
+            MarkdownBlock::CodeBlock {lang: String::new(), code: "indented\n  more\n\nlast".to_string()},
+            // indented lines continue a paragraph
+            MarkdownBlock::Paragraph {inlines: vec![inline("para not code")]},
+            MarkdownBlock::CodeBlock {lang: String::new(), code: "unclosed".to_string()},
+        ]);
+    }
+    
+    #[test]
+    fn emphasis_and_code_spans() {
+        assert_eq!(parse_inlines("a *b* **c** ***d*** `e *f*` snake_case_name 2 * 3"), vec![
+            inline("a "),
+            MarkdownInline {italic: true, ..inline("b")},
+            inline(" "),
+            MarkdownInline {bold: true, ..inline("c")},
+            inline(" "),
+            MarkdownInline {bold: true, italic: true, ..inline("d")},
+            inline(" "),
+            MarkdownInline {code: true, ..inline("e *f*")},
+            inline(" snake_case_name 2 * 3"),
+        ]);
+        assert_eq!(parse_inlines("\\*not\\* *open"), vec![inline("*not* *open")]);
+    }
+    
+    #[test]
+    fn links_and_autolinks() {
+        assert_eq!(parse_inlines("see [the *docs*](https://a.b/c \"title\") and <https://x.y> or [broken](nope"), vec![
+            inline("see "),
+            link("the ", "https://a.b/c"),
+            MarkdownInline {italic: true, ..link("docs", "https://a.b/c")},
+            inline(" and "),
+            link("https://x.y", "https://x.y"),
+            inline(" or [broken](nope"),
+        ]);
+    }
+}
@@ -0,0 +1,325 @@
+use makepad_render::*;
+use crate::scrollview::*;
+use crate::markdown::*;
+use crate::textbuffer::*;
+use crate::tokentype::*;
+
+#[derive(Clone, PartialEq)]
+pub enum MarkdownEvent {
+    None,
+    LinkClicked {url: String},
+}
+
+// fills in the token chunks of a code block, given the language from its fence
+pub type MarkdownCodeTokenizer = fn(lang: &str, text_buffer: &mut TextBuffer);
+
+#[derive(Clone)]
+pub struct MarkdownView {
+    pub view: ScrollView,
+    pub rich_text: RichText,
+    pub bg: Quad,
+    pub code_tokenizer: Option<MarkdownCodeTokenizer>,
+    pub blocks: Vec<MarkdownBlock>,
+    pub _code_buffers: Vec<TextBuffer>,
+    pub _links: Vec<(String, Rect)>,
+    pub _hover_url: Option<String>,
+}
+
+impl MarkdownView {
+    pub fn new(cx: &mut Cx) -> Self {
+        Self {
+            view: ScrollView::new(cx),
+            rich_text: RichText {
+                text: Text {
+                    ligatures: true,
+                    ..Text::new(cx)
+                },
+                ..RichText::new(cx)
+            },
+            bg: Quad::new(cx),
+            code_tokenizer: None,
+            blocks: Vec::new(),
+            _code_buffers: Vec::new(),
+            _links: Vec::new(),
+            _hover_url: None,
+        }
+    }
+    
+    pub fn style(cx: &mut Cx) {
+        live!(cx, r#"
+            self::layout_main: Layout {
+                padding: {l: 10., t: 10., r: 10., b: 10.},
+                new_line_padding: 6.,
+                line_wrap: NewLine,
+            }
+            
+            self::layout_code: Layout {
+                walk: Walk {width: Fill, height: Compute},
+                padding: all(8.),
+                line_wrap: NewLine,
+            }
+            
+            self::text_style_body: TextStyle {
+                font_size: 9.0,
+                line_spacing: 1.6,
+                ..crate::widgetstyle::text_style_normal
+            }
+            
+            self::text_style_heading1: TextStyle {
+                font_size: 16.0,
+                ..self::text_style_body
+            }
+            
+            self::text_style_heading2: TextStyle {
+                font_size: 13.0,
+                ..self::text_style_body
+            }
+            
+            self::text_style_heading3: TextStyle {
+                font_size: 11.0,
+                ..self::text_style_body
+            }
+            
+            self::text_style_code: TextStyle {
+                font_size: 8.0,
+                ..crate::widgetstyle::text_style_fixed
+            }
+            
+            self::list_indent: 16.;
+            
+            self::color_text: #b;
+            self::color_heading: #e;
+            self::color_link: #5b9bd3;
+            self::color_link_hover: #8cc4f4;
+            self::color_code: #c;
+            self::color_code_bg: #2a;
+            self::color_rule: #4;
+            
+            self::color_keyword: #5b9bd3;
+            self::color_flow: #c485be;
+            self::color_looping: #ff8c00;
+            self::color_call: #dcdcae;
+            self::color_type_name: #56c9b1;
+            self::color_string: #cc917b;
+            self::color_number: #b6ceaa;
+            self::color_comment: #638d54;
+            self::color_error: #fe0000;
+            self::color_warning: #e1e570;
+        "#)
+    }
+    
+    pub fn set_markdown(&mut self, cx: &mut Cx, source: &str) {
+        self.blocks = parse_markdown(source);
+        self._code_buffers.truncate(0);
+        for block in &self.blocks {
+            if let MarkdownBlock::CodeBlock {lang, code} = block {
+                let mut text_buffer = TextBuffer::default();
+                text_buffer.load_from_utf8(code);
+                if let Some(code_tokenizer) = self.code_tokenizer {
+                    code_tokenizer(lang, &mut text_buffer);
+                }
+                self._code_buffers.push(text_buffer);
+            }
+        }
+        self._hover_url = None;
+        self.view.redraw_view_area(cx);
+    }
+    
+    fn find_link(&self, cx: &Cx, abs: Vec2) -> Option<String> {
+        let scroll_pos = self.view.get_scroll_pos(cx);
+        let pos = Vec2 {x: abs.x + scroll_pos.x, y: abs.y + scroll_pos.y};
+        for (url, rect) in &self._links {
+            if rect.contains(pos.x, pos.y) {
+                return Some(url.clone())
+            }
+        }
+        None
+    }
+    
+    pub fn handle_markdown_view(&mut self, cx: &mut Cx, event: &mut Event) -> MarkdownEvent {
+        if self.view.handle_scroll_view(cx, event) {
+            self._hover_url = None;
+        }
+        match event.hits(cx, self.view.get_view_area(cx), HitOpt::default()) {
+            Event::FingerHover(fe) => {
+                let hover_url = self.find_link(cx, fe.abs);
+                if hover_url.is_some() {
+                    cx.set_hover_mouse_cursor(MouseCursor::Hand);
+                }
+                if hover_url != self._hover_url {
+                    self._hover_url = hover_url;
+                    self.view.redraw_view_area(cx);
+                }
+            },
+            Event::FingerUp(fe) => {
+                if fe.is_over && fe.abs.distance(&fe.abs_start) < 4.0 {
+                    if let Some(url) = self.find_link(cx, fe.abs) {
+                        return MarkdownEvent::LinkClicked {url}
+                    }
+                }
+            },
+            _ => ()
+        }
+        MarkdownEvent::None
+    }
+    
+    fn token_color(cx: &Cx, token_type: TokenType) -> Option<Color> {
+        Some(match token_type {
+            TokenType::Keyword | TokenType::BuiltinType | TokenType::Bool => live_color!(cx, self::color_keyword),
+            TokenType::Flow | TokenType::Impl => live_color!(cx, self::color_flow),
+            TokenType::Looping => live_color!(cx, self::color_looping),
+            TokenType::Fn | TokenType::Call | TokenType::Macro => live_color!(cx, self::color_call),
+            TokenType::TypeName | TokenType::TypeDef => live_color!(cx, self::color_type_name),
+            TokenType::String | TokenType::StringMultiBegin | TokenType::StringChunk | TokenType::StringMultiEnd
+                | TokenType::Regex | TokenType::Color => live_color!(cx, self::color_string),
+            TokenType::Number => live_color!(cx, self::color_number),
+            TokenType::CommentLine | TokenType::CommentChunk | TokenType::CommentMultiBegin
+                | TokenType::CommentMultiEnd => live_color!(cx, self::color_comment),
+            TokenType::Error => live_color!(cx, self::color_error),
+            TokenType::Warning => live_color!(cx, self::color_warning),
+            _ => return None
+        })
+    }
+    
+    fn draw_spans(&mut self, cx: &mut Cx, spans: &[TextSpan], links: &[Option<String>]) {
+        self.rich_text.draw_rich_text(cx, spans);
+        for (span_index, link) in links.iter().enumerate() {
+            if let Some(url) = link {
+                for rect in &self.rich_text.span_rects[span_index] {
+                    self._links.push((url.clone(), *rect));
+                }
+            }
+        }
+        cx.turtle_new_line();
+    }
+    
+    fn draw_inlines(&mut self, cx: &mut Cx, inlines: &[MarkdownInline], text_style: TextStyle, color: Color) {
+        let text_style_code = live_text_style!(cx, self::text_style_code);
+        let mut spans = Vec::new();
+        let mut links = Vec::new();
+        for inline in inlines {
+            let is_hovered = inline.link.is_some() && inline.link == self._hover_url;
+            let color = if is_hovered {
+                live_color!(cx, self::color_link_hover)
+            }
+            else if inline.link.is_some() {
+                live_color!(cx, self::color_link)
+            }
+            else if inline.code {
+                live_color!(cx, self::color_code)
+            }
+            else {
+                color
+            };
+            let mut span = if inline.code {
+                TextSpan::text(&inline.text, TextStyle {font_size: text_style.font_size * 0.9, ..text_style_code}, color)
+            }
+            else {
+                TextSpan::text(&inline.text, text_style, color)
+            };
+            span = span.with_variant(match (inline.bold, inline.italic) {
+                (true, true) => FontVariant::BoldItalic,
+                (true, false) => FontVariant::Bold,
+                (false, true) => FontVariant::Italic,
+                (false, false) => FontVariant::Regular
+            });
+            if inline.link.is_some() {
+                span = span.with_underline();
+            }
+            spans.push(span);
+            links.push(inline.link.clone());
+        }
+        self.draw_spans(cx, &spans, &links);
+    }
+    
+    fn draw_code_block(&mut self, cx: &mut Cx, code_index: usize) {
+        let text_style = live_text_style!(cx, self::text_style_code);
+        let color_code = live_color!(cx, self::color_code);
+        let mut spans = Vec::new();
+        let text_buffer = &self._code_buffers[code_index];
+        if text_buffer.token_chunks.len() > 0 {
+            for token_chunk in &text_buffer.token_chunks {
+                if token_chunk.token_type == TokenType::Eof {
+                    break;
+                }
+                let text: String = text_buffer.flat_text[token_chunk.offset..token_chunk.offset + token_chunk.len].iter().collect();
+                let color = Self::token_color(cx, token_chunk.token_type).unwrap_or(color_code);
+                spans.push(TextSpan::text(&text, text_style, color));
+            }
+        }
+        else {
+            let text: String = text_buffer.lines.iter().map( | line | line.iter().collect::<String>()).collect::<Vec<String>>().join("\n");
+            spans.push(TextSpan::text(&text, text_style, color_code));
+        }
+        
+        self.bg.color = live_color!(cx, self::color_code_bg);
+        let inst = self.bg.begin_quad(cx, live_layout!(cx, self::layout_code));
+        self.rich_text.draw_rich_text(cx, &spans);
+        self.bg.end_quad(cx, inst);
+        cx.turtle_new_line();
+    }
+    
+    pub fn draw_markdown_view(&mut self, cx: &mut Cx) {
+        if self.view.begin_view(cx, live_layout!(cx, self::layout_main)).is_err() {
+            return
+        }
+        self._links.truncate(0);
+        
+        let color_text = live_color!(cx, self::color_text);
+        let color_heading = live_color!(cx, self::color_heading);
+        let text_style_body = live_text_style!(cx, self::text_style_body);
+        let list_indent = live_float!(cx, self::list_indent);
+        
+        let blocks = std::mem::replace(&mut self.blocks, Vec::new());
+        let mut code_index = 0;
+        for block in &blocks {
+            match block {
+                MarkdownBlock::Heading {level, inlines} => {
+                    let text_style = match level {
+                        1 => live_text_style!(cx, self::text_style_heading1),
+                        2 => live_text_style!(cx, self::text_style_heading2),
+                        _ => live_text_style!(cx, self::text_style_heading3),
+                    };
+                    self.draw_inlines(cx, inlines, text_style, color_heading);
+                },
+                MarkdownBlock::Paragraph {inlines} => {
+                    self.draw_inlines(cx, inlines, text_style_body, color_text);
+                },
+                MarkdownBlock::ListItem {depth, number, inlines} => {
+                    // the marker hangs in the indent so wrapped lines line up with the text
+                    let indent = list_indent * (*depth + 1) as f32;
+                    cx.begin_turtle(Layout {
+                        walk: Walk::wh(Width::Fill, Height::Compute),
+                        padding: Padding {l: indent, t: 0., r: 0., b: 0.},
+                        line_wrap: LineWrap::NewLine,
+                        ..Layout::default()
+                    }, Area::Empty);
+                    let marker = match number {
+                        Some(number) => format!("{}.", number),
+                        None => if depth % 2 == 0 {"•".to_string()} else {"◦".to_string()}
+                    };
+                    self.rich_text.text.text_style = text_style_body;
+                    let marker_width = self.rich_text.text.get_text_width(cx, &marker.chars().collect::<Vec<char>>());
+                    cx.move_turtle(-marker_width - 4.0, 0.);
+                    self.rich_text.draw_rich_text(cx, &[TextSpan::text(&marker, text_style_body, color_text)]);
+                    cx.move_turtle(4.0, 0.);
+                    self.draw_inlines(cx, inlines, text_style_body, color_text);
+                    cx.end_turtle(Area::Empty);
+                    cx.turtle_new_line();
+                },
+                MarkdownBlock::CodeBlock {..} => {
+                    self.draw_code_block(cx, code_index);
+                    code_index += 1;
+                },
+                MarkdownBlock::Rule => {
+                    self.bg.color = live_color!(cx, self::color_rule);
+                    self.bg.draw_quad(cx, Walk {width: Width::Fill, height: Height::Fix(1.), margin: Margin {l: 0., t: 4., r: 0., b: 4.}, ..Walk::default()});
+                    cx.turtle_new_line();
+                }
+            }
+        }
+        self.blocks = blocks;
+        
+        self.view.end_view(cx);
+    }
+}
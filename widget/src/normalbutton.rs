@@ -124,5 +124,11 @@ impl NormalButton {
         
         self._bg_area = self.bg.end_quad(cx, bg_inst);
         self.animator.set_area(cx, self._bg_area);
+        cx.add_access_node(AccessNode {
+            actions: vec![AccessAction::Click],
+            area: self._bg_area,
+            ..AccessNode::new(AccessRole::Button, label)
+        });
+        cx.add_focusable(self._bg_area, false);
     }
 }
@@ -168,14 +168,19 @@ impl Tab {
         }
     }
     
+    // the tab folds up from the size it had when the close was clicked
     pub fn anim_close(&self, _cx: &Cx) -> Anim {
+        let rect = self._close_anim_rect;
         Anim{
             play: Play::Single {duration: 0.1, cut: true, term: true, end: 1.0},
             tracks: vec![
-                Track::Float{
+                Track::Walk{
                     live_id: live_id!(self::tab_closing),
                     ease: Ease::OutExp,
-                    keys: vec![(0.0, 1.0), (1.0, 0.0)],
+                    keys: vec![
+                        (0.0, Walk::wh(Width::Fix(rect.w), Height::Fix(rect.h))),
+                        (1.0, Walk::wh(Width::Fix(0.), Height::Fix(rect.h)))
+                    ],
                     cut_init: None
                 }
             ]
@@ -208,6 +213,9 @@ impl Tab {
             match self.tab_close.handle_tab_close(cx, event) {
                 ButtonEvent::Down => {
                     self._close_anim_rect = self._bg_area.get_rect(cx);
+                    // draw at full size until the first animate arrives
+                    let rect = self._close_anim_rect;
+                    self.animator.set_last_walk(live_id!(self::tab_closing), Walk::wh(Width::Fix(rect.w), Height::Fix(rect.h)));
                     self.animator.play_anim(cx, self.anim_close(cx));
                     return TabEvent::Closing;
                 },
@@ -219,8 +227,7 @@ impl Tab {
             Event::Animate(ae) => {
                 // its playing the term anim, run a redraw
                 if self.animator.term_anim_playing() {
-                    self.animator.calc_float(cx, live_id!(self::tab_closing), ae.time);
-                    cx.redraw_child_area(self._bg_area);
+                    self.animator.calc_walk(cx, live_id!(self::tab_closing), ae.time);
                 }
                 else {
                     self.animator.calc_area(cx, self._bg_area, ae.time);
@@ -247,6 +254,16 @@ impl Tab {
                 self.animator.play_anim(cx, self.anim_down(cx));
                 return TabEvent::Select;
             },
+            Event::AccessAction(AccessActionEvent {action: AccessAction::Click, ..}) |
+            Event::KeyDown(KeyEvent {key_code: KeyCode::Return, ..}) |
+            Event::KeyDown(KeyEvent {key_code: KeyCode::Space, ..}) => {
+                if self.animator.term_anim_playing() {
+                    return TabEvent::None
+                }
+                self._is_selected = true;
+                self._is_focussed = true;
+                return TabEvent::Select;
+            },
             Event::FingerHover(fe) => {
                 cx.set_hover_mouse_cursor(MouseCursor::Hand);
                 match fe.hover_state {
@@ -313,13 +330,7 @@ impl Tab {
         // check if we are closing
         if self.animator.term_anim_playing() {
             // so so BUT how would we draw this thing with its own clipping
-            let bg_inst = self.bg.draw_quad(
-                cx,
-                Walk::wh(
-                    Width::Fix(self._close_anim_rect.w * self.animator.last_float(cx, live_id!(self::tab_closing))),
-                    Height::Fix(self._close_anim_rect.h),
-                )
-            );
+            let bg_inst = self.bg.draw_quad(cx, self.animator.last_walk(cx, live_id!(self::tab_closing)));
             bg_inst.push_last_color(cx, &self.animator, live_id!(self::shader_bg::border_color));
             self._bg_area = bg_inst.into();
             self.animator.set_area(cx, self._bg_area);
@@ -334,6 +345,11 @@ impl Tab {
             };
             let bg_inst = self.bg.begin_quad(cx, layout);
             bg_inst.push_last_color(cx, &self.animator, live_id!(self::shader_bg::border_color));
+            cx.begin_access_node(AccessNode {
+                actions: vec![AccessAction::Click],
+                selected: self._is_selected,
+                ..AccessNode::new(AccessRole::Tab, &self.label)
+            });
             if self.is_closeable {
                 self.tab_close.draw_tab_close(cx);
                 cx.turtle_align_y();
@@ -355,6 +371,8 @@ impl Tab {
         if let Some(bg_inst) = self._bg_inst.take() {
             self._bg_area = self.bg.end_quad(cx, bg_inst);
             self.animator.set_area(cx, self._bg_area); // if our area changed, update animation
+            cx.end_access_node(self._bg_area);
+            cx.add_focusable(self._bg_area, self._is_selected);
         }
     }
     
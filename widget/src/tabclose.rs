@@ -103,6 +103,10 @@ impl TabClose {
                     _ => ()
                 }
             },
+            // closing from a screen reader skips straight to the close anim, like a finger down
+            Event::AccessAction(ae) => if let AccessAction::Click = ae.action {
+                return ButtonEvent::Down;
+            },
             Event::FingerUp(fe) => if fe.is_over {
                 if !fe.is_touch {self.animator.play_anim(cx, live_anim!(cx, self::anim_over))}
                 else {self.animator.play_anim(cx, live_anim!(cx, self::anim_default))}
@@ -126,5 +130,10 @@ impl TabClose {
         bg_inst.push_last_float(cx, &self.animator, live_id!(self::shader_bg::down));
         self._bg_area = bg_inst.into();
         self.animator.set_area(cx, self._bg_area); // if our area changed, update animation
+        cx.add_access_node(AccessNode {
+            actions: vec![AccessAction::Click],
+            area: self._bg_area,
+            ..AccessNode::new(AccessRole::Button, "Close")
+        });
     }
 }
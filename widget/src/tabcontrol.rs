@@ -192,6 +192,8 @@ impl TabControl {
         }
         self._tab_now_selected = None;
         self._tab_id_alloc = 0;
+        cx.begin_access_node(AccessNode::new(AccessRole::TabList, ""));
+        cx.begin_focus_scope(FocusScope::Row);
         Ok(())
     }
     
@@ -234,8 +236,10 @@ impl TabControl {
             }
         }
         live_style_begin!(cx, self::tab_control_style);
-        self.tabs_view.end_view(cx);
+        cx.end_focus_scope();
+        let tabs_area = self.tabs_view.end_view(cx);
         live_style_end!(cx, self::tab_control_style);
+        cx.end_access_node(tabs_area);
         if self._tab_now_selected != self._tab_last_selected {
             // lets scroll the thing into view
             if let Some(tab_id) = self._tab_now_selected {
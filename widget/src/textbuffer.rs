@@ -28,6 +28,11 @@ pub struct TextBuffer {
     pub old_token_chunks: Vec<TokenChunk>,
     pub token_chunks_id: u32,
     pub keyboard: TextBufferKeyboard,
+    // the line moves of the recent edits. folds hang on to row numbers, so every editor on the
+    // buffer catches up on these from the mutation id it last saw
+    pub line_shifts: Vec<TextLineShift>,
+    // the shifts are complete after this mutation id, older ones got dropped
+    pub line_shifts_since: u32,
 } 
 
 impl TextBuffer {
@@ -66,7 +71,9 @@ pub enum TextBufferMessageLevel {
 #[derive(Clone)]
 pub struct TextBufferMessage {
     pub level: TextBufferMessageLevel,
-    pub body: String
+    pub body: String,
+    // language server diagnostics get replaced as a set, build messages stay
+    pub from_lsp: bool
 }
 
 #[derive(Clone, Copy, PartialEq, Default)]
@@ -133,6 +140,33 @@ pub struct TextUndo {
     pub cursors: TextCursorSet
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub struct TextLineShift {
+    pub mutation_id: u32,
+    pub start: TextPos,
+    pub end_row: usize,
+    pub added: usize
+}
+
+impl TextLineShift {
+    // where a row ended up after the edit, None if the edit removed it
+    pub fn shift_row(&self, row: usize) -> Option<usize> {
+        if row < self.start.row || (row == self.start.row && self.start.col > 0) {
+            Some(row)
+        }
+        else if row == self.start.row && self.end_row == self.start.row {
+            // inserted in front of the line, so it got pushed down
+            Some(row + self.added)
+        }
+        else if row <= self.end_row {
+            None
+        }
+        else {
+            Some(row + self.added - (self.end_row - self.start.row))
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct TextOp {
     pub start: usize,
@@ -427,6 +461,18 @@ impl TextBuffer {
         self.is_crlf = !utf8.find("\r\n").is_none();
         self.lines = TextBuffer::split_string_to_lines(utf8);
         self.mutation_id += 1;
+        // all rows changed
+        self.line_shifts.truncate(0);
+        self.line_shifts_since = self.mutation_id;
+    }
+    
+    // the line shifts after mutation_id in order, None if some of them were dropped already
+    pub fn line_shifts_after(&self, mutation_id: u32) -> Option<&[TextLineShift]> {
+        if mutation_id < self.line_shifts_since {
+            return None
+        }
+        let start = self.line_shifts.iter().position( | v | v.mutation_id > mutation_id).unwrap_or(self.line_shifts.len());
+        Some(&self.line_shifts[start..])
     }
     
     pub fn replace_line(&mut self, row: usize, start_col: usize, len: usize, rep_line: Vec<char>) -> Vec<char> {
@@ -456,6 +502,20 @@ impl TextBuffer {
         let start_pos = self.offset_to_text_pos(start);
         let end_pos = self.offset_to_text_pos_next(start + len, start_pos, start);
         
+        if end_pos.row != start_pos.row || rep_lines.len() != 1 {
+            self.line_shifts.push(TextLineShift {
+                mutation_id: self.mutation_id,
+                start: start_pos,
+                end_row: end_pos.row,
+                added: rep_lines.len() - 1
+            });
+            // an editor that fell this far behind resets its folds
+            if self.line_shifts.len() > 256 {
+                let dropped = self.line_shifts.remove(0);
+                self.line_shifts_since = dropped.mutation_id;
+            }
+        }
+        
         if start_pos.row == end_pos.row && rep_lines.len() == 1 { // replace in one line
             let rep_line_zero = rep_lines.drain(0..1).next().unwrap();
             
@@ -738,3 +798,84 @@ impl<'a> LineTokenizer<'a> {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shift(row: usize, col: usize, end_row: usize, added: usize) -> TextLineShift {
+        TextLineShift {mutation_id: 0, start: TextPos {row: row, col: col}, end_row: end_row, added: added}
+    }
+
+    fn lines(n: usize) -> Vec<Vec<char>> {
+        vec![vec![]; n]
+    }
+
+    #[test]
+    fn rows_shift_around_inserted_lines() {
+        // two lines inserted at the start of row 3
+        let s = shift(3, 0, 3, 2);
+        assert_eq!(s.shift_row(1), Some(1));
+        assert_eq!(s.shift_row(3), Some(5));
+        assert_eq!(s.shift_row(6), Some(8));
+        // two lines inserted in the middle of row 3, which stays put
+        let s = shift(3, 4, 3, 2);
+        assert_eq!(s.shift_row(2), Some(2));
+        assert_eq!(s.shift_row(3), Some(3));
+        assert_eq!(s.shift_row(4), Some(6));
+    }
+
+    #[test]
+    fn rows_shift_around_deleted_lines() {
+        // from the middle of row 2 to row 5
+        let s = shift(2, 5, 5, 0);
+        assert_eq!(s.shift_row(1), Some(1));
+        assert_eq!(s.shift_row(2), Some(2));
+        assert_eq!(s.shift_row(3), None);
+        assert_eq!(s.shift_row(5), None);
+        assert_eq!(s.shift_row(6), Some(3));
+        // whole rows 2 to 4, from the start of row 2
+        let s = shift(2, 0, 4, 0);
+        assert_eq!(s.shift_row(1), Some(1));
+        assert_eq!(s.shift_row(2), None);
+        assert_eq!(s.shift_row(4), None);
+        assert_eq!(s.shift_row(5), Some(3));
+        // rows 2 to 4 replaced by 3 new ones
+        let s = shift(2, 1, 4, 3);
+        assert_eq!(s.shift_row(5), Some(6));
+    }
+
+    #[test]
+    fn every_reader_catches_up_from_its_own_mutation_id() {
+        let mut text_buffer = TextBuffer::from_utf8("a\nb\nc\nd");
+        let seen = text_buffer.mutation_id;
+        let offset = text_buffer.text_pos_to_offset(TextPos {row: 1, col: 0});
+        text_buffer.replace_range(offset, 0, lines(3));
+        let middle = text_buffer.mutation_id;
+        // a single line edit doesnt move rows
+        text_buffer.replace_range(0, 1, vec![vec!['x']]);
+        let offset = text_buffer.text_pos_to_offset(TextPos {row: 0, col: 0});
+        text_buffer.replace_range(offset, 0, lines(2));
+        assert_eq!(text_buffer.line_shifts_after(seen).unwrap().len(), 2);
+        assert_eq!(text_buffer.line_shifts_after(middle).unwrap().len(), 1);
+        assert_eq!(text_buffer.line_shifts_after(text_buffer.mutation_id).unwrap().len(), 0);
+        let row = text_buffer.line_shifts_after(seen).unwrap().iter().fold(Some(3), | row, s | row.and_then( | row | s.shift_row(row)));
+        assert_eq!(row, Some(6));
+    }
+
+    #[test]
+    fn missed_shifts_are_none() {
+        let mut text_buffer = TextBuffer::from_utf8("a\nb");
+        let seen = text_buffer.mutation_id;
+        for _ in 0..300 {
+            text_buffer.replace_range(0, 0, lines(2));
+        }
+        assert!(text_buffer.line_shifts_after(seen).is_none());
+        assert_eq!(text_buffer.line_shifts_after(text_buffer.mutation_id - 10).unwrap().len(), 10);
+        // a reload moves everything
+        let seen = text_buffer.mutation_id;
+        text_buffer.load_from_utf8("c");
+        assert!(text_buffer.line_shifts_after(seen).is_none());
+        assert_eq!(text_buffer.line_shifts_after(text_buffer.mutation_id).unwrap().len(), 0);
+    }
+}
@@ -2,6 +2,7 @@ use makepad_render::*;
 
 use crate::textbuffer::*;
 use crate::tokentype::*;
+use crate::tokenparser::*;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct TextCursor {
@@ -120,7 +121,8 @@ pub struct TextCursorSet {
     pub set: Vec<TextCursor>,
     pub last_cursor: usize,
     pub insert_undo_group: u64,
-    pub last_clamp_range: Option<(usize, usize)>
+    pub last_clamp_range: Option<(usize, usize)>,
+    pub expand_stack: Vec<(Vec<TextCursor>, Vec<TextCursor>)>
 }
 
 impl TextCursorSet {
@@ -129,7 +131,8 @@ impl TextCursorSet {
             set: vec![TextCursor {head: 0, tail: 0, max: 0}],
             last_cursor: 0,
             insert_undo_group: 0,
-            last_clamp_range: None
+            last_clamp_range: None,
+            expand_stack: Vec::new()
         }
     }
     
@@ -180,6 +183,21 @@ impl TextCursorSet {
         ret
     }
     
+    pub fn get_ident_before_last_cursor(&self, text_buffer: &TextBuffer) -> String {
+        let cursor = &self.set[self.last_cursor];
+        if cursor.head != cursor.tail {
+            return String::new()
+        }
+        let pos = text_buffer.offset_to_text_pos(cursor.head);
+        let line = &text_buffer.lines[pos.row];
+        let end = pos.col.min(line.len());
+        let mut start = end;
+        while start > 0 && (line[start - 1].is_alphanumeric() || line[start - 1] == '_') {
+            start -= 1;
+        }
+        line[start..end].iter().collect()
+    }
+    
     fn fuse_adjacent(&mut self, text_buffer: &TextBuffer) {
         let mut index = 0;
         let mut old_calc = (TextPos {row: 0, col: 0}, 0);
@@ -1106,6 +1124,184 @@ impl TextCursorSet {
         self.fuse_adjacent(text_buffer)
     }
     
+    // the offset range of the tokens from..to, without the whitespace around it
+    fn get_token_range(from: usize, to: usize, text_buffer: &TextBuffer) -> Option<(usize, usize)> {
+        let token_chunks = &text_buffer.token_chunks;
+        let is_space = | tt: TokenType | tt == TokenType::Whitespace || tt == TokenType::Newline;
+        let mut from = from;
+        let mut to = to;
+        while from < to && is_space(token_chunks[from].token_type) {
+            from += 1;
+        }
+        while to > from && is_space(token_chunks[to - 1].token_type) {
+            to -= 1;
+        }
+        if from == to {
+            return None
+        }
+        Some((token_chunks[from].offset, token_chunks[to - 1].offset + token_chunks[to - 1].len))
+    }
+
+    // a call or macro takes its name along with its brackets
+    fn get_call_range(open: usize, text_buffer: &TextBuffer) -> Option<(usize, usize)> {
+        let token_chunks = &text_buffer.token_chunks;
+        let close = token_chunks[open].pair_token;
+        if open == 0 || close <= open {
+            return None
+        }
+        match token_chunks[open - 1].token_type {
+            TokenType::Call | TokenType::Macro | TokenType::Identifier | TokenType::TypeName => {
+                Some((token_chunks[open - 1].offset, token_chunks[close].offset + token_chunks[close].len))
+            },
+            _ => None
+        }
+    }
+    
+    // all the ranges a selection could grow into: the token, the expression,
+    // the statement or item, and the bracketed group with and without brackets
+    fn get_expand_ranges(start: usize, end: usize, text_buffer: &TextBuffer) -> Vec<(usize, usize)> {
+        let token_chunks = &text_buffer.token_chunks;
+        let mut ranges = Vec::new();
+        if start == end {
+            if let Some((offset, len)) = TextCursorSet::get_nearest_token_chunk(start, text_buffer) {
+                ranges.push((offset, offset + len));
+            }
+        }
+        // the innermost group around the selection, jumping over the groups that close before it
+        let mut tp = TokenParser::new(&text_buffer.flat_text, token_chunks);
+        let mut group = None;
+        while tp.advance() && tp.cur_offset() <= start {
+            let (tok_start, tok_end) = tp.cur_range();
+            // a token that ends right at a cursor is not under it
+            if tp.cur_type() != TokenType::Whitespace && tp.cur_type() != TokenType::Newline && end <= tok_end && start < tok_end {
+                ranges.push((tok_start, tok_end));
+            }
+            if tp.cur_type() == TokenType::ParenOpen {
+                let (open, close) = tp.cur_pair_range();
+                if open < start && end <= close {
+                    group = Some((tp.index, token_chunks[tp.index].pair_token));
+                }
+                else if open == start && end <= close + 1 {
+                    // the selection is this group, it can still take its name along
+                    if let Some(range) = TextCursorSet::get_call_range(tp.index, text_buffer) {
+                        ranges.push(range);
+                    }
+                }
+                else if close < start {
+                    tp.jump_to_pair();
+                }
+            }
+        }
+        let (first, last) = if let Some((open, close)) = group {
+            if let Some(range) = TextCursorSet::get_token_range(open + 1, close, text_buffer) {
+                ranges.push(range);
+            }
+            ranges.push((token_chunks[open].offset, token_chunks[close].offset + token_chunks[close].len));
+            if let Some(range) = TextCursorSet::get_call_range(open, text_buffer) {
+                ranges.push(range);
+            }
+            (open + 1, close)
+        }
+        else {
+            (0, token_chunks.len())
+        };
+        // walk the siblings in the group, nested groups are one sibling. expressions end on a
+        // delimiter or an assignment, statements on a ; or on a {} block that ends its line
+        let mut tp = TokenParser::new(&text_buffer.flat_text, token_chunks);
+        tp.next_index = first;
+        let mut expr_start = first;
+        let mut stmt_start = first;
+        while tp.next_index < last && tp.advance() {
+            let mut expr_end = None;
+            let mut stmt_end = false;
+            match tp.cur_type() {
+                TokenType::ParenOpen => {
+                    let is_block = tp.cur_char() == '{';
+                    let open = tp.index;
+                    tp.jump_to_pair();
+                    if is_block && tp.index > open {
+                        let mut scan = tp.next_index;
+                        while scan < last && token_chunks[scan].token_type == TokenType::Whitespace {
+                            scan += 1;
+                        }
+                        stmt_end = scan >= last || token_chunks[scan].token_type == TokenType::Newline;
+                    }
+                },
+                TokenType::Delimiter => {
+                    expr_end = Some(tp.index);
+                    stmt_end = tp.cur_char() == ';';
+                },
+                TokenType::Operator => {
+                    let op = tp.cur_chunk();
+                    if op == ['='] || op == ['=', '>'] || op.len() == 2 && op[1] == '=' && "+-*/|&^%".contains(op[0]) {
+                        expr_end = Some(tp.index);
+                    }
+                },
+                _ => ()
+            }
+            let next = tp.next_index;
+            if stmt_end && expr_end.is_none() {
+                expr_end = Some(next);
+            }
+            if next >= last && expr_end.is_none() {
+                expr_end = Some(last);
+            }
+            if let Some(expr_end) = expr_end {
+                if let Some(range) = TextCursorSet::get_token_range(expr_start, expr_end, text_buffer) {
+                    ranges.push(range);
+                }
+                expr_start = next;
+            }
+            if stmt_end || next >= last {
+                if let Some(range) = TextCursorSet::get_token_range(stmt_start, next.min(last), text_buffer) {
+                    ranges.push(range);
+                }
+                stmt_start = next;
+            }
+        }
+        ranges
+    }
+
+    pub fn expand_selection(&mut self, text_buffer: &TextBuffer) {
+        let before = self.set.clone();
+        let char_count = text_buffer.calc_char_count();
+        for cursor in &mut self.set {
+            let (start, end) = cursor.order();
+            let ranges = TextCursorSet::get_expand_ranges(start, end, text_buffer);
+            // the smallest range that is bigger than what we have
+            let range = ranges.iter()
+                .filter( | (s, e) | *s <= start && *e >= end && e - s > end - start)
+                .min_by_key( | (s, e) | e - s)
+                .cloned()
+                .unwrap_or((0, char_count));
+            cursor.tail = range.0;
+            cursor.head = range.1;
+            cursor.calc_max(text_buffer, (TextPos {row: 0, col: 0}, 0));
+        }
+        self.insert_undo_group += 1;
+        // cursors can grow over eachother
+        let last = self.set[self.last_cursor].clone();
+        self.set.sort_by_key( | v | v.order().0);
+        self.last_cursor = self.set.iter().position( | v | *v == last).unwrap_or(0);
+        self.fuse_adjacent(text_buffer);
+        if before != self.set {
+            self.expand_stack.push((before, self.set.clone()));
+        }
+    }
+
+    pub fn shrink_selection(&mut self) {
+        // we can only go back along the way we expanded
+        if let Some((before, after)) = self.expand_stack.pop() {
+            if after == self.set {
+                self.set = before;
+                self.last_cursor = self.last_cursor.min(self.set.len() - 1);
+                self.insert_undo_group += 1;
+                return
+            }
+        }
+        self.expand_stack.truncate(0);
+    }
+
     pub fn get_token_highlight(&self, text_buffer: &TextBuffer) -> Vec<char> {
         let cursor = &self.set[self.last_cursor];
         if cursor.head != cursor.tail {
@@ -1386,3 +1582,144 @@ impl DrawCursors {
         return mark_spaces
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // a small stand-in for the language tokenizers: brackets, strings, delimiters and operators
+    fn tokenized(text: &str) -> TextBuffer {
+        let mut text_buffer = TextBuffer::from_utf8(text);
+        let chars: Vec<char> = text.chars().collect();
+        let is_ident = | c: char | c.is_alphanumeric() || c == '_';
+        let mut pair_stack = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let start = i;
+            let c = chars[i];
+            i += 1;
+            let token_type = if c == '\n' {
+                TokenType::Newline
+            }
+            else if c == ' ' {
+                while i < chars.len() && chars[i] == ' ' {i += 1}
+                TokenType::Whitespace
+            }
+            else if "([{".contains(c) {
+                TokenType::ParenOpen
+            }
+            else if ")]}".contains(c) {
+                TokenType::ParenClose
+            }
+            else if c == '"' {
+                while i < chars.len() && chars[i] != '"' {i += 1}
+                i = (i + 1).min(chars.len());
+                TokenType::String
+            }
+            else if ";,".contains(c) {
+                TokenType::Delimiter
+            }
+            else if is_ident(c) {
+                while i < chars.len() && is_ident(chars[i]) {i += 1}
+                TokenType::Identifier
+            }
+            else {
+                while i < chars.len() && "=+-*/<>!&|".contains(chars[i]) {i += 1}
+                TokenType::Operator
+            };
+            TokenChunk::push_with_pairing(&mut text_buffer.token_chunks, &mut pair_stack, chars.get(i).cloned().unwrap_or('\0'), start, i, token_type);
+        }
+        text_buffer.flat_text = chars;
+        text_buffer
+    }
+    
+    fn cursors_at(text_buffer: &TextBuffer, at: &[&str]) -> TextCursorSet {
+        let text: String = text_buffer.flat_text.iter().collect();
+        let mut cursors = TextCursorSet::new();
+        cursors.set = at.iter().map( | at | {
+            let offset = text.find(at).unwrap();
+            TextCursor {head: offset, tail: offset, max: 0}
+        }).collect();
+        cursors
+    }
+    
+    fn selected(cursors: &TextCursorSet, text_buffer: &TextBuffer) -> Vec<String> {
+        cursors.set.iter().map( | cursor | {
+            let (start, end) = cursor.order();
+            text_buffer.flat_text[start..end].iter().collect()
+        }).collect()
+    }
+    
+    fn expand(cursors: &mut TextCursorSet, text_buffer: &TextBuffer) -> String {
+        cursors.expand_selection(text_buffer);
+        selected(cursors, text_buffer).join("|")
+    }
+    
+    #[test]
+    fn expands_through_nested_brackets() {
+        let text_buffer = tokenized("let x = foo(a, (b + c), d);\nlet y = 2;");
+        let mut cursors = cursors_at(&text_buffer, &["b +"]);
+        assert_eq!(expand(&mut cursors, &text_buffer), "b");
+        assert_eq!(expand(&mut cursors, &text_buffer), "b + c");
+        assert_eq!(expand(&mut cursors, &text_buffer), "(b + c)");
+        assert_eq!(expand(&mut cursors, &text_buffer), "a, (b + c), d");
+        assert_eq!(expand(&mut cursors, &text_buffer), "(a, (b + c), d)");
+        assert_eq!(expand(&mut cursors, &text_buffer), "foo(a, (b + c), d)");
+        assert_eq!(expand(&mut cursors, &text_buffer), "let x = foo(a, (b + c), d);");
+        assert_eq!(expand(&mut cursors, &text_buffer), "let x = foo(a, (b + c), d);\nlet y = 2;");
+        // nothing left to grow into
+        assert_eq!(expand(&mut cursors, &text_buffer), "let x = foo(a, (b + c), d);\nlet y = 2;");
+        assert_eq!(cursors.expand_stack.len(), 8);
+    }
+    
+    #[test]
+    fn brackets_in_strings_dont_count() {
+        let text_buffer = tokenized("call(\"x (y\", z);");
+        let mut cursors = cursors_at(&text_buffer, &["z"]);
+        assert_eq!(expand(&mut cursors, &text_buffer), "z");
+        assert_eq!(expand(&mut cursors, &text_buffer), "\"x (y\", z");
+        assert_eq!(expand(&mut cursors, &text_buffer), "(\"x (y\", z)");
+        let mut cursors = cursors_at(&text_buffer, &["x ("]);
+        assert_eq!(expand(&mut cursors, &text_buffer), "x");
+        assert_eq!(expand(&mut cursors, &text_buffer), "\"x (y\"");
+        assert_eq!(expand(&mut cursors, &text_buffer), "\"x (y\", z");
+    }
+    
+    #[test]
+    fn every_cursor_expands_by_itself() {
+        let text_buffer = tokenized("f(a) + g(bb, c)");
+        let mut cursors = cursors_at(&text_buffer, &["a)", "bb"]);
+        assert_eq!(expand(&mut cursors, &text_buffer), "a|bb");
+        assert_eq!(expand(&mut cursors, &text_buffer), "(a)|bb, c");
+        assert_eq!(expand(&mut cursors, &text_buffer), "f(a)|(bb, c)");
+    }
+    
+    #[test]
+    fn shrink_walks_back_until_the_cursor_moves() {
+        let text_buffer = tokenized("foo(a, (b + c));");
+        let mut cursors = cursors_at(&text_buffer, &["b +"]);
+        let start = cursors.set.clone();
+        expand(&mut cursors, &text_buffer);
+        expand(&mut cursors, &text_buffer);
+        expand(&mut cursors, &text_buffer);
+        cursors.shrink_selection();
+        assert_eq!(selected(&cursors, &text_buffer), vec!["b + c"]);
+        cursors.shrink_selection();
+        cursors.shrink_selection();
+        assert!(cursors.set == start);
+        // an empty stack leaves the selection alone
+        cursors.shrink_selection();
+        assert!(cursors.set == start);
+        
+        // after moving the cursor the old expansions dont apply anymore
+        expand(&mut cursors, &text_buffer);
+        expand(&mut cursors, &text_buffer);
+        cursors.move_right(1, false, &text_buffer);
+        let moved = cursors.set.clone();
+        cursors.shrink_selection();
+        assert!(cursors.set == moved);
+        assert_eq!(cursors.expand_stack.len(), 0);
+        // the cursor went past the inner group
+        assert_eq!(expand(&mut cursors, &text_buffer), ")");
+    }
+}
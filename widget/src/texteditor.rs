@@ -4,6 +4,8 @@ use crate::textbuffer::*;
 use crate::textcursor::*;
 use crate::scrollshadow::*;
 use crate::tokentype::*;
+use crate::completionlist::*;
+use crate::textvim::*;
 
 #[derive(Clone)]
 pub struct TextEditor {
@@ -23,7 +25,10 @@ pub struct TextEditor {
     pub search_marker: Quad,
     pub text: Text,
     pub line_number_text: Text,
+    pub vim_status_text: Text,
     pub cursors: TextCursorSet,
+    pub completion: CompletionList,
+    pub vim: TextVim,
 
     //pub base_font_size: f32,
     pub open_font_scale: f32,
@@ -41,6 +46,7 @@ pub struct TextEditor {
     pub draw_cursor_row: bool,
     pub search_markers_bypass: Vec<TextCursor>,
     pub folding_depth: usize,
+    pub folded_rows: Vec<usize>,
     pub colors: CodeEditorColors,
 
     pub read_only: bool,
@@ -74,6 +80,7 @@ pub struct TextEditor {
     pub _select_scroll: Option<SelectScroll>,
     pub _grid_select_corner: Option<TextPos>,
     pub _is_row_select: bool,
+    pub _paste_read: Option<(ClipboardRead, PasteAs)>,
     //pub _line_chunk: Vec<(f32, char)>,
 
     //pub _highlight_selection: Vec<char>,
@@ -94,6 +101,8 @@ pub struct TextEditor {
     pub _draw_cursors: DrawCursors,
     pub _draw_search: DrawCursors,
     pub _draw_messages: DrawCursors,
+    pub _draw_drop: DrawCursors,
+    pub _drop_cursors: Vec<TextCursor>,
 
     pub _cursor_blink_timer: Timer,
     pub _cursor_blink_flipflop: f32,
@@ -103,7 +112,14 @@ pub struct TextEditor {
     pub _last_tabs: usize,
     pub _newline_tabs: usize,
 
-    pub _last_lag_mutation_id: u32
+    pub _last_lag_mutation_id: u32,
+    // folded_rows are up to date with the text buffer line shifts until here
+    pub _folds_mutation_id: u32,
+
+    pub _fold_blocks: Vec<FoldBlock>,
+    pub _fold_hidden: Vec<FoldBlock>,
+    pub _fold_next: usize,
+    pub _line_number_skip: bool,
 }
 
 #[derive(Clone, PartialEq)]
@@ -116,7 +132,20 @@ pub enum TextEditorEvent {
     KeyFocusLost,
     Escape,
     Search(String),
-    Decl(String)
+    Decl(String),
+    Hover,
+    Complete,
+    GotoDefinition,
+    Save,
+    OpenFiles(Vec<String>)
+}
+
+// what a clipboard read gets inserted as
+#[derive(Clone, Copy, PartialEq)]
+pub enum PasteAs {
+    Text,
+    Html,
+    StringLiteral
 }
 
 #[derive(Default, Clone)]
@@ -157,11 +186,50 @@ pub struct CodeEditorColors {
 
 impl TextEditor {
 
+    pub fn command_find() -> CommandId {uid!()}
+    pub fn command_find_declaration() -> CommandId {uid!()}
+    pub fn command_auto_format() -> CommandId {uid!()}
+    pub fn command_complete() -> CommandId {uid!()}
+    pub fn command_hover() -> CommandId {uid!()}
+    pub fn command_goto_definition() -> CommandId {uid!()}
+    pub fn command_fold_block() -> CommandId {uid!()}
+    pub fn command_unfold_block() -> CommandId {uid!()}
+    pub fn command_select_next_match() -> CommandId {uid!()}
+    pub fn command_select_prev_match() -> CommandId {uid!()}
+    pub fn command_add_next_match() -> CommandId {uid!()}
+    pub fn command_add_prev_match() -> CommandId {uid!()}
+    pub fn command_paste_html() -> CommandId {uid!()}
+    pub fn command_paste_string() -> CommandId {uid!()}
+    pub fn command_expand_selection() -> CommandId {uid!()}
+    pub fn command_shrink_selection() -> CommandId {uid!()}
+
+    pub fn default_keymap(cx: &mut Cx) {
+        Self::command_find().set_name(cx, "editor.find").bind_in(cx, "editor", "Cmd+F");
+        Self::command_find_declaration().set_name(cx, "editor.find_declaration").bind_in(cx, "editor", "Cmd+S");
+        Self::command_auto_format().set_name(cx, "editor.auto_format").bind_in(cx, "editor", "Cmd+Return");
+        Self::command_complete().set_name(cx, "editor.complete").bind_in(cx, "editor", "Ctrl+Space");
+        Self::command_hover().set_name(cx, "editor.hover").bind_in(cx, "editor", "F1");
+        Self::command_goto_definition().set_name(cx, "editor.goto_definition").bind_in(cx, "editor", "F12");
+        Self::command_fold_block().set_name(cx, "editor.fold_block").bind_in(cx, "editor", "Cmd+Shift+[");
+        Self::command_unfold_block().set_name(cx, "editor.unfold_block").bind_in(cx, "editor", "Cmd+Shift+]");
+        Self::command_select_next_match().set_name(cx, "editor.select_next_match").bind_in(cx, "editor", "Cmd+D");
+        Self::command_select_prev_match().set_name(cx, "editor.select_prev_match").bind_in(cx, "editor", "Cmd+E");
+        Self::command_add_next_match().set_name(cx, "editor.add_next_match").bind_in(cx, "editor", "Cmd+Shift+D");
+        Self::command_add_prev_match().set_name(cx, "editor.add_prev_match").bind_in(cx, "editor", "Cmd+Shift+E");
+        Self::command_paste_html().set_name(cx, "editor.paste_html").bind_in(cx, "editor", "Cmd+Shift+V");
+        Self::command_paste_string().set_name(cx, "editor.paste_string").bind_in(cx, "editor", "Cmd+Alt+V");
+        // structural selection, off the alt key that folds the code
+        Self::command_expand_selection().set_name(cx, "editor.expand_selection").bind_in(cx, "editor", "Cmd+Shift+Up");
+        Self::command_shrink_selection().set_name(cx, "editor.shrink_selection").bind_in(cx, "editor", "Cmd+Shift+Down");
+    }
+
     pub fn new(cx: &mut Cx) -> Self {
         Self {
             read_only: false,
             multiline: true,
             cursors: TextCursorSet::new(),
+            completion: CompletionList::new(cx),
+            vim: TextVim::new(),
             indent_lines: Quad {
                 z: 0.001,
                 ..Quad::new(cx)
@@ -201,6 +269,12 @@ impl TextEditor {
                 wrapping: Wrapping::Line,
                 ..Text::new(cx)
             },
+            vim_status_text: Text {
+                z: 2.3,
+                shader: live_shader!(cx, makepad_render::text::shader),
+                wrapping: Wrapping::Line,
+                ..Text::new(cx)
+            },
             //base_font_size: 8.0,
             open_font_scale: 1.0,
             folded_font_scale: 0.07,
@@ -233,6 +307,7 @@ impl TextEditor {
             _anim_select: Vec::new(),
             _grid_select_corner: None,
             _is_row_select: false,
+            _paste_read: None,
             _view_area: Area::Empty,
             //_bg_area: Area::Empty,
             _highlight_area: Area::Empty,
@@ -247,6 +322,7 @@ impl TextEditor {
             _line_largest_font: 0.,
             _final_fill_height: 0.,
             folding_depth: 2,
+            folded_rows: Vec::new(),
             _anim_folding: AnimFolding {
                 state: AnimFoldingState::Open,
                 focussed_line: 0,
@@ -256,6 +332,8 @@ impl TextEditor {
             _draw_cursors: DrawCursors::new(),
             _draw_search: DrawCursors::new(),
             _draw_messages: DrawCursors::new(),
+            _draw_drop: DrawCursors::new(),
+            _drop_cursors: Vec::new(),
 
             _paren_stack: Vec::new(),
             _indent_stack: Vec::new(),
@@ -272,6 +350,11 @@ impl TextEditor {
             _cursor_blink_flipflop: 0.,
             _cursor_area: Area::Empty,
             _last_lag_mutation_id: 0,
+            _folds_mutation_id: 0,
+            _fold_blocks: Vec::new(),
+            _fold_hidden: Vec::new(),
+            _fold_next: 0,
+            _line_number_skip: false,
             _last_tabs: 0,
             _newline_tabs: 0,
         }
@@ -520,6 +603,7 @@ impl TextEditor {
         self.cursor_row.color = live_color!(cx, self::color_cursor_row);
         self.text.text_style = live_text_style!(cx, self::text_style_editor_text);
         self.line_number_text.text_style = live_text_style!(cx, self::text_style_editor_text);
+        self.vim_status_text.text_style = live_text_style!(cx, self::text_style_editor_text);
         
         self.bg.shader = live_shader!(cx, self::shader_bg);
         self.indent_lines.shader = live_shader!(cx, self::shader_indent_lines);
@@ -549,8 +633,18 @@ impl TextEditor {
         cx.set_down_mouse_cursor(MouseCursor::Text);
         // give us the focus
         self.set_key_focus(cx);
+        self.completion.close(cx);
         self._undo_id += 1;
 
+        // the fold markers live in the click margin next to the line numbers
+        if self.draw_line_numbers && fe.rel.x >= self.line_number_width - self.line_number_click_margin && fe.rel.x < self.line_number_width {
+            let offset = self.compute_offset_from_ypos(cx, fe.abs.y, text_buffer, false);
+            let row = text_buffer.offset_to_text_pos(offset).row;
+            if self.toggle_fold_at_row(cx, row, text_buffer) {
+                return
+            }
+        }
+
         let offset;
         //let scroll_pos = self._bg_area.get_scroll_pos(cx);
         if fe.rel.x < self.line_number_width - self.line_number_click_margin {
@@ -652,7 +746,12 @@ impl TextEditor {
         }
     }
 
-    fn handle_finger_up(&mut self, cx: &mut Cx, _fe: &FingerUpEvent, _text_buffer: &mut TextBuffer) {
+    fn handle_finger_up(&mut self, cx: &mut Cx, _fe: &FingerUpEvent, text_buffer: &mut TextBuffer) {
+        // selecting with the mouse fills the primary selection, like other X11 apps do
+        let selection = self.cursors.get_all_as_string(text_buffer);
+        if selection.len() > 0 {
+            cx.clipboard_write(ClipboardSelection::Primary, ClipboardContent::text(&selection));
+        }
         self.cursors.clear_last_clamp_range();
         self._select_scroll = None;
         self._last_finger_move = None;
@@ -662,53 +761,80 @@ impl TextEditor {
         self.reset_cursor_blinker(cx);
     }
 
-    fn handle_key_down(&mut self, cx: &mut Cx, ke: &KeyEvent, text_buffer: &mut TextBuffer) {
-        let cursor_moved = match ke.key_code {
-            KeyCode::KeyE => {
-                if ke.modifiers.logo || ke.modifiers.control {
-                    let pos = self.cursors.get_last_cursor_head();
-                    let mut moved = false;
-                    for result in text_buffer.markers.search_cursors.iter().rev() {
-                        if result.head < pos {
-                            if ke.modifiers.shift {
-                                self.cursors.add_last_cursor_head_and_tail(result.head, result.tail, text_buffer);
-                            }
-                            else {
-                                self.cursors.set_last_cursor_head_and_tail(result.head, result.tail, text_buffer);
-                            }
-                            moved = true;
-                            break;
-                        }
-                    }
-
-                    moved
-                }
-                else {
-                    false
-                }
+    fn select_match(&mut self, text_buffer: &mut TextBuffer, next: bool, add: bool) -> bool {
+        let pos = self.cursors.get_last_cursor_head();
+        let result = if next {
+            text_buffer.markers.search_cursors.iter().find( | result | result.tail > pos)
+        }
+        else {
+            text_buffer.markers.search_cursors.iter().rev().find( | result | result.head < pos)
+        };
+        if let Some(result) = result {
+            if add {
+                self.cursors.add_last_cursor_head_and_tail(result.head, result.tail, text_buffer);
             }
-            KeyCode::KeyD => {
-                if ke.modifiers.logo || ke.modifiers.control {
-                    let pos = self.cursors.get_last_cursor_head();
-                    let mut moved = false;
-                    for result in text_buffer.markers.search_cursors.iter() {
-                        if result.tail > pos {
-                            if ke.modifiers.shift {
-                                self.cursors.add_last_cursor_head_and_tail(result.head, result.tail, text_buffer);
-                            }
-                            else {
-                                self.cursors.set_last_cursor_head_and_tail(result.head, result.tail, text_buffer);
-                            }
-                            moved = true;
-                            break;
-                        }
-                    }
-                    moved
-                }
-                else {
-                    false
-                }
+            else {
+                self.cursors.set_last_cursor_head_and_tail(result.head, result.tail, text_buffer);
             }
+            return true
+        }
+        false
+    }
+
+    fn handle_command(&mut self, cx: &mut Cx, command: CommandId, text_buffer: &mut TextBuffer) {
+        let cursor_moved = if command == Self::command_select_next_match() {
+            self.select_match(text_buffer, true, false)
+        }
+        else if command == Self::command_select_prev_match() {
+            self.select_match(text_buffer, false, false)
+        }
+        else if command == Self::command_add_next_match() {
+            self.select_match(text_buffer, true, true)
+        }
+        else if command == Self::command_add_prev_match() {
+            self.select_match(text_buffer, false, true)
+        }
+        else if command == Self::command_expand_selection() && self.multiline {
+            self.cursors.expand_selection(text_buffer);
+            self._undo_id += 1;
+            true
+        }
+        else if command == Self::command_shrink_selection() && self.multiline {
+            self.cursors.shrink_selection();
+            self._undo_id += 1;
+            true
+        }
+        else if command == Cx::command_undo() && !self.read_only {
+            text_buffer.undo(true, &mut self.cursors);
+            true
+        }
+        else if command == Cx::command_redo() && !self.read_only {
+            text_buffer.redo(true, &mut self.cursors);
+            true
+        }
+        else if command == Cx::command_cut() && !self.read_only {
+            // the actual copy comes from the TextCopy event from the platform layer
+            self.cursors.replace_text("", text_buffer, None);
+            true
+        }
+        else if command == Cx::command_select_all() {
+            self.cursors.select_all(text_buffer);
+            // don't scroll!
+            self.view.redraw_view_area(cx);
+            false
+        }
+        else {
+            false
+        };
+        if cursor_moved {
+            self.scroll_last_cursor_visible(cx, text_buffer, 0.);
+            self.view.redraw_view_area(cx);
+            self.reset_cursor_blinker(cx);
+        }
+    }
+
+    fn handle_key_down(&mut self, cx: &mut Cx, ke: &KeyEvent, text_buffer: &mut TextBuffer) {
+        let cursor_moved = match ke.key_code {
             KeyCode::ArrowUp => {
                 if !self.multiline {
                     false
@@ -717,7 +843,7 @@ impl TextEditor {
                     false
                 }
                 else {
-                    if self._anim_folding.state.is_folded() && self.cursors.set.len() == 1 {
+                    if (self._anim_folding.state.is_folded() || self._fold_hidden.len() > 0) && self.cursors.set.len() == 1 {
                         // compute the nearest nonfolded line up
                         let delta = self.compute_next_unfolded_line_up(text_buffer);
                         self.cursors.move_up(delta, ke.modifiers.shift, text_buffer);
@@ -737,7 +863,7 @@ impl TextEditor {
                     false
                 }
                 else {
-                    if self._anim_folding.state.is_folded() && self.cursors.set.len() == 1 {
+                    if (self._anim_folding.state.is_folded() || self._fold_hidden.len() > 0) && self.cursors.set.len() == 1 {
                         // compute the nearest nonfolded line down
                         let delta = self.compute_next_unfolded_line_down(text_buffer);
                         self.cursors.move_down(delta, ke.modifiers.shift, text_buffer);
@@ -807,46 +933,6 @@ impl TextEditor {
                     false
                 }
             },
-            KeyCode::KeyZ => {
-                if !self.read_only {
-                    if ke.modifiers.logo || ke.modifiers.control {
-                        if ke.modifiers.shift { // redo
-                            text_buffer.redo(true, &mut self.cursors);
-                            true
-                        }
-                        else { // undo
-                            text_buffer.undo(true, &mut self.cursors);
-                            true
-                        }
-                    }
-                    else {
-                        false
-                    }
-                }
-                else {
-                    false
-                }
-            },
-            KeyCode::KeyX => { // cut, the actual copy comes from the TextCopy event from the platform layer
-                if !self.read_only && (ke.modifiers.logo || ke.modifiers.control) { // cut
-                    self.cursors.replace_text("", text_buffer, None);
-                    true
-                }
-                else {
-                    false
-                }
-            },
-            KeyCode::KeyA => { // select all
-                if ke.modifiers.logo || ke.modifiers.control { // cut
-                    self.cursors.select_all(text_buffer);
-                    // don't scroll!
-                    self.view.redraw_view_area(cx);
-                    false
-                }
-                else {
-                    false
-                }
-            },
             KeyCode::Alt => {
                 // how do we find the center line of the view
                 // its simply the top line
@@ -959,6 +1045,8 @@ impl TextEditor {
     }
 
     pub fn handle_text_editor(&mut self, cx: &mut Cx, event: &mut Event, text_buffer: &mut TextBuffer) -> TextEditorEvent {
+        // the buffer may have changed through another editor or outside of one
+        self.shift_folds_after_edit(text_buffer);
         if self.view.handle_scroll_view(cx, event) {
             if let Some(last_finger_move) = self._last_finger_move {
                 if let Some(grid_select_corner) = self._grid_select_corner {
@@ -974,6 +1062,13 @@ impl TextEditor {
             // generate the entire file as GPU text-buffer just the visible area
             // in JS this wasn't possible performantly but in Rust its a breeze.
             self.view.redraw_view_area(cx);
+            if self.completion.is_open {
+                self.completion.view.redraw_view_area(cx);
+            }
+        }
+        if let CompletionListEvent::Insert(item) = self.completion.handle_completion_list(cx, event) {
+            self.completion.insert_completion(&item, &mut self.cursors, text_buffer);
+            self.view.redraw_view_area(cx);
         }
         let last_mutation_id = text_buffer.mutation_id;
         // global events
@@ -1047,21 +1142,60 @@ impl TextEditor {
                     }
                 }
             },
+            Event::ClipboardRead(cr) => if let Some((read, paste_as)) = &mut self._paste_read {
+                if let Some(content) = read.resolve(cr) {
+                    let text = match paste_as {
+                        PasteAs::Text => content.text.clone(),
+                        PasteAs::Html => content.html.clone().or(content.text.clone()),
+                        PasteAs::StringLiteral => content.text.as_ref().map( | text | {
+                            let mut out = String::from("\"");
+                            for c in text.chars() {
+                                match c {
+                                    '\\' => out.push_str("\\\\"),
+                                    '"' => out.push_str("\\\""),
+                                    '\n' => out.push_str("\\n"),
+                                    '\r' => out.push_str("\\r"),
+                                    '\t' => out.push_str("\\t"),
+                                    _ => out.push(c)
+                                }
+                            }
+                            out.push('"');
+                            out
+                        })
+                    };
+                    self._paste_read = None;
+                    if let Some(text) = text {
+                        if !self.read_only && text.len() > 0 {
+                            self.cursors.replace_text(&text, text_buffer, None);
+                            self.scroll_last_cursor_visible(cx, text_buffer, 0.);
+                            self.view.redraw_view_area(cx);
+                            self.reset_cursor_blinker(cx);
+                        }
+                    }
+                }
+            },
             _ => ()
         }
         // editor local
         match event.hits(cx, self.view.get_view_area(cx), HitOpt::default()) {
             Event::KeyFocus(_kf) => {
+                cx.set_key_focus_context("editor");
                 self.reset_cursor_blinker(cx);
                 self.view.redraw_view_area(cx);
                 return TextEditorEvent::KeyFocus
             },
             Event::KeyFocusLost(_kf) => {
+                self.completion.close(cx);
                 self.view.redraw_view_area(cx);
                 return TextEditorEvent::KeyFocusLost
             },
             Event::FingerDown(fe) => {
                 self.handle_finger_down(cx, &fe, text_buffer);
+                // middle click pastes the primary selection where it clicked
+                if fe.digit == 2 && !self.read_only {
+                    let read = cx.clipboard_read(ClipboardSelection::Primary, &[ClipboardFormat::Text]);
+                    self._paste_read = Some((read, PasteAs::Text));
+                }
             },
             Event::FingerHover(_fe) => {
                 cx.set_hover_mouse_cursor(MouseCursor::Text);
@@ -1072,42 +1206,104 @@ impl TextEditor {
             Event::FingerMove(fe) => {
                 self.handle_finger_move(cx, &fe, text_buffer);
             },
+            Event::Drag(de) => {
+                // a caret of its own shows where it would go, the cursors and focus stay until the drop
+                let drop_cursors = if !self.read_only && de.state != HoverState::Out {
+                    cx.accept_drag(DragAction::Copy);
+                    let offset = self.text.find_closest_offset(cx, &self._text_area, de.abs);
+                    vec![TextCursor {head: offset, tail: offset, max: 0}]
+                }
+                else {
+                    Vec::new()
+                };
+                if drop_cursors != self._drop_cursors {
+                    self._drop_cursors = drop_cursors;
+                    self.view.redraw_view_area(cx);
+                }
+            },
+            Event::Drop(de) => match de.payload {
+                // files from another application get opened instead
+                DragPayload::Files(paths) if de.is_external => {
+                    self.clear_drop_cursor(cx);
+                    return TextEditorEvent::OpenFiles(paths)
+                },
+                payload => {
+                    self.clear_drop_cursor(cx);
+                    let offset = self.text.find_closest_offset(cx, &self._text_area, de.abs);
+                    self.cursors.clear_and_set_last_cursor_head(offset, text_buffer);
+                    self.cursors.replace_text(&payload.to_text(), text_buffer, None);
+                    self.set_key_focus(cx);
+                    self.view.redraw_view_area(cx);
+                }
+            },
             Event::KeyDown(ke) => {
+                match self.completion.handle_completion_key(cx, &ke) {
+                    CompletionListEvent::None => (),
+                    CompletionListEvent::Insert(item) => {
+                        self.completion.insert_completion(&item, &mut self.cursors, text_buffer);
+                        self.view.redraw_view_area(cx);
+                        return TextEditorEvent::Change
+                    },
+                    _ => return TextEditorEvent::None
+                }
+                if self.vim.enabled && !self.read_only {
+                    match self.handle_vim_input(cx, VimInput::Key(ke.clone()), text_buffer) {
+                        VimAction::PassThrough => (),
+                        VimAction::Save => return TextEditorEvent::Save,
+                        _ => return if last_mutation_id != text_buffer.mutation_id {TextEditorEvent::Change} else {TextEditorEvent::None}
+                    }
+                }
                 if ke.key_code == KeyCode::Escape {
                     let pos = self.cursors.get_last_cursor_head();
                     self.cursors.clear_and_set_last_cursor_head_and_tail(pos, pos, text_buffer);
                     return TextEditorEvent::Escape
-                }/*
-                if ke.key_code == KeyCode::KeyD && (ke.modifiers.logo || ke.modifiers.control) {
-                    // check if d is inside a search cursor
-                    let pos = self.cursors.get_last_cursor_head();
-                    let mut repeat = false;
-                    for result in text_buffer.markers.search_cursors.iter().rev(){
-                        if pos >= result.tail && pos <= result.head {
-                            repeat = true;
-                            break;
-                        }
-                    }/*
-                    if !repeat{
-                        let search = self.cursors.get_ident_around_last_cursor_and_set(text_buffer);
-                        // lets select the ident
-
-                        return TextEditorEvent::Decl(search)
-                    }*/
-                }*/
-                if ke.key_code == KeyCode::KeyF && (ke.modifiers.logo || ke.modifiers.control) {
+                }
+                self.handle_key_down(cx, &ke, text_buffer);
+                self.update_completion_filter(cx, text_buffer);
+            },
+            Event::Command(command) => {
+                if command == Self::command_find() {
                     let search = self.cursors.get_ident_around_last_cursor_and_set(text_buffer);
                     return TextEditorEvent::Search(search)
                 }
-                if ke.key_code == KeyCode::KeyS && (ke.modifiers.logo || ke.modifiers.control) {
+                if command == Self::command_find_declaration() {
                     let search = self.cursors.get_ident_around_last_cursor_and_set(text_buffer);
                     return TextEditorEvent::Decl(search)
                 }
-
-                if ke.key_code == KeyCode::Return && (ke.modifiers.logo || ke.modifiers.control) {
+                if command == Self::command_auto_format() {
                     return TextEditorEvent::AutoFormat
                 }
-                self.handle_key_down(cx, &ke, text_buffer);
+                if command == Self::command_complete() {
+                    // providers fill the list in through add_items
+                    let filter = self.cursors.get_ident_before_last_cursor(text_buffer);
+                    self.completion.open(cx, &filter, Vec::new());
+                    return TextEditorEvent::Complete
+                }
+                if command == Self::command_hover() {
+                    return TextEditorEvent::Hover
+                }
+                if command == Self::command_goto_definition() {
+                    return TextEditorEvent::GotoDefinition
+                }
+                if command == Self::command_fold_block() {
+                    self.fold_block_at_cursor(cx, text_buffer);
+                    return TextEditorEvent::None
+                }
+                if command == Self::command_unfold_block() {
+                    self.unfold_block_at_cursor(cx, text_buffer);
+                    return TextEditorEvent::None
+                }
+                if command == Self::command_paste_html() && !self.read_only {
+                    let read = cx.clipboard_read(ClipboardSelection::Clipboard, &[ClipboardFormat::Html, ClipboardFormat::Text]);
+                    self._paste_read = Some((read, PasteAs::Html));
+                    return TextEditorEvent::None
+                }
+                if command == Self::command_paste_string() && !self.read_only {
+                    let read = cx.clipboard_read(ClipboardSelection::Clipboard, &[ClipboardFormat::Text]);
+                    self._paste_read = Some((read, PasteAs::StringLiteral));
+                    return TextEditorEvent::None
+                }
+                self.handle_command(cx, command, text_buffer);
             },
             Event::KeyUp(ke) => {
                 match ke.key_code {
@@ -1119,8 +1315,10 @@ impl TextEditor {
                 self.reset_cursor_blinker(cx);
             },
             Event::TextInput(te) => {
-                if !self.read_only {
+                // in vim normal mode typing is commands, not text
+                if !self.read_only && (!self.vim.enabled || self.handle_vim_input(cx, VimInput::Text(te.clone()), text_buffer) == VimAction::PassThrough) {
                     self.handle_text_input(cx, &te, text_buffer);
+                    self.update_completion_filter(cx, text_buffer);
                 }
             },
             Event::TextCopy(_) => match event { // access the original event
@@ -1134,13 +1332,154 @@ impl TextEditor {
         // i need to know if selection changed, ifso
         //
         if last_mutation_id != text_buffer.mutation_id {
+            self.shift_folds_after_edit(text_buffer);
             TextEditorEvent::Change
         }
         else {
+            self.unfold_around_cursors(cx);
             TextEditorEvent::None
         }
     }
 
+    fn handle_vim_input(&mut self, cx: &mut Cx, input: VimInput, text_buffer: &mut TextBuffer) -> VimAction {
+        let action = if self.vim.mode == VimMode::Insert {
+            match &input {
+                VimInput::Key(ke) if ke.key_code == KeyCode::Escape => {
+                    self.vim.leave_insert(&mut self.cursors, text_buffer);
+                    VimAction::None
+                },
+                VimInput::Key(ke) if ke.modifiers.control || ke.modifiers.logo => VimAction::PassThrough,
+                _ => {
+                    self.vim.record(&input);
+                    VimAction::PassThrough
+                }
+            }
+        }
+        else {
+            self.vim.handle_vim_input(input, &mut self.cursors, text_buffer)
+        };
+        match action {
+            VimAction::Repeat(inputs) => {
+                // replay the last change, inserted text goes through the editor like it did the first time
+                self.vim.replaying = true;
+                for input in inputs {
+                    if self.handle_vim_input(cx, input.clone(), text_buffer) == VimAction::PassThrough {
+                        match input {
+                            VimInput::Key(ke) => self.handle_key_down(cx, &ke, text_buffer),
+                            VimInput::Text(te) => self.handle_text_input(cx, &te, text_buffer)
+                        }
+                    }
+                }
+                self.vim.replaying = false;
+                VimAction::None
+            },
+            VimAction::PassThrough => VimAction::PassThrough,
+            action => {
+                self.scroll_last_cursor_visible(cx, text_buffer, 0.);
+                self.view.redraw_view_area(cx);
+                self.reset_cursor_blinker(cx);
+                action
+            }
+        }
+    }
+
+    fn fold_block(&mut self, cx: &mut Cx, block: FoldBlock, text_buffer: &TextBuffer) {
+        if !self.folded_rows.contains(&block.row) {
+            self.folded_rows.push(block.row);
+            self.folded_rows.sort();
+        }
+        // cursors cant stay inside what we hide, park them on the open brace
+        if self.cursors.set.iter().any( | v | v.head > block.open && v.head < block.close) {
+            self.cursors.clear_and_set_last_cursor_head_and_tail(block.open, block.open, text_buffer);
+        }
+        self.view.redraw_view_area(cx);
+    }
+
+    pub fn toggle_fold_at_row(&mut self, cx: &mut Cx, row: usize, text_buffer: &TextBuffer) -> bool {
+        let block = if let Some(block) = self._fold_blocks.iter().find( | v | v.row == row) {
+            block.clone()
+        }
+        else {
+            return false
+        };
+        if self.folded_rows.contains(&row) {
+            self.folded_rows.retain( | v | *v != row);
+            self.view.redraw_view_area(cx);
+        }
+        else {
+            self.fold_block(cx, block, text_buffer);
+        }
+        true
+    }
+
+    pub fn fold_block_at_cursor(&mut self, cx: &mut Cx, text_buffer: &TextBuffer) {
+        let head = self.cursors.get_last_cursor_head();
+        let row = text_buffer.offset_to_text_pos(head).row;
+        // prefer the block that starts on this line, otherwise the innermost one around us
+        let block = self._fold_blocks.iter().find( | v | v.row == row)
+            .or_else( || self._fold_blocks.iter().filter( | v | v.open < head && head <= v.close).last())
+            .cloned();
+        if let Some(block) = block {
+            self.fold_block(cx, block, text_buffer);
+        }
+    }
+
+    pub fn unfold_block_at_cursor(&mut self, cx: &mut Cx, text_buffer: &TextBuffer) {
+        let head = self.cursors.get_last_cursor_head();
+        let row = text_buffer.offset_to_text_pos(head).row;
+        let fold_blocks = &self._fold_blocks;
+        let len = self.folded_rows.len();
+        self.folded_rows.retain( | folded | {
+            *folded != row && !fold_blocks.iter().any( | v | v.row == *folded && v.open <= head && head <= v.close)
+        });
+        if len != self.folded_rows.len() {
+            self.view.redraw_view_area(cx);
+        }
+    }
+
+    fn unfold_around_cursors(&mut self, cx: &mut Cx) {
+        // a jump (search, goto) or a click on the placeholder lands us inside a fold, so open it
+        let mut changed = false;
+        for block in &self._fold_hidden {
+            if self.cursors.set.iter().any( | v | v.head > block.open && v.head < block.close) {
+                self.folded_rows.retain( | v | *v != block.row);
+                changed = true;
+            }
+        }
+        if changed {
+            self.view.redraw_view_area(cx);
+        }
+    }
+
+    fn shift_folds_after_edit(&mut self, text_buffer: &TextBuffer) {
+        // folds move along with the lines each edit added or removed, in the order they happened
+        if self._folds_mutation_id == text_buffer.mutation_id {
+            return
+        }
+        if let Some(shifts) = text_buffer.line_shifts_after(self._folds_mutation_id) {
+            for shift in shifts {
+                self.folded_rows = self.folded_rows.iter().filter_map( | row | shift.shift_row(*row)).collect();
+            }
+        }
+        else { // we missed some, so we dont know where they went
+            self.folded_rows.truncate(0);
+        }
+        self._folds_mutation_id = text_buffer.mutation_id;
+    }
+
+    fn update_completion_filter(&mut self, cx: &mut Cx, text_buffer: &TextBuffer) {
+        if !self.completion.is_open {
+            return
+        }
+        let filter = self.cursors.get_ident_before_last_cursor(text_buffer);
+        if filter.len() == 0 {
+            self.completion.close(cx);
+        }
+        else {
+            self.completion.set_filter(cx, &filter);
+        }
+    }
+
     pub fn has_key_focus(&self, cx: &Cx) -> bool {
         cx.has_key_focus(self._view_area)
     }
@@ -1187,6 +1526,8 @@ impl TextEditor {
         self._draw_cursors = DrawCursors::new();
         self._draw_messages = DrawCursors::new();
         self._draw_search = DrawCursors::new();
+        self._draw_drop = DrawCursors::new();
+        self._draw_drop.set_next(&self._drop_cursors);
         self._tokens_on_line = 0;
         self._visible_lines = 0;
         self._newline_tabs = 0;
@@ -1199,17 +1540,53 @@ impl TextEditor {
             if self.search_markers_bypass.len() > 0 {&self.search_markers_bypass}else {&text_buffer.markers.search_cursors}
         );
         self._line_geometry.truncate(0);
+        self.compute_fold_blocks(text_buffer);
+        self._fold_next = 0;
+        self._line_number_skip = false;
         self._line_largest_font = self.text.text_style.font_size;
         self._last_indent_color = self.colors.indent_line_unknown;
         // indent
         cx.move_turtle(self.line_number_width, self.top_padding);
     }
 
+    fn compute_fold_blocks(&mut self, text_buffer: &TextBuffer) {
+        self._fold_blocks.truncate(0);
+        self._fold_hidden.truncate(0);
+        let token_chunks = &text_buffer.token_chunks;
+        let flat_text = &text_buffer.flat_text;
+        let mut row = 0;
+        for (index, tok) in token_chunks.iter().enumerate() {
+            match tok.token_type {
+                TokenType::Newline => {
+                    row += 1;
+                },
+                TokenType::ParenOpen => {
+                    // one foldable block per line, the first brace that spans lines
+                    if flat_text[tok.offset] != '{' || tok.pair_token <= index || self._fold_blocks.last().map_or(false, | v | v.row == row) {
+                        continue;
+                    }
+                    let close = token_chunks[tok.pair_token].offset;
+                    if flat_text[close] == '}' && flat_text[tok.offset..close].iter().any( | v | *v == '\n') {
+                        self._fold_blocks.push(FoldBlock {row: row, open: tok.offset, close: close});
+                    }
+                },
+                _ => ()
+            }
+        }
+        // a fold inside another fold is already hidden
+        for block in &self._fold_blocks {
+            if self.folded_rows.contains(&block.row) && self._fold_hidden.last().map_or(true, | v | block.open > v.close) {
+                self._fold_hidden.push(block.clone());
+            }
+        }
+    }
+
     pub fn begin_text_editor(&mut self, cx: &mut Cx, text_buffer: &TextBuffer) -> Result<(), ()> {
         // adjust dilation based on DPI factor
         self.view.begin_view(cx, live_layout!(cx, self::layout_bg)) ?;
         
         self.apply_style(cx);
+        self.shift_folds_after_edit(text_buffer);
 
         if !text_buffer.is_loaded {
             //et bg_inst = self.bg.begin_quad(cx, &Layout {
@@ -1232,6 +1609,17 @@ impl TextEditor {
                 self._set_key_focus_on_load = false;
                 self.set_key_focus(cx);
             }
+            // the whole buffer goes out as the value, only pay for that when someone listens
+            if cx.is_accessibility_enabled() {
+                cx.add_access_node(AccessNode {
+                    value: Some(text_buffer.get_as_string()),
+                    cursor: Some(self.cursors.get_last_cursor_head()),
+                    editable: !self.read_only,
+                    actions: vec![AccessAction::Focus],
+                    area: view_area,
+                    ..AccessNode::new(AccessRole::Text, "Editor")
+                });
+            }
 
             let inst = self.bg.begin_quad_fill(cx);
             inst.set_do_scroll(cx, false, false); // don't scroll the bg
@@ -1333,44 +1721,15 @@ impl TextEditor {
             walk: cx.get_rel_turtle_pos(),
             font_size: self._line_largest_font,
             was_folded: self._line_was_folded,
+            // the closing row of a fold shares the line of its header
+            is_hidden: self._line_number_skip,
             indent_id: if let Some((_, id)) = self._indent_stack.last() {*id}else {0.}
         };
 
-        // draw a linenumber if we are visible
-        let origin = cx.get_turtle_origin();
-        if self.draw_line_numbers && cx.turtle_line_is_visible(self._monospace_size.y, self._scroll_pos) {
-            // lets format a number, we go to 4 numbers
-            // yes this is dumb as rocks. but we need to be cheapnfast
-            let chunk = &mut self._line_number_chunk;
-            chunk.truncate(0);
-            let line_num = self._line_geometry.len() + 1 + self.line_number_offset;
-            let mut scale = 10000;
-            let mut fill = false;
-            loop {
-                let digit = ((line_num / scale) % 10) as u8;
-                if digit != 0 {
-                    fill = true;
-                }
-                if fill {
-                    chunk.push((48 + digit) as char);
-                }
-                else {
-                    chunk.push(' ');
-                }
-                if scale <= 1 {
-                    break
-                }
-                scale /= 10;
-            }
-            if line_num == self._last_cursor_pos.row + 1 {
-                self.line_number_text.color = self.colors.line_number_highlight;
-            }
-            else {
-                self.line_number_text.color = self.colors.line_number_normal;
-            }
-            let chunk_width = self._monospace_size.x * 5.0;
-            self.line_number_text.add_text(cx, origin.x + (self.line_number_width - chunk_width - self.line_number_click_margin), origin.y + line_geom.walk.y, 0, self._line_number_inst.as_mut().unwrap(), chunk, | _, _, _, _ | {0.});
+        if !self._line_number_skip {
+            self.draw_line_number(cx, line_geom.walk.y);
         }
+        self._line_number_skip = false;
 
         cx.turtle_new_line_min_height(self._monospace_size.y);
 
@@ -1413,6 +1772,92 @@ impl TextEditor {
         self._line_largest_font = self.text.text_style.font_size;
     }
 
+    fn draw_line_number(&mut self, cx: &mut Cx, y: f32) {
+        // draw a linenumber if we are visible
+        if !self.draw_line_numbers || !cx.turtle_line_is_visible(self._monospace_size.y, self._scroll_pos) {
+            return
+        }
+        let origin = cx.get_turtle_origin();
+        let row = self._line_geometry.len();
+        // lets format a number, we go to 4 numbers
+        // yes this is dumb as rocks. but we need to be cheapnfast
+        let chunk = &mut self._line_number_chunk;
+        chunk.truncate(0);
+        let line_num = row + 1 + self.line_number_offset;
+        let mut scale = 10000;
+        let mut fill = false;
+        loop {
+            let digit = ((line_num / scale) % 10) as u8;
+            if digit != 0 {
+                fill = true;
+            }
+            if fill {
+                chunk.push((48 + digit) as char);
+            }
+            else {
+                chunk.push(' ');
+            }
+            if scale <= 1 {
+                break
+            }
+            scale /= 10;
+        }
+        if line_num == self._last_cursor_pos.row + 1 {
+            self.line_number_text.color = self.colors.line_number_highlight;
+        }
+        else {
+            self.line_number_text.color = self.colors.line_number_normal;
+        }
+        let chunk_width = self._monospace_size.x * 5.0;
+        self.line_number_text.add_text(cx, origin.x + (self.line_number_width - chunk_width - self.line_number_click_margin), origin.y + y, 0, self._line_number_inst.as_mut().unwrap(), chunk, | _, _, _, _ | {0.});
+
+        // and the fold marker in the click margin
+        if self._fold_blocks.binary_search_by_key(&row, | v | v.row).is_ok() {
+            let folded = self.folded_rows.contains(&row);
+            self.line_number_text.color = if folded {self.colors.line_number_highlight} else {self.colors.whitespace};
+            let chunk = &mut self._line_number_chunk;
+            chunk.truncate(0);
+            chunk.push(if folded {'+'} else {'-'});
+            self.line_number_text.add_text(cx, origin.x + self.line_number_width - self.line_number_click_margin, origin.y + y, 0, self._line_number_inst.as_mut().unwrap(), chunk, | _, _, _, _ | {0.});
+        }
+    }
+
+    // skips whats inside folded blocks, it still keeps a line geometry per row
+    fn draw_folded_chunk(&mut self, cx: &mut Cx, token_chunk: &TokenChunk) -> bool {
+        let offset = token_chunk.offset;
+        while let Some(block) = self._fold_hidden.get(self._fold_next) {
+            if offset < block.close {
+                break;
+            }
+            self._fold_next += 1;
+        }
+        let block = if let Some(block) = self._fold_hidden.get(self._fold_next) {block.clone()} else {return false};
+        if offset <= block.open {
+            return false
+        }
+        if offset == block.open + 1 {
+            if let Some(geom) = cx.walk_turtle_right_no_wrap(self._monospace_size.x * 3., self._monospace_size.y, self._scroll_pos) {
+                self.text.color = self.colors.comment;
+                self.text.add_text(cx, geom.x, geom.y, offset, self._text_inst.as_mut().unwrap(), &['.', '.', '.'], | _, _, _, _ | {0.});
+            }
+        }
+        if token_chunk.token_type == TokenType::Newline {
+            let walk = cx.get_rel_turtle_pos();
+            if !self._line_number_skip {
+                self.draw_line_number(cx, walk.y);
+            }
+            self._line_geometry.push(LineGeom {
+                walk: walk,
+                font_size: self._line_largest_font,
+                was_folded: self._line_was_folded,
+                is_hidden: self._line_number_skip,
+                indent_id: 0.
+            });
+            self._line_number_skip = true;
+        }
+        true
+    }
+
     fn draw_indent_lines(&mut self, cx: &mut Cx, geom_y: f32, tabs: usize) {
         let y_pos = geom_y - cx.get_turtle_origin().y;
         let tab_variable_width = self._monospace_base.x * 4. * self.text.text_style.font_size * self._anim_font_scale;
@@ -1436,7 +1881,7 @@ impl TextEditor {
     }
 
     pub fn draw_chunk(&mut self, cx: &mut Cx, token_chunks_index: usize, flat_text: &Vec<char>, token_chunk: &TokenChunk, markers: &TextBufferMarkers) {
-        if token_chunk.len == 0 {
+        if token_chunk.len == 0 || self.draw_folded_chunk(cx, token_chunk) {
             return
         }
 
@@ -1646,6 +2091,8 @@ impl TextEditor {
             let draw_cursors = &mut self._draw_cursors;
             let draw_messages = &mut self._draw_messages;
             let draw_search = &mut self._draw_search;
+            let drop_cursors = &self._drop_cursors;
+            let draw_drop = &mut self._draw_drop;
 
             let height = self._monospace_size.y;
             let search_cursors = if self.search_markers_bypass.len()>0 {&self.search_markers_bypass} else {&markers.search_cursors};
@@ -1658,6 +2105,7 @@ impl TextEditor {
                     //line_chunk.push((x, ch));
                     draw_search.mark_text_select_only(search_cursors, offset, x, geom.y, w, height);
                     draw_messages.mark_text_select_only(&markers.message_cursors, offset, x, geom.y, w, height);
+                    draw_drop.mark_text_with_cursor(drop_cursors, ch, offset, x, geom.y, w, height, z, 0, mark_spaces);
                     draw_cursors.mark_text_with_cursor(cursors, ch, offset, x, geom.y, w, height, z, last_cursor, mark_spaces)
                 });
             }
@@ -1665,6 +2113,7 @@ impl TextEditor {
                 self.text.add_text(cx, geom.x, geom.y, offset, self._text_inst.as_mut().unwrap(), &chunk, | ch, offset, x, w | {
                     //line_chunk.push((x, ch));
                     draw_messages.mark_text_select_only(&markers.message_cursors, offset, x, geom.y, w, height);
+                    draw_drop.mark_text_with_cursor(drop_cursors, ch, offset, x, geom.y, w, height, z, 0, mark_spaces);
                     draw_cursors.mark_text_with_cursor(cursors, ch, offset, x, geom.y, w, height, z, last_cursor, mark_spaces)
                 });
             }
@@ -1803,7 +2252,9 @@ impl TextEditor {
         self.place_ime_and_draw_cursor_row(cx);
         self.set_indent_line_highlight_id(cx);
         self.draw_shadows(cx);
+        self.draw_vim_status(cx);
         self.bg.end_quad_fill(cx, &self._bg_inst.take().unwrap());
+        self.draw_completion_list(cx);
 
         self.view.end_view(cx);
 
@@ -1819,6 +2270,7 @@ impl TextEditor {
                 self.scroll_last_cursor_visible(cx, text_buffer, self._final_fill_height * 0.8);
             }
 
+            self.unfold_around_cursors(cx);
             self.view.redraw_view_area(cx);
         }
         else if let Some(scroll_pos_on_load) = self._scroll_pos_on_load {
@@ -1834,20 +2286,42 @@ impl TextEditor {
     
     
     fn draw_cursors(&mut self, cx: &mut Cx) {
-        if self.has_key_focus(cx) {
-            let origin = cx.get_turtle_origin();
-            for rc in &self._draw_cursors.cursors {
-                self.cursor.z = rc.z + 0.1;
+        let origin = cx.get_turtle_origin();
+        // the drop caret shows without focus, the drag didnt take it
+        let has_key_focus = self.has_key_focus(cx);
+        let cursors = self._draw_cursors.cursors.iter().filter( | _ | has_key_focus).chain(self._draw_drop.cursors.iter());
+        for rc in cursors {
+            self.cursor.z = rc.z + 0.1;
 
-                let inst = self.cursor.draw_quad_rel(cx, Rect {x: rc.x - origin.x, y: rc.y - origin.y, w: rc.w, h: rc.h});
-                if inst.need_uniforms_now(cx) {
-                    inst.push_uniform_float(cx, self._cursor_blink_flipflop);
-                    //blink
-                }
+            let inst = self.cursor.draw_quad_rel(cx, Rect {x: rc.x - origin.x, y: rc.y - origin.y, w: rc.w, h: rc.h});
+            if inst.need_uniforms_now(cx) {
+                inst.push_uniform_float(cx, self._cursor_blink_flipflop);
+                //blink
             }
         }
     }
 
+    fn clear_drop_cursor(&mut self, cx: &mut Cx) {
+        if self._drop_cursors.len() != 0 {
+            self._drop_cursors.truncate(0);
+            self.view.redraw_view_area(cx);
+        }
+    }
+
+    fn draw_completion_list(&mut self, cx: &mut Cx) {
+        // dont make an overlay for every editor until it first needs one
+        if !self.completion.is_open && self.completion.overlay.view_id.is_none() {
+            return
+        }
+        if let Some(last_cursor) = self._draw_cursors.last_cursor {
+            let rc = self._draw_cursors.cursors[last_cursor];
+            let scroll_pos = self.view.get_scroll_pos(cx);
+            let filter_width = self.completion.filter.chars().count() as f32 * self._monospace_size.x;
+            self.completion.anchor = Vec2 {x: rc.x - scroll_pos.x - filter_width, y: rc.y + rc.h - scroll_pos.y};
+        }
+        self.completion.draw_completion_list(cx);
+    }
+
     fn draw_shadows(&mut self, cx: &mut Cx) {
         let gutter_width = live_float!(cx, self::gutter_width);
         self.shadow.draw_shadow_left_at(cx, Rect {
@@ -1860,6 +2334,34 @@ impl TextEditor {
         self.shadow.draw_shadow_top(cx);
     }
 
+    fn draw_vim_status(&mut self, cx: &mut Cx) {
+        if !self.vim.enabled {
+            return
+        }
+        let status: Vec<char> = self.vim.status_text().chars().collect();
+        if status.len() == 0 {
+            return
+        }
+        // a drawcall of its own so it stays in the bottom right corner when scrolling
+        let inst = cx.new_instance_draw_call(self.vim_status_text.shader, None, 0);
+        inst.set_do_scroll(cx, false, false);
+        let mut aligned = self.vim_status_text.begin_text(cx);
+        let origin = cx.get_turtle_origin();
+        let font_size = self.vim_status_text.text_style.font_size;
+        let char_size = Vec2 {x: self._monospace_base.x * font_size, y: self._monospace_base.y * font_size};
+        self.vim_status_text.color = self.colors.line_number_highlight;
+        self.vim_status_text.add_text(
+            cx,
+            origin.x + cx.get_width_total() - (status.len() as f32 + 2.) * char_size.x,
+            origin.y + cx.get_height_total() - 1.5 * char_size.y,
+            0,
+            &mut aligned,
+            &status,
+            | _, _, _, _ | {0.}
+        );
+        self.vim_status_text.end_text(cx, &aligned);
+    }
+
     fn draw_message_markers(&mut self, cx: &mut Cx, text_buffer: &TextBuffer) {
         let origin = cx.get_turtle_origin();
         let message_markers = &mut self._draw_messages.selections;
@@ -2193,13 +2695,18 @@ impl TextEditor {
         last_scroll_none
     }
 
+    fn is_line_skipped(&self, row: usize) -> bool {
+        let geom = &self._line_geometry[row];
+        geom.is_hidden || self._anim_folding.state.is_folded() && geom.was_folded
+    }
+
     fn compute_next_unfolded_line_up(&self, text_buffer: &TextBuffer) -> usize {
         let pos = self.cursors.get_last_cursor_text_pos(text_buffer);
         let mut delta = 1;
         if pos.row > 0 && pos.row < self._line_geometry.len() {
             let mut scan = pos.row - 1;
             while scan >0 {
-                if !self._line_geometry[scan].was_folded {
+                if !self.is_line_skipped(scan) {
                     delta = pos.row - scan;
                     break;
                 }
@@ -2214,7 +2721,7 @@ impl TextEditor {
         let mut delta = 1;
         let mut scan = pos.row + 1;
         while scan < self._line_geometry.len() {
-            if !self._line_geometry[scan].was_folded {
+            if !self.is_line_skipped(scan) {
                 delta = scan - pos.row;
                 break;
             }
@@ -2290,10 +2797,18 @@ pub struct AnimSelect {
 pub struct LineGeom {
     walk: Vec2,
     was_folded: bool,
+    is_hidden: bool,
     font_size: f32,
     indent_id: f32
 }
 
+#[derive(Clone, Debug)]
+pub struct FoldBlock {
+    pub row: usize,
+    pub open: usize,
+    pub close: usize
+}
+
 #[derive(Clone, Default)]
 pub struct SelectScroll {
     // pub margin:Margin,
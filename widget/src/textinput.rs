@@ -113,6 +113,8 @@ impl TextInput {
         }
         
         if self.text_editor.begin_text_editor(cx, text_buffer).is_err() {
+            // a clean view keeps its area, it still has to stay in the tab order
+            cx.add_focusable(self.text_editor._view_area, false);
             live_style_end!(cx, self::style_text_input);
             return;
         }
@@ -129,6 +131,7 @@ impl TextInput {
         }
         
         self.text_editor.end_text_editor(cx, text_buffer);
+        cx.add_focusable(self.text_editor._view_area, false);
         live_style_end!(cx, self::style_text_input);
     }
 }
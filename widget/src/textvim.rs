@@ -0,0 +1,756 @@
+use makepad_render::*;
+use std::collections::HashMap;
+
+use crate::textbuffer::*;
+use crate::textcursor::*;
+use crate::tokentype::*;
+
+// a modal editing layer on top of the texteditor cursors
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VimMode {
+    Normal,
+    Insert,
+    Visual,
+    VisualLine
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VimInput {
+    Key(KeyEvent),
+    Text(TextInputEvent)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VimAction {
+    None,
+    PassThrough,
+    Save,
+    Repeat(Vec<VimInput>)
+}
+
+#[derive(Clone, Default)]
+pub struct VimRegister {
+    pub text: String,
+    pub linewise: bool
+}
+
+#[derive(Clone)]
+pub struct TextVim {
+    pub enabled: bool,
+    pub mode: VimMode,
+    pub command_line: Option<String>,
+    pub registers: HashMap<char, VimRegister>,
+    pub replaying: bool,
+    count: usize,
+    op_count: usize,
+    operator: Option<char>,
+    pending: Option<char>,
+    register: char,
+    changed: bool,
+    recording: Vec<VimInput>,
+    last_change: Vec<VimInput>,
+}
+
+impl TextVim {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            mode: VimMode::Normal,
+            command_line: None,
+            registers: HashMap::new(),
+            replaying: false,
+            count: 0,
+            op_count: 0,
+            operator: None,
+            pending: None,
+            register: '"',
+            changed: false,
+            recording: Vec::new(),
+            last_change: Vec::new(),
+        }
+    }
+
+    pub fn is_visual(&self) -> bool {
+        self.mode == VimMode::Visual || self.mode == VimMode::VisualLine
+    }
+
+    // what the editor shows in its corner, like the vim mode line
+    pub fn status_text(&self) -> String {
+        if let Some(command_line) = &self.command_line {
+            return format!(":{}", command_line)
+        }
+        match self.mode {
+            VimMode::Insert => "-- INSERT --".to_string(),
+            VimMode::Visual => "-- VISUAL --".to_string(),
+            VimMode::VisualLine => "-- VISUAL LINE --".to_string(),
+            VimMode::Normal => {
+                let mut pending = String::new();
+                if self.register != '"' {
+                    pending.push('"');
+                    pending.push(self.register);
+                }
+                if self.op_count > 0 {
+                    pending.push_str(&self.op_count.to_string());
+                }
+                if let Some(op) = self.operator {
+                    pending.push(op);
+                }
+                if self.count > 0 {
+                    pending.push_str(&self.count.to_string());
+                }
+                if let Some(ch) = self.pending {
+                    pending.push(ch);
+                }
+                pending
+            }
+        }
+    }
+
+    // inserted text and keys are handled by the editor itself, we only record them for .
+    pub fn record(&mut self, input: &VimInput) {
+        if !self.replaying && self.changed {
+            self.recording.push(input.clone());
+        }
+    }
+
+    pub fn leave_insert(&mut self, cursors: &mut TextCursorSet, text_buffer: &TextBuffer) {
+        self.mode = VimMode::Normal;
+        // the normal mode cursor sits on the last char typed
+        TextVim::move_heads(cursors, text_buffer, false, | head, tb | {
+            if head > 0 && tb.get_char(head - 1) != '\n' {head - 1} else {head}
+        });
+        if !self.replaying {
+            self.last_change = std::mem::replace(&mut self.recording, Vec::new());
+        }
+        self.changed = false;
+    }
+
+    pub fn handle_vim_input(&mut self, input: VimInput, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) -> VimAction {
+        if self.command_line.is_some() {
+            return self.handle_command_line(&input, cursors, text_buffer)
+        }
+        let chars: Vec<char> = match &input {
+            VimInput::Text(te) => {
+                if te.was_paste {
+                    return VimAction::PassThrough
+                }
+                te.input.chars().collect()
+            },
+            VimInput::Key(ke) => {
+                if ke.modifiers.control || ke.modifiers.logo {
+                    if ke.key_code == KeyCode::KeyR {
+                        for _ in 0..self.take_count().max(1) {
+                            text_buffer.redo(true, cursors);
+                        }
+                        return VimAction::None
+                    }
+                    return VimAction::PassThrough
+                }
+                match ke.key_code {
+                    KeyCode::Escape => return self.escape(cursors, text_buffer),
+                    KeyCode::ArrowLeft | KeyCode::Backspace => vec!['h'],
+                    KeyCode::ArrowRight => vec!['l'],
+                    KeyCode::ArrowUp => vec!['k'],
+                    KeyCode::ArrowDown | KeyCode::Return => vec!['j'],
+                    KeyCode::Home => vec!['0'],
+                    KeyCode::End => vec!['$'],
+                    // the rest arrives as text input
+                    _ => return VimAction::None
+                }
+            }
+        };
+        if !self.replaying {
+            self.recording.push(input);
+        }
+        let mut action = VimAction::None;
+        for ch in chars {
+            action = self.handle_char(ch, cursors, text_buffer);
+        }
+        // a command is done when nothing is pending anymore
+        if self.operator.is_none() && self.pending.is_none() && self.count == 0 && self.command_line.is_none() && self.mode == VimMode::Normal {
+            if !self.replaying {
+                if self.changed {
+                    self.last_change = std::mem::replace(&mut self.recording, Vec::new());
+                }
+                else {
+                    self.recording.truncate(0);
+                }
+            }
+            self.changed = false;
+        }
+        action
+    }
+
+    fn escape(&mut self, cursors: &mut TextCursorSet, text_buffer: &TextBuffer) -> VimAction {
+        let idle = self.operator.is_none() && self.pending.is_none() && self.count == 0;
+        self.operator = None;
+        self.pending = None;
+        self.count = 0;
+        self.op_count = 0;
+        self.register = '"';
+        if self.is_visual() {
+            self.mode = VimMode::Normal;
+            TextVim::move_heads(cursors, text_buffer, false, | head, _ | head);
+            self.changed = false;
+            self.recording.truncate(0);
+            return VimAction::None
+        }
+        if idle {VimAction::PassThrough} else {VimAction::None}
+    }
+
+    fn take_count(&mut self) -> usize {
+        let count = match (self.op_count, self.count) {
+            (0, c) => c,
+            (o, 0) => o,
+            (o, c) => o * c
+        };
+        self.op_count = 0;
+        self.count = 0;
+        count
+    }
+
+    fn handle_char(&mut self, ch: char, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) -> VimAction {
+        match self.pending.take() {
+            Some('"') => {
+                self.register = ch;
+                return VimAction::None
+            },
+            Some('r') => {
+                self.replace_chars(ch, cursors, text_buffer);
+                return VimAction::None
+            },
+            Some('g') => {
+                if ch == 'g' {
+                    self.do_motion('g', cursors, text_buffer);
+                }
+                return VimAction::None
+            },
+            _ => ()
+        }
+        if ch.is_ascii_digit() && (ch != '0' || self.count > 0) {
+            self.count = self.count * 10 + (ch as usize - '0' as usize);
+            return VimAction::None
+        }
+        match ch {
+            '"' | 'r' | 'g' => {
+                self.pending = Some(ch);
+            },
+            ':' => {
+                self.command_line = Some(String::new());
+            },
+            'h' | 'j' | 'k' | 'l' | ' ' | 'w' | 'b' | 'e' | '0' | '^' | '$' | 'G' | '%' => {
+                self.do_motion(ch, cursors, text_buffer);
+            },
+            'd' | 'c' | 'y' | '>' | '<' => {
+                self.do_operator(ch, cursors, text_buffer);
+            },
+            'x' => self.do_shortcut('d', 'l', cursors, text_buffer),
+            'X' => self.do_shortcut('d', 'h', cursors, text_buffer),
+            'D' => self.do_shortcut('d', '$', cursors, text_buffer),
+            'C' => self.do_shortcut('c', '$', cursors, text_buffer),
+            's' => self.do_shortcut('c', 'l', cursors, text_buffer),
+            'Y' => self.do_shortcut('y', 'y', cursors, text_buffer),
+            'i' | 'a' | 'I' | 'A' | 'o' | 'O' => {
+                if !self.is_visual() {
+                    self.do_insert(ch, cursors, text_buffer);
+                }
+            },
+            'v' | 'V' => {
+                let mode = if ch == 'v' {VimMode::Visual} else {VimMode::VisualLine};
+                if self.mode == mode {
+                    return self.escape(cursors, text_buffer)
+                }
+                if self.mode == VimMode::Normal {
+                    TextVim::move_heads(cursors, text_buffer, false, | head, _ | head);
+                }
+                self.mode = mode;
+            },
+            'p' | 'P' => {
+                self.paste(ch == 'p', cursors, text_buffer);
+            },
+            'u' => {
+                for _ in 0..self.take_count().max(1) {
+                    text_buffer.undo(true, cursors);
+                }
+            },
+            '.' => {
+                self.take_count();
+                return VimAction::Repeat(self.last_change.clone())
+            },
+            _ => {
+                // unknown commands cancel whatever was pending
+                self.operator = None;
+                self.take_count();
+            }
+        }
+        VimAction::None
+    }
+
+    fn handle_command_line(&mut self, input: &VimInput, cursors: &mut TextCursorSet, text_buffer: &TextBuffer) -> VimAction {
+        let line = self.command_line.as_mut().unwrap();
+        match input {
+            VimInput::Text(te) => {
+                line.push_str(&te.input);
+            },
+            VimInput::Key(ke) => match ke.key_code {
+                KeyCode::Escape => {
+                    self.command_line = None;
+                },
+                KeyCode::Backspace => {
+                    if line.pop().is_none() {
+                        self.command_line = None;
+                    }
+                },
+                KeyCode::Return => {
+                    let command = self.command_line.take().unwrap();
+                    let command = command.trim();
+                    if command == "w" || command == "wq" || command == "x" || command == "write" {
+                        return VimAction::Save
+                    }
+                    if let Ok(line) = command.parse::<usize>() {
+                        let offset = text_buffer.text_pos_to_offset(TextPos {row: line.max(1) - 1, col: 0});
+                        cursors.clear_and_set_last_cursor_head_and_tail(offset, offset, text_buffer);
+                    }
+                },
+                _ => ()
+            }
+        }
+        VimAction::None
+    }
+
+    fn move_heads<F>(cursors: &mut TextCursorSet, text_buffer: &TextBuffer, select: bool, f: F)
+    where F: Fn(usize, &TextBuffer) -> usize {
+        for cursor in &mut cursors.set {
+            cursor.head = f(cursor.head, text_buffer);
+            if !select {
+                cursor.tail = cursor.head;
+            }
+            cursor.calc_max(text_buffer, (TextPos {row: 0, col: 0}, 0));
+        }
+    }
+
+    fn line_start(head: usize, text_buffer: &TextBuffer) -> usize {
+        head - text_buffer.offset_to_text_pos(head).col
+    }
+
+    // whitespace, word chars and punctuation are the three kinds of runs words are made of
+    fn char_class(ch: char) -> usize {
+        if ch.is_whitespace() || ch == '\0' {0}
+        else if ch.is_alphanumeric() || ch == '_' {1}
+        else {2}
+    }
+
+    fn word_next(head: usize, text_buffer: &TextBuffer) -> usize {
+        let end = text_buffer.calc_char_count();
+        let mut pos = head;
+        let class = TextVim::char_class(text_buffer.get_char(pos));
+        while pos < end && class != 0 && TextVim::char_class(text_buffer.get_char(pos)) == class {
+            pos += 1;
+        }
+        while pos < end && TextVim::char_class(text_buffer.get_char(pos)) == 0 {
+            // an empty line counts as a word
+            if pos > head && text_buffer.get_char(pos) == '\n' && text_buffer.get_char(pos - 1) == '\n' {
+                break
+            }
+            pos += 1;
+        }
+        pos
+    }
+
+    fn word_end(head: usize, text_buffer: &TextBuffer) -> usize {
+        let end = text_buffer.calc_char_count();
+        let mut pos = head + 1;
+        while pos < end && TextVim::char_class(text_buffer.get_char(pos)) == 0 {
+            pos += 1;
+        }
+        if pos >= end {
+            return head
+        }
+        let class = TextVim::char_class(text_buffer.get_char(pos));
+        while pos + 1 < end && TextVim::char_class(text_buffer.get_char(pos + 1)) == class {
+            pos += 1;
+        }
+        pos
+    }
+
+    fn word_prev(head: usize, text_buffer: &TextBuffer) -> usize {
+        if head == 0 {
+            return 0
+        }
+        let mut pos = head - 1;
+        while pos > 0 && TextVim::char_class(text_buffer.get_char(pos)) == 0 {
+            pos -= 1;
+        }
+        let class = TextVim::char_class(text_buffer.get_char(pos));
+        while pos > 0 && TextVim::char_class(text_buffer.get_char(pos - 1)) == class {
+            pos -= 1;
+        }
+        pos
+    }
+
+    fn pair_of(head: usize, text_buffer: &TextBuffer) -> usize {
+        for tok in &text_buffer.token_chunks {
+            if tok.offset == head && (tok.token_type == TokenType::ParenOpen || tok.token_type == TokenType::ParenClose) {
+                return text_buffer.token_chunks[tok.pair_token].offset
+            }
+            if tok.offset > head {
+                break
+            }
+        }
+        head
+    }
+
+    fn do_motion(&mut self, ch: char, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) {
+        let count = self.take_count();
+        let op = self.operator.take();
+        let select = op.is_some() || self.is_visual();
+        if op.is_some() {
+            TextVim::move_heads(cursors, text_buffer, false, | head, _ | head);
+        }
+        let mut linewise = false;
+        let mut inclusive = false;
+        match ch {
+            'h' => cursors.move_left(count.max(1), select, text_buffer),
+            'l' | ' ' => cursors.move_right(count.max(1), select, text_buffer),
+            'j' => {
+                cursors.move_down(count.max(1), select, text_buffer);
+                linewise = true;
+            },
+            'k' => {
+                cursors.move_up(count.max(1), select, text_buffer);
+                linewise = true;
+            },
+            'w' if op != Some('c') => {
+                for _ in 0..count.max(1) {
+                    TextVim::move_heads(cursors, text_buffer, select, TextVim::word_next);
+                }
+                // an operator stops at the end of the line instead of eating the newline
+                if op.is_some() {
+                    for cursor in &mut cursors.set {
+                        while cursor.head > cursor.tail && text_buffer.offset_to_text_pos(cursor.head).col == 0 && text_buffer.get_char(cursor.head - 1) == '\n' {
+                            cursor.head -= 1;
+                        }
+                    }
+                }
+            },
+            'w' | 'e' => { // cw works like ce
+                for _ in 0..count.max(1) {
+                    TextVim::move_heads(cursors, text_buffer, select, TextVim::word_end);
+                }
+                inclusive = true;
+            },
+            'b' => for _ in 0..count.max(1) {
+                TextVim::move_heads(cursors, text_buffer, select, TextVim::word_prev);
+            },
+            '^' => cursors.move_home(select, text_buffer),
+            '0' => TextVim::move_heads(cursors, text_buffer, select, TextVim::line_start),
+            '$' => cursors.move_end(select, text_buffer),
+            'G' | 'g' => {
+                let row = if count > 0 {count - 1} else if ch == 'g' {0} else {text_buffer.lines.len().max(1) - 1};
+                let offset = text_buffer.text_pos_to_offset(TextPos {row: row, col: 0});
+                TextVim::move_heads(cursors, text_buffer, select, | _, _ | offset);
+                linewise = true;
+            },
+            '%' => {
+                TextVim::move_heads(cursors, text_buffer, select, TextVim::pair_of);
+                inclusive = true;
+            },
+            _ => ()
+        }
+        if let Some(op) = op {
+            self.apply_operator(op, linewise, inclusive, cursors, text_buffer);
+        }
+    }
+
+    fn do_operator(&mut self, op: char, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) {
+        if self.is_visual() {
+            let linewise = self.mode == VimMode::VisualLine;
+            self.take_count();
+            self.apply_operator(op, linewise, true, cursors, text_buffer);
+            return
+        }
+        if self.operator == Some(op) { // dd, yy, cc and friends work on lines
+            self.operator = None;
+            let count = self.take_count().max(1);
+            TextVim::move_heads(cursors, text_buffer, false, | head, _ | head);
+            if count > 1 {
+                cursors.move_down(count - 1, true, text_buffer);
+            }
+            self.apply_operator(op, true, false, cursors, text_buffer);
+        }
+        else if self.operator.is_some() {
+            self.operator = None;
+            self.take_count();
+        }
+        else {
+            self.operator = Some(op);
+            self.op_count = self.count;
+            self.count = 0;
+        }
+    }
+
+    fn do_shortcut(&mut self, op: char, motion: char, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) {
+        if self.is_visual() {
+            self.do_operator(op, cursors, text_buffer);
+            return
+        }
+        self.operator = Some(op);
+        self.op_count = self.count;
+        self.count = 0;
+        if motion == op {
+            self.do_operator(op, cursors, text_buffer);
+        }
+        else {
+            self.do_motion(motion, cursors, text_buffer);
+        }
+    }
+
+    fn apply_operator(&mut self, op: char, linewise: bool, inclusive: bool, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) {
+        let char_count = text_buffer.calc_char_count();
+        let mut register = VimRegister {text: String::new(), linewise: linewise};
+        for cursor in &mut cursors.set {
+            let (start, end) = cursor.order();
+            let (start, end) = if linewise {
+                let start_row = text_buffer.offset_to_text_pos(start).row;
+                let end_row = text_buffer.offset_to_text_pos(end).row;
+                for row in start_row..=end_row {
+                    register.text.extend(text_buffer.lines[row].iter());
+                    register.text.push('\n');
+                }
+                let start = text_buffer.text_pos_to_offset(TextPos {row: start_row, col: 0});
+                if op == 'c' { // change keeps the line itself
+                    (start, text_buffer.text_pos_to_offset(TextPos {row: end_row, col: text_buffer.lines[end_row].len()}))
+                }
+                else if end_row + 1 < text_buffer.lines.len() {
+                    (start, text_buffer.text_pos_to_offset(TextPos {row: end_row + 1, col: 0}))
+                }
+                else { // the last line takes the newline in front of it
+                    (if start > 0 {start - 1} else {0}, char_count)
+                }
+            }
+            else {
+                let end = if inclusive {(end + 1).min(char_count)} else {end};
+                text_buffer.get_range_as_string(start, end - start, &mut register.text);
+                (start, end)
+            };
+            cursor.tail = start;
+            cursor.head = end;
+        }
+        if op == 'y' || op == 'd' || op == 'c' {
+            if self.register != '"' {
+                self.registers.insert(self.register, register.clone());
+            }
+            self.registers.insert('"', register);
+        }
+        self.register = '"';
+        match op {
+            'd' | 'c' => {
+                cursors.replace_text("", text_buffer, None);
+            },
+            '>' => {
+                cursors.insert_tab(text_buffer, "    ");
+            },
+            '<' => {
+                cursors.remove_tab(text_buffer, 4);
+            },
+            _ => ()
+        }
+        if op == 'y' || op == '>' || op == '<' {
+            for cursor in &mut cursors.set {
+                let (start, _) = cursor.order();
+                cursor.head = start;
+                cursor.tail = start;
+                cursor.calc_max(text_buffer, (TextPos {row: 0, col: 0}, 0));
+            }
+        }
+        self.changed = op != 'y';
+        self.mode = if op == 'c' {VimMode::Insert} else {VimMode::Normal};
+    }
+
+    fn replace_chars(&mut self, ch: char, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) {
+        let count = self.take_count().max(1);
+        cursors.move_right(count, true, text_buffer);
+        let text: String = std::iter::repeat(ch).take(count).collect();
+        cursors.replace_text(&text, text_buffer, None);
+        cursors.move_left(1, false, text_buffer);
+        self.changed = true;
+    }
+
+    fn do_insert(&mut self, ch: char, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) {
+        self.take_count();
+        match ch {
+            'a' => TextVim::move_heads(cursors, text_buffer, false, | head, tb | {
+                if tb.get_char(head) != '\n' && tb.get_char(head) != '\0' {head + 1} else {head}
+            }),
+            'I' => cursors.move_home(false, text_buffer),
+            'A' => cursors.move_end(false, text_buffer),
+            'o' => {
+                cursors.move_end(false, text_buffer);
+                cursors.insert_newline_with_indent(text_buffer);
+            },
+            'O' => {
+                // open a line above with the same indent
+                let row = cursors.get_last_cursor_text_pos(text_buffer).row;
+                let indent: String = text_buffer.lines[row].iter().take_while( | v | **v == ' ').collect();
+                TextVim::move_heads(cursors, text_buffer, false, TextVim::line_start);
+                cursors.replace_text(&format!("{}\n", indent), text_buffer, None);
+                cursors.move_left(1, false, text_buffer);
+            },
+            _ => ()
+        }
+        self.changed = true;
+        self.mode = VimMode::Insert;
+    }
+
+    fn paste(&mut self, after: bool, cursors: &mut TextCursorSet, text_buffer: &mut TextBuffer) {
+        let register = if let Some(register) = self.registers.get(&self.register) {register.clone()} else {return};
+        self.register = '"';
+        let text = register.text.repeat(self.take_count().max(1));
+        if self.is_visual() {
+            cursors.replace_text(&text, text_buffer, None);
+            self.mode = VimMode::Normal;
+        }
+        else if register.linewise {
+            let row = cursors.get_last_cursor_text_pos(text_buffer).row + if after {1} else {0};
+            if row < text_buffer.lines.len() {
+                let offset = text_buffer.text_pos_to_offset(TextPos {row: row, col: 0});
+                cursors.clear_and_set_last_cursor_head_and_tail(offset, offset, text_buffer);
+                cursors.replace_text(&text, text_buffer, None);
+                cursors.clear_and_set_last_cursor_head_and_tail(offset, offset, text_buffer);
+            }
+            else { // past the last line the newline goes in front
+                let offset = text_buffer.calc_char_count();
+                cursors.clear_and_set_last_cursor_head_and_tail(offset, offset, text_buffer);
+                cursors.replace_text(&format!("\n{}", &text[0..text.len() - 1]), text_buffer, None);
+                cursors.clear_and_set_last_cursor_head_and_tail(offset + 1, offset + 1, text_buffer);
+            }
+        }
+        else {
+            if after {
+                TextVim::move_heads(cursors, text_buffer, false, | head, tb | {
+                    if tb.get_char(head) != '\n' && tb.get_char(head) != '\0' {head + 1} else {head}
+                });
+            }
+            cursors.replace_text(&text, text_buffer, None);
+            cursors.move_left(1, false, text_buffer);
+        }
+        self.changed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, keys: &str) -> (TextVim, TextCursorSet, TextBuffer) {
+        let mut vim = TextVim::new();
+        vim.enabled = true;
+        let mut cursors = TextCursorSet::new();
+        let mut text_buffer = TextBuffer::from_utf8(text);
+        for ch in keys.chars() {
+            let input = VimInput::Text(TextInputEvent {input: ch.to_string(), replace_last: false, was_paste: false});
+            vim.handle_vim_input(input, &mut cursors, &mut text_buffer);
+        }
+        (vim, cursors, text_buffer)
+    }
+
+    fn head_after(text: &str, keys: &str) -> usize {
+        let (_, cursors, _) = run(text, keys);
+        cursors.set[0].head
+    }
+
+    fn text_after(text: &str, keys: &str) -> String {
+        let (_, _, text_buffer) = run(text, keys);
+        text_buffer.get_as_string()
+    }
+
+    #[test]
+    fn word_motions() {
+        let text = "foo bar.baz qux";
+        assert_eq!(head_after(text, "w"), 4);
+        assert_eq!(head_after(text, "ww"), 7);
+        assert_eq!(head_after(text, "www"), 8);
+        assert_eq!(head_after(text, "e"), 2);
+        assert_eq!(head_after(text, "ee"), 6);
+        assert_eq!(head_after(text, "eee"), 7);
+        assert_eq!(head_after(text, "wwwb"), 7);
+        assert_eq!(head_after(text, "wwwbb"), 4);
+        // the end of the buffer stops e instead of wrapping
+        assert_eq!(head_after("ab", "eee"), 1);
+        // words continue on the next line and an empty line is a word
+        assert_eq!(head_after("foo\nbar", "w"), 4);
+        assert_eq!(head_after("foo\n\nbar", "w"), 4);
+        assert_eq!(head_after("foo\n\nbar", "ww"), 5);
+    }
+
+    #[test]
+    fn line_motions() {
+        let text = "one\ntwo\nthree";
+        assert_eq!(head_after(text, "G"), 8);
+        assert_eq!(head_after(text, "Ggg"), 0);
+        assert_eq!(head_after(text, "2G"), 4);
+        assert_eq!(head_after(text, "ll0"), 0);
+        assert_eq!(head_after(text, "j"), 4);
+    }
+
+    #[test]
+    fn counts() {
+        let text = "abcdef ghi jkl mno";
+        assert_eq!(head_after(text, "3l"), 3);
+        assert_eq!(head_after(text, "3lh"), 2);
+        assert_eq!(head_after(text, "2w"), 11);
+        assert_eq!(head_after(text, "12l"), 12);
+        assert_eq!(text_after(text, "3x"), "def ghi jkl mno");
+        assert_eq!(text_after(text, "2dw"), "jkl mno");
+        assert_eq!(text_after(text, "d2w"), "jkl mno");
+        assert_eq!(text_after("a\nb\nc\nd", "2dd"), "c\nd");
+        assert_eq!(text_after("a\nb\nc\nd", "2d2d"), "");
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(text_after("foo bar", "dw"), "bar");
+        assert_eq!(text_after("foo bar", "de"), " bar");
+        assert_eq!(text_after("foo bar", "d$"), "");
+        assert_eq!(text_after("foo bar", "wD"), "foo ");
+        // dw on the last word leaves the newline alone
+        assert_eq!(text_after("foo\nbar", "dw"), "\nbar");
+        assert_eq!(text_after("a\nb\nc", "jdd"), "a\nc");
+        assert_eq!(text_after("a\nb\nc", "Gdd"), "a\nb");
+        // cw changes up to the end of the word like ce
+        let (vim, _, text_buffer) = run("foo bar", "cw");
+        assert_eq!(text_buffer.get_as_string(), " bar");
+        assert_eq!(vim.mode, VimMode::Insert);
+    }
+
+    #[test]
+    fn registers_and_paste() {
+        assert_eq!(text_after("a\nb", "yyp"), "a\na\nb");
+        assert_eq!(text_after("a\nb", "yyP"), "a\na\nb");
+        assert_eq!(text_after("a\nb", "jyyp"), "a\nb\nb");
+        assert_eq!(text_after("a\nb", "ddp"), "b\na");
+        assert_eq!(text_after("foo bar", "dwwP"), "barfoo ");
+        let (vim, _, _) = run("foo bar", "\"ayw");
+        assert_eq!(vim.registers[&'a'].text, "foo ");
+        assert_eq!(vim.registers[&'"'].text, "foo ");
+        assert!(!vim.registers[&'a'].linewise);
+    }
+
+    #[test]
+    fn modes_and_status() {
+        let (vim, _, _) = run("foo", "2d");
+        assert_eq!(vim.status_text(), "2d");
+        let (vim, _, _) = run("foo", "d3");
+        assert_eq!(vim.status_text(), "d3");
+        let (vim, _, _) = run("foo", "i");
+        assert_eq!(vim.status_text(), "-- INSERT --");
+        let (vim, _, _) = run("foo", "V");
+        assert_eq!(vim.status_text(), "-- VISUAL LINE --");
+        let (vim, _, _) = run("foo", ":12");
+        assert_eq!(vim.status_text(), ":12");
+        let (vim, _, _) = run("foo", "dw");
+        assert_eq!(vim.status_text(), "");
+        assert_eq!(text_after("foo bar", "vld"), "o bar");
+    }
+}
@@ -11,6 +11,10 @@ use crate::desktopbutton::*;
 use crate::splitter::*;
 use crate::tabcontrol::*;
 use crate::xrcontrol::*;
+use crate::completionlist::*;
+use crate::markdownview::*;
+use crate::layoutdebugger::*;
+use crate::focusring::*;
 
 pub fn set_widget_style(cx: &mut Cx) {
     
@@ -54,6 +58,11 @@ pub fn set_widget_style(cx: &mut Cx) {
     Splitter::style(cx);
     TabControl::style(cx);
     XRControl::style(cx);
+    CompletionList::style(cx);
+    MarkdownView::style(cx);
+    LayoutDebugger::style(cx);
+    FocusRing::style(cx);
+    TextEditor::default_keymap(cx);
 }
 
   /*